@@ -0,0 +1,31 @@
+/*
+ * Brew a little recipe from C and read back what it left in the pantry.
+ *
+ *   cargo build -p brewco-ffi --release
+ *   cc brewco-ffi/examples/hello.c -Ibrewco-ffi/include -Ltarget/release -lbrewco_ffi -o hello
+ *   LD_LIBRARY_PATH=target/release ./hello
+ */
+
+#include <stdio.h>
+#include "brewco.h"
+
+int main(void) {
+    brewco_interpreter *brewco = brewco_new();
+    if (!brewco) {
+        return 1;
+    }
+
+    brewco_eval(brewco, "beans order = { drink: \"latte\", shots: 2 }\nbeans greeting = \"Hello from Brewco\"");
+    char *greeting = brewco_get_string(brewco, "greeting");
+    char *order = brewco_get_json(brewco, "order");
+    printf("%s\n%s\n", greeting, order);
+    brewco_free_string(greeting);
+    brewco_free_string(order);
+
+    if (brewco_eval(brewco, "beans cup = [1]\npourout cup[3]") == BREWCO_SPILLED) {
+        printf("spilled: %s\n", brewco_last_spill(brewco));
+    }
+
+    brewco_free(brewco);
+    return 0;
+}
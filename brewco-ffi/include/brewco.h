@@ -0,0 +1,46 @@
+/*
+ * brewco.h: embed the Brewco interpreter from C or C++
+ *
+ * Link against libbrewco_ffi (cargo build -p brewco-ffi --release).
+ * Strings from brewco_get_string and brewco_get_json are yours to free with
+ * brewco_free_string; brewco_last_spill's belongs to the interpreter until
+ * the next brewco_eval. Use an interpreter from the thread that made it.
+ */
+
+#ifndef BREWCO_H
+#define BREWCO_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct BrewcoInterpreter brewco_interpreter;
+
+#define BREWCO_OK 0
+#define BREWCO_SPILLED 1
+#define BREWCO_BAD_ARGUMENT (-1)
+#define BREWCO_FINISHED 2 /* the source called finish_service(); brewco_last_spill names the code */
+
+/* A fresh interpreter, or NULL if brewco couldn't start one */
+brewco_interpreter *brewco_new(void);
+
+/* Parse and brew source; globals stay for the next call. BREWCO_OK, BREWCO_SPILLED, BREWCO_FINISHED or BREWCO_BAD_ARGUMENT */
+int brewco_eval(brewco_interpreter *brewco, const char *source);
+
+/* Why the last brewco_eval spilled or finished, or NULL */
+const char *brewco_last_spill(const brewco_interpreter *brewco);
+
+/* A global as pourout would show it, or NULL when there's no such global */
+char *brewco_get_string(const brewco_interpreter *brewco, const char *name);
+
+/* A global as JSON, or NULL when there's no such global or it's a brew */
+char *brewco_get_json(const brewco_interpreter *brewco, const char *name);
+
+void brewco_free_string(char *text);
+void brewco_free(brewco_interpreter *brewco);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,242 @@
+/*
+ * 🔗 Brewco FFI: a C door into the coffee shop ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @ffi_note: "Any language that can call C can order a brew!" ✨
+ *
+ * A C ABI over brewco's interpreter, for C, C++ and runtimes that can't
+ * link Rust directly. include/brewco.h declares it and examples/hello.c
+ * uses it. An interpreter keeps its globals between brewco_eval calls, so a
+ * host can brew a recipe once and read what it left in the pantry.
+ *
+ * Strings brewco hands out are the caller's to free with brewco_free_string,
+ * except brewco_last_spill's, which the interpreter keeps until the next
+ * brewco_eval. An interpreter belongs to the thread that made it. A panic
+ * inside brewco is caught at the border and reported as a spill instead of
+ * unwinding into C.
+ */
+
+// brewco-ffi/src/lib.rs
+
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use brewco::interpreter::{ControlFlow, Interpreter, Value};
+use brewco::parser;
+
+/// brewco_eval brewed the source without spilling
+pub const BREWCO_OK: c_int = 0;
+/// brewco_eval's source didn't parse or spilled; brewco_last_spill says why
+pub const BREWCO_SPILLED: c_int = 1;
+/// A NULL interpreter or source, or source that isn't UTF-8
+pub const BREWCO_BAD_ARGUMENT: c_int = -1;
+/// brewco_eval's source called finish_service(); brewco_last_spill names the exit code.
+/// The host isn't exited: closing is up to it
+pub const BREWCO_FINISHED: c_int = 2;
+
+/// The interpreter behind a `brewco_interpreter *`
+pub struct BrewcoInterpreter {
+    interpreter: Interpreter,
+    last_spill: Option<CString>,
+}
+
+/// Text for C: brewco's strings can't hold a NUL, but a value's text might
+fn c_text(text: String) -> CString {
+    CString::new(text.replace('\0', "\\0")).unwrap_or_default()
+}
+
+/// A NUL-terminated UTF-8 string from C, or None when it's NULL or not UTF-8
+unsafe fn rust_text<'a>(text: *const c_char) -> Option<&'a str> {
+    if text.is_null() {
+        return None;
+    }
+    CStr::from_ptr(text).to_str().ok()
+}
+
+/// Make an interpreter; NULL only if brewco couldn't start one
+#[no_mangle]
+pub extern "C" fn brewco_new() -> *mut BrewcoInterpreter {
+    panic::catch_unwind(|| Box::into_raw(Box::new(BrewcoInterpreter { interpreter: Interpreter::new(), last_spill: None })))
+        .unwrap_or(ptr::null_mut())
+}
+
+/// Parse and brew `source` on the interpreter: BREWCO_OK, BREWCO_SPILLED, BREWCO_FINISHED or BREWCO_BAD_ARGUMENT
+///
+/// # Safety
+/// `brewco` comes from brewco_new and hasn't been freed; `source` is NUL-terminated
+#[no_mangle]
+pub unsafe extern "C" fn brewco_eval(brewco: *mut BrewcoInterpreter, source: *const c_char) -> c_int {
+    let (Some(brewco), Some(source)) = (brewco.as_mut(), rust_text(source)) else {
+        return BREWCO_BAD_ARGUMENT;
+    };
+    brewco.last_spill = None;
+    let interpreter = &mut brewco.interpreter;
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+        let parsed = parser::parse_source(source);
+        if !parsed.errors.is_empty() {
+            return Err((BREWCO_SPILLED, parsed.errors.join("\n")));
+        }
+        match interpreter.run(&parsed.statements) {
+            Err(ControlFlow::RuntimeError(message)) => Err((BREWCO_SPILLED, interpreter.spill_report(&message).headline())),
+            Err(ControlFlow::FinishService(code)) => Err((BREWCO_FINISHED, format!("finish_service({}) closed the shop", code))),
+            _ => Ok(()),
+        }
+    }))
+    .unwrap_or_else(|_| Err((BREWCO_SPILLED, "Brewco itself spilled while brewing this".to_string())));
+    match outcome {
+        Ok(()) => BREWCO_OK,
+        Err((status, spill)) => {
+            brewco.last_spill = Some(c_text(spill));
+            status
+        }
+    }
+}
+
+/// Why the last brewco_eval spilled or finished, or NULL if it did neither; valid until the next brewco_eval
+///
+/// # Safety
+/// `brewco` comes from brewco_new and hasn't been freed
+#[no_mangle]
+pub unsafe extern "C" fn brewco_last_spill(brewco: *const BrewcoInterpreter) -> *const c_char {
+    match brewco.as_ref().and_then(|brewco| brewco.last_spill.as_ref()) {
+        Some(spill) => spill.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// A global as the text `pourout` would show, or NULL when there's no such global
+///
+/// # Safety
+/// `brewco` comes from brewco_new and hasn't been freed; `name` is NUL-terminated
+#[no_mangle]
+pub unsafe extern "C" fn brewco_get_string(brewco: *const BrewcoInterpreter, name: *const c_char) -> *mut c_char {
+    global_text(brewco, name, |value| Some(value.to_string()))
+}
+
+/// A global as JSON, or NULL when there's no such global or it can't be written as JSON (a brew)
+///
+/// # Safety
+/// `brewco` comes from brewco_new and hasn't been freed; `name` is NUL-terminated
+#[no_mangle]
+pub unsafe extern "C" fn brewco_get_json(brewco: *const BrewcoInterpreter, name: *const c_char) -> *mut c_char {
+    global_text(brewco, name, |value| serde_json::Value::try_from(value).ok().map(|json| json.to_string()))
+}
+
+unsafe fn global_text(brewco: *const BrewcoInterpreter, name: *const c_char, text: impl Fn(&Value) -> Option<String>) -> *mut c_char {
+    let (Some(brewco), Some(name)) = (brewco.as_ref(), rust_text(name)) else {
+        return ptr::null_mut();
+    };
+    panic::catch_unwind(AssertUnwindSafe(|| brewco.interpreter.get_global(name).as_ref().and_then(&text)))
+        .ok()
+        .flatten()
+        .map_or(ptr::null_mut(), |text| c_text(text).into_raw())
+}
+
+/// Free a string from brewco_get_string or brewco_get_json; NULL is fine
+///
+/// # Safety
+/// `text` came from one of those and hasn't been freed
+#[no_mangle]
+pub unsafe extern "C" fn brewco_free_string(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
+
+/// Close the interpreter; NULL is fine
+///
+/// # Safety
+/// `brewco` comes from brewco_new and hasn't been freed
+#[no_mangle]
+pub unsafe extern "C" fn brewco_free(brewco: *mut BrewcoInterpreter) {
+    if !brewco.is_null() {
+        drop(Box::from_raw(brewco));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c(text: &str) -> CString {
+        CString::new(text).unwrap()
+    }
+
+    /// Take a string brewco handed out, freeing it the way a C caller would
+    unsafe fn taken(text: *mut c_char) -> Option<String> {
+        if text.is_null() {
+            return None;
+        }
+        let owned = CStr::from_ptr(text).to_string_lossy().into_owned();
+        brewco_free_string(text);
+        Some(owned)
+    }
+
+    #[test]
+    fn globals_outlive_each_eval() {
+        unsafe {
+            let brewco = brewco_new();
+            assert_eq!(brewco_eval(brewco, c("beans order = {drink: \"latte\", shots: 2}").as_ptr()), BREWCO_OK);
+            assert_eq!(brewco_eval(brewco, c("beans total = order.shots brew_op 3").as_ptr()), BREWCO_OK);
+            assert_eq!(taken(brewco_get_string(brewco, c("total").as_ptr())).as_deref(), Some("6"));
+            let order: serde_json::Value = serde_json::from_str(&taken(brewco_get_json(brewco, c("order").as_ptr())).unwrap()).unwrap();
+            assert_eq!(order, serde_json::json!({ "drink": "latte", "shots": 2 }));
+            assert!(taken(brewco_get_string(brewco, c("missing").as_ptr())).is_none());
+            brewco_free(brewco);
+        }
+    }
+
+    #[test]
+    fn a_spill_is_kept_until_the_next_eval() {
+        unsafe {
+            let brewco = brewco_new();
+            assert_eq!(brewco_eval(brewco, c("beans cups = 0\nbeans each = 12 / cups").as_ptr()), BREWCO_SPILLED);
+            let spill = CStr::from_ptr(brewco_last_spill(brewco)).to_string_lossy().into_owned();
+            assert!(spill.contains("line 2"), "{}", spill);
+            assert_eq!(brewco_eval(brewco, c("beans = ").as_ptr()), BREWCO_SPILLED);
+            assert!(!brewco_last_spill(brewco).is_null());
+            assert_eq!(brewco_eval(brewco, c("beans cups = 1").as_ptr()), BREWCO_OK);
+            assert!(brewco_last_spill(brewco).is_null());
+            brewco_free(brewco);
+        }
+    }
+
+    #[test]
+    fn finish_service_comes_back_as_a_status_instead_of_exiting() {
+        unsafe {
+            let brewco = brewco_new();
+            let recipe = c("beans before = 1\ntaste_carefully {\n    finish_service(3)\n} if_spilled (e) {\n    beans caught = e\n}\nbeans after = 2");
+            assert_eq!(brewco_eval(brewco, recipe.as_ptr()), BREWCO_FINISHED);
+            let spill = CStr::from_ptr(brewco_last_spill(brewco)).to_string_lossy().into_owned();
+            assert_eq!(spill, "finish_service(3) closed the shop");
+            assert_eq!(taken(brewco_get_string(brewco, c("before").as_ptr())).as_deref(), Some("1"));
+            assert!(taken(brewco_get_string(brewco, c("caught").as_ptr())).is_none());
+            assert!(taken(brewco_get_string(brewco, c("after").as_ptr())).is_none());
+            // The interpreter is still there to brew with
+            assert_eq!(brewco_eval(brewco, c("beans again = 3").as_ptr()), BREWCO_OK);
+            brewco_free(brewco);
+        }
+    }
+
+    #[test]
+    fn bad_arguments_are_refused_without_touching_them() {
+        unsafe {
+            let brewco = brewco_new();
+            assert_eq!(brewco_eval(ptr::null_mut(), c("pourout 1").as_ptr()), BREWCO_BAD_ARGUMENT);
+            assert_eq!(brewco_eval(brewco, ptr::null()), BREWCO_BAD_ARGUMENT);
+            let not_utf8 = [0xC3u8, 0x28, 0];
+            assert_eq!(brewco_eval(brewco, not_utf8.as_ptr() as *const c_char), BREWCO_BAD_ARGUMENT);
+            assert!(brewco_get_string(brewco, ptr::null()).is_null());
+            assert!(brewco_last_spill(ptr::null()).is_null());
+            brewco_free_string(ptr::null_mut());
+            brewco_free(brewco);
+            brewco_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn a_nul_in_a_value_is_escaped_rather_than_cutting_it_short() {
+        assert_eq!(c_text("oat\0milk".to_string()).to_str().unwrap(), "oat\\0milk");
+    }
+}
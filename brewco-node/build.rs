@@ -0,0 +1,5 @@
+// brewco-node/build.rs
+
+fn main() {
+    napi_build::setup();
+}
@@ -0,0 +1,28 @@
+// brewco-node/index.d.ts
+
+/** Options for `evaluate` */
+export interface EvaluateOptions {
+  /** A brew to call once the recipe has brewed */
+  call?: string
+  /** The arguments for that brew */
+  args?: Array<any>
+}
+
+/** What `evaluate` resolves with */
+export interface Evaluation {
+  /** Everything the recipe poured, in order */
+  output: string
+  /** What the brew `options.call` names served */
+  value?: any
+  /** Why the recipe (or that brew) spilled, if it did */
+  spill?: string
+}
+
+/** Brew `source` off the main thread; resolves with its output, its spill, and what `options.call`'s brew served */
+export function evaluate(source: string, options?: EvaluateOptions): Promise<Evaluation>
+
+/**
+ * Make `callback` a native called `name` in every recipe evaluated from now on. It gets the
+ * recipe's arguments as JavaScript values, and what it returns is what the native serves.
+ */
+export function registerNative(name: string, callback: (...args: any[]) => any): void
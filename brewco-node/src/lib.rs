@@ -0,0 +1,181 @@
+/*
+ * 🟢 Brewco Node: the coffee shop as a Node addon ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @addon_note: "require it, await it, sip it!" ✨
+ *
+ * Node bindings built with napi-rs. `evaluate(source, options)` brews a
+ * recipe on a fresh interpreter on libuv's worker pool and resolves with
+ * what it poured, the spill if it spilled and, when `options.call` names a
+ * brew, what that brew served for `options.args`. Values cross over as
+ * JSON, through the conversions in coffee_serde.rs.
+ *
+ * `registerNative(name, callback)` makes a JavaScript function a native in
+ * every recipe evaluated after it. The recipe brews off the main thread, so
+ * a call waits there while Node runs the callback. Callbacks have to answer
+ * right away (a Promise spills), and one that throws spills the recipe
+ * instead of taking Node down.
+ */
+
+// brewco-node/src/lib.rs
+
+// Natives hand brewco's ControlFlow back as their Err, and that size is the
+// interpreter's call (see the same allow in brewco's lib.rs)
+#![allow(clippy::result_large_err)]
+
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Mutex, OnceLock};
+
+use brewco::interpreter::{self, ControlFlow, Interpreter, Value};
+use brewco::parser;
+use napi::bindgen_prelude::AsyncTask;
+use napi::threadsafe_function::{ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Env, JsFunction, Status, Task};
+use napi_derive::napi;
+
+/// A native from JavaScript: it gets its arguments as a JSON array and answers in JSON
+type JsNative = ThreadsafeFunction<String, ErrorStrategy::Fatal>;
+
+/// Natives registered from JavaScript, added to every interpreter evaluate makes
+fn js_natives() -> &'static Mutex<HashMap<String, JsNative>> {
+    static JS_NATIVES: OnceLock<Mutex<HashMap<String, JsNative>>> = OnceLock::new();
+    JS_NATIVES.get_or_init(Default::default)
+}
+
+/// Wraps a callback so it always answers with `{"served": ...}` or `{"spill": "..."}`;
+/// a throw that reached the threadsafe function would be fatal to Node
+const ANSWER_IN_JSON: &str = r#"(callback) => (args) => {
+  try {
+    const served = callback(...JSON.parse(args));
+    if (served && typeof served.then === "function") {
+      return JSON.stringify({ spill: "a native from JavaScript has to answer right away, not with a Promise" });
+    }
+    return JSON.stringify({ served: served === undefined ? null : served });
+  } catch (e) {
+    return JSON.stringify({ spill: String(e && e.message !== undefined ? e.message : e) });
+  }
+}"#;
+
+/// Options for `evaluate`
+#[napi(object)]
+#[derive(Default)]
+pub struct EvaluateOptions {
+    /// A brew to call once the recipe has brewed
+    pub call: Option<String>,
+    /// The arguments for that brew
+    pub args: Option<Vec<serde_json::Value>>,
+}
+
+/// What `evaluate` resolves with
+#[napi(object)]
+pub struct Evaluation {
+    /// Everything the recipe poured, in order
+    pub output: String,
+    /// What the brew `options.call` names served
+    pub value: Option<serde_json::Value>,
+    /// Why the recipe (or that brew) spilled, if it did
+    pub spill: Option<String>,
+}
+
+pub struct Evaluate {
+    source: String,
+    options: EvaluateOptions,
+}
+
+impl Task for Evaluate {
+    type Output = Evaluation;
+    type JsValue = Evaluation;
+
+    fn compute(&mut self) -> napi::Result<Evaluation> {
+        interpreter::catch_pours();
+        let brewed = panic::catch_unwind(AssertUnwindSafe(|| self.brew()))
+            .unwrap_or_else(|_| Err("Brewco itself spilled while brewing this".to_string()));
+        let output = interpreter::take_caught_pours();
+        Ok(match brewed {
+            Ok(value) => Evaluation { output, value, spill: None },
+            Err(spill) => Evaluation { output, value: None, spill: Some(spill) },
+        })
+    }
+
+    fn resolve(&mut self, _env: Env, evaluation: Evaluation) -> napi::Result<Evaluation> {
+        Ok(evaluation)
+    }
+}
+
+impl Evaluate {
+    /// Brew the source, then the brew `options.call` names: what that served, or why something spilled
+    fn brew(&self) -> Result<Option<serde_json::Value>, String> {
+        let parsed = parser::parse_source(&self.source);
+        if !parsed.errors.is_empty() {
+            return Err(parsed.errors.join("\n"));
+        }
+        let mut interpreter = Interpreter::new();
+        let natives: Vec<(String, JsNative)> =
+            js_natives().lock().unwrap_or_else(|e| e.into_inner()).iter().map(|(name, native)| (name.clone(), native.clone())).collect();
+        for (name, native) in natives {
+            interpreter.register_native("node", &name, "args...", move |_, name, args| call_js_native(&native, name, args));
+        }
+        if let Err(ControlFlow::RuntimeError(message)) = interpreter.run(&parsed.statements) {
+            return Err(interpreter.spill_report(&message).headline());
+        }
+        let Some(brew) = &self.options.call else {
+            return Ok(None);
+        };
+        let args = self.options.args.clone().unwrap_or_default().into_iter().map(Value::from).collect();
+        let served = interpreter.call_function(brew, args).map_err(|report| report.headline())?;
+        serde_json::Value::try_from(&served)
+            .map(Some)
+            .map_err(|why| format!("{} served something JavaScript can't take: {}", brew, why))
+    }
+}
+
+/// Call a native from JavaScript on Node's main thread and wait for its answer
+fn call_js_native(native: &JsNative, name: &str, args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let spill = |why: String| ControlFlow::RuntimeError(format!("{}(): {}", name, why));
+    let args = args.iter().map(serde_json::Value::try_from).collect::<Result<Vec<_>, _>>().map_err(spill)?;
+    let (sender, answer) = mpsc::channel();
+    let status = native.call_with_return_value(
+        serde_json::Value::Array(args).to_string(),
+        ThreadsafeFunctionCallMode::Blocking,
+        move |reply: String| {
+            let _ = sender.send(reply);
+            Ok(())
+        },
+    );
+    if status != Status::Ok {
+        return Err(spill(format!("Node wouldn't take the call ({})", status)));
+    }
+    let reply = answer.recv().map_err(|_| spill("Node closed before it answered".to_string()))?;
+    let mut reply: serde_json::Value = serde_json::from_str(&reply).map_err(|e| spill(e.to_string()))?;
+    match reply.get("spill").and_then(|why| why.as_str()) {
+        Some(why) => Err(spill(why.to_string())),
+        None => Ok(reply.get_mut("served").map_or(Value::Null, |served| Value::from(served.take()))),
+    }
+}
+
+/// Brew `source` off the main thread; resolves with its output, its spill, and what `options.call`'s brew served
+#[napi]
+pub fn evaluate(source: String, options: Option<EvaluateOptions>) -> AsyncTask<Evaluate> {
+    AsyncTask::new(Evaluate { source, options: options.unwrap_or_default() })
+}
+
+/// Make `callback` a native called `name` in every recipe evaluated from now on. It gets the
+/// recipe's arguments as JavaScript values, and what it returns is what the native serves.
+#[napi(js_name = "registerNative")]
+pub fn register_native(env: Env, name: String, callback: JsFunction) -> napi::Result<()> {
+    let callable = name.starts_with(|c: char| c.is_alphabetic() || c == '_') && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if !callable {
+        return Err(napi::Error::from_reason(format!("'{}' isn't a name a recipe could call", name)));
+    }
+    if interpreter::is_builtin_native(&name) {
+        return Err(napi::Error::from_reason(format!("{}() is already a built-in native", name)));
+    }
+    let answer_in_json: JsFunction = env.run_script(ANSWER_IN_JSON)?;
+    let wrapped: JsFunction = answer_in_json.call(None, &[callback])?.try_into()?;
+    let mut native: JsNative = wrapped.create_threadsafe_function(0, |ctx: ThreadSafeCallContext<String>| Ok(vec![ctx.value]))?;
+    // A registered native shouldn't keep Node running; a pending evaluate does that while it needs one
+    native.unref(&env)?;
+    js_natives().lock().unwrap_or_else(|e| e.into_inner()).insert(name, native);
+    Ok(())
+}
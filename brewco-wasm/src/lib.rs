@@ -0,0 +1,75 @@
+/*
+ * 🌐 Brewco Wasm: a coffee shop in the browser ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @playground_note: "No install, no server, just brew!" ✨
+ *
+ * brewco's lexer, parser and interpreter built for wasm32-unknown-unknown,
+ * with `eval` exported through wasm-bindgen so an online playground can
+ * brew recipes client-side. Each call brews on a fresh interpreter and
+ * hands back everything the recipe poured, followed by the spill if it
+ * spilled. The REPL, server, ledger and plugins aren't in the browser
+ * build, and recipes that reach for files or packages spill there.
+ */
+
+// brewco-wasm/src/lib.rs
+
+use brewco::interpreter::{self, ControlFlow, Interpreter};
+use brewco::parser;
+use wasm_bindgen::prelude::*;
+
+/// Brew `source` and return what it poured, then the spill if there was one. JavaScript
+/// can't bind a name `eval`, so there it's `brewEval`.
+#[wasm_bindgen(js_name = "brewEval")]
+pub fn eval(source: &str) -> String {
+    interpreter::catch_pours();
+    let spill = brew(source);
+    let mut poured = interpreter::take_caught_pours();
+    if let Some(spill) = spill {
+        poured.push_str(&spill);
+        poured.push('\n');
+    }
+    poured
+}
+
+fn brew(source: &str) -> Option<String> {
+    let parsed = parser::parse_source(source);
+    if !parsed.errors.is_empty() {
+        return Some(parsed.errors.join("\n"));
+    }
+    let mut interpreter = Interpreter::new();
+    match interpreter.run(&parsed.statements) {
+        Err(ControlFlow::RuntimeError(message)) => Some(interpreter.spill_report(&message).headline()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pours_come_back_in_order() {
+        assert_eq!(eval("pourout \"latte\"\npourout_flat 1, 2\npourout \"\""), "latte\n1 2\n");
+    }
+
+    #[test]
+    fn a_spill_follows_what_was_poured() {
+        let brewed = eval("pourout \"before\"\nbeans cups = 0\npourout 12 / cups\npourout \"after\"");
+        let (poured, spill) = brewed.split_once('\n').unwrap();
+        assert_eq!(poured, "before");
+        assert!(spill.contains("line 3") && !spill.contains("after"), "{}", spill);
+    }
+
+    #[test]
+    fn each_eval_starts_with_an_empty_pantry() {
+        eval("beans cups = 3");
+        assert!(eval("pourout cups").contains("cups"));
+    }
+
+    #[test]
+    fn syntax_spills_are_returned_not_brewed() {
+        let brewed = eval("pourout \"cortado\"\nbeans = ");
+        assert!(!brewed.contains("cortado") && brewed.contains("line 2"), "{}", brewed);
+    }
+}
@@ -0,0 +1,17 @@
+// fuzz/fuzz_targets/lex_and_parse.rs
+//
+// Arbitrary bytes through the lexer and parser: they may spill, but must never
+// panic, hang or overflow the stack.
+
+#![no_main]
+
+use brewco::{lexer, parser};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let source = String::from_utf8_lossy(data);
+    for _ in lexer::CoffeeTokenStream::with_comments(&source) {}
+    let result = parser::parse_source(&source);
+    // Every spill has somewhere to point
+    assert_eq!(result.errors.len(), result.error_positions.len());
+});
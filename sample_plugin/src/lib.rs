@@ -0,0 +1,65 @@
+/*
+ * 🔌 Grinder: a sample Brewco native plugin ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @plugin_note: "Bring your own grinder!" ✨
+ *
+ * Build it with `cargo build --release`, then point brewco.toml at it:
+ *
+ *     [brew]
+ *     plugins = ["sample_plugin/target/release/libgrinder.so"]
+ *
+ * and recipes can call grind_size(n) and reverse_words(text) like any
+ * other native. See src/coffee_plugins.rs in brewco for the interface.
+ */
+
+// sample_plugin/src/lib.rs
+
+use std::ffi::{c_char, CStr, CString};
+
+use serde_json::{json, Value};
+
+#[no_mangle]
+pub extern "C" fn brewco_plugin_abi() -> u32 {
+    1
+}
+
+#[no_mangle]
+pub extern "C" fn brewco_plugin_natives() -> *const c_char {
+    c"grind_size\nreverse_words".as_ptr()
+}
+
+/// # Safety
+/// `name` and `args_json` are NUL-terminated strings from brewco
+#[no_mangle]
+pub unsafe extern "C" fn brewco_plugin_call(name: *const c_char, args_json: *const c_char) -> *mut c_char {
+    let name = CStr::from_ptr(name).to_string_lossy();
+    let args: Vec<Value> = serde_json::from_str(&CStr::from_ptr(args_json).to_string_lossy()).unwrap_or_default();
+    let answer = match brew(&name, &args) {
+        Ok(value) => json!({ "ok": value }),
+        Err(message) => json!({ "spill": message }),
+    };
+    CString::new(answer.to_string()).unwrap_or_default().into_raw()
+}
+
+/// # Safety
+/// `result` came from brewco_plugin_call and is freed once
+#[no_mangle]
+pub unsafe extern "C" fn brewco_plugin_free(result: *mut c_char) {
+    if !result.is_null() {
+        drop(CString::from_raw(result));
+    }
+}
+
+fn brew(name: &str, args: &[Value]) -> Result<Value, String> {
+    match (name, args) {
+        ("grind_size", [Value::Number(seconds)]) => {
+            let seconds = seconds.as_f64().unwrap_or(0.0);
+            Ok(json!(if seconds < 30.0 { "fine" } else if seconds < 240.0 { "medium" } else { "coarse" }))
+        }
+        ("grind_size", _) => Err("grind_size() expects the brew time in seconds".to_string()),
+        ("reverse_words", [Value::String(text)]) => Ok(json!(text.split_whitespace().rev().collect::<Vec<_>>().join(" "))),
+        ("reverse_words", _) => Err("reverse_words() expects a string".to_string()),
+        _ => Err(format!("grinder has no {}()", name)),
+    }
+}
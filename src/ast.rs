@@ -12,7 +12,10 @@
  */
 
 // src/ast.rs
-#[derive(Debug, Clone)]
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
 pub enum Expr {
     Number(f64),
     String(String),
@@ -54,7 +57,7 @@ pub enum Expr {
     Super,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum BinaryOperator {
     Add,            // + or add
     Subtract,       // - or sip
@@ -74,16 +77,43 @@ pub enum BinaryOperator {
     BitXor,         // ^ or spice
     Shl,            // << or double_shot
     Shr,            // >> or half_caf
+    ThenPour,       // |> or then_pour (the parser turns it into a call)
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum UnaryOperator {
     Negate,         // -
     Not,            // ! or no_foam
     BitNot,         // ~ or invert
 }
 
-#[derive(Debug, Clone)]
+/// What one roast arm matches: a value, or an inclusive range of numbers like `0..59`
+#[derive(Debug, Clone, Serialize)]
+pub enum RoastPattern {
+    Value(Expr),
+    Range(Expr, Expr),
+}
+
+/// Where a print statement sends its line
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum PourSpout {
+    Line,           // pourout: stdout, ending the line
+    Flat,           // pourout_flat: stdout, leaving the line open
+    Spill,          // spill_out: stderr, ending the line
+}
+
+impl PourSpout {
+    pub fn from_word(word: &str) -> Option<PourSpout> {
+        match word {
+            "pourout" => Some(PourSpout::Line),
+            "pourout_flat" => Some(PourSpout::Flat),
+            "spill_out" => Some(PourSpout::Spill),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub enum Statement {
     VarDecl { 
         name: String, 
@@ -98,7 +128,7 @@ pub enum Statement {
         name: String,
         fields: Vec<(String, Expr)>
     },
-    Print(Expr),
+    Print(Expr, PourSpout),
     If {
         condition: Expr,
         then_branch: Vec<Statement>,
@@ -123,6 +153,7 @@ pub enum Statement {
         parent: Option<String>,
         fields: Vec<FieldDecl>,
         methods: Vec<Statement>,
+        doc: Option<String>, // the 🎀🎀 lines above it
     },
     CoffeeRecipeDecl {
         name: String,
@@ -133,14 +164,18 @@ pub enum Statement {
         params: Vec<ParamDecl>,
         body: Vec<Statement>,
         return_type: Option<String>,
+        doc: Option<String>, // the 🎀🎀 lines above it
     },
     BrewTime(Expr),
     Return(Option<Expr>),
+    YieldSip(Expr), // yield_sip value: only inside a generator brew
     Break,
     Continue,
     ExprStmt(Expr),
     Foreach {
         var: String,
+        /// The index in `pour item, i in cup`, or the value in `pour key, value in menu`
+        second_var: Option<String>,
         iterable: Expr,
         body: Vec<Statement>,
     }, // foreach loop
@@ -150,7 +185,7 @@ pub enum Statement {
     },
     RoastSwitch {
         value: Expr,
-        arms: Vec<(Expr, Vec<Statement>)>,
+        arms: Vec<(Vec<RoastPattern>, Vec<Statement>)>,
         default: Vec<Statement>,
     },
     TryCatch {
@@ -158,22 +193,34 @@ pub enum Statement {
         error_variable: Option<String>,
         catch_branch: Vec<Statement>,
     },
+    BenchBrew {
+        name: String,
+        body: Vec<Statement>,
+    }, // bench_brew "name" { ... }, only run by `brew bench`
+    TasteTest {
+        name: String,
+        body: Vec<Statement>,
+    }, // taste_test "name" { ... }, only run by `brew test`
+    SourceMark {
+        line: usize,
+        column: usize,
+    }, // inserted by the parser before each statement; running it just records where we are
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FieldDecl {
     pub name: String,
     pub value: Expr,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MethodSignature {
     pub name: String,
     pub params: Vec<ParamDecl>,
     pub return_type: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ParamDecl {
     pub name: String,
     pub type_name: String,
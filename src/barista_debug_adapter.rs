@@ -0,0 +1,609 @@
+// src/barista_debug_adapter.rs - The Barista Debug Adapter for editors that speak DAP ☕
+
+use serde_json::{json, Value as JsonValue};
+use std::cell::RefCell;
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver};
+
+use crate::barista_language_server::{read_framed_message, write_framed_message};
+use crate::brew_debugger::{BrewDebugSession, BrewStepMode};
+use crate::ast::PourSpout;
+use crate::interpreter::{self, BrewDebugHook, ControlFlow, Interpreter, Value};
+use crate::parser;
+
+/// Default port editors connect to (`"debugServer": 4711` in a VS Code launch config)
+pub const BARISTA_DAP_PORT: u16 = 4711;
+
+/// The only thread a Brewco recipe ever brews on
+const BREWING_THREAD_ID: i64 = 1;
+
+/// One client connection: incoming requests arrive on a channel, responses and events go out on the socket
+pub struct DapConnection {
+    outgoing: TcpStream,
+    incoming: Receiver<JsonValue>,
+    next_seq: i64,
+}
+
+impl DapConnection {
+    /// Next request from the editor; `None` once the editor hangs up
+    fn next_request(&mut self) -> Option<JsonValue> {
+        self.incoming.recv().ok()
+    }
+
+    fn send_message(&mut self, mut message: JsonValue) {
+        message["seq"] = json!(self.next_seq);
+        self.next_seq += 1;
+        // A vanished editor is noticed by the reader thread, so write errors can be ignored here
+        let _ = write_framed_message(&mut self.outgoing, &message);
+    }
+
+    fn respond(&mut self, request: &JsonValue, body: JsonValue) {
+        self.send_message(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": true,
+            "body": body,
+        }));
+    }
+
+    fn respond_error(&mut self, request: &JsonValue, message: &str) {
+        self.send_message(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": false,
+            "message": message,
+        }));
+    }
+
+    fn send_event(&mut self, event: &str, body: JsonValue) {
+        self.send_message(json!({ "type": "event", "event": event, "body": body }));
+    }
+}
+
+/// What a `variablesReference` handed to the editor points at
+enum VariableHandle {
+    FramePantry(Option<usize>), // a brew frame's beans, or the globals
+    Ingredients(Value),         // the items of a cup or the fields of an object
+}
+
+/// Debug hook that pauses by talking DAP instead of prompting on a console
+struct BaristaDebugAdapter {
+    connection: Rc<RefCell<DapConnection>>,
+    session: BrewDebugSession,
+    program_path: String,
+    stop_on_entry: bool,
+    pause_requested: bool,
+    variable_handles: Vec<VariableHandle>,
+}
+
+impl BaristaDebugAdapter {
+    /// Requests that can be answered while the recipe is still brewing
+    fn handle_running_request(&mut self, request: &JsonValue) {
+        let mut connection = self.connection.borrow_mut();
+        match request["command"].as_str().unwrap_or("") {
+            "setBreakpoints" => {
+                let body = set_breakpoints(&mut self.session, request);
+                connection.respond(request, body);
+            }
+            "threads" => connection.respond(request, brewing_threads()),
+            "pause" => {
+                self.pause_requested = true;
+                connection.respond(request, json!({}));
+            }
+            "disconnect" => {
+                connection.respond(request, json!({}));
+                std::process::exit(0);
+            }
+            _ => connection.respond_error(request, "The recipe is still brewing; pause it first"),
+        }
+    }
+
+    fn stack_trace(&self, interpreter: &Interpreter) -> JsonValue {
+        let frames = interpreter.brew_frames();
+        let (line, column) = interpreter.current_location();
+        let source = json!({ "name": self.program_path.rsplit('/').next(), "path": self.program_path });
+        let innermost = frames.last().map_or("<main>", |frame| frame.label.as_str());
+        let mut stack_frames = vec![json!({
+            "id": 0, "name": innermost, "source": source, "line": line, "column": column,
+        })];
+        for (depth, frame) in frames.iter().enumerate().rev() {
+            let caller = if depth == 0 { "<main>" } else { frames[depth - 1].label.as_str() };
+            stack_frames.push(json!({
+                "id": frames.len() - depth,
+                "name": caller,
+                "source": source,
+                "line": frame.call_site.0,
+                "column": frame.call_site.1,
+            }));
+        }
+        json!({ "stackFrames": stack_frames, "totalFrames": frames.len() + 1 })
+    }
+
+    /// Stack frame ids count outward from the paused statement; brew frames count inward from main
+    fn brew_frame_index(interpreter: &Interpreter, frame_id: usize) -> Option<usize> {
+        interpreter.brew_frames().len().checked_sub(frame_id + 1)
+    }
+
+    fn new_handle(&mut self, handle: VariableHandle) -> usize {
+        self.variable_handles.push(handle);
+        self.variable_handles.len()
+    }
+
+    /// Describe a value, handing out a reference when the editor can expand it
+    fn describe_variable(&mut self, name: String, value: Value) -> JsonValue {
+        let reference = match &value {
+            Value::Array(_) | Value::Set(_) | Value::Chain(_) | Value::Object { .. } => self.new_handle(VariableHandle::Ingredients(value.clone())),
+            _ => 0,
+        };
+        json!({
+            "name": name,
+            "value": shown_value(&value),
+            "type": value.type_name(),
+            "variablesReference": reference,
+        })
+    }
+
+    fn variables(&mut self, interpreter: &Interpreter, reference: usize) -> JsonValue {
+        let beans: Vec<(String, Value)> = match self.variable_handles.get(reference.wrapping_sub(1)) {
+            Some(VariableHandle::FramePantry(frame_index)) => interpreter.frame_pantry(*frame_index),
+            Some(VariableHandle::Ingredients(Value::Array(cup))) => cup.iter()
+                .enumerate()
+                .map(|(index, item)| (format!("[{}]", index), item.clone()))
+                .collect(),
+            Some(VariableHandle::Ingredients(Value::Chain(chain))) => chain.borrow().iter()
+                .enumerate()
+                .map(|(index, item)| (format!("[{}]", index), item.clone()))
+                .collect(),
+            Some(VariableHandle::Ingredients(Value::Set(set))) => set.borrow().items()
+                .enumerate()
+                .map(|(index, item)| (format!("{{{}}}", index), item.clone()))
+                .collect(),
+            Some(VariableHandle::Ingredients(Value::Object { fields, .. })) => {
+                let mut fields: Vec<(String, Value)> = fields.iter()
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect();
+                fields.sort_by(|a, b| a.0.cmp(&b.0));
+                fields
+            }
+            _ => Vec::new(),
+        };
+        let variables: Vec<JsonValue> = beans.into_iter()
+            .map(|(name, value)| self.describe_variable(name, value))
+            .collect();
+        json!({ "variables": variables })
+    }
+
+    /// Answer requests until the editor says to keep brewing
+    fn paused_loop(&mut self, interpreter: &mut Interpreter) {
+        let depth = interpreter.brew_frames().len();
+        loop {
+            let Some(request) = self.connection.borrow_mut().next_request() else {
+                std::process::exit(0); // the editor hung up mid-pause
+            };
+            let resume_mode = match request["command"].as_str().unwrap_or("") {
+                "continue" => Some(BrewStepMode::Continue),
+                "stepIn" => Some(BrewStepMode::StepIn),
+                "next" => Some(BrewStepMode::StepOver(depth)),
+                "stepOut" => Some(BrewStepMode::StepOut(depth)),
+                _ => None,
+            };
+            if let Some(mode) = resume_mode {
+                self.session.step_mode = mode;
+                self.variable_handles.clear();
+                self.connection.borrow_mut().respond(&request, json!({ "allThreadsContinued": true }));
+                return;
+            }
+
+            match request["command"].as_str().unwrap_or("") {
+                "stackTrace" => {
+                    let body = self.stack_trace(interpreter);
+                    self.connection.borrow_mut().respond(&request, body);
+                }
+                "scopes" => {
+                    let frame_id = request["arguments"]["frameId"].as_u64().unwrap_or(0) as usize;
+                    let mut scopes = Vec::new();
+                    if let Some(index) = Self::brew_frame_index(interpreter, frame_id) {
+                        let reference = self.new_handle(VariableHandle::FramePantry(Some(index)));
+                        scopes.push(json!({ "name": "Pantry", "presentationHint": "locals", "variablesReference": reference, "expensive": false }));
+                    }
+                    let reference = self.new_handle(VariableHandle::FramePantry(None));
+                    scopes.push(json!({ "name": "Global Pantry", "variablesReference": reference, "expensive": false }));
+                    self.connection.borrow_mut().respond(&request, json!({ "scopes": scopes }));
+                }
+                "variables" => {
+                    let reference = request["arguments"]["variablesReference"].as_u64().unwrap_or(0) as usize;
+                    let body = self.variables(interpreter, reference);
+                    self.connection.borrow_mut().respond(&request, body);
+                }
+                "evaluate" => {
+                    let expression = request["arguments"]["expression"].as_str().unwrap_or("");
+                    match interpreter.evaluate_in_pause(expression) {
+                        Ok(served) => {
+                            let value = served.unwrap_or(Value::Null);
+                            let described = self.describe_variable(expression.to_string(), value);
+                            self.connection.borrow_mut().respond(&request, json!({
+                                "result": described["value"],
+                                "type": described["type"],
+                                "variablesReference": described["variablesReference"],
+                            }));
+                        }
+                        Err(spill) => self.connection.borrow_mut().respond_error(&request, &spill),
+                    }
+                }
+                "pause" => self.connection.borrow_mut().respond(&request, json!({})),
+                _ => self.handle_running_request(&request),
+            }
+        }
+    }
+}
+
+impl BrewDebugHook for BaristaDebugAdapter {
+    fn brew_checkpoint(&mut self, interpreter: &mut Interpreter) {
+        // Pick up breakpoint edits and pause requests sent while brewing
+        loop {
+            let pending = self.connection.borrow().incoming.try_recv();
+            match pending {
+                Ok(request) => self.handle_running_request(&request),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => std::process::exit(0),
+            }
+        }
+
+        let (line, _) = interpreter.current_location();
+        let depth = interpreter.brew_frames().len();
+        let reason = if self.stop_on_entry {
+            "entry"
+        } else if self.pause_requested {
+            "pause"
+        } else if self.session.breakpoints.contains(&line) {
+            "breakpoint"
+        } else if self.session.should_pause(line, depth) {
+            "step"
+        } else {
+            return;
+        };
+        self.stop_on_entry = false;
+        self.pause_requested = false;
+
+        self.connection.borrow_mut().send_event("stopped", json!({
+            "reason": reason,
+            "threadId": BREWING_THREAD_ID,
+            "allThreadsStopped": true,
+        }));
+        self.paused_loop(interpreter);
+    }
+}
+
+fn brewing_threads() -> JsonValue {
+    json!({ "threads": [{ "id": BREWING_THREAD_ID, "name": "brewco" }] })
+}
+
+/// Replace the breakpoints for the program; every line inside the recipe is accepted
+fn set_breakpoints(session: &mut BrewDebugSession, request: &JsonValue) -> JsonValue {
+    session.breakpoints.clear();
+    let requested = request["arguments"]["breakpoints"].as_array().cloned().unwrap_or_default();
+    let breakpoints: Vec<JsonValue> = requested.iter()
+        .filter_map(|breakpoint| breakpoint["line"].as_u64())
+        .map(|line| {
+            session.breakpoints.insert(line as usize);
+            json!({ "verified": true, "line": line })
+        })
+        .collect();
+    json!({ "breakpoints": breakpoints })
+}
+
+fn shown_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("{:?}", s),
+        Value::Array(cup) => format!("cup({})", cup.len()),
+        Value::Set(set) => format!("set({})", set.borrow().len()),
+        Value::Chain(chain) => format!("chain({})", chain.borrow().len()),
+        Value::Object { class_name, .. } => format!("{} {{..}}", class_name),
+        other => format!("{}", other),
+    }
+}
+
+/// Wait for an editor on `port`, then brew the recipe it launches under the debugger.
+/// Given a `waiting` recipe (`brew dap my_script.brewco`), the adapter holds that one
+/// back until an editor attaches, and brews it for the editor's `attach` request.
+pub fn run_debug_adapter(port: u16, waiting: Option<String>) {
+    let listener = match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            println!("[ERROR] Could not open the debug adapter on port {}: {}", port, e);
+            std::process::exit(1);
+        }
+    };
+    match &waiting {
+        Some(program) => println!("🐞 {} is waiting for an editor to attach on 127.0.0.1:{}", program, port),
+        None => println!("🐞 Barista Debug Adapter listening on 127.0.0.1:{} (point your editor's debugServer here)", port),
+    }
+    let stream = match listener.accept() {
+        Ok((stream, _)) => stream,
+        Err(e) => {
+            println!("[ERROR] Could not accept the editor's connection: {}", e);
+            std::process::exit(1);
+        }
+    };
+    serve_debug_session(stream, waiting);
+}
+
+/// Speak DAP with one connected editor until it disconnects, brewing the recipe
+/// it launches or attaches to and sending everything the recipe pours back as `output` events
+fn serve_debug_session(stream: TcpStream, waiting: Option<String>) {
+    let (sender, receiver) = mpsc::channel();
+    let mut reader = BufReader::new(stream.try_clone().expect("debug adapter socket should be cloneable"));
+    std::thread::spawn(move || {
+        while let Some(message) = read_framed_message(&mut reader) {
+            if sender.send(message).is_err() {
+                break;
+            }
+        }
+    });
+    let connection = Rc::new(RefCell::new(DapConnection { outgoing: stream, incoming: receiver, next_seq: 1 }));
+
+    // Configuration phase: nothing brews until the editor sends configurationDone
+    let mut session = BrewDebugSession::new_session();
+    session.step_mode = BrewStepMode::Continue;
+    let mut program_path = None;
+    let mut stop_on_entry = false;
+    loop {
+        let Some(request) = connection.borrow_mut().next_request() else { return };
+        let mut connection = connection.borrow_mut();
+        match request["command"].as_str().unwrap_or("") {
+            "initialize" => {
+                connection.respond(&request, json!({
+                    "supportsConfigurationDoneRequest": true,
+                    "supportsEvaluateForHovers": true,
+                }));
+                connection.send_event("initialized", json!({}));
+            }
+            // The recipe waiting in the adapter, or else the one the attach configuration names
+            "attach" => match waiting.clone().or_else(|| request["arguments"]["program"].as_str().map(str::to_string)) {
+                Some(program) => {
+                    program_path = Some(program);
+                    stop_on_entry = request["arguments"]["stopOnEntry"].as_bool().unwrap_or(false);
+                    connection.respond(&request, json!({}));
+                }
+                None => connection.respond_error(&request, "No recipe is waiting to be attached to; start `brew dap <recipe>` or give one in the 'program' argument"),
+            },
+            "launch" => match request["arguments"]["program"].as_str() {
+                Some(program) => {
+                    program_path = Some(program.to_string());
+                    stop_on_entry = request["arguments"]["stopOnEntry"].as_bool().unwrap_or(false);
+                    connection.respond(&request, json!({}));
+                }
+                None => connection.respond_error(&request, "Give the recipe to brew in the 'program' argument"),
+            },
+            "setBreakpoints" => {
+                let body = set_breakpoints(&mut session, &request);
+                connection.respond(&request, body);
+            }
+            "setExceptionBreakpoints" => connection.respond(&request, json!({ "breakpoints": [] })),
+            "threads" => connection.respond(&request, brewing_threads()),
+            "configurationDone" => {
+                connection.respond(&request, json!({}));
+                break;
+            }
+            "disconnect" => {
+                connection.respond(&request, json!({}));
+                return;
+            }
+            _ => connection.respond_error(&request, "No recipe is brewing yet"),
+        }
+    }
+
+    let program_path = program_path.unwrap_or_default();
+    let exit_code = match std::fs::read_to_string(&program_path) {
+        Err(_) => {
+            connection.borrow_mut().send_event("output", json!({
+                "category": "stderr",
+                "output": format!("[ERROR] Could not read file: {}\n", program_path),
+            }));
+            1
+        }
+        Ok(code) => {
+            let stmts = parser::parse_source(&code);
+            if !stmts.errors.is_empty() {
+                let mut output = String::from("☕ Oops! Looks like your coffee script hit a sour note:\n");
+                for err in &stmts.errors {
+                    output.push_str(&format!("  - {}\n", err));
+                }
+                connection.borrow_mut().send_event("output", json!({ "category": "stderr", "output": output }));
+                1
+            } else {
+                let mut interpreter = Interpreter::new();
+                interpreter.set_debug_hook(Box::new(BaristaDebugAdapter {
+                    connection: Rc::clone(&connection),
+                    session,
+                    program_path: program_path.clone(),
+                    stop_on_entry,
+                    pause_requested: false,
+                    variable_handles: Vec::new(),
+                }));
+                let pour_connection = Rc::clone(&connection);
+                interpreter::tap_pours(move |text, spout| {
+                    let (category, output) = match spout {
+                        PourSpout::Line => ("stdout", format!("{}\n", text)),
+                        PourSpout::Flat => ("stdout", text.to_string()),
+                        PourSpout::Spill => ("stderr", format!("{}\n", text)),
+                    };
+                    pour_connection.borrow_mut().send_event("output", json!({ "category": category, "output": output }));
+                });
+                let brewed = interpreter.run(&stmts.statements);
+                interpreter::untap_pours();
+                match brewed {
+                    Err(ControlFlow::RuntimeError(msg)) => {
+                        let mut spill = interpreter.spill_report(&msg);
+                        spill.frame_in_source(&program_path, &code);
+                        connection.borrow_mut().send_event("output", json!({ "category": "stderr", "output": spill.brew_detailed_report() }));
+                        1
+                    }
+                    Err(ControlFlow::FinishService(code)) => code,
+                    _ => 0,
+                }
+            }
+        }
+    };
+
+    let mut connection = connection.borrow_mut();
+    connection.send_event("exited", json!({ "exitCode": exit_code }));
+    connection.send_event("terminated", json!({}));
+    while let Some(request) = connection.next_request() {
+        connection.respond(&request, json!({}));
+        if request["command"] == "disconnect" {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The editor's end of a debug session, with the adapter serving on its own thread
+    struct EditorEnd {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+        next_seq: i64,
+    }
+
+    impl EditorEnd {
+        fn connect() -> EditorEnd {
+            EditorEnd::connect_waiting(None)
+        }
+
+        /// Connect to an adapter started as `brew dap <waiting>`
+        fn connect_waiting(waiting: Option<String>) -> EditorEnd {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let address = listener.local_addr().unwrap();
+            std::thread::spawn(move || serve_debug_session(listener.accept().unwrap().0, waiting));
+            let writer = TcpStream::connect(address).unwrap();
+            EditorEnd { reader: BufReader::new(writer.try_clone().unwrap()), writer, next_seq: 1 }
+        }
+
+        fn send(&mut self, command: &str, arguments: JsonValue) {
+            let request = json!({ "seq": self.next_seq, "type": "request", "command": command, "arguments": arguments });
+            self.next_seq += 1;
+            write_framed_message(&mut self.writer, &request).unwrap();
+        }
+
+        /// Skip ahead to the next response or event named `name`, keeping what was skipped
+        fn wait_for(&mut self, name: &str, skipped: &mut Vec<JsonValue>) -> JsonValue {
+            loop {
+                let message = read_framed_message(&mut self.reader).expect("the adapter hung up");
+                if message["command"] == name || message["event"] == name {
+                    return message;
+                }
+                skipped.push(message);
+            }
+        }
+
+        fn request(&mut self, command: &str, arguments: JsonValue) -> JsonValue {
+            self.send(command, arguments);
+            self.wait_for(command, &mut Vec::new())
+        }
+    }
+
+    fn recipe(code: &str) -> String {
+        let path = std::env::temp_dir().join(format!("brewco-dap-{}.brewco", uuid::Uuid::new_v4().simple()));
+        std::fs::write(&path, code).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn a_breakpoint_pauses_with_the_pantry_and_pours_come_back_as_output() {
+        let program = recipe("beans cups = 2\npourout \"brewing\", cups\nspill_out \"careful\"\n");
+        let mut editor = EditorEnd::connect();
+        let initialized = editor.request("initialize", json!({ "adapterID": "brewco" }));
+        assert_eq!(initialized["success"], true);
+        assert_eq!(editor.request("launch", json!({ "program": program }))["success"], true);
+        let breakpoints = editor.request("setBreakpoints", json!({ "source": { "path": program }, "breakpoints": [{ "line": 2 }] }));
+        assert_eq!(breakpoints["body"]["breakpoints"][0]["verified"], true);
+        editor.send("configurationDone", json!({}));
+
+        let stopped = editor.wait_for("stopped", &mut Vec::new());
+        assert_eq!(stopped["body"]["reason"], "breakpoint");
+        let trace = editor.request("stackTrace", json!({ "threadId": BREWING_THREAD_ID }));
+        assert_eq!(trace["body"]["stackFrames"][0]["line"], 2);
+        let scopes = editor.request("scopes", json!({ "frameId": 0 }));
+        let globals = scopes["body"]["scopes"][0]["variablesReference"].clone();
+        let variables = editor.request("variables", json!({ "variablesReference": globals }));
+        let cups = variables["body"]["variables"].as_array().unwrap().iter().find(|bean| bean["name"] == "cups").cloned();
+        assert_eq!(cups.unwrap()["value"], "2");
+        assert_eq!(editor.request("evaluate", json!({ "expression": "cups add 1" }))["body"]["result"], "3");
+
+        editor.send("continue", json!({ "threadId": BREWING_THREAD_ID }));
+        let mut poured = Vec::new();
+        let exited = editor.wait_for("exited", &mut poured);
+        assert_eq!(exited["body"]["exitCode"], 0);
+        let outputs: Vec<(JsonValue, JsonValue)> = poured.iter()
+            .filter(|message| message["event"] == "output")
+            .map(|message| (message["body"]["category"].clone(), message["body"]["output"].clone()))
+            .collect();
+        assert_eq!(outputs, vec![(json!("stdout"), json!("brewing 2\n")), (json!("stderr"), json!("careful\n"))]);
+        editor.wait_for("terminated", &mut Vec::new());
+        assert_eq!(editor.request("disconnect", json!({}))["success"], true);
+    }
+
+    /// Attach, finish configuring, and collect what the recipe pours until it exits
+    fn attach_and_brew(editor: &mut EditorEnd, arguments: JsonValue) -> (JsonValue, Vec<JsonValue>) {
+        editor.request("initialize", json!({}));
+        assert_eq!(editor.request("attach", arguments)["success"], true);
+        editor.send("configurationDone", json!({}));
+        let mut poured = Vec::new();
+        let exited = editor.wait_for("exited", &mut poured);
+        let outputs = poured.into_iter().filter(|message| message["event"] == "output").map(|message| message["body"]["output"].clone()).collect();
+        (exited["body"]["exitCode"].clone(), outputs)
+    }
+
+    #[test]
+    fn attach_brews_the_recipe_waiting_in_the_adapter() {
+        let mut editor = EditorEnd::connect_waiting(Some(recipe("pourout \"waited\"\n")));
+        let (exit_code, outputs) = attach_and_brew(&mut editor, json!({}));
+        assert_eq!(exit_code, 0);
+        assert_eq!(outputs, vec![json!("waited\n")]);
+    }
+
+    #[test]
+    fn attach_brews_the_program_it_names_when_nothing_is_waiting() {
+        let mut editor = EditorEnd::connect();
+        let (exit_code, outputs) = attach_and_brew(&mut editor, json!({ "program": recipe("pourout 1\n") }));
+        assert_eq!(exit_code, 0);
+        assert_eq!(outputs, vec![json!("1\n")]);
+
+        let mut editor = EditorEnd::connect();
+        editor.request("initialize", json!({}));
+        let attached = editor.request("attach", json!({}));
+        assert_eq!(attached["success"], false);
+        assert!(attached["message"].as_str().unwrap().contains("brew dap <recipe>"));
+    }
+
+    #[test]
+    fn a_spill_comes_back_on_stderr_with_a_failing_exit_code() {
+        let program = recipe("beans cups = 0\npourout 12 / cups\n");
+        let mut editor = EditorEnd::connect();
+        editor.request("initialize", json!({}));
+        editor.request("launch", json!({ "program": program }));
+        editor.send("configurationDone", json!({}));
+        let mut poured = Vec::new();
+        let exited = editor.wait_for("exited", &mut poured);
+        assert_eq!(exited["body"]["exitCode"], 1);
+        let spill = poured.iter().find(|message| message["event"] == "output").unwrap();
+        assert_eq!(spill["body"]["category"], "stderr");
+        assert!(spill["body"]["output"].as_str().unwrap().contains("line 2"));
+    }
+
+    #[test]
+    fn breakpoints_are_replaced_not_added_to() {
+        let mut session = BrewDebugSession::new_session();
+        set_breakpoints(&mut session, &json!({ "arguments": { "breakpoints": [{ "line": 3 }, { "line": 7 }] } }));
+        let body = set_breakpoints(&mut session, &json!({ "arguments": { "breakpoints": [{ "line": 5 }] } }));
+        assert_eq!(body["breakpoints"].as_array().unwrap().len(), 1);
+        assert!(session.breakpoints.contains(&5) && !session.breakpoints.contains(&3));
+    }
+}
@@ -1,16 +1,17 @@
 // src/barista_language_server.rs - The Barista Language Server & Coffee Shop Assistant ☕
 
 use serde::{Serialize, Deserialize};
+use serde_json::{json, Value as JsonValue};
 use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
 use crate::espresso_errors::{CoffeeSpillReport, SpillType};
-use crate::{lexer, parser, ast};
+use crate::lexer::{CoffeeTokenStream, SpannedToken, Token};
+use crate::{ast, brew_linter, brewco_codegen, parser};
+use crate::type_checker::TypeChecker;
 
 /// The Barista Language Server - provides intelligent coffee brewing assistance
 pub struct BaristaLanguageServer {
     coffee_workspace: CoffeeWorkspace,
-    brewing_diagnostics: Vec<CoffeeBrewingDiagnostic>,
-    autocomplete_suggestions: HashMap<String, Vec<CoffeeSuggestion>>,
-    hover_info_cache: HashMap<String, CoffeeHoverInfo>,
     coffee_shop_settings: CoffeeShopSettings,
 }
 
@@ -18,6 +19,7 @@ pub struct BaristaLanguageServer {
 #[derive(Clone)]
 pub struct CoffeeWorkspace {
     pub roastery_root: String,
+    pub client_root: Option<String>, // the folder the editor opened (initialize's rootUri); rename stays inside it
     pub open_coffee_files: HashMap<String, OpenCoffeeFile>,
     pub brewing_configuration: BrewingConfiguration,
 }
@@ -29,6 +31,7 @@ pub struct OpenCoffeeFile {
     pub coffee_content: String,
     pub brewing_version: u64,
     pub parsed_coffee_ast: Option<Vec<ast::Statement>>,
+    pub statement_chunks: Option<Vec<parser::ParsedChunk>>, // per top-level statement, reused across edits
     pub brewing_errors: Vec<CoffeeBrewingDiagnostic>,
     pub last_sip_time: std::time::SystemTime, // last modification time
 }
@@ -66,9 +69,22 @@ pub struct CoffeeShopSettings {
     pub show_coffee_emoji_hints: bool,
     pub auto_complete_coffee_terms: bool,
     pub highlight_coffee_syntax: bool,
+    pub match_coffee_brackets: bool,     // Light up the partner of the bracket under the cursor
     pub barista_wisdom_level: BaristaWisdomLevel,
 }
 
+impl Default for CoffeeShopSettings {
+    fn default() -> Self {
+        CoffeeShopSettings {
+            show_coffee_emoji_hints: true,
+            auto_complete_coffee_terms: true,
+            highlight_coffee_syntax: true,
+            match_coffee_brackets: true,
+            barista_wisdom_level: BaristaWisdomLevel::ExperiencedBarista,
+        }
+    }
+}
+
 /// Barista wisdom levels
 #[derive(Clone)]
 pub enum BaristaWisdomLevel {
@@ -126,7 +142,7 @@ pub struct CoffeeSuggestion {
 }
 
 /// Coffee suggestion kinds
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub enum CoffeeSuggestionKind {
     CoffeeKeyword,      // Language keywords
     BeanVariable,       // Variables
@@ -138,6 +154,24 @@ pub enum CoffeeSuggestionKind {
     CoffeeEmoji,        // Coffee emojis
 }
 
+/// One highlighted span for `textDocument/semanticTokens`, on a single 0-based line
+/// with UTF-16 columns (🎀 counts as two)
+#[derive(Clone)]
+pub struct CoffeeSemanticToken {
+    pub line: u32,
+    pub start_column: u32,
+    pub length: u32,
+    pub token_type: u32,     // index into COFFEE_SEMANTIC_TOKEN_TYPES
+    pub token_modifiers: u32, // bit set over COFFEE_SEMANTIC_TOKEN_MODIFIERS
+}
+
+/// A replacement for one range of a coffee file
+#[derive(Clone)]
+pub struct CoffeeTextEdit {
+    pub edit_range: CoffeeRange,
+    pub new_text: String,
+}
+
 /// Coffee hover information
 #[derive(Clone)]
 pub struct CoffeeHoverInfo {
@@ -147,18 +181,698 @@ pub struct CoffeeHoverInfo {
     pub barista_tips: Vec<String>,
 }
 
+/// Coffee keywords with what they brew, for completions and hovers
+const COFFEE_KEYWORD_NOTES: &[(&str, &str)] = &[
+    ("beans", "Declare a coffee bean variable"),
+    ("brew", "Define a brewing function"),
+    ("taste", "Conditional brewing (if statement)"),
+    ("otherwise", "Alternative brewing (else statement)"),
+    ("steep", "Brewing loop (while statement)"),
+    ("pour", "Pouring loop (for statement)"),
+    ("pourout", "Display coffee output"),
+    ("pourout_flat", "Display coffee output without ending the line"),
+    ("spill_out", "Display coffee output on stderr"),
+    ("bean", "Define a coffee bean class"),
+    ("coffee_recipe", "Define a coffee recipe interface"),
+    ("new", "Create a new coffee bean instance"),
+    ("this", "Reference to current coffee bean"),
+    ("super", "Reference to parent coffee bean"),
+];
+
+/// Coffee operators with their everyday spelling
+const COFFEE_OPERATOR_NOTES: &[(&str, &str)] = &[
+    ("pour_in", "Assignment operator (=)"),
+    ("add", "Addition operator (+)"),
+    ("sip", "Subtraction operator (-)"),
+    ("same_blend", "Equality comparison (==)"),
+    ("different_blend", "Inequality comparison (!=)"),
+    ("more_caffeine", "Greater than (>)"),
+    ("less_caffeine", "Less than (<)"),
+];
+
+/// Semantic token legend advertised to editors; order matters
+const COFFEE_SEMANTIC_TOKEN_TYPES: &[&str] = &[
+    "keyword", "operator", "string", "number", "comment",
+    "variable", "parameter", "property", "method", "function", "class", "interface",
+];
+const COFFEE_SEMANTIC_TOKEN_MODIFIERS: &[&str] = &["declaration"];
+const DECLARATION_MODIFIER: u32 = 1;
+
+fn semantic_type(name: &str) -> u32 {
+    COFFEE_SEMANTIC_TOKEN_TYPES.iter().position(|t| *t == name).unwrap_or(0) as u32
+}
+
+/// Classify every token of a coffee file straight from the lexer, comments included
+fn brewed_semantic_tokens(content: &str) -> Vec<CoffeeSemanticToken> {
+    let declarations = brewed_declarations(content);
+    let is_declared = |name: &str, kind: fn(&CoffeeSuggestionKind) -> bool| {
+        declarations.iter().any(|d| d.name == name && kind(&d.kind))
+    };
+    let source_lines: Vec<Vec<char>> = content.lines().map(|line| line.chars().collect()).collect();
+    let tokens: Vec<_> = CoffeeTokenStream::with_comments(content)
+        .filter(|spanned| spanned.token != Token::Newline)
+        .collect();
+
+    let mut highlighted = Vec::new();
+    let mut param_depth = 0usize; // > 0 while inside a brew's parameter list
+    for (i, spanned) in tokens.iter().enumerate() {
+        let previous = i.checked_sub(1).map(|p| &tokens[p].token);
+        let next = tokens.get(i + 1).map(|n| &n.token);
+        let classified = match &spanned.token {
+            Token::Comment(_) => Some(("comment", 0)),
+            Token::String(_) => Some(("string", 0)),
+            Token::Number(_) => Some(("number", 0)),
+            // The parser recognises these by name rather than as lexer keywords
+            Token::Identifier(name) if matches!(name.as_str(), "true" | "false" | "pourout" | "pourout_flat" | "spill_out") => Some(("keyword", 0)),
+            Token::Identifier(name) => Some(match previous {
+                Some(Token::Brew) => ("function", DECLARATION_MODIFIER),
+                Some(Token::Bean) | Some(Token::Blend) => ("class", DECLARATION_MODIFIER),
+                Some(Token::CoffeeRecipe) => ("interface", DECLARATION_MODIFIER),
+                Some(Token::Beans) => ("variable", DECLARATION_MODIFIER),
+                Some(Token::LParen) | Some(Token::Comma) if param_depth > 0 => ("parameter", DECLARATION_MODIFIER),
+                Some(Token::Dot) if next == Some(&Token::LParen) => ("method", 0),
+                Some(Token::Dot) => ("property", 0),
+                _ if is_declared(name, |k| matches!(k, CoffeeSuggestionKind::CoffeeClass)) => ("class", 0),
+                _ if is_declared(name, |k| matches!(k, CoffeeSuggestionKind::CoffeeInterface)) => ("interface", 0),
+                _ if next == Some(&Token::LParen) => ("function", 0),
+                _ => ("variable", 0),
+            }),
+            Token::Beans | Token::Bean | Token::Brew | Token::Blend | Token::Taste | Token::Otherwise
+            | Token::Steep | Token::Pour | Token::Roast | Token::Serve | Token::Break | Token::Continue
+            | Token::This | Token::Super | Token::Private | Token::Public | Token::CoffeeRecipe | Token::New
+            | Token::BrewTime | Token::Foreach | Token::TasteCarefully | Token::IfSpilled | Token::RefillWith
+            | Token::Grind | Token::In | Token::BenchBrew | Token::TasteTest | Token::ServeBack | Token::YieldSip
+            | Token::Cup | Token::CoffeeChain | Token::CoffeeMenu => Some(("keyword", 0)),
+            Token::LParen | Token::RParen | Token::LBrace | Token::RBrace | Token::LBracket | Token::RBracket
+            | Token::Comma | Token::Dot | Token::Colon | Token::Semicolon | Token::Arrow | Token::Newline => None,
+            _ => Some(("operator", 0)), // themed operators like same_blend, and their symbol spellings
+        };
+
+        // Track the parameter list of `brew name(...)`
+        match &spanned.token {
+            Token::LParen if param_depth > 0 => param_depth += 1,
+            Token::LParen if i >= 2 && tokens[i - 2].token == Token::Brew => param_depth = 1,
+            Token::RParen if param_depth > 0 => param_depth -= 1,
+            _ => {}
+        }
+
+        let Some((token_type, token_modifiers)) = classified else { continue };
+        // Editors want one span per line, so multi-line strings are split
+        for line in spanned.line..=spanned.end_line {
+            let Some(line_chars) = source_lines.get(line - 1) else { break };
+            let start = if line == spanned.line { spanned.column - 1 } else { 0 };
+            let end = if line == spanned.end_line { spanned.end_column - 1 } else { line_chars.len() };
+            let (start, end) = (start.min(line_chars.len()), end.min(line_chars.len()));
+            if start >= end {
+                continue;
+            }
+            highlighted.push(CoffeeSemanticToken {
+                line: (line - 1) as u32,
+                start_column: utf16_width(&line_chars[..start]),
+                length: utf16_width(&line_chars[start..end]),
+                token_type: semantic_type(token_type),
+                token_modifiers,
+            });
+        }
+    }
+    highlighted
+}
+
+fn utf16_width(chars: &[char]) -> u32 {
+    chars.iter().map(|c| c.len_utf16() as u32).sum()
+}
+
+/// Snippet completions, with ${n:placeholders}
+const COFFEE_SNIPPETS: &[(&str, &str)] = &[
+    ("coffee_class", "bean ${1:CoffeeClass} {\n    beans ${2:property} pour_in ${3:value}\n    \n    brew ${4:method}() {\n        ${5:// brewing logic}\n    }\n}"),
+    ("coffee_function", "brew ${1:function_name}(${2:parameters}) {\n    ${3:// brewing logic}\n    return ${4:result}\n}"),
+    ("coffee_loop", "steep ${1:condition} {\n    ${2:// brewing process}\n}"),
+    ("coffee_if", "taste ${1:condition} {\n    ${2:// perfect brew}\n} otherwise {\n    ${3:// needs more work}\n}"),
+];
+
+/// A name declared in a coffee file
+#[derive(Clone)]
+struct CoffeeDeclaration {
+    name: String,
+    kind: CoffeeSuggestionKind,
+    signature: String,
+    line: usize, // 1-based
+    params: Vec<String>, // brews only
+}
+
+impl CoffeeDeclaration {
+    fn named(name: &str, kind: CoffeeSuggestionKind, keyword: &str, line: usize) -> Self {
+        CoffeeDeclaration { name: name.to_string(), kind, signature: format!("{} {}", keyword, name), line, params: Vec::new() }
+    }
+
+    fn variable(name: &str, line: usize) -> Self {
+        CoffeeDeclaration::named(name, CoffeeSuggestionKind::BeanVariable, "beans", line)
+    }
+}
+
+/// Find every `beans`, `brew`, `bean` and `coffee_recipe` declaration by scanning tokens,
+/// so files that don't fully parse still get completions and hovers
+fn brewed_declarations(content: &str) -> Vec<CoffeeDeclaration> {
+    let tokens: Vec<_> = CoffeeTokenStream::new(content).collect();
+    let mut declarations = Vec::new();
+    for (i, spanned) in tokens.iter().enumerate() {
+        let Some(Token::Identifier(name)) = tokens.get(i + 1).map(|next| &next.token) else { continue };
+        let (kind, signature) = match spanned.token {
+            Token::Beans => (CoffeeSuggestionKind::BeanVariable, format!("beans {}", name)),
+            Token::Bean => (CoffeeSuggestionKind::CoffeeClass, format!("bean {}", name)),
+            Token::CoffeeRecipe => (CoffeeSuggestionKind::CoffeeInterface, format!("coffee_recipe {}", name)),
+            Token::Brew => {
+                let params: Vec<String> = tokens[i + 2..].iter()
+                    .take_while(|param| param.token != Token::RParen)
+                    .filter_map(|param| match &param.token {
+                        Token::Identifier(param_name) => Some(param_name.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                let signature = format!("brew {}({})", name, params.join(", "));
+                declarations.push(CoffeeDeclaration { name: name.clone(), kind: CoffeeSuggestionKind::BrewingFunction, signature, line: spanned.line, params });
+                continue;
+            }
+            _ => continue,
+        };
+        declarations.push(CoffeeDeclaration { name: name.clone(), kind, signature, line: spanned.line, params: Vec::new() });
+    }
+    declarations
+}
+
+/// Keywords, operators and snippets, good anywhere a statement or expression can go
+fn coffee_keyword_suggestions() -> Vec<CoffeeSuggestion> {
+    let mut suggestions = Vec::new();
+    for &(keyword, description) in COFFEE_KEYWORD_NOTES {
+        suggestions.push(CoffeeSuggestion {
+            suggestion_text: keyword.to_string(),
+            brewing_kind: CoffeeSuggestionKind::CoffeeKeyword,
+            detailed_info: description.to_string(),
+            coffee_snippet: None,
+            barista_rating: 5.0,
+        });
+    }
+    for &(operator, description) in COFFEE_OPERATOR_NOTES {
+        suggestions.push(CoffeeSuggestion {
+            suggestion_text: operator.to_string(),
+            brewing_kind: CoffeeSuggestionKind::CoffeeKeyword,
+            detailed_info: description.to_string(),
+            coffee_snippet: None,
+            barista_rating: 4.5,
+        });
+    }
+    for &(name, snippet) in COFFEE_SNIPPETS {
+        suggestions.push(CoffeeSuggestion {
+            suggestion_text: name.to_string(),
+            brewing_kind: CoffeeSuggestionKind::CoffeeSnippet,
+            detailed_info: format!("Coffee code snippet: {}", name),
+            coffee_snippet: Some(snippet.to_string()),
+            barista_rating: 4.8,
+        });
+    }
+    suggestions
+}
+
+/// A brew call with its parameters as snippet placeholders, so the editor hints each argument
+fn brew_suggestion(name: &str, params: &[ast::ParamDecl], detail: String) -> CoffeeSuggestion {
+    let placeholders: Vec<String> = params.iter().enumerate()
+        .map(|(i, param)| format!("${{{}:{}}}", i + 1, param.name))
+        .collect();
+    let signature: Vec<&str> = params.iter().map(|param| param.name.as_str()).collect();
+    CoffeeSuggestion {
+        suggestion_text: name.to_string(),
+        brewing_kind: CoffeeSuggestionKind::BrewingFunction,
+        detailed_info: format!("brew {}({}) - {}", name, signature.join(", "), detail),
+        coffee_snippet: Some(format!("{}({})", name, placeholders.join(", "))),
+        barista_rating: 5.0,
+    }
+}
+
+fn declared_suggestion(declaration: CoffeeDeclaration) -> CoffeeSuggestion {
+    let coffee_snippet = match declaration.kind {
+        CoffeeSuggestionKind::BrewingFunction => {
+            let params = declaration.params.iter().enumerate()
+                .map(|(i, param)| format!("${{{}:{}}}", i + 1, param))
+                .collect::<Vec<_>>();
+            Some(format!("{}({})", declaration.name, params.join(", ")))
+        }
+        _ => None,
+    };
+    CoffeeSuggestion {
+        suggestion_text: declaration.name,
+        brewing_kind: declaration.kind,
+        detailed_info: declaration.signature,
+        coffee_snippet,
+        barista_rating: 5.0,
+    }
+}
+
+/// Fields and methods of a bean and the beans it blends from
+fn bean_member_suggestions(program: &[ast::Statement], bean_name: &str) -> Vec<CoffeeSuggestion> {
+    let mut suggestions: Vec<CoffeeSuggestion> = Vec::new();
+    let mut next_bean = Some(bean_name.to_string());
+    while let Some(current) = next_bean.take() {
+        let Some((parent, fields, methods)) = program.iter().find_map(|statement| match statement {
+            ast::Statement::BeanDecl { name, parent, fields, methods, .. } if *name == current => Some((parent, fields, methods)),
+            _ => None,
+        }) else { break };
+        for field in fields {
+            if suggestions.iter().all(|s| s.suggestion_text != field.name) {
+                suggestions.push(CoffeeSuggestion {
+                    suggestion_text: field.name.clone(),
+                    brewing_kind: CoffeeSuggestionKind::BeanVariable,
+                    detailed_info: format!("field of {}", current),
+                    coffee_snippet: None,
+                    barista_rating: 5.0,
+                });
+            }
+        }
+        for method in methods {
+            if let ast::Statement::BrewDecl { name, params, .. } = method {
+                if name != "init" && suggestions.iter().all(|s| s.suggestion_text != *name) {
+                    suggestions.push(brew_suggestion(name, params, format!("method of {}", current)));
+                }
+            }
+        }
+        // Guard against a bean that blends from itself
+        next_bean = parent.clone().filter(|parent| parent != bean_name);
+    }
+    suggestions
+}
+
+/// Where a receiver's value came from, as far as the tokens tell
+enum CoffeeReceiver {
+    Bean(String),
+    Module(String),
+}
+
+/// Find `name = new Bean(...)`, `name: Bean = ...` or `name = grind "path"` anywhere in the file
+fn coffee_receiver_origin(tokens: &[SpannedToken], name: &str) -> Option<CoffeeReceiver> {
+    let tokens: Vec<&Token> = tokens.iter().map(|spanned| &spanned.token).collect();
+    tokens.windows(4).find_map(|window| match window {
+        [Token::Identifier(n), Token::Equals | Token::PourIn, Token::New, Token::Identifier(bean)] if n == name => Some(CoffeeReceiver::Bean(bean.clone())),
+        [Token::Identifier(n), Token::Equals | Token::PourIn, Token::Grind, Token::String(path)] if n == name => Some(CoffeeReceiver::Module(path.clone())),
+        [Token::Identifier(n), Token::Colon, Token::Identifier(bean), _] if n == name && bean.starts_with(char::is_uppercase) => Some(CoffeeReceiver::Bean(bean.clone())),
+        _ => None,
+    })
+}
+
+/// Names visible before token `cursor`: the locals of every enclosing block plus globals,
+/// with brews, beans and recipes declared anywhere at the top level (they are hoisted).
+/// Also reports the bean whose body encloses the cursor, for `this.`
+fn scoped_coffee_names(tokens: &[SpannedToken], cursor: usize) -> (Vec<CoffeeDeclaration>, Option<String>) {
+    struct OpenBlock {
+        names: Vec<CoffeeDeclaration>,
+        bean: Option<String>,
+        is_bean_body: bool,
+    }
+    let mut globals: Vec<CoffeeDeclaration> = Vec::new();
+    let mut blocks: Vec<OpenBlock> = Vec::new();
+    let mut pending: Vec<CoffeeDeclaration> = Vec::new(); // parameters and loop beans waiting for their `{`
+    let mut pending_bean: Option<String> = None;
+    let mut in_header = false;
+    let mut param_depth = 0usize;
+    let mut snapshot = None;
+
+    for (i, spanned) in tokens.iter().enumerate() {
+        if i == cursor {
+            let scopes: Vec<&[CoffeeDeclaration]> = blocks.iter().map(|b| b.names.as_slice()).collect();
+            snapshot = Some((visible_names(&globals, &scopes), blocks.last().and_then(|b| b.bean.clone())));
+        }
+        let before_cursor = i < cursor;
+        let next = tokens.get(i + 1).map(|t| &t.token);
+        let declare = |declaration: CoffeeDeclaration, globals: &mut Vec<CoffeeDeclaration>, blocks: &mut Vec<OpenBlock>| {
+            match blocks.last_mut() {
+                Some(block) => block.names.push(declaration),
+                None => globals.push(declaration),
+            }
+        };
+        match (&spanned.token, next) {
+            (Token::Beans, Some(Token::Identifier(name))) if before_cursor => {
+                let declaration = CoffeeDeclaration::variable(name, spanned.line);
+                let is_field = blocks.last().is_some_and(|b| b.is_bean_body);
+                if in_header {
+                    pending.push(declaration);
+                } else if !is_field {
+                    // Fields are only reachable through `this.`
+                    declare(declaration, &mut globals, &mut blocks);
+                }
+            }
+            (Token::Brew, Some(Token::Identifier(name))) => {
+                let params: Vec<String> = tokens[i + 2..].iter()
+                    .skip(1)
+                    .take_while(|t| t.token != Token::RParen)
+                    .zip(tokens[i + 2..].iter())
+                    .filter_map(|(t, before)| match (&t.token, &before.token) {
+                        (Token::Identifier(param), Token::LParen | Token::Comma) => Some(param.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                let in_bean_body = blocks.last().is_some_and(|b| b.is_bean_body);
+                if (before_cursor || blocks.is_empty()) && !in_bean_body {
+                    let signature = format!("brew {}({})", name, params.join(", "));
+                    declare(CoffeeDeclaration { name: name.clone(), kind: CoffeeSuggestionKind::BrewingFunction, signature, line: spanned.line, params }, &mut globals, &mut blocks);
+                }
+                in_header = true;
+            }
+            (Token::Bean, Some(Token::Identifier(name))) if before_cursor || blocks.is_empty() => {
+                declare(CoffeeDeclaration::named(name, CoffeeSuggestionKind::CoffeeClass, "bean", spanned.line), &mut globals, &mut blocks);
+                pending_bean = Some(name.clone());
+                in_header = true;
+            }
+            (Token::CoffeeRecipe, Some(Token::Identifier(name))) if before_cursor || blocks.is_empty() => {
+                declare(CoffeeDeclaration::named(name, CoffeeSuggestionKind::CoffeeInterface, "recipe", spanned.line), &mut globals, &mut blocks);
+            }
+            (Token::Pour | Token::Foreach | Token::IfSpilled, _) => in_header = true,
+            (Token::Identifier(name), _) if in_header && before_cursor => {
+                let previous = i.checked_sub(1).map(|p| &tokens[p].token);
+                let binds = match previous {
+                    Some(Token::LParen) => param_depth == 1,
+                    // The second name in `pour item, i in cup`
+                    Some(Token::Comma) if param_depth == 0 => {
+                        i >= 3 && matches!(tokens[i - 3].token, Token::Pour | Token::Foreach)
+                    }
+                    Some(Token::Comma) => param_depth == 1,
+                    Some(Token::Pour) | Some(Token::Foreach) => true,
+                    _ => false,
+                };
+                if binds {
+                    pending.push(CoffeeDeclaration::variable(name, spanned.line));
+                }
+            }
+            (Token::LParen, _) => param_depth += 1,
+            (Token::RParen, _) => param_depth = param_depth.saturating_sub(1),
+            (Token::LBrace, _) => {
+                let is_bean_body = pending_bean.is_some();
+                let bean = pending_bean.take().or_else(|| blocks.last().and_then(|b| b.bean.clone()));
+                blocks.push(OpenBlock { names: std::mem::take(&mut pending), bean, is_bean_body });
+                in_header = false;
+            }
+            (Token::RBrace, _) => {
+                blocks.pop();
+            }
+            (Token::Newline, next) if in_header && param_depth == 0 && next != Some(&Token::LBrace) => {
+                // A header with no body, like a brew signature inside a recipe
+                pending.clear();
+                pending_bean = None;
+                in_header = false;
+            }
+            _ => {}
+        }
+    }
+
+    let (mut names, bean) = snapshot.unwrap_or_else(|| {
+        let scopes: Vec<&[CoffeeDeclaration]> = blocks.iter().map(|b| b.names.as_slice()).collect();
+        (visible_names(&globals, &scopes), blocks.last().and_then(|b| b.bean.clone()))
+    });
+    // Hoisted top-level brews, beans and recipes declared after the cursor
+    for declaration in globals {
+        if declaration.kind != CoffeeSuggestionKind::BeanVariable && names.iter().all(|n| n.name != declaration.name) {
+            names.push(declaration);
+        }
+    }
+    (names, bean)
+}
+
+/// Innermost declaration of each name wins; `blocks` run outermost first
+fn visible_names(globals: &[CoffeeDeclaration], blocks: &[&[CoffeeDeclaration]]) -> Vec<CoffeeDeclaration> {
+    let mut visible: Vec<CoffeeDeclaration> = Vec::new();
+    for scope in blocks.iter().rev().chain(std::iter::once(&globals)) {
+        for declaration in scope.iter().rev() {
+            if visible.iter().all(|v| v.name != declaration.name) {
+                visible.push(declaration.clone());
+            }
+        }
+    }
+    visible
+}
+
+/// The source text before a 0-based line and column
+fn coffee_prefix(content: &str, line: u32, column: u32) -> String {
+    let mut prefix = String::new();
+    for (index, text) in content.split('\n').enumerate() {
+        if index < line as usize {
+            prefix.push_str(text);
+            prefix.push('\n');
+        } else {
+            prefix.extend(text.chars().take(column as usize));
+            break;
+        }
+    }
+    prefix
+}
+
+/// Byte offset of a 0-based line and UTF-16 column, clamped to the content
+fn utf16_position_to_offset(content: &str, line: u32, column: u32) -> usize {
+    let mut line_start = 0;
+    for _ in 0..line {
+        match content[line_start..].find('\n') {
+            Some(newline) => line_start += newline + 1,
+            None => return content.len(),
+        }
+    }
+    let mut units = 0;
+    for (offset, c) in content[line_start..].char_indices() {
+        if units >= column as usize || c == '\n' {
+            return line_start + offset;
+        }
+        units += c.len_utf16();
+    }
+    content.len()
+}
+
+/// Re-parse the statement windows an edit touched. The edit replaced old lines
+/// `edit_first_line..=old_last_line` with `edit_first_line..=new_last_line`; parsing
+/// restarts at the chunk holding the edit and stops as soon as a new chunk lines up
+/// with an old one past the edit, which is then reused (shifted) along with the rest.
+fn reparse_edited_chunks(chunks: &mut Vec<parser::ParsedChunk>, content: &str, edit_first_line: usize, old_last_line: usize, new_last_line: usize) {
+    let delta = new_last_line as isize - old_last_line as isize;
+    // Edits past the last chunk reparse it too: it may have run into the end of the file unclosed
+    let first_touched = chunks.iter().position(|chunk| chunk.last_line >= edit_first_line)
+        .unwrap_or(chunks.len().saturating_sub(1));
+    let start_line = chunks.get(first_touched).map_or(1, |chunk| chunk.first_line).min(edit_first_line);
+    let mut reusable = chunks.drain(first_touched..)
+        .filter(|chunk| chunk.first_line > old_last_line)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .peekable();
+
+    let start_offset = utf16_position_to_offset(content, (start_line - 1) as u32, 0);
+    for chunk in parser::CoffeeChunkParser::new(&content[start_offset..], start_line) {
+        if chunk.first_line > new_last_line {
+            while reusable.peek().is_some_and(|old| (old.first_line as isize + delta) < chunk.first_line as isize) {
+                reusable.next();
+            }
+            if reusable.peek().is_some_and(|old| old.first_line as isize + delta == chunk.first_line as isize) {
+                for mut old in reusable {
+                    old.shift_lines(delta);
+                    chunks.push(old);
+                }
+                return;
+            }
+        }
+        chunks.push(chunk);
+    }
+}
+
+/// What renaming an identifier has to agree with
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum CoffeeBinding {
+    Local(usize), // declared inside a brew or block, by the token at this index
+    Global,       // declared at the top level, or not in this file at all (another file's, a module's)
+    Member,       // a field or method: after `.`, an object key, or declared in a bean body
+}
+
+/// A name in a coffee file with its 0-based range and what it binds to
+struct CoffeeSymbol {
+    name: String,
+    range: CoffeeRange,
+    binding: CoffeeBinding,
+}
+
+/// Every identifier (and string object key) with the declaration it resolves to, walking the
+/// blocks the way `scoped_coffee_names` does: parameters and loop beans belong to the block
+/// after their header, and a `beans` declaration is only seen from the statement after it
+fn coffee_symbols(content: &str) -> Vec<CoffeeSymbol> {
+    struct OpenBlock {
+        names: Vec<(String, usize)>,
+        is_bean_body: bool,
+    }
+    #[derive(PartialEq)]
+    enum Bracket {
+        Paren,
+        Object,
+        Block,
+    }
+    let tokens: Vec<SpannedToken> = CoffeeTokenStream::new(content).collect();
+    let previous = |i: usize| tokens[..i].iter().rev().map(|t| &t.token).find(|t| **t != Token::Newline);
+    let following = |i: usize| tokens[i + 1..].iter().map(|t| &t.token).find(|t| **t != Token::Newline);
+    let range_of = |spanned: &SpannedToken, skip: u32, name: &str| {
+        let (line, column) = ((spanned.line - 1) as u32, (spanned.column - 1) as u32 + skip);
+        CoffeeRange { start_line: line, start_column: column, end_line: line, end_column: column + name.chars().count() as u32 }
+    };
+
+    let mut symbols = Vec::new();
+    let mut blocks: Vec<OpenBlock> = Vec::new();
+    let mut brackets: Vec<Bracket> = Vec::new();
+    let mut pending: Vec<(String, usize)> = Vec::new(); // parameters and loop beans waiting for their `{`
+    let mut declared: Vec<(String, usize, usize)> = Vec::new(); // `beans` seen once their statement ends
+    let mut pending_bean = false;
+    let mut in_header = false;
+    let mut header_brackets = 0;
+
+    for (i, spanned) in tokens.iter().enumerate() {
+        let in_bean_body = blocks.last().is_some_and(|b| b.is_bean_body);
+        match &spanned.token {
+            Token::Identifier(name) => {
+                let before = previous(i);
+                let is_key = brackets.last() == Some(&Bracket::Object)
+                    && matches!(before, Some(Token::LBrace | Token::Comma))
+                    && following(i) == Some(&Token::Colon);
+                let binding = if i > 0 && tokens[i - 1].token == Token::Dot {
+                    // Members of a ground module are its globals
+                    let receiver = i.checked_sub(2).map(|r| &tokens[r].token);
+                    match receiver {
+                        Some(Token::Identifier(module)) if matches!(coffee_receiver_origin(&tokens, module), Some(CoffeeReceiver::Module(_))) => CoffeeBinding::Global,
+                        _ => CoffeeBinding::Member,
+                    }
+                } else if is_key {
+                    CoffeeBinding::Member
+                } else if matches!(before, Some(Token::Beans | Token::Brew)) && in_bean_body {
+                    if before == Some(&Token::Brew) {
+                        in_header = true;
+                        header_brackets = brackets.len();
+                    }
+                    CoffeeBinding::Member
+                } else if before == Some(&Token::Beans) && in_header {
+                    pending.push((name.clone(), i));
+                    CoffeeBinding::Local(i)
+                } else if before == Some(&Token::Beans) {
+                    if blocks.is_empty() {
+                        CoffeeBinding::Global
+                    } else {
+                        declared.push((name.clone(), i, brackets.len()));
+                        CoffeeBinding::Local(i)
+                    }
+                } else if matches!(before, Some(Token::Brew | Token::Bean | Token::CoffeeRecipe)) {
+                    let binding = match blocks.last_mut() {
+                        Some(block) => {
+                            block.names.push((name.clone(), i));
+                            CoffeeBinding::Local(i)
+                        }
+                        None => CoffeeBinding::Global,
+                    };
+                    if before != Some(&Token::CoffeeRecipe) {
+                        pending_bean = before == Some(&Token::Bean);
+                        in_header = true;
+                        header_brackets = brackets.len();
+                    }
+                    binding
+                } else {
+                    let depth = brackets.len() - header_brackets.min(brackets.len());
+                    let binds = in_header && match before {
+                        Some(Token::LParen) => depth == 1,
+                        // The second name in `pour item, i in cup`
+                        Some(Token::Comma) if depth == 0 => i >= 3 && matches!(tokens[i - 3].token, Token::Pour | Token::Foreach),
+                        Some(Token::Comma) => depth == 1,
+                        Some(Token::Pour | Token::Foreach) => true,
+                        _ => false,
+                    };
+                    if binds {
+                        pending.push((name.clone(), i));
+                        CoffeeBinding::Local(i)
+                    } else {
+                        blocks.iter().rev()
+                            .find_map(|block| block.names.iter().rev().find(|(declared, _)| declared == name))
+                            .map_or(CoffeeBinding::Global, |&(_, at)| CoffeeBinding::Local(at))
+                    }
+                };
+                symbols.push(CoffeeSymbol { name: name.clone(), range: range_of(spanned, 0, name), binding });
+            }
+            Token::String(key) if brackets.last() == Some(&Bracket::Object)
+                && matches!(previous(i), Some(Token::LBrace | Token::Comma))
+                && following(i) == Some(&Token::Colon) =>
+            {
+                symbols.push(CoffeeSymbol { name: key.clone(), range: range_of(spanned, 1, key), binding: CoffeeBinding::Member });
+            }
+            Token::Pour | Token::Foreach | Token::IfSpilled => {
+                in_header = true;
+                header_brackets = brackets.len();
+            }
+            Token::LParen | Token::LBracket => brackets.push(Bracket::Paren),
+            Token::RParen | Token::RBracket => {
+                brackets.pop();
+            }
+            Token::LBrace => {
+                let is_object = matches!(
+                    previous(i),
+                    Some(Token::Equals | Token::PourIn | Token::Comma | Token::LParen | Token::LBracket | Token::Colon
+                        | Token::Serve | Token::ServeBack | Token::YieldSip)
+                ) || matches!(previous(i), Some(Token::Identifier(word)) if word.starts_with("pourout") || word == "spill_out");
+                if is_object {
+                    brackets.push(Bracket::Object);
+                } else {
+                    blocks.push(OpenBlock { names: std::mem::take(&mut pending), is_bean_body: pending_bean });
+                    brackets.push(Bracket::Block);
+                    pending_bean = false;
+                    in_header = false;
+                }
+            }
+            Token::RBrace => {
+                declared.retain(|(_, _, level)| *level < brackets.len());
+                if brackets.pop() == Some(Bracket::Block) {
+                    blocks.pop();
+                }
+            }
+            Token::Newline | Token::Semicolon => {
+                if in_header && brackets.len() == header_brackets && following(i) != Some(&Token::LBrace) {
+                    // A header with no body, like a brew signature inside a recipe
+                    pending.clear();
+                    pending_bean = false;
+                    in_header = false;
+                }
+                let (ready, waiting): (Vec<_>, Vec<_>) = declared.drain(..).partition(|(_, _, level)| *level >= brackets.len());
+                declared = waiting;
+                if let Some(block) = blocks.last_mut() {
+                    block.names.extend(ready.into_iter().map(|(name, at, _)| (name, at)));
+                }
+            }
+            _ => {}
+        }
+    }
+    symbols
+}
+
+/// The name under a 0-based line and column (keywords don't count)
+fn coffee_symbol_at(symbols: &[CoffeeSymbol], line: u32, column: u32) -> Option<&CoffeeSymbol> {
+    symbols.iter().find(|symbol| symbol.range.start_line == line && symbol.range.start_column <= column && column <= symbol.range.end_column)
+}
+
+/// `file://` URI for a path on disk, the way editors name documents
+fn coffee_file_uri(path: &std::path::Path) -> String {
+    let absolute = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    format!("file://{}", absolute.display())
+}
+
+/// The identifier or keyword under a 0-based line and column
+fn coffee_word_at(content: &str, line: u32, column: u32) -> Option<String> {
+    let chars: Vec<char> = content.lines().nth(line as usize)?.chars().collect();
+    let is_word = |c: &char| c.is_alphanumeric() || *c == '_';
+    let mut start = (column as usize).min(chars.len());
+    while start > 0 && is_word(&chars[start - 1]) {
+        start -= 1;
+    }
+    let word: String = chars[start..].iter().take_while(|c| is_word(c)).collect();
+    if word.is_empty() { None } else { Some(word) }
+}
+
 impl BaristaLanguageServer {
     pub fn new_coffee_shop_server() -> Self {
-        let default_settings = CoffeeShopSettings {
-            show_coffee_emoji_hints: true,
-            auto_complete_coffee_terms: true,
-            highlight_coffee_syntax: true,
-            barista_wisdom_level: BaristaWisdomLevel::ExperiencedBarista,
-        };
+        let default_settings = CoffeeShopSettings::default();
         
         let brewing_config = BrewingConfiguration {
             auto_brew_on_save: true,
-            strict_coffee_type_checking: true,
+            strict_coffee_type_checking: false, // same as a plain `brew`, without --strict-brew
             preferred_brewing_style: BrewingStyle::EspressoShot,
             coffee_linting_rules: CoffeeLintingRules {
                 enforce_coffee_naming: true,
@@ -171,25 +885,23 @@ impl BaristaLanguageServer {
         BaristaLanguageServer {
             coffee_workspace: CoffeeWorkspace {
                 roastery_root: ".".to_string(),
+                client_root: None,
                 open_coffee_files: HashMap::new(),
                 brewing_configuration: brewing_config,
             },
-            brewing_diagnostics: Vec::new(),
-            autocomplete_suggestions: HashMap::new(),
-            hover_info_cache: HashMap::new(),
             coffee_shop_settings: default_settings,
         }
     }
     
     /// Open a coffee file in the workspace
     pub fn open_coffee_file(&mut self, file_path: &str, content: &str) -> Result<(), CoffeeSpillReport> {
-        println!("☕ Opening coffee file: {}", file_path);
-        
+        // No printing here: stdout is the editor's JSON-RPC channel when serving LSP
         let coffee_file = OpenCoffeeFile {
             file_path: file_path.to_string(),
             coffee_content: content.to_string(),
             brewing_version: 1,
             parsed_coffee_ast: None,
+            statement_chunks: None,
             brewing_errors: Vec::new(),
             last_sip_time: std::time::SystemTime::now(),
         };
@@ -200,39 +912,94 @@ impl BaristaLanguageServer {
         Ok(())
     }
     
+    /// Replace an open coffee file's content with the editor's latest version
+    pub fn change_coffee_file(&mut self, file_path: &str, content: &str, version: u64) -> Result<(), CoffeeSpillReport> {
+        match self.coffee_workspace.open_coffee_files.get_mut(file_path) {
+            Some(coffee_file) => {
+                coffee_file.coffee_content = content.to_string();
+                coffee_file.brewing_version = version;
+                // The last AST that parsed stays around so completions work mid-edit
+                coffee_file.statement_chunks = None;
+                coffee_file.last_sip_time = std::time::SystemTime::now();
+            }
+            None => return self.open_coffee_file(file_path, content),
+        }
+        self.brew_file_analysis(file_path)
+    }
+
+    /// Apply a ranged edit from the editor and reparse only the statements it touched
+    pub fn edit_coffee_file(&mut self, file_path: &str, range: &CoffeeRange, new_text: &str, version: u64) -> Result<(), CoffeeSpillReport> {
+        let coffee_file = self.coffee_workspace.open_coffee_files.get_mut(file_path).ok_or_else(|| {
+            CoffeeSpillReport::new_brewing_disaster(
+                SpillType::BeanNotFound,
+                0, 0,
+                &format!("Coffee file '{}' not found in workspace", file_path)
+            )
+        })?;
+        let content = &mut coffee_file.coffee_content;
+        let start = utf16_position_to_offset(content, range.start_line, range.start_column);
+        let end = utf16_position_to_offset(content, range.end_line, range.end_column).max(start);
+        content.replace_range(start..end, new_text);
+        coffee_file.brewing_version = version;
+        coffee_file.last_sip_time = std::time::SystemTime::now();
+
+        if let Some(chunks) = coffee_file.statement_chunks.as_mut() {
+            let edit_first_line = range.start_line as usize + 1;
+            let old_last_line = range.end_line as usize + 1;
+            let new_last_line = edit_first_line + new_text.matches('\n').count();
+            reparse_edited_chunks(chunks, &coffee_file.coffee_content, edit_first_line, old_last_line, new_last_line);
+        }
+        self.brew_file_analysis(file_path)
+    }
+
+    /// Forget a coffee file the editor closed
+    pub fn close_coffee_file(&mut self, file_path: &str) {
+        self.coffee_workspace.open_coffee_files.remove(file_path);
+    }
+
     /// Analyze a coffee file and provide diagnostics
     pub fn brew_file_analysis(&mut self, file_path: &str) -> Result<(), CoffeeSpillReport> {
-        // First, get the content and parse it
+        // First, get the content and parse it, reusing the statement chunks kept up to
+        // date by edits; a file is parsed from scratch only when it has none
         let (coffee_content, parsed_ast) = {
-            let coffee_file = self.coffee_workspace.open_coffee_files.get(file_path).ok_or_else(|| {
+            let coffee_file = self.coffee_workspace.open_coffee_files.get_mut(file_path).ok_or_else(|| {
                 CoffeeSpillReport::new_brewing_disaster(
                     SpillType::BeanNotFound,
                     0, 0,
                     &format!("Coffee file '{}' not found in workspace", file_path)
                 )
             })?;
-            
-            let coffee_tokens = lexer::lex(&coffee_file.coffee_content);
-            let brewing_result = parser::parse(&coffee_tokens);
-            
-            (coffee_file.coffee_content.clone(), brewing_result)
+
+            let coffee_content = coffee_file.coffee_content.clone();
+            let chunks = coffee_file.statement_chunks
+                .get_or_insert_with(|| parser::CoffeeChunkParser::new(&coffee_content, 1).collect());
+            let mut brewing_result = parser::ParseResult::default();
+            for chunk in chunks.iter() {
+                brewing_result.statements.extend(chunk.result.statements.iter().cloned());
+                brewing_result.errors.extend(chunk.result.errors.iter().cloned());
+                brewing_result.error_positions.extend(chunk.result.error_positions.iter().copied());
+                brewing_result.statement_lines.extend(chunk.result.statement_lines.iter().copied());
+            }
+            (coffee_content, brewing_result)
         };
         
         // Generate diagnostics with suggestions before getting mutable borrow
         let mut diagnostics = Vec::new();
-        for (i, error) in parsed_ast.errors.iter().enumerate() {
+        for (error, &(line, column)) in parsed_ast.errors.iter().zip(&parsed_ast.error_positions) {
             let suggestion = self.generate_barista_suggestion(error);
+            // Parser positions are 1-based; the sour note runs to the end of its line
+            let line_length = coffee_content.lines().nth(line - 1).map_or(0, |l| l.chars().count());
             let diagnostic = CoffeeBrewingDiagnostic {
                 brewing_range: CoffeeRange {
-                    start_line: i as u32,
-                    start_column: 0,
-                    end_line: i as u32,
-                    end_column: 100,
+                    start_line: (line - 1) as u32,
+                    start_column: (column - 1) as u32,
+                    end_line: (line - 1) as u32,
+                    end_column: line_length.max(column) as u32,
                 },
                 severity: BrewingSeverity::CoffeeSpill,
                 spill_message: error.clone(),
                 barista_suggestion: Some(suggestion),
-                brewing_code: Some(format!("COFFEE_PARSE_ERROR_{}", i)),
+                brewing_code: Some(SpillType::UnexpectedIngredient.code().to_string()),
                 related_information: Vec::new(),
             };
             diagnostics.push(diagnostic);
@@ -247,11 +1014,11 @@ impl BaristaLanguageServer {
         
         // Store the AST if parsing succeeded
         if parsed_ast.errors.is_empty() {
-            coffee_file.parsed_coffee_ast = Some(parsed_ast.statements);
-            
-            // Perform additional analysis with the content copy
-            self.analyze_coffee_style_content(&coffee_content, file_path)?;
-            self.analyze_coffee_complexity_content(&coffee_content, file_path)?;
+            self.analyze_coffee_types(&parsed_ast.statements, file_path);
+            self.analyze_coffee_lints(&parsed_ast.statements, file_path);
+            if let Some(coffee_file) = self.coffee_workspace.open_coffee_files.get_mut(file_path) {
+                coffee_file.parsed_coffee_ast = Some(parsed_ast.statements);
+            }
         }
         
         Ok(())
@@ -268,181 +1035,325 @@ impl BaristaLanguageServer {
         }
     }
     
-    /// Analyze coffee coding style
-    fn analyze_coffee_style_content(&mut self, content: &str, file_path: &str) -> Result<(), CoffeeSpillReport> {
-        if !self.coffee_workspace.brewing_configuration.coffee_linting_rules.enforce_coffee_naming {
-            return Ok(());
-        }
-        
-        let lines: Vec<&str> = content.lines().collect();
-        
-        for (line_num, line) in lines.iter().enumerate() {
-            // Check for non-coffee variable names
-            if line.contains("beans ") && !self.is_coffee_themed_name(line) {
-                let diagnostic = CoffeeBrewingDiagnostic {
-                    brewing_range: CoffeeRange {
-                        start_line: line_num as u32,
-                        start_column: 0,
-                        end_line: line_num as u32,
-                        end_column: line.len() as u32,
-                    },
-                    severity: BrewingSeverity::WeakBrew,
-                    spill_message: "Consider using coffee-themed variable names for better flavor!".to_string(),
-                    barista_suggestion: Some("☕ Try names like 'coffee_strength', 'bean_count', 'brewing_time', etc.".to_string()),
-                    brewing_code: Some("COFFEE_STYLE_NAMING".to_string()),
-                    related_information: Vec::new(),
-                };
-                
-                if let Some(coffee_file) = self.coffee_workspace.open_coffee_files.get_mut(file_path) {
-                    coffee_file.brewing_errors.push(diagnostic);
-                }
-            }
+    /// What the Freshness Checker would refuse to brew. With strict type checking on, that
+    /// includes adding a string and a number, as `--strict-brew` does.
+    fn analyze_coffee_types(&mut self, statements: &[ast::Statement], file_path: &str) {
+        let strict = self.coffee_workspace.brewing_configuration.strict_coffee_type_checking;
+        let mut checker = TypeChecker::new().with_strict_brew(strict);
+        let Err(errors) = checker.check(statements) else { return };
+        let Some(coffee_file) = self.coffee_workspace.open_coffee_files.get_mut(file_path) else { return };
+        for (error, &(line, column)) in errors.into_iter().zip(checker.error_positions()) {
+            let line = line.saturating_sub(1);
+            let line_length = coffee_file.coffee_content.lines().nth(line).map_or(0, |l| l.chars().count());
+            coffee_file.brewing_errors.push(CoffeeBrewingDiagnostic {
+                brewing_range: CoffeeRange {
+                    start_line: line as u32,
+                    start_column: column.saturating_sub(1) as u32,
+                    end_line: line as u32,
+                    end_column: line_length.max(column) as u32,
+                },
+                severity: BrewingSeverity::CoffeeSpill,
+                spill_message: error,
+                barista_suggestion: None,
+                brewing_code: Some("freshness".to_string()),
+                related_information: Vec::new(),
+            });
         }
-        
-        Ok(())
-    }
-    
-    /// Check if a variable name is coffee-themed
-    fn is_coffee_themed_name(&self, line: &str) -> bool {
-        let coffee_keywords = [
-            "coffee", "bean", "brew", "espresso", "latte", "mocha", "cappuccino",
-            "roast", "grind", "steam", "milk", "foam", "cup", "mug", "barista",
-            "cafe", "shop", "aroma", "flavor", "blend", "strength", "caffeine"
-        ];
-        
-        coffee_keywords.iter().any(|keyword| line.to_lowercase().contains(keyword))
     }
-    
-    /// Analyze coffee code complexity
-    fn analyze_coffee_complexity_content(&mut self, content: &str, file_path: &str) -> Result<(), CoffeeSpillReport> {
-        let max_complexity = self.coffee_workspace.brewing_configuration.coffee_linting_rules.max_brewing_complexity;
-        
-        // Simple complexity calculation - count nested blocks
-        let mut complexity = 0u32;
-        let mut nesting_level = 0u32;
-        
-        for line in content.lines() {
-            if line.trim().contains('{') {
-                nesting_level += 1;
-                complexity += nesting_level;
-            }
-            if line.trim().contains('}') {
-                nesting_level = nesting_level.saturating_sub(1);
-            }
+
+    /// Style and correctness checks from the Brew Linter. The workspace's linting rules
+    /// set the defaults; a brewlint.toml in the roastery overrides them.
+    fn analyze_coffee_lints(&mut self, statements: &[ast::Statement], file_path: &str) {
+        let rules = &self.coffee_workspace.brewing_configuration.coffee_linting_rules;
+        let mut config = brew_linter::BrewLintConfig::default();
+        config.max_complexity = rules.max_brewing_complexity as usize;
+        if !rules.enforce_coffee_naming {
+            config.set_severity(brew_linter::BrewLintRule::CoffeeNaming, brew_linter::LintSeverity::Off);
         }
-        
-        if complexity > max_complexity {
-            let diagnostic = CoffeeBrewingDiagnostic {
+        // A broken brewlint.toml is reported by `brew lint`; the editor keeps the defaults
+        let config = config.clone()
+            .discover(std::path::Path::new(&self.coffee_workspace.roastery_root))
+            .unwrap_or(config);
+
+        let Some(coffee_file) = self.coffee_workspace.open_coffee_files.get_mut(file_path) else { return };
+        for finding in brew_linter::lint_coffee_statements(statements, &config) {
+            let line = finding.line.saturating_sub(1);
+            let line_length = coffee_file.coffee_content.lines().nth(line).map_or(0, |l| l.chars().count());
+            coffee_file.brewing_errors.push(CoffeeBrewingDiagnostic {
                 brewing_range: CoffeeRange {
-                    start_line: 0,
-                    start_column: 0,
-                    end_line: 0,
-                    end_column: 0,
+                    start_line: line as u32,
+                    start_column: finding.column.saturating_sub(1) as u32,
+                    end_line: line as u32,
+                    end_column: line_length.max(finding.column) as u32,
                 },
-                severity: BrewingSeverity::BitterTaste,
-                spill_message: format!("This coffee recipe is too complex! Complexity: {}, Max: {}", complexity, max_complexity),
-                barista_suggestion: Some("☕ Consider breaking this into smaller brewing functions for better taste!".to_string()),
-                brewing_code: Some("COFFEE_COMPLEXITY_WARNING".to_string()),
+                severity: match finding.severity {
+                    brew_linter::LintSeverity::Error => BrewingSeverity::CoffeeSpill,
+                    brew_linter::LintSeverity::Warn => BrewingSeverity::BitterTaste,
+                    _ => BrewingSeverity::WeakBrew,
+                },
+                spill_message: finding.message,
+                barista_suggestion: None,
+                brewing_code: Some(finding.rule.name().to_string()),
                 related_information: Vec::new(),
-            };
-            
-            if let Some(coffee_file) = self.coffee_workspace.open_coffee_files.get_mut(file_path) {
-                coffee_file.brewing_errors.push(diagnostic);
-            }
+            });
         }
-        
-        Ok(())
     }
     
-    /// Provide autocomplete suggestions
+    /// Provide autocomplete suggestions for the cursor's context: module paths after `grind`,
+    /// bean fields and methods or module exports after `.`, otherwise keywords plus the
+    /// beans, brews and beans classes in scope at the cursor
     pub fn get_coffee_completions(&mut self, file_path: &str, line: u32, column: u32) -> Vec<CoffeeSuggestion> {
-        let mut suggestions = Vec::new();
-        
-        // Coffee keywords
-        let coffee_keywords = vec![
-            ("beans", "Declare a coffee bean variable"),
-            ("brew", "Define a brewing function"),
-            ("taste", "Conditional brewing (if statement)"),
-            ("otherwise", "Alternative brewing (else statement)"),
-            ("steep", "Brewing loop (while statement)"),
-            ("pour", "Pouring loop (for statement)"),
-            ("pourout", "Display coffee output"),
-            ("bean", "Define a coffee bean class"),
-            ("coffee_recipe", "Define a coffee recipe interface"),
-            ("new", "Create a new coffee bean instance"),
-            ("this", "Reference to current coffee bean"),
-            ("super", "Reference to parent coffee bean"),
-        ];
-        
-        for (keyword, description) in coffee_keywords {
-            suggestions.push(CoffeeSuggestion {
-                suggestion_text: keyword.to_string(),
-                brewing_kind: CoffeeSuggestionKind::CoffeeKeyword,
-                detailed_info: description.to_string(),
-                coffee_snippet: None,
-                barista_rating: 5.0,
-            });
+        let Some(coffee_file) = self.coffee_workspace.open_coffee_files.get(file_path) else {
+            return coffee_keyword_suggestions();
+        };
+        let content = &coffee_file.coffee_content;
+        let prefix = coffee_prefix(content, line, column);
+        let mut prefix_tokens: Vec<SpannedToken> = CoffeeTokenStream::new(&prefix).collect();
+        // The word being typed isn't context
+        if prefix.ends_with(|c: char| c.is_alphanumeric() || c == '_') && matches!(prefix_tokens.last().map(|t| &t.token), Some(Token::Identifier(_))) {
+            prefix_tokens.pop();
         }
-        
-        // Coffee operators
-        let coffee_operators = vec![
-            ("pour_in", "Assignment operator (=)"),
-            ("add", "Addition operator (+)"),
-            ("sip", "Subtraction operator (-)"),
-            ("same_blend", "Equality comparison (==)"),
-            ("different_blend", "Inequality comparison (!=)"),
-            ("more_caffeine", "Greater than (>)"),
-            ("less_caffeine", "Less than (<)"),
-        ];
-        
-        for (operator, description) in coffee_operators {
-            suggestions.push(CoffeeSuggestion {
-                suggestion_text: operator.to_string(),
-                brewing_kind: CoffeeSuggestionKind::CoffeeKeyword,
-                detailed_info: description.to_string(),
-                coffee_snippet: None,
-                barista_rating: 4.5,
-            });
+        let tail: Vec<&Token> = prefix_tokens.iter().rev().take(2).map(|t| &t.token).collect();
+        let inside_string = prefix.lines().last().is_some_and(|l| l.matches('"').count() % 2 == 1);
+
+        match tail.as_slice() {
+            [Token::Grind, ..] => self.grind_path_suggestions(file_path, true),
+            [Token::String(_), Token::Grind] if inside_string => self.grind_path_suggestions(file_path, false),
+            [Token::Dot, receiver] => {
+                let tokens: Vec<SpannedToken> = CoffeeTokenStream::new(content).collect();
+                let (_, enclosing_bean) = scoped_coffee_names(&tokens, prefix_tokens.len());
+                let ast = coffee_file.parsed_coffee_ast.as_deref().unwrap_or(&[]);
+                match receiver {
+                    Token::This => enclosing_bean.map(|bean| bean_member_suggestions(ast, &bean)).unwrap_or_default(),
+                    Token::Identifier(name) => match coffee_receiver_origin(&tokens, name) {
+                        Some(CoffeeReceiver::Bean(bean)) => bean_member_suggestions(ast, &bean),
+                        Some(CoffeeReceiver::Module(path)) => self.module_export_suggestions(file_path, &path),
+                        None => {
+                            // Unknown receiver: offer every bean's members rather than nothing
+                            let mut suggestions: Vec<CoffeeSuggestion> = Vec::new();
+                            for statement in ast {
+                                if let ast::Statement::BeanDecl { name, .. } = statement {
+                                    for member in bean_member_suggestions(ast, name) {
+                                        if suggestions.iter().all(|s| s.suggestion_text != member.suggestion_text) {
+                                            suggestions.push(member);
+                                        }
+                                    }
+                                }
+                            }
+                            suggestions
+                        }
+                    },
+                    _ => Vec::new(),
+                }
+            }
+            _ => {
+                let tokens: Vec<SpannedToken> = CoffeeTokenStream::new(content).collect();
+                let (in_scope, _) = scoped_coffee_names(&tokens, prefix_tokens.len());
+                let mut suggestions = coffee_keyword_suggestions();
+                for declaration in in_scope {
+                    suggestions.retain(|s| s.suggestion_text != declaration.name);
+                    suggestions.push(declared_suggestion(declaration));
+                }
+                suggestions
+            }
         }
-        
-        // Coffee snippets
-        let coffee_snippets = vec![
-            ("coffee_class", "bean ${1:CoffeeClass} {\n    beans ${2:property} pour_in ${3:value}\n    \n    brew ${4:method}() {\n        ${5:// brewing logic}\n    }\n}"),
-            ("coffee_function", "brew ${1:function_name}(${2:parameters}) {\n    ${3:// brewing logic}\n    return ${4:result}\n}"),
-            ("coffee_loop", "steep ${1:condition} {\n    ${2:// brewing process}\n}"),
-            ("coffee_if", "taste ${1:condition} {\n    ${2:// perfect brew}\n} otherwise {\n    ${3:// needs more work}\n}"),
+    }
+
+    /// `.brewco` files in the workspace, relative to its root, for `grind "..."`
+    fn grind_path_suggestions(&self, file_path: &str, quoted: bool) -> Vec<CoffeeSuggestion> {
+        let root = std::fs::canonicalize(&self.coffee_workspace.roastery_root).unwrap_or_default();
+        self.workspace_coffee_sources().into_iter()
+            .filter(|(uri, _)| uri != file_path)
+            .filter_map(|(uri, _)| {
+                let path = std::path::PathBuf::from(uri.strip_prefix("file://")?);
+                let relative = path.strip_prefix(&root).unwrap_or(&path).display().to_string();
+                Some(CoffeeSuggestion {
+                    suggestion_text: if quoted { format!("\"{}\"", relative) } else { relative },
+                    brewing_kind: CoffeeSuggestionKind::CoffeeModule,
+                    detailed_info: "Coffee module".to_string(),
+                    coffee_snippet: None,
+                    barista_rating: 4.5,
+                })
+            })
+            .collect()
+    }
+
+    /// Top-level names a ground module exports, read from the module file
+    fn module_export_suggestions(&self, file_path: &str, module_path: &str) -> Vec<CoffeeSuggestion> {
+        let document_dir = std::path::Path::new(file_path.strip_prefix("file://").unwrap_or(file_path))
+            .parent()
+            .map(|dir| dir.to_path_buf())
+            .unwrap_or_default();
+        let candidates = [
+            std::path::Path::new(&self.coffee_workspace.roastery_root).join(module_path),
+            document_dir.join(module_path),
         ];
-        
-        for (name, snippet) in coffee_snippets {
-            suggestions.push(CoffeeSuggestion {
-                suggestion_text: name.to_string(),
-                brewing_kind: CoffeeSuggestionKind::CoffeeSnippet,
-                detailed_info: format!("Coffee code snippet: {}", name),
-                coffee_snippet: Some(snippet.to_string()),
-                barista_rating: 4.8,
-            });
-        }
-        
-        suggestions
+        let Some(source) = candidates.iter().find_map(|path| std::fs::read_to_string(path).ok()) else {
+            return Vec::new();
+        };
+        let module = parser::parse_source(&source);
+        module.statements.iter()
+            .filter_map(|statement| match statement {
+                ast::Statement::VarDecl { name, value, .. } => Some(CoffeeSuggestion {
+                    suggestion_text: name.clone(),
+                    brewing_kind: CoffeeSuggestionKind::BeanVariable,
+                    detailed_info: format!("= {}, exported from {}", brewco_codegen::generate_expr(value), module_path),
+                    coffee_snippet: None,
+                    barista_rating: 5.0,
+                }),
+                ast::Statement::ArrayDecl { name, .. } | ast::Statement::ObjectDecl { name, .. } => {
+                    Some(CoffeeSuggestion {
+                        suggestion_text: name.clone(),
+                        brewing_kind: CoffeeSuggestionKind::BeanVariable,
+                        detailed_info: format!("exported from {}", module_path),
+                        coffee_snippet: None,
+                        barista_rating: 5.0,
+                    })
+                }
+                ast::Statement::BrewDecl { name, params, .. } => Some(brew_suggestion(name, params, format!("exported from {}", module_path))),
+                ast::Statement::BeanDecl { name, .. } => Some(CoffeeSuggestion {
+                    suggestion_text: name.clone(),
+                    brewing_kind: CoffeeSuggestionKind::CoffeeClass,
+                    detailed_info: format!("bean exported from {}", module_path),
+                    coffee_snippet: None,
+                    barista_rating: 5.0,
+                }),
+                _ => None,
+            })
+            .collect()
     }
     
     /// Get hover information for coffee elements
     pub fn get_coffee_hover_info(&mut self, file_path: &str, line: u32, column: u32) -> Option<CoffeeHoverInfo> {
-        // Mock implementation - in real implementation, this would analyze the AST
-        Some(CoffeeHoverInfo {
-            hover_content: "☕ **Coffee Bean Variable**\n\nA delicious coffee bean that holds a value.".to_string(),
-            coffee_type_info: Some("Type: CoffeeBean<String>".to_string()),
-            brewing_examples: vec![
-                "beans my_coffee pour_in \"Espresso\"".to_string(),
-                "beans coffee_strength pour_in 9".to_string(),
-            ],
-            barista_tips: vec![
-                "☕ Use descriptive coffee-themed names for better code flavor!".to_string(),
-                "☕ Consider the strength of your coffee when choosing values.".to_string(),
-            ],
+        let coffee_file = self.coffee_workspace.open_coffee_files.get(file_path)?;
+        let word = coffee_word_at(&coffee_file.coffee_content, line, column)?;
+
+        if let Some(declaration) = brewed_declarations(&coffee_file.coffee_content).into_iter().find(|d| d.name == word) {
+            let kind = match declaration.kind {
+                CoffeeSuggestionKind::BrewingFunction => "Brew",
+                CoffeeSuggestionKind::CoffeeClass => "Bean",
+                CoffeeSuggestionKind::CoffeeInterface => "Coffee Recipe",
+                _ => "Coffee Bean Variable",
+            };
+            // Comment lines right above the declaration document it
+            let docs = parser::parse_source(&coffee_file.coffee_content).trivia.doc_comment(declaration.line)
+                .map_or(String::new(), |docs| format!("{}\n\n", docs));
+            return Some(CoffeeHoverInfo {
+                hover_content: format!("☕ **{}** `{}`\n\n{}Declared on line {}.", kind, declaration.name, docs, declaration.line),
+                coffee_type_info: Some(declaration.signature),
+                brewing_examples: Vec::new(),
+                barista_tips: Vec::new(),
+            });
+        }
+
+        COFFEE_KEYWORD_NOTES.iter().chain(COFFEE_OPERATOR_NOTES).find(|(keyword, _)| *keyword == word).map(|(keyword, description)| {
+            CoffeeHoverInfo {
+                hover_content: format!("☕ **{}**\n\n{}", keyword, description),
+                coffee_type_info: None,
+                brewing_examples: Vec::new(),
+                barista_tips: Vec::new(),
+            }
         })
     }
     
+    /// Highlighting for every token in an open coffee file
+    pub fn get_coffee_semantic_tokens(&self, file_path: &str) -> Vec<CoffeeSemanticToken> {
+        self.coffee_workspace.open_coffee_files.get(file_path)
+            .map(|coffee_file| brewed_semantic_tokens(&coffee_file.coffee_content))
+            .unwrap_or_default()
+    }
+
+    /// Rename the bean, brew, variable or field under the cursor, and only what resolves to it:
+    /// a local stays in its file and scope, a top-level name reaches every workspace file where
+    /// no local shadows it, and a field or method renames member accesses, object keys and bean
+    /// members of that name. Refuses names that aren't plain identifiers or that something already uses.
+    pub fn rename_coffee_symbol(&self, file_path: &str, line: u32, column: u32, new_name: &str) -> Result<HashMap<String, Vec<CoffeeTextEdit>>, String> {
+        let coffee_file = self.coffee_workspace.open_coffee_files.get(file_path)
+            .ok_or_else(|| format!("Coffee file '{}' is not open", file_path))?;
+        let symbols = coffee_symbols(&coffee_file.coffee_content);
+        let target = coffee_symbol_at(&symbols, line, column)
+            .ok_or("Only beans, brews and variables can be renamed; there's no name under the cursor")?;
+        let (old_name, binding) = (target.name.clone(), target.binding);
+
+        let renamed: Vec<Token> = CoffeeTokenStream::new(new_name).map(|spanned| spanned.token).collect();
+        if renamed != [Token::Identifier(new_name.to_string())] {
+            return Err(format!("'{}' isn't a valid Brewco name (keywords and symbols can't be used)", new_name));
+        }
+
+        // A local can only be seen from its own file
+        let sources = match binding {
+            CoffeeBinding::Local(_) => vec![(file_path.to_string(), coffee_file.coffee_content.clone())],
+            _ => self.workspace_coffee_sources(),
+        };
+        if binding == CoffeeBinding::Global
+            && !sources.iter().any(|(_, content)| brewed_declarations(content).iter().any(|d| d.name == old_name))
+        {
+            return Err(format!("'{}' isn't declared anywhere in the workspace, so it can't be renamed", old_name));
+        }
+        if new_name == old_name {
+            return Ok(HashMap::new());
+        }
+        for (uri, content) in &sources {
+            let symbols = coffee_symbols(content);
+            if binding == CoffeeBinding::Member {
+                if symbols.iter().any(|symbol| symbol.binding == CoffeeBinding::Member && symbol.name == new_name) {
+                    return Err(format!("'{}' is already a field or method in {}; renaming would merge them", new_name, uri));
+                }
+                continue;
+            }
+            if let Some(existing) = brewed_declarations(content).iter().find(|d| d.name == new_name) {
+                return Err(format!("'{}' is already declared on line {} of {}; renaming would shadow it", new_name, existing.line, uri));
+            }
+            if symbols.iter().any(|symbol| symbol.binding != CoffeeBinding::Member && symbol.name == new_name) {
+                return Err(format!("'{}' is already used in {}; renaming would capture it", new_name, uri));
+            }
+        }
+
+        let mut changes = HashMap::new();
+        for (uri, content) in sources {
+            let edits: Vec<CoffeeTextEdit> = coffee_symbols(&content).into_iter()
+                .filter(|symbol| symbol.name == old_name && symbol.binding == binding)
+                .map(|symbol| CoffeeTextEdit { edit_range: symbol.range, new_text: new_name.to_string() })
+                .collect();
+            if !edits.is_empty() {
+                changes.insert(uri, edits);
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Every coffee file in the workspace: open files as the editor has them, the rest from disk
+    /// under the folder the editor opened (none without one). Hidden folders, build output,
+    /// installed beans and symlinks out of the folder are left alone.
+    fn workspace_coffee_sources(&self) -> Vec<(String, String)> {
+        let mut sources: Vec<(String, String)> = self.coffee_workspace.open_coffee_files.iter()
+            .map(|(uri, file)| (uri.clone(), file.coffee_content.clone()))
+            .collect();
+        let mut pending: Vec<std::path::PathBuf> = self.coffee_workspace.client_root.iter().map(std::path::PathBuf::from).collect();
+        while let Some(dir) = pending.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let name = entry.file_name().to_string_lossy().to_string();
+                let Ok(file_type) = entry.file_type() else { continue };
+                if file_type.is_dir() {
+                    if !name.starts_with('.') && name != "target" && name != "coffee_beans" {
+                        pending.push(path);
+                    }
+                } else if file_type.is_file() && name.ends_with(".brewco") {
+                    let uri = coffee_file_uri(&path);
+                    if sources.iter().all(|(open_uri, _)| *open_uri != uri) {
+                        if let Ok(content) = std::fs::read_to_string(&path) {
+                            sources.push((uri, content));
+                        }
+                    }
+                }
+            }
+        }
+        sources.sort_by(|a, b| a.0.cmp(&b.0));
+        sources
+    }
+
     /// Get all diagnostics for a file
     pub fn get_coffee_diagnostics(&self, file_path: &str) -> Vec<CoffeeBrewingDiagnostic> {
         if let Some(coffee_file) = self.coffee_workspace.open_coffee_files.get(file_path) {
@@ -455,6 +1366,463 @@ impl BaristaLanguageServer {
     /// Update coffee shop settings
     pub fn update_coffee_shop_settings(&mut self, settings: CoffeeShopSettings) {
         self.coffee_shop_settings = settings;
-        println!("☕ Coffee shop settings updated! Your barista is now more helpful!");
+        eprintln!("☕ Coffee shop settings updated! Your barista is now more helpful!");
     }
-} 
\ No newline at end of file
+}
+
+/// Read one `Content-Length` framed JSON message (LSP and DAP share this framing)
+pub fn read_framed_message(reader: &mut impl BufRead) -> Option<JsonValue> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).ok()? == 0 {
+            return None;
+        }
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(length) = header.strip_prefix("Content-Length:") {
+            content_length = length.trim().parse::<usize>().ok();
+        }
+    }
+    let mut payload = vec![0; content_length?];
+    reader.read_exact(&mut payload).ok()?;
+    serde_json::from_slice(&payload).ok()
+}
+
+/// Write one `Content-Length` framed JSON message
+pub fn write_framed_message(writer: &mut impl Write, message: &JsonValue) -> io::Result<()> {
+    let payload = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{}", payload.len(), payload)?;
+    writer.flush()
+}
+
+impl BrewingSeverity {
+    /// LSP DiagnosticSeverity: 1 error, 2 warning, 3 information, 4 hint
+    fn lsp_severity(&self) -> u8 {
+        match self {
+            BrewingSeverity::CoffeeSpill => 1,
+            BrewingSeverity::BitterTaste => 2,
+            BrewingSeverity::WeakBrew => 3,
+            BrewingSeverity::PerfectBrew => 4,
+        }
+    }
+}
+
+impl CoffeeSuggestionKind {
+    /// LSP CompletionItemKind
+    fn lsp_kind(&self) -> u8 {
+        match self {
+            CoffeeSuggestionKind::CoffeeKeyword => 14,
+            CoffeeSuggestionKind::BeanVariable => 6,
+            CoffeeSuggestionKind::BrewingFunction => 3,
+            CoffeeSuggestionKind::CoffeeClass => 7,
+            CoffeeSuggestionKind::CoffeeInterface => 8,
+            CoffeeSuggestionKind::CoffeeModule => 9,
+            CoffeeSuggestionKind::CoffeeSnippet => 15,
+            CoffeeSuggestionKind::CoffeeEmoji => 1,
+        }
+    }
+}
+
+fn lsp_range(range: &CoffeeRange) -> JsonValue {
+    json!({
+        "start": { "line": range.start_line, "character": range.start_column },
+        "end": { "line": range.end_line, "character": range.end_column },
+    })
+}
+
+fn lsp_diagnostic(diagnostic: &CoffeeBrewingDiagnostic) -> JsonValue {
+    let mut message = diagnostic.spill_message.clone();
+    if let Some(suggestion) = &diagnostic.barista_suggestion {
+        message = format!("{}\n{}", message, suggestion);
+    }
+    json!({
+        "range": lsp_range(&diagnostic.brewing_range),
+        "severity": diagnostic.severity.lsp_severity(),
+        "code": diagnostic.brewing_code,
+        "source": "brewco",
+        "message": message,
+    })
+}
+
+fn lsp_completion_item(suggestion: &CoffeeSuggestion) -> JsonValue {
+    let mut item = json!({
+        "label": suggestion.suggestion_text,
+        "kind": suggestion.brewing_kind.lsp_kind(),
+        "detail": suggestion.detailed_info,
+    });
+    if let Some(snippet) = &suggestion.coffee_snippet {
+        item["insertText"] = json!(snippet);
+        item["insertTextFormat"] = json!(2); // snippet with ${1:placeholders}
+    }
+    item
+}
+
+fn lsp_hover(hover: &CoffeeHoverInfo) -> JsonValue {
+    let mut markdown = hover.hover_content.clone();
+    if let Some(type_info) = &hover.coffee_type_info {
+        markdown.push_str(&format!("\n\n```brewco\n{}\n```", type_info));
+    }
+    for example in &hover.brewing_examples {
+        markdown.push_str(&format!("\n\n```brewco\n{}\n```", example));
+    }
+    for tip in &hover.barista_tips {
+        markdown.push_str(&format!("\n\n{}", tip));
+    }
+    json!({ "contents": { "kind": "markdown", "value": markdown } })
+}
+
+/// Where a request points: the document URI and a 0-based line and column
+fn text_position(params: &JsonValue) -> (String, u32, u32) {
+    (
+        params["textDocument"]["uri"].as_str().unwrap_or("").to_string(),
+        params["position"]["line"].as_u64().unwrap_or(0) as u32,
+        params["position"]["character"].as_u64().unwrap_or(0) as u32,
+    )
+}
+
+impl BaristaLanguageServer {
+    fn publish_diagnostics(&self, out: &mut impl Write, uri: &str) -> io::Result<()> {
+        let diagnostics: Vec<JsonValue> = self.get_coffee_diagnostics(uri).iter().map(lsp_diagnostic).collect();
+        write_framed_message(out, &json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        }))
+    }
+
+    /// Handle one JSON-RPC request and hand back its result; `Err` becomes a JSON-RPC error
+    fn handle_lsp_request(&mut self, method: &str, params: &JsonValue) -> Result<JsonValue, (i64, String)> {
+        match method {
+            "initialize" => {
+                let root = params["rootUri"].as_str()
+                    .and_then(|uri| uri.strip_prefix("file://"))
+                    .or_else(|| params["rootPath"].as_str());
+                if let Some(root) = root {
+                    self.coffee_workspace.roastery_root = root.to_string();
+                    self.coffee_workspace.client_root = Some(root.to_string());
+                }
+                // Editors opt in with `"initializationOptions": { "strictBrew": true }`
+                if let Some(strict) = params["initializationOptions"]["strictBrew"].as_bool() {
+                    self.coffee_workspace.brewing_configuration.strict_coffee_type_checking = strict;
+                }
+                Ok(json!({
+                    "capabilities": {
+                        "textDocumentSync": 2, // incremental edits
+                        "completionProvider": { "triggerCharacters": ["."] },
+                        "hoverProvider": true,
+                        "renameProvider": true,
+                        "semanticTokensProvider": {
+                            "legend": {
+                                "tokenTypes": COFFEE_SEMANTIC_TOKEN_TYPES,
+                                "tokenModifiers": COFFEE_SEMANTIC_TOKEN_MODIFIERS,
+                            },
+                            "full": true,
+                        },
+                    },
+                    "serverInfo": { "name": "barista-language-server", "version": env!("CARGO_PKG_VERSION") },
+                }))
+            }
+            "shutdown" => Ok(JsonValue::Null),
+            "textDocument/completion" => {
+                let (uri, line, column) = text_position(params);
+                let items: Vec<JsonValue> = self.get_coffee_completions(&uri, line, column).iter().map(lsp_completion_item).collect();
+                Ok(json!(items))
+            }
+            "textDocument/hover" => {
+                let (uri, line, column) = text_position(params);
+                Ok(self.get_coffee_hover_info(&uri, line, column).map_or(JsonValue::Null, |hover| lsp_hover(&hover)))
+            }
+            "textDocument/semanticTokens/full" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                // LSP packs tokens as [deltaLine, deltaStart, length, type, modifiers] relative to the previous one
+                let mut data = Vec::new();
+                let (mut last_line, mut last_start) = (0, 0);
+                for token in self.get_coffee_semantic_tokens(uri) {
+                    let delta_start = if token.line == last_line { token.start_column - last_start } else { token.start_column };
+                    data.extend([token.line - last_line, delta_start, token.length, token.token_type, token.token_modifiers]);
+                    last_line = token.line;
+                    last_start = token.start_column;
+                }
+                Ok(json!({ "data": data }))
+            }
+            "textDocument/rename" => {
+                let (uri, line, column) = text_position(params);
+                let new_name = params["newName"].as_str().unwrap_or("");
+                let changes = self.rename_coffee_symbol(&uri, line, column, new_name).map_err(|refusal| (-32803, refusal))?;
+                let changes: serde_json::Map<String, JsonValue> = changes.into_iter()
+                    .map(|(uri, edits)| {
+                        let edits: Vec<JsonValue> = edits.iter()
+                            .map(|edit| json!({ "range": lsp_range(&edit.edit_range), "newText": edit.new_text }))
+                            .collect();
+                        (uri, json!(edits))
+                    })
+                    .collect();
+                Ok(json!({ "changes": changes }))
+            }
+            _ => Err((-32601, format!("The barista doesn't know how to brew '{}'", method))),
+        }
+    }
+
+    /// Handle one notification; document changes republish that document's diagnostics
+    fn handle_lsp_notification(&mut self, out: &mut impl Write, method: &str, params: &JsonValue) -> io::Result<()> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or("").to_string();
+        let analysis = match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or("");
+                self.open_coffee_file(&uri, text)
+            }
+            "textDocument/didChange" => {
+                // Incremental sync: ranged edits in order; a change without a range is the whole document
+                let version = params["textDocument"]["version"].as_u64().unwrap_or(0);
+                let mut analysis = Ok(());
+                for change in params["contentChanges"].as_array().into_iter().flatten() {
+                    let text = change["text"].as_str().unwrap_or("");
+                    let range = &change["range"];
+                    analysis = if range.is_object() {
+                        let position = |end: &str, field: &str| range[end][field].as_u64().unwrap_or(0) as u32;
+                        let edited = CoffeeRange {
+                            start_line: position("start", "line"),
+                            start_column: position("start", "character"),
+                            end_line: position("end", "line"),
+                            end_column: position("end", "character"),
+                        };
+                        self.edit_coffee_file(&uri, &edited, text, version)
+                    } else {
+                        self.change_coffee_file(&uri, text, version)
+                    };
+                }
+                analysis
+            }
+            "textDocument/didClose" => {
+                self.close_coffee_file(&uri);
+                Ok(())
+            }
+            _ => return Ok(()),
+        };
+        if let Err(spill) = analysis {
+            eprintln!("{}", spill);
+        }
+        self.publish_diagnostics(out, &uri)
+    }
+}
+
+/// Serve the Language Server Protocol over stdin/stdout until the editor says `exit`
+pub fn run_language_server() {
+    let mut server = BaristaLanguageServer::new_coffee_shop_server();
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut shutdown_requested = false;
+
+    while let Some(message) = read_framed_message(&mut input) {
+        let method = message["method"].as_str().unwrap_or("").to_string();
+        let params = &message["params"];
+        let written = if message.get("id").is_some() {
+            shutdown_requested |= method == "shutdown";
+            let response = match server.handle_lsp_request(&method, params) {
+                Ok(result) => json!({ "jsonrpc": "2.0", "id": message["id"], "result": result }),
+                Err((code, error)) => json!({
+                    "jsonrpc": "2.0",
+                    "id": message["id"],
+                    "error": { "code": code, "message": error },
+                }),
+            };
+            write_framed_message(&mut out, &response)
+        } else if method == "exit" {
+            std::process::exit(if shutdown_requested { 0 } else { 1 });
+        } else {
+            server.handle_lsp_notification(&mut out, &method, params)
+        };
+        if written.is_err() {
+            break; // the editor closed our stdout
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A workspace directory on disk holding `files`, and a server rooted there
+    fn workspace_server(label: &str, files: &[(&str, &str)]) -> (BaristaLanguageServer, std::path::PathBuf) {
+        let root = std::env::temp_dir().join(format!("brewco-lsp-{}-{}", label, uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&root).unwrap();
+        for (name, content) in files {
+            std::fs::write(root.join(name), content).unwrap();
+        }
+        let root = std::fs::canonicalize(root).unwrap();
+        let mut server = BaristaLanguageServer::new_coffee_shop_server();
+        server.handle_lsp_request("initialize", &json!({ "rootUri": format!("file://{}", root.display()) })).unwrap();
+        (server, root)
+    }
+
+    fn open(server: &mut BaristaLanguageServer, uri: &str, text: &str) -> JsonValue {
+        let mut out = Vec::new();
+        server.handle_lsp_notification(&mut out, "textDocument/didOpen", &json!({ "textDocument": { "uri": uri, "text": text } })).unwrap();
+        read_framed_message(&mut out.as_slice()).unwrap()
+    }
+
+    fn position(uri: &str, line: u32, character: u32) -> JsonValue {
+        json!({ "textDocument": { "uri": uri }, "position": { "line": line, "character": character } })
+    }
+
+    #[test]
+    fn framed_messages_survive_a_round_trip() {
+        let mut framed = Vec::new();
+        write_framed_message(&mut framed, &json!({ "method": "initialized", "params": { "note": "☕" } })).unwrap();
+        assert!(framed.starts_with(b"Content-Length: "));
+        let mut reader = framed.as_slice();
+        assert_eq!(read_framed_message(&mut reader).unwrap()["params"]["note"], "☕");
+        assert!(read_framed_message(&mut reader).is_none());
+    }
+
+    #[test]
+    fn rename_edits_every_file_in_the_workspace() {
+        let (mut server, root) = workspace_server("rename", &[("menu.brewco", "pourout price\n")]);
+        let uri = format!("file://{}/main.brewco", root.display());
+        open(&mut server, &uri, "beans price = 4\npourout price add 1\n");
+
+        let mut params = position(&uri, 1, 9);
+        params["newName"] = json!("cost");
+        let renamed = server.handle_lsp_request("textDocument/rename", &params).unwrap();
+        let changes = renamed["changes"].as_object().unwrap();
+        assert_eq!(changes[&uri].as_array().unwrap().len(), 2);
+        let menu = format!("file://{}/menu.brewco", root.display());
+        assert_eq!(changes[&menu][0]["range"]["start"], json!({ "line": 0, "character": 8 }));
+        assert_eq!(changes[&menu][0]["newText"], "cost");
+    }
+
+    /// Where each edit of a rename lands in `uri`, as (line, character)
+    fn edit_starts(renamed: &JsonValue, uri: &str) -> Vec<(u64, u64)> {
+        renamed["changes"][uri].as_array().map_or_else(Vec::new, |edits| {
+            let mut starts: Vec<(u64, u64)> = edits.iter()
+                .map(|edit| (edit["range"]["start"]["line"].as_u64().unwrap(), edit["range"]["start"]["character"].as_u64().unwrap()))
+                .collect();
+            starts.sort();
+            starts
+        })
+    }
+
+    #[test]
+    fn rename_leaves_shadowing_locals_and_parameters_alone() {
+        let (mut server, root) = workspace_server("shadowing", &[("halves.brewco", "brew halve(total) {\n    serve total pourop 2\n}\npourout total\n")]);
+        let uri = format!("file://{}/main.brewco", root.display());
+        open(&mut server, &uri, "beans total = 10\nbrew tally(total) {\n    serve total add 1\n}\nbrew bump() {\n    beans total = total add 1\n    serve total\n}\npourout total\n");
+        let halves = format!("file://{}/halves.brewco", root.display());
+
+        let mut params = position(&uri, 0, 7);
+        params["newName"] = json!("sum");
+        let renamed = server.handle_lsp_request("textDocument/rename", &params).unwrap();
+        assert_eq!(edit_starts(&renamed, &uri), vec![(0, 6), (5, 18), (8, 8)]);
+        assert_eq!(edit_starts(&renamed, &halves), vec![(3, 8)]);
+
+        // The parameter is its brew's own
+        let mut params = position(&uri, 2, 11);
+        params["newName"] = json!("sum");
+        let renamed = server.handle_lsp_request("textDocument/rename", &params).unwrap();
+        assert_eq!(edit_starts(&renamed, &uri), vec![(1, 11), (2, 10)]);
+        assert_eq!(renamed["changes"].as_object().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rename_keeps_members_and_variables_apart() {
+        let (mut server, root) = workspace_server("members", &[]);
+        let uri = format!("file://{}/main.brewco", root.display());
+        let text = "bean Till {\n    beans total pour_in 0\n    brew ring(amount) {\n        this.total = this.total add amount\n    }\n}\nbeans order = {\"total\": 5, count: 1}\nbeans total = order.total\npourout total\n";
+        open(&mut server, &uri, text);
+
+        let mut params = position(&uri, 7, 21);
+        params["newName"] = json!("amount_due");
+        let renamed = server.handle_lsp_request("textDocument/rename", &params).unwrap();
+        assert_eq!(edit_starts(&renamed, &uri), vec![(1, 10), (3, 13), (3, 26), (6, 16), (7, 20)]);
+
+        let mut params = position(&uri, 7, 7);
+        params["newName"] = json!("owed");
+        let renamed = server.handle_lsp_request("textDocument/rename", &params).unwrap();
+        assert_eq!(edit_starts(&renamed, &uri), vec![(7, 6), (8, 8)]);
+    }
+
+    #[test]
+    fn rename_without_a_root_folder_only_touches_open_files() {
+        let (_, root) = workspace_server("rootless", &[("menu.brewco", "pourout price\n")]);
+        let mut server = BaristaLanguageServer::new_coffee_shop_server();
+        server.handle_lsp_request("initialize", &json!({})).unwrap();
+        let uri = format!("file://{}/main.brewco", root.display());
+        open(&mut server, &uri, "beans price = 4\npourout price\n");
+
+        let mut params = position(&uri, 0, 7);
+        params["newName"] = json!("cost");
+        let renamed = server.handle_lsp_request("textDocument/rename", &params).unwrap();
+        assert_eq!(renamed["changes"].as_object().unwrap().keys().collect::<Vec<_>>(), vec![&uri]);
+    }
+
+    #[test]
+    fn rename_refuses_keywords_and_names_already_taken() {
+        let (mut server, root) = workspace_server("conflict", &[("menu.brewco", "beans cost = 3\n")]);
+        let uri = format!("file://{}/main.brewco", root.display());
+        open(&mut server, &uri, "beans price = 4\npourout price\n");
+
+        let mut params = position(&uri, 0, 7);
+        params["newName"] = json!("cost");
+        let (code, refusal) = server.handle_lsp_request("textDocument/rename", &params).unwrap_err();
+        assert_eq!(code, -32803);
+        assert!(refusal.contains("already declared on line 1"), "{}", refusal);
+
+        params["newName"] = json!("steep");
+        let (_, refusal) = server.handle_lsp_request("textDocument/rename", &params).unwrap_err();
+        assert!(refusal.contains("isn't a valid Brewco name"), "{}", refusal);
+    }
+
+    #[test]
+    fn ranged_edits_republish_the_diagnostics() {
+        let (mut server, root) = workspace_server("edits", &[]);
+        let uri = format!("file://{}/main.brewco", root.display());
+        let opened = open(&mut server, &uri, "beans cups = 1\npourout cups\n");
+        assert_eq!(opened["params"]["diagnostics"], json!([]));
+
+        let edit = |text: &str, start: u32, end: u32| json!({
+            "textDocument": { "uri": uri, "version": 2 },
+            "contentChanges": [{ "range": { "start": { "line": 1, "character": start }, "end": { "line": 1, "character": end } }, "text": text }],
+        });
+        let mut out = Vec::new();
+        server.handle_lsp_notification(&mut out, "textDocument/didChange", &edit("beans = ", 0, 8)).unwrap();
+        let broken = read_framed_message(&mut out.as_slice()).unwrap();
+        assert_eq!(broken["params"]["diagnostics"][0]["range"]["start"]["line"], 1);
+
+        let mut out = Vec::new();
+        server.handle_lsp_notification(&mut out, "textDocument/didChange", &edit("pourout ", 0, 8)).unwrap();
+        let fixed = read_framed_message(&mut out.as_slice()).unwrap();
+        assert_eq!(fixed["params"]["diagnostics"], json!([]));
+    }
+
+    #[test]
+    fn semantic_tokens_mark_declarations_and_count_emoji_as_two_columns() {
+        let tokens = brewed_semantic_tokens("🎀 note\nbeans cups = 2\n");
+        assert_eq!((tokens[0].line, tokens[0].length, tokens[0].token_type), (0, 7, semantic_type("comment")));
+        let cups = tokens.iter().find(|token| token.line == 1 && token.start_column == 6).unwrap();
+        assert_eq!(cups.token_type, semantic_type("variable"));
+        assert_eq!(cups.token_modifiers, DECLARATION_MODIFIER);
+    }
+
+    #[test]
+    fn member_completions_come_from_the_receivers_bean() {
+        let (mut server, root) = workspace_server("members", &[]);
+        let uri = format!("file://{}/main.brewco", root.display());
+        open(&mut server, &uri, "bean Latte {\n    beans milk = \"oat\"\n    brew froth() {\n        serve 1\n    }\n}\nbeans cup = new Latte()\npourout cup.milk\n");
+        let items = server.handle_lsp_request("textDocument/completion", &position(&uri, 7, 12)).unwrap();
+        let labels: Vec<&str> = items.as_array().unwrap().iter().filter_map(|item| item["label"].as_str()).collect();
+        assert!(labels.contains(&"milk") && labels.contains(&"froth"), "{:?}", labels);
+        assert!(!labels.contains(&"steep"));
+    }
+
+    #[test]
+    fn unknown_methods_are_json_rpc_errors() {
+        let mut server = BaristaLanguageServer::new_coffee_shop_server();
+        let (code, _) = server.handle_lsp_request("textDocument/teleport", &json!({})).unwrap_err();
+        assert_eq!(code, -32601);
+    }
+}
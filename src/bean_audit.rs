@@ -0,0 +1,163 @@
+/*
+ * 🔍 Brewco Bean Audit ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @inspection_note: "Trust your beans, but check the sacks!" ✨
+ *
+ * `brew audit` checks the beans installed under coffee_beans/ against
+ * roastery.lock: each locked bean has to be installed, at the locked
+ * version, with files whose checksum matches the one taken when it was
+ * installed. Beans installed without being locked are pointed out too.
+ *
+ * It also fetches the registry's advisories (`<registry>/advisories.json`,
+ * a list of `{id, bean, versions, severity, summary}`, where no versions
+ * means every version) and flags the locked beans they name. The last
+ * advisories fetched are kept in the bean cache, so an audit without a
+ * network still checks against them.
+ */
+
+// src/bean_audit.rs
+
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bean_registry::{registry_failure, registry_request};
+use crate::coffee_package_roastery::{bean_cache_dir, bean_checksum, installed_bean_dir, registry_token, registry_url, RoasteryLock, ROASTERY_LOCK_FILE};
+
+const ADVISORIES_FILE: &str = "advisories.json";
+
+/// How long to wait on the registry before falling back to the cached advisories
+const REGISTRY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A known-bad bean release, as the registry publishes it
+#[derive(Serialize, Deserialize)]
+struct BeanAdvisory {
+    id: String,
+    bean: String,
+    #[serde(default)]
+    versions: Vec<String>, // empty: every version
+    #[serde(default)]
+    severity: Option<String>,
+    #[serde(default)]
+    summary: String,
+}
+
+impl BeanAdvisory {
+    fn covers(&self, bean: &str, version: &str) -> bool {
+        self.bean == bean && (self.versions.is_empty() || self.versions.iter().any(|bad| bad == version))
+    }
+}
+
+/// The registry's advisories, and where they came from; the cached copy when the registry can't be reached
+fn fetch_advisories() -> Result<(Vec<BeanAdvisory>, String), String> {
+    let registry = registry_url();
+    let url = format!("{}/{}", registry.trim_end_matches('/'), ADVISORIES_FILE);
+    let cached = bean_cache_dir().map(|cache| cache.join(ADVISORIES_FILE));
+    let fetched = registry_request("GET", &registry, registry_token().as_deref(), &format!("/{}", ADVISORIES_FILE), REGISTRY_TIMEOUT)
+        .call()
+        .map_err(registry_failure)
+        .and_then(|response| response.into_string().map_err(|e| e.to_string()));
+    match fetched {
+        Ok(body) => {
+            let advisories: Vec<BeanAdvisory> = serde_json::from_str(&body).map_err(|e| format!("{} isn't a list of advisories: {}", url, e))?;
+            if let Some(cached) = &cached {
+                let kept = cached.parent().map_or(Ok(()), std::fs::create_dir_all).and_then(|_| std::fs::write(cached, &body));
+                if let Err(e) = kept {
+                    eprintln!("[Coffee Warning] Could not keep the advisories in {}: {}", cached.display(), e);
+                }
+            }
+            Ok((advisories, url))
+        }
+        Err(why) => {
+            let Some(saved) = cached.as_ref().and_then(|cached| std::fs::read_to_string(cached).ok()) else {
+                return Err(format!("couldn't fetch {} ({}) and there are no cached advisories to fall back on", url, why));
+            };
+            let advisories = serde_json::from_str(&saved).map_err(|e| format!("the cached advisories are damaged: {}", e))?;
+            Ok((advisories, format!("the cached copy (couldn't reach {}: {})", url, why)))
+        }
+    }
+}
+
+/// `brew audit`: check the installed beans against roastery.lock and the registry's advisories
+pub fn run_brew_audit() {
+    let lock = match RoasteryLock::load(Path::new(ROASTERY_LOCK_FILE)) {
+        Ok(lock) => lock,
+        Err(msg) => {
+            println!("[ERROR] {}", msg);
+            std::process::exit(1);
+        }
+    };
+    let bean_storage = Path::new("coffee_beans");
+    let mut problems = 0;
+
+    println!("🔍 Auditing {} bean(s) in {}", lock.locked_beans.len(), ROASTERY_LOCK_FILE);
+    for (name, locked) in &lock.locked_beans {
+        let Some(bean_dir) = installed_bean_dir(bean_storage, name, &locked.bean_version) else {
+            println!("  ❌ {} {}: locked but not installed", name, locked.bean_version);
+            problems += 1;
+            continue;
+        };
+        match bean_checksum(&bean_dir) {
+            Ok(checksum) if checksum == locked.checksum => println!("  ✅ {} {}", name, locked.bean_version),
+            Ok(checksum) => {
+                println!(
+                    "  ❌ {} {}: its files don't match {}\n       locked:    {}\n       installed: {}",
+                    name, locked.bean_version, ROASTERY_LOCK_FILE, locked.checksum, checksum
+                );
+                problems += 1;
+            }
+            Err(e) => {
+                println!("  ❌ {} {}: couldn't read {}: {}", name, locked.bean_version, bean_dir.display(), e);
+                problems += 1;
+            }
+        }
+    }
+
+    // Beans someone dropped in by hand have nothing to check them against
+    if let Ok(entries) = std::fs::read_dir(bean_storage) {
+        let mut strays: Vec<String> = entries
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|dir_name| {
+                let name = dir_name.split_once('@').map_or(dir_name.as_str(), |(name, _)| name);
+                !lock.locked_beans.contains_key(name)
+            })
+            .collect();
+        strays.sort();
+        for stray in strays {
+            println!("  ⚠️  {}: installed under coffee_beans/ but not in {}", stray, ROASTERY_LOCK_FILE);
+            problems += 1;
+        }
+    }
+
+    match fetch_advisories() {
+        Ok((advisories, source)) => {
+            println!("🚨 Advisories from {}", source);
+            let mut flagged = 0;
+            for (name, locked) in &lock.locked_beans {
+                for advisory in advisories.iter().filter(|advisory| advisory.covers(name, &locked.bean_version)) {
+                    let severity = advisory.severity.as_deref().map(|severity| format!(" ({})", severity)).unwrap_or_default();
+                    println!("  ❌ {} {}: {}{} {}", name, locked.bean_version, advisory.id, severity, advisory.summary);
+                    flagged += 1;
+                }
+            }
+            if flagged == 0 {
+                println!("  ✅ none for these beans");
+            }
+            problems += flagged;
+        }
+        Err(msg) => {
+            println!("[ERROR] {}", msg);
+            problems += 1;
+        }
+    }
+
+    if problems > 0 {
+        println!("☕ {} problem(s) found", problems);
+        std::process::exit(1);
+    }
+    println!("☕ Every bean checks out");
+}
@@ -0,0 +1,792 @@
+/*
+ * 🏪 Brewco Bean Registry ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @market_note: "Every team deserves a bean market of its own!" ✨
+ *
+ * `brew registry serve` is a registry `brew` can install beans from and
+ * `brew publish` can send them to, so a team can keep private beans on its
+ * own machine (point BREWCO_REGISTRY at it). It speaks:
+ *
+ *     GET /beans/<name>                  the bean's versions, its latest and that one's dependencies
+ *     GET /beans/<name>/<version>        the same, with this version's dependencies
+ *     GET /beans/<name>/<version>/sack   the version's files as a .tar.gz, checksum in X-Bean-Checksum
+ *     PUT /beans/<name>/<version>        publish a version: a .tar.gz with roastery.json at its top
+ *     GET /advisories.json               the advisories `brew audit` checks beans against
+ *
+ * Publishing takes `Authorization: Bearer <token>`, a token made with
+ * `brew registry token <barista>`. The first barista to publish a bean owns
+ * it and only they publish its next versions; a published version is never
+ * replaced. With --private, reading takes a token too. `brew` sends the one
+ * in BREWCO_REGISTRY_TOKEN.
+ *
+ * The store is a plain directory:
+ *
+ *     tokens.json                        each barista and their token's sha256
+ *     advisories.json                    written by hand, served as it is
+ *     beans/<name>/bean.json             the bean's info, and each version's dependencies and checksum
+ *     beans/<name>/<version>.tar.gz      each published version
+ *
+ * The client half, which installing, publishing and `brew audit` go
+ * through, is here as well, so both sides agree on the paths.
+ */
+
+// src/bean_registry.rs
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::coffee_package_roastery::{
+    bean_checksum, registry_token, registry_url, unplain_bean, CoffeeBeanPackageInfo, RoasteryManifest, ROASTERY_LOCK_FILE,
+};
+use crate::interpreter;
+
+/// The biggest sack a registry takes or `brew` downloads
+const MAX_SACK_BYTES: usize = 16 * 1024 * 1024;
+
+/// The most a sack may hold once unpacked, and how many files and directories, so a small
+/// sack that inflates to something huge (a gzip bomb) is turned away while it's being unpacked
+const MAX_UNPACKED_BYTES: u64 = 64 * 1024 * 1024;
+const MAX_SACK_ENTRIES: usize = 10_000;
+
+/// How long `brew` waits on a registry before giving up
+const REGISTRY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often an idle registry looks up to check for Ctrl-C
+const CTRL_C_GLANCE: Duration = Duration::from_millis(200);
+
+const TOKENS_FILE: &str = "tokens.json";
+const ADVISORIES_FILE: &str = "advisories.json";
+const BEAN_FILE: &str = "bean.json";
+
+/// What stays out of a published sack: installed beans, the lockfile, and hidden or build directories
+const UNPUBLISHED: &[&str] = &["coffee_beans", ROASTERY_LOCK_FILE, "target"];
+
+/// A token a registry accepts, kept only as its sha256
+#[derive(Serialize, Deserialize)]
+struct RegistryToken {
+    barista: String,
+    token_sha256: String,
+}
+
+/// A bean as its registry keeps it
+#[derive(Serialize, Deserialize)]
+struct StoredBean {
+    bean_name: String,
+    bean_owner: String, // the barista who published it first
+    bean_description: String,
+    roastery_author: String,
+    roastery_homepage: Option<String>,
+    coffee_keywords: Vec<String>,
+    download_count: u64,
+    versions: BTreeMap<String, StoredVersion>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredVersion {
+    brewing_dependencies: HashMap<String, String>,
+    checksum: String, // bean_checksum of the sack's files
+    published_by: String,
+    published_at: String,
+}
+
+impl StoredBean {
+    fn latest_version(&self) -> Option<&String> {
+        self.versions.keys().max_by(|a, b| version_order(a, b))
+    }
+
+    /// What `brew` reads about the bean, with `version`'s dependencies
+    fn package_info(&self, version: &str) -> CoffeeBeanPackageInfo {
+        let mut available_versions: Vec<String> = self.versions.keys().cloned().collect();
+        available_versions.sort_by(|a, b| version_order(a, b));
+        CoffeeBeanPackageInfo {
+            bean_name: self.bean_name.clone(),
+            available_versions,
+            latest_version: self.latest_version().cloned().unwrap_or_default(),
+            bean_description: self.bean_description.clone(),
+            roastery_author: self.roastery_author.clone(),
+            download_count: self.download_count,
+            brewing_dependencies: self.versions.get(version).map(|stored| stored.brewing_dependencies.clone()).unwrap_or_default(),
+            roastery_homepage: self.roastery_homepage.clone(),
+            coffee_keywords: self.coffee_keywords.clone(),
+        }
+    }
+}
+
+/// Versions compare part by part, numbers as numbers, so 1.10.0 comes after 1.9.0
+fn version_order(a: &str, b: &str) -> Ordering {
+    let parts = |version: &str| -> Vec<Result<u64, String>> {
+        version.split(['.', '-', '+']).map(|part| part.parse::<u64>().map_err(|_| part.to_string())).collect()
+    };
+    parts(a).cmp(&parts(b))
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// One answer to a registry request
+struct Answer {
+    status: u16,
+    body: Vec<u8>,
+    content_type: &'static str,
+    checksum: Option<String>,
+}
+
+impl Answer {
+    fn json(status: u16, body: serde_json::Value) -> Self {
+        Answer { status, body: body.to_string().into_bytes(), content_type: "application/json", checksum: None }
+    }
+
+    fn refuse(status: u16, why: impl Into<String>) -> Self {
+        Answer::json(status, json!({ "error": why.into() }))
+    }
+}
+
+/// A registry serving the beans in `store`
+struct BeanRegistry {
+    store: PathBuf,
+    private: bool,
+}
+
+impl BeanRegistry {
+    fn bean_dir(&self, name: &str) -> PathBuf {
+        self.store.join("beans").join(name)
+    }
+
+    fn load_tokens(store: &Path) -> Result<Vec<RegistryToken>, String> {
+        let path = store.join(TOKENS_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(tokens) => serde_json::from_str(&tokens).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    /// The barista whose token came with `request`, if one did and it's known
+    fn barista(&self, request: &Request) -> Result<Option<String>, String> {
+        let Some(token) = request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv("Authorization"))
+            .and_then(|header| header.value.as_str().strip_prefix("Bearer "))
+        else {
+            return Ok(None);
+        };
+        let token_sha256 = sha256_hex(token.trim().as_bytes());
+        let tokens = Self::load_tokens(&self.store)?;
+        Ok(tokens.into_iter().find(|known| known.token_sha256 == token_sha256).map(|known| known.barista))
+    }
+
+    fn load_bean(&self, name: &str) -> Result<Option<StoredBean>, String> {
+        let path = self.bean_dir(name).join(BEAN_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(bean) => serde_json::from_str(&bean).map(Some).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    fn save_bean(&self, bean: &StoredBean) -> Result<(), String> {
+        let path = self.bean_dir(&bean.bean_name).join(BEAN_FILE);
+        let saved = serde_json::to_string_pretty(bean).map_err(|e| e.to_string())?;
+        std::fs::write(&path, saved + "\n").map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn serve_request(&self, mut request: Request) {
+        let answer = self.answer(&mut request);
+        let mut response = Response::from_data(answer.body).with_status_code(answer.status);
+        let headers = [
+            Header::from_bytes("Content-Type", answer.content_type).ok(),
+            answer.checksum.and_then(|checksum| Header::from_bytes("X-Bean-Checksum", checksum).ok()),
+        ];
+        for header in headers.into_iter().flatten() {
+            response.add_header(header);
+        }
+        if let Err(e) = request.respond(response) {
+            eprintln!("[Coffee Warning] Couldn't answer a registry request: {}", e);
+        }
+    }
+
+    fn answer(&self, request: &mut Request) -> Answer {
+        let path = request.url().split('?').next().unwrap_or("/").to_string();
+        let barista = match self.barista(request) {
+            Ok(barista) => barista,
+            Err(why) => return Answer::refuse(500, why),
+        };
+        if self.private && barista.is_none() {
+            return Answer::refuse(401, "this registry is private; send a token from `brew registry token` as BREWCO_REGISTRY_TOKEN");
+        }
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        if let ["beans", name, rest @ ..] = segments.as_slice() {
+            if let Some(why) = unplain_bean(name, rest.first().copied().unwrap_or("0")) {
+                return Answer::refuse(400, why);
+            }
+        }
+        let method = request.method().clone();
+        match (method, segments.as_slice()) {
+            (Method::Get, [ADVISORIES_FILE]) => match std::fs::read(self.store.join(ADVISORIES_FILE)) {
+                Ok(advisories) => Answer { status: 200, body: advisories, content_type: "application/json", checksum: None },
+                Err(_) => Answer::json(200, json!([])),
+            },
+            (Method::Get, ["beans", name]) => self.bean_info(name, None),
+            (Method::Get, ["beans", name, version]) => self.bean_info(name, Some(version)),
+            (Method::Get, ["beans", name, version, "sack"]) => self.bean_sack(name, version),
+            (Method::Put, ["beans", name, version]) => match barista {
+                Some(barista) => self.publish(name, version, &barista, request),
+                None => Answer::refuse(401, "publishing takes a token from `brew registry token`, sent as BREWCO_REGISTRY_TOKEN"),
+            },
+            _ => Answer::refuse(404, format!("nothing at {}; beans are under /beans/<name>", path)),
+        }
+    }
+
+    fn bean_info(&self, name: &str, version: Option<&str>) -> Answer {
+        let bean = match self.load_bean(name) {
+            Ok(Some(bean)) => bean,
+            Ok(None) => return Answer::refuse(404, format!("no bean named '{}' has been published here", name)),
+            Err(why) => return Answer::refuse(500, why),
+        };
+        let version = match version {
+            Some(version) if !bean.versions.contains_key(version) => {
+                let published = bean.package_info(version).available_versions.join(", ");
+                return Answer::refuse(404, format!("'{}' has no version {}; published: {}", name, version, published));
+            }
+            Some(version) => version.to_string(),
+            None => bean.latest_version().cloned().unwrap_or_default(),
+        };
+        Answer::json(200, json!(bean.package_info(&version)))
+    }
+
+    fn bean_sack(&self, name: &str, version: &str) -> Answer {
+        let mut bean = match self.load_bean(name) {
+            Ok(Some(bean)) => bean,
+            Ok(None) => return Answer::refuse(404, format!("no bean named '{}' has been published here", name)),
+            Err(why) => return Answer::refuse(500, why),
+        };
+        let Some(checksum) = bean.versions.get(version).map(|stored| stored.checksum.clone()) else {
+            return Answer::refuse(404, format!("'{}' has no version {}", name, version));
+        };
+        let sack = match std::fs::read(self.bean_dir(name).join(format!("{}.tar.gz", version))) {
+            Ok(sack) => sack,
+            Err(e) => return Answer::refuse(500, format!("the sack for '{}' {} is missing: {}", name, version, e)),
+        };
+        bean.download_count += 1;
+        if let Err(why) = self.save_bean(&bean) {
+            eprintln!("[Coffee Warning] Couldn't count a download of '{}': {}", name, why);
+        }
+        Answer { status: 200, body: sack, content_type: "application/gzip", checksum: Some(checksum) }
+    }
+
+    fn publish(&self, name: &str, version: &str, barista: &str, request: &mut Request) -> Answer {
+        let existing = match self.load_bean(name) {
+            Ok(existing) => existing,
+            Err(why) => return Answer::refuse(500, why),
+        };
+        if let Some(existing) = &existing {
+            if existing.bean_owner != barista {
+                return Answer::refuse(403, format!("'{}' belongs to {}; only they publish its versions", name, existing.bean_owner));
+            }
+            if existing.versions.contains_key(version) {
+                return Answer::refuse(409, format!("'{}' {} is already published, and published versions don't change; bump the version", name, version));
+            }
+        }
+
+        let mut sack = Vec::new();
+        if let Err(e) = request.as_reader().take(MAX_SACK_BYTES as u64 + 1).read_to_end(&mut sack) {
+            return Answer::refuse(400, format!("couldn't read the sack: {}", e));
+        }
+        if sack.len() > MAX_SACK_BYTES {
+            return Answer::refuse(413, format!("sacks can be at most {} bytes", MAX_SACK_BYTES));
+        }
+
+        // Unpack it aside to check what's inside and take its checksum, the same one `brew` takes once installed
+        let staging = self.store.join("staging").join(uuid::Uuid::new_v4().simple().to_string());
+        let unpacked = unpack_sack(&sack, &staging).and_then(|_| {
+            let manifest = std::fs::read_to_string(staging.join("roastery.json")).map_err(|_| "the sack has no roastery.json at its top".to_string())?;
+            let manifest: RoasteryManifest = serde_json::from_str(&manifest).map_err(|e| format!("the sack's roastery.json: {}", e))?;
+            let checksum = bean_checksum(&staging).map_err(|e| e.to_string())?;
+            Ok((manifest, checksum))
+        });
+        let _ = std::fs::remove_dir_all(&staging);
+        let (manifest, checksum) = match unpacked {
+            Ok(unpacked) => unpacked,
+            Err(why) => return Answer::refuse(400, why),
+        };
+        if manifest.roastery_name != name || manifest.roastery_version != version {
+            return Answer::refuse(
+                400,
+                format!("the sack's roastery.json is '{}' {}, not '{}' {}", manifest.roastery_name, manifest.roastery_version, name, version),
+            );
+        }
+        // Installing turns every dependency into a path, so none gets in that couldn't be one
+        let dependencies = manifest.required_bean_dependencies.iter().chain(&manifest.dev_brewing_dependencies);
+        if let Some(why) = dependencies.filter_map(|(name, version)| unplain_bean(name, version)).next() {
+            return Answer::refuse(400, format!("the sack's roastery.json depends on a bean that can't be installed: {}", why));
+        }
+
+        let bean_dir = self.bean_dir(name);
+        let stored = std::fs::create_dir_all(&bean_dir).and_then(|_| std::fs::write(bean_dir.join(format!("{}.tar.gz", version)), &sack));
+        if let Err(e) = stored {
+            return Answer::refuse(500, format!("couldn't store the sack: {}", e));
+        }
+        let mut bean = existing.unwrap_or_else(|| StoredBean {
+            bean_name: name.to_string(),
+            bean_owner: barista.to_string(),
+            bean_description: String::new(),
+            roastery_author: String::new(),
+            roastery_homepage: None,
+            coffee_keywords: Vec::new(),
+            download_count: 0,
+            versions: BTreeMap::new(),
+        });
+        bean.bean_description = manifest.roastery_description;
+        bean.roastery_author = manifest.head_barista;
+        bean.roastery_homepage = manifest.roastery_homepage;
+        bean.coffee_keywords = manifest.coffee_keywords;
+        bean.versions.insert(
+            version.to_string(),
+            StoredVersion {
+                brewing_dependencies: manifest.required_bean_dependencies,
+                checksum: checksum.clone(),
+                published_by: barista.to_string(),
+                published_at: chrono::Utc::now().to_rfc3339(),
+            },
+        );
+        if let Err(why) = self.save_bean(&bean) {
+            return Answer::refuse(500, why);
+        }
+        eprintln!("☕ {} published '{}' {}", barista, name, version);
+        Answer::json(201, json!({ "bean_name": name, "bean_version": version, "checksum": checksum }))
+    }
+}
+
+/// Unpack a .tar.gz sack into `into`, entry by entry: only files and directories, none that
+/// would land outside it, and no more than MAX_SACK_ENTRIES and MAX_UNPACKED_BYTES in all
+fn unpack_sack(sack: &[u8], into: &Path) -> Result<(), String> {
+    let unreadable = |e: std::io::Error| format!("the sack isn't a .tar.gz brew can unpack: {}", e);
+    std::fs::create_dir_all(into).map_err(|e| format!("{}: {}", into.display(), e))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(sack));
+    let (mut entries, mut unpacked) = (0, 0);
+    for entry in archive.entries().map_err(unreadable)? {
+        let mut entry = entry.map_err(unreadable)?;
+        entries += 1;
+        unpacked += entry.header().size().map_err(unreadable)?;
+        if entries > MAX_SACK_ENTRIES || unpacked > MAX_UNPACKED_BYTES {
+            return Err(format!(
+                "the sack unpacks to more than {} entries or {} MiB",
+                MAX_SACK_ENTRIES,
+                MAX_UNPACKED_BYTES / (1024 * 1024)
+            ));
+        }
+        let path = entry.path().map_err(unreadable)?.display().to_string();
+        if !matches!(entry.header().entry_type(), tar::EntryType::Regular | tar::EntryType::Directory) {
+            return Err(format!("the sack's {} isn't a plain file or directory", path));
+        }
+        if !entry.unpack_in(into).map_err(unreadable)? {
+            return Err(format!("the sack's {} would land outside the bean", path));
+        }
+    }
+    Ok(())
+}
+
+/// `brew registry serve`: serve the beans in `store` until Ctrl-C
+pub fn run_registry_serve(port: u16, store: &str, private: bool) {
+    let registry = BeanRegistry { store: PathBuf::from(store), private };
+    if let Err(e) = std::fs::create_dir_all(registry.store.join("beans")) {
+        println!("[ERROR] Couldn't open the registry store {}: {}", store, e);
+        std::process::exit(1);
+    }
+    let tokens = match BeanRegistry::load_tokens(&registry.store) {
+        Ok(tokens) => tokens,
+        Err(why) => {
+            println!("[ERROR] {}", why);
+            std::process::exit(1);
+        }
+    };
+    let address = format!("0.0.0.0:{}", port);
+    let server = match Server::http(address.as_str()) {
+        Ok(server) => server,
+        Err(e) => {
+            println!("[ERROR] The registry couldn't open at {}: {}", address, e);
+            std::process::exit(1);
+        }
+    };
+    eprintln!(
+        "☕ Brewco registry open at http://{} with the beans in {}{} (Ctrl-C to close)",
+        address,
+        store,
+        if private { ", private" } else { "" }
+    );
+    if tokens.is_empty() {
+        eprintln!("☕ No one can publish yet; `brew registry token <barista> --store {}` makes a token", store);
+    }
+
+    loop {
+        if interpreter::take_interrupt() {
+            break;
+        }
+        match server.recv_timeout(CTRL_C_GLANCE) {
+            Ok(Some(request)) => registry.serve_request(request),
+            Ok(None) => continue,
+            Err(e) => {
+                println!("[ERROR] The registry stopped taking requests: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    eprintln!("☕ Registry closed.");
+}
+
+/// `brew registry token <barista>`: make a publishing token for the registry in `store`
+pub fn run_registry_token(store: &str, barista: &str) {
+    let store = Path::new(store);
+    let mut tokens = match BeanRegistry::load_tokens(store) {
+        Ok(tokens) => tokens,
+        Err(why) => {
+            println!("[ERROR] {}", why);
+            std::process::exit(1);
+        }
+    };
+    let token = format!("brewco_{}", uuid::Uuid::new_v4().simple());
+    tokens.push(RegistryToken { barista: barista.to_string(), token_sha256: sha256_hex(token.as_bytes()) });
+    let saved = serde_json::to_string_pretty(&tokens).map_err(|e| e.to_string()).and_then(|tokens| {
+        std::fs::create_dir_all(store)
+            .and_then(|_| std::fs::write(store.join(TOKENS_FILE), tokens + "\n"))
+            .map_err(|e| format!("{}: {}", store.join(TOKENS_FILE).display(), e))
+    });
+    if let Err(why) = saved {
+        println!("[ERROR] {}", why);
+        std::process::exit(1);
+    }
+    println!("{}", token);
+    eprintln!("☕ A token for {}; the registry keeps only its sha256, so this is the one time it's shown", barista);
+}
+
+/// A request to a registry, with the token if there is one
+pub fn registry_request(method: &str, registry: &str, token: Option<&str>, path: &str, timeout: Duration) -> ureq::Request {
+    let request = ureq::AgentBuilder::new()
+        .timeout(timeout)
+        .build()
+        .request(method, &format!("{}{}", registry.trim_end_matches('/'), path));
+    match token {
+        Some(token) => request.set("Authorization", &format!("Bearer {}", token)),
+        None => request,
+    }
+}
+
+/// Why a registry request failed, in the registry's words when it gave some
+pub fn registry_failure(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, response) => {
+            let said = response
+                .into_string()
+                .ok()
+                .and_then(|body| serde_json::from_str::<serde_json::Value>(&body).ok())
+                .and_then(|body| body.get("error")?.as_str().map(str::to_string));
+            match said {
+                Some(said) => format!("{} ({})", said, code),
+                None => format!("the registry answered {}", code),
+            }
+        }
+        ureq::Error::Transport(transport) => match transport.message() {
+            Some(message) => format!("{}: {}", transport.kind(), message),
+            None => transport.kind().to_string(),
+        },
+    }
+}
+
+/// What `registry` knows about a bean: its latest version, or `version`
+pub fn fetch_bean_info(registry: &str, token: Option<&str>, name: &str, version: Option<&str>) -> Result<CoffeeBeanPackageInfo, String> {
+    let path = match version {
+        Some(version) => format!("/beans/{}/{}", name, version),
+        None => format!("/beans/{}", name),
+    };
+    registry_request("GET", registry, token, &path, REGISTRY_TIMEOUT)
+        .call()
+        .map_err(registry_failure)?
+        .into_string()
+        .map_err(|e| e.to_string())
+        .and_then(|info| serde_json::from_str(&info).map_err(|e| format!("the registry's answer isn't a bean's info: {}", e)))
+}
+
+/// Download a version's sack from `registry` and unpack it into `into`; serves its checksum,
+/// once it's checked against the one the registry took when it was published
+pub fn fetch_bean_sack(registry: &str, token: Option<&str>, name: &str, version: &str, into: &Path) -> Result<String, String> {
+    let response = registry_request("GET", registry, token, &format!("/beans/{}/{}/sack", name, version), REGISTRY_TIMEOUT)
+        .call()
+        .map_err(registry_failure)?;
+    // Without a published checksum there'd be nothing to hold the sack to, so it isn't unpacked
+    let Some(published) = response.header("X-Bean-Checksum").map(str::to_string) else {
+        return Err("the registry sent the sack without an X-Bean-Checksum to check it against".to_string());
+    };
+    let mut sack = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_SACK_BYTES as u64 + 1)
+        .read_to_end(&mut sack)
+        .map_err(|e| format!("the download broke off: {}", e))?;
+    if sack.len() > MAX_SACK_BYTES {
+        return Err(format!("the sack is over {} bytes", MAX_SACK_BYTES));
+    }
+    unpack_sack(&sack, into)?;
+    let checksum = bean_checksum(into).map_err(|e| format!("{}: {}", into.display(), e))?;
+    if published != checksum {
+        let _ = std::fs::remove_dir_all(into);
+        return Err(format!("what arrived doesn't match what was published ({} published, {} arrived)", published, checksum));
+    }
+    Ok(checksum)
+}
+
+/// Pack the roastery in `roastery_dir` into a .tar.gz sack, leaving out UNPUBLISHED and hidden files
+fn pack_sack(roastery_dir: &Path) -> std::io::Result<Vec<u8>> {
+    let mut sack = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    let mut waiting = vec![PathBuf::new()];
+    while let Some(relative_dir) = waiting.pop() {
+        let mut entries: Vec<_> = std::fs::read_dir(roastery_dir.join(&relative_dir))?.collect::<Result<_, _>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            if file_name.starts_with('.') || (relative_dir.as_os_str().is_empty() && UNPUBLISHED.contains(&file_name.as_str())) {
+                continue;
+            }
+            let relative = relative_dir.join(&file_name);
+            if entry.file_type()?.is_dir() {
+                waiting.push(relative);
+            } else {
+                sack.append_path_with_name(entry.path(), &relative)?;
+            }
+        }
+    }
+    sack.into_inner()?.finish()
+}
+
+/// `brew publish`: send the roastery in the current directory to the registry as a bean
+pub fn run_brew_publish() {
+    let manifest: RoasteryManifest = match std::fs::read_to_string("roastery.json") {
+        Ok(manifest) => match serde_json::from_str(&manifest) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                println!("[ERROR] roastery.json: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Err(_) => {
+            println!("[ERROR] there's no roastery.json here; `brew publish` sends the roastery it's run in (start one with `brew new`)");
+            std::process::exit(1);
+        }
+    };
+    let Some(token) = registry_token() else {
+        println!("[ERROR] publishing takes a token: set BREWCO_REGISTRY_TOKEN to one from `brew registry token <barista>`");
+        std::process::exit(1);
+    };
+    let sack = match pack_sack(Path::new(".")) {
+        Ok(sack) => sack,
+        Err(e) => {
+            println!("[ERROR] Couldn't pack the roastery: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let registry = registry_url();
+    let path = format!("/beans/{}/{}", manifest.roastery_name, manifest.roastery_version);
+    let published = registry_request("PUT", &registry, Some(&token), &path, REGISTRY_TIMEOUT)
+        .set("Content-Type", "application/gzip")
+        .send_bytes(&sack)
+        .map_err(registry_failure);
+    match published {
+        Ok(_) => println!(
+            "☕ Published '{}' {} to {} ({} bytes)",
+            manifest.roastery_name,
+            manifest.roastery_version,
+            registry,
+            sack.len()
+        ),
+        Err(why) => {
+            println!("[ERROR] Couldn't publish '{}' {} to {}: {}", manifest.roastery_name, manifest.roastery_version, registry, why);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh, empty directory under the system's temp dir
+    fn scratch_dir(label: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("brewco-registry-{}-{}", label, uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A roastery with a roastery.json and one recipe, packed as a sack
+    fn roastery_sack(name: &str, version: &str) -> Vec<u8> {
+        let dir = scratch_dir("roastery");
+        let manifest = json!({
+            "roastery_name": name, "roastery_version": version, "roastery_description": "", "head_barista": "ana",
+            "coffee_license": "MIT", "required_bean_dependencies": {}, "dev_brewing_dependencies": {},
+            "brewing_scripts": {}, "coffee_keywords": [], "roastery_homepage": null, "bean_repository": null,
+        });
+        std::fs::write(dir.join("roastery.json"), manifest.to_string()).unwrap();
+        std::fs::write(dir.join("main.brewco"), "pourout \"hi\"\n").unwrap();
+        pack_sack(&dir).unwrap()
+    }
+
+    /// A sack holding one entry whose name is written straight into the tar header
+    fn sack_with_raw_entry(name: &str, entry_type: tar::EntryType) -> Vec<u8> {
+        let mut header = tar::Header::new_old();
+        header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_entry_type(entry_type);
+        header.set_size(2);
+        header.set_mode(0o644);
+        header.set_cksum();
+        let mut sack = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        sack.append(&header, &b"hi"[..]).unwrap();
+        sack.into_inner().unwrap().finish().unwrap()
+    }
+
+    /// Serve `registry` on a free port for as long as the test runs, and give back its URL
+    fn serve(registry: BeanRegistry) -> String {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                registry.serve_request(request);
+            }
+        });
+        url
+    }
+
+    /// Serve one sack with whatever X-Bean-Checksum header `checksum` says, or none
+    fn serve_sack_once(sack: Vec<u8>, checksum: Option<&str>) -> String {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        let checksum = checksum.map(str::to_string);
+        std::thread::spawn(move || {
+            let request = server.recv().unwrap();
+            let mut response = Response::from_data(sack);
+            if let Some(checksum) = checksum {
+                response.add_header(Header::from_bytes("X-Bean-Checksum", checksum).unwrap());
+            }
+            let _ = request.respond(response);
+        });
+        url
+    }
+
+    fn publish(url: &str, token: Option<&str>, name: &str, version: &str) -> u16 {
+        match registry_request("PUT", url, token, &format!("/beans/{}/{}", name, version), REGISTRY_TIMEOUT).send_bytes(&roastery_sack(name, version)) {
+            Ok(response) => response.status(),
+            Err(ureq::Error::Status(code, _)) => code,
+            Err(e) => panic!("the registry didn't answer: {}", e),
+        }
+    }
+
+    fn token_for(store: &Path, barista: &str) -> String {
+        let token = format!("brewco_{}", uuid::Uuid::new_v4().simple());
+        let mut tokens = BeanRegistry::load_tokens(store).unwrap();
+        tokens.push(RegistryToken { barista: barista.to_string(), token_sha256: sha256_hex(token.as_bytes()) });
+        std::fs::write(store.join(TOKENS_FILE), serde_json::to_string(&tokens).unwrap()).unwrap();
+        token
+    }
+
+    #[test]
+    fn publishing_takes_a_token_and_keeps_beans_with_their_owner() {
+        let store = scratch_dir("store");
+        let (ana, bo) = (token_for(&store, "ana"), token_for(&store, "bo"));
+        let url = serve(BeanRegistry { store: store.clone(), private: false });
+
+        assert_eq!(publish(&url, None, "crema", "1.0.0"), 401);
+        assert_eq!(publish(&url, Some("brewco_not_a_token"), "crema", "1.0.0"), 401);
+        assert_eq!(publish(&url, Some(&ana), "crema", "1.0.0"), 201);
+        assert_eq!(publish(&url, Some(&ana), "crema", "1.0.0"), 409);
+        assert_eq!(publish(&url, Some(&bo), "crema", "1.1.0"), 403);
+        assert_eq!(publish(&url, Some(&ana), "crema", "1.10.0"), 201);
+
+        let info = registry_request("GET", &url, None, "/beans/crema", REGISTRY_TIMEOUT).call().unwrap().into_string().unwrap();
+        let info: CoffeeBeanPackageInfo = serde_json::from_str(&info).unwrap();
+        assert_eq!(info.available_versions, vec!["1.0.0", "1.10.0"]);
+        assert_eq!(info.latest_version, "1.10.0");
+    }
+
+    #[test]
+    fn a_private_registry_turns_away_reads_without_a_token() {
+        let store = scratch_dir("private");
+        let ana = token_for(&store, "ana");
+        let url = serve(BeanRegistry { store, private: true });
+        assert_eq!(publish(&url, Some(&ana), "crema", "1.0.0"), 201);
+        let anonymous = registry_request("GET", &url, None, "/beans/crema", REGISTRY_TIMEOUT).call();
+        assert!(matches!(anonymous, Err(ureq::Error::Status(401, _))));
+        assert!(registry_request("GET", &url, Some(&ana), "/beans/crema", REGISTRY_TIMEOUT).call().is_ok());
+    }
+
+    #[test]
+    fn bean_names_that_could_leave_the_store_are_refused() {
+        let url = serve(BeanRegistry { store: scratch_dir("names"), private: false });
+        for path in ["/beans/.hidden", "/beans/crema/..%2F..%2Ftokens", "/beans/crema/.1.0/sack", "/beans/cr%2Fema"] {
+            let answer = registry_request("GET", &url, None, path, REGISTRY_TIMEOUT).call();
+            assert!(matches!(answer, Err(ureq::Error::Status(400, _))), "{} wasn't refused", path);
+        }
+        assert!(unplain_bean("../crema", "1.0.0").is_some());
+        assert!(unplain_bean("crema", "1.0/../../x").is_some());
+        assert!(unplain_bean("crema", "1.0.0+build-7").is_none());
+    }
+
+    #[test]
+    fn fetched_sacks_are_held_to_the_published_checksum() {
+        let store = scratch_dir("fetch");
+        let ana = token_for(&store, "ana");
+        let url = serve(BeanRegistry { store, private: false });
+        assert_eq!(publish(&url, Some(&ana), "crema", "1.0.0"), 201);
+
+        let into = scratch_dir("fetched").join("crema@1.0.0");
+        let checksum = fetch_bean_sack(&url, None, "crema", "1.0.0", &into).unwrap();
+        assert_eq!(checksum, bean_checksum(&into).unwrap());
+        assert!(into.join("main.brewco").is_file());
+    }
+
+    #[test]
+    fn a_sack_without_a_checksum_is_not_unpacked() {
+        let into = scratch_dir("unchecked").join("crema@1.0.0");
+        let url = serve_sack_once(roastery_sack("crema", "1.0.0"), None);
+        let why = fetch_bean_sack(&url, None, "crema", "1.0.0", &into).unwrap_err();
+        assert!(why.contains("X-Bean-Checksum"), "{}", why);
+        assert!(!into.exists());
+    }
+
+    #[test]
+    fn a_sack_that_doesnt_match_its_checksum_is_thrown_away() {
+        let into = scratch_dir("tampered").join("crema@1.0.0");
+        let url = serve_sack_once(roastery_sack("crema", "1.0.0"), Some("0000"));
+        let why = fetch_bean_sack(&url, None, "crema", "1.0.0", &into).unwrap_err();
+        assert!(why.contains("doesn't match"), "{}", why);
+        assert!(!into.exists());
+    }
+
+    #[test]
+    fn unpacking_keeps_entries_inside_the_bean() {
+        let into = scratch_dir("unpack");
+        let why = unpack_sack(&sack_with_raw_entry("../escaped.brewco", tar::EntryType::Regular), &into.join("bean")).unwrap_err();
+        assert!(why.contains("outside the bean"), "{}", why);
+        assert!(!into.join("escaped.brewco").exists());
+
+        let why = unpack_sack(&sack_with_raw_entry("link", tar::EntryType::Symlink), &into.join("bean")).unwrap_err();
+        assert!(why.contains("isn't a plain file"), "{}", why);
+    }
+
+    #[test]
+    fn versions_order_numerically() {
+        assert_eq!(version_order("1.10.0", "1.9.0"), Ordering::Greater);
+        assert_eq!(version_order("2.0.0", "2.0.0"), Ordering::Equal);
+        assert_eq!(version_order("0.9", "0.10"), Ordering::Less);
+    }
+}
@@ -0,0 +1,223 @@
+/*
+ * 🌳 Brewco Bean Tree ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @orchard_note: "Every cup starts with knowing where its beans grew!" ✨
+ *
+ * `brew tree` prints the beans a roastery depends on, and the beans those
+ * depend on, with the version of each that's installed:
+ *
+ *     my_cafe 1.0.0
+ *     ├── coffee_math 1.1.0
+ *     │   └── number_grinder 0.3.0
+ *     └── espresso_utils (wants 2.0.0, not installed)
+ *
+ * and `brew why <bean>` lists every path from the roastery's own
+ * dependencies down to one bean. The tree comes from the roastery.json in
+ * the current directory and those of the beans installed under
+ * coffee_beans/ (as `name/` or `name@version/`); a bean seen again in
+ * another branch is printed once and marked (*) after that.
+ */
+
+// src/bean_tree.rs
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+const BEAN_STORAGE: &str = "coffee_beans";
+
+/// The bean names and wanted versions a roastery.json lists under `key`, sorted by name
+fn manifest_dependencies(manifest: &serde_json::Value, key: &str) -> Vec<(String, String)> {
+    let Some(dependencies) = manifest.get(key).and_then(|dependencies| dependencies.as_object()) else {
+        return Vec::new();
+    };
+    let dependencies: BTreeMap<&String, String> = dependencies
+        .iter()
+        .map(|(name, wanted)| (name, wanted.as_str().map_or_else(|| wanted.to_string(), str::to_string)))
+        .collect();
+    dependencies.into_iter().map(|(name, wanted)| (name.clone(), wanted)).collect()
+}
+
+fn read_manifest(path: &Path) -> Result<serde_json::Value, String> {
+    let manifest = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    serde_json::from_str(&manifest).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// A bean as installed under coffee_beans/
+struct InstalledBean {
+    version: Option<String>,
+    dependencies: Vec<(String, String)>,
+}
+
+/// The roastery's dependency graph, as found on disk
+pub struct BeanTree {
+    name: String,
+    version: Option<String>,
+    dependencies: Vec<(String, String)>,
+    dev_dependencies: Vec<(String, String)>,
+    installed: BTreeMap<String, InstalledBean>,
+}
+
+impl BeanTree {
+    /// Read the roastery.json in `roastery_dir` and every installed bean's
+    pub fn load(roastery_dir: &Path) -> Result<Self, String> {
+        let manifest_path = roastery_dir.join("roastery.json");
+        if !manifest_path.is_file() {
+            return Err(format!(
+                "there's no {}; `brew tree` and `brew why` read a roastery's (start one with `brew new`)",
+                manifest_path.display()
+            ));
+        }
+        let manifest = read_manifest(&manifest_path)?;
+        let mut installed = BTreeMap::new();
+        if let Ok(entries) = std::fs::read_dir(roastery_dir.join(BEAN_STORAGE)) {
+            let mut bean_dirs: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()).collect();
+            bean_dirs.sort();
+            for bean_dir in bean_dirs {
+                let dir_name = bean_dir.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+                let (name, dir_version) = match dir_name.split_once('@') {
+                    Some((name, version)) => (name.to_string(), Some(version.to_string())),
+                    None => (dir_name.clone(), None),
+                };
+                let bean_manifest = match bean_dir.join("roastery.json") {
+                    path if path.is_file() => Some(read_manifest(&path)?),
+                    _ => None,
+                };
+                let version = bean_manifest
+                    .as_ref()
+                    .and_then(|manifest| manifest.get("roastery_version")?.as_str().map(str::to_string))
+                    .or(dir_version);
+                let dependencies = bean_manifest
+                    .as_ref()
+                    .map(|manifest| manifest_dependencies(manifest, "required_bean_dependencies"))
+                    .unwrap_or_default();
+                installed.insert(name, InstalledBean { version, dependencies });
+            }
+        }
+        Ok(BeanTree {
+            name: manifest.get("roastery_name").and_then(|name| name.as_str()).unwrap_or("roastery").to_string(),
+            version: manifest.get("roastery_version").and_then(|version| version.as_str()).map(str::to_string),
+            dependencies: manifest_dependencies(&manifest, "required_bean_dependencies"),
+            dev_dependencies: manifest_dependencies(&manifest, "dev_brewing_dependencies"),
+            installed,
+        })
+    }
+
+    /// `name version`, or what was wanted when it isn't installed
+    fn bean_label(&self, name: &str, wanted: &str) -> String {
+        match self.installed.get(name) {
+            Some(InstalledBean { version: Some(version), .. }) => format!("{} {}", name, version),
+            Some(InstalledBean { version: None, .. }) => name.to_string(),
+            None => format!("{} (wants {}, not installed)", name, wanted),
+        }
+    }
+
+    fn bean_dependencies(&self, name: &str) -> &[(String, String)] {
+        self.installed.get(name).map_or(&[], |bean| bean.dependencies.as_slice())
+    }
+
+    /// Print `dependencies` under `prefix`, each bean's own below it
+    fn draw_branches(&self, dependencies: &[(String, String)], prefix: &str, drawn: &mut HashSet<String>, lines: &mut Vec<String>) {
+        for (index, (name, wanted)) in dependencies.iter().enumerate() {
+            let last = index + 1 == dependencies.len();
+            let (branch, below) = if last { ("└── ", "    ") } else { ("├── ", "│   ") };
+            let label = self.bean_label(name, wanted);
+            let own = self.bean_dependencies(name);
+            if !own.is_empty() && !drawn.insert(name.clone()) {
+                lines.push(format!("{}{}{} (*)", prefix, branch, label));
+                continue;
+            }
+            lines.push(format!("{}{}{}", prefix, branch, label));
+            self.draw_branches(own, &format!("{}{}", prefix, below), drawn, lines);
+        }
+    }
+
+    /// The whole tree, one line per bean
+    pub fn draw(&self) -> String {
+        let mut lines = vec![match &self.version {
+            Some(version) => format!("{} {}", self.name, version),
+            None => self.name.clone(),
+        }];
+        let mut drawn = HashSet::new();
+        self.draw_branches(&self.dependencies, "", &mut drawn, &mut lines);
+        if !self.dev_dependencies.is_empty() {
+            lines.push(String::new());
+            lines.push("[dev]".to_string());
+            self.draw_branches(&self.dev_dependencies, "", &mut drawn, &mut lines);
+        }
+        if self.dependencies.is_empty() && self.dev_dependencies.is_empty() {
+            lines.push("└── (no beans yet)".to_string());
+        }
+        lines.join("\n") + "\n"
+    }
+
+    /// Every path from the roastery down to `bean`, each as the labels along it
+    pub fn paths_to(&self, bean: &str) -> Vec<Vec<String>> {
+        fn walk(tree: &BeanTree, dependencies: &[(String, String)], bean: &str, path: &mut Vec<String>, on_path: &mut Vec<String>, found: &mut Vec<Vec<String>>) {
+            for (name, wanted) in dependencies {
+                // A bean that (somehow) depends on itself again can't lead anywhere new
+                if on_path.contains(name) {
+                    continue;
+                }
+                path.push(tree.bean_label(name, wanted));
+                if name == bean {
+                    found.push(path.clone());
+                } else {
+                    on_path.push(name.clone());
+                    walk(tree, tree.bean_dependencies(name), bean, path, on_path, found);
+                    on_path.pop();
+                }
+                path.pop();
+            }
+        }
+        let mut found = Vec::new();
+        let root = match &self.version {
+            Some(version) => format!("{} {}", self.name, version),
+            None => self.name.clone(),
+        };
+        walk(self, &self.dependencies, bean, &mut vec![root.clone()], &mut Vec::new(), &mut found);
+        walk(self, &self.dev_dependencies, bean, &mut vec![format!("{} [dev]", root)], &mut Vec::new(), &mut found);
+        found
+    }
+}
+
+/// `brew tree`: the current roastery's dependency tree
+pub fn run_brew_tree() {
+    match BeanTree::load(Path::new(".")) {
+        Ok(tree) => print!("{}", tree.draw()),
+        Err(msg) => {
+            println!("[ERROR] {}", msg);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// `brew why <bean>`: which of the roastery's own dependencies pull `bean` in, and through what
+pub fn run_brew_why(bean: &str) {
+    let tree = match BeanTree::load(Path::new(".")) {
+        Ok(tree) => tree,
+        Err(msg) => {
+            println!("[ERROR] {}", msg);
+            std::process::exit(1);
+        }
+    };
+    let paths = tree.paths_to(bean);
+    if paths.is_empty() {
+        println!("[ERROR] nothing in this roastery's tree depends on '{}'; `brew tree` shows what does get pulled in", bean);
+        std::process::exit(1);
+    }
+    let direct: Vec<&Vec<String>> = paths.iter().filter(|path| path.len() == 2).collect();
+    let through: HashSet<&String> = paths.iter().filter(|path| path.len() > 2).map(|path| &path[1]).collect();
+    println!("☕ {} is in the tree because:", paths[0].last().map_or(bean, String::as_str));
+    if !direct.is_empty() {
+        println!("  the roastery depends on it directly{}", if direct.iter().any(|path| path[0].ends_with("[dev]")) { " (as a dev bean)" } else { "" });
+    }
+    let mut through: Vec<&String> = through.into_iter().collect();
+    through.sort();
+    for top_level in through {
+        println!("  {} pulls it in:", top_level);
+        for path in paths.iter().filter(|path| path.len() > 2 && &path[1] == top_level) {
+            println!("    {}", path.join(" → "));
+        }
+    }
+}
@@ -0,0 +1,128 @@
+/*
+ * 💰 Brewco Big Brews ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @till_note: "Every cent counted, not a single grain spilled!" ✨
+ *
+ * A BigBrew is an exact decimal number of any size, for the scripts where
+ * 0.1 + 0.2 has to be 0.3: prices, totals, tax. Make one from a string with
+ * `big_brew("19.99")` (a plain number works too, but it has already been
+ * rounded to the nearest binary fraction by then), and the usual operators
+ * keep it exact. Mixing in a plain number turns the result into a BigBrew;
+ * `big_to_number` goes back.
+ *
+ * Big brews remember their decimal places, so `big_brew("1.10") * 2` pours
+ * out as 2.20. Division carries 100 significant digits.
+ */
+
+// src/big_brew.rs
+
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use bigdecimal::{BigDecimal, RoundingMode, ToPrimitive, Zero};
+
+use crate::ast::BinaryOperator;
+use crate::interpreter::{ControlFlow, Value};
+
+/// Places `big_round` can round to, either side of the decimal point
+const MAX_ROUND_PLACES: f64 = 1000.0;
+
+fn big_spill(message: impl Into<String>) -> ControlFlow {
+    ControlFlow::RuntimeError(message.into())
+}
+
+/// A plain number as the decimal it prints as, so 0.1 becomes exactly 0.1
+fn from_number(native: &str, n: f64) -> Result<BigDecimal, ControlFlow> {
+    if !n.is_finite() {
+        return Err(big_spill(format!("{}() can't make a big brew from {}", native, n)));
+    }
+    BigDecimal::from_str(&n.to_string()).map_err(|e| big_spill(format!("{}() couldn't read {}: {}", native, n, e)))
+}
+
+/// `big_brew(value)`: an exact decimal from a string like "19.99", a number, or another big brew
+pub fn big_brew(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [Value::String(text)] => {
+            let cleaned: String = text.trim().chars().filter(|&ch| ch != '_').collect();
+            BigDecimal::from_str(&cleaned)
+                .map(Value::BigBrew)
+                .map_err(|_| big_spill(format!("big_brew() couldn't read '{}' as a decimal number", text)))
+        }
+        [Value::Number(n)] => Ok(Value::BigBrew(from_number("big_brew", *n)?)),
+        [Value::BigBrew(big)] => Ok(Value::BigBrew(big.clone())),
+        [other] => Err(big_spill(format!("big_brew() expects a string or a number, but got a {}", other.type_name()))),
+        _ => Err(big_spill(format!("big_brew() expects 1 argument, but got {}", args.len()))),
+    }
+}
+
+/// `big_to_number(big)`: the nearest plain number
+pub fn big_to_number(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [Value::BigBrew(big)] => Ok(Value::Number(big.to_f64().unwrap_or(f64::NAN))),
+        [Value::Number(n)] => Ok(Value::Number(*n)),
+        [other] => Err(big_spill(format!("big_to_number() expects a big brew, but got a {}", other.type_name()))),
+        _ => Err(big_spill(format!("big_to_number() expects 1 argument, but got {}", args.len()))),
+    }
+}
+
+/// `big_round(big, places)`: round half away from zero to a number of decimal places
+pub fn big_round(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [Value::BigBrew(big), Value::Number(places)] if places.fract() == 0.0 && places.abs() <= MAX_ROUND_PLACES => {
+            Ok(Value::BigBrew(big.with_scale_round(*places as i64, RoundingMode::HalfUp)))
+        }
+        [Value::BigBrew(_), _] => Err(big_spill("big_round() expects the decimal places as a whole number")),
+        [other, _] => Err(big_spill(format!("big_round() expects a big brew, but got a {}", other.type_name()))),
+        _ => Err(big_spill(format!("big_round() expects 2 arguments, but got {}", args.len()))),
+    }
+}
+
+/// A big brew or a plain number as an exact decimal; None for anything else
+fn big_operand(value: &Value) -> Result<Option<BigDecimal>, ControlFlow> {
+    match value {
+        Value::BigBrew(big) => Ok(Some(big.clone())),
+        Value::Number(n) => from_number("big brew arithmetic", *n).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Order two numbers exactly, where either may be a big brew
+pub fn big_ordering(left: &Value, right: &Value) -> Result<Ordering, ControlFlow> {
+    match (big_operand(left)?, big_operand(right)?) {
+        (Some(l), Some(r)) => Ok(l.cmp(&r)),
+        _ => Err(big_spill(format!("Can't order a {} and a {} as numbers", left.type_name(), right.type_name()))),
+    }
+}
+
+/// A binary operator with a big brew on at least one side
+pub fn big_binary_op(left: &Value, op: &BinaryOperator, right: &Value) -> Result<Value, ControlFlow> {
+    let (l, r) = match (big_operand(left)?, big_operand(right)?) {
+        (Some(l), Some(r)) => (l, r),
+        // "Total: " + price
+        _ => {
+            return match (left, op, right) {
+                (Value::String(l), BinaryOperator::Add, r) => Ok(Value::String(format!("{}{}", l, r))),
+                (l, BinaryOperator::Add, Value::String(r)) => Ok(Value::String(format!("{}{}", l, r))),
+                (_, BinaryOperator::Equal, _) => Ok(Value::Boolean(false)),
+                (_, BinaryOperator::NotEqual, _) => Ok(Value::Boolean(true)),
+                _ => Err(big_spill(format!("Can't mix a {} and a {} in big brew arithmetic", left.type_name(), right.type_name()))),
+            };
+        }
+    };
+    match op {
+        BinaryOperator::Add => Ok(Value::BigBrew(l + r)),
+        BinaryOperator::Subtract => Ok(Value::BigBrew(l - r)),
+        BinaryOperator::Multiply => Ok(Value::BigBrew(l * r)),
+        BinaryOperator::Divide | BinaryOperator::Modulo if r.is_zero() => Err(big_spill("Division by zero!")),
+        BinaryOperator::Divide => Ok(Value::BigBrew(l / r)),
+        BinaryOperator::Modulo => Ok(Value::BigBrew(l % r)),
+        BinaryOperator::Equal => Ok(Value::Boolean(l == r)),
+        BinaryOperator::NotEqual => Ok(Value::Boolean(l != r)),
+        BinaryOperator::Greater => Ok(Value::Boolean(l > r)),
+        BinaryOperator::Less => Ok(Value::Boolean(l < r)),
+        BinaryOperator::GreaterEqual => Ok(Value::Boolean(l >= r)),
+        BinaryOperator::LessEqual => Ok(Value::Boolean(l <= r)),
+        _ => Err(big_spill("Invalid operation on big brews")),
+    }
+}
@@ -0,0 +1,111 @@
+/*
+ * 🍸 Brewco Brew Blends ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @mixology_note: "Two good brews poured together make a signature drink!" ✨
+ *
+ * Brews built out of other brews: `memoize_brew` remembers what a brew served
+ * for each set of arguments, `compose_brews(f, g)` pours g's result into f,
+ * and `partial_brew(f, ...)` fixes f's first arguments. A blend is a brew like
+ * any other — call it, pass it around, hand it to sort_cup_with — and
+ * `Interpreter::call_brew` unwraps it when it's called.
+ */
+
+// src/brew_blends.rs
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::flavor_set::BlendKey;
+use crate::interpreter::{ControlFlow, Value};
+
+/// What a `Value::Blend` wraps
+pub enum BrewBlend {
+    /// Results by argument list; arguments that can't go in a flavor set skip the cache
+    Memoized { brew: Value, cache: RefCell<HashMap<Vec<BlendKey>, Value>> },
+    /// `outer(inner(args...))`
+    Composed { outer: Value, inner: Value },
+    /// `brew(bound..., args...)`
+    Partial { brew: Value, bound: Vec<Value> },
+}
+
+impl BrewBlend {
+    /// How the blend shows up when poured out: the call that made it
+    pub fn recipe(&self) -> String {
+        match self {
+            BrewBlend::Memoized { brew, .. } => format!("memoize_brew({})", brew_name(brew)),
+            BrewBlend::Composed { outer, inner } => format!("compose_brews({}, {})", brew_name(outer), brew_name(inner)),
+            BrewBlend::Partial { brew, bound } => {
+                let bound: Vec<String> = bound.iter().map(|value| format!("{:?}", value)).collect();
+                format!("partial_brew({}, {})", brew_name(brew), bound.join(", "))
+            }
+        }
+    }
+
+    /// The cached result for these arguments, if there is one
+    pub fn remembered(&self, args: &[Value]) -> Option<Value> {
+        match self {
+            BrewBlend::Memoized { cache, .. } => cache.borrow().get(&cache_key(args)?).cloned(),
+            _ => None,
+        }
+    }
+
+    /// Remember what a memoized brew served for these arguments
+    pub fn remember(&self, args: &[Value], served: &Value) {
+        if let (BrewBlend::Memoized { cache, .. }, Some(key)) = (self, cache_key(args)) {
+            cache.borrow_mut().insert(key, served.clone());
+        }
+    }
+}
+
+fn cache_key(args: &[Value]) -> Option<Vec<BlendKey>> {
+    args.iter().map(BlendKey::of).collect()
+}
+
+fn brew_name(brew: &Value) -> String {
+    match brew {
+        Value::Blend(blend) => blend.recipe(),
+        Value::BoundMethod { class_name, .. } => format!("<{} method>", class_name),
+        _ => "<brew>".to_string(),
+    }
+}
+
+fn blend_value(blend: BrewBlend) -> Value {
+    Value::Blend(Rc::new(blend))
+}
+
+fn brew_arg(native: &str, value: &Value) -> Result<Value, ControlFlow> {
+    if value.is_brew() {
+        Ok(value.clone())
+    } else {
+        Err(ControlFlow::RuntimeError(format!("{}() expects a brew, but got a {}", native, value.type_name())))
+    }
+}
+
+/// `memoize_brew(f)`: a brew that calls f once per distinct argument list and remembers the result
+pub fn memoize_brew(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [brew] => Ok(blend_value(BrewBlend::Memoized { brew: brew_arg("memoize_brew", brew)?, cache: RefCell::new(HashMap::new()) })),
+        _ => Err(ControlFlow::RuntimeError(format!("memoize_brew() expects 1 argument, but got {}", args.len()))),
+    }
+}
+
+/// `compose_brews(f, g)`: a brew that serves f(g(args...))
+pub fn compose_brews(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [outer, inner] => Ok(blend_value(BrewBlend::Composed {
+            outer: brew_arg("compose_brews", outer)?,
+            inner: brew_arg("compose_brews", inner)?,
+        })),
+        _ => Err(ControlFlow::RuntimeError(format!("compose_brews() expects 2 arguments, but got {}", args.len()))),
+    }
+}
+
+/// `partial_brew(f, a, b, ...)`: a brew that serves f(a, b, ..., args...)
+pub fn partial_brew(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.split_first() {
+        Some((brew, bound)) => Ok(blend_value(BrewBlend::Partial { brew: brew_arg("partial_brew", brew)?, bound: bound.to_vec() })),
+        None => Err(ControlFlow::RuntimeError("partial_brew() expects a brew and the arguments to fix".to_string())),
+    }
+}
@@ -0,0 +1,475 @@
+/*
+ * 🧾 Brewco Command Line Orders ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @order_note: "Every order read back before it's brewed!" ✨
+ *
+ * Turns `brew`'s command line into a `BrewInvocation`. Flags can go before
+ * or after the recipe (`brew --turbo cafe.brewco`, `brew cafe.brewco --turbo`),
+ * `-e 'pourout "hi"'` brews a one-liner, `brew -` brews whatever is piped in,
+ * and everything after `--` belongs to the recipe itself. Unknown or
+ * misplaced flags are reported by name instead of being mistaken for a file.
+ */
+
+// src/brew_cli.rs
+
+use std::io::Read;
+
+use lexopt::{Arg, Parser, ValueExt};
+
+use crate::brew_doc::DocFormat;
+use crate::brew_inspect::InspectView;
+use crate::brew_tracer;
+use crate::coffee_io::RECIPE_TEMPLATES;
+use crate::cupping_bench::CuppingSettings;
+
+/// Where the recipe to brew comes from
+pub enum RecipeSource {
+    File(String),
+    Inline(String), // -e "<code>"
+    Stdin,          // `-`
+}
+
+impl RecipeSource {
+    /// How the recipe is named in spills and traces
+    pub fn label(&self) -> &str {
+        match self {
+            RecipeSource::File(path) => path,
+            RecipeSource::Inline(_) => "<-e>",
+            RecipeSource::Stdin => "<stdin>",
+        }
+    }
+
+    pub fn read(&self) -> Result<String, String> {
+        match self {
+            RecipeSource::File(path) => std::fs::read_to_string(path).map_err(|_| format!("Could not read file: {}", path)),
+            RecipeSource::Inline(code) => Ok(code.clone()),
+            RecipeSource::Stdin => {
+                let mut code = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut code)
+                    .map(|_| code)
+                    .map_err(|e| format!("Could not read the recipe from stdin: {}", e))
+            }
+        }
+    }
+}
+
+/// Which machine brews a recipe
+pub enum BrewEngine {
+    Interpreter,
+    Turbo,
+    Profile { folded: Option<String> },
+    Trace(u8),
+}
+
+pub enum BrewCommand {
+    Help,
+    Version,
+    Repl,
+    Run { source: RecipeSource, engine: BrewEngine, call: Option<String> },
+    Debug { source: RecipeSource, breakpoints: Vec<usize> },
+    Check { paths: Vec<String> },
+    Lint { paths: Vec<String>, config: Option<String> },
+    Test { paths: Vec<String>, update_snapshots: bool, watch: bool },
+    Fmt { paths: Vec<String>, check: bool },
+    Lsp,
+    Dap { port: u16, program: Option<String> },
+    Bench { file: String, settings: CuppingSettings },
+    Explain { code: Option<String> },
+    Watch { file: String },
+    New { name: String, template: String },
+    Doc { paths: Vec<String>, format: DocFormat, out: Option<String> },
+    Inspect { source: RecipeSource, view: InspectView, json: bool },
+    Kernel { connection_file: String },
+    KernelInstall,
+    Playground { port: u16, fuel: u64 },
+    Literate { paths: Vec<String>, check: bool },
+    Learn { lesson: Option<String> },
+    Tree,
+    Why { bean: String },
+    Audit,
+    Publish,
+    RegistryServe { port: u16, store: String, private: bool },
+    RegistryToken { barista: String, store: String },
+}
+
+/// Everything one `brew` command line asked for
+pub struct BrewInvocation {
+    pub command: BrewCommand,
+    pub deny_warnings: bool,
+    pub strict_brew: bool,
+    pub allow_fs: bool,
+    pub allow_exec: bool,
+    pub allow_net: bool,
+    pub lang: Option<String>, // --lang, which wins over BREWCO_LANG
+    pub script_args: Vec<String>, // everything after `--`
+}
+
+/// Where `brew playground` listens unless --port says otherwise
+const PLAYGROUND_PORT: u16 = 7878;
+
+/// Statements (and loop rounds) each playground recipe gets unless --fuel says otherwise
+const PLAYGROUND_FUEL: u64 = 100_000;
+
+/// Where `brew registry` serves from unless --port says otherwise
+const REGISTRY_PORT: u16 = 4873;
+
+/// Where `brew registry` keeps its beans and tokens unless --store says otherwise
+const REGISTRY_STORE: &str = "brewco_registry";
+
+/// Subcommands recognised as the first positional argument
+const SUBCOMMANDS: &[&str] = &["help", "repl", "debug", "check", "lint", "test", "fmt", "lsp", "dap", "bench", "explain", "watch", "new", "doc", "inspect", "kernel", "playground", "literate", "learn", "tree", "why", "audit", "publish", "registry"];
+
+/// Parse `brew`'s arguments, binary name first (as from `std::env::args`)
+pub fn parse_brew_args(args: Vec<String>) -> Result<BrewInvocation, String> {
+    let (ours, script_args) = match args.iter().position(|arg| arg == "--") {
+        Some(split) => (args[..split].to_vec(), args[split + 1..].to_vec()),
+        None => (args, Vec::new()),
+    };
+
+    let mut subcommand: Option<String> = None;
+    let mut positionals: Vec<String> = Vec::new();
+    let mut inline_code: Option<String> = None;
+    let mut engines: Vec<(&str, BrewEngine)> = Vec::new();
+    let mut folded: Option<String> = None;
+    let mut strict_brew = false;
+    let mut lang: Option<String> = None;
+    let mut call: Option<String> = None;
+    let (mut deny_warnings, mut allow_fs, mut allow_exec, mut allow_net) = (false, false, false, false);
+    let (mut help, mut version) = (false, false);
+    // Flags that only one subcommand understands, checked once we know which one it is
+    let mut breakpoints: Vec<usize> = Vec::new();
+    let mut config: Option<String> = None;
+    let mut port: Option<u16> = None;
+    let mut fuel: Option<u64> = None;
+    let mut settings = CuppingSettings::default();
+    let (mut update_snapshots, mut check, mut watch) = (false, false, false);
+    let mut template: Option<String> = None;
+    let mut doc_format = DocFormat::Markdown;
+    let mut doc_out: Option<String> = None;
+    let mut inspect_views: Vec<(&str, InspectView)> = Vec::new();
+    let mut json = false;
+    let mut install = false;
+    let mut store: Option<String> = None;
+    let mut private = false;
+    let mut owned_flags: Vec<(&str, &[&str])> = Vec::new(); // (flag, subcommands it belongs to)
+
+    let mut parser = Parser::from_iter(ours);
+    while let Some(arg) = parser.next().map_err(|e| e.to_string())? {
+        match arg {
+            Arg::Short('h') | Arg::Long("help") => help = true,
+            Arg::Short('V') | Arg::Long("version") => version = true,
+            Arg::Short('e') | Arg::Long("eval") => {
+                if inline_code.is_some() {
+                    return Err("-e was given twice; put the whole one-liner in one -e".to_string());
+                }
+                inline_code = Some(flag_value(&mut parser, "-e", "some code to brew")?);
+            }
+            Arg::Long("deny-warnings") => deny_warnings = true,
+            Arg::Long("strict-brew") => strict_brew = true,
+            Arg::Long("lang") => lang = Some(flag_value(&mut parser, "--lang", "a language code like es")?),
+            Arg::Long("call") => call = Some(flag_value(&mut parser, "--call", "the name of a brew to call")?),
+            Arg::Long("allow-fs") => allow_fs = true,
+            Arg::Long("allow-exec") => allow_exec = true,
+            Arg::Long("allow-net") => allow_net = true,
+            Arg::Long("turbo") => engines.push(("--turbo", BrewEngine::Turbo)),
+            Arg::Long("profile") => engines.push(("--profile", BrewEngine::Profile { folded: None })),
+            Arg::Long("folded") => folded = Some(flag_value(&mut parser, "--folded", "a file to write folded stacks to")?),
+            Arg::Long("trace") => {
+                let verbosity = match parser.optional_value() {
+                    None => brew_tracer::TRACE_STATEMENTS,
+                    Some(level) => match level.string().ok().and_then(|level| level.parse::<u8>().ok()) {
+                        Some(level) if (brew_tracer::TRACE_STATEMENTS..=brew_tracer::TRACE_BREWS).contains(&level) => level,
+                        _ => return Err("--trace verbosity must be 1, 2 or 3".to_string()),
+                    },
+                };
+                engines.push(("--trace", BrewEngine::Trace(verbosity)));
+            }
+            Arg::Long("break") => {
+                owned_flags.push(("--break", &["debug"]));
+                breakpoints.push(number_value(&mut parser, "--break", "a line number")?);
+            }
+            Arg::Long("config") => {
+                owned_flags.push(("--config", &["lint"]));
+                config = Some(flag_value(&mut parser, "--config", "a brewlint.toml path")?);
+            }
+            Arg::Long("update-snapshots") => {
+                owned_flags.push(("--update-snapshots", &["test"]));
+                update_snapshots = true;
+            }
+            Arg::Long("watch") => {
+                owned_flags.push(("--watch", &["test"]));
+                watch = true;
+            }
+            Arg::Long("check") => {
+                owned_flags.push(("--check", &["fmt", "literate"]));
+                check = true;
+            }
+            Arg::Long("template") => {
+                owned_flags.push(("--template", &["new"]));
+                template = Some(flag_value(&mut parser, "--template", "a template name like basic")?);
+            }
+            Arg::Long("format") => {
+                owned_flags.push(("--format", &["doc"]));
+                let name = flag_value(&mut parser, "--format", "markdown or html")?;
+                doc_format = DocFormat::from_name(&name).ok_or_else(|| format!("--format expects markdown or html, found '{}'", name))?;
+            }
+            Arg::Long("out") => {
+                owned_flags.push(("--out", &["doc"]));
+                doc_out = Some(flag_value(&mut parser, "--out", "a file to write the docs to")?);
+            }
+            Arg::Long("tokens") => {
+                owned_flags.push(("--tokens", &["inspect"]));
+                inspect_views.push(("--tokens", InspectView::Tokens));
+            }
+            Arg::Long("ast") => {
+                owned_flags.push(("--ast", &["inspect"]));
+                inspect_views.push(("--ast", InspectView::Ast));
+            }
+            Arg::Long("source") => {
+                owned_flags.push(("--source", &["inspect"]));
+                inspect_views.push(("--source", InspectView::Source));
+            }
+            Arg::Long("json") => {
+                owned_flags.push(("--json", &["inspect"]));
+                json = true;
+            }
+            Arg::Long("install") => {
+                owned_flags.push(("--install", &["kernel"]));
+                install = true;
+            }
+            Arg::Long("port") => {
+                owned_flags.push(("--port", &["dap", "playground", "registry"]));
+                port = Some(number_value(&mut parser, "--port", "a port number")?);
+            }
+            Arg::Long("store") => {
+                owned_flags.push(("--store", &["registry"]));
+                store = Some(flag_value(&mut parser, "--store", "a directory for the registry's beans")?);
+            }
+            Arg::Long("private") => {
+                owned_flags.push(("--private", &["registry"]));
+                private = true;
+            }
+            Arg::Long("fuel") => {
+                owned_flags.push(("--fuel", &["playground"]));
+                fuel = Some(number_value::<u64>(&mut parser, "--fuel", "a whole number of steps")?.max(1));
+            }
+            Arg::Long("iterations") => {
+                owned_flags.push(("--iterations", &["bench"]));
+                settings.iterations = number_value::<usize>(&mut parser, "--iterations", "a whole number of cups")?.max(1);
+            }
+            Arg::Long("warmup") => {
+                owned_flags.push(("--warmup", &["bench"]));
+                settings.warmup = number_value(&mut parser, "--warmup", "a whole number of cups")?;
+            }
+            Arg::Value(value) => {
+                let value = value.string().map_err(|e| e.to_string())?;
+                if subcommand.is_none() && positionals.is_empty() && SUBCOMMANDS.contains(&value.as_str()) {
+                    subcommand = Some(value);
+                } else {
+                    positionals.push(value);
+                }
+            }
+            Arg::Short(flag) => return Err(format!("unknown flag '-{}'", flag)),
+            Arg::Long(flag) => return Err(format!("unknown flag '--{}'", flag)),
+        }
+    }
+
+    let subcommand = subcommand.as_deref();
+    if let Some(&(flag, owners)) = owned_flags.iter().find(|(_, owners)| !subcommand.is_some_and(|sub| owners.contains(&sub))) {
+        let owners: Vec<String> = owners.iter().map(|owner| format!("`brew {}`", owner)).collect();
+        return Err(format!("{} only works with {}", flag, owners.join(" or ")));
+    }
+    if engines.len() > 1 {
+        let names: Vec<&str> = engines.iter().map(|(name, _)| *name).collect();
+        return Err(format!("pick one of {}, not several", names.join(", ")));
+    }
+    let engine = match engines.pop() {
+        Some((_, BrewEngine::Profile { .. })) => BrewEngine::Profile { folded: folded.take() },
+        Some((_, engine)) => engine,
+        None => BrewEngine::Interpreter,
+    };
+    if folded.is_some() {
+        return Err("--folded only works with --profile".to_string());
+    }
+    if subcommand.is_some() && subcommand != Some("watch") && !matches!(engine, BrewEngine::Interpreter) {
+        return Err("--turbo, --profile and --trace pick how a recipe brews; they don't go with subcommands".to_string());
+    }
+    if call.is_some() && (subcommand.is_some() || !matches!(engine, BrewEngine::Interpreter)) {
+        return Err("--call brews a recipe on the interpreter and then calls one of its brews; it doesn't go with subcommands, --turbo, --profile or --trace".to_string());
+    }
+    if subcommand.is_some() && !matches!(subcommand, Some("debug" | "inspect")) && inline_code.is_some() {
+        return Err("-e brews a one-liner; it doesn't go with subcommands".to_string());
+    }
+
+    let command = if help || subcommand == Some("help") {
+        BrewCommand::Help
+    } else if version {
+        BrewCommand::Version
+    } else {
+        match subcommand {
+            Some("repl") => {
+                no_positionals("repl", &positionals)?;
+                BrewCommand::Repl
+            }
+            Some("debug") => match recipe_source("debug", inline_code, positionals)? {
+                RecipeSource::Stdin => return Err("`brew debug` reads its commands from stdin, so the recipe can't come from there too".to_string()),
+                source => BrewCommand::Debug { source, breakpoints },
+            },
+            Some("check") => BrewCommand::Check { paths: positionals },
+            Some("lint") => BrewCommand::Lint { paths: positionals, config },
+            Some("test") => BrewCommand::Test { paths: positionals, update_snapshots, watch },
+            Some("fmt") => BrewCommand::Fmt { paths: positionals, check },
+            Some("lsp") => {
+                no_positionals("lsp", &positionals)?;
+                BrewCommand::Lsp
+            }
+            Some("dap") => match positionals.as_slice() {
+                [] | [_] => BrewCommand::Dap {
+                    port: port.unwrap_or(crate::barista_debug_adapter::BARISTA_DAP_PORT),
+                    program: positionals.first().cloned(),
+                },
+                [_, extra, ..] => return Err(format!("unexpected argument '{}'; `brew dap` waits with one recipe", extra)),
+            },
+            Some("bench") => match positionals.as_slice() {
+                [file] => BrewCommand::Bench { file: file.clone(), settings },
+                [] => return Err("`brew bench` needs a recipe to time".to_string()),
+                [_, extra, ..] => return Err(format!("unexpected argument '{}'; `brew bench` times one recipe", extra)),
+            },
+            Some("watch") => match recipe_source("watch", inline_code, positionals)? {
+                RecipeSource::File(file) => BrewCommand::Watch { file },
+                _ => return Err("`brew watch` needs a recipe file to watch; stdin has nothing to watch".to_string()),
+            },
+            Some("new") => {
+                let template = template.unwrap_or_else(|| "basic".to_string());
+                if !RECIPE_TEMPLATES.contains(&template.as_str()) {
+                    return Err(format!("--template must be one of {}, found '{}'", RECIPE_TEMPLATES.join(", "), template));
+                }
+                match positionals.as_slice() {
+                    [name] => BrewCommand::New { name: name.clone(), template },
+                    [] => return Err("`brew new` needs a name for the roastery, like `brew new my_cafe`".to_string()),
+                    [_, extra, ..] => return Err(format!("unexpected argument '{}'; `brew new` makes one roastery", extra)),
+                }
+            }
+            Some("doc") => BrewCommand::Doc { paths: positionals, format: doc_format, out: doc_out },
+            Some("inspect") => {
+                let view = match inspect_views.as_slice() {
+                    [(_, view)] => *view,
+                    [] => return Err("`brew inspect` needs --tokens, --ast or --source to say what to show".to_string()),
+                    _ => return Err("pick one of --tokens, --ast and --source".to_string()),
+                };
+                BrewCommand::Inspect { source: recipe_source("inspect", inline_code, positionals)?, view, json }
+            }
+            Some("kernel") => match positionals.as_slice() {
+                [] if install => BrewCommand::KernelInstall,
+                [_, ..] if install => return Err("`brew kernel --install` takes no connection file; Jupyter passes one when it starts the kernel".to_string()),
+                [connection_file] => BrewCommand::Kernel { connection_file: connection_file.clone() },
+                [] => return Err("`brew kernel` needs the connection file Jupyter gives it, or --install to register the kernel".to_string()),
+                [_, extra, ..] => return Err(format!("unexpected argument '{}'; `brew kernel` takes one connection file", extra)),
+            },
+            Some("playground") => {
+                no_positionals("playground", &positionals)?;
+                if allow_fs || allow_exec || allow_net {
+                    return Err("the playground brews anyone's recipe in a sandbox; --allow-fs, --allow-exec and --allow-net don't open it up".to_string());
+                }
+                BrewCommand::Playground {
+                    port: port.unwrap_or(PLAYGROUND_PORT),
+                    fuel: fuel.unwrap_or(PLAYGROUND_FUEL),
+                }
+            }
+            Some("learn") => match positionals.as_slice() {
+                [] => BrewCommand::Learn { lesson: None },
+                [lesson] => BrewCommand::Learn { lesson: Some(lesson.clone()) },
+                [_, extra, ..] => return Err(format!("unexpected argument '{}'; `brew learn` takes one lesson", extra)),
+            },
+            Some("literate") if positionals.is_empty() => {
+                return Err("`brew literate` needs a Markdown file whose ```brewco blocks it brews".to_string());
+            }
+            Some("literate") => BrewCommand::Literate { paths: positionals, check },
+            Some("tree") => {
+                no_positionals("tree", &positionals)?;
+                BrewCommand::Tree
+            }
+            Some("audit") => {
+                no_positionals("audit", &positionals)?;
+                BrewCommand::Audit
+            }
+            Some("publish") => {
+                no_positionals("publish", &positionals)?;
+                BrewCommand::Publish
+            }
+            Some("registry") => {
+                let store = store.unwrap_or_else(|| REGISTRY_STORE.to_string());
+                match positionals.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+                    ["serve"] => BrewCommand::RegistryServe { port: port.unwrap_or(REGISTRY_PORT), store, private },
+                    ["token", barista] if port.is_none() && !private => BrewCommand::RegistryToken { barista: barista.to_string(), store },
+                    ["token", _] => return Err("--port and --private are for `brew registry serve`; `brew registry token` only takes --store".to_string()),
+                    ["token"] => return Err("`brew registry token` needs the barista the token is for, like `brew registry token khushi`".to_string()),
+                    ["serve", extra, ..] | ["token", _, extra, ..] => {
+                        return Err(format!("unexpected argument '{}'; `brew registry serve` takes none, `brew registry token` one barista", extra));
+                    }
+                    _ => return Err("`brew registry` needs serve or token <barista>".to_string()),
+                }
+            }
+            Some("why") => match positionals.as_slice() {
+                [bean] => BrewCommand::Why { bean: bean.clone() },
+                [] => return Err("`brew why` needs the bean to explain, like `brew why number_grinder`".to_string()),
+                [_, extra, ..] => return Err(format!("unexpected argument '{}'; `brew why` explains one bean", extra)),
+            },
+            Some("explain") => match positionals.as_slice() {
+                [] => BrewCommand::Explain { code: None },
+                [code] => BrewCommand::Explain { code: Some(code.clone()) },
+                [_, extra, ..] => return Err(format!("unexpected argument '{}'; `brew explain` takes one spill code", extra)),
+            },
+            _ if call.is_some() && inline_code.is_none() && positionals.is_empty() => {
+                return Err("--call needs a recipe whose brew it calls".to_string());
+            }
+            _ if inline_code.is_none() && positionals.is_empty() && !matches!(engine, BrewEngine::Interpreter) => {
+                return Err("which recipe should brew? Give a file or -e \"<code>\"".to_string());
+            }
+            _ if inline_code.is_none() && positionals.is_empty() => BrewCommand::Repl,
+            _ => BrewCommand::Run { source: recipe_source("brew", inline_code, positionals)?, engine, call },
+        }
+    };
+
+    if !script_args.is_empty() && !matches!(command, BrewCommand::Run { .. } | BrewCommand::Debug { .. } | BrewCommand::Watch { .. }) {
+        return Err("arguments after -- are for a recipe, but nothing is being brewed".to_string());
+    }
+    Ok(BrewInvocation { command, deny_warnings, strict_brew, allow_fs, allow_exec, allow_net, lang, script_args })
+}
+
+/// The recipe a brewing command works on: one file, or the -e one-liner
+fn recipe_source(command: &str, inline_code: Option<String>, positionals: Vec<String>) -> Result<RecipeSource, String> {
+    match (inline_code, positionals.as_slice()) {
+        (Some(code), []) => Ok(RecipeSource::Inline(code)),
+        (Some(_), [file, ..]) => Err(format!("give either a recipe file ('{}') or -e code, not both", file)),
+        (None, [file]) if file == "-" => Ok(RecipeSource::Stdin),
+        (None, [file]) => Ok(RecipeSource::File(file.clone())),
+        (None, []) => Err(format!("`brew {}` needs a recipe file or -e \"<code>\"", command)),
+        (None, [_, extra, ..]) => Err(format!(
+            "unexpected argument '{}'; put arguments for the recipe after --, like `brew cafe.brewco -- {}`",
+            extra, extra
+        )),
+    }
+}
+
+fn no_positionals(command: &str, positionals: &[String]) -> Result<(), String> {
+    match positionals.first() {
+        Some(extra) => Err(format!("unexpected argument '{}'; `brew {}` doesn't take any", extra, command)),
+        None => Ok(()),
+    }
+}
+
+fn flag_value(parser: &mut Parser, flag: &str, wanted: &str) -> Result<String, String> {
+    parser
+        .value()
+        .and_then(|value| value.string())
+        .map_err(|_| format!("{} expects {}", flag, wanted))
+}
+
+fn number_value<T: std::str::FromStr>(parser: &mut Parser, flag: &str, wanted: &str) -> Result<T, String> {
+    flag_value(parser, flag, wanted)?
+        .parse::<T>()
+        .map_err(|_| format!("{} expects {}", flag, wanted))
+}
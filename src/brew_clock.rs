@@ -0,0 +1,284 @@
+/*
+ * ⏰ Brewco BrewClock ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @timing_note: "A perfect pour-over is all about the timing!" ✨
+ *
+ * Dates and times for recipes. A moment is a plain number: milliseconds since
+ * the Unix epoch, so moments compare and sort like any other number. Durations
+ * are BrewDuration beans holding `millis`. Formatting and parsing use strftime
+ * patterns (`%Y-%m-%d %H:%M`), and every native that shows or reads a wall
+ * clock takes an optional zone: "UTC" (the default), "local", an offset like
+ * "+05:30", or an IANA name like "Europe/Paris".
+ */
+
+// src/brew_clock.rs
+
+use std::collections::HashMap;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Datelike, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
+
+use crate::interpreter::{ControlFlow, Value};
+
+/// How moments are shown when no pattern is given: RFC 3339 with milliseconds
+const DEFAULT_CLOCK_PATTERN: &str = "%Y-%m-%dT%H:%M:%S%.3f%:z";
+
+/// Where a wall clock hangs
+enum BrewZone {
+    Utc,
+    Local,
+    Fixed(FixedOffset),
+    Named(chrono_tz::Tz),
+}
+
+impl BrewZone {
+    fn parse(native: &str, zone: Option<&Value>) -> Result<BrewZone, ControlFlow> {
+        let name = match zone {
+            None => return Ok(BrewZone::Utc),
+            Some(Value::String(name)) => name.trim(),
+            Some(other) => return Err(clock_spill(native, format!("expects the zone as a string, but got {:?}", other))),
+        };
+        if name.eq_ignore_ascii_case("utc") || name == "Z" {
+            return Ok(BrewZone::Utc);
+        }
+        if name.eq_ignore_ascii_case("local") {
+            return Ok(BrewZone::Local);
+        }
+        if let Ok(offset) = name.parse::<FixedOffset>() {
+            return Ok(BrewZone::Fixed(offset));
+        }
+        name.parse::<chrono_tz::Tz>().map(BrewZone::Named).map_err(|_| {
+            clock_spill(native, format!("doesn't know the zone '{}'; try \"UTC\", \"local\", \"+05:30\" or a name like \"Europe/Paris\"", name))
+        })
+    }
+
+    /// The wall clock reading at a moment
+    fn at(&self, moment: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            BrewZone::Utc => moment.fixed_offset(),
+            BrewZone::Local => moment.with_timezone(&Local).fixed_offset(),
+            BrewZone::Fixed(offset) => moment.with_timezone(offset),
+            BrewZone::Named(tz) => moment.with_timezone(tz).fixed_offset(),
+        }
+    }
+
+    /// Show a moment on this clock. Named zones format themselves, so `%Z` reads "EST"
+    fn format(&self, moment: DateTime<Utc>, pattern: &str) -> String {
+        match self {
+            BrewZone::Named(tz) => moment.with_timezone(tz).format(pattern).to_string(),
+            _ => self.at(moment).format(pattern).to_string(),
+        }
+    }
+
+    /// The moment a wall clock reading names. Readings skipped by a clock change
+    /// don't exist; repeated ones pick the earlier moment.
+    fn localize(&self, reading: NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+        match self {
+            BrewZone::Utc => Some(reading.and_utc().fixed_offset()),
+            BrewZone::Local => Local.from_local_datetime(&reading).earliest().map(|moment| moment.fixed_offset()),
+            BrewZone::Fixed(offset) => offset.from_local_datetime(&reading).single(),
+            BrewZone::Named(tz) => tz.from_local_datetime(&reading).earliest().map(|moment| moment.fixed_offset()),
+        }
+    }
+}
+
+fn clock_spill(native: &str, message: String) -> ControlFlow {
+    ControlFlow::RuntimeError(format!("{}() {}", native, message))
+}
+
+fn moment_arg(native: &str, value: Option<&Value>) -> Result<DateTime<Utc>, ControlFlow> {
+    match value {
+        Some(Value::Number(millis)) if millis.is_finite() => DateTime::from_timestamp_millis(millis.round() as i64)
+            .ok_or_else(|| clock_spill(native, format!("can't place {} ms on the calendar", millis))),
+        Some(other) => Err(clock_spill(native, format!("expects a moment (milliseconds since the epoch), but got {:?}", other))),
+        None => Err(clock_spill(native, "expects a moment (milliseconds since the epoch)".to_string())),
+    }
+}
+
+fn moment_value(moment: DateTime<FixedOffset>) -> Value {
+    Value::Number(moment.timestamp_millis() as f64)
+}
+
+fn pattern_arg<'a>(native: &str, value: Option<&'a Value>) -> Result<Option<&'a str>, ControlFlow> {
+    match value {
+        None => Ok(None),
+        Some(Value::String(pattern)) => {
+            // chrono panics on a bad pattern at format time, so check it up front
+            if StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error)) {
+                return Err(clock_spill(native, format!("can't make sense of the pattern '{}'", pattern)));
+            }
+            Ok(Some(pattern))
+        }
+        Some(other) => Err(clock_spill(native, format!("expects the pattern as a string, but got {:?}", other))),
+    }
+}
+
+fn arity(native: &str, args: &[Value], range: std::ops::RangeInclusive<usize>) -> Result<(), ControlFlow> {
+    if range.contains(&args.len()) {
+        return Ok(());
+    }
+    let expected = if range.start() == range.end() {
+        range.start().to_string()
+    } else {
+        format!("{} to {}", range.start(), range.end())
+    };
+    Err(clock_spill(native, format!("expects {} arguments, but got {}", expected, args.len())))
+}
+
+/// `clock_now()`: this moment, to the millisecond
+pub fn clock_now(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    arity("clock_now", &args, 0..=0)?;
+    Ok(moment_value(Utc::now().fixed_offset()))
+}
+
+/// `clock_format(moment, pattern?, zone?)`
+pub fn clock_format(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    arity("clock_format", &args, 1..=3)?;
+    let moment = moment_arg("clock_format", args.first())?;
+    let pattern = pattern_arg("clock_format", args.get(1))?.unwrap_or(DEFAULT_CLOCK_PATTERN);
+    let zone = BrewZone::parse("clock_format", args.get(2))?;
+    Ok(Value::String(zone.format(moment, pattern)))
+}
+
+/// `clock_parse(text, pattern?, zone?)`: without a pattern the text must be RFC 3339.
+/// Readings without an offset are taken in `zone`; a date alone means midnight.
+pub fn clock_parse(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    arity("clock_parse", &args, 1..=3)?;
+    let text = match args.first() {
+        Some(Value::String(text)) => text.trim(),
+        _ => return Err(clock_spill("clock_parse", "expects the text to parse as a string".to_string())),
+    };
+    let zone = BrewZone::parse("clock_parse", args.get(2))?;
+    let Some(pattern) = pattern_arg("clock_parse", args.get(1))? else {
+        return DateTime::parse_from_rfc3339(text)
+            .map(moment_value)
+            .map_err(|e| clock_spill("clock_parse", format!("couldn't read '{}' as an RFC 3339 moment: {}", text, e)));
+    };
+    if let Ok(moment) = DateTime::parse_from_str(text, pattern) {
+        return Ok(moment_value(moment));
+    }
+    let reading = match NaiveDateTime::parse_from_str(text, pattern) {
+        Ok(reading) => reading,
+        Err(e) => match NaiveDate::parse_from_str(text, pattern) {
+            Ok(date) => date.and_time(chrono::NaiveTime::MIN),
+            Err(_) => return Err(clock_spill("clock_parse", format!("couldn't read '{}' with the pattern '{}': {}", text, pattern, e))),
+        },
+    };
+    zone.localize(reading)
+        .map(moment_value)
+        .ok_or_else(|| clock_spill("clock_parse", format!("'{}' never happens on that clock (skipped by a clock change)", text)))
+}
+
+/// `clock_parts(moment, zone?)`: the calendar fields of a moment as a ClockParts bean
+pub fn clock_parts(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    arity("clock_parts", &args, 1..=2)?;
+    let moment = moment_arg("clock_parts", args.first())?;
+    let reading = BrewZone::parse("clock_parts", args.get(1))?.at(moment);
+    let mut fields = HashMap::new();
+    for (name, part) in [
+        ("year", reading.year() as f64),
+        ("month", reading.month() as f64),
+        ("day", reading.day() as f64),
+        ("hour", reading.hour() as f64),
+        ("minute", reading.minute() as f64),
+        ("second", reading.second() as f64),
+        ("millisecond", reading.timestamp_subsec_millis() as f64),
+        ("day_of_year", reading.ordinal() as f64),
+        ("offset_minutes", (reading.offset().local_minus_utc() / 60) as f64),
+    ] {
+        fields.insert(name.to_string(), Value::Number(part));
+    }
+    fields.insert("weekday".to_string(), Value::String(reading.format("%A").to_string()));
+    Ok(Value::object("ClockParts", fields))
+}
+
+/// `clock_add_months(moment, months, zone?)`: calendar months on the zone's wall clock.
+/// Days past the end of the new month land on its last day (Jan 31 + 1 month = Feb 28/29).
+pub fn clock_add_months(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    arity("clock_add_months", &args, 2..=3)?;
+    let moment = moment_arg("clock_add_months", args.first())?;
+    let months = match args.get(1) {
+        Some(Value::Number(months)) if months.fract() == 0.0 && months.abs() <= u32::MAX as f64 => *months,
+        _ => return Err(clock_spill("clock_add_months", "expects a whole number of months".to_string())),
+    };
+    let zone = BrewZone::parse("clock_add_months", args.get(2))?;
+    let reading = zone.at(moment).naive_local();
+    let shifted = if months >= 0.0 {
+        reading.checked_add_months(Months::new(months as u32))
+    } else {
+        reading.checked_sub_months(Months::new(-months as u32))
+    };
+    shifted
+        .and_then(|shifted| zone.localize(shifted))
+        .map(moment_value)
+        .ok_or_else(|| clock_spill("clock_add_months", format!("can't move {} months from there", months)))
+}
+
+// Durations
+
+/// Milliseconds in one of each duration unit
+fn unit_millis(native: &str, unit: Option<&Value>) -> Result<f64, ControlFlow> {
+    let Some(Value::String(unit)) = unit else {
+        return Err(clock_spill(native, "expects a unit: ms, seconds, minutes, hours, days or weeks".to_string()));
+    };
+    match unit.as_str() {
+        "ms" | "millisecond" | "milliseconds" => Ok(1.0),
+        "s" | "sec" | "secs" | "second" | "seconds" => Ok(1_000.0),
+        "min" | "mins" | "minute" | "minutes" => Ok(60_000.0),
+        "h" | "hour" | "hours" => Ok(3_600_000.0),
+        "d" | "day" | "days" => Ok(86_400_000.0),
+        "w" | "week" | "weeks" => Ok(604_800_000.0),
+        _ => Err(clock_spill(native, format!("doesn't know the unit '{}'; try ms, seconds, minutes, hours, days or weeks", unit))),
+    }
+}
+
+fn duration_value(millis: f64) -> Value {
+    let mut fields = HashMap::new();
+    fields.insert("millis".to_string(), Value::Number(millis));
+    Value::object("BrewDuration", fields)
+}
+
+fn duration_arg(native: &str, value: Option<&Value>) -> Result<f64, ControlFlow> {
+    if let Some(Value::Object { class_name, fields }) = value {
+        if class_name == "BrewDuration" {
+            if let Some(Value::Number(millis)) = fields.get("millis") {
+                return Ok(*millis);
+            }
+        }
+    }
+    Err(clock_spill(native, "expects a BrewDuration (see brew_duration())".to_string()))
+}
+
+/// `brew_duration(amount, unit)`: e.g. `brew_duration(90, "minutes")`
+pub fn brew_duration(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    arity("brew_duration", &args, 2..=2)?;
+    let amount = match &args[0] {
+        Value::Number(amount) if amount.is_finite() => *amount,
+        other => return Err(clock_spill("brew_duration", format!("expects a number of units, but got {:?}", other))),
+    };
+    Ok(duration_value(amount * unit_millis("brew_duration", args.get(1))?))
+}
+
+/// `duration_in(duration, unit)`: how many of `unit` fit in it, fractions included
+pub fn duration_in(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    arity("duration_in", &args, 2..=2)?;
+    let millis = duration_arg("duration_in", args.first())?;
+    Ok(Value::Number(millis / unit_millis("duration_in", args.get(1))?))
+}
+
+/// `clock_add(moment, duration)`: a moment shifted by a duration (negative ones go back)
+pub fn clock_add(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    arity("clock_add", &args, 2..=2)?;
+    let moment = moment_arg("clock_add", args.first())?;
+    let millis = duration_arg("clock_add", args.get(1))?;
+    Ok(Value::Number(moment.timestamp_millis() as f64 + millis.round()))
+}
+
+/// `clock_between(start, end)`: the duration from one moment to another
+pub fn clock_between(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    arity("clock_between", &args, 2..=2)?;
+    let start = moment_arg("clock_between", args.first())?;
+    let end = moment_arg("clock_between", args.get(1))?;
+    Ok(duration_value((end - start).num_milliseconds() as f64))
+}
@@ -0,0 +1,212 @@
+/*
+ * 🐞 Brewco Brew Debugger ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @debugger_note: "Pause the pour, peek in the pantry, then keep brewing!" ✨
+ *
+ * `brew debug file.brewco` runs a recipe with a console debugger attached:
+ * line breakpoints, step/next/finish/continue, a look at the pantry
+ * (local beans) and brewing expressions inside the paused frame.
+ */
+
+// src/brew_debugger.rs
+
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+
+use crate::interpreter::{BrewDebugHook, Interpreter};
+
+/// How far to brew before pausing again
+#[derive(Clone, Copy, PartialEq)]
+pub enum BrewStepMode {
+    Continue,
+    StepIn,
+    StepOver(usize), // pause at the next statement no deeper than this many frames
+    StepOut(usize),  // pause once fewer than this many frames are active
+}
+
+/// Breakpoint and stepping state shared by every debugger front end
+pub struct BrewDebugSession {
+    pub breakpoints: BTreeSet<usize>,
+    pub step_mode: BrewStepMode,
+}
+
+impl BrewDebugSession {
+    pub fn new_session() -> Self {
+        BrewDebugSession {
+            breakpoints: BTreeSet::new(),
+            step_mode: BrewStepMode::StepIn,
+        }
+    }
+
+    /// Should execution stop before the statement at `line` with `depth` active brews?
+    pub fn should_pause(&self, line: usize, depth: usize) -> bool {
+        let stepped = match self.step_mode {
+            BrewStepMode::Continue => false,
+            BrewStepMode::StepIn => true,
+            BrewStepMode::StepOver(frames) => depth <= frames,
+            BrewStepMode::StepOut(frames) => depth < frames,
+        };
+        stepped || self.breakpoints.contains(&line)
+    }
+}
+
+/// Console front end: prompts on stdin whenever the session pauses
+pub struct ConsoleBrewDebugger {
+    session: BrewDebugSession,
+    recipe_name: String,
+    source_lines: Vec<String>,
+}
+
+impl ConsoleBrewDebugger {
+    pub fn new_console_debugger(recipe_name: &str, recipe_source: &str, breakpoints: &[usize]) -> Self {
+        let mut session = BrewDebugSession::new_session();
+        session.breakpoints.extend(breakpoints.iter().copied());
+        ConsoleBrewDebugger {
+            session,
+            recipe_name: recipe_name.to_string(),
+            source_lines: recipe_source.lines().map(|line| line.to_string()).collect(),
+        }
+    }
+
+    fn show_location(&self, interpreter: &Interpreter) {
+        let (line, column) = interpreter.current_location();
+        let snippet = self.source_lines.get(line.wrapping_sub(1)).map(|s| s.trim_end()).unwrap_or("");
+        let frame = interpreter.brew_frames().last().map_or("<main>", |frame| frame.label.as_str());
+        println!("🐞 Paused in {} at {}:{}:{}", frame, self.recipe_name, line, column);
+        println!("   {:>4} | {}", line, snippet);
+    }
+
+    fn list_source(&self, around: usize) {
+        let first = around.saturating_sub(3).max(1);
+        let last = (around + 3).min(self.source_lines.len());
+        for line in first..=last {
+            let marker = if line == around { "☕" } else if self.session.breakpoints.contains(&line) { "●" } else { " " };
+            println!(" {} {:>4} | {}", marker, line, self.source_lines[line - 1]);
+        }
+    }
+
+    fn show_backtrace(&self, interpreter: &Interpreter) {
+        let (line, column) = interpreter.current_location();
+        let frames = interpreter.brew_frames();
+        let innermost = frames.last().map_or("<main>", |frame| frame.label.as_str());
+        println!("  #0 {} at line {}, column {}", innermost, line, column);
+        for (depth, frame) in frames.iter().enumerate().rev() {
+            let caller = if depth == 0 { "<main>" } else { frames[depth - 1].label.as_str() };
+            println!("  #{} {} at line {}, column {}", frames.len() - depth, caller, frame.call_site.0, frame.call_site.1);
+        }
+    }
+
+    fn show_pantry(&self, interpreter: &Interpreter, frame_index: Option<usize>) {
+        let beans = interpreter.frame_pantry(frame_index);
+        if beans.is_empty() {
+            println!("  (the pantry is empty)");
+        }
+        for (name, value) in beans {
+            println!("  {} = {}", name, value);
+        }
+    }
+
+    fn show_help(&self) {
+        println!("🐞 Brew debugger commands:");
+        println!("  step | s              Brew one statement, stepping into brews");
+        println!("  next | n              Brew one statement, stepping over brews");
+        println!("  finish | out          Brew until the current brew serves");
+        println!("  continue | c          Brew until the next breakpoint");
+        println!("  break | b <line>      Set a breakpoint");
+        println!("  delete | d <line>     Remove a breakpoint");
+        println!("  breakpoints           List breakpoints");
+        println!("  pantry | locals       Show beans in the paused brew");
+        println!("  globals               Show the global pantry");
+        println!("  print | p <expr>      Brew an expression in the paused frame");
+        println!("  where | bt            Show the brew call stack");
+        println!("  list | l              Show source around the paused line");
+        println!("  quit | q              Stop brewing and leave");
+    }
+}
+
+impl BrewDebugHook for ConsoleBrewDebugger {
+    fn brew_checkpoint(&mut self, interpreter: &mut Interpreter) {
+        let (line, _) = interpreter.current_location();
+        let depth = interpreter.brew_frames().len();
+        if !self.session.should_pause(line, depth) {
+            return;
+        }
+        self.show_location(interpreter);
+
+        loop {
+            print!("(brew-debug) ");
+            let _ = io::stdout().flush();
+            let mut input = String::new();
+            if io::stdin().read_line(&mut input).unwrap_or(0) == 0 {
+                // stdin closed: let the recipe finish on its own
+                self.session.step_mode = BrewStepMode::Continue;
+                self.session.breakpoints.clear();
+                return;
+            }
+            let input = input.trim();
+            let (command, argument) = match input.split_once(' ') {
+                Some((command, argument)) => (command, argument.trim()),
+                None => (input, ""),
+            };
+            match command {
+                "step" | "s" => {
+                    self.session.step_mode = BrewStepMode::StepIn;
+                    return;
+                }
+                "next" | "n" => {
+                    self.session.step_mode = BrewStepMode::StepOver(depth);
+                    return;
+                }
+                "finish" | "out" => {
+                    self.session.step_mode = BrewStepMode::StepOut(depth);
+                    return;
+                }
+                "continue" | "c" => {
+                    self.session.step_mode = BrewStepMode::Continue;
+                    return;
+                }
+                "break" | "b" | "delete" | "d" => match argument.parse::<usize>() {
+                    Ok(bp_line) if (1..=self.source_lines.len()).contains(&bp_line) => {
+                        if command.starts_with('b') {
+                            self.session.breakpoints.insert(bp_line);
+                            println!("● Breakpoint set at line {}", bp_line);
+                        } else if self.session.breakpoints.remove(&bp_line) {
+                            println!("Breakpoint at line {} removed", bp_line);
+                        } else {
+                            println!("No breakpoint at line {}", bp_line);
+                        }
+                    }
+                    _ => println!("Give a line number between 1 and {}", self.source_lines.len()),
+                },
+                "breakpoints" => {
+                    if self.session.breakpoints.is_empty() {
+                        println!("No breakpoints set");
+                    }
+                    for bp_line in &self.session.breakpoints {
+                        println!("● line {}", bp_line);
+                    }
+                }
+                "pantry" | "locals" => {
+                    let frame_index = depth.checked_sub(1);
+                    self.show_pantry(interpreter, frame_index);
+                }
+                "globals" => self.show_pantry(interpreter, None),
+                "print" | "p" => match interpreter.evaluate_in_pause(argument) {
+                    Ok(Some(value)) => println!("{}", value),
+                    Ok(None) => println!("(brewed)"),
+                    Err(spill) => println!("☕ Spill: {}", spill),
+                },
+                "where" | "bt" => self.show_backtrace(interpreter),
+                "list" | "l" => self.list_source(line),
+                "help" | "h" | "?" => self.show_help(),
+                "quit" | "q" => {
+                    println!("☕ Debug session ended. Happy brewing!");
+                    std::process::exit(0);
+                }
+                "" => {}
+                other => println!("Unknown command '{}'. Type 'help' for debugger commands.", other),
+            }
+        }
+    }
+}
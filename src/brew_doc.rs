@@ -0,0 +1,393 @@
+/*
+ * 📖 Brewco Menu Board ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @menu_note: "Every brew deserves a description on the board!" ✨
+ *
+ * `brew doc` writes a menu for a roastery: every brew and bean in its
+ * recipes, with signatures, parameter types and examples, as Markdown or
+ * one HTML page. Documentation goes in 🎀🎀 lines right above a
+ * declaration (a single 🎀 stays an ordinary comment):
+ *
+ *     🎀🎀 Froths the milk, doubling how much there is.
+ *     🎀🎀 Example: froth(2)
+ *     brew froth(milk: Number): Number { ... }
+ *
+ * An `Example:` line starts an example that runs until the next empty
+ * 🎀🎀 line. A brew without one gets the first call found in the
+ * roastery instead, looking in the *_test.brewco recipes first.
+ */
+
+// src/brew_doc.rs
+
+use std::path::{Path, PathBuf};
+
+use crate::ast::{FieldDecl, ParamDecl, Statement};
+use crate::brew_formatter::coffee_files_under;
+use crate::coffee_package_roastery::RoasteryManifest;
+use crate::parser;
+
+/// What `brew doc` writes
+#[derive(Clone, Copy, PartialEq)]
+pub enum DocFormat {
+    Markdown,
+    Html,
+}
+
+impl DocFormat {
+    pub fn from_name(name: &str) -> Option<DocFormat> {
+        match name {
+            "markdown" | "md" => Some(DocFormat::Markdown),
+            "html" => Some(DocFormat::Html),
+            _ => None,
+        }
+    }
+}
+
+/// A 🎀🎀 comment split into its prose and its examples
+#[derive(Default)]
+struct DocText {
+    prose: Vec<String>,
+    examples: Vec<String>,
+}
+
+impl DocText {
+    fn from_comment(doc: Option<&str>) -> DocText {
+        let mut text = DocText::default();
+        let mut example: Option<Vec<&str>> = None;
+        for line in doc.unwrap_or_default().lines() {
+            if let Some(first) = line.trim_start().strip_prefix("Example:") {
+                if let Some(done) = example.take() {
+                    text.examples.push(done.join("\n"));
+                }
+                example = Some(if first.trim().is_empty() { Vec::new() } else { vec![first.trim()] });
+            } else if let Some(lines) = example.as_mut() {
+                if line.trim().is_empty() {
+                    text.examples.push(lines.join("\n"));
+                    example = None;
+                } else {
+                    lines.push(line);
+                }
+            } else {
+                text.prose.push(line.to_string());
+            }
+        }
+        if let Some(done) = example {
+            text.examples.push(done.join("\n"));
+        }
+        text.examples.retain(|example| !example.trim().is_empty());
+        text.examples = text.examples.iter().map(|example| dedent(example)).collect();
+        text
+    }
+}
+
+/// Take off the indentation every line of an example shares
+fn dedent(code: &str) -> String {
+    let indent = code.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    code.lines().map(|line| line.get(indent..).unwrap_or(line.trim_start())).collect::<Vec<_>>().join("\n")
+}
+
+/// An example and, when it was found in the roastery rather than written in the doc, where
+struct DocExample {
+    code: String,
+    found_at: Option<String>,
+}
+
+struct BrewEntry {
+    name: String,
+    params: Vec<ParamDecl>,
+    return_type: Option<String>,
+    text: DocText,
+    examples: Vec<DocExample>,
+}
+
+struct BeanEntry {
+    name: String,
+    parent: Option<String>,
+    fields: Vec<String>,
+    text: DocText,
+    methods: Vec<BrewEntry>,
+}
+
+/// Everything documented in one recipe
+struct RecipeEntry {
+    path: String,
+    brews: Vec<BrewEntry>,
+    beans: Vec<BeanEntry>,
+}
+
+/// Every recipe the examples can be pulled from: path shown and source, tests first
+type RoasterySources = Vec<(String, String)>;
+
+fn is_taste_test(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name.to_string_lossy().ends_with("_test.brewco"))
+}
+
+/// `brew name(a: Number, b): String`, leaving out the types nobody wrote
+fn brew_signature(brew: &BrewEntry) -> String {
+    let params: Vec<String> = brew.params.iter().map(|param| match param.type_name.as_str() {
+        "Any" => param.name.clone(),
+        type_name => format!("{}: {}", param.name, type_name),
+    }).collect();
+    let serves = brew.return_type.as_ref().map_or(String::new(), |return_type| format!(": {}", return_type));
+    format!("brew {}({}){}", brew.name, params.join(", "), serves)
+}
+
+fn bean_signature(bean: &BeanEntry) -> String {
+    match &bean.parent {
+        Some(parent) => format!("bean {} blend {}", bean.name, parent),
+        None => format!("bean {}", bean.name),
+    }
+}
+
+/// The first line in the roastery that calls `name` (as `.name(` for a method), except its own declaration
+fn find_usage(name: &str, method: bool, sources: &RoasterySources) -> Option<DocExample> {
+    let call = format!("{}(", name);
+    for (path, source) in sources {
+        for (index, line) in source.lines().enumerate() {
+            let code = line.trim();
+            if code.starts_with('\u{1F380}') || code.starts_with("brew ") {
+                continue;
+            }
+            let calls = code.match_indices(&call).any(|(at, _)| {
+                // A brew can be called through a grinded module, as `shop.name(`
+                match code[..at].chars().next_back() {
+                    Some('.') => true,
+                    Some(c) => !(method || c.is_alphanumeric() || c == '_'),
+                    None => !method,
+                }
+            });
+            if calls {
+                return Some(DocExample { code: code.to_string(), found_at: Some(format!("{}:{}", path, index + 1)) });
+            }
+        }
+    }
+    None
+}
+
+fn brew_entry(statement: &Statement, method: bool, sources: &RoasterySources) -> Option<BrewEntry> {
+    let Statement::BrewDecl { name, params, return_type, doc, .. } = statement else {
+        return None;
+    };
+    let text = DocText::from_comment(doc.as_deref());
+    let mut examples: Vec<DocExample> = text.examples.iter().map(|code| DocExample { code: code.clone(), found_at: None }).collect();
+    if examples.is_empty() {
+        examples.extend(find_usage(name, method, sources));
+    }
+    Some(BrewEntry { name: name.clone(), params: params.clone(), return_type: return_type.clone(), text, examples })
+}
+
+fn recipe_entry(path: String, statements: &[Statement], sources: &RoasterySources) -> RecipeEntry {
+    let mut recipe = RecipeEntry { path, brews: Vec::new(), beans: Vec::new() };
+    for statement in statements {
+        match statement {
+            Statement::BrewDecl { .. } => recipe.brews.extend(brew_entry(statement, false, sources)),
+            Statement::BeanDecl { name, parent, fields, methods, doc } => recipe.beans.push(BeanEntry {
+                name: name.clone(),
+                parent: parent.clone(),
+                fields: fields.iter().map(|FieldDecl { name, .. }| name.clone()).collect(),
+                text: DocText::from_comment(doc.as_deref()),
+                methods: methods.iter().filter_map(|method| brew_entry(method, true, sources)).collect(),
+            }),
+            _ => {}
+        }
+    }
+    recipe
+}
+
+// Markdown
+
+fn markdown_brew(out: &mut String, brew: &BrewEntry, heading: &str) {
+    out.push_str(&format!("{} `{}`\n\n", heading, brew_signature(brew)));
+    markdown_prose(out, &brew.text);
+    if !brew.params.is_empty() {
+        out.push_str("| Parameter | Type |\n| --- | --- |\n");
+        for param in &brew.params {
+            out.push_str(&format!("| `{}` | {} |\n", param.name, param.type_name));
+        }
+        out.push('\n');
+    }
+    if let Some(return_type) = &brew.return_type {
+        out.push_str(&format!("**Serves:** {}\n\n", return_type));
+    }
+    for example in &brew.examples {
+        match &example.found_at {
+            Some(found_at) => out.push_str(&format!("**Example** (from `{}`):\n\n", found_at)),
+            None => out.push_str("**Example:**\n\n"),
+        }
+        out.push_str(&format!("```brewco\n{}\n```\n\n", example.code));
+    }
+}
+
+fn markdown_prose(out: &mut String, text: &DocText) {
+    let prose = text.prose.join("\n");
+    if !prose.trim().is_empty() {
+        out.push_str(prose.trim());
+        out.push_str("\n\n");
+    }
+}
+
+fn render_markdown(title: &str, description: Option<&str>, recipes: &[RecipeEntry]) -> String {
+    let mut out = format!("# 📖 {}\n\n", title);
+    if let Some(description) = description {
+        out.push_str(&format!("{}\n\n", description));
+    }
+    for recipe in recipes {
+        out.push_str(&format!("## 📄 {}\n\n", recipe.path));
+        for bean in &recipe.beans {
+            out.push_str(&format!("### `{}`\n\n", bean_signature(bean)));
+            markdown_prose(&mut out, &bean.text);
+            if !bean.fields.is_empty() {
+                let fields: Vec<String> = bean.fields.iter().map(|field| format!("`{}`", field)).collect();
+                out.push_str(&format!("**Fields:** {}\n\n", fields.join(", ")));
+            }
+            for method in &bean.methods {
+                markdown_brew(&mut out, method, "####");
+            }
+        }
+        for brew in &recipe.brews {
+            markdown_brew(&mut out, brew, "###");
+        }
+    }
+    out
+}
+
+// HTML
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn html_prose(out: &mut String, text: &DocText) {
+    for paragraph in text.prose.join("\n").split("\n\n").map(str::trim).filter(|paragraph| !paragraph.is_empty()) {
+        out.push_str(&format!("<p>{}</p>\n", escape_html(paragraph)));
+    }
+}
+
+fn html_brew(out: &mut String, brew: &BrewEntry, heading: &str) {
+    out.push_str(&format!("<{0} id=\"{1}\"><code>{2}</code></{0}>\n", heading, escape_html(&brew.name), escape_html(&brew_signature(brew))));
+    html_prose(out, &brew.text);
+    if !brew.params.is_empty() {
+        out.push_str("<table>\n<tr><th>Parameter</th><th>Type</th></tr>\n");
+        for param in &brew.params {
+            out.push_str(&format!("<tr><td><code>{}</code></td><td>{}</td></tr>\n", escape_html(&param.name), escape_html(&param.type_name)));
+        }
+        out.push_str("</table>\n");
+    }
+    if let Some(return_type) = &brew.return_type {
+        out.push_str(&format!("<p><strong>Serves:</strong> {}</p>\n", escape_html(return_type)));
+    }
+    for example in &brew.examples {
+        match &example.found_at {
+            Some(found_at) => out.push_str(&format!("<p class=\"example\">Example (from <code>{}</code>):</p>\n", escape_html(found_at))),
+            None => out.push_str("<p class=\"example\">Example:</p>\n"),
+        }
+        out.push_str(&format!("<pre><code>{}</code></pre>\n", escape_html(&example.code)));
+    }
+}
+
+const MENU_BOARD_STYLE: &str = "body { font-family: sans-serif; max-width: 52rem; margin: 2rem auto; padding: 0 1rem; color: #3b2417; background: #fffaf3; }
+h1, h2 { border-bottom: 2px solid #c8a27a; }
+pre, code { background: #f3e6d6; border-radius: 4px; }
+pre { padding: 0.75rem; overflow-x: auto; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid #c8a27a; padding: 0.25rem 0.75rem; text-align: left; }
+.example { font-style: italic; }";
+
+fn render_html(title: &str, description: Option<&str>, recipes: &[RecipeEntry]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n<style>\n{1}\n</style>\n</head>\n<body>\n<h1>📖 {0}</h1>\n",
+        escape_html(title),
+        MENU_BOARD_STYLE
+    );
+    if let Some(description) = description {
+        out.push_str(&format!("<p>{}</p>\n", escape_html(description)));
+    }
+    for recipe in recipes {
+        out.push_str(&format!("<h2>📄 {}</h2>\n", escape_html(&recipe.path)));
+        for bean in &recipe.beans {
+            out.push_str(&format!("<h3 id=\"{}\"><code>{}</code></h3>\n", escape_html(&bean.name), escape_html(&bean_signature(bean))));
+            html_prose(&mut out, &bean.text);
+            if !bean.fields.is_empty() {
+                let fields: Vec<String> = bean.fields.iter().map(|field| format!("<code>{}</code>", escape_html(field))).collect();
+                out.push_str(&format!("<p><strong>Fields:</strong> {}</p>\n", fields.join(", ")));
+            }
+            for method in &bean.methods {
+                html_brew(&mut out, method, "h4");
+            }
+        }
+        for brew in &recipe.brews {
+            html_brew(&mut out, brew, "h3");
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// `brew doc [--format markdown|html] [--out FILE] [paths...]`: print the roastery's menu, or write it to FILE
+pub fn run_brew_doc(paths: &[String], format: DocFormat, out: Option<&str>) {
+    let roots = if paths.is_empty() { vec![".".to_string()] } else { paths.to_vec() };
+    let mut files: Vec<PathBuf> = roots.iter().flat_map(|root| coffee_files_under(Path::new(root))).collect();
+    // Taste tests hold the best examples, so they're searched first
+    files.sort_by_key(|path| !is_taste_test(path));
+
+    let mut spilled = 0;
+    let mut sources: RoasterySources = Vec::new();
+    for path in &files {
+        match std::fs::read_to_string(path) {
+            Ok(source) => sources.push((path.strip_prefix(".").unwrap_or(path).display().to_string(), source)),
+            Err(_) => {
+                eprintln!("[ERROR] Could not read file: {}", path.display());
+                spilled += 1;
+            }
+        }
+    }
+
+    let mut recipes = Vec::new();
+    for (path, source) in sources.iter().filter(|(path, _)| !is_taste_test(Path::new(path))) {
+        let parsed = parser::parse_source(source);
+        if !parsed.errors.is_empty() {
+            for (err, (line, column)) in parsed.errors.iter().zip(&parsed.error_positions) {
+                eprintln!("[ERROR] {}:{}:{}: syntax spill: {}", path, line, column, err);
+            }
+            spilled += 1;
+            continue;
+        }
+        let recipe = recipe_entry(path.clone(), &parsed.statements, &sources);
+        if !recipe.brews.is_empty() || !recipe.beans.is_empty() {
+            recipes.push(recipe);
+        }
+    }
+    recipes.sort_by(|a, b| a.path.cmp(&b.path));
+
+    // The manifest names the roastery, when there is one
+    let manifest: Option<RoasteryManifest> = std::fs::read_to_string("roastery.json")
+        .ok()
+        .and_then(|manifest| serde_json::from_str(&manifest).ok());
+    let title = manifest.as_ref().map_or("Brewco Recipes", |manifest| manifest.roastery_name.as_str());
+    let description = manifest.as_ref().map(|manifest| manifest.roastery_description.as_str());
+    let menu = match format {
+        DocFormat::Markdown => render_markdown(title, description, &recipes),
+        DocFormat::Html => render_html(title, description, &recipes),
+    };
+
+    match out {
+        Some(out) => {
+            if let Err(e) = std::fs::write(out, &menu) {
+                eprintln!("[ERROR] Could not write {}: {}", out, e);
+                std::process::exit(1);
+            }
+            let brews: usize = recipes.iter().map(|recipe| recipe.brews.len() + recipe.beans.iter().map(|bean| bean.methods.len()).sum::<usize>()).sum();
+            let beans: usize = recipes.iter().map(|recipe| recipe.beans.len()).sum();
+            println!("📖 Wrote {} brew(s) and {} bean(s) from {} recipe(s) to {}", brews, beans, recipes.len(), out);
+        }
+        None => print!("{}", menu),
+    }
+    if spilled > 0 {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,431 @@
+/*
+ * 🧹 Brewco Brew Formatter ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @formatter_note: "Every recipe poured the same way, comments and all!" ✨
+ *
+ * `brew fmt` re-pours recipes with four-space indentation, one space around
+ * themed operators and long argument lists wrapped one per line. It works on
+ * the token stream (🎀 and // comments included) so only whitespace ever
+ * changes, and `brew fmt --check` lists the files that still need a pour.
+ */
+
+// src/brew_formatter.rs
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::ast::PourSpout;
+use crate::coffee_io::CoffeeFileBrewery;
+use crate::lexer::{CoffeeTokenStream, Token};
+use crate::parser;
+
+/// Lines longer than this get their argument lists wrapped, unless brewco.toml sets [fmt] width
+pub const BREW_FMT_WIDTH: usize = 100;
+const BREW_FMT_INDENT: &str = "    ";
+
+/// A token exactly as it was written, so `add` stays `add` and `+` stays `+`
+struct PouredToken {
+    token: Token,
+    text: String,
+    spaced_before: bool, // the source had whitespace before it on the same line
+    unary: bool,         // a sip / no_foam / invert applied to what follows
+}
+
+/// One output line: a run of tokens at an indentation level
+struct PouredLine {
+    indent: usize,
+    tokens: Range<usize>,
+}
+
+/// An open bracket and the indentation of the line that opened it
+struct OpenCup {
+    indent: usize,
+    block: bool, // a `{ ... }` block of statements rather than a literal
+}
+
+/// Re-pour a whole recipe, wrapping lines longer than `width`. Recipes that don't parse are
+/// left alone and their errors returned.
+pub fn format_coffee_source(source: &str, width: usize) -> Result<String, Vec<String>> {
+    let parsed = parser::parse_source(source);
+    if !parsed.errors.is_empty() {
+        return Err(parsed.errors);
+    }
+    let (tokens, source_lines) = pour_tokens(source)?;
+
+    // Wrapping adds newlines inside brackets; if that ever upsets the parser, settle for no wrapping
+    for wrap_width in [Some(width), None] {
+        let formatted = lay_out(&tokens, &source_lines, wrap_width);
+        if same_brew(source, &formatted) {
+            return Ok(formatted);
+        }
+    }
+    Err(vec!["The formatter could not re-pour this recipe without changing its meaning, so it was left untouched".to_string()])
+}
+
+/// The tokens of a recipe, and the range of them on each source line
+type PouredLines = (Vec<PouredToken>, Vec<Range<usize>>);
+
+/// Split the source into tokens with their original spelling, grouped by source line
+fn pour_tokens(source: &str) -> Result<PouredLines, Vec<String>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut line_starts = vec![0];
+    line_starts.extend(chars.iter().enumerate().filter(|(_, c)| **c == '\n').map(|(i, _)| i + 1));
+    let offset = |line: usize, column: usize| (line_starts[line - 1] + column - 1).min(chars.len());
+
+    let mut tokens: Vec<PouredToken> = Vec::new();
+    let mut source_lines = Vec::new();
+    let mut line_start = 0;
+    let mut previous_end = 0;
+    let mut previous_code: Option<Token> = None;
+    for spanned in CoffeeTokenStream::with_comments(source) {
+        let start = offset(spanned.line, spanned.column);
+        let end = offset(spanned.end_line, spanned.end_column);
+        if let Some(stray) = chars[previous_end..start].iter().position(|c| !c.is_whitespace()) {
+            return Err(vec![stray_character_spill(&chars, &line_starts, previous_end + stray)]);
+        }
+        let spaced_before = start > previous_end;
+        previous_end = end;
+
+        if spanned.token == Token::Newline {
+            source_lines.push(line_start..tokens.len());
+            line_start = tokens.len();
+            previous_code = None;
+            continue;
+        }
+        let text: String = chars[start..end].iter().collect();
+        let unary = matches!(spanned.token, Token::Sip | Token::NoFoam | Token::Invert)
+            && !previous_code.as_ref().is_some_and(is_operand);
+        if !matches!(spanned.token, Token::Comment(_)) {
+            previous_code = Some(spanned.token.clone());
+        }
+        tokens.push(PouredToken { token: spanned.token, text: text.trim_end().to_string(), spaced_before, unary });
+    }
+    if let Some(stray) = chars[previous_end..].iter().position(|c| !c.is_whitespace()) {
+        return Err(vec![stray_character_spill(&chars, &line_starts, previous_end + stray)]);
+    }
+    if line_start < tokens.len() {
+        source_lines.push(line_start..tokens.len());
+    }
+    Ok((tokens, source_lines))
+}
+
+/// The lexer quietly skips characters it doesn't know; the formatter refuses to drop them
+fn stray_character_spill(chars: &[char], line_starts: &[usize], at: usize) -> String {
+    let line = line_starts.partition_point(|&start| start <= at);
+    let column = at - line_starts[line - 1] + 1;
+    format!(
+        "Stray character '{}' at line {}, column {} is not part of any brew; remove it or wrap it in a 🎀 comment",
+        chars[at], line, column
+    )
+}
+
+/// Tokens after which a `-` is subtraction and a `(` or `[` is a call or index
+fn is_operand(token: &Token) -> bool {
+    match token {
+        Token::Identifier(name) => PourSpout::from_word(name).is_none(),
+        Token::Number(_) | Token::String(_) | Token::RParen | Token::RBracket | Token::This | Token::Super => true,
+        _ => false,
+    }
+}
+
+fn is_opener(token: &Token) -> bool {
+    matches!(token, Token::LParen | Token::LBracket | Token::LBrace)
+}
+
+fn is_closer(token: &Token) -> bool {
+    matches!(token, Token::RParen | Token::RBracket | Token::RBrace)
+}
+
+/// Does a `{` after this token start an object literal rather than a block?
+fn opens_literal(previous: Option<&Token>) -> bool {
+    match previous {
+        Some(Token::Identifier(name)) => PourSpout::from_word(name).is_some(),
+        Some(token) => matches!(
+            token,
+            Token::PourIn | Token::Equals | Token::RefillWith | Token::LParen | Token::LBracket
+                | Token::LBrace | Token::Comma | Token::Colon | Token::Serve | Token::ServeBack | Token::YieldSip
+        ) || is_binary_operator(token),
+        None => false,
+    }
+}
+
+fn is_binary_operator(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Add | Token::Sip | Token::BrewOp | Token::PourOp | Token::Grounds | Token::SameBlend
+            | Token::DifferentBlend | Token::LessCaffeine | Token::MoreCaffeine | Token::NotStronger
+            | Token::NotWeaker | Token::With | Token::Or | Token::BlendWith | Token::TopWith | Token::ThenPour
+            | Token::Spice | Token::DoubleShot | Token::HalfCaf
+    )
+}
+
+/// Should one space separate these two neighbours?
+fn wants_space(previous: &PouredToken, next: &PouredToken) -> bool {
+    use Token::*;
+    match (&previous.token, &next.token) {
+        (_, Comment(_)) => true,
+        (LBrace, RBrace) => false,
+        (_, RParen | RBracket | Comma | Dot | DotDot | Semicolon | Colon) => false,
+        (LParen | LBracket | Dot | DotDot, _) => false,
+        (token, LParen | LBracket) if is_operand(token) => false,
+        // `serve(x)` and `roast serve()` keep however the author wrote them
+        (_, LParen) if previous.text.chars().all(|c| c.is_alphanumeric() || c == '_') => next.spaced_before,
+        // `-5` and `!ready` hug their operand; `no_foam ready` needs the space
+        _ if previous.unary => previous.text.chars().all(|c| c.is_alphanumeric() || c == '_'),
+        _ => true,
+    }
+}
+
+fn render_line(tokens: &[PouredToken], line: &PouredLine) -> String {
+    let mut text = BREW_FMT_INDENT.repeat(line.indent);
+    for i in line.tokens.clone() {
+        if i > line.tokens.start && wants_space(&tokens[i - 1], &tokens[i]) {
+            text.push(' ');
+        }
+        text.push_str(&tokens[i].text);
+    }
+    text
+}
+
+/// Indent every source line by its bracket nesting, tidy blank lines and wrap what is too long
+fn lay_out(tokens: &[PouredToken], source_lines: &[Range<usize>], wrap_width: Option<usize>) -> String {
+    let mut open_cups: Vec<OpenCup> = Vec::new();
+    let mut lines: Vec<Option<PouredLine>> = Vec::new(); // None is a blank line
+    let mut blank_pending = false;
+    let mut continues_statement = false;
+
+    for range in source_lines {
+        if range.is_empty() {
+            blank_pending = true;
+            continue;
+        }
+        let first = &tokens[range.start].token;
+        let indent = if is_closer(first) {
+            open_cups.last().map_or(0, |cup| cup.indent)
+        } else {
+            open_cups.last().map_or(0, |cup| cup.indent + 1) + usize::from(continues_statement)
+        };
+
+        // One blank line at most, and none just inside brackets or at the top of the file
+        let after_opener = lines.last().and_then(|line| line.as_ref()).is_none_or(|line| {
+            line.tokens.clone().rev().find(|&i| !matches!(tokens[i].token, Token::Comment(_)))
+                .is_some_and(|i| is_opener(&tokens[i].token))
+        });
+        if blank_pending && !after_opener && !is_closer(first) {
+            lines.push(None);
+        }
+        blank_pending = false;
+
+        for i in range.clone() {
+            let token = &tokens[i].token;
+            if is_opener(token) {
+                let previous = i.checked_sub(1).map(|p| &tokens[p].token);
+                let block = *token == Token::LBrace && !opens_literal(previous);
+                open_cups.push(OpenCup { indent, block });
+            } else if is_closer(token) {
+                open_cups.pop();
+            }
+        }
+        let last_code = range.clone().rev().find(|&i| !matches!(tokens[i].token, Token::Comment(_)));
+        continues_statement = last_code.is_some_and(|i| tokens[i].token == Token::Comma)
+            && open_cups.last().is_none_or(|cup| cup.block);
+
+        lines.push(Some(PouredLine { indent, tokens: range.clone() }));
+    }
+
+    let mut formatted = String::new();
+    for line in lines {
+        let Some(line) = line else {
+            formatted.push('\n');
+            continue;
+        };
+        let mut wrapped = Vec::new();
+        if let Some(width) = wrap_width {
+            wrap_line(tokens, line, width, &mut wrapped);
+        } else {
+            wrapped.push(line);
+        }
+        for piece in wrapped {
+            formatted.push_str(&render_line(tokens, &piece));
+            formatted.push('\n');
+        }
+    }
+    formatted
+}
+
+/// The matching closer for the opener at `open`, if it is on the same line
+fn matching_closer(tokens: &[PouredToken], open: usize, end: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, poured) in tokens.iter().enumerate().take(end).skip(open) {
+        if is_opener(&poured.token) {
+            depth += 1;
+        } else if is_closer(&poured.token) {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+/// Commas directly inside `range`, skipping anything nested deeper
+fn top_level_commas(tokens: &[PouredToken], range: Range<usize>) -> Vec<usize> {
+    let mut depth = 0;
+    let mut commas = Vec::new();
+    for i in range {
+        match &tokens[i].token {
+            token if is_opener(token) => depth += 1,
+            token if is_closer(token) => depth -= 1,
+            Token::Comma if depth == 0 => commas.push(i),
+            _ => {}
+        }
+    }
+    commas
+}
+
+/// Can the bracket at `open` hold one item per line? Calls, cups and menus can; brew parameters and blocks can't.
+fn wrappable_opener(tokens: &[PouredToken], open: usize) -> bool {
+    let previous = open.checked_sub(1).map(|p| &tokens[p].token);
+    match tokens[open].token {
+        Token::LParen => {
+            let declares = open.checked_sub(2).is_some_and(|p| matches!(tokens[p].token, Token::Brew | Token::Roast));
+            previous.is_some_and(is_operand) && !declares
+        }
+        Token::LBracket => !previous.is_some_and(is_operand),
+        Token::LBrace => opens_literal(previous),
+        _ => false,
+    }
+}
+
+fn wrap_line(tokens: &[PouredToken], line: PouredLine, width: usize, wrapped: &mut Vec<PouredLine>) {
+    if render_line(tokens, &line).chars().count() <= width {
+        wrapped.push(line);
+        return;
+    }
+    let PouredLine { indent, tokens: range } = line;
+
+    // `pourout a, b, c` fills continuation lines with as many items as fit
+    let pours = matches!(&tokens[range.start].token, Token::Identifier(name) if PourSpout::from_word(name).is_some());
+    let commas = if pours { top_level_commas(tokens, range.clone()) } else { Vec::new() };
+    if !commas.is_empty() {
+        let mut pieces = Vec::new();
+        let mut piece = PouredLine { indent, tokens: range.start..commas[0] + 1 };
+        for item_end in commas[1..].iter().map(|comma| comma + 1).chain([range.end]) {
+            let grown = PouredLine { indent: piece.indent, tokens: piece.tokens.start..item_end };
+            if render_line(tokens, &grown).chars().count() <= width {
+                piece = grown;
+            } else {
+                let next_start = piece.tokens.end;
+                pieces.push(piece);
+                piece = PouredLine { indent: indent + 1, tokens: next_start..item_end };
+            }
+        }
+        pieces.push(piece);
+        for piece in pieces {
+            // A single item that is still too long may wrap its own brackets
+            if piece.tokens.start == range.start {
+                wrapped.push(piece);
+            } else {
+                wrap_line(tokens, piece, width, wrapped);
+            }
+        }
+        return;
+    }
+
+    // The outermost bracket with more than one item gets one item per line
+    let mut i = range.start;
+    while i < range.end {
+        if is_opener(&tokens[i].token) {
+            let Some(close) = matching_closer(tokens, i, range.end) else { break };
+            let commas = top_level_commas(tokens, i + 1..close);
+            if !commas.is_empty() && wrappable_opener(tokens, i) {
+                wrap_line(tokens, PouredLine { indent, tokens: range.start..i + 1 }, width, wrapped);
+                let mut item_start = i + 1;
+                for item_end in commas.into_iter().map(|comma| comma + 1).chain([close]) {
+                    wrap_line(tokens, PouredLine { indent: indent + 1, tokens: item_start..item_end }, width, wrapped);
+                    item_start = item_end;
+                }
+                wrap_line(tokens, PouredLine { indent, tokens: close..range.end }, width, wrapped);
+                return;
+            }
+            i = close;
+        }
+        i += 1;
+    }
+    wrapped.push(PouredLine { indent, tokens: range });
+}
+
+/// Formatting must leave exactly the same tokens (newlines aside) and still parse
+fn same_brew(source: &str, formatted: &str) -> bool {
+    let brewed = |text| CoffeeTokenStream::with_comments(text)
+        .map(|spanned| spanned.token)
+        .filter(|token| *token != Token::Newline)
+        .map(|token| match token {
+            Token::Comment(text) => Token::Comment(text.trim_end().to_string()),
+            other => other,
+        })
+        .collect::<Vec<_>>();
+    brewed(source) == brewed(formatted) && parser::parse_source(formatted).errors.is_empty()
+}
+
+/// Every `.brewco` file at or under `path`, skipping hidden directories and `target`
+pub fn coffee_files_under(path: &Path) -> Vec<PathBuf> {
+    if !path.is_dir() {
+        return vec![path.to_path_buf()];
+    }
+    CoffeeFileBrewery::scan_pantry_deep_skipping(&path.to_string_lossy(), "**/*.brewco", &["target"])
+        .unwrap_or_default()
+        .into_iter()
+        .map(|relative| path.join(relative))
+        .collect()
+}
+
+/// `brew fmt [--check] [paths...]`: rewrite files in place, or with `--check` only report them
+pub fn run_brew_fmt(paths: &[String], check: bool, width: usize) {
+    let roots = if paths.is_empty() { vec![".".to_string()] } else { paths.to_vec() };
+    let mut unpoured = 0;
+    let mut spilled = 0;
+    for path in roots.iter().flat_map(|root| coffee_files_under(Path::new(root))) {
+        let shown = path.display();
+        let code = match std::fs::read_to_string(&path) {
+            Ok(code) => code,
+            Err(_) => {
+                println!("[ERROR] Could not read file: {}", shown);
+                spilled += 1;
+                continue;
+            }
+        };
+        let formatted = match format_coffee_source(&code, width) {
+            Ok(formatted) => formatted,
+            Err(errors) => {
+                println!("☕ Could not format {}:", shown);
+                for err in errors {
+                    println!("  - {}", err);
+                }
+                spilled += 1;
+                continue;
+            }
+        };
+        if formatted == code {
+            continue;
+        }
+        unpoured += 1;
+        if check {
+            println!("🧹 {} needs a fresh pour", shown);
+        } else if let Err(e) = std::fs::write(&path, formatted) {
+            println!("[ERROR] Could not write {}: {}", shown, e);
+            spilled += 1;
+        } else {
+            println!("🧹 Formatted {}", shown);
+        }
+    }
+
+    if check && unpoured > 0 {
+        println!("☕ {} recipe(s) need formatting; run `brew fmt` to pour them neatly", unpoured);
+    }
+    if spilled > 0 || (check && unpoured > 0) {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,111 @@
+/*
+ * 🔍 Brewco Cupping Lens ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @lens_note: "Look closely at the grounds before you brew!" ✨
+ *
+ * `brew inspect --tokens` lists the tokens the lexer brews out of a recipe
+ * (comments included), and `brew inspect --ast` shows the syntax tree the
+ * parser builds. With `--json` both come out as JSON, so formatters,
+ * analyzers and editor plugins can read Brewco without their own parser.
+ *
+ * Tokens are objects like {"token": {"Identifier": "latte"}, "line": 1,
+ * "column": 7, "end_line": 1, "end_column": 12}; keywords and symbols are
+ * plain strings such as "Beans" or "LBrace". The tree is
+ * {"statements": [...], "errors": [...]}, with every statement preceded by
+ * a {"SourceMark": {"line": .., "column": ..}} saying where it starts.
+ *
+ * `brew inspect --source` prints the recipe back from its tree, the way
+ * tools that rewrite the tree would save their result.
+ */
+
+// src/brew_inspect.rs
+
+use serde::Serialize;
+
+use crate::ast::Statement;
+use crate::brewco_codegen;
+use crate::lexer::{CoffeeTokenStream, SpannedToken, Token};
+use crate::parser;
+
+/// What `brew inspect` shows
+#[derive(Clone, Copy, PartialEq)]
+pub enum InspectView {
+    Tokens,
+    Ast,
+    Source,
+}
+
+/// One syntax spill, for the JSON tree
+#[derive(Serialize)]
+struct InspectedSpill<'a> {
+    line: usize,
+    column: usize,
+    message: &'a str,
+}
+
+#[derive(Serialize)]
+struct InspectedTree<'a> {
+    statements: &'a [Statement],
+    errors: Vec<InspectedSpill<'a>>,
+}
+
+/// `brew inspect --tokens|--ast|--source [--json] <recipe>`; spills when the recipe doesn't parse, after showing what did
+pub fn run_brew_inspect(source: &str, label: &str, view: InspectView, json: bool) {
+    let spilled = match view {
+        InspectView::Tokens => {
+            let tokens: Vec<SpannedToken> = CoffeeTokenStream::with_comments(source).collect();
+            if json {
+                println!("{}", to_json(&tokens));
+            } else {
+                for spanned in &tokens {
+                    println!("{}:{}:{}  {:?}", label, spanned.line, spanned.column, spanned.token);
+                }
+            }
+            tokens.iter().any(|spanned| matches!(spanned.token, Token::LexError(_)))
+        }
+        InspectView::Ast => {
+            let parsed = parser::parse_source(source);
+            if json {
+                let errors = parsed.errors.iter()
+                    .zip(&parsed.error_positions)
+                    .map(|(message, &(line, column))| InspectedSpill { line, column, message })
+                    .collect();
+                println!("{}", to_json(&InspectedTree { statements: &parsed.statements, errors }));
+            } else {
+                for statement in parsed.statements.iter().filter(|statement| !matches!(statement, Statement::SourceMark { .. })) {
+                    println!("{:#?}", statement);
+                }
+                for (error, (line, column)) in parsed.errors.iter().zip(&parsed.error_positions) {
+                    eprintln!("{}:{}:{}: syntax spill: {}", label, line, column, error);
+                }
+            }
+            !parsed.errors.is_empty()
+        }
+        InspectView::Source => {
+            let parsed = parser::parse_source(source);
+            if parsed.errors.is_empty() {
+                let printed = brewco_codegen::generate_source(&parsed.statements);
+                if json {
+                    println!("{}", to_json(&printed));
+                } else {
+                    print!("{}", printed);
+                }
+            }
+            for (error, (line, column)) in parsed.errors.iter().zip(&parsed.error_positions) {
+                eprintln!("{}:{}:{}: syntax spill: {}", label, line, column, error);
+            }
+            !parsed.errors.is_empty()
+        }
+    };
+    if spilled {
+        std::process::exit(1);
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|e| {
+        eprintln!("[ERROR] Couldn't write the JSON: {}", e);
+        std::process::exit(1);
+    })
+}
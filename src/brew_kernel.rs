@@ -0,0 +1,565 @@
+/*
+ * 📓 Brewco Notebook Kernel ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @notebook_note: "A cup per cell, and the pantry stays warm between them!" ✨
+ *
+ * `brew kernel <connection file>` speaks the Jupyter messaging protocol
+ * (5.3) over ZeroMQ so notebooks can brew Brewco. Every cell brews on the
+ * same Interpreter, so beans and brews carry over from cell to cell. Pours
+ * stream to the notebook as they happen (spill_out as stderr), and a cell
+ * ending in a bare expression shows its value as the cell's result: as text,
+ * as a table for cups and menus, and as JSON when JSON can hold it.
+ *
+ * Heartbeat, control and iopub run as tokio tasks; the shell loop stays on
+ * the main thread, since that's where the Interpreter lives. Jupyter stops
+ * a cell with SIGINT, which the Ctrl-C handler turns into an InterruptedBrew
+ * spill. `brew kernel --install` writes the kernelspec Jupyter starts us by.
+ */
+
+// src/brew_kernel.rs
+
+use std::fs;
+use std::path::PathBuf;
+
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+use sha2::Sha256;
+use tokio::sync::mpsc::{self, UnboundedSender};
+use zeromq::{PubSocket, RepSocket, RouterSocket, Socket, SocketRecv, SocketSend, ZmqMessage};
+
+use crate::ast::PourSpout;
+use crate::espresso_errors::{CoffeeSpillReport, SpillType};
+use crate::interpreter::{self, ControlFlow, Interpreter, Value};
+use crate::lexer::{self, CoffeeTokenStream, Token};
+use crate::native;
+use crate::parser;
+
+/// The messaging protocol version we speak
+const PROTOCOL_VERSION: &str = "5.3";
+
+/// Separates ZeroMQ routing identities from the signed parts of a message
+const DELIMITER: &[u8] = b"<IDS|MSG>";
+
+/// What the cell's last value is remembered as, like the REPL's `_`
+const LAST_SERVED: &str = "_";
+
+/// The connection file Jupyter writes and passes to `brew kernel`
+#[derive(Deserialize)]
+struct ConnectionInfo {
+    transport: String,
+    ip: String,
+    shell_port: u16,
+    iopub_port: u16,
+    stdin_port: u16,
+    control_port: u16,
+    hb_port: u16,
+    key: String,
+    signature_scheme: String,
+}
+
+impl ConnectionInfo {
+    fn endpoint(&self, port: u16) -> String {
+        format!("{}://{}:{}", self.transport, self.ip, port)
+    }
+}
+
+/// Signs outgoing messages and checks incoming ones with the connection's key;
+/// an empty key means messages go unsigned
+#[derive(Clone)]
+struct MessageSigner {
+    key: Vec<u8>,
+}
+
+impl MessageSigner {
+    fn mac(&self, parts: &[&[u8]]) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC takes keys of any length");
+        for part in parts {
+            mac.update(part);
+        }
+        mac
+    }
+
+    fn sign(&self, parts: &[&[u8]]) -> String {
+        if self.key.is_empty() {
+            return String::new();
+        }
+        self.mac(parts).finalize().into_bytes().iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    fn verify(&self, signature: &[u8], parts: &[&[u8]]) -> bool {
+        if self.key.is_empty() {
+            return true;
+        }
+        let digest: Option<Vec<u8>> = signature
+            .chunks(2)
+            .map(|pair| std::str::from_utf8(pair).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok()))
+            .collect();
+        digest.is_some_and(|digest| self.mac(parts).verify_slice(&digest).is_ok())
+    }
+}
+
+/// One message off the wire, with the routing identities that came in front of it
+struct KernelMessage {
+    identities: Vec<Vec<u8>>,
+    header: JsonValue,
+    content: JsonValue,
+}
+
+impl KernelMessage {
+    fn decode(frames: ZmqMessage, signer: &MessageSigner) -> Result<KernelMessage, String> {
+        let frames: Vec<Vec<u8>> = frames.into_vec().into_iter().map(|frame| frame.to_vec()).collect();
+        let split = frames.iter().position(|frame| frame == DELIMITER).ok_or("a message without the <IDS|MSG> delimiter")?;
+        let parts = &frames[split + 1..];
+        if parts.len() < 5 {
+            return Err(format!("a message with {} parts after the delimiter, not 5", parts.len()));
+        }
+        if !signer.verify(&parts[0], &[&parts[1], &parts[2], &parts[3], &parts[4]]) {
+            return Err("a message whose signature doesn't match the key".to_string());
+        }
+        let json = |part: &[u8]| serde_json::from_slice::<JsonValue>(part).map_err(|e| format!("a message that isn't JSON: {}", e));
+        Ok(KernelMessage { identities: frames[..split].to_vec(), header: json(&parts[1])?, content: json(&parts[4])? })
+    }
+
+    fn msg_type(&self) -> &str {
+        self.header["msg_type"].as_str().unwrap_or("")
+    }
+
+    /// A message in reply to this one, sent along `route`: its identities for a reply, a topic for iopub
+    fn answer(&self, route: Vec<Vec<u8>>, msg_type: &str, content: JsonValue, signer: &MessageSigner, session: &str) -> ZmqMessage {
+        let header = json!({
+            "msg_id": uuid::Uuid::new_v4().to_string(),
+            "session": session,
+            "username": "brewco",
+            "date": chrono::Utc::now().to_rfc3339(),
+            "msg_type": msg_type,
+            "version": PROTOCOL_VERSION,
+        });
+        let parts: Vec<Vec<u8>> = [&header, &self.header, &json!({}), &content].iter().map(|part| part.to_string().into_bytes()).collect();
+        let signature = signer.sign(&[&parts[0], &parts[1], &parts[2], &parts[3]]);
+        let mut frames = ZmqMessage::from(DELIMITER.to_vec());
+        for identity in route.into_iter().rev() {
+            frames.push_front(identity.into());
+        }
+        frames.push_back(signature.into_bytes().into());
+        for part in parts {
+            frames.push_back(part.into());
+        }
+        frames
+    }
+
+    fn reply(&self, msg_type: &str, content: JsonValue, signer: &MessageSigner, session: &str) -> ZmqMessage {
+        self.answer(self.identities.clone(), msg_type, content, signer, session)
+    }
+
+    fn broadcast(&self, msg_type: &str, content: JsonValue, signer: &MessageSigner, session: &str) -> ZmqMessage {
+        self.answer(vec![msg_type.as_bytes().to_vec()], msg_type, content, signer, session)
+    }
+}
+
+/// A cell that didn't brew: the parts Jupyter shows as an error
+struct CellSpill {
+    ename: String,
+    evalue: String,
+    traceback: Vec<String>,
+}
+
+impl CellSpill {
+    fn from_report(mut report: CoffeeSpillReport, code: &str) -> Self {
+        report.frame_in_source("<cell>", code);
+        CellSpill {
+            ename: format!("{:?}", report.spill_type),
+            evalue: report.bitter_message.clone(),
+            traceback: report.brew_detailed_report().lines().map(str::to_string).collect(),
+        }
+    }
+}
+
+/// The shell side of the kernel: the interpreter every cell brews on
+struct BrewKernel {
+    interpreter: Interpreter,
+    execution_count: u64,
+    signer: MessageSigner,
+    session: String,
+    iopub: UnboundedSender<ZmqMessage>,
+}
+
+impl BrewKernel {
+    fn publish(&self, parent: &KernelMessage, msg_type: &str, content: JsonValue) {
+        let _ = self.iopub.send(parent.broadcast(msg_type, content, &self.signer, &self.session));
+    }
+
+    /// The reply to a shell request, if it gets one
+    fn handle(&mut self, request: &KernelMessage) -> Option<(&'static str, JsonValue)> {
+        match request.msg_type() {
+            "kernel_info_request" => Some(("kernel_info_reply", kernel_info())),
+            "execute_request" => Some(("execute_reply", self.execute(request))),
+            "complete_request" => Some(("complete_reply", self.complete(&request.content))),
+            "inspect_request" => Some(("inspect_reply", self.inspect(&request.content))),
+            "is_complete_request" => Some(("is_complete_reply", is_complete(request.content["code"].as_str().unwrap_or("")))),
+            "history_request" => Some(("history_reply", json!({"status": "ok", "history": []}))),
+            "comm_info_request" => Some(("comm_info_reply", json!({"status": "ok", "comms": {}}))),
+            "shutdown_request" => Some(("shutdown_reply", json!({"status": "ok", "restart": request.content["restart"]}))),
+            other => {
+                eprintln!("[Coffee Warning] The kernel doesn't know what to do with a {}", other);
+                None
+            }
+        }
+    }
+
+    fn execute(&mut self, request: &KernelMessage) -> JsonValue {
+        let code = request.content["code"].as_str().unwrap_or("");
+        let silent = request.content["silent"].as_bool().unwrap_or(false);
+        if !silent {
+            self.execution_count += 1;
+            self.publish(request, "execute_input", json!({"code": code, "execution_count": self.execution_count}));
+        }
+
+        let (iopub, signer, session) = (self.iopub.clone(), self.signer.clone(), self.session.clone());
+        let parent = KernelMessage { identities: Vec::new(), header: request.header.clone(), content: JsonValue::Null };
+        interpreter::tap_pours(move |text, spout| {
+            let (name, text) = match spout {
+                PourSpout::Line => ("stdout", format!("{}\n", text)),
+                PourSpout::Flat => ("stdout", text.to_string()),
+                PourSpout::Spill => ("stderr", format!("{}\n", text)),
+            };
+            let _ = iopub.send(parent.broadcast("stream", json!({"name": name, "text": text}), &signer, &session));
+        });
+        interpreter::clear_interrupt();
+        let brewed = self.brew_cell(code);
+        interpreter::untap_pours();
+
+        match brewed {
+            Ok(Some(value)) if !matches!(value, Value::Null) => {
+                if !silent {
+                    let result = json!({"execution_count": self.execution_count, "data": display_data(&value), "metadata": {}});
+                    self.publish(request, "execute_result", result);
+                }
+                self.interpreter.set_global(LAST_SERVED, value);
+            }
+            Ok(_) => {}
+            Err(spill) => {
+                let error = json!({"ename": spill.ename, "evalue": spill.evalue, "traceback": spill.traceback});
+                self.publish(request, "error", error.clone());
+                let mut reply = json!({"status": "error", "execution_count": self.execution_count});
+                reply.as_object_mut().unwrap().extend(error.as_object().unwrap().clone());
+                return reply;
+            }
+        }
+        json!({"status": "ok", "execution_count": self.execution_count, "user_expressions": {}, "payload": []})
+    }
+
+    fn brew_cell(&mut self, code: &str) -> Result<Option<Value>, CellSpill> {
+        let parsed = parser::parse_source(code);
+        if let (Some(error), Some(&(line, column))) = (parsed.errors.first(), parsed.error_positions.first()) {
+            let report = CoffeeSpillReport::new_brewing_disaster(SpillType::IncompleteRecipe, line, column, error);
+            return Err(CellSpill::from_report(report, code));
+        }
+        match self.interpreter.run_serving_last(&parsed.statements) {
+            Ok(served) => Ok(served),
+            Err(ControlFlow::RuntimeError(message)) => Err(CellSpill::from_report(self.interpreter.spill_report(&message), code)),
+            // The kernel keeps serving; restarting it is how a notebook closes the shop
+            Err(ControlFlow::FinishService(status)) => Err(CellSpill {
+                ename: "FinishedService".to_string(),
+                evalue: format!("finish_service({}) closed the shop; the rest of that cell was left unbrewed", status),
+                traceback: Vec::new(),
+            }),
+            Err(_) => Err(CellSpill {
+                ename: "UnfinishedOrder".to_string(),
+                evalue: "Only a brew can serve, break or continue; that cell was left unfinished.".to_string(),
+                traceback: Vec::new(),
+            }),
+        }
+    }
+
+    /// Beans, brews, natives and keywords starting with the word under the cursor
+    fn complete(&self, content: &JsonValue) -> JsonValue {
+        let code = content["code"].as_str().unwrap_or("");
+        let cursor = content["cursor_pos"].as_u64().unwrap_or(0) as usize;
+        let (start, word) = word_before(code, cursor);
+        let mut matches: Vec<String> = self
+            .interpreter
+            .binding_names()
+            .into_iter()
+            .chain(self.interpreter.native_brews().into_iter().map(|(name, _)| name.to_string()))
+            .chain(lexer::COFFEE_WORDS.iter().map(|word| word.to_string()))
+            .filter(|name| name.starts_with(&word))
+            .collect();
+        matches.sort();
+        matches.dedup();
+        json!({"status": "ok", "matches": matches, "cursor_start": start, "cursor_end": cursor, "metadata": {}})
+    }
+
+    /// A native's parameters, or what a bean in the pantry holds
+    fn inspect(&self, content: &JsonValue) -> JsonValue {
+        let code = content["code"].as_str().unwrap_or("");
+        let cursor = content["cursor_pos"].as_u64().unwrap_or(0) as usize;
+        let (_, word) = word_before(code, cursor);
+        let found = match self.interpreter.native_brews().into_iter().find(|(name, _)| *name == word) {
+            Some((name, native)) => Some(format!("{}({})  [{}]", name, native.params, native.group)),
+            None => self.interpreter.get_global(&word).map(|value| format!("{}: {:?}", value.type_name(), value)),
+        };
+        match found {
+            Some(text) => json!({"status": "ok", "found": true, "data": {"text/plain": text}, "metadata": {}}),
+            None => json!({"status": "ok", "found": false, "data": {}, "metadata": {}}),
+        }
+    }
+}
+
+/// The identifier ending at `cursor` (counted in characters, as Jupyter does) and where it starts
+fn word_before(code: &str, cursor: usize) -> (usize, String) {
+    let before: Vec<char> = code.chars().take(cursor).collect();
+    let start = before.iter().rposition(|c| !(c.is_alphanumeric() || *c == '_')).map_or(0, |at| at + 1);
+    (start, before[start..].iter().collect())
+}
+
+fn kernel_info() -> JsonValue {
+    json!({
+        "status": "ok",
+        "protocol_version": PROTOCOL_VERSION,
+        "implementation": "brewco",
+        "implementation_version": env!("CARGO_PKG_VERSION"),
+        "language_info": {
+            "name": "brewco",
+            "version": env!("CARGO_PKG_VERSION"),
+            "mimetype": "text/x-brewco",
+            "file_extension": ".brewco",
+        },
+        "banner": format!("☕ Brewco {} - a cup per cell", env!("CARGO_PKG_VERSION")),
+        "help_links": [],
+    })
+}
+
+/// Open brackets mean the cell isn't finished yet; anything else that won't parse is invalid
+fn is_complete(code: &str) -> JsonValue {
+    let mut depth = 0i64;
+    for spanned in CoffeeTokenStream::new(code) {
+        match spanned.token {
+            Token::LBrace | Token::LBracket | Token::LParen => depth += 1,
+            Token::RBrace | Token::RBracket | Token::RParen => depth -= 1,
+            _ => {}
+        }
+    }
+    if depth > 0 {
+        json!({"status": "incomplete", "indent": "    "})
+    } else if parser::parse_source(code).errors.is_empty() {
+        json!({"status": "complete"})
+    } else {
+        json!({"status": "invalid"})
+    }
+}
+
+/// How a cell's last value shows: as pretty_blend text, as a table for cups and menus, and as JSON when it fits
+fn display_data(value: &Value) -> JsonValue {
+    let mut data = json!({"text/plain": native::pretty_blend(value)});
+    if let Some(table) = html_table(value) {
+        data["text/html"] = JsonValue::String(table);
+    }
+    if matches!(value, Value::Array(_) | Value::Set(_) | Value::Chain(_) | Value::Object { .. }) {
+        if let Ok(json) = JsonValue::try_from(value) {
+            data["application/json"] = json;
+        }
+    }
+    data
+}
+
+fn html_table(value: &Value) -> Option<String> {
+    let items: Vec<Value> = match value {
+        Value::Array(cup) => cup.to_vec(),
+        Value::Set(set) => set.borrow().items().cloned().collect(),
+        Value::Chain(chain) => chain.borrow().iter().cloned().collect(),
+        Value::Object { class_name, fields } => {
+            let mut names: Vec<&String> = fields.keys().collect();
+            names.sort();
+            let rows: String = names.iter().map(|name| format!("<tr><th>{}</th><td>{}</td></tr>", escape_html(name), cell_html(&fields[*name]))).collect();
+            let caption = if class_name.is_empty() { String::new() } else { format!("<caption>{}</caption>", escape_html(class_name)) };
+            return Some(format!("<table>{}{}</table>", caption, rows));
+        }
+        _ => return None,
+    };
+    // A cup of menus (rows from sip_csv, say) gets a column per field
+    if !items.is_empty() && items.iter().all(|item| matches!(item, Value::Object { .. })) {
+        let mut columns: Vec<String> = items
+            .iter()
+            .flat_map(|item| match item {
+                Value::Object { fields, .. } => fields.keys().cloned().collect(),
+                _ => Vec::new(),
+            })
+            .collect();
+        columns.sort();
+        columns.dedup();
+        let header: String = columns.iter().map(|column| format!("<th>{}</th>", escape_html(column))).collect();
+        let rows: String = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let Value::Object { fields, .. } = item else { unreachable!() };
+                let cells: String = columns.iter().map(|column| format!("<td>{}</td>", fields.get(column).map_or(String::new(), cell_html))).collect();
+                format!("<tr><th>{}</th>{}</tr>", index, cells)
+            })
+            .collect();
+        return Some(format!("<table><tr><th></th>{}</tr>{}</table>", header, rows));
+    }
+    let rows: String = items.iter().enumerate().map(|(index, item)| format!("<tr><th>{}</th><td>{}</td></tr>", index, cell_html(item))).collect();
+    Some(format!("<table>{}</table>", rows))
+}
+
+fn cell_html(value: &Value) -> String {
+    match value {
+        Value::String(text) => escape_html(text),
+        other => escape_html(&format!("{:?}", other)),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Answer the control channel: shutdowns, interrupts and kernel_info, even while a cell brews
+async fn serve_control(mut control: RouterSocket, signer: MessageSigner, session: String) {
+    while let Ok(frames) = control.recv().await {
+        let request = match KernelMessage::decode(frames, &signer) {
+            Ok(request) => request,
+            Err(e) => {
+                eprintln!("[Coffee Warning] The kernel skipped {} on the control channel", e);
+                continue;
+            }
+        };
+        let (msg_type, content) = match request.msg_type() {
+            "shutdown_request" => ("shutdown_reply", json!({"status": "ok", "restart": request.content["restart"]})),
+            "interrupt_request" => {
+                let _ = interpreter::request_interrupt();
+                ("interrupt_reply", json!({"status": "ok"}))
+            }
+            "kernel_info_request" => ("kernel_info_reply", kernel_info()),
+            _ => continue,
+        };
+        let _ = control.send(request.reply(msg_type, content, &signer, &session)).await;
+        if msg_type == "shutdown_reply" {
+            std::process::exit(0);
+        }
+    }
+}
+
+async fn bind<S: Socket>(info: &ConnectionInfo, port: u16, channel: &str) -> Result<S, String> {
+    let mut socket = S::new();
+    let endpoint = info.endpoint(port);
+    socket.bind(&endpoint).await.map_err(|e| format!("couldn't open the {} channel on {}: {}", channel, endpoint, e))?;
+    Ok(socket)
+}
+
+async fn serve_notebook(info: ConnectionInfo) -> Result<(), String> {
+    let signer = MessageSigner { key: info.key.clone().into_bytes() };
+    let session = uuid::Uuid::new_v4().to_string();
+    let mut shell: RouterSocket = bind(&info, info.shell_port, "shell").await?;
+    let control: RouterSocket = bind(&info, info.control_port, "control").await?;
+    let _stdin: RouterSocket = bind(&info, info.stdin_port, "stdin").await?;
+    let mut iopub: PubSocket = bind(&info, info.iopub_port, "iopub").await?;
+    let mut heartbeat: RepSocket = bind(&info, info.hb_port, "heartbeat").await?;
+
+    tokio::spawn(async move {
+        while let Ok(ping) = heartbeat.recv().await {
+            if heartbeat.send(ping).await.is_err() {
+                break;
+            }
+        }
+    });
+    let (iopub_sender, mut iopub_queue) = mpsc::unbounded_channel::<ZmqMessage>();
+    tokio::spawn(async move {
+        while let Some(message) = iopub_queue.recv().await {
+            let _ = iopub.send(message).await;
+        }
+    });
+    tokio::spawn(serve_control(control, signer.clone(), session.clone()));
+
+    let mut kernel = BrewKernel { interpreter: Interpreter::new(), execution_count: 0, signer: signer.clone(), session: session.clone(), iopub: iopub_sender };
+    loop {
+        let frames = shell.recv().await.map_err(|e| format!("the shell channel closed: {}", e))?;
+        let request = match KernelMessage::decode(frames, &signer) {
+            Ok(request) => request,
+            Err(e) => {
+                eprintln!("[Coffee Warning] The kernel skipped {} on the shell channel", e);
+                continue;
+            }
+        };
+        kernel.publish(&request, "status", json!({"execution_state": "busy"}));
+        let reply = kernel.handle(&request);
+        if let Some((msg_type, content)) = reply {
+            shell.send(request.reply(msg_type, content, &signer, &session)).await.map_err(|e| format!("couldn't answer on the shell channel: {}", e))?;
+        }
+        kernel.publish(&request, "status", json!({"execution_state": "idle"}));
+        if request.msg_type() == "shutdown_request" {
+            // Let iopub get the idle status out before the shop closes
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            return Ok(());
+        }
+    }
+}
+
+/// `brew kernel <connection file>`: serve a notebook until it shuts us down
+pub fn run_brew_kernel(connection_file: &str) {
+    let info: ConnectionInfo = match fs::read_to_string(connection_file).map_err(|e| e.to_string()).and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string())) {
+        Ok(info) => info,
+        Err(e) => {
+            println!("[ERROR] Could not read the connection file {}: {}", connection_file, e);
+            std::process::exit(1);
+        }
+    };
+    if !info.key.is_empty() && info.signature_scheme != "hmac-sha256" {
+        println!("[ERROR] The kernel only signs messages with hmac-sha256, not {}", info.signature_scheme);
+        std::process::exit(1);
+    }
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            println!("[ERROR] Could not start the kernel: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = runtime.block_on(serve_notebook(info)) {
+        println!("[ERROR] The kernel stopped: {}", e);
+        std::process::exit(1);
+    }
+    // Background tasks may still hold sockets; don't wait on them
+    runtime.shutdown_background();
+}
+
+/// Where Jupyter looks for kernelspecs of this user
+fn jupyter_data_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("JUPYTER_DATA_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    if cfg!(windows) {
+        return std::env::var_os("APPDATA").map(|appdata| PathBuf::from(appdata).join("jupyter"));
+    }
+    let home = PathBuf::from(std::env::var_os("HOME")?);
+    if cfg!(target_os = "macos") {
+        Some(home.join("Library").join("Jupyter"))
+    } else {
+        Some(std::env::var_os("XDG_DATA_HOME").map_or_else(|| home.join(".local").join("share"), PathBuf::from).join("jupyter"))
+    }
+}
+
+/// `brew kernel --install`: tell Jupyter how to start this brew as a kernel
+pub fn install_kernelspec() {
+    let (Some(data_dir), Ok(brew)) = (jupyter_data_dir(), std::env::current_exe()) else {
+        println!("[ERROR] Could not work out where Jupyter keeps its kernels; set JUPYTER_DATA_DIR");
+        std::process::exit(1);
+    };
+    let dir = data_dir.join("kernels").join("brewco");
+    let spec = json!({
+        "argv": [brew.to_string_lossy(), "kernel", "{connection_file}"],
+        "display_name": "Brewco",
+        "language": "brewco",
+        "interrupt_mode": "signal",
+    });
+    let written = fs::create_dir_all(&dir).and_then(|_| fs::write(dir.join("kernel.json"), format!("{:#}\n", spec)));
+    match written {
+        Ok(()) => println!("📓 Installed the Brewco kernel in {}; pick \"Brewco\" when you start a notebook", dir.display()),
+        Err(e) => {
+            println!("[ERROR] Could not write the kernelspec to {}: {}", dir.display(), e);
+            std::process::exit(1);
+        }
+    }
+}
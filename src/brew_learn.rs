@@ -0,0 +1,309 @@
+/*
+ * 🎓 Brewco Barista School ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @school_note: "Every barista started with their first pour!" ✨
+ *
+ * `brew learn` opens the REPL with a tutor at the counter. Lessons are
+ * data (LESSONS below): what the lesson teaches, a task, a hint, and
+ * checks. The learner brews at the counter as usual, and `:check` brews
+ * the orders they served since the lesson began on a fresh interpreter,
+ * then runs the lesson's checks (assertions, and what had to be poured)
+ * against it. A passed lesson is remembered in `~/.brewco_lessons.json`,
+ * so `brew learn` picks up at the first one not passed yet.
+ */
+
+// src/brew_learn.rs
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::interpreter::{self, ControlFlow, Interpreter};
+use crate::parser;
+
+const PROGRESS_FILE: &str = ".brewco_lessons.json";
+
+/// Steps a checked answer gets, so a steep that never ends fails the check instead of hanging it
+const CHECK_FUEL: u64 = 1_000_000;
+
+/// One lesson of the tutorial
+pub struct Lesson {
+    pub id: &'static str,
+    pub title: &'static str,
+    /// What the lesson teaches, with a small example
+    pub teaching: &'static str,
+    pub task: &'static str,
+    pub hint: &'static str,
+    /// Brewed one by one after the learner's orders; each spills (an assertion, usually) unless the task is done
+    pub checks: &'static [&'static str],
+    /// What the learner's orders have to pour, if the task is about pouring
+    pub pours: Option<&'static str>,
+}
+
+pub const LESSONS: &[Lesson] = &[
+    Lesson {
+        id: "pour",
+        title: "Pour it out",
+        teaching: "`pourout` shows a value. Strings go in double quotes, and several values\n\
+                   go out separated by spaces:\n\n    pourout \"Good morning\"\n    pourout \"Shots:\", 2",
+        task: "Pour out the greeting Hello, Brewco!",
+        hint: "pourout \"Hello, Brewco!\"",
+        checks: &[],
+        pours: Some("Hello, Brewco!"),
+    },
+    Lesson {
+        id: "beans",
+        title: "Beans hold values",
+        teaching: "`beans` declares a name for a value; later orders can use it, and `=` changes it:\n\n    \
+                   beans cups = 2\n    beans ounces = cups * 8\n    cups = cups + 1",
+        task: "Declare `shots` as 3, then `price` as shots times 2.5",
+        hint: "beans shots = 3, then beans price = shots * 2.5",
+        checks: &["assert_same_blend(shots, 3)", "assert_same_blend(price, 7.5)"],
+        pours: None,
+    },
+    Lesson {
+        id: "brew",
+        title: "Brews",
+        teaching: "`brew` declares a function and `serve` hands back its result:\n\n    \
+                   brew add_milk(drink) {\n        serve drink + \" with milk\"\n    }\n    pourout add_milk(\"tea\")",
+        task: "Write a brew `double(n)` that serves n times 2",
+        hint: "brew double(n) { ... } with serve n * 2 inside",
+        checks: &["assert_same_blend(double(4), 8)", "assert_same_blend(double(-1), -2)", "assert_same_blend(double(0), 0)"],
+        pours: None,
+    },
+    Lesson {
+        id: "taste",
+        title: "Taste and otherwise",
+        teaching: "`taste` brews a block only when its condition holds, and `otherwise` when it doesn't:\n\n    \
+                   taste shots > 2 {\n        pourout \"strong\"\n    } otherwise {\n        pourout \"mild\"\n    }",
+        task: "Write `size_for(ounces)`: \"small\" under 8, \"medium\" from 8 up to 12, \"large\" above that",
+        hint: "Check ounces < 8 first and serve \"small\"; inside otherwise, check ounces <= 12; serve \"large\" last",
+        checks: &[
+            "assert_same_blend(size_for(6), \"small\")",
+            "assert_same_blend(size_for(8), \"medium\")",
+            "assert_same_blend(size_for(12), \"medium\")",
+            "assert_same_blend(size_for(16), \"large\")",
+        ],
+        pours: None,
+    },
+    Lesson {
+        id: "steep",
+        title: "Steep while it's true",
+        teaching: "`steep` repeats a block for as long as its condition holds:\n\n    \
+                   beans left = 3\n    steep left > 0 {\n        pourout left\n        left = left - 1\n    }",
+        task: "Write `sum_to(n)` that adds up 1, 2, ... n with a steep (and serves 0 for 0)",
+        hint: "Keep a total and a counter in beans; steep while the counter is <= n, adding it to the total",
+        checks: &["assert_same_blend(sum_to(10), 55)", "assert_same_blend(sum_to(1), 1)", "assert_same_blend(sum_to(0), 0)"],
+        pours: None,
+    },
+    Lesson {
+        id: "cups",
+        title: "Cups and pour ... in",
+        teaching: "A cup holds values in order: `[\"espresso\", \"latte\"]`. `pour x in cup` brews a block\n\
+                   for each of them, and `cup_size(cup)` counts them:\n\n    \
+                   beans menu = [\"espresso\", \"latte\"]\n    pour drink in menu {\n        pourout drink\n    }",
+        task: "Write `total(prices)` that serves the sum of a cup of prices",
+        hint: "Start a sum at 0, then pour price in prices { ... } adding each price",
+        checks: &["assert_same_blend(total([1.5, 2, 3]), 6.5)", "assert_same_blend(total([4]), 4)", "assert_same_blend(total([]), 0)"],
+        pours: None,
+    },
+    Lesson {
+        id: "bean",
+        title: "Beans with brews inside",
+        teaching: "`bean` declares a kind of object. Fields are declared with `pour_in`, brews inside it\n\
+                   see the object as `this`, and `new` makes one:\n\n    \
+                   bean Counter {\n        beans count pour_in 0\n        brew tick() {\n            this.count = this.count + 1\n        }\n    }\n    \
+                   beans clicks = new Counter()\n    clicks.tick()",
+        task: "Declare a bean `Mug` with a field `filled` starting at 0 and a brew `refill(amount)` adding to it",
+        hint: "bean Mug { beans filled pour_in 0 ... } with this.filled = this.filled + amount in refill",
+        checks: &[
+            "beans learner_mug = new Mug()",
+            "assert_same_blend(learner_mug.filled, 0)",
+            "learner_mug.refill(3)",
+            "learner_mug.refill(2)",
+            "assert_same_blend(learner_mug.filled, 5)",
+        ],
+        pours: None,
+    },
+    Lesson {
+        id: "spills",
+        title: "Catching spills",
+        teaching: "When something can't brew (like dividing by zero) it spills. `taste_carefully` catches\n\
+                   a spill from its block and brews `if_spilled` instead:\n\n    \
+                   taste_carefully {\n        beans per_cup = 12 / cups\n    } if_spilled (mess) {\n        pourout \"Spilled:\", mess\n    }",
+        task: "Write `safe_divide(a, b)` serving a / b, or 0 when the division spills",
+        hint: "Keep the answer in a bean: set it inside taste_carefully, set it to 0 in if_spilled, then serve it",
+        checks: &["assert_same_blend(safe_divide(6, 3), 2)", "assert_same_blend(safe_divide(1, 0), 0)"],
+        pours: None,
+    },
+];
+
+/// Which lessons have been passed, kept in `~/.brewco_lessons.json`
+#[derive(Serialize, Deserialize, Default)]
+struct LessonProgress {
+    completed: Vec<String>,
+}
+
+/// `~/.brewco_lessons.json`, or nothing if there's no home directory to keep it in
+fn progress_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").filter(|home| !home.is_empty()).map(|home| PathBuf::from(home).join(PROGRESS_FILE))
+}
+
+impl LessonProgress {
+    fn load() -> Self {
+        progress_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|saved| serde_json::from_str(&saved).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        let Some(path) = progress_path() else { return };
+        let saved = serde_json::to_string_pretty(self).map_err(|e| e.to_string()).and_then(|json| std::fs::write(&path, json).map_err(|e| e.to_string()));
+        if let Err(e) = saved {
+            println!("⚠️  Could not save lesson progress to {}: {}", path.display(), e);
+        }
+    }
+
+    fn passed(&self, lesson: &Lesson) -> bool {
+        self.completed.iter().any(|id| id == lesson.id)
+    }
+}
+
+/// Brew the learner's `orders` on a fresh interpreter, then each of the lesson's checks
+pub fn check_lesson(lesson: &Lesson, orders: &str) -> Result<(), String> {
+    let parsed = parser::parse_source(orders);
+    if let Some(error) = parsed.errors.first() {
+        return Err(format!("your orders don't parse together: {}", error));
+    }
+    let mut interpreter = Interpreter::new();
+    interpreter.set_fuel(CHECK_FUEL);
+    interpreter::catch_pours();
+    let ran = interpreter.run(&parsed.statements);
+    let poured = interpreter::take_caught_pours();
+    if let Err(ControlFlow::RuntimeError(message)) = ran {
+        return Err(format!("your orders spilled when brewed again: {}", message));
+    }
+    if let Some(wanted) = lesson.pours {
+        if !poured.lines().any(|line| line.trim_end() == wanted) {
+            return Err(format!("nothing poured out exactly {}", wanted));
+        }
+    }
+    for check in lesson.checks {
+        let parsed = parser::parse_source(check);
+        interpreter::catch_pours();
+        let ran = interpreter.run(&parsed.statements);
+        interpreter::take_caught_pours();
+        if let Err(ControlFlow::RuntimeError(message)) = ran {
+            return Err(format!("`{}` spilled:\n  {}", check, message.replace('\n', "\n  ")));
+        }
+    }
+    Ok(())
+}
+
+/// The tutor at the counter: which lesson is going, and where its orders start
+pub struct Tutor {
+    current: usize,
+    /// How many orders had been served when the lesson began; the ones after are the answer
+    pub orders_from: usize,
+    progress: LessonProgress,
+}
+
+impl Tutor {
+    /// Start at the lesson named by id or number, or at the first one not passed yet
+    pub fn start(choice: &str, orders_served: usize) -> Result<Tutor, String> {
+        let progress = LessonProgress::load();
+        let current = if choice.is_empty() {
+            LESSONS.iter().position(|lesson| !progress.passed(lesson)).unwrap_or(0)
+        } else {
+            find_lesson(choice)?
+        };
+        let tutor = Tutor { current, orders_from: orders_served, progress };
+        tutor.present();
+        Ok(tutor)
+    }
+
+    fn lesson(&self) -> &'static Lesson {
+        &LESSONS[self.current]
+    }
+
+    /// What goes in the prompt so the learner knows which lesson they're on
+    pub fn prompt_tag(&self) -> String {
+        format!("🎓 {}/{} {}", self.current + 1, LESSONS.len(), self.lesson().id)
+    }
+
+    pub fn present(&self) {
+        let lesson = self.lesson();
+        println!("\n🎓 Lesson {} of {}: {}", self.current + 1, LESSONS.len(), lesson.title);
+        println!("{}\n", lesson.teaching);
+        println!("📝 Your task: {}", lesson.task);
+        println!("   Brew it at the counter, then :check it (:hint helps, :skip moves on, :lessons lists them all)\n");
+    }
+
+    pub fn hint(&self) {
+        println!("💡 {}", self.lesson().hint);
+    }
+
+    /// Check the orders served since the lesson began; a pass is saved and the next lesson starts
+    pub fn check(&mut self, orders: &[String]) {
+        let answer = orders.get(self.orders_from..).unwrap_or_default().join("\n");
+        if answer.trim().is_empty() {
+            println!("☕ Nothing to check yet: brew your answer at the counter first, then :check");
+            return;
+        }
+        let lesson = self.lesson();
+        if let Err(why) = check_lesson(lesson, &answer) {
+            println!("☕ Not quite yet: {}", why);
+            println!("   Fix it at the counter and :check again (:hint if you're stuck)");
+            return;
+        }
+        println!("✅ Lesson passed: {}!", lesson.title);
+        if !self.progress.passed(lesson) {
+            self.progress.completed.push(lesson.id.to_string());
+            self.progress.save();
+        }
+        self.move_on(orders.len());
+    }
+
+    /// On to the next lesson without passing this one
+    pub fn skip(&mut self, orders_served: usize) {
+        println!("⏭️  Skipped {}; come back with :learn {} any time", self.lesson().title, self.lesson().id);
+        self.move_on(orders_served);
+    }
+
+    fn move_on(&mut self, orders_served: usize) {
+        self.orders_from = orders_served;
+        // The next lesson not passed yet, coming round to the first ones again after the last
+        let after = LESSONS.iter().enumerate().skip(self.current + 1);
+        let before = LESSONS.iter().enumerate().take(self.current);
+        match after.chain(before).find(|(_, lesson)| !self.progress.passed(lesson)) {
+            Some((next, _)) => {
+                self.current = next;
+                self.present();
+            }
+            None if self.progress.passed(self.lesson()) => {
+                println!("🎉 That's every lesson! You're a Brewco barista now. The counter is yours.");
+            }
+            None => println!("☕ That's the only lesson left to pass; :learn {} brings it back", self.lesson().id),
+        }
+    }
+
+    pub fn list(&self) {
+        println!("🎓 Lessons ({} of {} passed):", self.progress.completed.len().min(LESSONS.len()), LESSONS.len());
+        for (index, lesson) in LESSONS.iter().enumerate() {
+            let mark = if self.progress.passed(lesson) { "✅" } else { "  " };
+            let here = if index == self.current { " ◀ you are here" } else { "" };
+            println!("  {} {:>2}. {:<8} {}{}", mark, index + 1, lesson.id, lesson.title, here);
+        }
+    }
+}
+
+/// A lesson's index from its id or its number (counting from 1)
+fn find_lesson(choice: &str) -> Result<usize, String> {
+    let by_number = choice.parse::<usize>().ok().filter(|n| (1..=LESSONS.len()).contains(n)).map(|n| n - 1);
+    by_number.or_else(|| LESSONS.iter().position(|lesson| lesson.id == choice)).ok_or_else(|| {
+        let ids: Vec<&str> = LESSONS.iter().map(|lesson| lesson.id).collect();
+        format!("there's no lesson '{}'; pick a number from 1 to {} or one of {}", choice, LESSONS.len(), ids.join(", "))
+    })
+}
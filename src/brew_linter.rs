@@ -0,0 +1,712 @@
+/*
+ * 🧽 Brewco Brew Linter ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @linter_note: "A tidy counter makes for better coffee!" ✨
+ *
+ * `brew lint` walks the parsed recipe looking for beans nobody tastes,
+ * beans hiding other beans, code stranded after `serve`, empty blocks,
+ * un-coffee-like names, brews with too many branches, strings quietly
+ * added to numbers and roast arms that can never match. Every rule's
+ * severity can be tuned in a `brewlint.toml`:
+ *
+ *     max_complexity = 10
+ *
+ *     [rules]
+ *     coffee_naming = "off"
+ *     unused_beans = "error"
+ *
+ * The language server runs the same rules for its style diagnostics.
+ */
+
+// src/brew_linter.rs
+
+use std::path::Path;
+
+use crate::ast::{BinaryOperator, Expr, RoastPattern, Statement};
+
+pub const BREWLINT_CONFIG_FILE: &str = "brewlint.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrewLintRule {
+    CoffeeNaming,
+    Complexity,
+    UnusedBeans,
+    ShadowedBeans,
+    UnreachableAfterServe,
+    EmptyBlock,
+    StringCoercion,
+    UnreachableRoastArm,
+}
+
+impl BrewLintRule {
+    pub const ALL: [BrewLintRule; 8] = [
+        BrewLintRule::CoffeeNaming,
+        BrewLintRule::Complexity,
+        BrewLintRule::UnusedBeans,
+        BrewLintRule::ShadowedBeans,
+        BrewLintRule::UnreachableAfterServe,
+        BrewLintRule::EmptyBlock,
+        BrewLintRule::StringCoercion,
+        BrewLintRule::UnreachableRoastArm,
+    ];
+
+    /// The rule's key in brewlint.toml and in reports
+    pub fn name(self) -> &'static str {
+        match self {
+            BrewLintRule::CoffeeNaming => "coffee_naming",
+            BrewLintRule::Complexity => "complexity",
+            BrewLintRule::UnusedBeans => "unused_beans",
+            BrewLintRule::ShadowedBeans => "shadowed_beans",
+            BrewLintRule::UnreachableAfterServe => "unreachable_after_serve",
+            BrewLintRule::EmptyBlock => "empty_block",
+            BrewLintRule::StringCoercion => "string_coercion",
+            BrewLintRule::UnreachableRoastArm => "unreachable_roast_arm",
+        }
+    }
+
+    fn default_severity(self) -> LintSeverity {
+        match self {
+            BrewLintRule::CoffeeNaming => LintSeverity::Note,
+            _ => LintSeverity::Warn,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintSeverity {
+    Off,
+    Note,
+    Warn,
+    Error,
+}
+
+impl LintSeverity {
+    fn from_config(value: &str) -> Option<Self> {
+        match value {
+            "off" | "allow" => Some(LintSeverity::Off),
+            "note" | "info" => Some(LintSeverity::Note),
+            "warn" | "warning" => Some(LintSeverity::Warn),
+            "error" | "deny" => Some(LintSeverity::Error),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LintSeverity::Off => "off",
+            LintSeverity::Note => "note",
+            LintSeverity::Warn => "warning",
+            LintSeverity::Error => "error",
+        }
+    }
+}
+
+/// Which rules run, how loudly, and the branch budget for one brew
+#[derive(Debug, Clone)]
+pub struct BrewLintConfig {
+    severities: [LintSeverity; 8],
+    pub max_complexity: usize,
+}
+
+impl Default for BrewLintConfig {
+    fn default() -> Self {
+        BrewLintConfig {
+            severities: BrewLintRule::ALL.map(BrewLintRule::default_severity),
+            max_complexity: 10,
+        }
+    }
+}
+
+impl BrewLintConfig {
+    /// Run just these rules, at warning severity
+    pub fn only(rules: &[BrewLintRule]) -> Self {
+        let mut config = BrewLintConfig { severities: [LintSeverity::Off; 8], ..Default::default() };
+        for &rule in rules {
+            config.set_severity(rule, LintSeverity::Warn);
+        }
+        config
+    }
+
+    pub fn severity(&self, rule: BrewLintRule) -> LintSeverity {
+        self.severities[rule as usize]
+    }
+
+    pub fn set_severity(&mut self, rule: BrewLintRule, severity: LintSeverity) {
+        self.severities[rule as usize] = severity;
+    }
+
+    /// Apply a brewlint.toml on top of these settings
+    pub fn apply_toml(mut self, text: &str) -> Result<Self, String> {
+        let mut section = String::new();
+        for (index, raw) in text.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let spill = |message: String| format!("{} line {}: {}", BREWLINT_CONFIG_FILE, index + 1, message);
+            if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+                section = name.trim().to_string();
+                if section != "rules" {
+                    return Err(spill(format!("unknown section [{}]; only [rules] is supported", section)));
+                }
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(spill(format!("expected `key = value`, found `{}`", line)));
+            };
+            let (key, value) = (key.trim(), value.trim().trim_matches('"'));
+            match (section.as_str(), key) {
+                ("", "max_complexity") => {
+                    self.max_complexity = value.parse()
+                        .map_err(|_| spill(format!("max_complexity must be a whole number, found `{}`", value)))?;
+                }
+                ("rules", rule_name) => {
+                    let rule = BrewLintRule::ALL.into_iter().find(|rule| rule.name() == rule_name)
+                        .ok_or_else(|| spill(format!("unknown rule `{}`", rule_name)))?;
+                    let severity = LintSeverity::from_config(value)
+                        .ok_or_else(|| spill(format!("severity must be off, note, warn or error, found `{}`", value)))?;
+                    self.set_severity(rule, severity);
+                }
+                (_, other) => return Err(spill(format!("unknown setting `{}`", other))),
+            }
+        }
+        Ok(self)
+    }
+
+    /// Apply the [lint] table of a brewco.toml, which takes the same settings as a brewlint.toml
+    pub fn apply_table(mut self, table: &toml::Table) -> Result<Self, String> {
+        for (key, value) in table {
+            match (key.as_str(), value) {
+                ("max_complexity", toml::Value::Integer(n)) if *n >= 0 => self.max_complexity = *n as usize,
+                ("max_complexity", _) => return Err(format!("max_complexity must be a whole number, found `{}`", value)),
+                ("rules", toml::Value::Table(rules)) => {
+                    for (rule_name, severity) in rules {
+                        let rule = BrewLintRule::ALL.into_iter().find(|rule| rule.name() == rule_name)
+                            .ok_or_else(|| format!("unknown rule `{}`", rule_name))?;
+                        let severity = severity.as_str().and_then(LintSeverity::from_config)
+                            .ok_or_else(|| format!("severity must be off, note, warn or error, found `{}`", severity))?;
+                        self.set_severity(rule, severity);
+                    }
+                }
+                (other, _) => return Err(format!("unknown setting `{}`", other)),
+            }
+        }
+        Ok(self)
+    }
+
+    /// Apply the nearest brewlint.toml in `dir` or its parents, if there is one
+    pub fn discover(self, dir: &Path) -> Result<Self, String> {
+        for candidate in dir.ancestors() {
+            let path = candidate.join(BREWLINT_CONFIG_FILE);
+            if let Ok(text) = std::fs::read_to_string(&path) {
+                return self.apply_toml(&text).map_err(|e| format!("{}: {}", path.display(), e));
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// One thing the linter would like changed
+#[derive(Debug, Clone)]
+pub struct LintFinding {
+    pub rule: BrewLintRule,
+    pub severity: LintSeverity,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// What a bean is known to hold, as far as the linter can tell without brewing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BeanFlavor {
+    Number,
+    Text,
+}
+
+/// A bean declared in some scope, waiting to be tasted
+struct DeclaredBean {
+    name: String,
+    position: (usize, usize),
+    used: bool,
+    flavor: Option<BeanFlavor>,
+}
+
+struct LintWalk<'a> {
+    config: &'a BrewLintConfig,
+    findings: Vec<LintFinding>,
+    scopes: Vec<Vec<DeclaredBean>>,
+    mark: (usize, usize),
+}
+
+/// Lint a parsed recipe; findings come back in source order
+pub fn lint_coffee_statements(statements: &[Statement], config: &BrewLintConfig) -> Vec<LintFinding> {
+    let mut walk = LintWalk { config, findings: Vec::new(), scopes: vec![Vec::new()], mark: (1, 1) };
+    walk.walk_block(statements);
+    walk.findings.sort_by_key(|finding| (finding.line, finding.column));
+    walk.findings
+}
+
+fn is_coffee_themed_name(name: &str) -> bool {
+    let coffee_keywords = [
+        "coffee", "bean", "brew", "espresso", "latte", "mocha", "cappuccino",
+        "roast", "grind", "steam", "milk", "foam", "cup", "mug", "barista",
+        "cafe", "shop", "aroma", "flavor", "blend", "strength", "caffeine"
+    ];
+    coffee_keywords.iter().any(|keyword| name.to_lowercase().contains(keyword))
+}
+
+/// Branch points in a brew body, not counting brews declared inside it
+fn brew_complexity(statements: &[Statement]) -> usize {
+    statements.iter().map(|statement| match statement {
+        Statement::If { then_branch, else_branch, .. } => 1 + brew_complexity(then_branch) + brew_complexity(else_branch),
+        Statement::While { body, .. } | Statement::For { body, .. } | Statement::Foreach { body, .. } => 1 + brew_complexity(body),
+        Statement::TryCatch { try_branch, catch_branch, .. } => 1 + brew_complexity(try_branch) + brew_complexity(catch_branch),
+        Statement::RoastSwitch { arms, default, .. } => {
+            arms.iter().map(|(_, arm)| 1 + brew_complexity(arm)).sum::<usize>() + brew_complexity(default)
+        }
+        Statement::BenchBrew { body, .. } | Statement::TasteTest { body, .. } => brew_complexity(body),
+        _ => 0,
+    }).sum()
+}
+
+fn same_literal(a: &Expr, b: &Expr) -> bool {
+    match (a, b) {
+        (Expr::Number(a), Expr::Number(b)) => a == b,
+        (Expr::String(a), Expr::String(b)) => a == b,
+        (Expr::Boolean(a), Expr::Boolean(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Does the earlier roast pattern already match everything the later one does?
+fn roast_covers(earlier: &RoastPattern, later: &RoastPattern) -> bool {
+    let literal_range = |pattern: &RoastPattern| match pattern {
+        RoastPattern::Value(Expr::Number(n)) => Some((*n, *n)),
+        RoastPattern::Range(Expr::Number(low), Expr::Number(high)) => Some((*low, *high)),
+        _ => None,
+    };
+    match (earlier, later) {
+        (RoastPattern::Value(earlier), RoastPattern::Value(later)) if same_literal(earlier, later) => true,
+        _ => match (literal_range(earlier), literal_range(later)) {
+            (Some((low, high)), Some((later_low, later_high))) => low <= later_low && later_high <= high,
+            _ => false,
+        },
+    }
+}
+
+/// Is there anything besides source marks in this block?
+fn is_empty_block(statements: &[Statement]) -> bool {
+    statements.iter().all(|statement| matches!(statement, Statement::SourceMark { .. }))
+}
+
+impl<'a> LintWalk<'a> {
+    fn report(&mut self, rule: BrewLintRule, position: (usize, usize), message: String) {
+        let severity = self.config.severity(rule);
+        if severity != LintSeverity::Off {
+            self.findings.push(LintFinding { rule, severity, line: position.0, column: position.1, message });
+        }
+    }
+
+    fn push_scope(&mut self) {
+        self.scopes.push(Vec::new());
+    }
+
+    /// Close a scope, reporting the beans in it that were never read
+    fn pop_scope(&mut self) {
+        let Some(scope) = self.scopes.pop() else { return };
+        for bean in scope.into_iter().filter(|bean| !bean.used) {
+            self.report(
+                BrewLintRule::UnusedBeans,
+                bean.position,
+                format!("beans '{}' is declared but never tasted", bean.name),
+            );
+        }
+    }
+
+    /// Declare a bean in the innermost scope. Parameters and loop beans count as used already.
+    fn declare(&mut self, name: &str, used: bool) {
+        let position = self.mark;
+        let earlier = self.scopes.iter().enumerate().rev()
+            .find_map(|(depth, scope)| scope.iter().find(|bean| bean.name == name).map(|bean| (depth, bean.position.0)));
+        if let Some((depth, line)) = earlier {
+            let message = if depth + 1 == self.scopes.len() {
+                format!("beans '{}' is declared again; it was already declared on line {}", name, line)
+            } else {
+                format!("beans '{}' shadows the one declared on line {}", name, line)
+            };
+            self.report(BrewLintRule::ShadowedBeans, position, message);
+        }
+        if !used && !is_coffee_themed_name(name) {
+            self.report(
+                BrewLintRule::CoffeeNaming,
+                position,
+                format!("Consider a coffee-themed name for '{}', like 'bean_count' or 'brewing_time'", name),
+            );
+        }
+        // Top-level beans may be grinded by other recipes, so only nested ones can go unused
+        let used = used || self.scopes.len() == 1;
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(DeclaredBean { name: name.to_string(), position, used, flavor: None });
+        }
+    }
+
+    fn taste(&mut self, name: &str) {
+        for scope in self.scopes.iter_mut().rev() {
+            if let Some(bean) = scope.iter_mut().rev().find(|bean| bean.name == name) {
+                bean.used = true;
+                return;
+            }
+        }
+    }
+
+    fn bean_mut(&mut self, name: &str) -> Option<&mut DeclaredBean> {
+        self.scopes.iter_mut().rev().find_map(|scope| scope.iter_mut().rev().find(|bean| bean.name == name))
+    }
+
+    /// Remember what a bean was last filled with, so later `add`s know what they mix
+    fn fill(&mut self, name: &str, value: &Expr) {
+        let flavor = self.flavor_of(value);
+        if let Some(bean) = self.bean_mut(name) {
+            bean.flavor = flavor;
+        }
+    }
+
+    fn flavor_of(&mut self, expr: &Expr) -> Option<BeanFlavor> {
+        match expr {
+            Expr::Number(_) => Some(BeanFlavor::Number),
+            Expr::String(_) => Some(BeanFlavor::Text),
+            Expr::Identifier(name) => self.bean_mut(name).and_then(|bean| bean.flavor),
+            Expr::BinaryOp { left, op: BinaryOperator::Add, right } => {
+                match (self.flavor_of(left), self.flavor_of(right)) {
+                    (Some(BeanFlavor::Text), _) | (_, Some(BeanFlavor::Text)) => Some(BeanFlavor::Text),
+                    (Some(BeanFlavor::Number), Some(BeanFlavor::Number)) => Some(BeanFlavor::Number),
+                    _ => None,
+                }
+            }
+            Expr::BinaryOp {
+                op: BinaryOperator::Subtract | BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulo,
+                ..
+            } => Some(BeanFlavor::Number),
+            _ => None,
+        }
+    }
+
+    /// Report roast arms whose value an earlier arm already matches; the first match always wins
+    fn check_roast_arms(&mut self, arms: &[(Vec<RoastPattern>, Vec<Statement>)]) {
+        let roast_position = self.mark;
+        let mut seen: Vec<(&RoastPattern, usize)> = Vec::new();
+        for (patterns, arm) in arms {
+            let position = arm.iter().find_map(|statement| match statement {
+                Statement::SourceMark { line, column } => Some((*line, *column)),
+                _ => None,
+            }).unwrap_or(roast_position);
+            // Only an arm whose every value is already taken can never brew
+            let covering: Option<Vec<usize>> = patterns.iter().map(|pattern| {
+                seen.iter().find(|(earlier, _)| roast_covers(earlier, pattern)).map(|(_, line)| *line)
+            }).collect();
+            match covering {
+                Some(lines) if !lines.is_empty() => {
+                    let line = lines.into_iter().max().unwrap_or(position.0);
+                    self.report(
+                        BrewLintRule::UnreachableRoastArm,
+                        position,
+                        format!("This roast arm never brews: the arm on line {} already matches the same value", line),
+                    );
+                }
+                _ => seen.extend(patterns.iter().map(|pattern| (pattern, position.0))),
+            }
+        }
+    }
+
+    fn walk_block(&mut self, statements: &[Statement]) {
+        let mut stopped_by: Option<&str> = None;
+        let mut reported_unreachable = false;
+        for statement in statements {
+            if let Statement::SourceMark { line, column } = statement {
+                self.mark = (*line, *column);
+                continue;
+            }
+            if let Some(keyword) = stopped_by {
+                if !reported_unreachable {
+                    reported_unreachable = true;
+                    let position = self.mark;
+                    self.report(
+                        BrewLintRule::UnreachableAfterServe,
+                        position,
+                        format!("This never brews: it comes after '{}'", keyword),
+                    );
+                }
+            }
+            match statement {
+                Statement::Return(_) => stopped_by = stopped_by.or(Some("serve")),
+                Statement::Break => stopped_by = stopped_by.or(Some("break")),
+                Statement::Continue => stopped_by = stopped_by.or(Some("continue")),
+                _ => {}
+            }
+            self.walk_statement(statement);
+        }
+    }
+
+    fn walk_scoped_block(&mut self, statements: &[Statement]) {
+        self.push_scope();
+        self.walk_block(statements);
+        self.pop_scope();
+    }
+
+    fn check_empty(&mut self, statements: &[Statement], what: &str) {
+        if is_empty_block(statements) {
+            let position = self.mark;
+            self.report(BrewLintRule::EmptyBlock, position, format!("This {} block is empty", what));
+        }
+    }
+
+    fn walk_brew(&mut self, name: &str, params: &[String], body: &[Statement]) {
+        let complexity = 1 + brew_complexity(body);
+        if complexity > self.config.max_complexity {
+            let position = self.mark;
+            self.report(
+                BrewLintRule::Complexity,
+                position,
+                format!(
+                    "brew '{}' is too complex to taste: {} branches, the limit is {}. Consider splitting it into smaller brews",
+                    name, complexity, self.config.max_complexity
+                ),
+            );
+        }
+        self.push_scope();
+        for param in params {
+            self.declare(param, true);
+        }
+        let position = self.mark;
+        self.walk_block(body);
+        self.mark = position;
+        self.pop_scope();
+    }
+
+    fn walk_statement(&mut self, statement: &Statement) {
+        let position = self.mark;
+        match statement {
+            Statement::VarDecl { name, value, .. } => {
+                self.walk_expr(value);
+                self.declare(name, false);
+                self.fill(name, value);
+            }
+            Statement::ArrayDecl { name, elements } => {
+                elements.iter().for_each(|element| self.walk_expr(element));
+                self.declare(name, false);
+            }
+            Statement::ObjectDecl { name, fields } => {
+                fields.iter().for_each(|(_, value)| self.walk_expr(value));
+                self.declare(name, false);
+            }
+            Statement::Print(expr, _) | Statement::BrewTime(expr) | Statement::ExprStmt(expr) | Statement::Return(Some(expr))
+            | Statement::YieldSip(expr) => {
+                self.walk_expr(expr);
+            }
+            Statement::If { condition, then_branch, else_branch } => {
+                self.walk_expr(condition);
+                self.check_empty(then_branch, "taste");
+                self.walk_scoped_block(then_branch);
+                self.walk_scoped_block(else_branch);
+            }
+            Statement::While { condition, body } => {
+                self.walk_expr(condition);
+                self.check_empty(body, "steep");
+                self.walk_scoped_block(body);
+            }
+            Statement::For { init, condition, increment, body } => {
+                self.push_scope();
+                if let Some(init) = init {
+                    self.walk_statement(init);
+                }
+                self.walk_expr(condition);
+                if let Some(increment) = increment {
+                    self.walk_expr(increment);
+                }
+                self.mark = position;
+                self.check_empty(body, "pour");
+                self.walk_block(body);
+                self.pop_scope();
+            }
+            Statement::Foreach { var, second_var, iterable, body } => {
+                self.walk_expr(iterable);
+                self.check_empty(body, "foreach");
+                self.push_scope();
+                self.declare(var, true);
+                if let Some(second_var) = second_var {
+                    self.declare(second_var, true);
+                }
+                self.walk_block(body);
+                self.pop_scope();
+            }
+            Statement::BrewDecl { name, params, body, .. } => {
+                let params: Vec<String> = params.iter().map(|param| param.name.clone()).collect();
+                self.walk_brew(name, &params, body);
+            }
+            Statement::ConstructorDecl { params, body } => {
+                let params: Vec<String> = params.iter().map(|param| param.name.clone()).collect();
+                self.walk_brew("constructor", &params, body);
+            }
+            Statement::RoastDecl { name, body } => self.walk_brew(name, &[], body),
+            Statement::BeanDecl { fields, methods, .. } => {
+                fields.iter().for_each(|field| self.walk_expr(&field.value));
+                self.push_scope();
+                self.walk_block(methods);
+                self.pop_scope();
+            }
+            Statement::RoastSwitch { value, arms, default } => {
+                self.walk_expr(value);
+                self.check_roast_arms(arms);
+                for (patterns, arm) in arms {
+                    for pattern in patterns {
+                        match pattern {
+                            RoastPattern::Value(arm_value) => self.walk_expr(arm_value),
+                            RoastPattern::Range(low, high) => {
+                                self.walk_expr(low);
+                                self.walk_expr(high);
+                            }
+                        }
+                    }
+                    self.walk_scoped_block(arm);
+                }
+                self.walk_scoped_block(default);
+            }
+            Statement::TryCatch { try_branch, error_variable, catch_branch } => {
+                self.check_empty(try_branch, "taste_carefully");
+                self.walk_scoped_block(try_branch);
+                self.mark = position;
+                self.check_empty(catch_branch, "if_spilled");
+                self.push_scope();
+                if let Some(error_variable) = error_variable {
+                    self.declare(error_variable, true);
+                }
+                self.walk_block(catch_branch);
+                self.pop_scope();
+            }
+            Statement::BenchBrew { body, .. } | Statement::TasteTest { body, .. } => self.walk_scoped_block(body),
+            Statement::CoffeeRecipeDecl { .. } | Statement::Return(None) | Statement::Break
+            | Statement::Continue | Statement::SourceMark { .. } => {}
+        }
+    }
+
+    fn walk_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Identifier(name) => self.taste(name),
+            Expr::ArrayLiteral(elements) => elements.iter().for_each(|element| self.walk_expr(element)),
+            Expr::ObjectLiteral(fields) => fields.iter().for_each(|(_, value)| self.walk_expr(value)),
+            Expr::BinaryOp { left, op, right } => {
+                self.walk_expr(left);
+                self.walk_expr(right);
+                if *op == BinaryOperator::Add {
+                    let flavors = (self.flavor_of(left), self.flavor_of(right));
+                    if let (Some(left), Some(right)) = flavors {
+                        if left != right {
+                            let position = self.mark;
+                            self.report(
+                                BrewLintRule::StringCoercion,
+                                position,
+                                "'add' mixes a String with a Number here, so the Number is quietly poured in as text".to_string(),
+                            );
+                        }
+                    }
+                }
+            }
+            Expr::Assignment { target, value } => {
+                // Pouring into a bean isn't tasting it, but `cup[i] = x` does read `cup`
+                if !matches!(target.as_ref(), Expr::Identifier(_)) {
+                    self.walk_expr(target);
+                }
+                self.walk_expr(value);
+                if let Expr::Identifier(name) = target.as_ref() {
+                    self.fill(name, value);
+                }
+            }
+            Expr::UnaryOp { expr, .. } => self.walk_expr(expr),
+            Expr::Call { callee, args } => {
+                self.walk_expr(callee);
+                args.iter().for_each(|arg| self.walk_expr(arg));
+            }
+            Expr::MemberAccess { object, .. } => self.walk_expr(object),
+            Expr::ArrayAccess { array, index } => {
+                self.walk_expr(array);
+                self.walk_expr(index);
+            }
+            Expr::NewBean { args, .. } => args.iter().for_each(|arg| self.walk_expr(arg)),
+            Expr::Number(_) | Expr::String(_) | Expr::Boolean(_) | Expr::Grind(_) | Expr::This | Expr::Super => {}
+        }
+    }
+}
+
+/// `brew lint [--config brewlint.toml] [paths...]`. Each file uses the nearest brewlint.toml
+/// unless one is given, either one applied on top of `base` (the roastery's [lint] settings);
+/// exits nonzero when anything at `error` severity turns up.
+pub fn run_brew_lint(paths: &[String], config_path: Option<&str>, base: &BrewLintConfig) {
+    let given_config = match config_path {
+        None => None,
+        Some(path) => match std::fs::read_to_string(path) {
+            Ok(text) => match base.clone().apply_toml(&text) {
+                Ok(config) => Some(config),
+                Err(e) => {
+                    println!("[ERROR] {}: {}", path, e);
+                    std::process::exit(1);
+                }
+            },
+            Err(_) => {
+                println!("[ERROR] Could not read file: {}", path);
+                std::process::exit(1);
+            }
+        },
+    };
+
+    let roots = if paths.is_empty() { vec![".".to_string()] } else { paths.to_vec() };
+    let mut counts = [0usize; 4];
+    for path in roots.iter().flat_map(|root| crate::brew_formatter::coffee_files_under(Path::new(root))) {
+        let shown = path.display();
+        let config = match &given_config {
+            Some(config) => Ok(config.clone()),
+            None => base.clone().discover(path.parent().unwrap_or(Path::new("."))),
+        };
+        let config = match config {
+            Ok(config) => config,
+            Err(e) => {
+                println!("[ERROR] {}", e);
+                counts[LintSeverity::Error as usize] += 1;
+                continue;
+            }
+        };
+        let code = match std::fs::read_to_string(&path) {
+            Ok(code) => code,
+            Err(_) => {
+                println!("[ERROR] Could not read file: {}", shown);
+                counts[LintSeverity::Error as usize] += 1;
+                continue;
+            }
+        };
+        let stmts = crate::parser::parse_source(&code);
+        if !stmts.errors.is_empty() {
+            for (err, (line, column)) in stmts.errors.iter().zip(&stmts.error_positions) {
+                println!("{}:{}:{}: error[syntax]: {}", shown, line, column, err);
+            }
+            counts[LintSeverity::Error as usize] += stmts.errors.len();
+            continue;
+        }
+        for finding in lint_coffee_statements(&stmts.statements, &config) {
+            println!(
+                "{}:{}:{}: {}[{}]: {}",
+                shown, finding.line, finding.column, finding.severity.label(), finding.rule.name(), finding.message
+            );
+            counts[finding.severity as usize] += 1;
+        }
+    }
+
+    let [_, notes, warnings, errors] = counts;
+    if notes + warnings + errors == 0 {
+        println!("🧽 The counter is spotless!");
+    } else {
+        println!("🧽 {} error(s), {} warning(s), {} note(s)", errors, warnings, notes);
+    }
+    if errors > 0 {
+        std::process::exit(1);
+    }
+}
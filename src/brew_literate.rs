@@ -0,0 +1,237 @@
+/*
+ * 📝 Brewco Literate Recipes ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @notebook_note: "Write the story, brew the story!" ✨
+ *
+ * `brew literate notes.md` brews every ```brewco fenced block in a
+ * Markdown file, top to bottom, on one interpreter, so a later block can
+ * use the beans and brews of an earlier one. A block followed by an
+ * ```output block has to pour exactly that (trailing spaces aside), which
+ * makes example-heavy docs their own tests. With `--check` nothing is
+ * poured: each block just passes or fails, like `brew test`.
+ *
+ * A block marked ```brewco ignore is skipped, and one marked
+ * ```brewco spills has to spill (its spill is swallowed and the blocks
+ * after it keep brewing). Spills point at lines in the Markdown file.
+ */
+
+// src/brew_literate.rs
+
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd};
+
+use crate::espresso_errors::{CoffeeSpillReport, SpillType};
+use crate::interpreter::{self, ControlFlow, Interpreter};
+use crate::parser;
+
+/// What a ```brewco block's info string asks for
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BlockMode {
+    Brew,
+    Ignore,  // ```brewco ignore: shown, never brewed
+    Spills,  // ```brewco spills: passes only if it spills
+}
+
+/// One ```brewco block of a Markdown file
+pub struct LiterateBlock {
+    pub line: usize,       // the opening fence
+    pub first_line: usize, // where the code itself starts
+    pub code: String,
+    pub mode: BlockMode,
+    pub expected: Option<String>, // the ```output block right after it, if there is one
+}
+
+/// How one block went
+enum BlockOutcome {
+    Brewed,
+    Matched,
+    Skipped,
+    Failed(String),
+    /// Spilled where nobody expected it; the blocks after it aren't brewed
+    Spilled(CoffeeSpillReport),
+}
+
+/// Every ```brewco block in `markdown`, each with the ```output block that follows it
+pub fn literate_blocks(markdown: &str) -> Vec<LiterateBlock> {
+    let line_at = |offset: usize| markdown[..offset].matches('\n').count() + 1;
+    let mut blocks: Vec<LiterateBlock> = Vec::new();
+    // The block an ```output block would belong to; any other code block in between breaks the pair
+    let mut awaiting_output = false;
+    let mut open: Option<(String, usize, Option<usize>, String)> = None; // info, fence line, first code line, code
+
+    for (event, range) in Parser::new(markdown).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let info = match kind {
+                    CodeBlockKind::Fenced(info) => info.to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                open = Some((info, line_at(range.start), None, String::new()));
+            }
+            Event::Text(text) => {
+                if let Some((_, _, first_line, code)) = open.as_mut() {
+                    first_line.get_or_insert_with(|| line_at(range.start));
+                    code.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                let Some((info, line, first_line, code)) = open.take() else { continue };
+                let mut words = info.split(|c: char| c.is_whitespace() || c == ',').filter(|word| !word.is_empty());
+                match words.next() {
+                    Some("brewco") => {
+                        let marks: Vec<&str> = words.collect();
+                        let mode = if marks.contains(&"ignore") {
+                            BlockMode::Ignore
+                        } else if marks.contains(&"spills") {
+                            BlockMode::Spills
+                        } else {
+                            BlockMode::Brew
+                        };
+                        let first_line = first_line.unwrap_or(line + 1);
+                        blocks.push(LiterateBlock { line, first_line, code, mode, expected: None });
+                        awaiting_output = true;
+                    }
+                    Some("output") if awaiting_output => {
+                        if let Some(block) = blocks.last_mut() {
+                            block.expected = Some(code);
+                        }
+                        awaiting_output = false;
+                    }
+                    _ => awaiting_output = false,
+                }
+            }
+            _ => {}
+        }
+    }
+    blocks
+}
+
+/// Output as compared against an ```output block: trailing spaces and blank lines don't count
+fn settled(output: &str) -> String {
+    output.lines().map(str::trim_end).collect::<Vec<_>>().join("\n").trim_end().to_string()
+}
+
+/// Indent every line of `text` for showing under a block's result
+fn indented(text: &str, prefix: &str) -> String {
+    if text.is_empty() {
+        return format!("{}(nothing)", prefix);
+    }
+    text.lines().map(|line| format!("{}{}", prefix, line)).collect::<Vec<_>>().join("\n")
+}
+
+/// Brew one block on the file's interpreter; what it poured, and how it went
+fn brew_block(interpreter: &mut Interpreter, block: &LiterateBlock, label: &str, markdown: &str) -> (String, BlockOutcome) {
+    if block.mode == BlockMode::Ignore {
+        return (String::new(), BlockOutcome::Skipped);
+    }
+    // Blank lines in front keep the parser's lines the same as the Markdown file's
+    let source = format!("{}{}", "\n".repeat(block.first_line - 1), block.code);
+    let parsed = parser::parse_source(&source);
+    if let (Some(error), Some(&(line, column))) = (parsed.errors.first(), parsed.error_positions.first()) {
+        let mut report = CoffeeSpillReport::new_brewing_disaster(SpillType::IncompleteRecipe, line, column, error);
+        report.frame_in_source(label, markdown);
+        return (String::new(), BlockOutcome::Spilled(report));
+    }
+
+    interpreter::catch_pours();
+    let ran = interpreter.run(&parsed.statements);
+    let poured = interpreter::take_caught_pours();
+    let outcome = match (ran, block.mode) {
+        (Err(ControlFlow::RuntimeError(_)), BlockMode::Spills) => BlockOutcome::Brewed,
+        (Err(ControlFlow::RuntimeError(message)), _) => {
+            let mut report = interpreter.spill_report(&message);
+            report.frame_in_source(label, markdown);
+            BlockOutcome::Spilled(report)
+        }
+        (_, BlockMode::Spills) => BlockOutcome::Failed("marked `spills`, but it brewed without spilling".to_string()),
+        _ => BlockOutcome::Brewed,
+    };
+    let outcome = match (&outcome, &block.expected) {
+        (BlockOutcome::Brewed, Some(expected)) if settled(&poured) == settled(expected) => BlockOutcome::Matched,
+        (BlockOutcome::Brewed, Some(expected)) => BlockOutcome::Failed(format!(
+            "its output doesn't match the ```output block after it\n  expected:\n{}\n  poured:\n{}",
+            indented(&settled(expected), "    "),
+            indented(&settled(&poured), "    ")
+        )),
+        _ => outcome,
+    };
+    (poured, outcome)
+}
+
+/// `brew literate [--check] <file.md>...`: brew each file's blocks and exit nonzero if any failed
+pub fn run_brew_literate(paths: &[String], check: bool) {
+    let (mut brewed, mut matched, mut failed) = (0, 0, 0);
+    for path in paths {
+        let markdown = match std::fs::read_to_string(path) {
+            Ok(markdown) => markdown,
+            Err(_) => {
+                println!("[ERROR] Could not read file: {}", path);
+                failed += 1;
+                continue;
+            }
+        };
+        let blocks = literate_blocks(&markdown);
+        if check {
+            println!("\n📝 {}", path);
+            if blocks.is_empty() {
+                println!("  (no ```brewco blocks)");
+            }
+        }
+
+        let mut interpreter = Interpreter::new();
+        for (index, block) in blocks.iter().enumerate() {
+            let (poured, outcome) = brew_block(&mut interpreter, block, path, &markdown);
+            if !check {
+                print!("{}", poured);
+            }
+            match outcome {
+                BlockOutcome::Skipped => {
+                    if check {
+                        println!("  ⏭️  line {} (ignore)", block.line);
+                    }
+                }
+                BlockOutcome::Brewed | BlockOutcome::Matched => {
+                    brewed += 1;
+                    let is_match = matches!(outcome, BlockOutcome::Matched);
+                    if is_match {
+                        matched += 1;
+                    }
+                    if check {
+                        println!("  ✅ line {}{}", block.line, if is_match { " (output matches)" } else { "" });
+                    }
+                }
+                BlockOutcome::Failed(why) => {
+                    brewed += 1;
+                    failed += 1;
+                    if check {
+                        println!("  ❌ line {}: {}", block.line, why.replace('\n', "\n     "));
+                    } else {
+                        eprintln!("☕ {}:{}: {}", path, block.line, why);
+                    }
+                }
+                BlockOutcome::Spilled(report) => {
+                    brewed += 1;
+                    failed += 1;
+                    let rest = blocks.len() - index - 1;
+                    if check {
+                        println!("  ❌ line {}: {}", block.line, report.headline());
+                    } else {
+                        eprint!("{}", report.brew_detailed_report());
+                    }
+                    if rest > 0 {
+                        let note = format!("☕ the {} block(s) after line {} in {} weren't brewed", rest, block.line, path);
+                        if check { println!("  {}", note) } else { eprintln!("{}", note) }
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    if check {
+        println!("\n☕ {} block(s) brewed, {} output(s) matched, {} failed", brewed, matched, failed);
+    }
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
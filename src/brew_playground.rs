@@ -0,0 +1,382 @@
+/*
+ * 🛝 Brewco Playground ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @playground_note: "Try any recipe, the counter stays clean!" ✨
+ *
+ * `brew playground` serves a one-page editor at `/` and brews whatever is
+ * POSTed to `/eval` as `{"code": "..."}`. Every recipe gets a fresh
+ * interpreter sealed as a sandbox (no files, commands, sockets, packages,
+ * console or process; see Interpreter::seal_sandbox) with a fuel budget, so
+ * a stranger's endless steep spills instead of keeping the shop busy. Output
+ * and wall-clock time are capped too, so no single recipe can eat the
+ * playground's memory or hold it up.
+ *
+ * The answer is JSON: what the recipe poured, whether it spilled, how much
+ * fuel it burnt, and diagnostics (syntax, type and runtime, with lines,
+ * columns and spill codes) that an editor can underline. `/eval` answers
+ * any origin, so a docs page (like the package roastery's) can call it from
+ * its own examples. Recipes brew one at a time, each on its own thread; Ctrl-C
+ * closes the playground.
+ */
+
+// src/brew_playground.rs
+
+use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value as JsonValue};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::espresso_errors::SpillType;
+use crate::interpreter::{self, ControlFlow, Interpreter, INTERRUPTED_BREW, OUT_OF_TIME, POURED_TOO_MUCH};
+use crate::parser;
+use crate::type_checker::TypeChecker;
+
+/// The biggest recipe `/eval` takes
+const MAX_RECIPE_BYTES: usize = 64 * 1024;
+
+/// How much of what a recipe pours makes it into the answer
+const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// Stack for the thread each recipe brews on; SANDBOX_MAX_DEPTH calls fit in it even in a debug build
+const BREWING_STACK_BYTES: usize = 32 * 1024 * 1024;
+
+/// Wall-clock time each recipe gets, on top of its fuel
+const BREW_TIME_LIMIT: Duration = Duration::from_secs(5);
+
+/// How long past BREW_TIME_LIMIT the playground waits on a brew stuck inside one native
+const BREW_TIME_GRACE: Duration = Duration::from_secs(1);
+
+/// How often an idle playground looks up to check for Ctrl-C
+const CTRL_C_GLANCE: Duration = Duration::from_millis(200);
+
+const PLAYGROUND_PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Brewco Playground ☕</title>
+<style>
+  body { font-family: system-ui, sans-serif; margin: 0; background: #fbf7f2; color: #3b2a20; }
+  header { padding: 0.8em 1.2em; background: #6f4e37; color: #fff; display: flex; gap: 1em; align-items: center; }
+  header h1 { font-size: 1.2em; margin: 0; flex: 1; }
+  button { font-size: 1em; padding: 0.4em 1.2em; border: 0; border-radius: 4px; background: #e8c39e; color: #3b2a20; cursor: pointer; }
+  main { display: grid; grid-template-columns: 1fr 1fr; gap: 1em; padding: 1em; height: calc(100vh - 5em); box-sizing: border-box; }
+  textarea, section { font-family: ui-monospace, monospace; font-size: 0.95em; border: 1px solid #d9c7b5; border-radius: 4px; background: #fff; }
+  textarea { resize: none; padding: 0.8em; tab-size: 4; }
+  section { overflow: auto; padding: 0.8em; }
+  pre { margin: 0; white-space: pre-wrap; }
+  .error { color: #b3261e; }
+  .warning { color: #8a5a00; }
+  .meta { color: #8c7b6e; margin-top: 1em; }
+</style>
+</head>
+<body>
+<header><h1>☕ Brewco Playground</h1><span>Ctrl+Enter brews</span><button id="brew">Brew</button></header>
+<main>
+<textarea id="code" spellcheck="false">brew greet(name) {
+    serve brew_format("Hello, {}!", name)
+}
+
+pour bean in ["espresso", "latte", "mocha"] {
+    pourout greet(bean)
+}</textarea>
+<section><pre id="output"></pre><div id="diagnostics"></div><div id="meta" class="meta"></div></section>
+</main>
+<script>
+const code = document.getElementById("code");
+const output = document.getElementById("output");
+const diagnostics = document.getElementById("diagnostics");
+const meta = document.getElementById("meta");
+
+async function brew() {
+  meta.textContent = "brewing...";
+  try {
+    const answer = await (await fetch("eval", { method: "POST", headers: { "Content-Type": "application/json" }, body: JSON.stringify({ code: code.value }) })).json();
+    output.textContent = answer.output ?? "";
+    diagnostics.replaceChildren(...(answer.diagnostics ?? []).map(d => {
+      const line = document.createElement("pre");
+      line.className = d.severity;
+      line.textContent = d.line + ":" + d.column + ": " + d.stage + " " + d.severity + (d.code ? " [" + d.code + "]" : "") + ": " + d.message;
+      return line;
+    }));
+    meta.textContent = answer.error ?? ((answer.spilled ? "spilled" : "brewed") + " with " + answer.fuel_used + " steps of fuel" + (answer.truncated ? ", output cut short" : ""));
+  } catch (e) {
+    meta.textContent = "couldn't reach the playground: " + e;
+  }
+}
+
+document.getElementById("brew").addEventListener("click", brew);
+code.addEventListener("keydown", e => {
+  if (e.key === "Enter" && (e.ctrlKey || e.metaKey)) { e.preventDefault(); brew(); }
+  if (e.key === "Tab") { e.preventDefault(); code.setRangeText("    ", code.selectionStart, code.selectionEnd, "end"); }
+});
+</script>
+</body>
+</html>
+"#;
+
+/// What one recipe brewed to, as `/eval` answers it
+struct Brewed {
+    output: String,
+    truncated: bool,
+    spilled: bool,
+    diagnostics: Vec<JsonValue>,
+    fuel_used: u64,
+}
+
+impl Brewed {
+    fn to_json(&self) -> JsonValue {
+        json!({
+            "output": self.output,
+            "truncated": self.truncated,
+            "spilled": self.spilled,
+            "diagnostics": self.diagnostics,
+            "fuel_used": self.fuel_used,
+        })
+    }
+
+    /// Whether the brew stopped because the playground is closing
+    fn interrupted(&self) -> bool {
+        self.diagnostics.iter().any(|d| d["message"].as_str().is_some_and(|message| message.starts_with(INTERRUPTED_BREW)))
+    }
+}
+
+fn diagnostic(severity: &str, stage: &str, line: usize, column: usize, code: Option<&str>, message: &str) -> JsonValue {
+    json!({"severity": severity, "stage": stage, "line": line, "column": column, "code": code, "message": message})
+}
+
+/// Check and brew `code` in a fresh sandbox, catching everything it pours
+fn brew_in_sandbox(code: &str, fuel: u64) -> Brewed {
+    let mut brewed = Brewed { output: String::new(), truncated: false, spilled: false, diagnostics: Vec::new(), fuel_used: 0 };
+
+    let parsed = parser::parse_source(code);
+    for (error, &(line, column)) in parsed.errors.iter().zip(&parsed.error_positions) {
+        brewed.diagnostics.push(diagnostic("error", "syntax", line, column, Some(SpillType::IncompleteRecipe.code()), error));
+    }
+    if !parsed.errors.is_empty() {
+        brewed.spilled = true;
+        return brewed;
+    }
+
+    let mut type_checker = TypeChecker::new().with_strict_brew(interpreter::strict_brew());
+    if let Err(errors) = type_checker.check(&parsed.statements) {
+        for (error, &(line, column)) in errors.iter().zip(type_checker.error_positions()) {
+            brewed.diagnostics.push(diagnostic("error", "type", line, column, Some(SpillType::WrongCupType.code()), error));
+        }
+        brewed.spilled = true;
+    }
+    for warning in type_checker.warnings() {
+        brewed.diagnostics.push(diagnostic("warning", "type", warning.line, warning.column, None, &warning.message));
+    }
+    if brewed.spilled {
+        return brewed;
+    }
+
+    let mut interpreter = Interpreter::new();
+    interpreter.seal_sandbox();
+    interpreter.set_fuel(fuel);
+    interpreter.set_time_limit(BREW_TIME_LIMIT);
+    interpreter::catch_pours_up_to(MAX_OUTPUT_BYTES);
+    let ran = interpreter.run(&parsed.statements);
+    brewed.output = interpreter::take_caught_pours();
+    brewed.fuel_used = interpreter.fuel_used().unwrap_or(0);
+    if let Err(ControlFlow::RuntimeError(message)) = ran {
+        brewed.truncated = message.starts_with(POURED_TOO_MUCH);
+        let report = interpreter.spill_report(&message);
+        brewed.diagnostics.push(diagnostic(
+            "error",
+            "runtime",
+            report.coffee_line,
+            report.brewing_column,
+            Some(report.spill_type.code()),
+            &report.bitter_message,
+        ));
+        brewed.spilled = true;
+    }
+    brewed
+}
+
+/// Brew on a thread of its own, so a deep recipe has stack to spare and a panic stays there.
+/// A brew stuck inside a single native past its time limit is left behind, not waited on.
+fn brew_on_own_thread(code: String, fuel: u64) -> Brewed {
+    let (done, brewed) = mpsc::channel();
+    let brewing = thread::Builder::new()
+        .name("playground brew".to_string())
+        .stack_size(BREWING_STACK_BYTES)
+        .spawn(move || {
+            let _ = done.send(panic::catch_unwind(AssertUnwindSafe(|| brew_in_sandbox(&code, fuel))));
+        });
+    let spilled = |message: &str| Brewed {
+        output: String::new(),
+        truncated: false,
+        spilled: true,
+        diagnostics: vec![diagnostic("error", "runtime", 0, 0, Some(SpillType::InternalSpill.code()), message)],
+        fuel_used: 0,
+    };
+    if brewing.is_err() {
+        return spilled("Brewco itself spilled while brewing this");
+    }
+    match brewed.recv_timeout(BREW_TIME_LIMIT + BREW_TIME_GRACE) {
+        Ok(Ok(brewed)) => brewed,
+        Ok(Err(_)) | Err(RecvTimeoutError::Disconnected) => spilled("Brewco itself spilled while brewing this"),
+        Err(RecvTimeoutError::Timeout) => spilled(&format!("{}: the brew ran past its {} s time limit", OUT_OF_TIME, BREW_TIME_LIMIT.as_secs())),
+    }
+}
+
+/// `brew playground`: serve the editor and `/eval` until Ctrl-C
+pub fn run_brew_playground(port: u16, fuel: u64) {
+    let address = format!("127.0.0.1:{}", port);
+    let server = match Server::http(address.as_str()) {
+        Ok(server) => server,
+        Err(e) => {
+            println!("[ERROR] The playground couldn't open at {}: {}", address, e);
+            std::process::exit(1);
+        }
+    };
+    eprintln!("☕ Brewco playground open at http://{} ({} steps of fuel per recipe; Ctrl-C to close)", address, fuel);
+
+    loop {
+        if interpreter::take_interrupt() {
+            break;
+        }
+        let request = match server.recv_timeout(CTRL_C_GLANCE) {
+            Ok(Some(request)) => request,
+            Ok(None) => continue,
+            Err(e) => {
+                println!("[ERROR] The playground stopped taking recipes: {}", e);
+                std::process::exit(1);
+            }
+        };
+        if !serve_request(request, fuel) {
+            break;
+        }
+    }
+    eprintln!("☕ Playground closed.");
+}
+
+/// Answer one request; false once Ctrl-C cut a brew short and the playground should close
+fn serve_request(mut request: Request, fuel: u64) -> bool {
+    let path = request.url().split('?').next().unwrap_or("/").to_string();
+    let (status, body, content_type, still_open) = match (request.method(), path.as_str()) {
+        (Method::Get, "/") => (200, PLAYGROUND_PAGE.to_string(), "text/html; charset=utf-8", true),
+        (Method::Options, "/eval") => (204, String::new(), "text/plain", true),
+        (Method::Post, "/eval") => match read_recipe(&mut request) {
+            Ok(code) => {
+                let brewed = brew_on_own_thread(code, fuel);
+                let interrupted = brewed.interrupted();
+                let status = if interrupted { 503 } else { 200 };
+                (status, brewed.to_json().to_string(), "application/json", !interrupted)
+            }
+            Err((status, why)) => (status, json!({ "error": why }).to_string(), "application/json", true),
+        },
+        (_, "/eval") => (405, json!({ "error": "POST a recipe to /eval" }).to_string(), "application/json", true),
+        _ => (404, "404 Not Found: the playground is at /".to_string(), "text/plain", true),
+    };
+
+    let header = |name: &str, value: &str| Header::from_bytes(name.as_bytes(), value.as_bytes()).ok();
+    let mut response = Response::from_string(body).with_status_code(status);
+    let headers = [
+        header("Content-Type", content_type),
+        header("Access-Control-Allow-Origin", "*"),
+        header("Access-Control-Allow-Methods", "POST, OPTIONS"),
+        header("Access-Control-Allow-Headers", "Content-Type"),
+    ];
+    for header in headers.into_iter().flatten() {
+        response.add_header(header);
+    }
+    if let Err(e) = request.respond(response) {
+        eprintln!("[Coffee Warning] Couldn't answer a playground request: {}", e);
+    }
+    still_open
+}
+
+/// The recipe in an `/eval` body, or the status and reason to turn it away with
+fn read_recipe(request: &mut Request) -> Result<String, (u16, String)> {
+    let mut body = Vec::new();
+    let read = request.as_reader().take(MAX_RECIPE_BYTES as u64 + 1).read_to_end(&mut body);
+    if let Err(e) = read {
+        return Err((400, format!("couldn't read the request: {}", e)));
+    }
+    if body.len() > MAX_RECIPE_BYTES {
+        return Err((413, format!("recipes can be at most {} bytes", MAX_RECIPE_BYTES)));
+    }
+    let order: JsonValue = serde_json::from_slice(&body).map_err(|e| (400, format!("the body isn't JSON: {}", e)))?;
+    match order.get("code").and_then(JsonValue::as_str) {
+        Some(code) => Ok(code.to_string()),
+        None => Err((400, "the body needs a \"code\" string with the recipe to brew".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runtime_messages(brewed: &Brewed) -> Vec<&str> {
+        brewed.diagnostics.iter().filter(|d| d["stage"] == "runtime").filter_map(|d| d["message"].as_str()).collect()
+    }
+
+    #[test]
+    fn pours_are_cut_off_at_the_output_cap() {
+        let brewed = brew_on_own_thread("beans s = \"x\"\nsteep (true) {\n    pourout s\n    s = s add s\n}\n".to_string(), 10_000);
+        assert!(brewed.spilled && brewed.truncated);
+        assert_eq!(brewed.output.len(), MAX_OUTPUT_BYTES);
+        assert!(runtime_messages(&brewed)[0].starts_with(POURED_TOO_MUCH));
+    }
+
+    #[test]
+    fn an_endless_steep_runs_out_of_fuel() {
+        let brewed = brew_on_own_thread("beans n = 0\nsteep (true) {\n    n = n add 1\n}\n".to_string(), 500);
+        assert!(brewed.spilled);
+        assert_eq!(brewed.fuel_used, 500);
+        assert!(runtime_messages(&brewed)[0].starts_with(interpreter::OUT_OF_FUEL));
+    }
+
+    #[test]
+    fn deep_brews_stop_at_the_sandbox_depth() {
+        let code = "brew down(n) {\n    taste (n == 0) {\n        serve 0\n    }\n    serve 1 add down(n - 1)\n}\npourout down(100)\npourout down(1000)\n";
+        let brewed = brew_on_own_thread(code.to_string(), 100_000);
+        assert_eq!(brewed.output, "100\n");
+        assert!(runtime_messages(&brewed)[0].contains("calls deep"));
+    }
+
+    #[test]
+    fn the_sandbox_keeps_files_shut() {
+        let brewed = brew_on_own_thread("pourout sip_file(\"Cargo.toml\")\n".to_string(), 100);
+        assert!(brewed.spilled);
+        assert!(runtime_messages(&brewed)[0].contains("isn't served in the sandbox"));
+    }
+
+    #[test]
+    fn a_permissive_roastery_doesnt_open_the_sandbox() {
+        let dir = std::env::temp_dir().join(format!("brewco-playground-{}", uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("brewco.toml"), "[allow]\nfs = true\nexec = true\nnet = true\n").unwrap();
+        let roastery = crate::roastery_config::load_roastery_config(Some(&dir)).unwrap();
+        assert!(roastery.allow_fs && roastery.allow_exec && roastery.allow_net);
+        // Grant everything the way a roastery-wide brew would; the sandbox has to hold anyway
+        interpreter::allow_fs_access();
+        interpreter::allow_exec_access();
+        interpreter::allow_net_access();
+
+        let passwd = "pour l in sip_lines(\"/etc/passwd\") {\n    pourout l\n}\n";
+        for code in [passwd, "pourout sip_file(\"Cargo.toml\")\n", "pourout brew_shell(\"echo hi\")\n"] {
+            let brewed = brew_on_own_thread(code.to_string(), 1_000);
+            assert!(brewed.spilled, "{} brewed in the sandbox", code);
+            assert_eq!(brewed.output, "");
+            assert!(runtime_messages(&brewed)[0].contains("isn't served in the sandbox"));
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn syntax_spills_come_back_as_diagnostics() {
+        let brewed = brew_on_own_thread("beans = \n".to_string(), 100);
+        assert!(brewed.spilled);
+        assert_eq!(brewed.diagnostics[0]["stage"], "syntax");
+        assert_eq!(brewed.diagnostics[0]["line"], 1);
+    }
+}
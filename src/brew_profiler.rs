@@ -0,0 +1,143 @@
+/*
+ * 🔥 Brewco Brew Profiler ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @profiler_note: "Find out which brew is hogging the espresso machine!" ✨
+ *
+ * `brew --profile` times every user brew and native call, then prints
+ * a table of where the caffeine went. It can also write folded stacks
+ * that flamegraph tools understand.
+ */
+
+// src/brew_profiler.rs
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Running totals for one brew or native
+#[derive(Default)]
+pub struct ProfiledBrew {
+    pub calls: u64,
+    pub total_time: Duration, // inclusive, recursion counted once
+    pub self_time: Duration,  // excluding time spent in callees
+    pub is_native: bool,
+}
+
+struct ProfileFrame {
+    label: String,
+    started: Instant,
+    child_time: Duration,
+    is_recursive: bool,
+    is_native: bool,
+}
+
+/// Call-stack aware timer the interpreter reports brew entries and exits to
+pub struct BrewProfiler {
+    brews: HashMap<String, ProfiledBrew>,
+    frames: Vec<ProfileFrame>,
+    folded_stacks: HashMap<String, Duration>,
+}
+
+impl BrewProfiler {
+    /// Start profiling with a root frame for top-level recipe code
+    pub fn new_profiler() -> Self {
+        let mut profiler = BrewProfiler {
+            brews: HashMap::new(),
+            frames: Vec::new(),
+            folded_stacks: HashMap::new(),
+        };
+        profiler.enter_brew("<main>");
+        profiler
+    }
+
+    pub fn enter_brew(&mut self, label: &str) {
+        self.push_frame(label, false);
+    }
+
+    /// Natives get a frame too, so the time of any brew a native calls back into
+    /// belongs under the native, not beside it
+    pub fn enter_native(&mut self, name: &str) {
+        self.push_frame(name, true);
+    }
+
+    fn push_frame(&mut self, label: &str, is_native: bool) {
+        let is_recursive = self.frames.iter().any(|frame| frame.label == label);
+        self.frames.push(ProfileFrame {
+            label: label.to_string(),
+            started: Instant::now(),
+            child_time: Duration::ZERO,
+            is_recursive,
+            is_native,
+        });
+    }
+
+    /// Close the innermost frame, brew or native
+    pub fn exit_brew(&mut self) {
+        let stack_path = self.stack_path();
+        let Some(frame) = self.frames.pop() else { return };
+        let elapsed = frame.started.elapsed();
+        let self_time = elapsed.saturating_sub(frame.child_time);
+        let entry = self.brews.entry(frame.label).or_default();
+        entry.calls += 1;
+        entry.self_time += self_time;
+        entry.is_native = frame.is_native;
+        if !frame.is_recursive {
+            entry.total_time += elapsed;
+        }
+        *self.folded_stacks.entry(stack_path).or_default() += self_time;
+        if let Some(parent) = self.frames.last_mut() {
+            parent.child_time += elapsed;
+        }
+    }
+
+    fn stack_path(&self) -> String {
+        self.frames.iter().map(|frame| frame.label.as_str()).collect::<Vec<_>>().join(";")
+    }
+
+    /// Close any frames still open (including the root) so totals are final
+    pub fn finish_profile(&mut self) {
+        while !self.frames.is_empty() {
+            self.exit_brew();
+        }
+    }
+
+    /// Sorted hotspot table, slowest inclusive time first
+    pub fn brew_report(&self) -> String {
+        let mut rows: Vec<(&String, &ProfiledBrew)> = self.brews.iter()
+            .filter(|(label, _)| label.as_str() != "<main>")
+            .collect();
+        rows.sort_by(|a, b| b.1.total_time.cmp(&a.1.total_time).then(a.0.cmp(b.0)));
+
+        let mut report = String::from("🔥 Brew Profile - where the caffeine went\n");
+        report.push_str(&format!(
+            "{:<32} {:>7} {:>10} {:>12} {:>12} {:>12}\n",
+            "brew", "kind", "calls", "total ms", "self ms", "avg µs"
+        ));
+        for (label, brew) in rows {
+            let avg_micros = brew.total_time.as_secs_f64() * 1_000_000.0 / brew.calls.max(1) as f64;
+            report.push_str(&format!(
+                "{:<32} {:>7} {:>10} {:>12.3} {:>12.3} {:>12.2}\n",
+                label,
+                if brew.is_native { "native" } else { "brew" },
+                brew.calls,
+                brew.total_time.as_secs_f64() * 1000.0,
+                brew.self_time.as_secs_f64() * 1000.0,
+                avg_micros
+            ));
+        }
+        if let Some(main) = self.brews.get("<main>") {
+            report.push_str(&format!("Total brewing time: {:.3} ms\n", main.total_time.as_secs_f64() * 1000.0));
+        }
+        report
+    }
+
+    /// Folded stacks ("<main>;outer;inner <self µs>" per line) for flamegraph tools
+    pub fn folded_output(&self) -> String {
+        let mut lines: Vec<String> = self.folded_stacks.iter()
+            .filter(|(_, time)| time.as_micros() > 0)
+            .map(|(stack, time)| format!("{} {}", stack, time.as_micros()))
+            .collect();
+        lines.sort();
+        lines.join("\n") + "\n"
+    }
+}
@@ -0,0 +1,99 @@
+/*
+ * 🌊 Brewco Brew Sequences ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @drip_note: "One drop at a time, never the whole pot!" ✨
+ *
+ * A sequence serves its values one at a time instead of all at once. Calling
+ * a generator brew — any brew with a `yield_sip` in its body — doesn't run it;
+ * it hands back a sequence. `pour x in seq { ... }` then brews it, and each
+ * `yield_sip value` pours one value through the loop body before the brew
+ * carries on from where it yielded. `break` (or a `serve` out of the loop)
+ * stops the brew right there, so endless generators are fine to pour from.
+ *
+ * `sip_lines(path)` streams a file the same way, one line at a time, without
+ * reading it all in. Every pour starts a sequence from the top.
+ */
+
+// src/brew_sequence.rs
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Lines};
+use std::rc::Rc;
+
+use crate::ast::Statement;
+use crate::interpreter::{ControlFlow, Value};
+
+/// What a `Value::Sequence` wraps
+pub enum BrewSequence {
+    /// A generator brew and the arguments it was called with, not yet brewed
+    Brewed { label: String, brew: Value, args: Vec<Value> },
+    /// A file, read line by line as it's poured
+    Lines { path: String },
+}
+
+impl BrewSequence {
+    /// How the sequence shows up when poured out
+    pub fn recipe(&self) -> String {
+        match self {
+            BrewSequence::Brewed { label, .. } => format!("<sequence from {}>", label),
+            BrewSequence::Lines { path } => format!("<sequence of lines in {}>", path),
+        }
+    }
+}
+
+/// Where the values of a pour go
+pub enum PourSink {
+    /// A `pour var[, i] in seq { body }` loop; `poured` counts the values so far
+    Body { var: String, second_var: Option<String>, body: Rc<Vec<Statement>>, poured: usize },
+    /// `take_sips` and `sequence_to_cup`, gathering up to `limit` values
+    Collect { items: Vec<Value>, limit: Option<usize> },
+}
+
+/// A pour in progress, kept on the interpreter while the sequence brews
+pub struct Pouring {
+    pub sink: PourSink,
+    /// Whether the values come from a generator brew's `yield_sip` (rather than a file)
+    pub generator: bool,
+    /// The pourer's brew frame and scope depth, restored while the loop body runs
+    pub frame: usize,
+    pub scope_base: usize,
+    pub tail_calls_allowed: bool,
+    /// The sink wants no more values
+    pub stopped: bool,
+    /// What stopped it, when that's more than a `break`: a `serve` or a spill from the loop body
+    pub flow: Option<ControlFlow>,
+}
+
+/// Does this brew body `yield_sip`? Brews declared inside it don't count.
+pub fn yields(body: &[Statement]) -> bool {
+    body.iter().any(|statement| match statement {
+        Statement::YieldSip(_) => true,
+        Statement::If { then_branch, else_branch, .. } => yields(then_branch) || yields(else_branch),
+        Statement::While { body, .. } | Statement::Foreach { body, .. } => yields(body),
+        Statement::For { init, body, .. } => init.as_deref().is_some_and(|init| yields(std::slice::from_ref(init))) || yields(body),
+        Statement::RoastSwitch { arms, default, .. } => arms.iter().any(|(_, arm)| yields(arm)) || yields(default),
+        Statement::TryCatch { try_branch, catch_branch, .. } => yields(try_branch) || yields(catch_branch),
+        _ => false,
+    })
+}
+
+/// Open a file for a line-by-line pour
+pub fn open_lines(native: &str, path: &str) -> Result<Lines<BufReader<File>>, ControlFlow> {
+    File::open(path)
+        .map(|file| BufReader::new(file).lines())
+        .map_err(|e| ControlFlow::RuntimeError(format!("{}() couldn't open '{}': {}", native, path, e)))
+}
+
+/// `sip_lines(path)`: a sequence of the file's lines, without their line endings
+pub fn sip_lines(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [Value::String(path)] => {
+            // Spill now if the file isn't there, rather than at the first pour
+            open_lines("sip_lines", path)?;
+            Ok(Value::Sequence(Rc::new(BrewSequence::Lines { path: path.clone() })))
+        }
+        [other] => Err(ControlFlow::RuntimeError(format!("sip_lines() expects a file path, but got a {}", other.type_name()))),
+        _ => Err(ControlFlow::RuntimeError(format!("sip_lines() expects 1 argument, but got {}", args.len()))),
+    }
+}
@@ -0,0 +1,78 @@
+/*
+ * 🔎 Brewco Brew Tracer ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @tracer_note: "Watch every drop fall into the cup!" ✨
+ *
+ * `brew --trace` narrates a recipe as it runs: every statement with its
+ * source location and scope depth, and (at higher verbosity) the values
+ * it brewed and every brew call. Traces go to stderr so program output
+ * stays clean.
+ */
+
+// src/brew_tracer.rs
+
+use crate::interpreter::Value;
+
+/// Statements only
+pub const TRACE_STATEMENTS: u8 = 1;
+/// Statements plus the values they evaluate
+pub const TRACE_VALUES: u8 = 2;
+/// Everything above plus brew calls and what they serve back
+pub const TRACE_BREWS: u8 = 3;
+
+pub struct BrewTracer {
+    pub verbosity: u8,
+    recipe_name: String,
+    source_lines: Vec<String>,
+}
+
+impl BrewTracer {
+    pub fn new_tracer(verbosity: u8, recipe_name: &str, recipe_source: &str) -> Self {
+        BrewTracer {
+            verbosity,
+            recipe_name: recipe_name.to_string(),
+            source_lines: recipe_source.lines().map(|line| line.to_string()).collect(),
+        }
+    }
+
+    pub fn trace_statement(&self, (line, column): (usize, usize), scope_depth: usize) {
+        let snippet = self.source_lines.get(line.wrapping_sub(1)).map(|s| s.trim()).unwrap_or("");
+        eprintln!(
+            "🔎 [trace] {}:{}:{} depth {} │ {}",
+            self.recipe_name, line, column, scope_depth, snippet
+        );
+    }
+
+    pub fn trace_value(&self, label: &str, value: &Value) {
+        if self.verbosity >= TRACE_VALUES {
+            eprintln!("🔎 [trace]     ↳ {} = {}", label, traced_value(value));
+        }
+    }
+
+    pub fn trace_brew_enter(&self, label: &str, params: &[String], args: &[Value]) {
+        if self.verbosity >= TRACE_BREWS {
+            let bound: Vec<String> = params.iter().zip(args)
+                .map(|(param, arg)| format!("{} = {}", param, traced_value(arg)))
+                .collect();
+            eprintln!("🔎 [trace] ☕ enter {}({})", label, bound.join(", "));
+        }
+    }
+
+    pub fn trace_brew_exit(&self, label: &str, served: Option<&Value>) {
+        if self.verbosity >= TRACE_BREWS {
+            match served {
+                Some(value) => eprintln!("🔎 [trace] ☕ {} served {}", label, traced_value(value)),
+                None => eprintln!("🔎 [trace] ☕ {} spilled", label),
+            }
+        }
+    }
+}
+
+/// Strings are quoted so empty and padded values stay visible
+fn traced_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("{:?}", s),
+        other => format!("{}", other),
+    }
+}
@@ -0,0 +1,121 @@
+/*
+ * 👀 Brewco Brew Watch ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @watch_note: "Save the recipe, smell the coffee!" ✨
+ *
+ * `brew watch cafe.brewco` brews the recipe, then brews it again every time
+ * it's saved, and so does `brew test --watch` for taste tests. Modules the
+ * recipe grinds in (`grind "beans.brewco"`), and the modules those grind
+ * in, are watched too.
+ *
+ * Each brew runs in a fresh `brew` process with the same command line minus
+ * the watch, so a spill or an exit() ends that brew and never the watch.
+ * Editors often save in several writes, so a change has to settle for a
+ * moment before the next brew starts. Ctrl-C stops watching.
+ */
+
+// src/brew_watch.rs
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::process::Command;
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+
+use crate::lexer::{CoffeeTokenStream, Token};
+
+/// How often the watched files are looked at
+const WATCH_POLL: Duration = Duration::from_millis(200);
+/// How long files have to stay unchanged after a save before they're brewed
+const WATCH_SETTLE: Duration = Duration::from_millis(150);
+
+/// The recipes plus every module they grind in, directly or through other modules.
+/// Grind paths are relative to where brew runs, the same as when they're brewed.
+pub fn grind_closure(recipes: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    let mut watched = Vec::new();
+    let mut waiting = recipes;
+    waiting.reverse();
+    while let Some(path) = waiting.pop() {
+        if !seen.insert(path.clone()) {
+            continue;
+        }
+        // A file that can't be read is still watched, so creating it brews again
+        if let Ok(source) = std::fs::read_to_string(&path) {
+            let tokens: Vec<Token> = CoffeeTokenStream::new(&source).map(|spanned| spanned.token).collect();
+            for pair in tokens.windows(2) {
+                if let [Token::Grind, Token::String(module)] = pair {
+                    waiting.push(PathBuf::from(module));
+                }
+            }
+        }
+        watched.push(path);
+    }
+    watched
+}
+
+/// When each file was last changed; None for a file that isn't there
+fn change_stamps(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    files.iter().map(|file| std::fs::metadata(file).and_then(|meta| meta.modified()).ok()).collect()
+}
+
+/// The command line `brew` was given, minus the `watch` subcommand or `--watch` flag
+pub fn order_without_watch(args: &[String]) -> Vec<String> {
+    let recipe_args = args.iter().position(|arg| arg == "--").unwrap_or(args.len());
+    let subcommand = args[..recipe_args].iter().position(|arg| arg == "watch");
+    args.iter()
+        .enumerate()
+        .filter(|&(i, arg)| Some(i) != subcommand && !(i < recipe_args && arg == "--watch"))
+        .map(|(_, arg)| arg.clone())
+        .collect()
+}
+
+/// `brew watch` and `brew test --watch`: brew `order` (brew's own arguments) in a fresh
+/// process now and after every change to what `recipes` finds or grinds in
+pub fn run_brew_watch(order: Vec<String>, recipes: impl Fn() -> Vec<PathBuf>) {
+    let brew = match std::env::current_exe() {
+        Ok(brew) => brew,
+        Err(e) => {
+            println!("[ERROR] Couldn't find the brew program to run again: {}", e);
+            std::process::exit(1);
+        }
+    };
+    loop {
+        let watched = grind_closure(recipes());
+        match Command::new(&brew).args(&order).status() {
+            Ok(status) if status.success() => println!("\n✅ Brewed fine."),
+            Ok(status) => match status.code() {
+                Some(code) => println!("\n☕ That brew spilled (exit code {}).", code),
+                None => println!("\n☕ That brew was stopped before it finished."),
+            },
+            Err(e) => {
+                println!("[ERROR] Couldn't start brew: {}", e);
+                std::process::exit(1);
+            }
+        }
+        println!("👀 Watching {} file(s); save one to brew again, Ctrl-C to stop.", watched.len());
+
+        let before = change_stamps(&watched);
+        let mut latest = before.clone();
+        while latest == before {
+            sleep(WATCH_POLL);
+            latest = change_stamps(&watched);
+        }
+        loop {
+            sleep(WATCH_SETTLE);
+            let settled = change_stamps(&watched);
+            if settled == latest {
+                break;
+            }
+            latest = settled;
+        }
+        let changed: Vec<String> = watched
+            .iter()
+            .zip(before.iter().zip(&latest))
+            .filter(|(_, (was, now))| was != now)
+            .map(|(file, _)| file.display().to_string())
+            .collect();
+        println!("\n🔁 {} changed, brewing again...\n", changed.join(", "));
+    }
+}
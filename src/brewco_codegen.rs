@@ -0,0 +1,523 @@
+/*
+ * 🖨️ Brewco Recipe Printer ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @printer_note: "What the parser grinds, the printer pours back!" ✨
+ *
+ * Turns a syntax tree back into canonical Brewco source, so tools that
+ * change the tree (refactorings, codemods) can write their result out.
+ * Parsing the output gives back the same tree, source marks aside:
+ *
+ *   - operators come out as their symbols (`add` is `+`, `same_blend` is
+ *     `==`, `with` is `&&`), which the lexer reads as the same tokens,
+ *     and parentheses go in only where precedence needs them
+ *   - four spaces of indentation, one statement per line, and a blank line
+ *     around brews, beans, recipes and test blocks at the top level
+ *   - 🎀🎀 doc comments stay on their brews and beans; other comments
+ *     aren't in the tree, so tools that need them use brew fmt instead
+ *
+ * `pourout a, b` and `pourout [a, b]` parse to the same tree and both come
+ * out as the former. Nodes the parser never builds (array, object, roast and
+ * constructor declarations, brew_time) are written the way their keywords
+ * suggest, with no promise that they parse back.
+ */
+
+// src/brewco_codegen.rs
+
+use crate::ast::*;
+use crate::lexer::{CoffeeTokenStream, Token};
+
+const INDENT: &str = "    ";
+
+/// Canonical source for a whole recipe, ending in a newline
+pub fn generate_source(statements: &[Statement]) -> String {
+    let mut out = String::new();
+    let mut previous_was_block = false;
+    for (index, statement) in code_statements(statements).enumerate() {
+        let is_block = is_declaration_block(statement);
+        if index > 0 && (is_block || previous_was_block) {
+            out.push('\n');
+        }
+        write_statement(&mut out, statement, 0);
+        previous_was_block = is_block;
+    }
+    out
+}
+
+/// Canonical source for one expression
+pub fn generate_expr(expr: &Expr) -> String {
+    let mut out = String::new();
+    write_expr(&mut out, expr);
+    out
+}
+
+fn code_statements(statements: &[Statement]) -> impl Iterator<Item = &Statement> {
+    statements.iter().filter(|statement| !matches!(statement, Statement::SourceMark { .. }))
+}
+
+fn is_declaration_block(statement: &Statement) -> bool {
+    matches!(
+        statement,
+        Statement::BrewDecl { .. }
+            | Statement::BeanDecl { .. }
+            | Statement::CoffeeRecipeDecl { .. }
+            | Statement::TasteTest { .. }
+            | Statement::BenchBrew { .. }
+    )
+}
+
+fn indent(out: &mut String, depth: usize) {
+    for _ in 0..depth {
+        out.push_str(INDENT);
+    }
+}
+
+fn write_doc(out: &mut String, doc: &Option<String>, depth: usize) {
+    for line in doc.iter().flat_map(|doc| doc.lines()) {
+        indent(out, depth);
+        out.push_str(crate::parser::DOC_COMMENT_MARKER);
+        if !line.is_empty() {
+            out.push(' ');
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+}
+
+/// ` {` plus the body on its own lines plus the closing `}`, or ` {}` when there's nothing in it
+fn write_block(out: &mut String, body: &[Statement], depth: usize) {
+    if code_statements(body).next().is_none() {
+        out.push_str(" {}");
+        return;
+    }
+    out.push_str(" {\n");
+    for statement in code_statements(body) {
+        write_statement(out, statement, depth + 1);
+    }
+    indent(out, depth);
+    out.push('}');
+}
+
+fn write_params(out: &mut String, params: &[ParamDecl]) {
+    let params: Vec<String> = params.iter().map(|param| match param.type_name.as_str() {
+        "Any" => param.name.clone(),
+        type_name => format!("{}: {}", param.name, type_name),
+    }).collect();
+    out.push_str(&params.join(", "));
+}
+
+fn write_brew(out: &mut String, name: &str, params: &[ParamDecl], return_type: &Option<String>, body: &[Statement], depth: usize) {
+    out.push_str("brew ");
+    out.push_str(name);
+    out.push('(');
+    write_params(out, params);
+    out.push(')');
+    if let Some(return_type) = return_type {
+        out.push_str(": ");
+        out.push_str(return_type);
+    }
+    write_block(out, body, depth);
+}
+
+fn write_statement(out: &mut String, statement: &Statement, depth: usize) {
+    if let Statement::BrewDecl { doc, .. } | Statement::BeanDecl { doc, .. } = statement {
+        write_doc(out, doc, depth);
+    }
+    indent(out, depth);
+    write_statement_inline(out, statement, depth);
+    out.push('\n');
+}
+
+/// One statement starting where the cursor is, without its line ending;
+/// nested lines are indented to `depth`
+fn write_statement_inline(out: &mut String, statement: &Statement, depth: usize) {
+    match statement {
+        Statement::VarDecl { name, type_ann, value } => {
+            out.push_str("beans ");
+            out.push_str(name);
+            if let Some(type_ann) = type_ann {
+                out.push_str(": ");
+                out.push_str(type_ann);
+            }
+            out.push_str(" = ");
+            write_expr(out, value);
+        }
+        Statement::ArrayDecl { name, elements } => {
+            out.push_str(&format!("beans {} = ", name));
+            write_expr(out, &Expr::ArrayLiteral(elements.clone()));
+        }
+        Statement::ObjectDecl { name, fields } => {
+            out.push_str(&format!("beans {} = ", name));
+            write_expr(out, &Expr::ObjectLiteral(fields.clone()));
+        }
+        Statement::Print(expr, spout) => {
+            out.push_str(match spout {
+                PourSpout::Line => "pourout ",
+                PourSpout::Flat => "pourout_flat ",
+                PourSpout::Spill => "spill_out ",
+            });
+            match expr {
+                // `pourout a, b` is how the parser reads several things to print
+                Expr::ArrayLiteral(items) if items.len() > 1 => write_list(out, items),
+                expr => write_expr(out, expr),
+            }
+        }
+        Statement::If { condition, then_branch, else_branch } => {
+            out.push_str("taste ");
+            write_expr(out, condition);
+            write_block(out, then_branch, depth);
+            if code_statements(else_branch).next().is_some() {
+                out.push_str(" otherwise");
+                write_block(out, else_branch, depth);
+            }
+        }
+        Statement::While { condition, body } => {
+            out.push_str("steep ");
+            write_expr(out, condition);
+            write_block(out, body, depth);
+        }
+        Statement::For { init, condition, increment, body } => {
+            out.push_str("pour ");
+            if let Some(init) = init {
+                write_statement_inline(out, init, depth);
+            }
+            out.push_str("; ");
+            write_expr(out, condition);
+            out.push(';');
+            if let Some(increment) = increment {
+                out.push(' ');
+                write_expr(out, increment);
+            }
+            write_block(out, body, depth);
+        }
+        Statement::RoastDecl { name, body } => write_brew(out, name, &[], &None, body, depth),
+        Statement::BeanDecl { name, parent, fields, methods, .. } => {
+            out.push_str("bean ");
+            out.push_str(name);
+            if let Some(parent) = parent {
+                out.push_str(" blend ");
+                out.push_str(parent);
+            }
+            if fields.is_empty() && methods.is_empty() {
+                out.push_str(" {}");
+                return;
+            }
+            out.push_str(" {\n");
+            for field in fields {
+                indent(out, depth + 1);
+                out.push_str(&format!("beans {} = ", field.name));
+                write_expr(out, &field.value);
+                out.push('\n');
+            }
+            for (index, method) in methods.iter().enumerate() {
+                if index > 0 || !fields.is_empty() {
+                    out.push('\n');
+                }
+                write_statement(out, method, depth + 1);
+            }
+            indent(out, depth);
+            out.push('}');
+        }
+        Statement::CoffeeRecipeDecl { name, methods } => {
+            out.push_str("recipe ");
+            out.push_str(name);
+            if methods.is_empty() {
+                out.push_str(" {}");
+                return;
+            }
+            out.push_str(" {\n");
+            for method in methods {
+                indent(out, depth + 1);
+                out.push_str(&method.name);
+                out.push('(');
+                write_params(out, &method.params);
+                out.push(')');
+                if method.return_type != "Any" {
+                    out.push_str(" -> ");
+                    out.push_str(&method.return_type);
+                }
+                out.push('\n');
+            }
+            indent(out, depth);
+            out.push('}');
+        }
+        Statement::BrewDecl { name, params, body, return_type, .. } => write_brew(out, name, params, return_type, body, depth),
+        Statement::ConstructorDecl { params, body } => write_brew(out, "constructor", params, &None, body, depth),
+        Statement::BrewTime(expr) => {
+            out.push_str("brew_time ");
+            write_expr(out, expr);
+        }
+        Statement::Return(value) => {
+            out.push_str("serve");
+            if let Some(value) = value {
+                out.push(' ');
+                write_expr(out, value);
+            }
+        }
+        Statement::YieldSip(value) => {
+            out.push_str("yield_sip ");
+            write_expr(out, value);
+        }
+        Statement::Break => out.push_str("break"),
+        Statement::Continue => out.push_str("continue"),
+        Statement::ExprStmt(expr) => write_expr(out, expr),
+        Statement::Foreach { var, second_var, iterable, body } => {
+            out.push_str("pour ");
+            out.push_str(var);
+            if let Some(second_var) = second_var {
+                out.push_str(", ");
+                out.push_str(second_var);
+            }
+            out.push_str(" in ");
+            write_expr(out, iterable);
+            write_block(out, body, depth);
+        }
+        Statement::RoastSwitch { value, arms, default } => {
+            out.push_str("roast ");
+            write_expr(out, value);
+            out.push_str(" {\n");
+            for (patterns, body) in arms {
+                indent(out, depth + 1);
+                for (index, pattern) in patterns.iter().enumerate() {
+                    if index > 0 {
+                        out.push_str(", ");
+                    }
+                    match pattern {
+                        RoastPattern::Value(value) => write_expr(out, value),
+                        RoastPattern::Range(low, high) => {
+                            write_operand(out, low, 0);
+                            out.push_str("..");
+                            write_operand(out, high, 0);
+                        }
+                    }
+                }
+                out.push(':');
+                write_arm_body(out, body, depth + 1);
+            }
+            if code_statements(default).next().is_some() {
+                indent(out, depth + 1);
+                out.push_str("otherwise:");
+                write_arm_body(out, default, depth + 1);
+            }
+            indent(out, depth);
+            out.push('}');
+        }
+        Statement::TryCatch { try_branch, error_variable, catch_branch } => {
+            out.push_str("taste_carefully");
+            write_block(out, try_branch, depth);
+            out.push_str(" if_spilled");
+            if let Some(error_variable) = error_variable {
+                out.push_str(&format!(" ({})", error_variable));
+            }
+            write_block(out, catch_branch, depth);
+        }
+        Statement::BenchBrew { name, body } => {
+            out.push_str(&format!("bench_brew \"{}\"", name));
+            write_block(out, body, depth);
+        }
+        Statement::TasteTest { name, body } => {
+            out.push_str(&format!("taste_test \"{}\"", name));
+            write_block(out, body, depth);
+        }
+        Statement::SourceMark { .. } => {}
+    }
+}
+
+/// A roast arm's body: one short statement on the arm's own line, anything else as a block
+fn write_arm_body(out: &mut String, body: &[Statement], depth: usize) {
+    let statements: Vec<&Statement> = code_statements(body).collect();
+    if let [statement] = statements.as_slice() {
+        let mut single = String::new();
+        write_statement_inline(&mut single, statement, depth);
+        // A lone statement starting with `{` would be read as the arm's block
+        if !single.contains('\n') && !single.starts_with('{') {
+            out.push(' ');
+            out.push_str(&single);
+            out.push('\n');
+            return;
+        }
+    }
+    write_block(out, body, depth);
+    out.push('\n');
+}
+
+// Expressions
+
+/// How tightly a binary operator binds, matching the parser's table
+fn precedence(op: &BinaryOperator) -> u8 {
+    use BinaryOperator::*;
+    match op {
+        ThenPour => 0,
+        Or => 1,
+        And => 2,
+        Equal | NotEqual => 3,
+        Less | Greater | LessEqual | GreaterEqual => 4,
+        Add | Subtract => 5,
+        Multiply | Divide | Modulo => 6,
+        BitAnd | BitOr | BitXor => 7,
+        Shl | Shr => 8,
+    }
+}
+
+fn operator_symbol(op: &BinaryOperator) -> &'static str {
+    use BinaryOperator::*;
+    match op {
+        Add => "+",
+        Subtract => "-",
+        Multiply => "*",
+        Divide => "/",
+        Modulo => "%",
+        Equal => "==",
+        NotEqual => "!=",
+        Greater => ">",
+        Less => "<",
+        GreaterEqual => ">=",
+        LessEqual => "<=",
+        And => "&&",
+        Or => "||",
+        BitAnd => "&",
+        BitOr => "|",
+        BitXor => "^",
+        Shl => "<<",
+        Shr => ">>",
+        ThenPour => "|>",
+    }
+}
+
+/// Numbers the way they were most likely written: whole ones without a `.0`
+fn number_literal(number: f64) -> String {
+    if number.fract() == 0.0 && number.abs() < 1e15 {
+        format!("{}", number as i64)
+    } else {
+        format!("{}", number)
+    }
+}
+
+/// Whether `key` can go in an object literal unquoted, i.e. it lexes as a plain identifier
+fn is_bare_key(key: &str) -> bool {
+    let tokens: Vec<Token> = CoffeeTokenStream::new(key).map(|spanned| spanned.token).collect();
+    matches!(tokens.as_slice(), [Token::Identifier(name)] if name == key && name != "true" && name != "false")
+}
+
+fn write_list(out: &mut String, items: &[Expr]) {
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            out.push_str(", ");
+        }
+        write_expr(out, item);
+    }
+}
+
+/// An operand of a binary operator that binds at `min_precedence` or tighter, parenthesized if it doesn't
+fn write_operand(out: &mut String, expr: &Expr, min_precedence: u8) {
+    let needs_parens = match expr {
+        Expr::BinaryOp { op, .. } => precedence(op) < min_precedence,
+        Expr::Assignment { .. } => true,
+        _ => false,
+    };
+    if needs_parens {
+        out.push('(');
+        write_expr(out, expr);
+        out.push(')');
+    } else {
+        write_expr(out, expr);
+    }
+}
+
+/// Something a call, `.member` or `[index]` hangs off, or a unary operator applies to
+fn write_postfix_base(out: &mut String, expr: &Expr) {
+    let needs_parens = matches!(expr, Expr::BinaryOp { .. } | Expr::Assignment { .. } | Expr::UnaryOp { .. })
+        || matches!(expr, Expr::Number(number) if *number < 0.0);
+    if needs_parens {
+        out.push('(');
+        write_expr(out, expr);
+        out.push(')');
+    } else {
+        write_expr(out, expr);
+    }
+}
+
+fn write_expr(out: &mut String, expr: &Expr) {
+    match expr {
+        Expr::Number(number) => out.push_str(&number_literal(*number)),
+        Expr::String(text) => {
+            out.push('"');
+            out.push_str(text);
+            out.push('"');
+        }
+        Expr::Boolean(value) => out.push_str(if *value { "true" } else { "false" }),
+        Expr::Identifier(name) => out.push_str(name),
+        Expr::ArrayLiteral(items) => {
+            out.push('[');
+            write_list(out, items);
+            out.push(']');
+        }
+        Expr::ObjectLiteral(fields) => {
+            if fields.is_empty() {
+                out.push_str("{}");
+                return;
+            }
+            out.push_str("{ ");
+            for (index, (key, value)) in fields.iter().enumerate() {
+                if index > 0 {
+                    out.push_str(", ");
+                }
+                if is_bare_key(key) {
+                    out.push_str(key);
+                } else {
+                    out.push_str(&format!("\"{}\"", key));
+                }
+                out.push_str(": ");
+                write_expr(out, value);
+            }
+            out.push_str(" }");
+        }
+        Expr::BinaryOp { left, op, right } => {
+            // Operators group to the left, so only the right side needs parens at the same level
+            let level = precedence(op);
+            write_operand(out, left, level);
+            out.push(' ');
+            out.push_str(operator_symbol(op));
+            out.push(' ');
+            write_operand(out, right, level + 1);
+        }
+        Expr::Assignment { target, value } => {
+            write_expr(out, target);
+            out.push_str(" = ");
+            write_expr(out, value);
+        }
+        Expr::UnaryOp { op, expr } => {
+            out.push_str(match op {
+                UnaryOperator::Negate => "-",
+                UnaryOperator::Not => "!",
+                UnaryOperator::BitNot => "~",
+            });
+            write_postfix_base(out, expr);
+        }
+        Expr::Call { callee, args } => {
+            write_postfix_base(out, callee);
+            out.push('(');
+            write_list(out, args);
+            out.push(')');
+        }
+        Expr::MemberAccess { object, member } => {
+            write_postfix_base(out, object);
+            out.push('.');
+            out.push_str(member);
+        }
+        Expr::ArrayAccess { array, index } => {
+            write_postfix_base(out, array);
+            out.push('[');
+            write_expr(out, index);
+            out.push(']');
+        }
+        Expr::NewBean { name, args } => {
+            out.push_str(&format!("new {}(", name));
+            write_list(out, args);
+            out.push(')');
+        }
+        Expr::Grind(path) => out.push_str(&format!("grind \"{}\"", path)),
+        Expr::This => out.push_str("this"),
+        Expr::Super => out.push_str("super"),
+    }
+}
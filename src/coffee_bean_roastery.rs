@@ -18,17 +18,16 @@
 // src/coffee_bean_roastery.rs - The Coffee Bean Import & Roastery System ☕
 
 use std::collections::HashMap;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use crate::espresso_errors::{CoffeeSpillReport, SpillType};
 use crate::coffee_io::CoffeeFileBrewery;
-use crate::interpreter::{Value, Interpreter};
-use crate::{lexer, parser};
+use crate::interpreter::{self, Value, Interpreter, ControlFlow};
+use crate::parser;
 
 /// The Coffee Bean Roastery - manages all imported coffee modules
 pub struct CoffeeBeanRoastery {
     roasted_beans: HashMap<String, RoastedCoffeeBean>,
     brewing_paths: Vec<PathBuf>,
-    current_brewing_dir: PathBuf,
 }
 
 /// A roasted coffee bean represents a loaded module with its exports
@@ -49,19 +48,19 @@ pub struct CoffeeImportDeclaration {
 
 impl CoffeeBeanRoastery {
     pub fn new_coffee_roastery() -> Self {
+        // Paths start at the module root when brewing a roastery, otherwise here
         let mut brewing_paths = vec![
-            PathBuf::from("./coffee_beans"),     // Local coffee beans
-            PathBuf::from("./roastery"),         // Project roastery
-            PathBuf::from("../shared_beans"),    // Shared coffee beans
+            interpreter::module_path("./coffee_beans"),     // Local coffee beans
+            interpreter::module_path("./roastery"),         // Project roastery
+            interpreter::module_path("../shared_beans"),    // Shared coffee beans
         ];
         
         // Add current directory
-        brewing_paths.push(PathBuf::from("."));
+        brewing_paths.push(interpreter::module_path("."));
         
         CoffeeBeanRoastery {
             roasted_beans: HashMap::new(),
             brewing_paths,
-            current_brewing_dir: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
         }
     }
     
@@ -83,8 +82,7 @@ impl CoffeeBeanRoastery {
         let coffee_source_code = CoffeeFileBrewery::sip_entire_recipe(&bean_file_path.to_string_lossy())?;
         
         // Parse and execute the coffee bean module
-        let coffee_tokens = lexer::lex(&coffee_source_code);
-        let brewing_result = parser::parse(&coffee_tokens);
+        let brewing_result = parser::parse_source(&coffee_source_code);
         
         if !brewing_result.errors.is_empty() {
             return Err(CoffeeSpillReport::new_brewing_disaster(
@@ -96,7 +94,11 @@ impl CoffeeBeanRoastery {
         
         // Create a fresh coffee interpreter for the module
         let mut bean_interpreter = Interpreter::new();
-        bean_interpreter.run(&brewing_result.statements);
+        if let Err(ControlFlow::RuntimeError(msg)) = bean_interpreter.run(&brewing_result.statements) {
+            let mut spill = bean_interpreter.spill_report(&msg);
+            spill.frame_in_source(&bean_file_path.to_string_lossy(), &coffee_source_code);
+            return Err(spill);
+        }
         
         // Extract exported flavors (variables/functions)
         let exported_flavors = self.extract_coffee_flavors(&bean_interpreter);
@@ -153,7 +155,7 @@ impl CoffeeBeanRoastery {
     }
     
     /// Extract coffee flavors (exports) from a module interpreter
-    fn extract_coffee_flavors(&self, bean_interpreter: &Interpreter) -> HashMap<String, Value> {
+    fn extract_coffee_flavors(&self, _bean_interpreter: &Interpreter) -> HashMap<String, Value> {
         // For now, we'll export everything from the module's global scope
         // In a more advanced implementation, we'd have explicit export statements
         
@@ -167,25 +169,22 @@ impl CoffeeBeanRoastery {
         &self,
         roasted_bean: &RoastedCoffeeBean,
         coffee_import: &CoffeeImportDeclaration,
-        coffee_interpreter: &mut Interpreter
+        _coffee_interpreter: &mut Interpreter
     ) -> Result<(), CoffeeSpillReport> {
         if coffee_import.imported_flavors.is_empty() {
             // Import everything with namespace
-            let namespace = coffee_import.import_alias.as_ref()
+            let _namespace = coffee_import.import_alias.as_ref()
                 .unwrap_or(&roasted_bean.bean_name);
                 
             // Create a module object containing all exports
-            let module_object = Value::Object {
-                class_name: "CoffeeModule".to_string(),
-                fields: roasted_bean.exported_flavors.clone(),
-            };
+            let _module_object = Value::object("CoffeeModule", roasted_bean.exported_flavors.clone());
             
             // TODO: Set variable in interpreter - need access to set_var
             // coffee_interpreter.set_var(namespace.clone(), module_object);
         } else {
             // Import specific flavors
             for flavor_name in &coffee_import.imported_flavors {
-                if let Some(flavor_value) = roasted_bean.exported_flavors.get(flavor_name) {
+                if let Some(_flavor_value) = roasted_bean.exported_flavors.get(flavor_name) {
                     // TODO: Set variable in interpreter
                     // coffee_interpreter.set_var(flavor_name.clone(), flavor_value.clone());
                 }
@@ -269,7 +268,7 @@ pub fn native_brew_import(
             };
             
             match roastery.brew_import_bean(&import_decl, interpreter) {
-                Ok(roasted_bean) => Ok(crate::interpreter::Value::Boolean(true)),
+                Ok(_roasted_bean) => Ok(crate::interpreter::Value::Boolean(true)),
                 Err(spill) => Err(crate::interpreter::ControlFlow::RuntimeError(
                     format!("Import brewing spill: {}", spill.bitter_message)
                 ))
@@ -282,16 +281,16 @@ pub fn native_brew_import(
 }
 
 pub fn native_list_coffee_beans(
-    args: Vec<crate::interpreter::Value>,
+    _args: Vec<crate::interpreter::Value>,
     roastery: &CoffeeBeanRoastery
 ) -> Result<crate::interpreter::Value, crate::interpreter::ControlFlow> {
     match roastery.scan_all_coffee_beans() {
         Ok(beans) => {
             let values: Vec<crate::interpreter::Value> = beans
                 .into_iter()
-                .map(|b| crate::interpreter::Value::String(b))
+                .map(crate::interpreter::Value::String)
                 .collect();
-            Ok(crate::interpreter::Value::Array(values))
+            Ok(crate::interpreter::Value::array(values))
         }
         Err(spill) => Err(crate::interpreter::ControlFlow::RuntimeError(
             format!("Bean scanning spill: {}", spill.bitter_message)
@@ -0,0 +1,82 @@
+/*
+ * ⛓️ Brewco Coffee Chains ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @queue_note: "First in line, first served, and nobody shuffles down!" ✨
+ *
+ * A CoffeeChain is a double-ended line of values: push and pop at either end
+ * without moving everything else along, which is what queues (push_back +
+ * pop_front) and stacks (push_back + pop_back) want. Cups shift every item
+ * when the front changes; chains don't.
+ *
+ * Chains are shared by reference like cups and change in place. They work
+ * with `pour ... in` (front to back) and `cup_size`.
+ */
+
+// src/coffee_chain.rs
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::interpreter::{ControlFlow, Value};
+
+/// The shared line behind `Value::Chain`
+pub type CoffeeChain = Rc<RefCell<VecDeque<Value>>>;
+
+/// `coffee_chain(cup?)`: a new chain, empty or holding the cup's items in order
+pub fn coffee_chain(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let items = match args.as_slice() {
+        [] => VecDeque::new(),
+        [Value::Array(cup)] => cup.iter().cloned().collect(),
+        [Value::Chain(chain)] => chain.borrow().clone(),
+        [other] => return Err(ControlFlow::RuntimeError(format!("coffee_chain() expects a cup to fill it from, but got a {}", other.type_name()))),
+        _ => return Err(ControlFlow::RuntimeError(format!("coffee_chain() expects 0 or 1 arguments, but got {}", args.len()))),
+    };
+    let chain = Rc::new(RefCell::new(items));
+    crate::grounds_collector::track_chain(&chain);
+    Ok(Value::Chain(chain))
+}
+
+fn chain_arg<'a>(native: &str, args: &'a [Value], count: usize) -> Result<&'a CoffeeChain, ControlFlow> {
+    match args.first() {
+        Some(Value::Chain(chain)) if args.len() == count => Ok(chain),
+        Some(other) if args.len() == count => {
+            Err(ControlFlow::RuntimeError(format!("{}() expects a coffee chain first, but got a {}", native, other.type_name())))
+        }
+        _ => Err(ControlFlow::RuntimeError(format!("{}() expects {} argument{}, but got {}", native, count, if count == 1 { "" } else { "s" }, args.len()))),
+    }
+}
+
+/// `chain_push_front(chain, value)` / `chain_push_back`: add at one end; serves the new length
+pub fn chain_push(native: &str, args: Vec<Value>, at_front: bool) -> Result<Value, ControlFlow> {
+    let chain = chain_arg(native, &args, 2)?;
+    let mut chain = chain.borrow_mut();
+    if at_front {
+        chain.push_front(args[1].clone());
+    } else {
+        chain.push_back(args[1].clone());
+    }
+    Ok(Value::Number(chain.len() as f64))
+}
+
+/// `chain_pop_front(chain)` / `chain_pop_back`: take the value off one end; an empty chain spills
+pub fn chain_pop(native: &str, args: Vec<Value>, at_front: bool) -> Result<Value, ControlFlow> {
+    let mut chain = chain_arg(native, &args, 1)?.borrow_mut();
+    let popped = if at_front { chain.pop_front() } else { chain.pop_back() };
+    popped.ok_or_else(|| ControlFlow::RuntimeError(format!("{}() can't pop from an empty coffee chain", native)))
+}
+
+/// `chain_peek_front(chain)` / `chain_peek_back`: look at one end without taking it; an empty chain spills
+pub fn chain_peek(native: &str, args: Vec<Value>, at_front: bool) -> Result<Value, ControlFlow> {
+    let chain = chain_arg(native, &args, 1)?.borrow();
+    let peeked = if at_front { chain.front() } else { chain.back() };
+    peeked.cloned().ok_or_else(|| ControlFlow::RuntimeError(format!("{}() can't peek into an empty coffee chain", native)))
+}
+
+/// `chain_to_cup(chain)`: the items as a cup, front to back
+pub fn chain_to_cup(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let chain = chain_arg("chain_to_cup", &args, 1)?;
+    let items = chain.borrow().iter().cloned().collect();
+    Ok(Value::array(items))
+}
@@ -0,0 +1,185 @@
+/*
+ * 📜 Brewco Coffee Formats ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @menu_note: "Every recipe card reads the same, whoever printed it!" ✨
+ *
+ * Turns the config and data files other tools write into Brewco values:
+ * tables and mappings become beans (objects), arrays and sequences become
+ * cups, and scalars become numbers, strings, booleans or null. Natives here
+ * take text, so they pair with sip_file() to read Cargo.toml, CI files and
+ * roastery configs without hand-rolled parsing. CSV natives read and write
+ * files directly, since quoting rules make line-by-line handling a trap.
+ */
+
+// src/coffee_formats.rs
+
+use std::collections::HashMap;
+
+use crate::interpreter::{ControlFlow, Value};
+
+/// The one text argument a format native takes
+fn format_text<'a>(native: &str, args: &'a [Value]) -> Result<&'a str, ControlFlow> {
+    match args {
+        [Value::String(text)] => Ok(text),
+        [_] => Err(ControlFlow::RuntimeError(format!("{}() expects the text to parse as a string (try sip_file())", native))),
+        _ => Err(ControlFlow::RuntimeError(format!("{}() expects 1 argument, but got {}", native, args.len()))),
+    }
+}
+
+/// `taste_toml(text)`: parse a TOML document into a bean of its tables
+pub fn taste_toml(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let text = format_text("taste_toml", &args)?;
+    let table: toml::Table = text.parse().map_err(|e: toml::de::Error| {
+        let (line, column) = e.span().map_or((1, 1), |span| line_and_column(text, span.start));
+        let message = match e.message().trim() {
+            "" => "this isn't valid TOML".to_string(),
+            message => message.replace('\n', "; "),
+        };
+        ControlFlow::RuntimeError(format!("taste_toml() couldn't parse the TOML at line {}, column {}: {}", line, column, message))
+    })?;
+    Ok(from_toml(toml::Value::Table(table)))
+}
+
+/// 1-based line and column of a byte offset
+fn line_and_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1)
+}
+
+fn from_toml(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(text) => Value::String(text),
+        toml::Value::Integer(n) => Value::Number(n as f64),
+        toml::Value::Float(n) => Value::Number(n),
+        toml::Value::Boolean(b) => Value::Boolean(b),
+        // Brewco has no date values, so dates keep their TOML spelling
+        toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+        toml::Value::Array(items) => Value::array(items.into_iter().map(from_toml).collect()),
+        toml::Value::Table(table) => Value::object("", table.into_iter().map(|(key, value)| (key, from_toml(value))).collect()),
+    }
+}
+
+/// `taste_yaml(text)`: parse a YAML document into beans, cups and scalars
+pub fn taste_yaml(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let text = format_text("taste_yaml", &args)?;
+    let document: serde_yaml::Value = serde_yaml::from_str(text)
+        .map_err(|e| ControlFlow::RuntimeError(format!("taste_yaml() couldn't parse the YAML: {}", e)))?;
+    Ok(from_yaml(document))
+}
+
+fn from_yaml(value: serde_yaml::Value) -> Value {
+    match value {
+        serde_yaml::Value::Null => Value::Null,
+        serde_yaml::Value::Bool(b) => Value::Boolean(b),
+        serde_yaml::Value::Number(n) => Value::Number(n.as_f64().unwrap_or(f64::NAN)),
+        serde_yaml::Value::String(text) => Value::String(text),
+        serde_yaml::Value::Sequence(items) => Value::array(items.into_iter().map(from_yaml).collect()),
+        serde_yaml::Value::Mapping(mapping) => {
+            let fields: HashMap<String, Value> = mapping
+                .into_iter()
+                .map(|(key, value)| (yaml_key(key), from_yaml(value)))
+                .collect();
+            Value::object("", fields)
+        }
+        // `!tag value`: the tag is dropped and the value kept
+        serde_yaml::Value::Tagged(tagged) => from_yaml(tagged.value),
+    }
+}
+
+/// Bean fields are named by strings, so other YAML keys are spelled out
+fn yaml_key(key: serde_yaml::Value) -> String {
+    match key {
+        serde_yaml::Value::String(text) => text,
+        serde_yaml::Value::Null => "null".to_string(),
+        serde_yaml::Value::Bool(b) => b.to_string(),
+        serde_yaml::Value::Number(n) => n.to_string(),
+        other => serde_yaml::to_string(&other).map(|text| text.trim_end().to_string()).unwrap_or_default(),
+    }
+}
+
+/// `sip_csv(path, has_header?)`: with a header (the default) every row is a bean keyed by
+/// column name; without one every row is a cup of strings. Fields stay strings.
+pub fn sip_csv(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (path, has_header) = match args.as_slice() {
+        [Value::String(path)] => (path, true),
+        [Value::String(path), Value::Boolean(has_header)] => (path, *has_header),
+        [_] | [_, _] => return Err(ControlFlow::RuntimeError("sip_csv() expects a string file path and an optional true/false for has_header".to_string())),
+        _ => return Err(ControlFlow::RuntimeError(format!("sip_csv() expects 1 or 2 arguments, but got {}", args.len()))),
+    };
+    let csv_spill = |e: csv::Error| ControlFlow::RuntimeError(format!("sip_csv() couldn't read '{}': {}", path, e));
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(has_header)
+        .flexible(!has_header)
+        .from_path(path)
+        .map_err(csv_spill)?;
+    let header: Vec<String> = if has_header {
+        reader.headers().map_err(csv_spill)?.iter().map(str::to_string).collect()
+    } else {
+        Vec::new()
+    };
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(csv_spill)?;
+        let fields = record.iter().map(|field| Value::String(field.to_string()));
+        rows.push(if has_header {
+            Value::object("", header.iter().cloned().zip(fields).collect())
+        } else {
+            Value::array(fields.collect())
+        });
+    }
+    Ok(Value::array(rows))
+}
+
+/// `pour_csv(path, rows)`: write a cup of rows. Cups are written as they are; beans get a
+/// header row of their field names (sorted), and a missing field is an empty cell.
+pub fn pour_csv(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (path, rows) = match args.as_slice() {
+        [Value::String(path), Value::Array(rows)] => (path, rows.to_vec()),
+        [_, _] => return Err(ControlFlow::RuntimeError("pour_csv() expects a string file path and a cup of rows".to_string())),
+        _ => return Err(ControlFlow::RuntimeError(format!("pour_csv() expects 2 arguments, but got {}", args.len()))),
+    };
+    let mut header: Vec<String> = Vec::new();
+    for row in &rows {
+        if let Value::Object { fields, .. } = row {
+            for name in fields.keys() {
+                if !header.contains(name) {
+                    header.push(name.clone());
+                }
+            }
+        }
+    }
+    header.sort();
+
+    let csv_spill = |e: csv::Error| ControlFlow::RuntimeError(format!("pour_csv() couldn't write '{}': {}", path, e));
+    let mut writer = csv::WriterBuilder::new().flexible(true).from_path(path).map_err(csv_spill)?;
+    if !header.is_empty() {
+        writer.write_record(&header).map_err(csv_spill)?;
+    }
+    for (index, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = match row {
+            Value::Array(cells) => cells.iter().map(csv_cell).collect(),
+            Value::Object { fields, .. } => {
+                header.iter().map(|name| fields.get(name).map(csv_cell).unwrap_or_default()).collect()
+            }
+            other => {
+                return Err(ControlFlow::RuntimeError(format!(
+                    "pour_csv() expects every row to be a cup or a bean, but row {} is {}",
+                    index,
+                    other.type_name()
+                )))
+            }
+        };
+        writer.write_record(&cells).map_err(csv_spill)?;
+    }
+    writer.flush().map_err(|e| ControlFlow::RuntimeError(format!("pour_csv() couldn't write '{}': {}", path, e)))?;
+    Ok(Value::Boolean(true))
+}
+
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
@@ -25,6 +25,13 @@ use crate::espresso_errors::{CoffeeSpillReport, SpillType};
 // Coffee-themed file operations
 pub struct CoffeeFileBrewery;
 
+/// What `recipe_details` found out about a path
+pub struct RecipeDetails {
+    pub size: u64,
+    pub modified_secs: Option<f64>, // seconds since the Unix epoch, when the platform knows
+    pub is_dir: bool,
+}
+
 impl CoffeeFileBrewery {
     /// Read the entire contents of a coffee recipe file
     pub fn sip_entire_recipe(recipe_path: &str) -> Result<String, CoffeeSpillReport> {
@@ -41,6 +48,28 @@ impl CoffeeFileBrewery {
         }
     }
     
+    /// Read a file's raw bytes, for anything that isn't text
+    pub fn sip_recipe_bytes(recipe_path: &str) -> Result<Vec<u8>, CoffeeSpillReport> {
+        fs::read(recipe_path).map_err(|e| {
+            CoffeeSpillReport::new_brewing_disaster(
+                SpillType::BeanNotFound,
+                0, 0,
+                &format!("Failed to sip bytes from '{}': {}", recipe_path, e)
+            )
+        })
+    }
+
+    /// Write raw bytes to a file, replacing what was there
+    pub fn pour_recipe_bytes(recipe_path: &str, coffee_bytes: &[u8]) -> Result<(), CoffeeSpillReport> {
+        fs::write(recipe_path, coffee_bytes).map_err(|e| {
+            CoffeeSpillReport::new_brewing_disaster(
+                SpillType::OverExtraction,
+                0, 0,
+                &format!("Failed to pour bytes to '{}': {}", recipe_path, e)
+            )
+        })
+    }
+
     /// Write coffee recipe to a file
     pub fn pour_recipe_to_file(recipe_path: &str, coffee_contents: &str) -> Result<(), CoffeeSpillReport> {
         match fs::write(recipe_path, coffee_contents) {
@@ -136,14 +165,135 @@ impl CoffeeFileBrewery {
         }
     }
     
+    /// Every file under a pantry whose path relative to it matches `pattern`, sorted.
+    /// Paths use `/` on every platform. Hidden entries only match a pattern that names
+    /// them with a leading `.`, and hidden directories aren't searched otherwise.
+    pub fn scan_pantry_deep(pantry_path: &str, pattern: &str) -> Result<Vec<String>, CoffeeSpillReport> {
+        Self::scan_pantry_deep_skipping(pantry_path, pattern, &[])
+    }
+
+    /// `scan_pantry_deep`, without searching directories named in `skipped_dirs` (like `target`)
+    pub fn scan_pantry_deep_skipping(pantry_path: &str, pattern: &str, skipped_dirs: &[&str]) -> Result<Vec<String>, CoffeeSpillReport> {
+        let root = Path::new(pantry_path);
+        if !root.is_dir() {
+            return Err(CoffeeSpillReport::new_brewing_disaster(
+                SpillType::BeanNotFound,
+                0, 0,
+                &format!("Failed to scan coffee pantry '{}': not a directory", pantry_path)
+            ));
+        }
+        let pattern_segments: Vec<&str> = pattern.split('/').filter(|segment| !segment.is_empty()).collect();
+        let search_hidden = pattern_segments.iter().any(|segment| segment.starts_with('.'));
+        let mut found = Vec::new();
+        let mut pending = vec![root.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else { continue };
+            for entry in entries.flatten() {
+                let entry_path = entry.path();
+                let Ok(relative) = entry_path.strip_prefix(root) else { continue };
+                let relative: Vec<String> = relative.components().map(|part| part.as_os_str().to_string_lossy().to_string()).collect();
+                if entry_path.is_dir() {
+                    let name = relative.last().map_or("", String::as_str);
+                    if (search_hidden || !name.starts_with('.')) && !skipped_dirs.contains(&name) {
+                        pending.push(entry_path);
+                    }
+                } else {
+                    let relative: Vec<&str> = relative.iter().map(String::as_str).collect();
+                    if pantry_glob_matches(&pattern_segments, &relative) {
+                        found.push(relative.join("/"));
+                    }
+                }
+            }
+        }
+        found.sort();
+        Ok(found)
+    }
+
+    /// Pour more onto the end of a recipe file, creating it if needed
+    pub fn top_up_recipe(recipe_path: &str, coffee_contents: &str) -> Result<(), CoffeeSpillReport> {
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(recipe_path)
+            .and_then(|mut file| file.write_all(coffee_contents.as_bytes()))
+            .map_err(|e| {
+                CoffeeSpillReport::new_brewing_disaster(
+                    SpillType::OverExtraction,
+                    0, 0,
+                    &format!("Failed to top up recipe '{}': {}", recipe_path, e)
+                )
+            })
+    }
+
+    /// Throw away a recipe file, or an empty pantry directory
+    pub fn toss_recipe(recipe_path: &str) -> Result<(), CoffeeSpillReport> {
+        let tossed = if Path::new(recipe_path).is_dir() {
+            fs::remove_dir(recipe_path)
+        } else {
+            fs::remove_file(recipe_path)
+        };
+        tossed.map_err(|e| {
+            CoffeeSpillReport::new_brewing_disaster(
+                SpillType::BeanNotFound,
+                0, 0,
+                &format!("Failed to toss '{}': {}", recipe_path, e)
+            )
+        })
+    }
+
+    /// Copy a recipe file, handing back how many bytes were copied
+    pub fn copy_recipe(from_path: &str, to_path: &str) -> Result<u64, CoffeeSpillReport> {
+        fs::copy(from_path, to_path).map_err(|e| {
+            CoffeeSpillReport::new_brewing_disaster(
+                SpillType::BeanNotFound,
+                0, 0,
+                &format!("Failed to copy recipe '{}' to '{}': {}", from_path, to_path, e)
+            )
+        })
+    }
+
+    /// Move (or rename) a recipe file or directory
+    pub fn move_recipe(from_path: &str, to_path: &str) -> Result<(), CoffeeSpillReport> {
+        fs::rename(from_path, to_path).map_err(|e| {
+            CoffeeSpillReport::new_brewing_disaster(
+                SpillType::BeanNotFound,
+                0, 0,
+                &format!("Failed to move recipe '{}' to '{}': {}", from_path, to_path, e)
+            )
+        })
+    }
+
+    /// Build a pantry directory along with any missing parents (like `mkdir -p`)
+    pub fn build_pantry(pantry_path: &str) -> Result<(), CoffeeSpillReport> {
+        fs::create_dir_all(pantry_path).map_err(|e| {
+            CoffeeSpillReport::new_brewing_disaster(
+                SpillType::OverExtraction,
+                0, 0,
+                &format!("Failed to build pantry '{}': {}", pantry_path, e)
+            )
+        })
+    }
+
+    /// Size, last-modified time and kind of a recipe file or directory
+    pub fn recipe_details(recipe_path: &str) -> Result<RecipeDetails, CoffeeSpillReport> {
+        let metadata = fs::metadata(recipe_path).map_err(|e| {
+            CoffeeSpillReport::new_brewing_disaster(
+                SpillType::BeanNotFound,
+                0, 0,
+                &format!("Failed to read details of '{}': {}", recipe_path, e)
+            )
+        })?;
+        let modified_secs = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|since_epoch| since_epoch.as_secs_f64());
+        Ok(RecipeDetails { size: metadata.len(), modified_secs, is_dir: metadata.is_dir() })
+    }
+    
     /// Create a new coffee recipe file with template
     pub fn brew_new_recipe(recipe_name: &str, recipe_type: &str) -> Result<(), CoffeeSpillReport> {
-        let template = match recipe_type {
-            "basic" => generate_basic_coffee_template(),
-            "bean" => generate_bean_coffee_template(),
-            "advanced" => generate_advanced_coffee_template(),
-            _ => generate_basic_coffee_template(),
-        };
+        let template = coffee_recipe_template(recipe_type).unwrap_or_else(generate_basic_coffee_template);
         
         let filename = if recipe_name.ends_with(".brewco") {
             recipe_name.to_string()
@@ -155,7 +305,81 @@ impl CoffeeFileBrewery {
     }
 }
 
+/// Match path segments against glob segments: `**` spans any number of directories,
+/// `*` and `?` stand in within one name, and `[abc]`, `[a-z]` or `[!abc]` pick one character
+fn pantry_glob_matches(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| {
+            path[..skip].iter().all(|name| !name.starts_with('.')) && pantry_glob_matches(rest, &path[skip..])
+        }),
+        Some((segment, rest)) => match path.split_first() {
+            Some((name, path_rest)) => {
+                glob_name_matches(segment, name) && pantry_glob_matches(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+fn glob_name_matches(segment: &str, name: &str) -> bool {
+    if name.starts_with('.') && !segment.starts_with('.') {
+        return false;
+    }
+    let segment: Vec<char> = segment.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    glob_chars_match(&segment, &name)
+}
+
+fn glob_chars_match(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len()).any(|skip| glob_chars_match(&pattern[1..], &name[skip..])),
+        Some('?') => !name.is_empty() && glob_chars_match(&pattern[1..], &name[1..]),
+        Some('[') => match (pattern.iter().position(|&c| c == ']'), name.first()) {
+            (Some(close), Some(&c)) if close > 1 => {
+                let (negated, class) = match pattern[1] {
+                    '!' | '^' => (true, &pattern[2..close]),
+                    _ => (false, &pattern[1..close]),
+                };
+                let mut in_class = false;
+                let mut i = 0;
+                while i < class.len() {
+                    if i + 2 < class.len() && class[i + 1] == '-' {
+                        in_class |= class[i] <= c && c <= class[i + 2];
+                        i += 3;
+                    } else {
+                        in_class |= class[i] == c;
+                        i += 1;
+                    }
+                }
+                in_class != negated && glob_chars_match(&pattern[close + 1..], &name[1..])
+            }
+            // No closing bracket: a literal '['
+            (None, Some('[')) => glob_chars_match(&pattern[1..], &name[1..]),
+            _ => false,
+        },
+        Some(&literal) => name.first() == Some(&literal) && glob_chars_match(&pattern[1..], &name[1..]),
+    }
+}
+
 // Coffee recipe templates
+
+/// The templates a new recipe or roastery can start from, in the order help lists them
+pub const RECIPE_TEMPLATES: &[&str] = &["basic", "bean", "advanced", "library", "cli"];
+
+/// A template's recipe, or None for a template that doesn't exist
+pub fn coffee_recipe_template(template: &str) -> Option<String> {
+    match template {
+        "basic" => Some(generate_basic_coffee_template()),
+        "bean" => Some(generate_bean_coffee_template()),
+        "advanced" => Some(generate_advanced_coffee_template()),
+        "library" => Some(generate_library_coffee_template()),
+        "cli" => Some(generate_cli_coffee_template()),
+        _ => None,
+    }
+}
+
 fn generate_basic_coffee_template() -> String {
     r#"🎀 Basic Brewco Recipe ☕
 🎀 Generated by the Coffee File Brewery
@@ -209,39 +433,104 @@ fn generate_advanced_coffee_template() -> String {
 🎀 Generated by the Coffee File Brewery
 
 🎀 Coffee Recipe Interface
-coffee_recipe BrewingMethod {
-    brew_coffee(beans: Number, water: Number) -> String
-    get_brew_time() -> Number
+recipe BrewingMethod {
+    pull_shot(grams, water) -> String
+    shot_time() -> Number
 }
 
 🎀 Espresso Bean Class
 bean EspressoMachine blend BrewingMethod {
-    beans machine_name pour_in "Professional Espresso"
-    beans pressure pour_in 9
-    
-    brew brew_coffee(beans: Number, water: Number): String {
-        beans ratio pour_in beans divide water
-        taste ratio more_caffeine 0.5 {
-            return "Perfect espresso! ☕"
+    beans machine_name = "Professional Espresso"
+    beans pressure = 9
+
+    brew pull_shot(grams, water) {
+        beans ratio = grams / water
+        taste (ratio more_caffeine 0.4) {
+            serve "Perfect espresso! ☕"
         } otherwise {
-            return "Needs more coffee beans! 🫘"
+            serve "Needs more coffee beans! 🫘"
         }
     }
-    
-    brew get_brew_time(): Number {
-        return 25  🎀 seconds
+
+    brew shot_time() {
+        serve 25  🎀 seconds
     }
 }
 
 🎀 Use the advanced features
-beans my_machine pour_in new EspressoMachine()
-beans result pour_in my_machine.brew_coffee(18, 36)
-pourout result
+beans my_machine = new EspressoMachine()
+pourout my_machine.pull_shot(18, 36)
+pourout "Ready in", my_machine.shot_time(), "seconds"
 
 🎀 Happy advanced brewing! ☕✨
 "#.to_string()
 }
 
+fn generate_library_coffee_template() -> String {
+    r#"🎀 Brewco Library Recipe ☕
+🎀 Generated by the Coffee File Brewery
+🎀 Grind it in from another recipe: beans shop = grind "main.brewco"
+
+beans house_blend = "Vanilla Latte"
+
+brew price_of(size) {
+    roast size {
+        "small": serve 3
+        "large": serve 5
+    }
+    serve 4
+}
+
+brew order_total(sizes) {
+    beans total = 0
+    pour size in sizes {
+        roast size {
+            "small": total = total + 3
+            "large": total = total + 5
+            otherwise: total = total + 4
+        }
+    }
+    serve total
+}
+
+🎀 Happy brewing! ☕✨
+"#.to_string()
+}
+
+fn generate_cli_coffee_template() -> String {
+    r#"🎀 Brewco Command Line Recipe ☕
+🎀 Generated by the Coffee File Brewery
+🎀 Run it with: brew main.brewco -- --size large Ada
+
+beans size = "medium"
+beans names = []
+beans order = coffee_order()
+beans i = 0
+steep (i < cup_size(order)) {
+    taste (order[i] same_blend "--size") {
+        taste (i + 1 same_blend cup_size(order)) {
+            pourout "--size needs a size, like --size large"
+            finish_service(2)
+        }
+        size = order[i + 1]
+        i = i + 2
+    } otherwise {
+        names = add_to_cup(names, order[i])
+        i = i + 1
+    }
+}
+
+taste (cup_size(names) same_blend 0) {
+    names = ["friend"]
+}
+pour name in names {
+    pourout "☕ One " + size + " coffee for " + name + "!"
+}
+
+🎀 Happy brewing! ☕✨
+"#.to_string()
+}
+
 // Native functions for Brewco file operations
 pub fn native_sip_file(args: Vec<crate::interpreter::Value>) -> Result<crate::interpreter::Value, crate::interpreter::ControlFlow> {
     if args.len() != 1 {
@@ -317,9 +606,9 @@ pub fn native_scan_pantry(args: Vec<crate::interpreter::Value>) -> Result<crate:
                 Ok(recipes) => {
                     let values: Vec<crate::interpreter::Value> = recipes
                         .into_iter()
-                        .map(|r| crate::interpreter::Value::String(r))
+                        .map(crate::interpreter::Value::String)
                         .collect();
-                    Ok(crate::interpreter::Value::Array(values))
+                    Ok(crate::interpreter::Value::array(values))
                 }
                 Err(spill) => Err(crate::interpreter::ControlFlow::RuntimeError(
                     format!("Pantry scanning spill: {}", spill.bitter_message)
@@ -330,4 +619,100 @@ pub fn native_scan_pantry(args: Vec<crate::interpreter::Value>) -> Result<crate:
             "scan_pantry() expects a string directory path".to_string()
         ))
     }
-} 
\ No newline at end of file
+}
+
+/// The string arguments a file native was handed, or a spill naming what it wanted
+fn file_native_paths<'a>(
+    native: &str,
+    wanted: &str,
+    args: &'a [crate::interpreter::Value],
+    count: usize,
+) -> Result<Vec<&'a str>, crate::interpreter::ControlFlow> {
+    if args.len() != count {
+        return Err(crate::interpreter::ControlFlow::RuntimeError(
+            format!("{}() expects {} argument{} ({})", native, count, if count == 1 { "" } else { "s" }, wanted)
+        ));
+    }
+    args.iter()
+        .map(|arg| match arg {
+            crate::interpreter::Value::String(path) => Ok(path.as_str()),
+            _ => Err(crate::interpreter::ControlFlow::RuntimeError(
+                format!("{}() expects string arguments ({})", native, wanted)
+            )),
+        })
+        .collect()
+}
+
+fn file_spill(kind: &str, spill: CoffeeSpillReport) -> crate::interpreter::ControlFlow {
+    crate::interpreter::ControlFlow::RuntimeError(format!("{} spill: {}", kind, spill.bitter_message))
+}
+
+pub fn native_sip_bytes(args: Vec<crate::interpreter::Value>) -> Result<crate::interpreter::Value, crate::interpreter::ControlFlow> {
+    let paths = file_native_paths("sip_bytes", "file path", &args, 1)?;
+    let bytes = CoffeeFileBrewery::sip_recipe_bytes(paths[0]).map_err(|spill| file_spill("File reading", spill))?;
+    Ok(crate::interpreter::Value::bytes(bytes))
+}
+
+pub fn native_pour_bytes(args: Vec<crate::interpreter::Value>) -> Result<crate::interpreter::Value, crate::interpreter::ControlFlow> {
+    match args.as_slice() {
+        [crate::interpreter::Value::String(path), crate::interpreter::Value::Bytes(bytes)] => {
+            CoffeeFileBrewery::pour_recipe_bytes(path, bytes).map_err(|spill| file_spill("File writing", spill))?;
+            Ok(crate::interpreter::Value::Boolean(true))
+        }
+        [_, _] => Err(crate::interpreter::ControlFlow::RuntimeError(
+            "pour_bytes() expects a string file path and bytes (see grind_to_bytes())".to_string()
+        )),
+        _ => Err(crate::interpreter::ControlFlow::RuntimeError(
+            "pour_bytes() expects 2 arguments (file path, bytes)".to_string()
+        )),
+    }
+}
+
+pub fn native_scan_pantry_deep(args: Vec<crate::interpreter::Value>) -> Result<crate::interpreter::Value, crate::interpreter::ControlFlow> {
+    let paths = file_native_paths("scan_pantry_deep", "directory path, glob pattern", &args, 2)?;
+    let found = CoffeeFileBrewery::scan_pantry_deep(paths[0], paths[1]).map_err(|spill| file_spill("Pantry scanning", spill))?;
+    Ok(crate::interpreter::Value::array(found.into_iter().map(crate::interpreter::Value::String).collect()))
+}
+
+pub fn native_top_up_file(args: Vec<crate::interpreter::Value>) -> Result<crate::interpreter::Value, crate::interpreter::ControlFlow> {
+    let paths = file_native_paths("top_up_file", "file path, content", &args, 2)?;
+    CoffeeFileBrewery::top_up_recipe(paths[0], paths[1]).map_err(|spill| file_spill("File writing", spill))?;
+    Ok(crate::interpreter::Value::Boolean(true))
+}
+
+pub fn native_toss_recipe(args: Vec<crate::interpreter::Value>) -> Result<crate::interpreter::Value, crate::interpreter::ControlFlow> {
+    let paths = file_native_paths("toss_recipe", "file path", &args, 1)?;
+    CoffeeFileBrewery::toss_recipe(paths[0]).map_err(|spill| file_spill("File tossing", spill))?;
+    Ok(crate::interpreter::Value::Boolean(true))
+}
+
+pub fn native_copy_recipe(args: Vec<crate::interpreter::Value>) -> Result<crate::interpreter::Value, crate::interpreter::ControlFlow> {
+    let paths = file_native_paths("copy_recipe", "from path, to path", &args, 2)?;
+    let copied = CoffeeFileBrewery::copy_recipe(paths[0], paths[1]).map_err(|spill| file_spill("File copying", spill))?;
+    Ok(crate::interpreter::Value::Number(copied as f64))
+}
+
+pub fn native_move_recipe(args: Vec<crate::interpreter::Value>) -> Result<crate::interpreter::Value, crate::interpreter::ControlFlow> {
+    let paths = file_native_paths("move_recipe", "from path, to path", &args, 2)?;
+    CoffeeFileBrewery::move_recipe(paths[0], paths[1]).map_err(|spill| file_spill("File moving", spill))?;
+    Ok(crate::interpreter::Value::Boolean(true))
+}
+
+pub fn native_build_pantry(args: Vec<crate::interpreter::Value>) -> Result<crate::interpreter::Value, crate::interpreter::ControlFlow> {
+    let paths = file_native_paths("build_pantry", "directory path", &args, 1)?;
+    CoffeeFileBrewery::build_pantry(paths[0]).map_err(|spill| file_spill("Pantry building", spill))?;
+    Ok(crate::interpreter::Value::Boolean(true))
+}
+
+pub fn native_recipe_details(args: Vec<crate::interpreter::Value>) -> Result<crate::interpreter::Value, crate::interpreter::ControlFlow> {
+    let paths = file_native_paths("recipe_details", "file path", &args, 1)?;
+    let details = CoffeeFileBrewery::recipe_details(paths[0]).map_err(|spill| file_spill("File details", spill))?;
+    let mut fields = std::collections::HashMap::new();
+    fields.insert("size".to_string(), crate::interpreter::Value::Number(details.size as f64));
+    fields.insert(
+        "modified".to_string(),
+        details.modified_secs.map(crate::interpreter::Value::Number).unwrap_or(crate::interpreter::Value::Null),
+    );
+    fields.insert("is_dir".to_string(), crate::interpreter::Value::Boolean(details.is_dir));
+    Ok(crate::interpreter::Value::object("RecipeDetails", fields))
+}
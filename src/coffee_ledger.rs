@@ -0,0 +1,177 @@
+/*
+ * 📒 Brewco Coffee Ledger ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @bookkeeping_note: "Every order written down, nothing forgotten!" ✨
+ *
+ * SQLite storage for recipes that need to remember things between brews.
+ * `open_ledger(path)` hands back a CoffeeLedger bean; `ledger_query` serves
+ * rows as beans keyed by column name and `ledger_execute` serves how many rows
+ * changed. Values always travel as bound parameters (`?` in the SQL with a cup,
+ * or `:name` with a bean), never pasted into the SQL text.
+ */
+
+// src/coffee_ledger.rs
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{Connection, Statement};
+
+use crate::interpreter::{ControlFlow, Value};
+
+/// The in-memory ledger, which needs no file access
+pub const MEMORY_LEDGER: &str = ":memory:";
+
+thread_local! {
+    /// Open connections, indexed by the `id` in each CoffeeLedger bean. Closed ones leave a gap.
+    static LEDGERS: RefCell<Vec<Option<Connection>>> = const { RefCell::new(Vec::new()) };
+}
+
+fn ledger_spill(native: &str, message: impl std::fmt::Display) -> ControlFlow {
+    ControlFlow::RuntimeError(format!("{}() {}", native, message))
+}
+
+/// The path `open_ledger` was asked for, so the interpreter can check file access first
+pub fn ledger_path(args: &[Value]) -> Option<&str> {
+    match args {
+        [Value::String(path)] => Some(path),
+        _ => None,
+    }
+}
+
+/// `open_ledger(path)`: open (or create) a SQLite database; ":memory:" keeps it in memory
+pub fn open_ledger(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let path = ledger_path(&args).ok_or_else(|| ledger_spill("open_ledger", "expects 1 argument, a database file path"))?;
+    let connection = Connection::open(path).map_err(|e| ledger_spill("open_ledger", format!("couldn't open '{}': {}", path, e)))?;
+    let id = LEDGERS.with(|ledgers| {
+        let mut ledgers = ledgers.borrow_mut();
+        ledgers.push(Some(connection));
+        ledgers.len() - 1
+    });
+    let mut fields = HashMap::new();
+    fields.insert("id".to_string(), Value::Number(id as f64));
+    fields.insert("path".to_string(), Value::String(path.to_string()));
+    Ok(Value::object("CoffeeLedger", fields))
+}
+
+/// `close_ledger(db)`: close the connection; using the ledger afterwards spills
+pub fn close_ledger(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let id = match args.as_slice() {
+        [ledger] => ledger_id("close_ledger", ledger)?,
+        _ => return Err(ledger_spill("close_ledger", format!("expects 1 argument, but got {}", args.len()))),
+    };
+    let connection = LEDGERS.with(|ledgers| ledgers.borrow_mut().get_mut(id).and_then(Option::take));
+    match connection {
+        Some(connection) => {
+            connection.close().map_err(|(_, e)| ledger_spill("close_ledger", e))?;
+            Ok(Value::Boolean(true))
+        }
+        None => Ok(Value::Boolean(false)),
+    }
+}
+
+/// `ledger_query(db, sql, params?)`: a cup of rows, each a bean keyed by column name
+pub fn ledger_query(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    with_statement("ledger_query", &args, |statement| {
+        let columns: Vec<String> = statement.column_names().into_iter().map(str::to_string).collect();
+        let mut rows = statement.raw_query();
+        let mut served = Vec::new();
+        while let Some(row) = rows.next().map_err(|e| ledger_spill("ledger_query", e))? {
+            let mut fields = HashMap::new();
+            for (index, column) in columns.iter().enumerate() {
+                let cell = row.get_ref(index).map_err(|e| ledger_spill("ledger_query", e))?;
+                fields.insert(column.clone(), from_sql(cell));
+            }
+            served.push(Value::object("", fields));
+        }
+        Ok(Value::array(served))
+    })
+}
+
+/// `ledger_execute(db, sql, params?)`: run one write and serve how many rows it changed
+pub fn ledger_execute(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    with_statement("ledger_execute", &args, |statement| {
+        let changed = statement.raw_execute().map_err(|e| ledger_spill("ledger_execute", e))?;
+        Ok(Value::Number(changed as f64))
+    })
+}
+
+fn ledger_id(native: &str, ledger: &Value) -> Result<usize, ControlFlow> {
+    if let Value::Object { class_name, fields } = ledger {
+        if class_name == "CoffeeLedger" {
+            if let Some(Value::Number(id)) = fields.get("id") {
+                return Ok(*id as usize);
+            }
+        }
+    }
+    Err(ledger_spill(native, "expects a ledger from open_ledger()"))
+}
+
+/// Prepare `sql` on the ledger, bind the parameters, and hand the statement to `brew`
+fn with_statement(
+    native: &str,
+    args: &[Value],
+    brew: impl FnOnce(&mut Statement) -> Result<Value, ControlFlow>,
+) -> Result<Value, ControlFlow> {
+    let (ledger, sql, params) = match args {
+        [ledger, Value::String(sql)] => (ledger, sql, None),
+        [ledger, Value::String(sql), params] => (ledger, sql, Some(params)),
+        [_, _] | [_, _, _] => return Err(ledger_spill(native, "expects the SQL as a string")),
+        _ => return Err(ledger_spill(native, format!("expects 2 or 3 arguments, but got {}", args.len()))),
+    };
+    let id = ledger_id(native, ledger)?;
+    LEDGERS.with(|ledgers| {
+        let ledgers = ledgers.borrow();
+        let connection = ledgers
+            .get(id)
+            .and_then(Option::as_ref)
+            .ok_or_else(|| ledger_spill(native, "was handed a ledger that's already closed"))?;
+        let mut statement = connection.prepare(sql).map_err(|e| ledger_spill(native, e))?;
+        match params {
+            None => {}
+            Some(Value::Array(cup)) => {
+                for (index, param) in cup.iter().enumerate() {
+                    statement.raw_bind_parameter(index + 1, to_sql(native, param)?).map_err(|e| ledger_spill(native, e))?;
+                }
+            }
+            Some(Value::Object { fields, .. }) => {
+                for (name, param) in fields.iter() {
+                    let placeholder = format!(":{}", name);
+                    let index = statement
+                        .parameter_index(&placeholder)
+                        .map_err(|e| ledger_spill(native, e))?
+                        .ok_or_else(|| ledger_spill(native, format!("has no {} in its SQL", placeholder)))?;
+                    statement.raw_bind_parameter(index, to_sql(native, param)?).map_err(|e| ledger_spill(native, e))?;
+                }
+            }
+            Some(other) => {
+                return Err(ledger_spill(native, format!("expects parameters as a cup (for ?) or a bean (for :name), but got a {}", other.type_name())))
+            }
+        }
+        brew(&mut statement)
+    })
+}
+
+fn to_sql(native: &str, value: &Value) -> Result<SqlValue, ControlFlow> {
+    match value {
+        Value::Null => Ok(SqlValue::Null),
+        Value::Boolean(b) => Ok(SqlValue::Integer(*b as i64)),
+        Value::Number(n) if n.fract() == 0.0 && n.abs() < 9.0e15 => Ok(SqlValue::Integer(*n as i64)),
+        Value::Number(n) => Ok(SqlValue::Real(*n)),
+        Value::String(text) => Ok(SqlValue::Text(text.clone())),
+        Value::Bytes(bytes) => Ok(SqlValue::Blob(bytes.to_vec())),
+        other => Err(ledger_spill(native, format!("can't store a {} in a ledger", other.type_name()))),
+    }
+}
+
+fn from_sql(cell: ValueRef) -> Value {
+    match cell {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(n) => Value::Number(n as f64),
+        ValueRef::Real(n) => Value::Number(n),
+        ValueRef::Text(text) => Value::String(String::from_utf8_lossy(text).into_owned()),
+        ValueRef::Blob(bytes) => Value::bytes(bytes.to_vec()),
+    }
+}
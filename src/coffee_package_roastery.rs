@@ -17,11 +17,14 @@
 
 // src/coffee_package_roastery.rs - The Coffee Bean Package Roastery Supply Chain ☕
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 use serde::{Serialize, Deserialize};
 use crate::espresso_errors::{CoffeeSpillReport, SpillType};
-use crate::coffee_io::CoffeeFileBrewery;
+use crate::coffee_io::{coffee_recipe_template, CoffeeFileBrewery, RECIPE_TEMPLATES};
+use crate::roastery_config::ROASTERY_CONFIG_FILE;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::bean_registry::{fetch_bean_info, fetch_bean_sack};
 
 /// The Coffee Bean Package Roastery - manages package installation and dependencies
 pub struct CoffeeBeanPackageRoastery {
@@ -45,6 +48,152 @@ pub struct RoasteryManifest {
     pub coffee_keywords: Vec<String>,
     pub roastery_homepage: Option<String>,
     pub bean_repository: Option<String>,
+    /// A compiled plugin the package ships, relative to it; see coffee_plugins.rs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub native_plugin: Option<String>,
+    /// The recipe `brew <roastery dir>` brews, relative to the roastery; main.brewco when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry_recipe: Option<String>,
+}
+
+/// Where beans come from unless BREWCO_REGISTRY points somewhere else
+pub const CENTRAL_REGISTRY: &str = "https://beans.brewco.org";
+
+/// The registry to install from and fetch advisories from
+pub fn registry_url() -> String {
+    std::env::var("BREWCO_REGISTRY").ok().filter(|url| !url.is_empty()).unwrap_or_else(|| CENTRAL_REGISTRY.to_string())
+}
+
+/// The token sent to the registry (BREWCO_REGISTRY_TOKEN), for publishing and private registries
+pub fn registry_token() -> Option<String> {
+    std::env::var("BREWCO_REGISTRY_TOKEN").ok().filter(|token| !token.is_empty())
+}
+
+/// The wasm32 build can't reach a registry
+#[cfg(target_arch = "wasm32")]
+fn fetch_bean_info(_registry: &str, _token: Option<&str>, _name: &str, _version: Option<&str>) -> Result<CoffeeBeanPackageInfo, String> {
+    Err("beans can't be installed from a registry in the wasm32 build".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn fetch_bean_sack(_registry: &str, _token: Option<&str>, _name: &str, _version: &str, _into: &Path) -> Result<String, String> {
+    Err("beans can't be installed from a registry in the wasm32 build".to_string())
+}
+
+/// Bean names and versions become paths (coffee_beans/<name>@<version>, a registry's store),
+/// so they keep to plain characters: letters, digits, _ and -, and . and + in versions
+fn plain_path_part(part: &str, extra: &[char]) -> bool {
+    !part.is_empty()
+        && !part.starts_with('.')
+        && part.len() <= 64
+        && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || extra.contains(&c))
+}
+
+pub fn is_plain_bean_name(name: &str) -> bool {
+    plain_path_part(name, &[])
+}
+
+pub fn is_plain_bean_version(version: &str) -> bool {
+    plain_path_part(version, &['.', '+'])
+}
+
+/// Why a bean name and version can't be installed or published, if they can't
+pub fn unplain_bean(name: &str, version: &str) -> Option<String> {
+    if !is_plain_bean_name(name) {
+        return Some(format!("'{}' isn't a bean name; they take letters, digits, _ and -", name));
+    }
+    if !is_plain_bean_version(version) {
+        return Some(format!("'{}' isn't a version of '{}'; versions take letters, digits, _, -, . and +", version, name));
+    }
+    None
+}
+
+pub const ROASTERY_LOCK_FILE: &str = "roastery.lock";
+
+/// roastery.lock: the exact version of every installed bean and a checksum of its files
+#[derive(Serialize, Deserialize, Default)]
+pub struct RoasteryLock {
+    pub locked_beans: BTreeMap<String, LockedBean>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct LockedBean {
+    pub bean_version: String,
+    pub checksum: String,   // "sha256:" and the hex digest of bean_checksum
+    pub bean_origin: String, // the registry it was installed from
+}
+
+impl RoasteryLock {
+    /// The lockfile at `path`; none yet is an empty one
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(lock) => serde_json::from_str(&lock).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(RoasteryLock::default()),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let lock = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, lock + "\n").map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+/// Every file under `dir` with its path relative to it, sorted, so a checksum doesn't depend on the listing order
+fn bean_files(dir: &Path) -> std::io::Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    let mut waiting = vec![dir.to_path_buf()];
+    while let Some(next) = waiting.pop() {
+        for entry in std::fs::read_dir(&next)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                waiting.push(path);
+            } else {
+                let relative = path.strip_prefix(dir).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+                files.push((relative, path));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// A sha256 over an installed bean's files, their paths included: "sha256:<hex>"
+pub fn bean_checksum(dir: &Path) -> std::io::Result<String> {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    for (relative, path) in bean_files(dir)? {
+        let contents = std::fs::read(&path)?;
+        hasher.update(relative.as_bytes());
+        hasher.update([0]);
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(&contents);
+    }
+    Ok(format!("sha256:{}", hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect::<String>()))
+}
+
+/// `~/.brewco/cache`, shared by every roastery on the machine, so beans installed once install offline after
+pub fn bean_cache_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").filter(|home| !home.is_empty()).map(|home| PathBuf::from(home).join(".brewco").join("cache"))
+}
+
+/// Copy a bean's files from one directory into another, making it if needed
+fn copy_bean_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    for (relative, path) in bean_files(from)? {
+        let target = to.join(&relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(&path, &target)?;
+    }
+    std::fs::create_dir_all(to)
+}
+
+/// Where an installed bean lives under coffee_beans/: `name@version/`, or plain `name/`
+pub fn installed_bean_dir(bean_storage: &Path, bean_name: &str, version: &str) -> Option<PathBuf> {
+    [bean_storage.join(format!("{}@{}", bean_name, version)), bean_storage.join(bean_name)]
+        .into_iter()
+        .find(|dir| dir.is_dir())
 }
 
 /// An installed coffee bean package
@@ -88,9 +237,9 @@ impl CoffeeBeanPackageRoastery {
         let default_roastery_sources = vec![
             RoasterySource {
                 source_name: "The Central Coffee Bean Repository".to_string(),
-                source_url: "https://beans.brewco.org".to_string(),
+                source_url: registry_url(),
                 is_secure_source: true,
-                authentication_token: None,
+                authentication_token: registry_token(),
             },
             RoasterySource {
                 source_name: "Community Coffee Roastery".to_string(),
@@ -115,14 +264,36 @@ impl CoffeeBeanPackageRoastery {
         })
     }
     
-    /// Initialize a new coffee roastery project
+    /// Initialize a new coffee roastery project in `roastery_dir`, with its main recipe
+    /// brewed from one of the coffee_io templates
     pub fn brew_new_roastery(
-        roastery_name: &str,
+        roastery_dir: &Path,
+        template: &str,
         head_barista: &str,
         description: &str
     ) -> Result<(), CoffeeSpillReport> {
+        let roastery_name = roastery_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| roastery_dir.display().to_string());
+        let main_recipe = coffee_recipe_template(template).ok_or_else(|| {
+            CoffeeSpillReport::new_brewing_disaster(
+                SpillType::BeanNotFound,
+                0, 0,
+                &format!("There's no '{}' template; pick one of {}", template, RECIPE_TEMPLATES.join(", "))
+            )
+        })?;
+        let already_brewing = std::fs::read_dir(roastery_dir).is_ok_and(|mut entries| entries.next().is_some());
+        if already_brewing || roastery_dir.is_file() {
+            return Err(CoffeeSpillReport::new_brewing_disaster(
+                SpillType::ConflictingFlavors,
+                0, 0,
+                &format!("'{}' already exists and isn't empty; pick another name", roastery_dir.display())
+            ));
+        }
+
         let manifest = RoasteryManifest {
-            roastery_name: roastery_name.to_string(),
+            roastery_name: roastery_name.clone(),
             roastery_version: "1.0.0".to_string(),
             roastery_description: description.to_string(),
             head_barista: head_barista.to_string(),
@@ -133,49 +304,39 @@ impl CoffeeBeanPackageRoastery {
             coffee_keywords: vec!["coffee".to_string(), "brewco".to_string()],
             roastery_homepage: None,
             bean_repository: None,
+            native_plugin: None,
+            entry_recipe: None,
         };
-        
-        Self::save_roastery_manifest(&manifest)?;
-        
-        // Create directory structure
-        std::fs::create_dir_all("coffee_beans").map_err(|e| {
-            CoffeeSpillReport::new_brewing_disaster(
-                SpillType::OverExtraction,
-                0, 0,
-                &format!("Failed to create coffee beans directory: {}", e)
-            )
-        })?;
-        
-        std::fs::create_dir_all("roastery").map_err(|e| {
-            CoffeeSpillReport::new_brewing_disaster(
-                SpillType::OverExtraction,
-                0, 0,
-                &format!("Failed to create roastery directory: {}", e)
-            )
-        })?;
-        
-        // Create a sample coffee file
-        let sample_coffee = r#"🎀 Welcome to your new Coffee Roastery! ☕
-🎀 Generated by the Coffee Bean Package Roastery
 
-beans greeting pour_in "Hello from your new roastery!"
-beans roastery_name pour_in "My Coffee Roastery"
+        // Create directory structure
+        for pantry in ["coffee_beans", "roastery", "tests"] {
+            std::fs::create_dir_all(roastery_dir.join(pantry)).map_err(|e| {
+                CoffeeSpillReport::new_brewing_disaster(
+                    SpillType::OverExtraction,
+                    0, 0,
+                    &format!("Failed to create {} directory: {}", pantry, e)
+                )
+            })?;
+        }
 
-pourout greeting
-pourout "Roastery Name:", roastery_name
+        let in_roastery = |file: &str| roastery_dir.join(file).to_string_lossy().into_owned();
+        Self::save_roastery_manifest_to(&manifest, &in_roastery("roastery.json"))?;
+        CoffeeFileBrewery::pour_recipe_to_file(&in_roastery("main.brewco"), &main_recipe)?;
+        CoffeeFileBrewery::pour_recipe_to_file(&in_roastery("tests/main_test.brewco"), &sample_taste_test(&roastery_name, template))?;
+        CoffeeFileBrewery::pour_recipe_to_file(&in_roastery(ROASTERY_CONFIG_FILE), SAMPLE_ROASTERY_CONFIG)?;
+        CoffeeFileBrewery::pour_recipe_to_file(&in_roastery(".gitignore"), "# Coffee beans installed from roastery.json\ncoffee_beans/\n")?;
 
-🎀 Happy brewing! ☕✨
-"#;
-        
-                    CoffeeFileBrewery::pour_recipe_to_file("main.brewco", sample_coffee)?;
-        
-        println!("☕ New Coffee Roastery '{}' brewed successfully!", roastery_name);
-        println!("📁 Files created:");
+        println!("☕ New Coffee Roastery '{}' brewed successfully from the {} template!", roastery_name, template);
+        println!("📁 Files created in {}:", roastery_dir.display());
         println!("   - roastery.json (roastery manifest)");
-                    println!("   - main.brewco (sample coffee file)");
+        println!("   - {} (project defaults for brew)", ROASTERY_CONFIG_FILE);
+        println!("   - main.brewco (sample coffee file)");
+        println!("   - tests/main_test.brewco (sample taste test)");
+        println!("   - .gitignore");
         println!("   - coffee_beans/ (dependencies directory)");
         println!("   - roastery/ (project source directory)");
-        
+        println!("🚀 Next: cd {} && brew . && brew test", roastery_dir.display());
+
         Ok(())
     }
     
@@ -186,10 +347,22 @@ pourout "Roastery Name:", roastery_name
         bean_version: Option<&str>
     ) -> Result<(), CoffeeSpillReport> {
         println!("☕ Brewing installation of coffee bean '{}'...", bean_name);
+        let unplain_spill = |why: String| CoffeeSpillReport::new_brewing_disaster(SpillType::BeanNotFound, 0, 0, &why);
+        if let Some(why) = unplain_bean(bean_name, bean_version.unwrap_or("0")) {
+            return Err(unplain_spill(why));
+        }
         
-        // Find the bean in roastery sources
-        let bean_info = self.search_coffee_bean(bean_name)?;
+        // Find the bean (at the version asked for, so its dependencies are that version's) in roastery sources
+        let bean_info = self.search_coffee_bean(bean_name, bean_version)?;
         let version_to_install = bean_version.unwrap_or(&bean_info.latest_version);
+        // What the registry serves turns into paths too, so it's held to the same names before anything touches the disk
+        let served = std::iter::once((bean_name, version_to_install))
+            .chain(bean_info.brewing_dependencies.iter().map(|(name, version)| (name.as_str(), version.as_str())));
+        for (name, version) in served {
+            if let Some(why) = unplain_bean(name, version) {
+                return Err(unplain_spill(format!("The registry served {}", why)));
+            }
+        }
         
         // Check if already installed
         if let Some(existing) = self.installed_beans.get(bean_name) {
@@ -207,13 +380,14 @@ pourout "Roastery Name:", roastery_name
             }
         }
         
-        // Download and install the bean
+        // Download and install the bean, then hold it to roastery.lock
         let installation_path = self.download_coffee_bean(bean_name, version_to_install)?;
+        self.lock_coffee_bean(bean_name, version_to_install, &installation_path)?;
         
         let installed_bean = InstalledCoffeeBean {
             bean_name: bean_name.to_string(),
             bean_version: version_to_install.to_string(),
-            bean_origin: "Central Coffee Bean Repository".to_string(),
+            bean_origin: self.registry_source().source_url.clone(),
             installation_path,
             brewing_dependencies: bean_info.brewing_dependencies.keys().cloned().collect(),
             install_time: std::time::SystemTime::now(),
@@ -233,54 +407,88 @@ pourout "Roastery Name:", roastery_name
         Ok(())
     }
     
-    /// Search for a coffee bean in roastery sources
-    fn search_coffee_bean(&self, bean_name: &str) -> Result<CoffeeBeanPackageInfo, CoffeeSpillReport> {
-        // For now, return mock data - in real implementation, this would query the sources
-        Ok(CoffeeBeanPackageInfo {
-            bean_name: bean_name.to_string(),
-            available_versions: vec!["1.0.0".to_string(), "1.1.0".to_string()],
-            latest_version: "1.1.0".to_string(),
-            bean_description: format!("A delicious {} coffee bean package", bean_name),
-            roastery_author: "Coffee Master".to_string(),
-            download_count: 1337,
-            brewing_dependencies: HashMap::new(),
-            roastery_homepage: Some(format!("https://beans.brewco.org/{}", bean_name)),
-            coffee_keywords: vec!["coffee".to_string(), "utility".to_string()],
+    /// The registry beans are installed from: the first roastery source
+    fn registry_source(&self) -> &RoasterySource {
+        &self.roastery_sources[0]
+    }
+
+    /// Search for a coffee bean in the registry, at its latest version or `version`
+    fn search_coffee_bean(&self, bean_name: &str, version: Option<&str>) -> Result<CoffeeBeanPackageInfo, CoffeeSpillReport> {
+        let source = self.registry_source();
+        fetch_bean_info(&source.source_url, source.authentication_token.as_deref(), bean_name, version).map_err(|why| {
+            CoffeeSpillReport::new_brewing_disaster(
+                SpillType::BeanNotFound,
+                0, 0,
+                &format!("Couldn't find coffee bean '{}' at {}: {}", bean_name, source.source_url, why)
+            )
         })
     }
     
-    /// Download a coffee bean package
+    /// Record an installed bean's checksum in roastery.lock; a bean already locked at
+    /// this version has to match what was locked
+    fn lock_coffee_bean(&self, bean_name: &str, version: &str, bean_dir: &Path) -> Result<(), CoffeeSpillReport> {
+        let lock_spill = |message: String| CoffeeSpillReport::new_brewing_disaster(SpillType::OverExtraction, 0, 0, &message);
+        let lock_path = Path::new(ROASTERY_LOCK_FILE);
+        let mut lock = RoasteryLock::load(lock_path).map_err(lock_spill)?;
+        let checksum = bean_checksum(bean_dir).map_err(|e| lock_spill(format!("Failed to checksum {}: {}", bean_dir.display(), e)))?;
+        if let Some(locked) = lock.locked_beans.get(bean_name) {
+            if locked.bean_version == version && locked.checksum != checksum {
+                // Whatever came in isn't what was locked, so it doesn't stay installed
+                let _ = std::fs::remove_dir_all(bean_dir);
+                return Err(lock_spill(format!(
+                    "Coffee bean '{}' {} doesn't match roastery.lock ({} locked, {} downloaded), so it wasn't installed",
+                    bean_name, version, locked.checksum, checksum
+                )));
+            }
+        }
+        lock.locked_beans.insert(
+            bean_name.to_string(),
+            LockedBean { bean_version: version.to_string(), checksum, bean_origin: self.registry_source().source_url.clone() },
+        );
+        lock.save(lock_path).map_err(lock_spill)
+    }
+
+    /// Download a coffee bean package, or copy it from the bean cache when it's been downloaded before
     fn download_coffee_bean(&self, bean_name: &str, version: &str) -> Result<PathBuf, CoffeeSpillReport> {
         let bean_dir = self.local_bean_storage.join(format!("{}@{}", bean_name, version));
+        // A fresh copy, not one laid over whatever an earlier install left behind
+        if bean_dir.exists() {
+            std::fs::remove_dir_all(&bean_dir).map_err(|e| {
+                CoffeeSpillReport::new_brewing_disaster(
+                    SpillType::OverExtraction,
+                    0, 0,
+                    &format!("Failed to clear {}: {}", bean_dir.display(), e)
+                )
+            })?;
+        }
+        let cached_dir = bean_cache_dir().map(|cache| cache.join(format!("{}@{}", bean_name, version)));
+        if let Some(cached_dir) = cached_dir.as_ref().filter(|dir| dir.is_dir()) {
+            copy_bean_dir(cached_dir, &bean_dir).map_err(|e| {
+                CoffeeSpillReport::new_brewing_disaster(
+                    SpillType::OverExtraction,
+                    0, 0,
+                    &format!("Failed to copy '{}' {} from the bean cache: {}", bean_name, version, e)
+                )
+            })?;
+            println!("☕ Found coffee bean '{}' {} in the bean cache ({})", bean_name, version, cached_dir.display());
+            return Ok(bean_dir);
+        }
         
-        // Create the directory
-        std::fs::create_dir_all(&bean_dir).map_err(|e| {
+        let source = self.registry_source();
+        fetch_bean_sack(&source.source_url, source.authentication_token.as_deref(), bean_name, version, &bean_dir).map_err(|why| {
             CoffeeSpillReport::new_brewing_disaster(
                 SpillType::OverExtraction,
                 0, 0,
-                &format!("Failed to create bean directory: {}", e)
+                &format!("Failed to download '{}' {} from {}: {}", bean_name, version, source.source_url, why)
             )
         })?;
-        
-        // For now, create a mock bean file - in real implementation, this would download from sources
-        let mock_bean_content = format!(r#"🎀 {} Coffee Bean Package ☕
-🎀 Version: {}
-🎀 Auto-downloaded from Central Coffee Bean Repository
-
-beans {}_greeting pour_in "Hello from {} bean!"
-beans {}_version pour_in "{}"
-
-brew get_{}_info() {{
-    pourout "Bean:", "{}"
-    pourout "Version:", {}_version
-    return {}_greeting
-}}
-
-🎀 Exported functions and variables are available for import! ☕
-"#, bean_name, version, bean_name, bean_name, bean_name, version, bean_name, bean_name, bean_name, bean_name);
-        
-        let bean_file = bean_dir.join("index.brewco");
-        CoffeeFileBrewery::pour_recipe_to_file(&bean_file.to_string_lossy(), &mock_bean_content)?;
+
+        // Keep a copy for the next roastery that wants it; installing still works without one
+        if let Some(cached_dir) = cached_dir {
+            if let Err(e) = copy_bean_dir(&bean_dir, &cached_dir) {
+                eprintln!("[Coffee Warning] Could not keep '{}' {} in the bean cache: {}", bean_name, version, e);
+            }
+        }
         
         Ok(bean_dir)
     }
@@ -304,9 +512,14 @@ brew get_{}_info() {{
                 })?;
             }
             
-            // Remove from manifest
+            // Remove from manifest and lockfile
             self.roastery_manifest.required_bean_dependencies.remove(bean_name);
             Self::save_roastery_manifest(&self.roastery_manifest)?;
+            let lock_spill = |message: String| CoffeeSpillReport::new_brewing_disaster(SpillType::OverExtraction, 0, 0, &message);
+            let mut lock = RoasteryLock::load(Path::new(ROASTERY_LOCK_FILE)).map_err(lock_spill)?;
+            if lock.locked_beans.remove(bean_name).is_some() {
+                lock.save(Path::new(ROASTERY_LOCK_FILE)).map_err(lock_spill)?;
+            }
             
             println!("☕ Coffee bean '{}' has been disposed of properly!", bean_name);
             Ok(())
@@ -333,6 +546,10 @@ brew get_{}_info() {{
     
     /// Save roastery manifest to file
     fn save_roastery_manifest(manifest: &RoasteryManifest) -> Result<(), CoffeeSpillReport> {
+        Self::save_roastery_manifest_to(manifest, "roastery.json")
+    }
+
+    fn save_roastery_manifest_to(manifest: &RoasteryManifest, manifest_path: &str) -> Result<(), CoffeeSpillReport> {
         let manifest_json = serde_json::to_string_pretty(manifest).map_err(|e| {
             CoffeeSpillReport::new_brewing_disaster(
                 SpillType::OverExtraction,
@@ -341,7 +558,7 @@ brew get_{}_info() {{
             )
         })?;
         
-        CoffeeFileBrewery::pour_recipe_to_file("roastery.json", &manifest_json)
+        CoffeeFileBrewery::pour_recipe_to_file(manifest_path, &manifest_json)
     }
     
     /// Create default manifest
@@ -358,60 +575,81 @@ brew get_{}_info() {{
             coffee_keywords: vec!["coffee".to_string()],
             roastery_homepage: None,
             bean_repository: None,
+            native_plugin: None,
+            entry_recipe: None,
         }
     }
     
     /// Default brewing scripts
     fn default_brewing_scripts() -> HashMap<String, String> {
         let mut scripts = HashMap::new();
-        scripts.insert("start".to_string(), "brew main.brewco".to_string());
-        scripts.insert("test".to_string(), "brew test/*.brewco".to_string());
-        scripts.insert("build".to_string(), "brew --optimize main.brewco".to_string());
+        scripts.insert("start".to_string(), "brew .".to_string());
+        scripts.insert("test".to_string(), "brew test tests".to_string());
+        scripts.insert("check".to_string(), "brew check main.brewco tests".to_string());
         scripts
     }
 }
 
-/// Native functions for Brewco package management
-pub fn native_install_bean(args: Vec<crate::interpreter::Value>) -> Result<crate::interpreter::Value, crate::interpreter::ControlFlow> {
-    if args.is_empty() {
-        return Err(crate::interpreter::ControlFlow::RuntimeError(
-            "install_bean() expects at least 1 argument (bean name)".to_string()
-        ));
-    }
-    
-    match &args[0] {
-        crate::interpreter::Value::String(bean_name) => {
-            let version = if args.len() > 1 {
-                match &args[1] {
-                    crate::interpreter::Value::String(v) => Some(v.as_str()),
-                    _ => None,
-                }
-            } else {
-                None
-            };
-            
-            // For now, just return success - real implementation would use roastery manager
-            println!("☕ Installing coffee bean '{}'...", bean_name);
-            Ok(crate::interpreter::Value::Boolean(true))
+/// The recipe a roastery brews when none is named
+pub const DEFAULT_ENTRY_RECIPE: &str = "main.brewco";
+
+/// The recipe `brew <roastery dir>` brews: the roastery.json's "entry_recipe", or main.brewco
+pub fn roastery_entry(roastery_dir: &Path) -> Result<PathBuf, String> {
+    let manifest_path = roastery_dir.join("roastery.json");
+    let entry = match std::fs::read_to_string(&manifest_path) {
+        Ok(manifest) => {
+            let manifest: serde_json::Value = serde_json::from_str(&manifest)
+                .map_err(|e| format!("{}: {}", manifest_path.display(), e))?;
+            match manifest.get("entry_recipe") {
+                None | Some(serde_json::Value::Null) => DEFAULT_ENTRY_RECIPE.to_string(),
+                Some(serde_json::Value::String(entry)) => entry.clone(),
+                Some(_) => return Err(format!("{}: \"entry_recipe\" must be a path to a recipe", manifest_path.display())),
+            }
         }
-        _ => Err(crate::interpreter::ControlFlow::RuntimeError(
-            "install_bean() expects a string bean name".to_string()
-        ))
+        Err(_) => DEFAULT_ENTRY_RECIPE.to_string(),
+    };
+    let entry_path = roastery_dir.join(&entry);
+    if !entry_path.is_file() {
+        return Err(format!(
+            "{} has no {} to brew; name the recipe to start from as \"entry_recipe\" in its roastery.json",
+            roastery_dir.display(),
+            entry
+        ));
     }
+    Ok(entry_path)
 }
 
-pub fn native_list_brewed_beans(args: Vec<crate::interpreter::Value>) -> Result<crate::interpreter::Value, crate::interpreter::ControlFlow> {
-    // Mock implementation - return some sample installed beans
-    let mock_beans = vec![
-        "espresso_utils".to_string(),
-        "coffee_math".to_string(),
-        "brewing_helpers".to_string(),
-    ];
-    
-    let values: Vec<crate::interpreter::Value> = mock_beans
-        .into_iter()
-        .map(|b| crate::interpreter::Value::String(b))
-        .collect();
-        
-    Ok(crate::interpreter::Value::Array(values))
-} 
\ No newline at end of file
+/// The brewco.toml a new roastery starts with
+const SAMPLE_ROASTERY_CONFIG: &str = r#"# Defaults for brew, brew check, brew fmt and brew lint inside this roastery
+[brew]
+strict = false
+deny_warnings = false
+"#;
+
+/// A taste test for a new roastery; the library template's tests grind in its main recipe
+fn sample_taste_test(roastery_name: &str, template: &str) -> String {
+    let header = format!("🎀 Taste tests for {} ☕\n🎀 Run them with: brew test\n\n", roastery_name);
+    let tests = if template == "library" {
+        r#"beans shop = grind "main.brewco"
+
+taste_test "a large costs more than a small" {
+    assert_brew(shop.price_of("large") more_caffeine shop.price_of("small"), "a large should cost more")
+}
+
+taste_test "an order adds up" {
+    assert_same_blend(shop.order_total(["small", "large"]), 8)
+}
+"#
+    } else {
+        r#"brew refill(cups) {
+    serve cups * 2
+}
+
+taste_test "a refill doubles the cups" {
+    assert_same_blend(refill(1), 2)
+    assert_same_blend(refill(0), 0)
+}
+"#
+    };
+    header + tests
+}
@@ -0,0 +1,194 @@
+/*
+ * 🔌 Brewco Native Plugins ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @plugin_note: "Every roastery brings its own grinder!" ✨
+ *
+ * Plugins add natives that aren't built into brewco. Every new interpreter
+ * puts the natives of each registered CoffeeNativePlugin in its native
+ * table, so they're called just like the built-in ones (and, like them, are
+ * found before brews with the same name).
+ *
+ * Compiled plugins are dynamic libraries (.so, .dylib or .dll). Rust has no
+ * stable ABI, so a library talks to brewco through four C functions and
+ * passes values as JSON text:
+ *
+ *     u32   brewco_plugin_abi(void);              // must return 1
+ *     char *brewco_plugin_natives(void);          // names, one per line; the plugin keeps it
+ *     char *brewco_plugin_call(char *name, char *args_json);
+ *     void  brewco_plugin_free(char *result);     // frees what brewco_plugin_call returned
+ *
+ * `args_json` is a JSON array of the arguments, and the call answers
+ * {"ok": value} or {"spill": "message"}. Values go over the way
+ * coffee_serde.rs writes them: numbers, strings, booleans, null, cups and
+ * menus as themselves, sets and chains as arrays, bytes as an array of
+ * numbers and big brews as strings. Brews can't be sent. sample_plugin/ is a plugin written in Rust to start from.
+ *
+ * A package ships one by naming it in its roastery.json as
+ * "native_plugin": "target/release/libname.so", relative to the package,
+ * and every package under coffee_beans/ that does is loaded. A roastery can
+ * also list libraries of its own in brewco.toml as [brew] plugins. Plugin
+ * natives run as brewco itself, with no --allow-fs/-exec/-net checks, so
+ * only install plugins you trust.
+ */
+
+// src/coffee_plugins.rs
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{c_char, CStr, CString};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use libloading::Library;
+
+use crate::interpreter::{ControlFlow, Value};
+
+/// The plugin interface version this brewco speaks
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// A set of natives that isn't built in
+pub trait CoffeeNativePlugin {
+    /// What spills and listings call the plugin
+    fn plugin_name(&self) -> &str;
+
+    /// Every native the plugin answers to
+    fn native_names(&self) -> &[String];
+
+    /// Brew one of `native_names()`
+    fn call_native(&self, name: &str, args: Vec<Value>) -> Result<Value, ControlFlow>;
+}
+
+thread_local! {
+    /// Plugins every new interpreter starts with
+    static REGISTERED_PLUGINS: RefCell<Vec<Rc<dyn CoffeeNativePlugin>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Give every interpreter made from now on `plugin`'s natives
+pub fn register_plugin(plugin: Rc<dyn CoffeeNativePlugin>) {
+    REGISTERED_PLUGINS.with(|plugins| plugins.borrow_mut().push(plugin));
+}
+
+/// The plugins registered so far, for a new interpreter
+pub fn registered_plugins() -> Vec<Rc<dyn CoffeeNativePlugin>> {
+    REGISTERED_PLUGINS.with(|plugins| plugins.borrow().clone())
+}
+
+type AbiFn = unsafe extern "C" fn() -> u32;
+type NativesFn = unsafe extern "C" fn() -> *const c_char;
+type CallFn = unsafe extern "C" fn(*const c_char, *const c_char) -> *mut c_char;
+type FreeFn = unsafe extern "C" fn(*mut c_char);
+
+/// A plugin loaded from a dynamic library
+pub struct DylibCoffeePlugin {
+    name: String,
+    natives: Vec<String>,
+    call: CallFn,
+    free: FreeFn,
+    // The functions above point into the library, so it's kept loaded for as long as they're used
+    _library: Library,
+}
+
+impl DylibCoffeePlugin {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let spill = |what: String| format!("plugin {}: {}", path.display(), what);
+        // Loading a library runs its initializers; plugins are code the roastery chose to trust
+        let library = unsafe { Library::new(path) }.map_err(|e| spill(format!("couldn't load it: {}", e)))?;
+        let (abi, natives, call, free) = unsafe {
+            let symbol = |name: &str| format!("it doesn't export {}(), so it isn't a brewco plugin", name);
+            let abi = *library.get::<AbiFn>(b"brewco_plugin_abi\0").map_err(|_| spill(symbol("brewco_plugin_abi")))?;
+            let natives = *library.get::<NativesFn>(b"brewco_plugin_natives\0").map_err(|_| spill(symbol("brewco_plugin_natives")))?;
+            let call = *library.get::<CallFn>(b"brewco_plugin_call\0").map_err(|_| spill(symbol("brewco_plugin_call")))?;
+            let free = *library.get::<FreeFn>(b"brewco_plugin_free\0").map_err(|_| spill(symbol("brewco_plugin_free")))?;
+            (abi, natives, call, free)
+        };
+        let version = unsafe { abi() };
+        if version != PLUGIN_ABI_VERSION {
+            return Err(spill(format!("it speaks plugin interface {}, but this brewco speaks {}", version, PLUGIN_ABI_VERSION)));
+        }
+        let listing = unsafe { natives() };
+        if listing.is_null() {
+            return Err(spill("brewco_plugin_natives() returned nothing".to_string()));
+        }
+        let natives = unsafe { CStr::from_ptr(listing) }
+            .to_string_lossy()
+            .lines()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+        let name = path.file_stem().map_or_else(|| path.display().to_string(), |stem| stem.to_string_lossy().trim_start_matches("lib").to_string());
+        Ok(DylibCoffeePlugin { name, natives, call, free, _library: library })
+    }
+}
+
+impl CoffeeNativePlugin for DylibCoffeePlugin {
+    fn plugin_name(&self) -> &str {
+        &self.name
+    }
+
+    fn native_names(&self) -> &[String] {
+        &self.natives
+    }
+
+    fn call_native(&self, name: &str, args: Vec<Value>) -> Result<Value, ControlFlow> {
+        let spill = |what: String| ControlFlow::RuntimeError(format!("{}() from plugin {}: {}", name, self.name, what));
+        let args = args.iter().map(serde_json::Value::try_from).collect::<Result<Vec<_>, _>>().map_err(spill)?;
+        let args = CString::new(serde_json::Value::Array(args).to_string()).map_err(|e| spill(e.to_string()))?;
+        let native = CString::new(name).map_err(|e| spill(e.to_string()))?;
+        let answer = unsafe {
+            let result = (self.call)(native.as_ptr(), args.as_ptr());
+            if result.is_null() {
+                return Err(spill("the plugin answered nothing".to_string()));
+            }
+            let answer = CStr::from_ptr(result).to_string_lossy().into_owned();
+            (self.free)(result);
+            answer
+        };
+        let answer: serde_json::Value = serde_json::from_str(&answer).map_err(|e| spill(format!("the plugin answered something that isn't JSON: {}", e)))?;
+        match answer {
+            serde_json::Value::Object(mut answer) => match (answer.remove("ok"), answer.remove("spill")) {
+                (Some(value), None) => Ok(Value::from(value)),
+                (None, Some(serde_json::Value::String(message))) => Err(ControlFlow::RuntimeError(message)),
+                _ => Err(spill("the plugin's answer needs exactly one of \"ok\" or \"spill\" (a string)".to_string())),
+            },
+            _ => Err(spill("the plugin's answer needs to be {\"ok\": ..} or {\"spill\": \"..\"}".to_string())),
+        }
+    }
+}
+
+/// The libraries installed packages name as their "native_plugin", in package order
+fn package_plugins(roastery_root: &Path) -> Vec<PathBuf> {
+    let Ok(packages) = std::fs::read_dir(roastery_root.join("coffee_beans")) else {
+        return Vec::new();
+    };
+    let mut packages: Vec<PathBuf> = packages.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()).collect();
+    packages.sort();
+    packages
+        .into_iter()
+        .filter_map(|package| {
+            let manifest = std::fs::read_to_string(package.join("roastery.json")).ok()?;
+            let manifest: serde_json::Value = serde_json::from_str(&manifest).ok()?;
+            let library = manifest.get("native_plugin")?.as_str()?;
+            Some(package.join(library))
+        })
+        .collect()
+}
+
+/// Load and register the plugins a roastery uses: its installed packages' and then `extra`
+pub fn load_roastery_plugins(roastery_root: &Path, extra: &[PathBuf]) -> Result<(), String> {
+    let mut taken: HashMap<String, String> = HashMap::new();
+    for path in package_plugins(roastery_root).iter().chain(extra) {
+        let plugin = DylibCoffeePlugin::load(path)?;
+        for native in plugin.native_names() {
+            if crate::interpreter::is_builtin_native(native) {
+                return Err(format!("plugin {}: {}() is already a built-in native", plugin.plugin_name(), native));
+            }
+            if let Some(other) = taken.insert(native.clone(), plugin.plugin_name().to_string()) {
+                return Err(format!("plugins {} and {} both brew {}()", other, plugin.plugin_name(), native));
+            }
+        }
+        register_plugin(Rc::new(plugin));
+    }
+    Ok(())
+}
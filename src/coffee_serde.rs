@@ -0,0 +1,187 @@
+/*
+ * 📦 Brewco Serde Pour ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @shipping_note: "Every cup travels well, as long as it isn't a brew!" ✨
+ *
+ * Values can go out through any serde format and come back in from one, so
+ * a program embedding brewco can hand structured data to a recipe and read
+ * what it served without matching on every variant by hand. With JSON there
+ * are conversions both ways: `Value::from(json)` and
+ * `serde_json::Value::try_from(&value)`.
+ *
+ * Going out, numbers with no fraction are written as integers, cups, sets
+ * and chains become sequences, bytes go as bytes (an array of numbers in
+ * JSON), big brews as their exact digits in a string, and menus and beans
+ * as maps of their fields in key order. Brews, blends, sequences and beans'
+ * declarations can't be written, and say so. Coming back, sequences are
+ * cups and maps are menus.
+ */
+
+// src/coffee_serde.rs
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, Serialize, SerializeMap, SerializeSeq, Serializer};
+
+use crate::interpreter::Value;
+
+/// The largest whole number an f64 holds exactly, so it can go out as an integer
+const EXACT_WHOLE: f64 = 9_007_199_254_740_992.0;
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Null => serializer.serialize_unit(),
+            Value::Boolean(b) => serializer.serialize_bool(*b),
+            Value::Number(n) if n.fract() == 0.0 && n.abs() <= EXACT_WHOLE => serializer.serialize_i64(*n as i64),
+            Value::Number(n) => serializer.serialize_f64(*n),
+            Value::String(text) => serializer.serialize_str(text),
+            Value::BigBrew(big) => serializer.serialize_str(&big.to_string()),
+            Value::Bytes(bytes) => serializer.serialize_bytes(bytes),
+            Value::Array(cup) => serialize_items(serializer, cup.iter()),
+            Value::Set(set) => serialize_items(serializer, set.borrow().items()),
+            Value::Chain(chain) => serialize_items(serializer, chain.borrow().iter()),
+            Value::Object { fields, .. } => {
+                let sorted: BTreeMap<&String, &Value> = fields.iter().collect();
+                let mut map = serializer.serialize_map(Some(sorted.len()))?;
+                for (key, field) in sorted {
+                    map.serialize_entry(key, field)?;
+                }
+                map.end()
+            }
+            other => Err(ser::Error::custom(format!("a {} can't be written out, only the data it works on", other.type_name()))),
+        }
+    }
+}
+
+fn serialize_items<'a, S: Serializer>(serializer: S, items: impl Iterator<Item = &'a Value>) -> Result<S::Ok, S::Error> {
+    let items: Vec<&Value> = items.collect();
+    let mut seq = serializer.serialize_seq(Some(items.len()))?;
+    for item in items {
+        seq.serialize_element(item)?;
+    }
+    seq.end()
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a number, string, boolean, null, sequence or map")
+    }
+
+    fn visit_bool<E: de::Error>(self, b: bool) -> Result<Value, E> {
+        Ok(Value::Boolean(b))
+    }
+
+    fn visit_i64<E: de::Error>(self, n: i64) -> Result<Value, E> {
+        Ok(Value::Number(n as f64))
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<Value, E> {
+        Ok(Value::Number(n as f64))
+    }
+
+    fn visit_f64<E: de::Error>(self, n: f64) -> Result<Value, E> {
+        Ok(Value::Number(n))
+    }
+
+    fn visit_str<E: de::Error>(self, text: &str) -> Result<Value, E> {
+        Ok(Value::String(text.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, text: String) -> Result<Value, E> {
+        Ok(Value::String(text))
+    }
+
+    fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Value, E> {
+        Ok(Value::bytes(bytes.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, bytes: Vec<u8>) -> Result<Value, E> {
+        Ok(Value::bytes(bytes))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Value::array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut fields = HashMap::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some((key, field)) = map.next_entry::<String, Value>()? {
+            fields.insert(key, field);
+        }
+        Ok(Value::object("", fields))
+    }
+}
+
+impl From<serde_json::Value> for Value {
+    fn from(json: serde_json::Value) -> Value {
+        match json {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Boolean(b),
+            serde_json::Value::Number(n) => Value::Number(n.as_f64().unwrap_or(f64::NAN)),
+            serde_json::Value::String(text) => Value::String(text),
+            serde_json::Value::Array(items) => Value::array(items.into_iter().map(Value::from).collect()),
+            serde_json::Value::Object(menu) => Value::object("", menu.into_iter().map(|(key, field)| (key, Value::from(field))).collect()),
+        }
+    }
+}
+
+/// JSON has no NaN or infinity, so those numbers are refused rather than turned into null
+impl TryFrom<&Value> for serde_json::Value {
+    type Error = String;
+
+    fn try_from(value: &Value) -> Result<serde_json::Value, String> {
+        if let Some(n) = first_unwritable_number(value) {
+            return Err(format!("{} can't be written as JSON", n));
+        }
+        serde_json::to_value(value).map_err(|e| e.to_string())
+    }
+}
+
+impl TryFrom<Value> for serde_json::Value {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<serde_json::Value, String> {
+        serde_json::Value::try_from(&value)
+    }
+}
+
+fn first_unwritable_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) if !n.is_finite() => Some(*n),
+        Value::Array(cup) => cup.iter().find_map(first_unwritable_number),
+        Value::Set(set) => set.borrow().items().find_map(first_unwritable_number),
+        Value::Chain(chain) => chain.borrow().iter().find_map(first_unwritable_number),
+        Value::Object { fields, .. } => fields.values().find_map(first_unwritable_number),
+        _ => None,
+    }
+}
@@ -0,0 +1,669 @@
+/*
+ * 🏪 Brewco Coffee Shop REPL ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @counter_note: "Every order remembered, even the ones from yesterday!" ✨
+ *
+ * `brew repl` reads orders through a rustyline line editor, so arrow keys,
+ * Ctrl-R search and the rest of readline work as expected. Orders that leave
+ * a `{`, `(` or `[` open keep prompting with `... >` until they close, and
+ * every order is kept in `~/.brewco_history` for the next visit. Tab
+ * completes keywords, native brews and whatever is in the pantry, and the
+ * order is colored as it's typed, with the partner of the bracket under the
+ * cursor lit up. `:set highlight off` (or NO_COLOR, or a stdout that isn't a
+ * terminal) turns the colors off. `:learn` (or `brew learn`) puts a tutor
+ * at the counter; see brew_learn.rs.
+ */
+
+// src/coffee_shop_repl.rs
+
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::{CmdKind, Highlighter};
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Config, Context, Editor, Helper};
+
+use crate::ast::Statement;
+use crate::barista_language_server::CoffeeShopSettings;
+use crate::brew_learn::Tutor;
+use crate::coffee_terminal;
+use crate::espresso_errors::{self, CoffeeSpillReport};
+use crate::interpreter::{self, ControlFlow, Interpreter, Value};
+use crate::lexer::{CoffeeTokenStream, Token, COFFEE_WORDS};
+use crate::native;
+use crate::parser;
+use crate::type_checker::{Type, TypeChecker};
+
+const HISTORY_FILE: &str = ".brewco_history";
+const HISTORY_SIZE: usize = 1000;
+/// Where the REPL keeps the value of the last bare expression
+const LAST_SERVED: &str = "_";
+/// How many orders `brewing_history` shows
+const HISTORY_SHOWN: usize = 20;
+
+const KEYWORD_COLOR: &str = "\x1b[1;35m";
+const STRING_COLOR: &str = "\x1b[32m";
+const NUMBER_COLOR: &str = "\x1b[33m";
+const COMMENT_COLOR: &str = "\x1b[2m";
+const BRACKET_MATCH_COLOR: &str = "\x1b[1;4;36m";
+const RESET_COLOR: &str = "\x1b[0m";
+
+/// Completion and live highlighting for the counter, shaped by the shop's settings
+struct CoffeeShopHelper {
+    /// Variables, beans and recipes declared so far, refreshed after every order
+    pantry: Vec<String>,
+    /// Every native the counter's interpreter knows
+    natives: Vec<String>,
+    settings: CoffeeShopSettings,
+    /// Whether the next highlight may light up a bracket; off for the final redraw
+    cursor_tracking: Cell<bool>,
+}
+
+impl Completer for CoffeeShopHelper {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos]
+            .char_indices()
+            .rev()
+            .take_while(|&(_, c)| c.is_alphanumeric() || c == '_')
+            .last()
+            .map_or(pos, |(index, _)| index);
+        let partial = &line[start..pos];
+        if partial.is_empty() || !self.settings.auto_complete_coffee_terms {
+            return Ok((pos, Vec::new()));
+        }
+        let mut candidates: Vec<String> = COFFEE_WORDS.iter()
+            .chain(native::BREW_CONSTANTS.iter().map(|(name, _)| name))
+            .map(|name| name.to_string())
+            .chain(self.natives.iter().cloned())
+            .chain(self.pantry.iter().cloned())
+            .filter(|name| name.starts_with(partial))
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for CoffeeShopHelper {
+    type Hint = String;
+}
+
+impl Highlighter for CoffeeShopHelper {
+    fn highlight<'l>(&self, line: &'l str, pos: usize) -> Cow<'l, str> {
+        let highlight_syntax = self.settings.highlight_coffee_syntax;
+        let match_brackets = self.settings.match_coffee_brackets && self.cursor_tracking.get();
+        if !highlight_syntax && !match_brackets {
+            return Cow::Borrowed(line);
+        }
+
+        // Byte ranges of the tokens worth coloring, from the lexer's 1-based columns
+        let offsets = char_offsets(line);
+        let byte_at = |column: usize| offsets.get(column.saturating_sub(1)).copied().unwrap_or(line.len());
+        let mut painted: Vec<(usize, usize, &str)> = Vec::new();
+        let mut open_brackets: Vec<usize> = Vec::new();
+        let mut bracket_pairs: Vec<(usize, usize)> = Vec::new();
+        for spanned in CoffeeTokenStream::with_comments(line) {
+            let (start, end) = (byte_at(spanned.column), byte_at(spanned.end_column));
+            let color = match &spanned.token {
+                Token::LBrace | Token::LParen | Token::LBracket => {
+                    open_brackets.push(start);
+                    None
+                }
+                Token::RBrace | Token::RParen | Token::RBracket => {
+                    if let Some(open) = open_brackets.pop() {
+                        bracket_pairs.push((open, start));
+                    }
+                    None
+                }
+                Token::String(_) => Some(STRING_COLOR),
+                Token::Number(_) => Some(NUMBER_COLOR),
+                Token::Comment(_) => Some(COMMENT_COLOR),
+                _ if COFFEE_WORDS.contains(&&line[start..end]) => Some(KEYWORD_COLOR),
+                _ => None,
+            };
+            if let (true, Some(color)) = (highlight_syntax, color) {
+                painted.push((start, end, color));
+            }
+        }
+        if match_brackets {
+            // The bracket under the cursor, or just before it, lights up its partner
+            let before = line[..pos].chars().next_back().map_or(pos, |c| pos - c.len_utf8());
+            let partner = bracket_pairs.iter().find_map(|&(open, close)| {
+                if open == pos || open == before {
+                    Some(close)
+                } else if close == pos || close == before {
+                    Some(open)
+                } else {
+                    None
+                }
+            });
+            if let Some(partner) = partner {
+                painted.push((partner, partner + 1, BRACKET_MATCH_COLOR));
+                painted.sort_by_key(|&(start, ..)| start);
+            }
+        }
+        if painted.is_empty() {
+            return Cow::Borrowed(line);
+        }
+
+        let mut colored = String::with_capacity(line.len() + painted.len() * 12);
+        let mut written = 0;
+        for (start, end, color) in painted {
+            if start < written {
+                continue;
+            }
+            colored.push_str(&line[written..start]);
+            colored.push_str(color);
+            colored.push_str(&line[start..end]);
+            colored.push_str(RESET_COLOR);
+            written = end;
+        }
+        colored.push_str(&line[written..]);
+        Cow::Owned(colored)
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize, kind: CmdKind) -> bool {
+        self.cursor_tracking.set(kind != CmdKind::ForcedRefresh);
+        self.settings.highlight_coffee_syntax || self.settings.match_coffee_brackets
+    }
+}
+
+impl Validator for CoffeeShopHelper {}
+
+impl Helper for CoffeeShopHelper {}
+
+pub fn start_repl() {
+    open_counter(None);
+}
+
+/// `brew learn [lesson]`: the REPL with a tutor, starting at `lesson` or the first one not passed yet
+pub fn start_learning(lesson: Option<&str>) {
+    open_counter(Some(lesson.unwrap_or("")));
+}
+
+fn open_counter(lesson: Option<&str>) {
+    println!("☕ Welcome to the Interactive Brewco Coffee Shop! ☕");
+    println!("🏪 Where every line of code is brewed to perfection!");
+    println!("Type 'exit', 'quit', or 'enough_caffeine' to leave");
+    println!("Type 'help' or 'barista_help' for brewing commands");
+    println!("================================================");
+
+    let config = Config::builder().max_history_size(HISTORY_SIZE).map(|builder| builder.build()).unwrap_or_default();
+    let mut barista: Editor<CoffeeShopHelper, DefaultHistory> = match Editor::with_config(config) {
+        Ok(editor) => editor,
+        Err(e) => {
+            println!("[ERROR] Could not open the coffee shop counter: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let mut settings = CoffeeShopSettings::default();
+    if !coffee_terminal::colors_welcome() {
+        settings.highlight_coffee_syntax = false;
+        settings.match_coffee_brackets = false;
+    }
+    barista.set_helper(Some(CoffeeShopHelper { pantry: Vec::new(), natives: Vec::new(), settings, cursor_tracking: Cell::new(false) }));
+    let history_path = brewing_history_path();
+    if let Some(path) = &history_path {
+        // A missing history file just means this is the first visit
+        let _ = barista.load_history(path);
+    }
+
+    let mut coffee_interpreter = Interpreter::new();
+    if let Some(helper) = barista.helper_mut() {
+        helper.natives = native_names(&coffee_interpreter);
+    }
+    let mut brewing_session = 1;
+    // Lines of a bean/brew/taste block that is still being typed
+    let mut pending_order = String::new();
+    // Orders that brewed without spilling, in order, for :save
+    let mut served_orders: Vec<String> = Vec::new();
+    let mut tutor: Option<Tutor> = lesson.and_then(|lesson| start_lesson(lesson, served_orders.len()));
+
+    loop {
+        let prompt = match &tutor {
+            _ if !pending_order.is_empty() => "... > ".to_string(),
+            Some(tutor) => format!("{} #{} > ", tutor.prompt_tag(), brewing_session),
+            None => format!("☕ Coffee Shop #{} > ", brewing_session),
+        };
+        let mut coffee_input = match barista.readline(&prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => {
+                if !pending_order.is_empty() {
+                    println!("☕ Order cancelled, that block was left unbrewed.");
+                    pending_order.clear();
+                }
+                continue;
+            }
+            Err(ReadlineError::Eof) => {
+                if !pending_order.is_empty() {
+                    println!("☕ The input ran dry before that block was closed, so it was left unbrewed.");
+                }
+                break;
+            }
+            Err(brewing_error) => {
+                let input_spill = CoffeeSpillReport::new_brewing_disaster(
+                    espresso_errors::SpillType::UnderExtraction,
+                    brewing_session, 1,
+                    &format!("Failed to read coffee input: {}", brewing_error)
+                );
+                println!("{}", input_spill);
+                break;
+            }
+        };
+
+        if !pending_order.is_empty() || unclosed_pours(coffee_input.trim()) > 0 {
+            pending_order.push_str(&coffee_input);
+            pending_order.push('\n');
+            if unclosed_pours(&pending_order) > 0 {
+                continue;
+            }
+            coffee_input = std::mem::take(&mut pending_order);
+        }
+        let brewing_command = coffee_input.trim();
+        if !brewing_command.is_empty() {
+            let _ = barista.add_history_entry(brewing_command);
+        }
+
+        // Special REPL commands with coffee flair
+        match brewing_command {
+            "exit" | "quit" | "enough_caffeine" => {
+                println!("☕ Thanks for visiting our Coffee Shop!");
+                println!("🌟 May your code be bug-free and your coffee strong!");
+                break;
+            },
+            "help" | "barista_help" => {
+                println!("☕ Coffee Shop Commands:");
+                println!("  beans var = value     Declare a new coffee bean variable");
+                println!("  pourout expression    Display the aroma of an expression");
+                println!("  expression           Echo its value, kept in _ for the next order");
+                println!("  clear_counter         Clear the coffee shop counter");
+                println!("  show_pantry          Show all declared coffee beans");
+                println!("  :type expression     Show the type the checker infers for an expression");
+                println!("  :ast code            Show the parsed syntax tree of some code");
+                println!("  :save file.brewco    Write every order that brewed cleanly to a recipe file");
+                println!("  :load file.brewco    Brew a recipe file into this session");
+                println!("  :natives [group|name]");
+                println!("                       List native brews by group, or the parameters of the matching ones");
+                println!("  :set highlight|brackets|completion on|off");
+                println!("                       Color orders as you type, light up matching brackets, or Tab-complete");
+                println!("  :learn [lesson]      Start the tutorial, at a lesson by number or name, or where you left off");
+                println!("  :check :hint :skip :lessons");
+                println!("                       Check your answer to the lesson, get a hint, move on, or list the lessons");
+                println!("  brewing_history      Show recent brewing commands (↑/↓ and Ctrl-R recall them)");
+                println!("  (Tab)                Complete keywords, native brews and pantry names");
+                println!("  (multi-line blocks)  Leave a {{ ( or [ open and keep typing at '... >'");
+                continue;
+            },
+            "clear_counter" => {
+                coffee_interpreter = Interpreter::new();
+                served_orders.clear();
+                if let Some(tutor) = tutor.as_mut() {
+                    tutor.orders_from = 0;
+                }
+                if let Some(helper) = barista.helper_mut() {
+                    helper.pantry.clear();
+                    helper.natives = native_names(&coffee_interpreter);
+                }
+                println!("☕ Coffee shop counter cleared! Fresh start brewing...");
+                continue;
+            },
+            "show_pantry" => {
+                show_pantry(&coffee_interpreter);
+                continue;
+            },
+            "brewing_history" => {
+                let orders: Vec<&String> = barista.history().iter().collect();
+                println!("☕ Recent Brewing History:");
+                for (number, order) in orders.iter().enumerate().skip(orders.len().saturating_sub(HISTORY_SHOWN)) {
+                    println!("  {:>4}  {}", number + 1, order.replace('\n', "\n        "));
+                }
+                continue;
+            },
+            counter_command if counter_command.starts_with(':') => {
+                let (name, argument) = counter_command.split_once(char::is_whitespace).unwrap_or((counter_command, ""));
+                let argument = argument.trim();
+                match name {
+                    ":type" | ":ast" => inspect_order(name, argument, &coffee_interpreter),
+                    ":learn" => {
+                        if let Some(started) = start_lesson(argument, served_orders.len()) {
+                            tutor = Some(started);
+                        }
+                    }
+                    ":check" | ":hint" | ":skip" | ":lessons" => match tutor.as_mut() {
+                        Some(tutor) if name == ":check" => tutor.check(&served_orders),
+                        Some(tutor) if name == ":hint" => tutor.hint(),
+                        Some(tutor) if name == ":skip" => tutor.skip(served_orders.len()),
+                        Some(tutor) => tutor.list(),
+                        None => println!("☕ No lesson going yet; :learn starts the tutorial."),
+                    },
+                    ":save" => save_session(argument, &served_orders),
+                    ":natives" => show_natives(argument, &coffee_interpreter),
+                    ":set" => {
+                        if let Some(helper) = barista.helper_mut() {
+                            change_setting(argument, &mut helper.settings);
+                        }
+                    }
+                    ":load" => {
+                        load_session(argument, &mut coffee_interpreter, &mut served_orders);
+                        if let Some(helper) = barista.helper_mut() {
+                            helper.pantry = coffee_interpreter.binding_names();
+                        }
+                    }
+                    _ => println!("☕ Unknown counter command '{}'. Try :type, :ast, :save, :load, :natives, :set or :learn, or 'help'.", name),
+                }
+                continue;
+            },
+            "" => continue, // Empty input
+            _ => {} // Process as Brewco code
+        }
+
+        // Tokenize and parse the coffee input
+        let brewing_result = parser::parse_source(brewing_command);
+
+        // Handle any coffee spills (errors)
+        if !brewing_result.errors.is_empty() {
+            for (brewing_error, &(line, column)) in brewing_result.errors.iter().zip(&brewing_result.error_positions) {
+                let mut spill_report = CoffeeSpillReport::new_brewing_disaster(
+                    espresso_errors::SpillType::IncompleteRecipe,
+                    line, column, // Within this REPL entry
+                    brewing_error
+                );
+                spill_report.frame_in_source("<order>", brewing_command);
+                println!("{}", spill_report);
+            }
+        } else {
+            // Execute the brewing instructions, echoing a bare expression's value into `_`
+            interpreter::clear_interrupt();
+            match coffee_interpreter.run_serving_last(&brewing_result.statements) {
+                Ok(Some(Value::Null)) | Ok(None) => served_orders.push(brewing_command.to_string()),
+                Ok(Some(value)) => {
+                    println!("{:?}", value);
+                    coffee_interpreter.set_global(LAST_SERVED, value);
+                    served_orders.push(brewing_command.to_string());
+                }
+                Err(ControlFlow::RuntimeError(msg)) => println!("☕ Spilled: {}", msg),
+                Err(ControlFlow::FinishService(code)) => {
+                    println!("☕ finish_service({}) closed the shop. Thanks for visiting!", code);
+                    break;
+                }
+                Err(_) => println!("☕ Only a brew can serve, break or continue; that order was left unfinished."),
+            }
+            if let Some(helper) = barista.helper_mut() {
+                helper.pantry = coffee_interpreter.binding_names();
+            }
+        }
+
+        brewing_session += 1;
+    }
+
+    if let Some(path) = &history_path {
+        if let Err(e) = barista.save_history(path) {
+            println!("⚠️  Could not save brewing history to {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Put a tutor at the counter for `lesson` (empty: where the learner left off)
+fn start_lesson(lesson: &str, orders_served: usize) -> Option<Tutor> {
+    match Tutor::start(lesson, orders_served) {
+        Ok(tutor) => Some(tutor),
+        Err(msg) => {
+            println!("☕ {}", msg);
+            None
+        }
+    }
+}
+
+/// `:type expr` and `:ast code`, for peeking at what the checker and parser make of an order
+fn inspect_order(name: &str, source: &str, coffee_interpreter: &Interpreter) {
+    if source.is_empty() {
+        println!("☕ {} needs something to look at, like {} 2 add 3", name, name);
+        return;
+    }
+
+    let parsed = parser::parse_source(source);
+    if !parsed.errors.is_empty() {
+        for (error, (line, column)) in parsed.errors.iter().zip(&parsed.error_positions) {
+            println!("{}", CoffeeSpillReport::new_brewing_disaster(espresso_errors::SpillType::IncompleteRecipe, *line, *column, error));
+        }
+        return;
+    }
+    let statements: Vec<&Statement> = parsed.statements.iter().filter(|st| !matches!(st, Statement::SourceMark { .. })).collect();
+
+    if name == ":ast" {
+        for statement in statements {
+            println!("{:#?}", statement);
+        }
+        return;
+    }
+    let expr = match statements.as_slice() {
+        [Statement::ExprStmt(expr)] => expr,
+        _ => {
+            println!("☕ :type takes a single expression, like :type froth_level add 1");
+            return;
+        }
+    };
+    let mut checker = TypeChecker::new().with_strict_brew(interpreter::strict_brew());
+    for (name, value) in coffee_interpreter.list_bindings() {
+        checker.assume(&name, pantry_type(&value));
+    }
+    match checker.infer_type(expr) {
+        Ok(inferred) => println!("{}", inferred),
+        Err(errors) => {
+            for error in errors {
+                println!("☕ Type spill: {}", error);
+            }
+        }
+    }
+}
+
+/// The checker's view of a value that's already in the pantry
+fn pantry_type(value: &Value) -> Type {
+    match value {
+        Value::Number(_) => Type::Number,
+        Value::String(_) => Type::String,
+        Value::Boolean(_) => Type::Boolean,
+        Value::Array(_) => Type::Array(Box::new(Type::Any)),
+        Value::Object { .. } => Type::Object(HashMap::new()),
+        Value::Function { params, .. } | Value::BoundMethod { params, .. } => Type::Function {
+            param_types: vec![Type::Any; params.len()],
+            return_type: Box::new(Type::Any),
+        },
+        Value::Null => Type::Null,
+        Value::Bean(_) | Value::Bytes(_) | Value::Set(_) | Value::Chain(_) | Value::Blend(_)
+        | Value::Sequence(_) | Value::BigBrew(_) => Type::Any,
+    }
+}
+
+/// `:set name on|off` for the counter's CoffeeShopSettings
+fn change_setting(argument: &str, settings: &mut CoffeeShopSettings) {
+    let mut words = argument.split_whitespace();
+    let (name, switch) = (words.next().unwrap_or(""), words.next().unwrap_or(""));
+    let turned_on = match switch {
+        "on" => true,
+        "off" => false,
+        _ => {
+            println!("☕ Try :set highlight off, :set brackets on or :set completion off");
+            return;
+        }
+    };
+    let setting = match name {
+        "highlight" => &mut settings.highlight_coffee_syntax,
+        "brackets" => &mut settings.match_coffee_brackets,
+        "completion" => &mut settings.auto_complete_coffee_terms,
+        _ => {
+            println!("☕ Unknown setting '{}'. The counter knows highlight, brackets and completion.", name);
+            return;
+        }
+    };
+    *setting = turned_on;
+    println!("☕ {} is {}", name, switch);
+}
+
+/// Byte offset of every char in a line, so lexer columns can index into it
+fn char_offsets(line: &str) -> Vec<usize> {
+    line.char_indices().map(|(offset, _)| offset).chain(std::iter::once(line.len())).collect()
+}
+
+/// `:save file`: every order that brewed without spilling, one after another
+fn save_session(path: &str, served_orders: &[String]) {
+    if path.is_empty() {
+        println!("☕ :save needs a file name, like :save session.brewco");
+        return;
+    }
+    let mut recipe = served_orders.join("\n");
+    recipe.push('\n');
+    match std::fs::write(path, recipe) {
+        Ok(()) => println!("☕ Saved {} order(s) to {}", served_orders.len(), path),
+        Err(e) => println!("[ERROR] Could not write {}: {}", path, e),
+    }
+}
+
+/// `:load file`: brew a whole recipe into the session, keeping it for :save if it brews cleanly
+fn load_session(path: &str, coffee_interpreter: &mut Interpreter, served_orders: &mut Vec<String>) {
+    if path.is_empty() {
+        println!("☕ :load needs a file name, like :load session.brewco");
+        return;
+    }
+    let code = match std::fs::read_to_string(path) {
+        Ok(code) => code,
+        Err(_) => {
+            println!("[ERROR] Could not read file: {}", path);
+            return;
+        }
+    };
+    let parsed = parser::parse_source(&code);
+    if !parsed.errors.is_empty() {
+        for (error, (line, column)) in parsed.errors.iter().zip(&parsed.error_positions) {
+            println!("  {}:{}:{}: {}", path, line, column, error);
+        }
+        println!("☕ {} didn't parse, so nothing was brewed.", path);
+        return;
+    }
+    interpreter::clear_interrupt();
+    match coffee_interpreter.run_until_spill(&parsed.statements) {
+        Ok(()) => {
+            served_orders.push(code.trim_end().to_string());
+            println!("☕ Loaded {}", path);
+        }
+        Err(ControlFlow::RuntimeError(msg)) => println!("☕ {} spilled partway through: {}", path, msg),
+        Err(_) => println!("☕ {} tried to serve, break or continue outside a brew.", path),
+    }
+}
+
+/// Print every variable with its type and a shortened value, then the beans and recipes
+fn show_pantry(coffee_interpreter: &Interpreter) {
+    let bindings = coffee_interpreter.list_bindings();
+    let beans = coffee_interpreter.list_beans();
+    let recipes = coffee_interpreter.list_recipes();
+    println!("☕ Current Coffee Bean Pantry:");
+    if bindings.is_empty() && beans.is_empty() && recipes.is_empty() {
+        println!("   📦 The pantry is empty!");
+        println!("   💡 Try declaring some: beans my_var pour_in 42");
+        return;
+    }
+
+    if !bindings.is_empty() {
+        println!("   📦 Variables:");
+        let name_width = bindings.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0);
+        let type_width = bindings.iter().map(|(_, value)| value.type_name().chars().count()).max().unwrap_or(0);
+        for (name, value) in &bindings {
+            println!(
+                "      {:<name_width$}  {:<type_width$}  {}",
+                name, value.type_name(), shortened_aroma(value),
+                name_width = name_width, type_width = type_width
+            );
+        }
+    }
+    if !beans.is_empty() {
+        println!("   🫘 Beans:");
+        for bean in beans {
+            let parent = bean.parent.as_ref().map_or(String::new(), |parent| format!(" (from {})", parent));
+            println!("      {}{}: {} field(s), {} method(s)", bean.name, parent, bean.fields.len(), bean.methods.len());
+        }
+    }
+    if !recipes.is_empty() {
+        println!("   📜 Recipes:");
+        for recipe in recipes {
+            let methods: Vec<&str> = recipe.methods.iter().map(|method| method.name.as_str()).collect();
+            println!("      {}: {}", recipe.name, if methods.is_empty() { "(no methods)".to_string() } else { methods.join(", ") });
+        }
+    }
+}
+
+fn native_names(coffee_interpreter: &Interpreter) -> Vec<String> {
+    coffee_interpreter.native_brews().into_iter().map(|(name, _)| name.to_string()).collect()
+}
+
+/// `:natives` lists every group's natives; `:natives text` shows the parameters of
+/// each native in a group called `text` or with `text` in its name
+fn show_natives(argument: &str, coffee_interpreter: &Interpreter) {
+    let natives = coffee_interpreter.native_brews();
+    if argument.is_empty() {
+        let mut groups: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (name, native) in &natives {
+            groups.entry(native.group.as_str()).or_default().push(name);
+        }
+        println!("☕ {} native brews (:natives group shows their parameters):", natives.len());
+        for (group, names) in groups {
+            println!("   {} ({}): {}", group, names.len(), names.join(", "));
+        }
+        return;
+    }
+    let matching: Vec<_> = natives.iter().filter(|(name, native)| native.group == argument || name.contains(argument)).collect();
+    if matching.is_empty() {
+        println!("☕ No native brew or group matches '{}'.", argument);
+        return;
+    }
+    for (name, native) in matching {
+        println!("   {}({})  [{}]", name, native.params, native.group);
+    }
+}
+
+/// A value's debug aroma on one line, cut down to fit beside its name
+fn shortened_aroma(value: &Value) -> String {
+    const SHOWN: usize = 60;
+    let aroma = match value {
+        Value::Function { params, return_type, .. } => {
+            let params: Vec<&str> = params.iter().map(|param| param.name.as_str()).collect();
+            let served = return_type.as_ref().map_or(String::new(), |served| format!(" -> {}", served));
+            format!("brew({}){}", params.join(", "), served)
+        }
+        other => format!("{:?}", other).replace('\n', "\\n"),
+    };
+    if aroma.chars().count() <= SHOWN {
+        return aroma;
+    }
+    let cut: String = aroma.chars().take(SHOWN - 1).collect();
+    format!("{}…", cut)
+}
+
+/// `~/.brewco_history`, or nothing if there's no home directory to keep it in
+fn brewing_history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(HISTORY_FILE))
+}
+
+/// How many `{`, `(` and `[` are still open in a REPL entry. Brackets inside
+/// strings and comments don't count, and stray closers are left for the parser.
+fn unclosed_pours(source: &str) -> usize {
+    let mut open: usize = 0;
+    for spanned in CoffeeTokenStream::new(source) {
+        match spanned.token {
+            Token::LBrace | Token::LParen | Token::LBracket => open += 1,
+            Token::RBrace | Token::RParen | Token::RBracket => open = open.saturating_sub(1),
+            _ => {}
+        }
+    }
+    open
+}
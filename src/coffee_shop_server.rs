@@ -0,0 +1,200 @@
+/*
+ * 🏪 Brewco Coffee Shop Server ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @service_note: "Every order gets a fresh cup, made to order!" ✨
+ *
+ * `open_coffee_shop(port, handler)` serves HTTP and brews `handler` once per
+ * request. Orders are taken one at a time on the brewing thread, because
+ * beans and cups aren't shared across threads — plenty for tiny services,
+ * webhooks and the playground backend.
+ *
+ * The handler gets a CoffeeRequest bean (method, path, query, headers, body,
+ * remote; header names are lowercased with `_` for `-`) and serves back a
+ * string (200, text/plain), bytes, or a bean with any of `status`, `body`,
+ * `headers` and `close_shop`. A handler that spills answers 500 and the shop
+ * stays open; Ctrl-C or `close_shop: true` closes it.
+ */
+
+// src/coffee_shop_server.rs
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tiny_http::{Header, Request, Response, Server};
+
+use crate::interpreter::{self, ControlFlow, Interpreter, Value, INTERRUPTED_BREW};
+use crate::native::percent_decode;
+
+/// How often an idle shop looks up to check for Ctrl-C
+const CTRL_C_GLANCE: Duration = Duration::from_millis(200);
+
+/// `open_coffee_shop(port, handler)`: serve until closed, then serve back how many orders were taken
+pub fn open_coffee_shop(interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (address, handler) = match args.as_slice() {
+        [Value::Number(port), handler] if port.fract() == 0.0 && (0.0..=65535.0).contains(port) => {
+            (format!("127.0.0.1:{}", port), handler.clone())
+        }
+        [Value::String(address), handler] => (address.clone(), handler.clone()),
+        [_, _] => {
+            return Err(ControlFlow::RuntimeError(
+                "open_coffee_shop() expects a port number (or \"host:port\") and a brew to handle orders".to_string(),
+            ))
+        }
+        _ => return Err(ControlFlow::RuntimeError(format!("open_coffee_shop() expects 2 arguments, but got {}", args.len()))),
+    };
+    if !handler.is_brew() {
+        return Err(ControlFlow::RuntimeError(format!(
+            "open_coffee_shop() needs a brew to handle orders, but got a {}",
+            handler.type_name()
+        )));
+    }
+
+    let server = Server::http(address.as_str())
+        .map_err(|e| ControlFlow::RuntimeError(format!("open_coffee_shop() couldn't open at {}: {}", address, e)))?;
+    match server.server_addr().to_ip() {
+        Some(bound) => eprintln!("☕ Coffee shop open at http://{} (Ctrl-C to close)", bound),
+        None => eprintln!("☕ Coffee shop open at {} (Ctrl-C to close)", address),
+    }
+
+    let mut served = 0usize;
+    loop {
+        if interpreter::take_interrupt() {
+            return Err(ControlFlow::RuntimeError(format!(
+                "{}: the coffee shop was closed (Ctrl-C) after {} orders",
+                INTERRUPTED_BREW, served
+            )));
+        }
+        let mut request = match server.recv_timeout(CTRL_C_GLANCE) {
+            Ok(Some(request)) => request,
+            Ok(None) => continue,
+            Err(e) => return Err(ControlFlow::RuntimeError(format!("open_coffee_shop() stopped taking orders: {}", e))),
+        };
+        served += 1;
+        let order = coffee_request(&mut request);
+        let label = format!("<order {} {}>", request.method(), request.url());
+        let (response, close_shop) = match interpreter.call_brew(label, handler.clone(), vec![order]) {
+            Ok(served_back) => coffee_response(served_back),
+            Err(ControlFlow::RuntimeError(spill)) if spill.starts_with(INTERRUPTED_BREW) => {
+                let _ = request.respond(Response::from_string("503 Service Unavailable: the shop is closing").with_status_code(503));
+                return Err(ControlFlow::RuntimeError(spill));
+            }
+            Err(ControlFlow::RuntimeError(spill)) => {
+                eprintln!("☕ Spilled while serving {} {}: {}", request.method(), request.url(), spill);
+                (Response::from_string("500 Internal Server Error: the barista spilled this order").with_status_code(500), false)
+            }
+            Err(_) => {
+                eprintln!("☕ The handler for {} {} broke out of its brew", request.method(), request.url());
+                (Response::from_string("500 Internal Server Error").with_status_code(500), false)
+            }
+        };
+        if let Err(e) = request.respond(response) {
+            eprintln!("☕ Couldn't hand an order back to the customer: {}", e);
+        }
+        if close_shop {
+            eprintln!("☕ Coffee shop closed after {} orders", served);
+            return Ok(Value::Number(served as f64));
+        }
+    }
+}
+
+/// The CoffeeRequest bean a handler brews with
+fn coffee_request(request: &mut Request) -> Value {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((url.as_str(), ""));
+
+    let mut query_fields = HashMap::new();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        query_fields.insert(percent_decode(key, true), Value::String(percent_decode(value, true)));
+    }
+
+    let mut header_fields: HashMap<String, Value> = HashMap::new();
+    for header in request.headers() {
+        // `order.headers.user_agent`: dashes can't appear in a field access
+        let name = header.field.as_str().as_str().to_ascii_lowercase().replace('-', "_");
+        let value = header.value.as_str().to_string();
+        // Repeated headers fold into one, the way HTTP allows
+        let folded = match header_fields.remove(&name) {
+            Some(Value::String(earlier)) => format!("{}, {}", earlier, value),
+            _ => value,
+        };
+        header_fields.insert(name, Value::String(folded));
+    }
+
+    let mut body = Vec::new();
+    if let Err(e) = request.as_reader().read_to_end(&mut body) {
+        eprintln!("☕ Couldn't read the whole order body: {}", e);
+    }
+
+    let mut fields = HashMap::new();
+    fields.insert("method".to_string(), Value::String(request.method().to_string()));
+    fields.insert("path".to_string(), Value::String(percent_decode(path, false)));
+    fields.insert("query".to_string(), Value::object("", query_fields));
+    fields.insert("headers".to_string(), Value::object("", header_fields));
+    fields.insert("body".to_string(), Value::String(String::from_utf8_lossy(&body).into_owned()));
+    fields.insert(
+        "remote".to_string(),
+        request.remote_addr().map_or(Value::Null, |remote| Value::String(remote.to_string())),
+    );
+    Value::object("CoffeeRequest", fields)
+}
+
+/// Turn what the handler served into a response, and whether to close up afterwards
+fn coffee_response(served: Value) -> (Response<std::io::Cursor<Vec<u8>>>, bool) {
+    let text_plain = || Header::from_bytes("Content-Type", "text/plain; charset=utf-8").unwrap();
+    let (status, body, headers, close_shop) = match served {
+        Value::Object { class_name, fields } if class_name != "CoffeeRequest" => {
+            let status = match fields.get("status") {
+                Some(Value::Number(status)) if (100.0..=599.0).contains(status) => *status as u16,
+                _ => 200,
+            };
+            let mut headers = Vec::new();
+            if let Some(Value::Object { fields: header_fields, .. }) = fields.get("headers") {
+                for (name, value) in header_fields.iter() {
+                    let value = match value {
+                        Value::String(text) => text.clone(),
+                        other => other.to_string(),
+                    };
+                    match Header::from_bytes(name.as_bytes(), value.as_bytes()) {
+                        Ok(header) => headers.push(header),
+                        Err(_) => eprintln!("☕ Skipped the response header '{}': it isn't valid HTTP", name),
+                    }
+                }
+            }
+            let close_shop = matches!(fields.get("close_shop"), Some(Value::Boolean(true)));
+            (status, fields.get("body").cloned().unwrap_or(Value::Null), headers, close_shop)
+        }
+        other => (200, other, Vec::new(), false),
+    };
+
+    let has_content_type = headers.iter().any(|header| header.field.equiv("Content-Type"));
+    let mut response = match body {
+        Value::Bytes(bytes) => {
+            let response = Response::from_data(bytes.to_vec());
+            if has_content_type {
+                response
+            } else {
+                response.with_header(Header::from_bytes("Content-Type", "application/octet-stream").unwrap())
+            }
+        }
+        Value::Null => Response::from_string(""),
+        body => {
+            let text = match body {
+                Value::String(text) => text,
+                other => other.to_string(),
+            };
+            let response = Response::from_string(text);
+            if has_content_type {
+                response
+            } else {
+                response.with_header(text_plain())
+            }
+        }
+    }
+    .with_status_code(status);
+    for header in headers {
+        response.add_header(header);
+    }
+    (response, close_shop)
+}
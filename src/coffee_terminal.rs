@@ -0,0 +1,260 @@
+/*
+ * 🎨 Brewco Coffee Terminal ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @counter_note: "A splash of color on the counter, never on the receipt!" ✨
+ *
+ * Natives for recipes that draw in the terminal: colored lines, clearing the
+ * screen and moving the cursor. Escape codes only go out when stdout is a
+ * terminal and NO_COLOR isn't set, so a recipe piped into a file or another
+ * program pours out plain text. `is_tty()` lets a recipe make the same call.
+ *
+ * The brewing bar shows how far a long brew has got, on stderr so it never
+ * mixes into what the recipe pours out. On a terminal it redraws in place;
+ * piped into a log it writes a line at each quarter instead. Without a total
+ * it's a spinner that counts.
+ */
+
+// src/coffee_terminal.rs
+
+use std::cell::RefCell;
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+use crate::ast::PourSpout;
+use crate::interpreter::{pour_text, ControlFlow, Value};
+
+const RESET: &str = "\x1b[0m";
+/// How wide the brewing bar's track is, in characters
+const BAR_WIDTH: usize = 30;
+/// The least time between two redraws of the bar on a terminal
+const BAR_REDRAW: Duration = Duration::from_millis(50);
+const SPINNER_FRAMES: &[char] = &['|', '/', '-', '\\'];
+
+/// The color and style words `pourout_colored` understands, with their SGR codes
+const COFFEE_COLORS: &[(&str, u8)] = &[
+    ("bold", 1),
+    ("dim", 2),
+    ("underline", 4),
+    ("black", 30),
+    ("red", 31),
+    ("green", 32),
+    ("yellow", 33),
+    ("blue", 34),
+    ("magenta", 35),
+    ("cyan", 36),
+    ("white", 37),
+];
+
+/// Is stdout a terminal rather than a pipe or a file?
+pub fn stdout_is_tty() -> bool {
+    io::stdout().is_terminal()
+}
+
+/// Whether escape codes are welcome on stdout: a terminal, and no NO_COLOR
+pub fn colors_welcome() -> bool {
+    stdout_is_tty() && std::env::var_os("NO_COLOR").is_none()
+}
+
+fn terminal_spill(message: impl Into<String>) -> ControlFlow {
+    ControlFlow::RuntimeError(message.into())
+}
+
+/// Write an escape sequence, but only to a terminal
+fn send_control(sequence: &str) {
+    if stdout_is_tty() {
+        print!("{}", sequence);
+        let _ = io::stdout().flush();
+    }
+}
+
+/// `pourout_colored(text, color)`: print a line in a color like "green" or "bold red"
+pub fn pourout_colored(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [text, Value::String(color)] => {
+            let mut codes = Vec::new();
+            for word in color.split_whitespace() {
+                match COFFEE_COLORS.iter().find(|(name, _)| name.eq_ignore_ascii_case(word)) {
+                    Some((_, code)) => codes.push(code.to_string()),
+                    None => {
+                        let known: Vec<&str> = COFFEE_COLORS.iter().map(|(name, _)| *name).collect();
+                        return Err(terminal_spill(format!(
+                            "pourout_colored() doesn't know the color '{}'; try one of {}",
+                            word,
+                            known.join(", ")
+                        )));
+                    }
+                }
+            }
+            if colors_welcome() && !codes.is_empty() {
+                pour_text(&format!("\x1b[{}m{}{}", codes.join(";"), text, RESET), PourSpout::Line)?;
+            } else {
+                pour_text(&text.to_string(), PourSpout::Line)?;
+            }
+            Ok(Value::Null)
+        }
+        [_, other] => Err(terminal_spill(format!("pourout_colored() expects the color as a string, but got a {}", other.type_name()))),
+        _ => Err(terminal_spill(format!("pourout_colored() expects 2 arguments, but got {}", args.len()))),
+    }
+}
+
+/// `clear_the_counter()`: clear the screen and put the cursor in the top-left corner
+pub fn clear_the_counter(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    if !args.is_empty() {
+        return Err(terminal_spill(format!("clear_the_counter() expects no arguments, but got {}", args.len())));
+    }
+    send_control("\x1b[2J\x1b[H");
+    Ok(Value::Null)
+}
+
+/// `place_cursor(row, column)`: move the cursor, counting from 1 at the top-left
+pub fn place_cursor(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [Value::Number(row), Value::Number(column)] if row.fract() == 0.0 && column.fract() == 0.0 && *row >= 1.0 && *column >= 1.0 => {
+            send_control(&format!("\x1b[{};{}H", *row as u32, *column as u32));
+            Ok(Value::Null)
+        }
+        [_, _] => Err(terminal_spill("place_cursor() expects a row and a column as whole numbers from 1")),
+        _ => Err(terminal_spill(format!("place_cursor() expects 2 arguments, but got {}", args.len()))),
+    }
+}
+
+/// `is_tty()`: whether what's poured out lands on a terminal
+pub fn is_tty(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    if !args.is_empty() {
+        return Err(terminal_spill(format!("is_tty() expects no arguments, but got {}", args.len())));
+    }
+    Ok(Value::Boolean(stdout_is_tty()))
+}
+
+/// The progress of a `start_brewing_bar` brew
+struct BrewingBar {
+    label: String,
+    total: Option<f64>, // None for a spinner
+    done: f64,
+    on_terminal: bool,
+    last_drawn: Option<Instant>,
+    quarters_logged: u32, // how many quarter marks a piped bar has written
+}
+
+thread_local! {
+    /// The bar in progress, if any; one at a time
+    static BREWING_BAR: RefCell<Option<BrewingBar>> = const { RefCell::new(None) };
+}
+
+impl BrewingBar {
+    fn line(&self) -> String {
+        match self.total {
+            Some(total) => {
+                let fraction = if total > 0.0 { (self.done / total).clamp(0.0, 1.0) } else { 1.0 };
+                let filled = (fraction * BAR_WIDTH as f64).round() as usize;
+                format!(
+                    "☕ {} [{}{}] {}/{} ({:.0}%)",
+                    self.label,
+                    "=".repeat(filled),
+                    " ".repeat(BAR_WIDTH - filled),
+                    self.done,
+                    total,
+                    fraction * 100.0
+                )
+            }
+            None => {
+                let frame = SPINNER_FRAMES[self.done as usize % SPINNER_FRAMES.len()];
+                format!("☕ {} {} {}", self.label, frame, self.done)
+            }
+        }
+    }
+
+    fn draw(&mut self, finished: bool) {
+        let mut stderr = io::stderr();
+        if self.on_terminal {
+            let due = self.last_drawn.is_none_or(|at| at.elapsed() >= BAR_REDRAW);
+            if due || finished {
+                // \x1b[2K clears what a longer line left behind
+                let _ = write!(stderr, "\r\x1b[2K{}", self.line());
+                if finished {
+                    let _ = writeln!(stderr);
+                }
+                let _ = stderr.flush();
+                self.last_drawn = Some(Instant::now());
+            }
+            return;
+        }
+        // Piped: a line at each quarter of a bar, and one when a spinner finishes
+        let quarters = match self.total {
+            Some(total) if total > 0.0 => ((self.done / total).clamp(0.0, 1.0) * 4.0).floor() as u32,
+            Some(_) => 4,
+            None if finished => 1,
+            None => 0,
+        };
+        if quarters > self.quarters_logged {
+            self.quarters_logged = quarters;
+            let _ = writeln!(stderr, "{}", self.line());
+        }
+    }
+}
+
+/// `start_brewing_bar(total?, label?)`: start a progress bar out of `total`, or a spinner without one
+pub fn start_brewing_bar(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (total, label) = match args.as_slice() {
+        [] => (None, None),
+        [Value::Number(total)] if *total >= 0.0 => (Some(*total), None),
+        [Value::Null] => (None, None),
+        [Value::Number(total), Value::String(label)] if *total >= 0.0 => (Some(*total), Some(label.clone())),
+        [Value::Null, Value::String(label)] => (None, Some(label.clone())),
+        [_] | [_, _] => {
+            return Err(terminal_spill("start_brewing_bar() expects a total that isn't negative (or null for a spinner) and an optional label"))
+        }
+        _ => return Err(terminal_spill(format!("start_brewing_bar() expects at most 2 arguments, but got {}", args.len()))),
+    };
+    let mut bar = BrewingBar {
+        label: label.unwrap_or_else(|| "Brewing".to_string()),
+        total,
+        done: 0.0,
+        on_terminal: io::stderr().is_terminal(),
+        last_drawn: None,
+        quarters_logged: 0,
+    };
+    bar.draw(false);
+    BREWING_BAR.with(|slot| {
+        if let Some(mut unfinished) = slot.borrow_mut().replace(bar) {
+            unfinished.draw(true);
+        }
+    });
+    Ok(Value::Null)
+}
+
+/// `advance_bar(n?)`: move the bar on by `n` (1 if left out)
+pub fn advance_bar(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let step = match args.as_slice() {
+        [] => 1.0,
+        [Value::Number(step)] if *step >= 0.0 => *step,
+        [_] => return Err(terminal_spill("advance_bar() expects a step that isn't negative")),
+        _ => return Err(terminal_spill(format!("advance_bar() expects at most 1 argument, but got {}", args.len()))),
+    };
+    BREWING_BAR.with(|slot| match slot.borrow_mut().as_mut() {
+        Some(bar) => {
+            bar.done += step;
+            bar.draw(false);
+            Ok(Value::Null)
+        }
+        None => Err(terminal_spill("advance_bar() needs a bar; call start_brewing_bar() first")),
+    })
+}
+
+/// `finish_bar()`: draw the bar one last time and end its line
+pub fn finish_bar(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    if !args.is_empty() {
+        return Err(terminal_spill(format!("finish_bar() expects no arguments, but got {}", args.len())));
+    }
+    match BREWING_BAR.with(|slot| slot.borrow_mut().take()) {
+        Some(mut bar) => {
+            if let Some(total) = bar.total {
+                bar.done = bar.done.max(total);
+            }
+            bar.draw(true);
+            Ok(Value::Null)
+        }
+        None => Err(terminal_spill("finish_bar() needs a bar; call start_brewing_bar() first")),
+    }
+}
@@ -0,0 +1,174 @@
+/*
+ * ⏱️ Brewco Cupping Bench ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @cupping_note: "Professional tasters cup every roast many times before judging it!" ✨
+ *
+ * `brew bench` runs every `bench_brew "name" { ... }` block in a recipe
+ * many times, on the tree-walking interpreter and on the Turbo Espresso
+ * machine, and reports how steady each brew is.
+ */
+
+// src/cupping_bench.rs
+
+use std::time::Instant;
+
+use crate::ast::Statement;
+use crate::interpreter::{ControlFlow, Interpreter};
+use crate::parser;
+use crate::turbo_espresso_compiler::{EspressoBrewingMachine, TurboEspressoCompiler};
+
+/// How many times each bench brew is cupped
+pub struct CuppingSettings {
+    pub iterations: usize,
+    pub warmup: usize,
+}
+
+impl Default for CuppingSettings {
+    fn default() -> Self {
+        CuppingSettings { iterations: 10, warmup: 2 }
+    }
+}
+
+/// Timing summary of one bench brew on one engine, in milliseconds
+pub struct CuppingStats {
+    pub mean: f64,
+    pub median: f64,
+    pub stddev: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl CuppingStats {
+    pub fn from_samples(samples: &[f64]) -> CuppingStats {
+        let count = samples.len().max(1) as f64;
+        let mean = samples.iter().sum::<f64>() / count;
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let median = match sorted.len() {
+            0 => 0.0,
+            n if n % 2 == 0 => (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0,
+            n => sorted[n / 2],
+        };
+        // Sample standard deviation: the cups we time are a sample of all possible runs
+        let stddev = if sorted.len() > 1 {
+            let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (count - 1.0);
+            variance.sqrt()
+        } else {
+            0.0
+        };
+        CuppingStats {
+            mean,
+            median,
+            stddev,
+            min: sorted.first().copied().unwrap_or(0.0),
+            max: sorted.last().copied().unwrap_or(0.0),
+        }
+    }
+
+    fn brew_summary(&self) -> String {
+        format!(
+            "mean {:>10.3}ms  median {:>10.3}ms  stddev {:>8.3}ms  (min {:.3}ms, max {:.3}ms)",
+            self.mean, self.median, self.stddev, self.min, self.max
+        )
+    }
+}
+
+/// Run every bench_brew block in `filename` and print the tasting notes
+pub fn run_cupping_session(filename: &str, settings: &CuppingSettings) {
+    let code = match std::fs::read_to_string(filename) {
+        Ok(c) => c,
+        Err(_) => {
+            println!("[ERROR] Could not read file: {}", filename);
+            std::process::exit(1);
+        }
+    };
+    let stmts = parser::parse_source(&code);
+    if !stmts.errors.is_empty() {
+        println!("☕ Oops! Looks like your coffee script hit a sour note:");
+        for err in stmts.errors {
+            println!("  - {}", err);
+        }
+        std::process::exit(1);
+    }
+
+    let bench_brews: Vec<(usize, &String, &Vec<Statement>)> = stmts.statements.iter()
+        .zip(stmts.statement_lines.iter())
+        .filter_map(|(statement, &line)| match statement {
+            Statement::BenchBrew { name, body } => Some((line, name, body)),
+            _ => None,
+        })
+        .collect();
+    if bench_brews.is_empty() {
+        println!("☕ No bench_brew blocks found in '{}'. Wrap the code to time in bench_brew \"name\" {{ ... }}", filename);
+        return;
+    }
+
+    println!(
+        "⏱️ Cupping {} bench brew(s) from '{}' ({} iterations, {} warmup)",
+        bench_brews.len(), filename, settings.iterations, settings.warmup
+    );
+
+    // Everything outside the bench blocks is setup, brewed once up front
+    let mut interpreter = Interpreter::new();
+    if let Err(ControlFlow::RuntimeError(msg)) = interpreter.run(&stmts.statements) {
+        let mut spill = interpreter.spill_report(&msg);
+        spill.frame_in_source(filename, &code);
+        eprint!("{}", spill.brew_detailed_report());
+        std::process::exit(1);
+    }
+    let mut turbo_compiler = TurboEspressoCompiler::new_turbo_brewing_engine();
+
+    for (line, name, body) in bench_brews {
+        println!("\nbench_brew \"{}\" (line {})", name, line);
+
+        let interpreter_stats = cup_on_interpreter(&mut interpreter, body, settings);
+        match &interpreter_stats {
+            Ok(stats) => println!("  interpreter  {}", stats.brew_summary()),
+            Err(spill) => println!("  interpreter  spilled: {}", spill),
+        }
+
+        // Turbo only sees the bench body, so it must not lean on setup beans
+        let recipe_name = format!("{}::{}", filename, name);
+        let turbo_stats = turbo_compiler
+            .brew_turbo_compilation(body, &code, &recipe_name)
+            .map_err(|spill| spill.bitter_message.clone())
+            .and_then(|compiled| {
+                let mut samples = Vec::with_capacity(settings.iterations);
+                for round in 0..settings.warmup + settings.iterations {
+                    let mut machine = EspressoBrewingMachine::new_brewing_machine();
+                    let started = Instant::now();
+                    machine.brew_compiled_recipe(&compiled).map_err(|spill| spill.bitter_message.clone())?;
+                    if round >= settings.warmup {
+                        samples.push(started.elapsed().as_secs_f64() * 1000.0);
+                    }
+                }
+                Ok(CuppingStats::from_samples(&samples))
+            });
+        match (&turbo_stats, &interpreter_stats) {
+            (Ok(turbo), Ok(tree)) => {
+                println!("  turbo        {}", turbo.brew_summary());
+                if turbo.median > 0.0 {
+                    println!("  turbo is {:.2}x the interpreter's speed (by median)", tree.median / turbo.median);
+                }
+            }
+            (Ok(turbo), Err(_)) => println!("  turbo        {}", turbo.brew_summary()),
+            (Err(reason), _) => println!("  turbo        skipped: {}", reason),
+        }
+    }
+}
+
+fn cup_on_interpreter(interpreter: &mut Interpreter, body: &[Statement], settings: &CuppingSettings) -> Result<CuppingStats, String> {
+    let mut samples = Vec::with_capacity(settings.iterations);
+    for round in 0..settings.warmup + settings.iterations {
+        let started = Instant::now();
+        interpreter.run_scoped_body(body).map_err(|e| match e {
+            ControlFlow::RuntimeError(msg) => msg,
+            other => format!("{:?} escaped the bench body", other),
+        })?;
+        if round >= settings.warmup {
+            samples.push(started.elapsed().as_secs_f64() * 1000.0);
+        }
+    }
+    Ok(CuppingStats::from_samples(&samples))
+}
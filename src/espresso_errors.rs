@@ -18,6 +18,7 @@
 // src/espresso_errors.rs - The Barista's Wisdom for Better Error Messages ☕
 
 use std::fmt;
+use std::sync::OnceLock;
 
 #[derive(Debug, Clone)]
 pub struct CoffeeSpillReport {
@@ -27,8 +28,32 @@ pub struct CoffeeSpillReport {
     pub bitter_message: String,
     pub barista_wisdom: Vec<String>,
     pub coffee_context: Option<String>,
+    pub coffee_frame: Option<SpillFrame>,
 }
 
+/// The source lines around a spill, drawn under the report with a caret under the spot
+#[derive(Debug, Clone)]
+pub struct SpillFrame {
+    pub recipe_name: String,
+    pub lines: Vec<(usize, String)>, // line number and text, from just before the spill to just after
+    pub span_width: usize,           // how many characters from the spill's column the caret covers
+}
+
+/// How many lines either side of the spilled one a frame shows
+const FRAME_CONTEXT_LINES: usize = 1;
+
+const SPILL_RED: &str = "\x1b[1;31m";
+const GUTTER_BLUE: &str = "\x1b[34m";
+const WISDOM_CYAN: &str = "\x1b[36m";
+const RESET_COLOR: &str = "\x1b[0m";
+
+/// Message catalogs built into the binary, by language code. English needs none:
+/// it's written out where each message is made, and is what any missing key falls back to.
+const EMBEDDED_CATALOGS: &[(&str, &str)] = &[("es", include_str!("../locales/es.toml"))];
+
+/// The catalog for the language picked at startup; None speaks English
+static CHOSEN_CATALOG: OnceLock<Option<toml::Table>> = OnceLock::new();
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum SpillType {
     // Parser spills - when syntax goes wrong
@@ -48,6 +73,236 @@ pub enum SpillType {
     MissingAroma,             // Missing method
     TooManyShots,             // Too many arguments
     NotEnoughCaffeine,        // Missing required feature
+
+    // Brewco's own spills - when the machine itself breaks
+    InternalSpill,            // A panic inside Brewco
+}
+
+/// What `brew explain` knows about one kind of spill
+pub struct SpillExplanation {
+    pub spill_type: SpillType,
+    /// Stable across releases: new kinds of spill get new codes, old codes are never reused
+    pub code: &'static str,
+    pub explanation: &'static str,
+    pub spilled_recipe: &'static str,
+    pub fixed_recipe: &'static str,
+}
+
+/// Every kind of spill with its code, in code order
+pub const SPILL_CATALOG: &[SpillExplanation] = &[
+    SpillExplanation {
+        spill_type: SpillType::UnexpectedIngredient,
+        code: "BRW0001",
+        explanation: "The lexer or parser found something it couldn't fit into the recipe: a stray \
+                      character, a keyword where a value belongs, or a value where a keyword belongs.",
+        spilled_recipe: "beans price = 4.50 $",
+        fixed_recipe: "beans price = 4.50",
+    },
+    SpillExplanation {
+        spill_type: SpillType::MissingBean,
+        code: "BRW0002",
+        explanation: "A name was needed here (a bean, a brew or a parameter) but the recipe didn't give one.",
+        spilled_recipe: "beans = 3",
+        fixed_recipe: "beans shots = 3",
+    },
+    SpillExplanation {
+        spill_type: SpillType::WrongBrewingMethod,
+        code: "BRW0003",
+        explanation: "An operation was used in a way it can't brew, like dividing by zero or calling \
+                      something that isn't a brew.",
+        spilled_recipe: "beans per_cup = 12 / 0",
+        fixed_recipe: "beans per_cup = 12 / cups",
+    },
+    SpillExplanation {
+        spill_type: SpillType::IncompleteRecipe,
+        code: "BRW0004",
+        explanation: "The recipe stopped part way through a statement: a block or a call that was \
+                      opened and never closed, or an operator with nothing after it.",
+        spilled_recipe: "taste shots more_caffeine 2 {\n    pourout \"strong\"",
+        fixed_recipe: "taste shots more_caffeine 2 {\n    pourout \"strong\"\n}",
+    },
+    SpillExplanation {
+        spill_type: SpillType::BeanNotFound,
+        code: "BRW0005",
+        explanation: "A name was used that nothing declared, or that was declared in a scope that \
+                      has already ended. Names are case-sensitive.",
+        spilled_recipe: "pourout Shots",
+        fixed_recipe: "beans shots = 2\npourout shots",
+    },
+    SpillExplanation {
+        spill_type: SpillType::WrongCupType,
+        code: "BRW0006",
+        explanation: "Two values of types that don't go together met in one operation. Under \
+                      --strict-brew that includes adding a string and a number.",
+        spilled_recipe: "🎀 brewed with --strict-brew\nbeans total = \"Total: \" + 5",
+        fixed_recipe: "beans total = brew_format(\"Total: {}\", 5)",
+    },
+    SpillExplanation {
+        spill_type: SpillType::OverExtraction,
+        code: "BRW0007",
+        explanation: "Brews called themselves (or each other) so deeply that the brewing stack ran \
+                      out. Usually a recursive brew is missing the case that stops it.",
+        spilled_recipe: "brew countdown(n) { serve countdown(n - 1) }",
+        fixed_recipe: "brew countdown(n) {\n    taste n less_caffeine 1 { serve 0 }\n    serve countdown(n - 1)\n}",
+    },
+    SpillExplanation {
+        spill_type: SpillType::UnderExtraction,
+        code: "BRW0008",
+        explanation: "A value was needed but none was brewed, like using what a brew serves when it \
+                      never reaches a `serve`.",
+        spilled_recipe: "brew half(n) { beans h = n / 2 }\npourout half(8) + 1",
+        fixed_recipe: "brew half(n) { serve n / 2 }\npourout half(8) + 1",
+    },
+    SpillExplanation {
+        spill_type: SpillType::ConflictingFlavors,
+        code: "BRW0009",
+        explanation: "Declarations disagree with each other, like a bean that claims a recipe it \
+                      doesn't fully brew.",
+        spilled_recipe: "coffee_recipe Drinkable {\n    sip_it()\n}\nbean Mug blend Drinkable { }",
+        fixed_recipe: "coffee_recipe Drinkable {\n    sip_it()\n}\nbean Mug blend Drinkable {\n    roast sip_it() { pourout \"Sip!\" }\n}",
+    },
+    SpillExplanation {
+        spill_type: SpillType::MissingAroma,
+        code: "BRW0010",
+        explanation: "A method or field was asked for on a bean that doesn't have it.",
+        spilled_recipe: "bean Mug {\n    roast refill() { pourout \"Topped up\" }\n}\nbeans mug = new Mug()\nmug.refil()",
+        fixed_recipe: "bean Mug {\n    roast refill() { pourout \"Topped up\" }\n}\nbeans mug = new Mug()\nmug.refill()",
+    },
+    SpillExplanation {
+        spill_type: SpillType::TooManyShots,
+        code: "BRW0011",
+        explanation: "A brew was called with more arguments than it has parameters.",
+        spilled_recipe: "brew double(n) { serve n * 2 }\npourout double(2, 3)",
+        fixed_recipe: "brew double(n) { serve n * 2 }\npourout double(2)",
+    },
+    SpillExplanation {
+        spill_type: SpillType::NotEnoughCaffeine,
+        code: "BRW0012",
+        explanation: "The recipe used something this way of brewing doesn't support yet, like a \
+                      feature the Turbo Espresso machine (--turbo) can't compile. Brewing without \
+                      --turbo supports everything.",
+        spilled_recipe: "🎀 brew --turbo: cups don't compile yet\nbeans menu = [\"latte\", \"mocha\"]",
+        fixed_recipe: "🎀 plain brew\nbeans menu = [\"latte\", \"mocha\"]",
+    },
+    SpillExplanation {
+        spill_type: SpillType::InternalSpill,
+        code: "BRW0013",
+        explanation: "Brewco itself broke while brewing a statement. That's a bug in Brewco, not in \
+                      the recipe: the statement is abandoned, the spill can be caught like any \
+                      other, and the recipe (or the REPL) carries on. Please report it along with \
+                      the recipe that triggered it.",
+        spilled_recipe: "🎀 any statement that trips a bug in Brewco",
+        fixed_recipe: "🎀 the same statement, once the bug is fixed",
+    },
+];
+
+impl SpillType {
+    /// The stable `BRWnnnn` code shown with every spill of this kind
+    pub fn code(&self) -> &'static str {
+        SPILL_CATALOG.iter().find(|entry| entry.spill_type == *self).map_or("BRW0000", |entry| entry.code)
+    }
+
+    /// The kind of spill a plain runtime message describes, going by its wording
+    pub fn for_runtime_message(message: &str) -> SpillType {
+        let said = message.to_lowercase();
+        let says = |words: &[&str]| words.iter().any(|word| said.contains(word));
+        if says(&["not found on", "no method", "has no field"]) {
+            SpillType::MissingAroma
+        } else if says(&["not found", "not declared"]) {
+            SpillType::BeanNotFound
+        } else if says(&["calls deep", "of its fuel", "time limit", "of output"]) {
+            SpillType::OverExtraction
+        } else if says(&["expects a", "but got a", "can't", "cannot", "mixes"]) {
+            SpillType::WrongCupType
+        } else {
+            SpillType::WrongBrewingMethod
+        }
+    }
+}
+
+/// Pick the language spills and help are told in: `--lang` when given, else BREWCO_LANG,
+/// else English. An unknown `--lang` is an error; an unknown BREWCO_LANG just stays English.
+pub fn choose_language(lang_flag: Option<&str>) -> Result<(), String> {
+    let (wanted, from_flag) = match lang_flag {
+        Some(lang) => (lang.to_string(), true),
+        None => (std::env::var("BREWCO_LANG").unwrap_or_default(), false),
+    };
+    // "es_ES.UTF-8" and "es-MX" both mean es
+    let code = wanted.split(['_', '-', '.']).next().unwrap_or("").trim().to_ascii_lowercase();
+    let catalog = match EMBEDDED_CATALOGS.iter().find(|(lang, _)| *lang == code) {
+        Some((lang, text)) => Some(text.parse::<toml::Table>().map_err(|e| format!("the '{}' message catalog is broken: {}", lang, e))?),
+        None if code.is_empty() || code == "en" || !from_flag => None,
+        None => {
+            let known: Vec<&str> = EMBEDDED_CATALOGS.iter().map(|(lang, _)| *lang).collect();
+            return Err(format!("brewco doesn't speak '{}' yet; try one of en, {}", wanted, known.join(", ")));
+        }
+    };
+    let _ = CHOSEN_CATALOG.set(catalog);
+    Ok(())
+}
+
+/// The chosen catalog's entry for a dotted key like "spills.BRW0001.title"
+fn catalog_entry(key: &str) -> Option<&'static toml::Value> {
+    let catalog = CHOSEN_CATALOG.get()?.as_ref()?;
+    let mut parts = key.split('.');
+    let mut entry = catalog.get(parts.next()?)?;
+    for part in parts {
+        entry = entry.get(part)?;
+    }
+    Some(entry)
+}
+
+/// A message in the chosen language, or `english` when the catalog has nothing for `key`
+pub fn localized(key: &str, english: &str) -> String {
+    catalog_entry(key).and_then(|entry| entry.as_str()).unwrap_or(english).to_string()
+}
+
+/// A list of messages, translated as a whole so a catalog can say it in more or fewer lines
+fn localized_list(key: &str, english: &[&str]) -> Vec<String> {
+    match catalog_entry(key).and_then(|entry| entry.as_array()) {
+        Some(lines) => lines.iter().filter_map(|line| line.as_str()).map(str::to_string).collect(),
+        None => english.iter().map(|line| line.to_string()).collect(),
+    }
+}
+
+/// "at line L, column C" in the chosen language
+fn spill_location(line: usize, column: usize) -> String {
+    localized("report.location", "at line {line}, column {column}")
+        .replace("{line}", &line.to_string())
+        .replace("{column}", &column.to_string())
+}
+
+/// `brew explain [code]`: the long story behind a spill code, or every code when none is given
+pub fn explain_spill(code: Option<&str>) -> Result<String, String> {
+    let Some(code) = code else {
+        let mut listing = localized("explain.listing", "☕ Spill codes (run `brew explain <code>` for the full story):");
+        listing.push('\n');
+        for entry in SPILL_CATALOG {
+            listing.push_str(&format!("  {}  {}\n", entry.code, spill_description(&entry.spill_type)));
+        }
+        return Ok(listing);
+    };
+    let wanted = code.trim().to_ascii_uppercase();
+    let entry = SPILL_CATALOG
+        .iter()
+        .find(|entry| entry.code == wanted)
+        .ok_or_else(|| format!("there's no spill code '{}'; `brew explain` lists them all", code))?;
+    let indent = |recipe: &str| recipe.lines().map(|line| format!("    {}\n", line)).collect::<String>();
+    let wisdom = generate_barista_wisdom(&entry.spill_type, "");
+    let explanation = localized(&format!("spills.{}.explanation", entry.code), entry.explanation);
+    let mut story = format!("☕ {} {}\n\n{}\n\n", entry.code, spill_description(&entry.spill_type), explanation);
+    story.push_str(&format!(
+        "{}\n\n{}\n{}\n\n{}\n",
+        localized("explain.spills", "This recipe spills:"),
+        indent(entry.spilled_recipe),
+        localized("explain.brews", "This one brews:"),
+        indent(entry.fixed_recipe)
+    ));
+    story.push_str(&format!("{}:\n", localized("report.wisdom", "The Barista's Wisdom")));
+    for (i, advice) in wisdom.iter().enumerate() {
+        story.push_str(&format!("   {}. {}\n", i + 1, advice));
+    }
+    Ok(story)
 }
 
 impl CoffeeSpillReport {
@@ -66,50 +321,148 @@ impl CoffeeSpillReport {
             bitter_message: message.to_string(),
             barista_wisdom,
             coffee_context: None,
+            coffee_frame: None,
         }
     }
     
+    /// The one-line form parse errors are listed in
+    pub fn brief(&self) -> String {
+        format!(
+            "{} [{}] {}: {}",
+            localized("report.spilled_beans", "Spilled beans"),
+            self.spill_type.code(),
+            spill_location(self.coffee_line, self.brewing_column),
+            self.bitter_message
+        )
+    }
+
+    /// The one-line form for runtime spills, named by what kind of spill it is
+    pub fn headline(&self) -> String {
+        format!(
+            "{} [{}] {}: {}",
+            spill_description(&self.spill_type),
+            self.spill_type.code(),
+            spill_location(self.coffee_line, self.brewing_column),
+            self.bitter_message
+        )
+    }
+
+    /// The report for a runtime spill nobody caught. A spill that was made from a report
+    /// (its message carries a `[BRWnnnn]` code) keeps its kind; the rest are sorted by wording.
+    pub fn from_runtime_spill(line: usize, column: usize, message: &str) -> Self {
+        let coded = SPILL_CATALOG.iter().find_map(|entry| {
+            let tag = format!("[{}]", entry.code);
+            // "<kind> [BRWnnnn] at line L, column C: what happened"
+            let rest = &message[message.find(&tag)? + tag.len()..];
+            Some((entry.spill_type.clone(), rest.find(": ").map_or(rest, |colon| &rest[colon + 2..])))
+        });
+        let (spill_type, said) = coded.unwrap_or_else(|| (SpillType::for_runtime_message(message), message));
+        Self::new_brewing_disaster(spill_type, line, column, said)
+    }
+
     pub fn add_coffee_context(&mut self, context: &str) {
         self.coffee_context = Some(context.to_string());
     }
-    
+
+    /// Keep the lines around the spill from the recipe's source, so the report can show them
+    pub fn frame_in_source(&mut self, recipe_name: &str, source: &str) {
+        let lines: Vec<&str> = source.lines().collect();
+        let Some(spilled) = lines.get(self.coffee_line.wrapping_sub(1)) else { return };
+        let first = self.coffee_line.saturating_sub(FRAME_CONTEXT_LINES).max(1);
+        let last = (self.coffee_line + FRAME_CONTEXT_LINES).min(lines.len());
+        self.coffee_frame = Some(SpillFrame {
+            recipe_name: recipe_name.to_string(),
+            lines: (first..=last).map(|number| (number, lines[number - 1].trim_end().to_string())).collect(),
+            span_width: spill_span_width(spilled, self.brewing_column),
+        });
+    }
+
+    /// The full report, colored when stdout is a terminal that wants colors
     pub fn brew_detailed_report(&self) -> String {
+        self.brew_report(crate::coffee_terminal::colors_welcome())
+    }
+
+    fn brew_report(&self, colored: bool) -> String {
+        let paint = |color: &str, text: &str| if colored { format!("{}{}{}", color, text, RESET_COLOR) } else { text.to_string() };
         let mut report = String::new();
         
         // Header with coffee emoji and error type
         report.push_str(&format!(
-            "☕ COFFEE SPILL ALERT! {} at line {}, column {}\n", 
-            spill_description(&self.spill_type),
-            self.coffee_line,
-            self.brewing_column
+            "☕ {} {} {}\n",
+            localized("report.alert", "COFFEE SPILL ALERT!"),
+            paint(SPILL_RED, &format!("{} [{}]", spill_description(&self.spill_type), self.spill_type.code())),
+            spill_location(self.coffee_line, self.brewing_column)
         ));
         
         // Main error message
-        report.push_str(&format!("🚨 What happened: {}\n", self.bitter_message));
+        report.push_str(&format!("🚨 {}: {}\n", localized("report.what_happened", "What happened"), self.bitter_message));
         
-        // Context if available
-        if let Some(ref context) = self.coffee_context {
-            report.push_str(&format!("📍 In this brewing context:\n   {}\n", context));
+        // The source around the spill, or whatever context there is without it
+        if let Some(frame) = &self.coffee_frame {
+            let gutter = frame.lines.iter().map(|(number, _)| number.to_string().len()).max().unwrap_or(1);
+            report.push_str(&format!(
+                "📍 {}:\n{}{} {}:{}:{}\n",
+                localized("report.context", "In this brewing context"),
+                " ".repeat(gutter),
+                paint(GUTTER_BLUE, "-->"),
+                frame.recipe_name,
+                self.coffee_line,
+                self.brewing_column
+            ));
+            for (number, text) in &frame.lines {
+                report.push_str(&format!(" {} {}\n", paint(GUTTER_BLUE, &format!("{:>gutter$} |", number)), text));
+                if *number == self.coffee_line {
+                    // Tabs stay tabs so the caret lines up under the spill
+                    let lead: String = text
+                        .chars()
+                        .take(self.brewing_column.saturating_sub(1))
+                        .map(|c| if c == '\t' { '\t' } else { ' ' })
+                        .collect();
+                    let caret = "^".repeat(frame.span_width);
+                    report.push_str(&format!(" {} {}{}\n", paint(GUTTER_BLUE, &format!("{:>gutter$} |", "")), lead, paint(SPILL_RED, &caret)));
+                }
+            }
+        } else if let Some(ref context) = self.coffee_context {
+            report.push_str(&format!("📍 {}:\n   {}\n", localized("report.context", "In this brewing context"), context));
         }
         
         // Barista wisdom (suggestions)
         if !self.barista_wisdom.is_empty() {
-            report.push_str("\n☕ The Barista's Wisdom:\n");
+            let heading = format!("☕ {}:", localized("report.wisdom", "The Barista's Wisdom"));
+            report.push_str(&format!("\n{}\n", paint(WISDOM_CYAN, &heading)));
             for (i, wisdom) in self.barista_wisdom.iter().enumerate() {
                 report.push_str(&format!("   {}. {}\n", i + 1, wisdom));
             }
         }
         
+        report.push_str(&format!("\n📖 {}: brew explain {}\n", localized("report.more", "More about this spill"), self.spill_type.code()));
+
         // Footer with encouragement
-        report.push_str("\n💪 Don't let this spill ruin your brew! Every barista makes mistakes. ");
-        report.push_str("Clean it up and keep brewing amazing code! ☕✨\n");
+        let encouragement = localized(
+            "report.encouragement",
+            "Don't let this spill ruin your brew! Every barista makes mistakes. Clean it up and keep brewing amazing code! ☕✨",
+        );
+        report.push_str(&format!("\n💪 {}\n", encouragement));
         
         report
     }
 }
 
-fn spill_description(spill_type: &SpillType) -> &'static str {
-    match spill_type {
+/// How wide the caret under a spill should be: the whole word or string starting at the
+/// column, or one character for anything else
+fn spill_span_width(line: &str, column: usize) -> usize {
+    let rest: Vec<char> = line.chars().skip(column.saturating_sub(1)).collect();
+    let is_word = |c: &char| c.is_alphanumeric() || *c == '_';
+    match rest.first() {
+        Some('"') => rest[1..].iter().position(|&c| c == '"').map_or(rest.len(), |close| close + 2),
+        Some(c) if is_word(c) => rest.iter().take_while(|c| is_word(c)).count(),
+        _ => 1,
+    }
+}
+
+/// The spill's name in the chosen language
+fn spill_description(spill_type: &SpillType) -> String {
+    let english = match spill_type {
         SpillType::UnexpectedIngredient => "Unexpected Ingredient Found",
         SpillType::MissingBean => "Missing Coffee Bean",
         SpillType::WrongBrewingMethod => "Wrong Brewing Method",
@@ -122,69 +475,70 @@ fn spill_description(spill_type: &SpillType) -> &'static str {
         SpillType::MissingAroma => "Missing Aroma",
         SpillType::TooManyShots => "Too Many Espresso Shots",
         SpillType::NotEnoughCaffeine => "Not Enough Caffeine",
-    }
+        SpillType::InternalSpill => "Internal Spill",
+    };
+    localized(&format!("spills.{}.title", spill_type.code()), english)
 }
 
 fn generate_barista_wisdom(spill_type: &SpillType, message: &str) -> Vec<String> {
-    let mut wisdom = Vec::new();
-    
-    match spill_type {
-        SpillType::UnexpectedIngredient => {
-            wisdom.push("Check if you're using the right coffee syntax".to_string());
-            wisdom.push("Maybe you meant to use a different operator like 'add' instead of '+'?".to_string());
-            wisdom.push("Look for missing semicolons or brackets that might be causing confusion".to_string());
-        }
-        
-        SpillType::MissingBean => {
-            wisdom.push("Don't forget to declare your beans with 'beans variable_name = value'".to_string());
-            wisdom.push("Check for typos in your variable names - Brewco is case-sensitive".to_string());
-            wisdom.push("Make sure the variable is in scope where you're trying to use it".to_string());
-        }
-        
-        SpillType::WrongBrewingMethod => {
-            wisdom.push("Brewco uses coffee-themed operators: 'add', 'sip', 'more_caffeine', etc.".to_string());
-            wisdom.push("Try 'same_blend' for equality comparison instead of '=='".to_string());
-            wisdom.push("Use 'pour_in' for assignment instead of '='".to_string());
-        }
-        
-        SpillType::IncompleteRecipe => {
-            wisdom.push("Every recipe needs all its ingredients - check for missing parts".to_string());
-            wisdom.push("Make sure your blocks are properly closed with '}'".to_string());
-            wisdom.push("Check that function calls have matching parentheses".to_string());
-        }
-        
-        SpillType::BeanNotFound => {
-            wisdom.push("This coffee bean hasn't been planted yet - declare it first".to_string());
-            wisdom.push("Check the spelling of your variable name".to_string());
-            wisdom.push("Make sure the variable is declared in the current scope".to_string());
-        }
-        
-        SpillType::WrongCupType => {
-            wisdom.push("You're trying to pour coffee into the wrong cup type".to_string());
-            wisdom.push("Check if you're mixing numbers with strings incorrectly".to_string());
-            wisdom.push("Use type conversion functions if needed".to_string());
-        }
-        
-        SpillType::TooManyShots => {
-            wisdom.push("This function is getting more espresso shots than it can handle".to_string());
-            wisdom.push("Check the function signature to see how many parameters it expects".to_string());
-            wisdom.push("Remove extra arguments or add parameters to the function definition".to_string());
-        }
-        
-        _ => {
-            wisdom.push("Take a sip of coffee and review the code carefully".to_string());
-            wisdom.push("Check the Brewco documentation for syntax examples".to_string());
-            wisdom.push("Try breaking the problem into smaller brewing steps".to_string());
-        }
-    }
+    // Kinds of spill with their own wisdom are translated by code; the rest share the general advice
+    let own_key = format!("spills.{}.wisdom", spill_type.code());
+    let (key, english): (String, &[&str]) = match spill_type {
+        SpillType::UnexpectedIngredient => (own_key, &[
+            "Check if you're using the right coffee syntax",
+            "Maybe you meant to use a different operator like 'add' instead of '+'?",
+            "Look for missing semicolons or brackets that might be causing confusion",
+        ]),
+        SpillType::MissingBean => (own_key, &[
+            "Don't forget to declare your beans with 'beans variable_name = value'",
+            "Check for typos in your variable names - Brewco is case-sensitive",
+            "Make sure the variable is in scope where you're trying to use it",
+        ]),
+        SpillType::WrongBrewingMethod => (own_key, &[
+            "Brewco uses coffee-themed operators: 'add', 'sip', 'more_caffeine', etc.",
+            "Try 'same_blend' for equality comparison instead of '=='",
+            "Use 'pour_in' for assignment instead of '='",
+        ]),
+        SpillType::IncompleteRecipe => (own_key, &[
+            "Every recipe needs all its ingredients - check for missing parts",
+            "Make sure your blocks are properly closed with '}'",
+            "Check that function calls have matching parentheses",
+        ]),
+        SpillType::BeanNotFound => (own_key, &[
+            "This coffee bean hasn't been planted yet - declare it first",
+            "Check the spelling of your variable name",
+            "Make sure the variable is declared in the current scope",
+        ]),
+        SpillType::WrongCupType => (own_key, &[
+            "You're trying to pour coffee into the wrong cup type",
+            "Check if you're mixing numbers with strings incorrectly",
+            "Use type conversion functions if needed",
+        ]),
+        SpillType::TooManyShots => (own_key, &[
+            "This function is getting more espresso shots than it can handle",
+            "Check the function signature to see how many parameters it expects",
+            "Remove extra arguments or add parameters to the function definition",
+        ]),
+        SpillType::InternalSpill => (own_key, &[
+            "This one's on the espresso machine, not on your recipe",
+            "Please report it with the recipe that caused it",
+            "Until it's fixed, try writing the statement a different way",
+        ]),
+        _ => ("wisdom.general".to_string(), &[
+            "Take a sip of coffee and review the code carefully",
+            "Check the Brewco documentation for syntax examples",
+            "Try breaking the problem into smaller brewing steps",
+        ]),
+    };
+    let mut wisdom = localized_list(&key, english);
     
     // Add context-specific wisdom based on the message content
     if message.contains("parse") || message.contains("syntax") {
-        wisdom.push("Use the Brewco REPL to test small snippets: 'cargo run repl'".to_string());
+        wisdom.push(localized("wisdom.parse", "Use the Brewco REPL to test small snippets: 'cargo run repl'"));
     }
     
     if message.contains("type") {
-        wisdom.push("Remember: Brewco is strongly typed like a perfectly calibrated espresso machine".to_string());
+        wisdom.push(localized("wisdom.type", "Remember: Brewco is strongly typed like a perfectly calibrated espresso machine"));
     }
     
     wisdom
@@ -0,0 +1,194 @@
+/*
+ * 🍯 Brewco Flavor Sets ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @tasting_note: "Every flavor on the shelf once, and only once!" ✨
+ *
+ * A FlavorSet holds each distinct value once, in the order it was first
+ * added, and answers "is this in here?" without walking a whole cup. Numbers,
+ * strings, booleans, null, bytes and cups of those can go in; beans and brews
+ * can't, since they don't have a fixed flavor to file them under.
+ *
+ * Sets are shared by reference like cups, and `set_add`/`set_remove` change
+ * the set in place. Union, intersection and difference brew a fresh set.
+ */
+
+// src/flavor_set.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use indexmap::IndexMap;
+
+use crate::interpreter::{ControlFlow, Value};
+
+/// The shared set behind `Value::Set`
+pub type CoffeeSet = Rc<RefCell<FlavorSet>>;
+
+/// Distinct values keyed by their blend, remembering insertion order
+#[derive(Default)]
+pub struct FlavorSet {
+    items: IndexMap<BlendKey, Value>,
+}
+
+/// What makes two values the same flavor; mirrors `same_blend` for the values a set accepts
+#[derive(Hash, PartialEq, Eq)]
+pub enum BlendKey {
+    Number(u64),
+    String(String),
+    Boolean(bool),
+    Null,
+    Bytes(Vec<u8>),
+    Cup(Vec<BlendKey>),
+}
+
+impl BlendKey {
+    pub fn of(value: &Value) -> Option<BlendKey> {
+        match value {
+            // -0 and 0 are the same blend, so they file under one key
+            Value::Number(n) => Some(BlendKey::Number(if *n == 0.0 { 0 } else { n.to_bits() })),
+            Value::String(s) => Some(BlendKey::String(s.clone())),
+            Value::Boolean(b) => Some(BlendKey::Boolean(*b)),
+            Value::Null => Some(BlendKey::Null),
+            Value::Bytes(bytes) => Some(BlendKey::Bytes(bytes.to_vec())),
+            Value::Array(cup) => cup.iter().map(BlendKey::of).collect::<Option<Vec<_>>>().map(BlendKey::Cup),
+            _ => None,
+        }
+    }
+}
+
+impl FlavorSet {
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = &Value> {
+        self.items.values()
+    }
+
+    /// Empty the set, serving what was in it
+    pub fn drain_items(&mut self) -> Vec<Value> {
+        self.items.drain(..).map(|(_, item)| item).collect()
+    }
+
+    fn contains(&self, value: &Value) -> bool {
+        BlendKey::of(value).is_some_and(|key| self.items.contains_key(&key))
+    }
+
+    /// Same size, and every flavor in one is in the other
+    pub fn same_flavors(&self, other: &FlavorSet) -> bool {
+        self.len() == other.len() && self.items.keys().all(|key| other.items.contains_key(key))
+    }
+}
+
+fn set_value(set: FlavorSet) -> Value {
+    let set = Rc::new(RefCell::new(set));
+    crate::grounds_collector::track_set(&set);
+    Value::Set(set)
+}
+
+fn set_key(native: &str, value: &Value) -> Result<BlendKey, ControlFlow> {
+    BlendKey::of(value).ok_or_else(|| {
+        ControlFlow::RuntimeError(format!(
+            "{}() can't put a {} in a flavor set; only numbers, strings, booleans, null, bytes and cups of those fit",
+            native,
+            value.type_name()
+        ))
+    })
+}
+
+/// Fill a set from a cup's items, skipping repeats
+fn set_from_items<'a>(native: &str, items: impl Iterator<Item = &'a Value>) -> Result<FlavorSet, ControlFlow> {
+    let mut set = FlavorSet::default();
+    for item in items {
+        set.items.entry(set_key(native, item)?).or_insert_with(|| item.clone());
+    }
+    Ok(set)
+}
+
+/// `flavor_set(cup?)`: a new set, empty or holding the cup's distinct items
+pub fn flavor_set(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [] => Ok(set_value(FlavorSet::default())),
+        [Value::Array(cup)] => Ok(set_value(set_from_items("flavor_set", cup.iter())?)),
+        [Value::Set(set)] => Ok(set_value(set_from_items("flavor_set", set.borrow().items())?)),
+        [other] => Err(ControlFlow::RuntimeError(format!("flavor_set() expects a cup to fill it from, but got a {}", other.type_name()))),
+        _ => Err(ControlFlow::RuntimeError(format!("flavor_set() expects 0 or 1 arguments, but got {}", args.len()))),
+    }
+}
+
+fn set_and_value<'a>(native: &str, args: &'a [Value]) -> Result<(&'a CoffeeSet, &'a Value), ControlFlow> {
+    match args {
+        [Value::Set(set), value] => Ok((set, value)),
+        [other, _] => Err(ControlFlow::RuntimeError(format!("{}() expects a flavor set first, but got a {}", native, other.type_name()))),
+        _ => Err(ControlFlow::RuntimeError(format!("{}() expects 2 arguments, but got {}", native, args.len()))),
+    }
+}
+
+/// `set_add(set, value)`: add in place; serves true if the value was new
+pub fn set_add(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (set, value) = set_and_value("set_add", &args)?;
+    let key = set_key("set_add", value)?;
+    let mut set = set.borrow_mut();
+    let before = set.len();
+    set.items.entry(key).or_insert_with(|| value.clone());
+    Ok(Value::Boolean(set.len() > before))
+}
+
+/// `set_remove(set, value)`: remove in place; serves true if it was there
+pub fn set_remove(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (set, value) = set_and_value("set_remove", &args)?;
+    let removed = match BlendKey::of(value) {
+        Some(key) => set.borrow_mut().items.shift_remove(&key).is_some(),
+        None => false,
+    };
+    Ok(Value::Boolean(removed))
+}
+
+/// `set_contains(set, value)`
+pub fn set_contains(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (set, value) = set_and_value("set_contains", &args)?;
+    Ok(Value::Boolean(set.borrow().contains(value)))
+}
+
+fn two_sets<'a>(native: &str, args: &'a [Value]) -> Result<(&'a CoffeeSet, &'a CoffeeSet), ControlFlow> {
+    match args {
+        [Value::Set(a), Value::Set(b)] => Ok((a, b)),
+        [_, _] => Err(ControlFlow::RuntimeError(format!("{}() expects two flavor sets", native))),
+        _ => Err(ControlFlow::RuntimeError(format!("{}() expects 2 arguments, but got {}", native, args.len()))),
+    }
+}
+
+/// `set_union(a, b)`: everything in either, a's order first
+pub fn set_union(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (a, b) = two_sets("set_union", &args)?;
+    let (a, b) = (a.borrow(), b.borrow());
+    Ok(set_value(set_from_items("set_union", a.items().chain(b.items()))?))
+}
+
+/// `set_intersection(a, b)`: what's in both, in a's order
+pub fn set_intersection(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (a, b) = two_sets("set_intersection", &args)?;
+    let (a, b) = (a.borrow(), b.borrow());
+    Ok(set_value(set_from_items("set_intersection", a.items().filter(|item| b.contains(item)))?))
+}
+
+/// `set_difference(a, b)`: what's in a but not b
+pub fn set_difference(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (a, b) = two_sets("set_difference", &args)?;
+    let (a, b) = (a.borrow(), b.borrow());
+    Ok(set_value(set_from_items("set_difference", a.items().filter(|item| !b.contains(item)))?))
+}
+
+/// `set_to_cup(set)`: the items as a cup, in the order they were added
+pub fn set_to_cup(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [Value::Set(set)] => Ok(Value::array(set.borrow().items().cloned().collect())),
+        [other] => Err(ControlFlow::RuntimeError(format!("set_to_cup() expects a flavor set, but got a {}", other.type_name()))),
+        _ => Err(ControlFlow::RuntimeError(format!("set_to_cup() expects 1 argument, but got {}", args.len()))),
+    }
+}
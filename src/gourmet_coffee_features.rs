@@ -4,7 +4,7 @@ use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 use crate::ast::{Statement, Expr};
-use crate::espresso_errors::{CoffeeSpillReport, SpillType};
+use crate::espresso_errors::CoffeeSpillReport;
 use crate::interpreter::Value;
 
 /// The Gourmet Coffee Blending System - advanced language features
@@ -432,7 +432,7 @@ impl GourmetCoffeeBlendingSystem {
         
         self.advanced_coffee_traits.trait_implementations
             .entry(implementing_type.to_string())
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(trait_impl);
         
         Ok(())
@@ -451,8 +451,8 @@ impl GourmetCoffeeBlendingSystem {
     /// Generate optimized brewing code for specialization
     fn generate_specialized_brewing_code(
         &self,
-        blend_name: &str,
-        concrete_flavors: &HashMap<String, String>
+        _blend_name: &str,
+        _concrete_flavors: &HashMap<String, String>
     ) -> Result<Vec<Statement>, CoffeeSpillReport> {
         // In a real implementation, this would generate optimized code
         // based on the concrete type parameters
@@ -463,7 +463,7 @@ impl GourmetCoffeeBlendingSystem {
     /// Create a brewing future for async execution
     fn create_brewing_future(
         &self,
-        brewing_recipe: Vec<Statement>
+        _brewing_recipe: Vec<Statement>
     ) -> Pin<Box<dyn Future<Output = Result<Value, CoffeeSpillReport>> + Send>> {
         Box::pin(async move {
             // Simulate async brewing
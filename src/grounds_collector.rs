@@ -0,0 +1,236 @@
+/*
+ * 🧹 Brewco Grounds Collector ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @cleanup_note: "Every good barista knocks out the puck between shots!" ✨
+ *
+ * Chains and sets are shared through Rc and changed in place, so a cycle
+ * through them (a chain holding a cup that holds the chain) keeps itself
+ * alive after the last bean that held it is gone. Cups and menus are copied
+ * on write, so no cycle can close without a chain or set in it. Every chain
+ * and set is tracked here by a Weak, and `grind_and_clean` finds the cycles nothing
+ * else can reach by trial deletion: from each one's strong count, take away
+ * the references the other tracked values hold, counting the cups and menus
+ * only one of them holds as its own. Whatever still has a count
+ * left is held from outside (a scope, a brew's arguments, a blend, Rust
+ * code), so it and everything it reaches are kept; the rest are emptied,
+ * which breaks their cycles and lets Rc free them.
+ *
+ * Under the AutomaticGrindAndClean memory model (the default) a collection
+ * runs between statements once enough new values have been made since the
+ * last one; under the others only `grind_and_clean()` collects.
+ */
+
+// src/grounds_collector.rs
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::flavor_set::FlavorSet;
+use crate::interpreter::{CoffeeChain, CoffeeSet, Value};
+use crate::turbo_espresso_compiler::CoffeeMemoryModel;
+
+/// New values made before the first automatic collection; after that, as many as survived the last one
+const CLEAN_EVERY: usize = 10_000;
+
+static AUTO_CLEAN: AtomicBool = AtomicBool::new(true);
+
+/// Follow a memory model: only AutomaticGrindAndClean collects without being asked
+pub fn set_memory_model(model: &CoffeeMemoryModel) {
+    AUTO_CLEAN.store(matches!(model, CoffeeMemoryModel::AutomaticGrindAndClean), Ordering::SeqCst);
+}
+
+/// One tracked value that can hold others
+enum Grounds {
+    Chain(Weak<RefCell<VecDeque<Value>>>),
+    Set(Weak<RefCell<FlavorSet>>),
+}
+
+/// A tracked value while a collection holds it
+enum HeldGrounds {
+    Chain(CoffeeChain),
+    Set(CoffeeSet),
+}
+
+impl Grounds {
+    fn is_gone(&self) -> bool {
+        match self {
+            Grounds::Chain(chain) => chain.strong_count() == 0,
+            Grounds::Set(set) => set.strong_count() == 0,
+        }
+    }
+
+    fn hold(&self) -> Option<HeldGrounds> {
+        match self {
+            Grounds::Chain(chain) => chain.upgrade().map(HeldGrounds::Chain),
+            Grounds::Set(set) => set.upgrade().map(HeldGrounds::Set),
+        }
+    }
+}
+
+impl HeldGrounds {
+    fn address(&self) -> usize {
+        match self {
+            HeldGrounds::Chain(chain) => Rc::as_ptr(chain) as *const () as usize,
+            HeldGrounds::Set(set) => Rc::as_ptr(set) as *const () as usize,
+        }
+    }
+
+    /// References from outside this collection, not counting its own hold
+    fn strong_count(&self) -> usize {
+        match self {
+            HeldGrounds::Chain(chain) => Rc::strong_count(chain) - 1,
+            HeldGrounds::Set(set) => Rc::strong_count(set) - 1,
+        }
+    }
+
+    /// The addresses of the tracked values this one holds, or None while it's borrowed for changing
+    fn held_addresses(&self) -> Option<Vec<usize>> {
+        fn addresses<'a>(items: impl Iterator<Item = &'a Value>) -> Vec<usize> {
+            let mut found = Vec::new();
+            items.for_each(|item| held_through(item, &mut found));
+            found
+        }
+        match self {
+            HeldGrounds::Chain(chain) => chain.try_borrow().ok().map(|chain| addresses(chain.iter())),
+            HeldGrounds::Set(set) => set.try_borrow().ok().map(|set| addresses(set.items())),
+        }
+    }
+
+    /// Take everything out, for dropping once no borrow is held; None if it's borrowed
+    fn empty_out(&self) -> Option<Vec<Value>> {
+        match self {
+            HeldGrounds::Chain(chain) => chain.try_borrow_mut().ok().map(|mut chain| chain.drain(..).collect()),
+            HeldGrounds::Set(set) => set.try_borrow_mut().ok().map(|mut set| set.drain_items()),
+        }
+    }
+}
+
+/// The chains and sets `value` holds: itself if it's one, or what's inside a cup or menu
+/// nothing else holds (a shared one is held from elsewhere too, so it isn't followed)
+fn held_through(value: &Value, found: &mut Vec<usize>) {
+    match value {
+        Value::Chain(chain) => found.push(Rc::as_ptr(chain) as *const () as usize),
+        Value::Set(set) => found.push(Rc::as_ptr(set) as *const () as usize),
+        Value::Array(cup) if Rc::strong_count(cup) == 1 => cup.iter().for_each(|item| held_through(item, found)),
+        Value::Object { fields, .. } | Value::BoundMethod { this_obj: fields, .. } if Rc::strong_count(fields) == 1 => {
+            fields.values().for_each(|field| held_through(field, found))
+        }
+        _ => {}
+    }
+}
+
+#[derive(Default)]
+struct GroundsBin {
+    tracked: Vec<Grounds>,
+    /// How many were tracked after the last sweep of the gone ones
+    kept: usize,
+}
+
+thread_local! {
+    static GROUNDS_BIN: RefCell<GroundsBin> = RefCell::new(GroundsBin::default());
+    static CLEAN_DUE: Cell<bool> = const { Cell::new(false) };
+}
+
+fn track(grounds: Grounds) {
+    GROUNDS_BIN.with(|bin| {
+        let mut bin = bin.borrow_mut();
+        bin.tracked.push(grounds);
+        if bin.tracked.len() >= bin.kept.max(CLEAN_EVERY) * 2 {
+            if AUTO_CLEAN.load(Ordering::Relaxed) {
+                CLEAN_DUE.with(|due| due.set(true));
+            } else {
+                // Nobody collects, but the Weaks of freed values still shouldn't pile up
+                bin.tracked.retain(|grounds| !grounds.is_gone());
+                bin.kept = bin.tracked.len();
+            }
+        }
+    });
+}
+
+pub fn track_chain(chain: &CoffeeChain) {
+    track(Grounds::Chain(Rc::downgrade(chain)));
+}
+
+pub fn track_set(set: &CoffeeSet) {
+    track(Grounds::Set(Rc::downgrade(set)));
+}
+
+/// Collect if enough has been made since the last collection; the interpreter asks between statements
+pub fn clean_if_due() {
+    if CLEAN_DUE.with(|due| due.replace(false)) {
+        grind_and_clean();
+    }
+}
+
+/// Empty every cycle of chains and sets (and the cups and menus between them) nothing
+/// outside it reaches; serves how many chains and sets were emptied
+pub fn grind_and_clean() -> usize {
+    let tracked = GROUNDS_BIN.with(|bin| std::mem::take(&mut bin.borrow_mut().tracked));
+    let held: Vec<HeldGrounds> = tracked.iter().filter_map(Grounds::hold).collect();
+    drop(tracked);
+    let index_of: HashMap<usize, usize> = held.iter().enumerate().map(|(index, grounds)| (grounds.address(), index)).collect();
+
+    // What each holds, and how many references are left once the tracked ones are taken away
+    let mut outside: Vec<usize> = held.iter().map(HeldGrounds::strong_count).collect();
+    let mut holds: Vec<Vec<usize>> = Vec::with_capacity(held.len());
+    for (index, grounds) in held.iter().enumerate() {
+        match grounds.held_addresses() {
+            Some(addresses) => {
+                let inner: Vec<usize> = addresses.iter().filter_map(|address| index_of.get(address).copied()).collect();
+                for &inner_index in &inner {
+                    outside[inner_index] -= 1;
+                }
+                holds.push(inner);
+            }
+            None => {
+                // Being changed right now, so it's certainly in use
+                outside[index] += 1;
+                holds.push(Vec::new());
+            }
+        }
+    }
+
+    // Keep what's held from outside and everything it reaches
+    let mut kept = vec![false; held.len()];
+    let mut reaching: Vec<usize> = (0..held.len()).filter(|&index| outside[index] > 0).collect();
+    while let Some(index) = reaching.pop() {
+        if !kept[index] {
+            kept[index] = true;
+            reaching.extend(holds[index].iter().copied().filter(|&inner| !kept[inner]));
+        }
+    }
+
+    // Empty the rest; the values taken out are dropped only after every borrow is let go
+    let mut grounds = Vec::new();
+    let mut emptied = 0;
+    for (index, grounds_held) in held.iter().enumerate() {
+        if !kept[index] {
+            if let Some(contents) = grounds_held.empty_out() {
+                grounds.push(contents);
+                emptied += 1;
+            }
+        }
+    }
+    drop(grounds);
+
+    GROUNDS_BIN.with(|bin| {
+        let mut bin = bin.borrow_mut();
+        // Values made while collecting were tracked in the meantime
+        let made_meanwhile = std::mem::take(&mut bin.tracked);
+        bin.tracked = held
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| kept[index])
+            .map(|(_, grounds)| match grounds {
+                HeldGrounds::Chain(chain) => Grounds::Chain(Rc::downgrade(chain)),
+                HeldGrounds::Set(set) => Grounds::Set(Rc::downgrade(set)),
+            })
+            .collect();
+        bin.tracked.extend(made_meanwhile);
+        bin.kept = bin.tracked.len();
+    });
+    emptied
+}
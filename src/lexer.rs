@@ -2,30 +2,36 @@
 
 /*
  * 🔍 Brewco Lexical Analyzer ☕
- * 
+ *
  * @author: "Khushi Motwani" 💖
  * @khushi_scanner_magic: "Where text becomes meaningful tokens!" ✨
  * @tokenization_love: "Every character matters in our coffee brew!" ☕
- * 
+ *
  * This lexer transforms raw Brewco source code into beautiful tokens!
  * From coffee beans to brewing instructions - every symbol has meaning.
- * 
+ *
  * Crafted with precision, powered by coffee love! ☕💖
  */
 
-#[derive(Debug, Clone, PartialEq)]
+use std::collections::VecDeque;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum Token {
     // Keywords
     Beans,
-    Bean,           // class
-    Brew,           // function
-    Blend,          // extends
-    Taste,          // if
-    Otherwise,      // else
-    Steep,          // while
-    Pour,           // for
-    Roast,          // switch
-    Serve,          // return
+    Bean,      // class
+    Brew,      // function
+    Blend,     // extends
+    Taste,     // if
+    Otherwise, // else
+    Steep,     // while
+    Pour,      // for
+    Roast,     // switch
+    Serve,     // return
     Break,
     Continue,
     This,
@@ -41,6 +47,8 @@ pub enum Token {
     RefillWith,     // for array element assignment
     Grind,          // import/load module
     In,             // in (for foreach loops)
+    BenchBrew,      // bench_brew (benchmark block)
+    TasteTest,      // taste_test (test block)
 
     // Themed Operators
     Add,            // add (arithmetic +)
@@ -59,291 +67,687 @@ pub enum Token {
     NoFoam,         // no_foam (!)
     BlendWith,      // blend_with (&)
     TopWith,        // top_with (|)
+    ThenPour,       // then_pour (|>)
     Spice,          // spice (^)
     Invert,         // invert (~)
     DoubleShot,     // double_shot (<<)
     HalfCaf,        // half_caf (>>)
     PourIn,         // pour_in (=)
     ServeBack,      // serve_back (return)
+    YieldSip,       // yield_sip (serve one value from a generator brew)
 
     // Data types
-    Cup,            // array
-    CoffeeChain,    // linked list
-    CoffeeMenu,     // hash map
-    
+    Cup,         // array
+    CoffeeChain, // linked list (Value::Chain, made by coffee_chain())
+    CoffeeMenu,  // hash map
+
     // Literals
     Identifier(String),
     Number(f64),
     String(String),
-    
+
     // Operators
-    Equals,         // =
-    Plus,           // +
-    Minus,          // -
-    Star,           // *
-    Slash,          // /
-    Percent,        // %
-    Colon,          // :
-    Semicolon,      // ;
-    Arrow,          // ->
-    Greater,        // >
-    Less,           // <
-    GreaterEqual,   // >=
-    LessEqual,      // <=
-    Equal,          // ==
-    NotEqual,       // !=
-    And,            // &&
-    OrSym,          // ||
-    Not,            // !
-    BitAnd,         // &
-    BitOr,          // |
-    BitXor,         // ^
-    BitNot,         // ~
-    Shl,            // <<
-    Shr,            // >>
-    
+    Equals,       // =
+    Plus,         // +
+    Minus,        // -
+    Star,         // *
+    Slash,        // /
+    Percent,      // %
+    Colon,        // :
+    Semicolon,    // ;
+    Arrow,        // ->
+    Greater,      // >
+    Less,         // <
+    GreaterEqual, // >=
+    LessEqual,    // <=
+    Equal,        // ==
+    NotEqual,     // !=
+    And,          // &&
+    OrSym,        // ||
+    Not,          // !
+    BitAnd,       // &
+    BitOr,        // |
+    BitXor,       // ^
+    BitNot,       // ~
+    Shl,          // <<
+    Shr,          // >>
+
     // Delimiters
-    LParen,         // (
-    RParen,         // )
-    LBrace,         // {
-    RBrace,         // }
-    LBracket,       // [
-    RBracket,       // ]
-    Comma,          // ,
-    Dot,            // .
-    Newline,        // \n
+    LParen,   // (
+    RParen,   // )
+    LBrace,   // {
+    RBrace,   // }
+    LBracket, // [
+    RBracket, // ]
+    Comma,    // ,
+    Dot,      // .
+    DotDot,   // .. (roast arm ranges)
+    Newline,  // \n
+
+    // Trivia, only produced by CoffeeTokenStream::with_comments
+    Comment(String), // 🎀 ... or // ... (text includes the marker)
+
+    // Something the lexer couldn't make sense of; the parser reports it as a spill
+    LexError(String),
+}
+
+/// Every word the lexer turns into a keyword or operator, plus the ones the
+/// parser recognises by name. Keep in step with the identifier match below.
+pub const COFFEE_WORDS: &[&str] = &[
+    "beans",
+    "bean",
+    "brew",
+    "blend",
+    "taste",
+    "otherwise",
+    "steep",
+    "pour",
+    "roast",
+    "serve",
+    "break",
+    "continue",
+    "this",
+    "super",
+    "private",
+    "public",
+    "recipe",
+    "new",
+    "brew_time",
+    "foreach",
+    "taste_carefully",
+    "if_spilled",
+    "refill_with",
+    "grind",
+    "in",
+    "bench_brew",
+    "taste_test",
+    "add",
+    "sip",
+    "brew_op",
+    "pour_op",
+    "grounds",
+    "same_blend",
+    "different_blend",
+    "less_caffeine",
+    "more_caffeine",
+    "not_stronger",
+    "not_weaker",
+    "with",
+    "or",
+    "no_foam",
+    "blend_with",
+    "top_with",
+    "spice",
+    "invert",
+    "double_shot",
+    "half_caf",
+    "pour_in",
+    "serve_back",
+    "true",
+    "false",
+    "pourout",
+    "pourout_flat",
+    "spill_out",
+    "then_pour",
+    "yield_sip",
+];
+
+/// What to say about a character that isn't part of any token
+fn stray_character(c: char) -> String {
+    let hint = match c {
+        '\'' | '`' | '\u{201C}' | '\u{201D}' | '\u{2018}' | '\u{2019}' => {
+            "; strings go in plain double quotes"
+        }
+        '#' => "; comments start with 🎀",
+        _ if !c.is_ascii() && !c.is_alphanumeric() => {
+            "; emoji can only go in strings and 🎀 comments"
+        }
+        _ => "",
+    };
+    format!(
+        "'{}' (U+{:04X}) isn't something Brewco knows how to brew{}",
+        c, c as u32, hint
+    )
+}
+
+/// Every `_` in a run of digits has a digit on both sides
+fn underscores_between_digits(digits: &str, radix: u32) -> bool {
+    let chars: Vec<char> = digits.chars().collect();
+    chars.iter().enumerate().all(|(i, &ch)| {
+        ch != '_'
+            || (i > 0
+                && i + 1 < chars.len()
+                && chars[i - 1].is_digit(radix)
+                && chars[i + 1].is_digit(radix))
+    })
 }
 
-pub fn lex(input: &str) -> Vec<Token> {
-    let mut tokens = Vec::new();
-    let mut chars = input.chars().peekable();
-    while let Some(&c) = chars.peek() {
-        // Skip lines that start with optional whitespace and then 🎀
-        if c == '\u{1F380}' || c == ' ' || c == '\t' {
-            // Clone iterator to check ahead
-            let mut clone = chars.clone();
-            // Skip whitespace
-            while let Some(&wc) = clone.peek() {
-                if wc == ' ' || wc == '\t' { clone.next(); } else { break; }
+/// A token together with the 1-based line and column where it starts and ends
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize, // one past the last character
+}
+
+/// Streaming lexer: brews one token at a time straight from the source text
+pub struct CoffeeTokenStream<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize,
+    peeked: VecDeque<SpannedToken>,
+    keep_comments: bool,
+}
+
+impl<'a> CoffeeTokenStream<'a> {
+    pub fn new(input: &'a str) -> Self {
+        CoffeeTokenStream {
+            chars: input.chars().peekable(),
+            line: 1,
+            column: 1,
+            peeked: VecDeque::new(),
+            keep_comments: false,
+        }
+    }
+
+    /// Like `new`, but comments come through as `Token::Comment` instead of being skipped
+    pub fn with_comments(input: &'a str) -> Self {
+        CoffeeTokenStream {
+            keep_comments: true,
+            ..CoffeeTokenStream::new(input)
+        }
+    }
+
+    /// Look at the next token without consuming it
+    pub fn peek(&mut self) -> Option<&SpannedToken> {
+        self.peek_nth(0)
+    }
+
+    /// Look `n` tokens ahead (0 is the next token) without consuming anything
+    pub fn peek_nth(&mut self, n: usize) -> Option<&SpannedToken> {
+        while self.peeked.len() <= n {
+            let token = self.brew_next_token()?;
+            self.peeked.push_back(token);
+        }
+        self.peeked.get(n)
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn brewed(&self, token: Token, line: usize, column: usize) -> Option<SpannedToken> {
+        Some(SpannedToken {
+            token,
+            line,
+            column,
+            end_line: self.line,
+            end_column: self.column,
+        })
+    }
+
+    /// Consume characters while `keep` says so
+    fn take_while(&mut self, keep: impl Fn(char) -> bool) -> String {
+        let mut taken = String::new();
+        while let Some(&ch) = self.chars.peek() {
+            if !keep(ch) {
+                break;
             }
-            if let Some(&wc) = clone.peek() {
-                if wc == '\u{1F380}' {
-                    // Advance the main iterator to the comment start
-                    while let Some(&wc) = chars.peek() {
-                        if wc == '\u{1F380}' { chars.next(); break; }
-                        if wc == '\n' { break; }
-                        chars.next();
-                    }
-                    // Skip until newline
-                    while let Some(&ch) = chars.peek() {
-                        if ch == '\n' { break; }
-                        chars.next();
-                    }
-                    continue;
-                }
+            taken.push(ch);
+            self.next_char();
+        }
+        taken
+    }
+
+    /// A number literal: decimal with `_` separators, a fraction and an exponent (`1_000.5e3`),
+    /// or a whole number in hex, binary or octal (`0xFF`, `0b1010`, `0o755`).
+    /// A malformed one comes back as a LexError rather than a number.
+    fn brew_number(&mut self) -> Token {
+        let mut ahead = self.chars.clone();
+        let radix = match (ahead.next(), ahead.next()) {
+            (Some('0'), Some('x' | 'X')) => Some((16, "hex")),
+            (Some('0'), Some('b' | 'B')) => Some((2, "binary")),
+            (Some('0'), Some('o' | 'O')) => Some((8, "octal")),
+            _ => None,
+        };
+        if let Some((radix, kind)) = radix {
+            let prefix: String = [self.next_char(), self.next_char()]
+                .into_iter()
+                .flatten()
+                .collect();
+            let digits = self.take_while(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+            let literal = format!("{}{}", prefix, digits);
+            if digits.is_empty() {
+                return Token::LexError(format!(
+                    "'{}' isn't a number; it needs {} digits after the prefix",
+                    literal, kind
+                ));
+            }
+            if !underscores_between_digits(&digits, radix) {
+                return Token::LexError(format!(
+                    "'{}' isn't a number; `_` can only sit between digits",
+                    literal
+                ));
             }
+            let cleaned: String = digits.chars().filter(|&ch| ch != '_').collect();
+            return match u64::from_str_radix(&cleaned, radix) {
+                Ok(number) => Token::Number(number as f64),
+                Err(_) if cleaned.chars().all(|ch| ch.is_digit(radix)) => {
+                    Token::LexError(format!("'{}' is too big for a {} literal", literal, kind))
+                }
+                Err(_) => Token::LexError(format!("'{}' isn't a valid {} number", literal, kind)),
+            };
         }
-        match c {
-            ' ' | '\t' | '\r' => { chars.next(); }
-            '\n' => { chars.next(); tokens.push(Token::Newline); }
-            '=' => {
-                chars.next();
-                if let Some(&'=') = chars.peek() {
-                    chars.next();
-                    tokens.push(Token::SameBlend);
-                } else {
-                    tokens.push(Token::PourIn);
+
+        let mut literal = String::new();
+        while let Some(&ch) = self.chars.peek() {
+            if ch.is_ascii_digit() || ch == '_' || ch == '.' {
+                // `0..59` is a range, not a number with two decimal points
+                let mut ahead = self.chars.clone();
+                ahead.next();
+                if ch == '.' && ahead.peek() == Some(&'.') {
+                    break;
                 }
+                literal.push(ch);
+                self.next_char();
+            } else {
+                break;
             }
-            '!' => {
-                chars.next();
-                if let Some(&'=') = chars.peek() {
-                    chars.next();
-                    tokens.push(Token::DifferentBlend);
-                } else {
-                    tokens.push(Token::NoFoam);
+        }
+        if let Some(&marker @ ('e' | 'E')) = self.chars.peek() {
+            let mut ahead = self.chars.clone();
+            ahead.next();
+            let after = ahead.next();
+            let signed = matches!(after, Some('+' | '-'));
+            let first_digit = if signed { ahead.next() } else { after };
+            if first_digit.is_some_and(|ch| ch.is_ascii_digit()) {
+                self.next_char();
+                literal.push(marker);
+                if signed {
+                    literal.extend(self.next_char());
                 }
-            }
-            '>' => {
-                chars.next();
-                if let Some(&'>') = chars.peek() {
-                    chars.next();
-                    tokens.push(Token::HalfCaf);
-                } else if let Some(&'=') = chars.peek() {
-                    chars.next();
-                    tokens.push(Token::NotWeaker);
-                } else {
-                    tokens.push(Token::MoreCaffeine);
+                literal.push_str(&self.take_while(|ch| ch.is_ascii_digit() || ch == '_'));
+            } else if signed || !after.is_some_and(|ch| ch.is_alphanumeric() || ch == '_') {
+                // `1e`, `1e+`: an exponent with no digits (but `2else` is a number and a word)
+                self.next_char();
+                literal.push(marker);
+                if signed {
+                    literal.extend(self.next_char());
                 }
+                return Token::LexError(format!(
+                    "'{}' isn't a number; the exponent has no digits",
+                    literal
+                ));
             }
-            '<' => {
-                chars.next();
-                if let Some(&'<') = chars.peek() {
-                    chars.next();
-                    tokens.push(Token::DoubleShot);
-                } else if let Some(&'=') = chars.peek() {
-                    chars.next();
-                    tokens.push(Token::NotStronger);
-                } else {
-                    tokens.push(Token::LessCaffeine);
+        }
+
+        if literal.matches('.').count() > 1 {
+            return Token::LexError(format!(
+                "'{}' isn't a number; it has more than one decimal point",
+                literal
+            ));
+        }
+        let (mantissa, exponent) = literal.split_once(['e', 'E']).unwrap_or((&literal, ""));
+        let exponent = exponent.trim_start_matches(['+', '-']);
+        let parts_ok = mantissa
+            .split('.')
+            .chain(Some(exponent).filter(|e| !e.is_empty()))
+            .all(|part| underscores_between_digits(part, 10));
+        if !parts_ok {
+            return Token::LexError(format!(
+                "'{}' isn't a number; `_` can only sit between digits",
+                literal
+            ));
+        }
+        let cleaned: String = literal.chars().filter(|&ch| ch != '_').collect();
+        match cleaned.parse::<f64>() {
+            Ok(number) if number.is_finite() => Token::Number(number),
+            Ok(_) => Token::LexError(format!("'{}' is too big to be a number", literal)),
+            Err(_) => Token::LexError(format!("'{}' isn't a number", literal)),
+        }
+    }
+
+    fn brew_next_token(&mut self) -> Option<SpannedToken> {
+        while let Some(&c) = self.chars.peek() {
+            let (line, column) = (self.line, self.column);
+            // Skip lines that start with optional whitespace and then 🎀
+            if c == '\u{1F380}' || c == ' ' || c == '\t' {
+                // Clone iterator to check ahead
+                let mut clone = self.chars.clone();
+                // Skip whitespace
+                while let Some(&wc) = clone.peek() {
+                    if wc == ' ' || wc == '\t' {
+                        clone.next();
+                    } else {
+                        break;
+                    }
                 }
-            }
-            '+' => { tokens.push(Token::Add); chars.next(); }
-            '-' => { 
-                chars.next();
-                if let Some(&'>') = chars.peek() {
-                    chars.next();
-                    tokens.push(Token::Arrow);
-                } else {
-                    tokens.push(Token::Sip);
+                if let Some(&wc) = clone.peek() {
+                    if wc == '\u{1F380}' {
+                        // Advance the main iterator to the comment start
+                        while let Some(&wc) = self.chars.peek() {
+                            if wc == '\u{1F380}' || wc == '\n' {
+                                break;
+                            }
+                            self.next_char();
+                        }
+                        // Take the comment (🎀 included) up to the newline
+                        let (comment_line, comment_column) = (self.line, self.column);
+                        let mut comment = String::new();
+                        while let Some(&ch) = self.chars.peek() {
+                            if ch == '\n' {
+                                break;
+                            }
+                            comment.push(ch);
+                            self.next_char();
+                        }
+                        if self.keep_comments {
+                            return self.brewed(
+                                Token::Comment(comment),
+                                comment_line,
+                                comment_column,
+                            );
+                        }
+                        continue;
+                    }
                 }
             }
-            '*' => { tokens.push(Token::BrewOp); chars.next(); }
-            '/' => {
-                chars.next();
-                if let Some(&'/') = chars.peek() {
-                    // It's a comment, consume until newline
-                    while let Some(&ch) = chars.peek() {
-                        if ch == '\n' { break; }
-                        chars.next();
+            match c {
+                ' ' | '\t' | '\r' => {
+                    self.next_char();
+                }
+                '\n' => {
+                    self.next_char();
+                    return self.brewed(Token::Newline, line, column);
+                }
+                '=' => {
+                    self.next_char();
+                    if let Some(&'=') = self.chars.peek() {
+                        self.next_char();
+                        return self.brewed(Token::SameBlend, line, column);
+                    } else {
+                        return self.brewed(Token::PourIn, line, column);
                     }
-                } else {
-                    tokens.push(Token::PourOp);
                 }
-            }
-            '%' => { tokens.push(Token::Grounds); chars.next(); }
-            ':' => { tokens.push(Token::Colon); chars.next(); }
-            ';' => { tokens.push(Token::Semicolon); chars.next(); }
-            '(' => { tokens.push(Token::LParen); chars.next(); }
-            ')' => { tokens.push(Token::RParen); chars.next(); }
-            '{' => { tokens.push(Token::LBrace); chars.next(); }
-            '}' => { tokens.push(Token::RBrace); chars.next(); }
-            '[' => { tokens.push(Token::LBracket); chars.next(); }
-            ']' => { tokens.push(Token::RBracket); chars.next(); }
-            ',' => { tokens.push(Token::Comma); chars.next(); }
-            '.' => { tokens.push(Token::Dot); chars.next(); }
-            '&' => {
-                chars.next();
-                if let Some(&'&') = chars.peek() {
-                    chars.next();
-                    tokens.push(Token::With);
-                } else {
-                    tokens.push(Token::BlendWith);
+                '!' => {
+                    self.next_char();
+                    if let Some(&'=') = self.chars.peek() {
+                        self.next_char();
+                        return self.brewed(Token::DifferentBlend, line, column);
+                    } else {
+                        return self.brewed(Token::NoFoam, line, column);
+                    }
                 }
-            }
-            '|' => {
-                chars.next();
-                if let Some(&'|') = chars.peek() {
-                    chars.next();
-                    tokens.push(Token::Or);
-                } else {
-                    tokens.push(Token::TopWith);
+                '>' => {
+                    self.next_char();
+                    if let Some(&'>') = self.chars.peek() {
+                        self.next_char();
+                        return self.brewed(Token::HalfCaf, line, column);
+                    } else if let Some(&'=') = self.chars.peek() {
+                        self.next_char();
+                        return self.brewed(Token::NotWeaker, line, column);
+                    } else {
+                        return self.brewed(Token::MoreCaffeine, line, column);
+                    }
                 }
-            }
-            '^' => { tokens.push(Token::Spice); chars.next(); }
-            '~' => { tokens.push(Token::Invert); chars.next(); }
-            '"' => {
-                chars.next();
-                let mut s = String::new();
-                while let Some(&ch) = chars.peek() {
-                    if ch == '"' { break; }
-                    s.push(ch); chars.next();
-                }
-                chars.next();
-                tokens.push(Token::String(s));
-            }
-            '0'..='9' => {
-                let mut num_str = String::new();
-                while let Some(&ch) = chars.peek() {
-                    if ch.is_digit(10) || ch == '.' {
-                        num_str.push(ch);
-                        chars.next();
+                '<' => {
+                    self.next_char();
+                    if let Some(&'<') = self.chars.peek() {
+                        self.next_char();
+                        return self.brewed(Token::DoubleShot, line, column);
+                    } else if let Some(&'=') = self.chars.peek() {
+                        self.next_char();
+                        return self.brewed(Token::NotStronger, line, column);
                     } else {
-                        break;
+                        return self.brewed(Token::LessCaffeine, line, column);
                     }
                 }
-                tokens.push(Token::Number(num_str.parse().unwrap()));
-            }
-            _ if c.is_alphabetic() => {
-                let mut ident = String::new();
-                while let Some(&ch) = chars.peek() {
-                    if ch.is_alphanumeric() || ch == '_' {
-                        ident.push(ch);
-                        chars.next();
+                '+' => {
+                    self.next_char();
+                    return self.brewed(Token::Add, line, column);
+                }
+                '-' => {
+                    self.next_char();
+                    if let Some(&'>') = self.chars.peek() {
+                        self.next_char();
+                        return self.brewed(Token::Arrow, line, column);
                     } else {
-                        break;
+                        return self.brewed(Token::Sip, line, column);
+                    }
+                }
+                '*' => {
+                    self.next_char();
+                    return self.brewed(Token::BrewOp, line, column);
+                }
+                '/' => {
+                    self.next_char();
+                    if let Some(&'/') = self.chars.peek() {
+                        // It's a comment, consume until newline
+                        let mut comment = String::from("/");
+                        while let Some(&ch) = self.chars.peek() {
+                            if ch == '\n' {
+                                break;
+                            }
+                            comment.push(ch);
+                            self.next_char();
+                        }
+                        if self.keep_comments {
+                            return self.brewed(Token::Comment(comment), line, column);
+                        }
+                    } else {
+                        return self.brewed(Token::PourOp, line, column);
+                    }
+                }
+                '%' => {
+                    self.next_char();
+                    return self.brewed(Token::Grounds, line, column);
+                }
+                ':' => {
+                    self.next_char();
+                    return self.brewed(Token::Colon, line, column);
+                }
+                ';' => {
+                    self.next_char();
+                    return self.brewed(Token::Semicolon, line, column);
+                }
+                '(' => {
+                    self.next_char();
+                    return self.brewed(Token::LParen, line, column);
+                }
+                ')' => {
+                    self.next_char();
+                    return self.brewed(Token::RParen, line, column);
+                }
+                '{' => {
+                    self.next_char();
+                    return self.brewed(Token::LBrace, line, column);
+                }
+                '}' => {
+                    self.next_char();
+                    return self.brewed(Token::RBrace, line, column);
+                }
+                '[' => {
+                    self.next_char();
+                    return self.brewed(Token::LBracket, line, column);
+                }
+                ']' => {
+                    self.next_char();
+                    return self.brewed(Token::RBracket, line, column);
+                }
+                ',' => {
+                    self.next_char();
+                    return self.brewed(Token::Comma, line, column);
+                }
+                '.' => {
+                    self.next_char();
+                    if let Some(&'.') = self.chars.peek() {
+                        self.next_char();
+                        return self.brewed(Token::DotDot, line, column);
                     }
+                    return self.brewed(Token::Dot, line, column);
+                }
+                '&' => {
+                    self.next_char();
+                    if let Some(&'&') = self.chars.peek() {
+                        self.next_char();
+                        return self.brewed(Token::With, line, column);
+                    } else {
+                        return self.brewed(Token::BlendWith, line, column);
+                    }
+                }
+                '|' => {
+                    self.next_char();
+                    if let Some(&'|') = self.chars.peek() {
+                        self.next_char();
+                        return self.brewed(Token::Or, line, column);
+                    } else if let Some(&'>') = self.chars.peek() {
+                        self.next_char();
+                        return self.brewed(Token::ThenPour, line, column);
+                    } else {
+                        return self.brewed(Token::TopWith, line, column);
+                    }
+                }
+                '^' => {
+                    self.next_char();
+                    return self.brewed(Token::Spice, line, column);
+                }
+                '~' => {
+                    self.next_char();
+                    return self.brewed(Token::Invert, line, column);
+                }
+                '"' => {
+                    self.next_char();
+                    let mut s = String::new();
+                    while let Some(&ch) = self.chars.peek() {
+                        if ch == '"' {
+                            break;
+                        }
+                        s.push(ch);
+                        self.next_char();
+                    }
+                    self.next_char();
+                    return self.brewed(Token::String(s), line, column);
+                }
+                '0'..='9' => {
+                    let token = self.brew_number();
+                    return self.brewed(token, line, column);
+                }
+                _ if c.is_alphabetic() || c == '_' => {
+                    let mut ident = String::new();
+                    while let Some(&ch) = self.chars.peek() {
+                        if ch.is_alphanumeric() || ch == '_' {
+                            ident.push(ch);
+                            self.next_char();
+                        } else {
+                            break;
+                        }
+                    }
+                    return self.brewed(
+                        match ident.as_str() {
+                            "beans" => Token::Beans,
+                            "bean" => Token::Bean,
+                            "brew" => Token::Brew,
+                            "blend" => Token::Blend,
+                            "taste" => Token::Taste,
+                            "otherwise" => Token::Otherwise,
+                            "steep" => Token::Steep,
+                            "pour" => Token::Pour,
+                            "roast" => Token::Roast,
+                            "serve" => Token::Serve,
+                            "break" => Token::Break,
+                            "continue" => Token::Continue,
+                            "this" => Token::This,
+                            "super" => Token::Super,
+                            "private" => Token::Private,
+                            "public" => Token::Public,
+                            "recipe" => Token::CoffeeRecipe,
+                            "new" => Token::New,
+                            "brew_time" => Token::BrewTime,
+                            "foreach" => Token::Foreach,
+                            "taste_carefully" => Token::TasteCarefully,
+                            "if_spilled" => Token::IfSpilled,
+                            "refill_with" => Token::RefillWith,
+                            "grind" => Token::Grind,
+                            "in" => Token::In,
+                            "bench_brew" => Token::BenchBrew,
+                            "taste_test" => Token::TasteTest,
+                            "add" => Token::Add,
+                            "sip" => Token::Sip,
+                            "brew_op" => Token::BrewOp,
+                            "pour_op" => Token::PourOp,
+                            "grounds" => Token::Grounds,
+                            "same_blend" => Token::SameBlend,
+                            "different_blend" => Token::DifferentBlend,
+                            "less_caffeine" => Token::LessCaffeine,
+                            "more_caffeine" => Token::MoreCaffeine,
+                            "not_stronger" => Token::NotStronger,
+                            "not_weaker" => Token::NotWeaker,
+                            "with" => Token::With,
+                            "or" => Token::Or,
+                            "no_foam" => Token::NoFoam,
+                            "blend_with" => Token::BlendWith,
+                            "top_with" => Token::TopWith,
+                            "then_pour" => Token::ThenPour,
+                            "spice" => Token::Spice,
+                            "invert" => Token::Invert,
+                            "double_shot" => Token::DoubleShot,
+                            "half_caf" => Token::HalfCaf,
+                            "pour_in" => Token::PourIn,
+                            "serve_back" => Token::ServeBack,
+                            "yield_sip" => Token::YieldSip,
+                            "true" => Token::Identifier("true".to_string()),
+                            "false" => Token::Identifier("false".to_string()),
+                            _ => Token::Identifier(ident),
+                        },
+                        line,
+                        column,
+                    );
+                }
+                // Spaces, carriage returns and a leading byte-order mark don't mean anything
+                _ if c.is_whitespace() || c == '\u{FEFF}' => {
+                    self.next_char();
+                }
+                _ => {
+                    self.next_char();
+                    return self.brewed(Token::LexError(stray_character(c)), line, column);
                 }
-                tokens.push(match ident.as_str() {
-                    "beans" => Token::Beans,
-                    "bean" => Token::Bean,
-                    "brew" => Token::Brew,
-                    "blend" => Token::Blend,
-                    "taste" => Token::Taste,
-                    "otherwise" => Token::Otherwise,
-                    "steep" => Token::Steep,
-                    "pour" => Token::Pour,
-                    "roast" => Token::Roast,
-                    "serve" => Token::Serve,
-                    "break" => Token::Break,
-                    "continue" => Token::Continue,
-                    "this" => Token::This,
-                    "super" => Token::Super,
-                    "private" => Token::Private,
-                    "public" => Token::Public,
-                    "recipe" => Token::CoffeeRecipe,
-                    "new" => Token::New,
-                    "brew_time" => Token::BrewTime,
-                    "foreach" => Token::Foreach,
-                    "taste_carefully" => Token::TasteCarefully,
-                    "if_spilled" => Token::IfSpilled,
-                    "refill_with" => Token::RefillWith,
-                    "grind" => Token::Grind,
-                    "in" => Token::In,
-                    "add" => Token::Add,
-                    "sip" => Token::Sip,
-                    "brew_op" => Token::BrewOp,
-                    "pour_op" => Token::PourOp,
-                    "grounds" => Token::Grounds,
-                    "same_blend" => Token::SameBlend,
-                    "different_blend" => Token::DifferentBlend,
-                    "less_caffeine" => Token::LessCaffeine,
-                    "more_caffeine" => Token::MoreCaffeine,
-                    "not_stronger" => Token::NotStronger,
-                    "not_weaker" => Token::NotWeaker,
-                    "with" => Token::With,
-                    "or" => Token::Or,
-                    "no_foam" => Token::NoFoam,
-                    "blend_with" => Token::BlendWith,
-                    "top_with" => Token::TopWith,
-                    "spice" => Token::Spice,
-                    "invert" => Token::Invert,
-                    "double_shot" => Token::DoubleShot,
-                    "half_caf" => Token::HalfCaf,
-                    "pour_in" => Token::PourIn,
-                    "serve_back" => Token::ServeBack,
-                    "true" => Token::Identifier("true".to_string()),
-                    "false" => Token::Identifier("false".to_string()),
-                    _ => Token::Identifier(ident),
-                });
             }
-            _ => { chars.next(); }
+        }
+        None
+    }
+}
+
+impl<'a> Iterator for CoffeeTokenStream<'a> {
+    type Item = SpannedToken;
+
+    fn next(&mut self) -> Option<SpannedToken> {
+        match self.peeked.pop_front() {
+            Some(token) => Some(token),
+            None => self.brew_next_token(),
         }
     }
-    tokens
 }
 
 /*
- * Coffee-Themed Token System 
+ * Coffee-Themed Token System
  * @designer: Khushi Motwani
  * @mood: Absolutely delighted ☕✨
- * 
+ *
  * Each token name was chosen with so much love!
  * "beans" for variables? Genius! (if I say so myself 😅)
- * "pourout" for print? Adorable! 
+ * "pourout" for print? Adorable!
  * "brew" for functions? Perfect! ☕
- * 
- * @khushi_confession: I spent way too much time naming these 
+ *
+ * @khushi_confession: I spent way too much time naming these
  * but regret NOTHING! 💖
  */
 
@@ -351,10 +755,10 @@ pub fn lex(input: &str) -> Vec<Token> {
  * Emoji Comment Support (🎀)
  * @innovation: Khushi Motwani's special touch
  * @why: "Because why not make comments prettier?" ✨
- * 
+ *
  * Yes, I made emoji comments a thing in my language!
  * 🎀 looks so cute and makes code documentation beautiful
- * 
+ *
  * This was probably my favorite feature to implement! ☕💖
  * - Khushi
  */
@@ -362,12 +766,12 @@ pub fn lex(input: &str) -> Vec<Token> {
 /*
  * @khushi_final_thoughts:
  * The lexer is like the first impression of a language
- * I wanted Brewco's first impression to be warm, 
+ * I wanted Brewco's first impression to be warm,
  * welcoming, and full of coffee love! ☕
- * 
- * Hope every developer who uses this feels the joy 
+ *
+ * Hope every developer who uses this feels the joy
  * I felt creating it! ✨
- * 
+ *
  * Keep tokenizing with love! 💖
- * - Khushi Motwani 
+ * - Khushi Motwani
  */
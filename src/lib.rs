@@ -0,0 +1,82 @@
+/*
+ * ☕ Brewco Library ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @pantry_note: "Everything brew can do, on a shelf other programs can reach!" ✨
+ *
+ * The lexer, parser, interpreter and tooling behind the `brew` command, as
+ * a library so other programs can embed Brewco: parse a recipe with
+ * `parser::parse_source`, brew it on an `interpreter::Interpreter`, then
+ * read its globals or call its brews. brewco-ffi/ wraps this in a C ABI
+ * and brewco-wasm/ in a browser module; the wasm32 build leaves out the
+ * REPL, server, ledger, plugins and notebook kernel, which need a real
+ * machine.
+ */
+
+// src/lib.rs
+
+// Brews hand ControlFlow (and the Value a brew serves) back through every `?`; boxing
+// it to shrink the Err side would cost an allocation on each serve, break and spill
+#![allow(clippy::result_large_err)]
+
+pub mod ast;
+pub mod lexer;
+pub mod parser;
+pub mod interpreter;
+pub mod native;
+pub mod type_checker;
+pub mod espresso_errors;          // The Barista's Wisdom System ☕
+pub mod coffee_io;                // The Coffee Import/Export System ☕
+pub mod coffee_bean_roastery;     // The Coffee Bean Import & Roastery System ☕
+pub mod coffee_package_roastery;  // The Coffee Bean Package Roastery Supply Chain ☕
+pub mod barista_language_server;  // The Barista Language Server & Coffee Shop Assistant ☕
+pub mod barista_debug_adapter;    // The Barista Debug Adapter for DAP editors ☕
+pub mod turbo_espresso_compiler;  // The Turbo Espresso Brewing Engine ☕
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(dead_code)] // Scaffolding: its registries are filled in, but nothing reads most of them back yet
+pub mod gourmet_coffee_features;  // The Gourmet Coffee Blending System ☕
+pub mod cupping_bench;            // The Cupping Bench for timing bench_brew blocks ☕
+pub mod brew_profiler;            // The Brew Profiler for finding hotspots ☕
+pub mod brew_tracer;              // The Brew Tracer for narrating execution ☕
+pub mod brew_debugger;            // The Brew Debugger for pausing and stepping ☕
+pub mod brew_formatter;           // The Brew Formatter for tidy recipes ☕
+pub mod brew_linter;              // The Brew Linter for style and correctness checks ☕
+pub mod taste_test_runner;        // The Taste Test Runner for taste_test blocks ☕
+#[cfg(not(target_arch = "wasm32"))]
+pub mod coffee_shop_repl;         // The Coffee Shop REPL for interactive brewing ☕
+pub mod brew_cli;                 // The Brew CLI for reading command line orders ☕
+pub mod coffee_formats;           // The Coffee Formats for TOML, YAML and CSV ☕
+pub mod brew_clock;               // The BrewClock for dates, times and durations ☕
+#[cfg(not(target_arch = "wasm32"))]
+pub mod coffee_shop_server;       // The Coffee Shop Server for tiny web services ☕
+#[cfg(not(target_arch = "wasm32"))]
+pub mod coffee_ledger;            // The Coffee Ledger for SQLite storage ☕
+pub mod flavor_set;               // The Flavor Sets for values kept once each ☕
+pub mod coffee_chain;             // The Coffee Chains for queues and stacks ☕
+pub mod brew_blends;              // The Brew Blends for memoized, composed and partial brews ☕
+pub mod brew_sequence;            // The Brew Sequences for generators and streamed lines ☕
+pub mod big_brew;                 // The Big Brews for exact decimal arithmetic ☕
+pub mod coffee_terminal;          // The Coffee Terminal for colors, cursor control and progress bars ☕
+pub mod roastery_config;          // The Roastery Config for brewco.toml project defaults ☕
+pub mod brew_watch;               // The Brew Watch for brewing again on every save ☕
+pub mod brew_doc;                 // The Menu Board for brew doc documentation pages ☕
+pub mod brew_inspect;             // The Cupping Lens for brew inspect token and syntax tree dumps ☕
+pub mod brewco_codegen;           // The Recipe Printer that writes a syntax tree back out as source ☕
+#[cfg(not(target_arch = "wasm32"))]
+pub mod coffee_plugins;           // The Native Plugins that bring natives in from dynamic libraries ☕
+pub mod native_registry;          // The Native Registry every interpreter looks natives up in ☕
+pub mod pantry_scale;             // The Pantry Scale that weighs live values for pantry_memory() ☕
+pub mod grounds_collector;        // The Grounds Collector that empties cycles of cups and beans nothing reaches ☕
+pub mod bean_tree;                // The Bean Tree behind brew tree and brew why ☕
+pub mod coffee_serde;             // The Serde Pour for sending values through serde formats ☕
+#[cfg(not(target_arch = "wasm32"))]
+pub mod brew_kernel;              // The Notebook Kernel that brews Jupyter cells ☕
+#[cfg(not(target_arch = "wasm32"))]
+pub mod brew_learn;               // The Barista School that teaches Brewco lesson by lesson at the REPL ☕
+pub mod brew_literate;            // The Literate Recipes that brew the code blocks of Markdown files ☕
+#[cfg(not(target_arch = "wasm32"))]
+pub mod brew_playground;          // The Playground that brews strangers' recipes in a sandbox over HTTP ☕
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bean_audit;               // The Bean Audit that checks installed beans against roastery.lock and advisories ☕
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bean_registry;            // The Bean Registry a team serves its own beans from, and brew's side of it ☕
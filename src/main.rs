@@ -12,132 +12,109 @@
 
 // src/main.rs
 
-mod ast;
-mod lexer;
-mod parser;
-mod interpreter;
-mod native;
-mod type_checker;
-mod espresso_errors; // The Barista's Wisdom System ☕
-mod coffee_io;       // The Coffee Import/Export System ☕
-mod coffee_bean_roastery;    // The Coffee Bean Import & Roastery System ☕
-mod coffee_package_roastery; // The Coffee Bean Package Roastery Supply Chain ☕
-mod barista_language_server; // The Barista Language Server & Coffee Shop Assistant ☕
-mod turbo_espresso_compiler; // The Turbo Espresso Brewing Engine ☕
-mod gourmet_coffee_features; // The Gourmet Coffee Blending System ☕
+use brewco::{
+    ast, parser, interpreter, type_checker, espresso_errors, coffee_package_roastery,
+    barista_language_server, barista_debug_adapter, turbo_espresso_compiler, cupping_bench,
+    brew_tracer, brew_debugger, brew_formatter, brew_linter, taste_test_runner, coffee_shop_repl,
+    brew_cli, roastery_config, brew_watch, brew_doc, brew_inspect, coffee_plugins, brew_kernel,
+    brew_playground, brew_literate, grounds_collector, bean_tree, bean_audit, bean_registry
+};
 
 use std::env;
-use std::io::{self, Write};
-use espresso_errors::CoffeeSpillReport;
+use std::path::{Path, PathBuf};
+use brew_cli::{BrewCommand, BrewEngine, RecipeSource};
 
-fn print_cli_help() {
-    println!("☕ Brewco CLI - Your Personal Coffee Shop Compiler ☕");
-    println!("Usage:");
-    println!("  brew <filename.brewco>   Brew a Brewco program");
-    println!("  brew repl              Start interactive coffee shop");
-    println!("  brew --help             Show this help message");
-    println!("  brew --version          Show version information");
-    println!("If no file is given, defaults to 'hello.brewco'.");
-    println!("\n💡 Pro tip: Use .brewco extension for your coffee recipes!");
-}
+/// The help in English; a language's catalog can swap in its own under "cli.help"
+const BREW_HELP: &str = "\
+☕ Brewco CLI - Your Personal Coffee Shop Compiler ☕
+Usage:
+  brew <filename.brewco> [-- args...]
+                          Brew a Brewco program; anything after -- is passed to it
+  brew <roastery dir>     Brew a roastery's entry recipe (\"entry_recipe\" in its roastery.json, or main.brewco),
+                          grinding modules from the entry's directory
+  brew -e \"<code>\"         Brew a one-liner, like brew -e 'pourout 2 add 3'
+  brew - [-- args...]     Brew a recipe piped in on stdin, like cat gen.brewco | brew -
+  brew <file> --call <brew> [-- args...]
+                          Brew a recipe, then call one of its brews with the args (as strings)
+                          and pour out what it serves
+  brew repl              Start interactive coffee shop
+  brew debug <file> [--break LINE]...
+                          Step through a recipe with breakpoints and a pantry view
+  brew check [file|dir]...
+                          Lex, parse and type-check recipes without brewing them
+  brew lint [--config brewlint.toml] [file|dir]...
+                          Check for unused or shadowed beans, dead code and empty blocks
+  brew test [--update-snapshots] [--watch] [file|dir]...
+                          Run taste_test blocks in *_test.brewco recipes, each on a fresh interpreter
+  brew fmt [--check] [file|dir]...
+                          Re-pour recipes in the canonical style (--check only reports)
+  brew lsp                Serve the Language Server Protocol over stdio for editors
+  brew dap [--port N] [file]
+                          Serve the Debug Adapter Protocol for editors (default port 4711); with a
+                          recipe, it waits for an editor to attach before brewing it
+  brew bench <file> [--iterations N] [--warmup N]
+                          Time bench_brew blocks on the interpreter and turbo
+  brew explain [BRWnnnn]  Explain a spill code with an example and its fix (no code lists them all)
+  brew watch <file> [-- args...]
+                          Brew a recipe, then again on every save of it or the modules it grinds in
+  brew new <name> [--template basic|bean|advanced|library|cli]
+                          Start a roastery: manifest, brewco.toml, main.brewco, a sample test and .gitignore
+  brew doc [--format markdown|html] [--out FILE] [file|dir]...
+                          List every brew and bean with signatures, 🎀🎀 docs and examples
+  brew tree               Show the roastery's dependency tree with the installed version of each bean
+  brew why <bean>         Show which of the roastery's dependencies pull a bean in, and through what
+  brew audit              Check installed beans against roastery.lock's checksums and the registry's advisories
+  brew publish            Send the roastery to the registry as a bean (BREWCO_REGISTRY_TOKEN holds the token)
+  brew registry serve [--port N] [--store DIR] [--private]
+                          Serve a registry of the beans in DIR to publish to and install from
+                          (default port 4873, store brewco_registry; --private wants a token to read too)
+  brew registry token <barista> [--store DIR]
+                          Make a token a barista publishes to the registry with
+  brew inspect --tokens|--ast|--source [--json] <file>
+                          Show a recipe's tokens or syntax tree, as JSON for other tools with --json,
+                          or the source printed back from the tree
+  brew kernel <connection-file> | --install
+                          Serve a Jupyter notebook (Jupyter starts this), or register the Brewco kernel with it
+  brew playground [--port N] [--fuel N]
+                          Serve a web editor and POST /eval, brewing each recipe in a fuel-limited sandbox
+                          (default port 7878, 100000 steps of fuel)
+  brew learn [lesson]     Learn Brewco lesson by lesson at the REPL (progress is kept in ~/.brewco_lessons.json)
+  brew literate [--check] <file.md>...
+                          Brew the ```brewco blocks of Markdown files in order on one interpreter,
+                          checking any ```output block after one (--check only reports)
+Brewing flags (go before or after the recipe):
+  --turbo                 Brew on the Turbo Espresso bytecode machine
+  --profile [--folded out.folded]
+                          Brew and print per-brew timings and call counts
+  --trace[=1|2|3]         Narrate statements (1), values (2) and brew calls (3) to stderr
+  --allow-fs              Let the recipe read and write files (sip_file, pour_to_file, ...)
+  --allow-exec            Let the recipe run shell commands (brew_shell, brew_shell_live)
+  --allow-net             Let the recipe serve HTTP (open_coffee_shop)
+  --strict-brew           Spill on string + number instead of joining them as text
+  --deny-warnings         Refuse to brew (or fail `brew check`) when the checker has warnings
+  --lang <code>           Tell spills and this help in another language: en or es (or set BREWCO_LANG)
+  -h, --help              Show this help message
+  -V, --version           Show version information
+With no arguments, brew opens the interactive coffee shop.
 
-fn start_repl() {
-    println!("☕ Welcome to the Interactive Brewco Coffee Shop! ☕");
-    println!("🏪 Where every line of code is brewed to perfection!");
-    println!("Type 'exit', 'quit', or 'enough_caffeine' to leave");
-    println!("Type 'help' or 'barista_help' for brewing commands");
-    println!("================================================");
-    
-    let mut coffee_interpreter = interpreter::Interpreter::new();
-    let mut brewing_session = 1;
-    
-    loop {
-        print!("☕ Coffee Shop #{} > ", brewing_session);
-        io::stdout().flush().unwrap();
-        
-        let mut coffee_input = String::new();
-        match io::stdin().read_line(&mut coffee_input) {
-            Ok(_) => {
-                let brewing_command = coffee_input.trim();
-                
-                // Special REPL commands with coffee flair
-                match brewing_command {
-                    "exit" | "quit" | "enough_caffeine" => {
-                        println!("☕ Thanks for visiting our Coffee Shop!");
-                        println!("🌟 May your code be bug-free and your coffee strong!");
-                        break;
-                    },
-                    "help" | "barista_help" => {
-                        println!("☕ Coffee Shop Commands:");
-                        println!("  beans var = value     Declare a new coffee bean variable");
-                        println!("  pourout expression    Display the aroma of an expression");
-                        println!("  clear_counter         Clear the coffee shop counter");
-                        println!("  show_pantry          Show all declared coffee beans");
-                        println!("  brewing_history      Show recent brewing commands");
-                        continue;
-                    },
-                    "clear_counter" => {
-                        coffee_interpreter = interpreter::Interpreter::new();
-                        println!("☕ Coffee shop counter cleared! Fresh start brewing...");
-                        continue;
-                    },
-                    "show_pantry" => {
-                        println!("☕ Current Coffee Bean Pantry:");
-                        println!("   📦 Variables are stored in the coffee interpreter's private pantry!");
-                        println!("   💡 Try declaring some: beans my_var pour_in 42");
-                        continue;
-                    },
-                    "" => continue, // Empty input
-                    _ => {} // Process as Brewco code
-                }
-                
-                // Tokenize and parse the coffee input
-                let coffee_tokens = lexer::lex(brewing_command);
-                let brewing_result = parser::parse(&coffee_tokens);
-                
-                // Handle any coffee spills (errors)
-                if !brewing_result.errors.is_empty() {
-                    for brewing_error in &brewing_result.errors {
-                        let spill_report = CoffeeSpillReport::new_brewing_disaster(
-                            espresso_errors::SpillType::IncompleteRecipe,
-                            1, 1, // REPL line numbers
-                            brewing_error
-                        );
-                        println!("{}", spill_report);
-                    }
-                } else {
-                    // Execute the brewing instructions
-                    coffee_interpreter.run(&brewing_result.statements);
-                }
-                
-                brewing_session += 1;
-            },
-            Err(brewing_error) => {
-                let input_spill = CoffeeSpillReport::new_brewing_disaster(
-                    espresso_errors::SpillType::UnderExtraction,
-                    brewing_session, 1,
-                    &format!("Failed to read coffee input: {}", brewing_error)
-                );
-                println!("{}", input_spill);
-                break;
-            }
-        }
-    }
+💡 Pro tip: Use .brewco extension for your coffee recipes!
+";
+
+fn print_cli_help() {
+    print!("{}", espresso_errors::localized("cli.help", BREW_HELP));
 }
 
-fn run_file(filename: &str) {
-    let code = match std::fs::read_to_string(filename) {
+/// Read, parse and type-check a recipe the way every run mode brews it, leaving with 1 on a
+/// spill (or on a warning under --deny-warnings); serves the source and its statements
+fn load_recipe(source: &RecipeSource, deny_warnings: bool) -> (String, Vec<ast::Statement>) {
+    let code = match source.read() {
         Ok(c) => c,
-        Err(_) => {
-            println!("[ERROR] Could not read file: {}", filename);
+        Err(msg) => {
+            println!("[ERROR] {}", msg);
             std::process::exit(1);
         }
     };
-    let tokens = lexer::lex(&code);
-    // For debugging:
-    // println!("[DEBUG] Tokens: {:#?}", tokens);
-
-    let stmts = parser::parse(&tokens);
+    let stmts = parser::parse_source(&code);
     if !stmts.errors.is_empty() {
         println!("☕ Oops! Looks like your coffee script hit a sour note:");
         for err in stmts.errors {
@@ -147,10 +124,7 @@ fn run_file(filename: &str) {
         std::process::exit(1);
     }
 
-    // For debugging:
-    // println!("[DEBUG] AST: {:#?}", stmts.statements);
-
-    let mut type_checker = type_checker::TypeChecker::new();
+    let mut type_checker = type_checker::TypeChecker::new().with_strict_brew(interpreter::strict_brew());
     if let Err(errors) = type_checker.check(&stmts.statements) {
         println!("☕ Your coffee isn't fresh! The Freshness Checker found these issues:");
         for err in errors {
@@ -158,35 +132,350 @@ fn run_file(filename: &str) {
         }
         std::process::exit(1);
     }
+    // Warnings go to stderr so they don't get mixed into what the recipe pours out
+    let warnings = type_checker.warnings();
+    if !warnings.is_empty() {
+        eprintln!("☕ The Freshness Checker has a few notes:");
+        for warning in warnings {
+            eprintln!("  - line {}, column {}: {}", warning.line, warning.column, warning.message);
+        }
+        if deny_warnings {
+            eprintln!("☕ Warnings are denied (--deny-warnings), so this won't brew.");
+            std::process::exit(1);
+        }
+    }
+    (code, stmts.statements)
+}
+
+fn run_file(source: &RecipeSource, deny_warnings: bool, call: Option<&str>) {
+    let (code, statements) = load_recipe(source, deny_warnings);
+    let mut interpreter = interpreter::Interpreter::new();
+    let outcome = interpreter.run(&statements);
+    settle_brew(&interpreter, outcome, source, &code);
+    // --call: the recipe has set its pantry up, now one of its brews gets the order
+    if let Some(brew) = call {
+        match interpreter.get_global(brew) {
+            Some(found) if found.is_brew() => {}
+            Some(found) => {
+                println!("[ERROR] --call {}: that's a {} in {}, not a brew", brew, found.type_name(), source.label());
+                std::process::exit(1);
+            }
+            None => {
+                println!("[ERROR] --call {}: {} has no brew by that name", brew, source.label());
+                std::process::exit(1);
+            }
+        }
+        let args = interpreter::script_args().iter().map(|arg| interpreter::Value::String(arg.clone())).collect();
+        match interpreter.call_function_flow(brew, args) {
+            Ok(interpreter::Value::Null) => {}
+            Ok(served) => {
+                // Nothing is catching pours here, so this goes to stdout and can't spill
+                let _ = interpreter::pour_out(&served, ast::PourSpout::Line);
+            }
+            Err(interpreter::ControlFlow::FinishService(status)) => finish_service(status),
+            Err(flow) => report_uncaught(interpreter.escaped_call_report(brew, flow), source, &code),
+        }
+    }
+}
+
+/// Make Ctrl-C ask the interpreter to stop at its next statement instead of killing the
+/// process; a second Ctrl-C before the first is noticed still exits
+fn install_interrupt_handler() {
+    let installed = ctrlc::set_handler(|| {
+        if interpreter::request_interrupt() {
+            std::process::exit(130);
+        }
+    });
+    if let Err(e) = installed {
+        eprintln!("[Coffee Warning] Could not catch Ctrl-C, it will stop the whole shop: {}", e);
+    }
+}
+
+/// End the recipe the way its top level ended: with finish_service's status, or on a spill
+/// nobody caught; a recipe that brewed to the end carries on
+fn settle_brew(interpreter: &interpreter::Interpreter, outcome: Result<(), interpreter::ControlFlow>, source: &RecipeSource, code: &str) {
+    match outcome {
+        Err(interpreter::ControlFlow::FinishService(status)) => finish_service(status),
+        Err(interpreter::ControlFlow::RuntimeError(msg)) => spill_uncaught(interpreter, &msg, source, code),
+        _ => {}
+    }
+}
+
+/// `finish_service(status)` reached the top: flush what was poured and close the shop
+fn finish_service(status: i32) -> ! {
+    use std::io::Write;
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stderr().flush();
+    std::process::exit(status);
+}
+
+/// End the recipe on a spill nobody caught, so CI sees a failing status: Ctrl-C leaves
+/// with 130, anything else with a full spill report on stderr and 1
+fn spill_uncaught(interpreter: &interpreter::Interpreter, msg: &str, source: &RecipeSource, code: &str) -> ! {
+    report_uncaught(interpreter.spill_report(msg), source, code)
+}
+
+fn report_uncaught(mut report: espresso_errors::CoffeeSpillReport, source: &RecipeSource, code: &str) -> ! {
+    if report.bitter_message.starts_with(interpreter::INTERRUPTED_BREW) {
+        eprintln!("☕ {}", report.bitter_message);
+        std::process::exit(130);
+    }
+    report.frame_in_source(source.label(), code);
+    eprint!("{}", report.brew_detailed_report());
+    std::process::exit(1);
+}
+
+fn run_file_profiled(source: &RecipeSource, deny_warnings: bool, folded_path: Option<&str>) {
+    let (code, statements) = load_recipe(source, deny_warnings);
+    let mut interpreter = interpreter::Interpreter::new();
+    interpreter.enable_profiler();
+    let outcome = interpreter.run(&statements);
+    if let Some(profile) = interpreter.finish_profile() {
+        println!();
+        print!("{}", profile.brew_report());
+        if let Some(path) = folded_path {
+            match std::fs::write(path, profile.folded_output()) {
+                Ok(_) => println!("🔥 Folded stacks written to {} (feed them to flamegraph.pl or inferno)", path),
+                Err(e) => println!("[ERROR] Could not write folded stacks to {}: {}", path, e),
+            }
+        }
+    }
+    settle_brew(&interpreter, outcome, source, &code);
+}
+
+fn run_file_traced(source: &RecipeSource, deny_warnings: bool, verbosity: u8) {
+    let (code, statements) = load_recipe(source, deny_warnings);
+    let mut interpreter = interpreter::Interpreter::new();
+    interpreter.enable_tracer(brew_tracer::BrewTracer::new_tracer(verbosity, source.label(), &code));
+    let outcome = interpreter.run(&statements);
+    settle_brew(&interpreter, outcome, source, &code);
+}
 
+fn run_file_debug(source: &RecipeSource, deny_warnings: bool, breakpoints: &[usize]) {
+    let (code, statements) = load_recipe(source, deny_warnings);
+    println!("🐞 Debugging '{}'. Type 'help' at the (brew-debug) prompt for commands.", source.label());
     let mut interpreter = interpreter::Interpreter::new();
-    interpreter.run(&stmts.statements);
+    interpreter.set_debug_hook(Box::new(brew_debugger::ConsoleBrewDebugger::new_console_debugger(source.label(), &code, breakpoints)));
+    let outcome = interpreter.run(&statements);
+    settle_brew(&interpreter, outcome, source, &code);
+    println!("☕ Recipe finished brewing.");
+}
+
+/// `brew check`: lex, parse and type-check without brewing anything.
+/// Exits with the number of spills found (capped at 125, since shells claim the codes above).
+/// Warnings only count as spills with --deny-warnings.
+fn run_check(paths: &[String], deny_warnings: bool) {
+    let roots = if paths.is_empty() { vec![".".to_string()] } else { paths.to_vec() };
+    let mut files = 0;
+    let mut spills = 0;
+    let mut warnings = 0;
+    for path in roots.iter().flat_map(|root| brew_formatter::coffee_files_under(std::path::Path::new(root))) {
+        files += 1;
+        let shown = path.display();
+        let code = match std::fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(_) => {
+                println!("[ERROR] Could not read file: {}", shown);
+                spills += 1;
+                continue;
+            }
+        };
+        let stmts = parser::parse_source(&code);
+        for (err, (line, column)) in stmts.errors.iter().zip(&stmts.error_positions) {
+            println!("{}:{}:{}: syntax spill: {}", shown, line, column, err);
+        }
+        spills += stmts.errors.len();
+        if !stmts.errors.is_empty() {
+            continue;
+        }
+
+        let mut type_checker = type_checker::TypeChecker::new().with_strict_brew(interpreter::strict_brew());
+        if let Err(errors) = type_checker.check(&stmts.statements) {
+            for (err, (line, column)) in errors.iter().zip(type_checker.error_positions()) {
+                println!("{}:{}:{}: type spill: {}", shown, line, column, err);
+            }
+            spills += errors.len();
+        }
+        for warning in type_checker.warnings() {
+            println!("{}:{}:{}: warning: {}", shown, warning.line, warning.column, warning.message);
+        }
+        warnings += type_checker.warnings().len();
+    }
+
+    if deny_warnings {
+        spills += warnings;
+    }
+    match (spills, warnings) {
+        (0, 0) => println!("☕ {} recipe(s) checked, all fresh!", files),
+        (0, _) => println!("☕ {} recipe(s) checked, fresh with {} warning(s)", files, warnings),
+        _ => println!("☕ {} recipe(s) checked, {} spill(s) found", files, spills),
+    }
+    std::process::exit(spills.min(125) as i32);
+}
+
+fn run_file_turbo(source: &RecipeSource, deny_warnings: bool, opt_level: turbo_espresso_compiler::OptimizationLevel) {
+    let (code, statements) = load_recipe(source, deny_warnings);
+    let mut turbo_compiler = turbo_espresso_compiler::TurboEspressoCompiler::new_turbo_brewing_engine().with_optimization_level(opt_level);
+    let compiled = match turbo_compiler.brew_turbo_compilation(&statements, &code, source.label()) {
+        Ok(compiled) => compiled,
+        Err(spill) => {
+            println!("{}", spill);
+            std::process::exit(1);
+        }
+    };
+
+    let mut espresso_machine = turbo_espresso_compiler::EspressoBrewingMachine::new_brewing_machine();
+    if let Err(spill) = espresso_machine.brew_compiled_recipe(&compiled) {
+        println!("{}", spill);
+        std::process::exit(1);
+    }
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    
-    if args.len() == 1 {
-        // No arguments - start REPL
-        start_repl();
-        return;
-    }
-    
-    let command = &args[1];
-    
-    match command.as_str() {
-        "help" => {
-            print_cli_help();
-            return;
-        }
-        "repl" => {
-            start_repl();
-            return;
-        }
-        _ => {
-            // Treat as filename
-            run_file(command);
+    let mut invocation = match brew_cli::parse_brew_args(env::args().collect()) {
+        Ok(invocation) => invocation,
+        Err(msg) => {
+            println!("[ERROR] {}", msg);
+            println!("☕ Run 'brew --help' to see every order the counter takes.");
+            std::process::exit(1);
+        }
+    };
+    // `brew <dir>` brews the roastery's entry recipe, grinding modules from beside it
+    let roastery_dir = match &mut invocation.command {
+        BrewCommand::Run { source: RecipeSource::File(path), .. } | BrewCommand::Debug { source: RecipeSource::File(path), .. }
+            if Path::new(path).is_dir() =>
+        {
+            let roastery_dir = PathBuf::from(&*path);
+            match coffee_package_roastery::roastery_entry(&roastery_dir) {
+                Ok(entry) => {
+                    interpreter::set_module_root(entry.parent().map_or_else(|| roastery_dir.clone(), Path::to_path_buf));
+                    *path = entry.to_string_lossy().into_owned();
+                    Some(roastery_dir)
+                }
+                Err(msg) => {
+                    println!("[ERROR] {}", msg);
+                    std::process::exit(1);
+                }
+            }
+        }
+        _ => None,
+    };
+    // brewco.toml sets the roastery's defaults; flags on the command line add to them
+    let roastery = match roastery_config::load_roastery_config(roastery_dir.as_deref()) {
+        Ok(roastery) => roastery,
+        Err(msg) => {
+            println!("[ERROR] {}", msg);
+            std::process::exit(1);
+        }
+    };
+    if let Err(msg) = espresso_errors::choose_language(invocation.lang.as_deref().or(roastery.lang.as_deref())) {
+        println!("[ERROR] {}", msg);
+        std::process::exit(1);
+    }
+    grounds_collector::set_memory_model(&roastery.memory_model);
+    if invocation.strict_brew || roastery.strict_brew {
+        interpreter::enable_strict_brew();
+    }
+    // The playground brews strangers' recipes, so brewco.toml's [allow] doesn't open it up any more than the flags do
+    let roastery_grants = !matches!(invocation.command, BrewCommand::Playground { .. });
+    if invocation.allow_fs || (roastery_grants && roastery.allow_fs) {
+        interpreter::allow_fs_access();
+    }
+    if invocation.allow_exec || (roastery_grants && roastery.allow_exec) {
+        interpreter::allow_exec_access();
+    }
+    if invocation.allow_net || (roastery_grants && roastery.allow_net) {
+        interpreter::allow_net_access();
+    }
+    let deny_warnings = invocation.deny_warnings || roastery.deny_warnings;
+    // Only commands that brew recipes need the roastery's native plugins
+    let brews_recipes = matches!(
+        invocation.command,
+        BrewCommand::Repl | BrewCommand::Learn { .. } | BrewCommand::Run { .. } | BrewCommand::Debug { .. } | BrewCommand::Test { watch: false, .. }
+            | BrewCommand::Dap { .. } | BrewCommand::Bench { .. } | BrewCommand::Kernel { .. }
+            | BrewCommand::Literate { .. }
+    );
+    if brews_recipes {
+        if let Err(msg) = coffee_plugins::load_roastery_plugins(&roastery.root, &roastery.plugins) {
+            println!("[ERROR] {}", msg);
+            std::process::exit(1);
+        }
+    }
+    interpreter::set_script_args(invocation.script_args);
+    // Ctrl-C interrupts what's brewing (or closes the server) instead of killing the process
+    let interruptible = matches!(
+        invocation.command,
+        BrewCommand::Repl | BrewCommand::Learn { .. } | BrewCommand::Test { watch: false, .. } | BrewCommand::Kernel { .. }
+            | BrewCommand::Playground { .. } | BrewCommand::Literate { .. } | BrewCommand::RegistryServe { .. }
+            | BrewCommand::Run { engine: BrewEngine::Interpreter | BrewEngine::Profile { .. } | BrewEngine::Trace(_), .. }
+    );
+    if interruptible {
+        install_interrupt_handler();
+    }
+
+    match invocation.command {
+        BrewCommand::Help => print_cli_help(),
+        BrewCommand::Version => println!("☕ Brewco {}", env!("CARGO_PKG_VERSION")),
+        BrewCommand::Repl => coffee_shop_repl::start_repl(),
+        BrewCommand::Learn { lesson } => coffee_shop_repl::start_learning(lesson.as_deref()),
+        BrewCommand::Run { source, engine, call } => match engine {
+            BrewEngine::Interpreter => run_file(&source, deny_warnings, call.as_deref()),
+            BrewEngine::Turbo => run_file_turbo(&source, deny_warnings, roastery.opt_level),
+            BrewEngine::Profile { folded } => run_file_profiled(&source, deny_warnings, folded.as_deref()),
+            BrewEngine::Trace(verbosity) => run_file_traced(&source, deny_warnings, verbosity),
+        },
+        BrewCommand::Debug { source, breakpoints } => run_file_debug(&source, deny_warnings, &breakpoints),
+        BrewCommand::Check { paths } => run_check(&paths, deny_warnings),
+        BrewCommand::Lint { paths, config } => brew_linter::run_brew_lint(&paths, config.as_deref(), &roastery.lint),
+        BrewCommand::Test { paths, watch: true, .. } => {
+            let roots = if paths.is_empty() { vec![".".to_string()] } else { paths };
+            let order = brew_watch::order_without_watch(&env::args().skip(1).collect::<Vec<_>>());
+            brew_watch::run_brew_watch(order, || {
+                roots.iter().flat_map(|root| taste_test_runner::taste_test_files(std::path::Path::new(root))).collect()
+            });
+        }
+        BrewCommand::Test { paths, update_snapshots, .. } => taste_test_runner::run_brew_test(&paths, update_snapshots),
+        BrewCommand::Fmt { paths, check } => brew_formatter::run_brew_fmt(&paths, check, roastery.fmt_width),
+        BrewCommand::Lsp => barista_language_server::run_language_server(),
+        BrewCommand::Dap { port, program } => barista_debug_adapter::run_debug_adapter(port, program),
+        BrewCommand::Bench { file, settings } => cupping_bench::run_cupping_session(&file, &settings),
+        BrewCommand::Watch { file } => {
+            let order = brew_watch::order_without_watch(&env::args().skip(1).collect::<Vec<_>>());
+            brew_watch::run_brew_watch(order, || vec![std::path::PathBuf::from(&file)]);
+        }
+        BrewCommand::New { name, template } => {
+            let head_barista = env::var("USER").unwrap_or_else(|_| "Coffee Enthusiast".to_string());
+            let description = "A new Brewco roastery project";
+            if let Err(spill) = coffee_package_roastery::CoffeeBeanPackageRoastery::brew_new_roastery(std::path::Path::new(&name), &template, &head_barista, description) {
+                println!("[ERROR] {}", spill.bitter_message);
+                std::process::exit(1);
+            }
         }
+        BrewCommand::Doc { paths, format, out } => brew_doc::run_brew_doc(&paths, format, out.as_deref()),
+        BrewCommand::Inspect { source, view, json } => match source.read() {
+            Ok(code) => brew_inspect::run_brew_inspect(&code, source.label(), view, json),
+            Err(msg) => {
+                println!("[ERROR] {}", msg);
+                std::process::exit(1);
+            }
+        },
+        BrewCommand::Kernel { connection_file } => brew_kernel::run_brew_kernel(&connection_file),
+        BrewCommand::KernelInstall => brew_kernel::install_kernelspec(),
+        BrewCommand::Playground { port, fuel } => brew_playground::run_brew_playground(port, fuel),
+        BrewCommand::Literate { paths, check } => brew_literate::run_brew_literate(&paths, check),
+        BrewCommand::Tree => bean_tree::run_brew_tree(),
+        BrewCommand::Why { bean } => bean_tree::run_brew_why(&bean),
+        BrewCommand::Audit => bean_audit::run_brew_audit(),
+        BrewCommand::Publish => bean_registry::run_brew_publish(),
+        BrewCommand::RegistryServe { port, store, private } => bean_registry::run_registry_serve(port, &store, private),
+        BrewCommand::RegistryToken { barista, store } => bean_registry::run_registry_token(&store, &barista),
+        BrewCommand::Explain { code } => match espresso_errors::explain_spill(code.as_deref()) {
+            Ok(story) => print!("{}", story),
+            Err(msg) => {
+                println!("[ERROR] {}", msg);
+                std::process::exit(1);
+            }
+        },
     }
 }
 
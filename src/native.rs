@@ -14,13 +14,16 @@
 // src/native.rs
 
 use crate::interpreter::{Value, ControlFlow};
+use crate::lexer::{CoffeeTokenStream, SpannedToken, Token};
+use std::collections::HashMap;
+use std::io::Write;
 
 pub fn root_drip(args: Vec<Value>) -> Result<Value, ControlFlow> {
     if args.len() != 1 {
         return Err(ControlFlow::RuntimeError(format!("root_drip() expects 1 argument, but got {}", args.len())));
     }
 
-    match args.get(0).unwrap() {
+    match args.first().unwrap() {
         Value::Number(n) => {
             if *n < 0.0 {
                 Err(ControlFlow::RuntimeError("Cannot take the square root of a negative number.".to_string()))
@@ -37,7 +40,7 @@ pub fn absolute_aroma(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("absolute_aroma() expects 1 argument, but got {}", args.len())));
     }
 
-    match args.get(0).unwrap() {
+    match args.first().unwrap() {
         Value::Number(n) => Ok(Value::Number(n.abs())),
         _ => Err(ControlFlow::RuntimeError("absolute_aroma() expects a number as an argument.".to_string())),
     }
@@ -48,7 +51,7 @@ pub fn round_up_the_grounds(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("round_up_the_grounds() expects 1 argument, but got {}", args.len())));
     }
 
-    match args.get(0).unwrap() {
+    match args.first().unwrap() {
         Value::Number(n) => Ok(Value::Number(n.ceil())),
         _ => Err(ControlFlow::RuntimeError("round_up_the_grounds() expects a number as an argument.".to_string())),
     }
@@ -59,7 +62,7 @@ pub fn settle_the_grounds(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("settle_the_grounds() expects 1 argument, but got {}", args.len())));
     }
 
-    match args.get(0).unwrap() {
+    match args.first().unwrap() {
         Value::Number(n) => Ok(Value::Number(n.floor())),
         _ => Err(ControlFlow::RuntimeError("settle_the_grounds() expects a number as an argument.".to_string())),
     }
@@ -70,7 +73,7 @@ pub fn extra_shot(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("extra_shot() expects 2 arguments, but got {}", args.len())));
     }
 
-    let base = match args.get(0).unwrap() {
+    let base = match args.first().unwrap() {
         Value::Number(n) => n,
         _ => return Err(ControlFlow::RuntimeError("extra_shot() expects numbers as arguments.".to_string())),
     };
@@ -89,7 +92,7 @@ pub fn string_length(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("string_length() expects 1 argument, but got {}", args.len())));
     }
 
-    match args.get(0).unwrap() {
+    match args.first().unwrap() {
         Value::String(s) => Ok(Value::Number(s.len() as f64)),
         _ => Err(ControlFlow::RuntimeError("string_length() expects a string as an argument.".to_string())),
     }
@@ -100,7 +103,7 @@ pub fn brew_blend(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("brew_blend() expects 2 arguments, but got {}", args.len())));
     }
 
-    let s1 = match args.get(0).unwrap() {
+    let s1 = match args.first().unwrap() {
         Value::String(s) => s,
         _ => return Err(ControlFlow::RuntimeError("brew_blend() expects strings as arguments.".to_string())),
     };
@@ -118,7 +121,7 @@ pub fn foam_up(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("foam_up() expects 1 argument, but got {}", args.len())));
     }
 
-    match args.get(0).unwrap() {
+    match args.first().unwrap() {
         Value::String(s) => Ok(Value::String(s.to_uppercase())),
         _ => Err(ControlFlow::RuntimeError("foam_up() expects a string as an argument.".to_string())),
     }
@@ -129,21 +132,119 @@ pub fn settle_down(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("settle_down() expects 1 argument, but got {}", args.len())));
     }
 
-    match args.get(0).unwrap() {
+    match args.first().unwrap() {
         Value::String(s) => Ok(Value::String(s.to_lowercase())),
         _ => Err(ControlFlow::RuntimeError("settle_down() expects a string as an argument.".to_string())),
     }
 }
 
+/// `skim_whitespace(s)`: s without leading or trailing whitespace
+pub fn skim_whitespace(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [Value::String(s)] => Ok(Value::String(s.trim().to_string())),
+        [other] => Err(ControlFlow::RuntimeError(format!("skim_whitespace() expects a string, but got a {}", other.type_name()))),
+        _ => Err(ControlFlow::RuntimeError(format!("skim_whitespace() expects 1 argument, but got {}", args.len()))),
+    }
+}
+
+/// `swap_flavor(s, from, to)`: every `from` in s replaced by `to`
+pub fn swap_flavor(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [Value::String(_), Value::String(from), Value::String(_)] if from.is_empty() => {
+            Err(ControlFlow::RuntimeError("swap_flavor() can't swap out an empty string".to_string()))
+        }
+        [Value::String(s), Value::String(from), Value::String(to)] => Ok(Value::String(s.replace(from.as_str(), to))),
+        [_, _, _] => Err(ControlFlow::RuntimeError("swap_flavor() expects three strings: the text, what to find, and its replacement".to_string())),
+        _ => Err(ControlFlow::RuntimeError(format!("swap_flavor() expects 3 arguments, but got {}", args.len()))),
+    }
+}
+
+/// The two strings contains_note, starts_with_aroma and ends_with_aroma compare
+fn string_pair<'a>(native: &str, args: &'a [Value]) -> Result<(&'a str, &'a str), ControlFlow> {
+    match args {
+        [Value::String(s), Value::String(note)] => Ok((s, note)),
+        [_, _] => Err(ControlFlow::RuntimeError(format!("{}() expects two strings", native))),
+        _ => Err(ControlFlow::RuntimeError(format!("{}() expects 2 arguments, but got {}", native, args.len()))),
+    }
+}
+
+pub fn contains_note(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (s, note) = string_pair("contains_note", &args)?;
+    Ok(Value::Boolean(s.contains(note)))
+}
+
+pub fn starts_with_aroma(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (s, aroma) = string_pair("starts_with_aroma", &args)?;
+    Ok(Value::Boolean(s.starts_with(aroma)))
+}
+
+pub fn ends_with_aroma(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (s, aroma) = string_pair("ends_with_aroma", &args)?;
+    Ok(Value::Boolean(s.ends_with(aroma)))
+}
+
+/// The longest string (or bytes) the natives and `add` will build, so a typo'd width or a
+/// doubling loop can't eat all the memory
+pub const MAX_BREWED_STRING: usize = 64 * 1024 * 1024;
+
+/// The most items the cup natives will pour into one cup: 64 MiB worth of slots, for the same reason
+pub const MAX_CUP_ITEMS: usize = MAX_BREWED_STRING / std::mem::size_of::<Value>();
+
+/// Spill instead of growing a cup past MAX_CUP_ITEMS
+fn check_cup_growth(native: &str, items: usize) -> Result<(), ControlFlow> {
+    if items > MAX_CUP_ITEMS {
+        return Err(ControlFlow::RuntimeError(format!("{}() would pour a cup of over {} items", native, MAX_CUP_ITEMS)));
+    }
+    Ok(())
+}
+
+/// `pad_cup_left(s, width, fill?)` / `pad_cup_right`: s filled out to `width` characters
+/// with `fill` (a space by default). Strings already that wide come back unchanged.
+pub fn pad_cup(native: &str, args: Vec<Value>, on_left: bool) -> Result<Value, ControlFlow> {
+    let (s, width, fill) = match args.as_slice() {
+        [Value::String(s), Value::Number(width)] => (s, *width, ' '),
+        [Value::String(s), Value::Number(width), Value::String(fill)] if fill.chars().count() == 1 => {
+            (s, *width, fill.chars().next().unwrap())
+        }
+        [_, _] | [_, _, _] => {
+            return Err(ControlFlow::RuntimeError(format!("{}() expects a string, a width, and an optional single-character fill", native)))
+        }
+        _ => return Err(ControlFlow::RuntimeError(format!("{}() expects 2 or 3 arguments, but got {}", native, args.len()))),
+    };
+    let missing = (width.max(0.0) as usize).saturating_sub(s.chars().count());
+    if missing.saturating_mul(fill.len_utf8()) > MAX_BREWED_STRING {
+        return Err(ControlFlow::RuntimeError(format!("{}() would brew a string over 64 MiB", native)));
+    }
+    let padding: String = std::iter::repeat_n(fill, missing).collect();
+    Ok(Value::String(if on_left { padding + s } else { format!("{}{}", s, padding) }))
+}
+
+/// `repeat_shot(s, n)`: s, n times over
+pub fn repeat_shot(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [Value::String(s), Value::Number(n)] if n.fract() == 0.0 && *n >= 0.0 => {
+            if s.len().saturating_mul(*n as usize) > MAX_BREWED_STRING {
+                return Err(ControlFlow::RuntimeError("repeat_shot() would brew a string over 64 MiB".to_string()));
+            }
+            Ok(Value::String(s.repeat(*n as usize)))
+        }
+        [_, _] => Err(ControlFlow::RuntimeError("repeat_shot() expects a string and a whole number of times".to_string())),
+        _ => Err(ControlFlow::RuntimeError(format!("repeat_shot() expects 2 arguments, but got {}", args.len()))),
+    }
+}
+
 // Array functions
 pub fn cup_size(args: Vec<Value>) -> Result<Value, ControlFlow> {
     if args.len() != 1 {
         return Err(ControlFlow::RuntimeError(format!("cup_size() expects 1 argument, but got {}", args.len())));
     }
 
-    match args.get(0).unwrap() {
+    match args.first().unwrap() {
         Value::Array(arr) => Ok(Value::Number(arr.len() as f64)),
-        _ => Err(ControlFlow::RuntimeError("cup_size() expects an array as an argument.".to_string())),
+        Value::Bytes(bytes) => Ok(Value::Number(bytes.len() as f64)),
+        Value::Set(set) => Ok(Value::Number(set.borrow().len() as f64)),
+        Value::Chain(chain) => Ok(Value::Number(chain.borrow().len() as f64)),
+        _ => Err(ControlFlow::RuntimeError("cup_size() expects an array (or bytes, a set, or a chain) as an argument.".to_string())),
     }
 }
 
@@ -152,29 +253,713 @@ pub fn add_to_cup(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("add_to_cup() expects 2 arguments, but got {}", args.len())));
     }
 
-    let mut arr = match args.get(0).unwrap() {
-        Value::Array(a) => a.clone(),
+    let mut arr = match args.first().unwrap() {
+        Value::Array(a) => a.to_vec(),
         _ => return Err(ControlFlow::RuntimeError("add_to_cup() expects an array as the first argument.".to_string())),
     };
 
+    check_cup_growth("add_to_cup", arr.len() + 1)?;
     arr.push(args.get(1).unwrap().clone());
-    Ok(Value::Array(arr))
+    Ok(Value::array(arr))
+}
+
+/// `sort_cup(cup)`: a sorted copy of a cup of numbers or a cup of strings
+pub fn sort_cup(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let mut sorted = match args.as_slice() {
+        [Value::Array(cup)] => cup.to_vec(),
+        [other] => return Err(ControlFlow::RuntimeError(format!("sort_cup() expects a cup, but got a {}", other.type_name()))),
+        _ => return Err(ControlFlow::RuntimeError(format!("sort_cup() expects 1 argument, but got {}", args.len()))),
+    };
+    if sorted.iter().all(|item| matches!(item, Value::Number(_))) {
+        sorted.sort_by(|a, b| match (a, b) {
+            (Value::Number(a), Value::Number(b)) => a.total_cmp(b),
+            _ => std::cmp::Ordering::Equal,
+        });
+    } else if sorted.iter().all(|item| matches!(item, Value::String(_))) {
+        sorted.sort_by(|a, b| match (a, b) {
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            _ => std::cmp::Ordering::Equal,
+        });
+    } else {
+        return Err(ControlFlow::RuntimeError(
+            "sort_cup() sorts a cup of all numbers or all strings; use sort_cup_with() for anything else".to_string(),
+        ));
+    }
+    Ok(Value::array(sorted))
+}
+
+/// `compare_blend(a, b)`: -1, 0 or 1 as a sorts before, with or after b; numbers (big brews too) by value, strings by character
+/// Natives aren't values, so `sort_cup_with` gets a brew that serves `compare_blend(...)`
+pub fn compare_blend(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    use std::cmp::Ordering;
+    let ordering = match args.as_slice() {
+        [Value::Number(a), Value::Number(b)] => a.total_cmp(b),
+        [Value::String(a), Value::String(b)] => a.cmp(b),
+        [Value::Boolean(a), Value::Boolean(b)] => a.cmp(b),
+        [a @ (Value::Number(_) | Value::BigBrew(_)), b @ (Value::Number(_) | Value::BigBrew(_))] => crate::big_brew::big_ordering(a, b)?,
+        [a, b] => {
+            return Err(ControlFlow::RuntimeError(format!(
+                "compare_blend() compares two numbers, two strings or two booleans, but got a {} and a {}",
+                a.type_name(),
+                b.type_name()
+            )))
+        }
+        _ => return Err(ControlFlow::RuntimeError(format!("compare_blend() expects 2 arguments, but got {}", args.len()))),
+    };
+    Ok(Value::Number(match ordering {
+        Ordering::Less => -1.0,
+        Ordering::Equal => 0.0,
+        Ordering::Greater => 1.0,
+    }))
+}
+
+/// `slice_cup(cup, start, end?)`: the items from start up to (not including) end; negatives count from the end
+pub fn slice_cup(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (cup, start, end) = match args.as_slice() {
+        [Value::Array(cup), Value::Number(start)] => (cup, *start, None),
+        [Value::Array(cup), Value::Number(start), Value::Number(end)] => (cup, *start, Some(*end)),
+        [_, _] | [_, _, _] => return Err(ControlFlow::RuntimeError("slice_cup() expects a cup, a start and an optional end".to_string())),
+        _ => return Err(ControlFlow::RuntimeError(format!("slice_cup() expects 2 or 3 arguments, but got {}", args.len()))),
+    };
+    let start = clamped_position(start, cup.len());
+    let end = end.map_or(cup.len(), |end| clamped_position(end, cup.len())).max(start);
+    Ok(Value::array(cup[start..end].to_vec()))
+}
+
+/// `insert_into_cup(cup, index, value)`: a copy with value placed at index (0 to cup_size)
+pub fn insert_into_cup(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (cup, index, value) = match args.as_slice() {
+        [Value::Array(cup), Value::Number(index), value] if index.fract() == 0.0 => (cup.to_vec(), *index, value.clone()),
+        [_, _, _] => return Err(ControlFlow::RuntimeError("insert_into_cup() expects a cup, a whole-number index and a value".to_string())),
+        _ => return Err(ControlFlow::RuntimeError(format!("insert_into_cup() expects 3 arguments, but got {}", args.len()))),
+    };
+    if index < 0.0 || index > cup.len() as f64 {
+        return Err(ControlFlow::RuntimeError(format!("insert_into_cup() index {} is out of bounds for a cup of {}", index, cup.len())));
+    }
+    let mut cup = cup;
+    cup.insert(index as usize, value);
+    Ok(Value::array(cup))
+}
+
+/// `remove_from_cup(cup, index)`: a copy without the item at index; -1 is the last item
+pub fn remove_from_cup(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (mut cup, index) = match args.as_slice() {
+        [Value::Array(cup), Value::Number(index)] if index.fract() == 0.0 => (cup.to_vec(), *index),
+        [_, _] => return Err(ControlFlow::RuntimeError("remove_from_cup() expects a cup and a whole-number index".to_string())),
+        _ => return Err(ControlFlow::RuntimeError(format!("remove_from_cup() expects 2 arguments, but got {}", args.len()))),
+    };
+    let position = if index < 0.0 { cup.len() as f64 + index } else { index };
+    if position < 0.0 || position >= cup.len() as f64 {
+        return Err(ControlFlow::RuntimeError(format!("remove_from_cup() index {} is out of bounds for a cup of {}", index, cup.len())));
+    }
+    cup.remove(position as usize);
+    Ok(Value::array(cup))
+}
+
+/// `find_in_cup(cup, value)`: the index of the first item that's the same blend as value, or -1
+pub fn find_in_cup(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    Ok(Value::Number(cup_position("find_in_cup", &args)?.map_or(-1.0, |index| index as f64)))
+}
+
+/// `cup_contains(cup, value)`: whether any item is the same blend as value
+pub fn cup_contains(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    Ok(Value::Boolean(cup_position("cup_contains", &args)?.is_some()))
+}
+
+/// `zip_cups(a, b)`: a cup of [a_item, b_item] pairs, as long as the shorter cup
+pub fn zip_cups(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [Value::Array(a), Value::Array(b)] => Ok(Value::array(
+            a
+                .iter()
+                .zip(b.iter())
+                .map(|(a, b)| Value::array(vec![a.clone(), b.clone()]))
+                .collect(),
+        )),
+        [_, _] => Err(ControlFlow::RuntimeError("zip_cups() expects two cups".to_string())),
+        _ => Err(ControlFlow::RuntimeError(format!("zip_cups() expects 2 arguments, but got {}", args.len()))),
+    }
+}
+
+/// `flatten_cup(nested, depth?)`: pour nested cups out into one, `depth` levels deep (1 by default)
+pub fn flatten_cup(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (cup, depth) = match args.as_slice() {
+        [Value::Array(cup)] => (cup, 1),
+        [Value::Array(cup), Value::Number(depth)] if depth.fract() == 0.0 && *depth >= 0.0 => (cup, *depth as usize),
+        [_] | [_, _] => return Err(ControlFlow::RuntimeError("flatten_cup() expects a cup and an optional whole-number depth".to_string())),
+        _ => return Err(ControlFlow::RuntimeError(format!("flatten_cup() expects 1 or 2 arguments, but got {}", args.len()))),
+    };
+    fn pour_flat(items: &[Value], depth: usize, flat: &mut Vec<Value>) {
+        for item in items {
+            match item {
+                Value::Array(inner) if depth > 0 => pour_flat(inner, depth - 1, flat),
+                other => flat.push(other.clone()),
+            }
+        }
+    }
+    let mut flat = Vec::new();
+    pour_flat(cup, depth, &mut flat);
+    Ok(Value::array(flat))
+}
+
+/// `unique_sips(cup)`: the cup without repeats, keeping each item's first appearance
+pub fn unique_sips(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [Value::Array(cup)] => {
+            let mut unique: Vec<Value> = Vec::new();
+            for item in cup.iter() {
+                if !unique.iter().any(|seen| same_blend(seen, item)) {
+                    unique.push(item.clone());
+                }
+            }
+            Ok(Value::array(unique))
+        }
+        [other] => Err(ControlFlow::RuntimeError(format!("unique_sips() expects a cup, but got a {}", other.type_name()))),
+        _ => Err(ControlFlow::RuntimeError(format!("unique_sips() expects 1 argument, but got {}", args.len()))),
+    }
+}
+
+/// `chunk_cup(cup, n)`: cups of n items each; the last may be shorter
+pub fn chunk_cup(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [Value::Array(cup), Value::Number(n)] if n.fract() == 0.0 && *n >= 1.0 => Ok(Value::array(
+            cup.chunks(*n as usize).map(|chunk| Value::array(chunk.to_vec())).collect(),
+        )),
+        [_, _] => Err(ControlFlow::RuntimeError("chunk_cup() expects a cup and a chunk size of at least 1".to_string())),
+        _ => Err(ControlFlow::RuntimeError(format!("chunk_cup() expects 2 arguments, but got {}", args.len()))),
+    }
+}
+
+fn cup_position(native: &str, args: &[Value]) -> Result<Option<usize>, ControlFlow> {
+    match args {
+        [Value::Array(cup), value] => Ok(cup.iter().position(|item| same_blend(item, value))),
+        [other, _] => Err(ControlFlow::RuntimeError(format!("{}() expects a cup first, but got a {}", native, other.type_name()))),
+        _ => Err(ControlFlow::RuntimeError(format!("{}() expects 2 arguments, but got {}", native, args.len()))),
+    }
+}
+
+// Bytes
+/// The text encodings grind_to_bytes and brew_from_bytes understand
+const BYTE_ENCODINGS: &str = "utf-8, latin-1, ascii, utf-16le or utf-16be";
+
+fn byte_encoding<'a>(native: &str, encoding: Option<&'a Value>) -> Result<&'a str, ControlFlow> {
+    match encoding {
+        None => Ok("utf-8"),
+        Some(Value::String(name)) => match name.to_ascii_lowercase().replace('_', "-").as_str() {
+            "utf-8" | "utf8" => Ok("utf-8"),
+            "latin-1" | "latin1" | "iso-8859-1" => Ok("latin-1"),
+            "ascii" => Ok("ascii"),
+            "utf-16le" => Ok("utf-16le"),
+            "utf-16be" => Ok("utf-16be"),
+            _ => Err(ControlFlow::RuntimeError(format!("{}() doesn't know the encoding '{}'; try {}", native, name, BYTE_ENCODINGS))),
+        },
+        Some(other) => Err(ControlFlow::RuntimeError(format!("{}() expects the encoding as a string, but got {:?}", native, other))),
+    }
+}
+
+/// `grind_to_bytes(text, encoding?)` encodes a string; `grind_to_bytes(cup)` packs numbers 0-255
+pub fn grind_to_bytes(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [Value::String(text)] | [Value::String(text), _] => {
+            let encoding = byte_encoding("grind_to_bytes", args.get(1))?;
+            let encoded = match encoding {
+                "latin-1" | "ascii" => {
+                    let limit = if encoding == "ascii" { 0x7f } else { 0xff };
+                    text.chars()
+                        .map(|c| match u8::try_from(c as u32) {
+                            Ok(byte) if byte <= limit => Ok(byte),
+                            _ => Err(ControlFlow::RuntimeError(format!("grind_to_bytes() can't fit '{}' into {}", c, encoding))),
+                        })
+                        .collect::<Result<Vec<u8>, ControlFlow>>()?
+                }
+                "utf-16le" => text.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+                "utf-16be" => text.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+                _ => text.as_bytes().to_vec(),
+            };
+            Ok(Value::bytes(encoded))
+        }
+        [Value::Array(cup)] => {
+            let packed = cup.iter()
+                .map(|item| match item {
+                    Value::Number(n) if n.fract() == 0.0 && (0.0..=255.0).contains(n) => Ok(*n as u8),
+                    other => Err(ControlFlow::RuntimeError(format!("grind_to_bytes() needs whole numbers from 0 to 255, but the cup holds {:?}", other))),
+                })
+                .collect::<Result<Vec<u8>, ControlFlow>>()?;
+            Ok(Value::bytes(packed))
+        }
+        [Value::Bytes(bytes)] => Ok(Value::Bytes(bytes.clone())),
+        [_] | [_, _] => Err(ControlFlow::RuntimeError("grind_to_bytes() expects a string (with an optional encoding) or a cup of numbers".to_string())),
+        _ => Err(ControlFlow::RuntimeError(format!("grind_to_bytes() expects 1 or 2 arguments, but got {}", args.len()))),
+    }
+}
+
+/// `brew_from_bytes(bytes, encoding?)`: decode bytes back into a string, spilling on invalid input
+pub fn brew_from_bytes(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let bytes = match args.as_slice() {
+        [Value::Bytes(bytes)] | [Value::Bytes(bytes), _] => bytes,
+        [_] | [_, _] => return Err(ControlFlow::RuntimeError("brew_from_bytes() expects bytes to decode".to_string())),
+        _ => return Err(ControlFlow::RuntimeError(format!("brew_from_bytes() expects 1 or 2 arguments, but got {}", args.len()))),
+    };
+    let encoding = byte_encoding("brew_from_bytes", args.get(1))?;
+    let invalid = |at: usize| ControlFlow::RuntimeError(format!("brew_from_bytes() found bytes that aren't valid {} at offset {}", encoding, at));
+    let text = match encoding {
+        "latin-1" => bytes.iter().map(|&byte| byte as char).collect(),
+        "ascii" => match bytes.iter().position(|byte| !byte.is_ascii()) {
+            Some(at) => return Err(invalid(at)),
+            None => bytes.iter().map(|&byte| byte as char).collect(),
+        },
+        "utf-16le" | "utf-16be" => {
+            if bytes.len() % 2 != 0 {
+                return Err(invalid(bytes.len() - 1));
+            }
+            let units: Vec<u16> = bytes.chunks(2)
+                .map(|pair| if encoding == "utf-16le" { u16::from_le_bytes([pair[0], pair[1]]) } else { u16::from_be_bytes([pair[0], pair[1]]) })
+                .collect();
+            String::from_utf16(&units).map_err(|_| invalid(0))?
+        }
+        _ => String::from_utf8(bytes.to_vec()).map_err(|e| invalid(e.utf8_error().valid_up_to()))?,
+    };
+    Ok(Value::String(text))
+}
+
+// Hashing
+/// What a hashing native digests: a string's UTF-8 or the bytes themselves
+fn digest_input<'a>(native: &str, value: &'a Value) -> Result<&'a [u8], ControlFlow> {
+    match value {
+        Value::String(text) => Ok(text.as_bytes()),
+        Value::Bytes(bytes) => Ok(bytes),
+        other => Err(ControlFlow::RuntimeError(format!("{}() expects a string or bytes, but got a {}", native, other.type_name()))),
+    }
+}
+
+fn hex_digest(digest: &[u8]) -> Value {
+    Value::String(digest.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// `brew_sha256(data)`: SHA-256 as lowercase hex
+pub fn brew_sha256(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    use sha2::Digest;
+    match args.as_slice() {
+        [data] => Ok(hex_digest(&sha2::Sha256::digest(digest_input("brew_sha256", data)?))),
+        _ => Err(ControlFlow::RuntimeError(format!("brew_sha256() expects 1 argument, but got {}", args.len()))),
+    }
+}
+
+/// `brew_md5(data)`: MD5 as lowercase hex. Fine for checksums, not for secrets
+pub fn brew_md5(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    use md5::Digest;
+    match args.as_slice() {
+        [data] => Ok(hex_digest(&md5::Md5::digest(digest_input("brew_md5", data)?))),
+        _ => Err(ControlFlow::RuntimeError(format!("brew_md5() expects 1 argument, but got {}", args.len()))),
+    }
+}
+
+/// `brew_hmac(key, data)`: HMAC-SHA256 as lowercase hex
+pub fn brew_hmac(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    use hmac::Mac;
+    match args.as_slice() {
+        [key, data] => {
+            let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(digest_input("brew_hmac", key)?)
+                .map_err(|e| ControlFlow::RuntimeError(format!("brew_hmac() can't use that key: {}", e)))?;
+            mac.update(digest_input("brew_hmac", data)?);
+            Ok(hex_digest(&mac.finalize().into_bytes()))
+        }
+        _ => Err(ControlFlow::RuntimeError(format!("brew_hmac() expects 2 arguments (key, data), but got {}", args.len()))),
+    }
+}
+
+/// `secure_random_bytes(n)`: n bytes from the operating system's secure random source
+pub fn secure_random_bytes(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let count = match args.as_slice() {
+        [Value::Number(n)] if n.fract() == 0.0 && (0.0..=1_048_576.0).contains(n) => *n as usize,
+        [_] => return Err(ControlFlow::RuntimeError("secure_random_bytes() expects a whole number of bytes, up to 1048576".to_string())),
+        _ => return Err(ControlFlow::RuntimeError(format!("secure_random_bytes() expects 1 argument, but got {}", args.len()))),
+    };
+    let mut bytes = vec![0u8; count];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|e| ControlFlow::RuntimeError(format!("secure_random_bytes() couldn't reach the system's random source: {}", e)))?;
+    Ok(Value::bytes(bytes))
+}
+
+// Encoding
+/// What an encoding native encodes: a string's UTF-8 or the bytes themselves
+fn encode_input<'a>(native: &str, args: &'a [Value]) -> Result<&'a [u8], ControlFlow> {
+    match args {
+        [data] => digest_input(native, data),
+        _ => Err(ControlFlow::RuntimeError(format!("{}() expects 1 argument, but got {}", native, args.len()))),
+    }
+}
+
+/// The text a decoding native takes, and whether to serve bytes instead of a string
+fn decode_input<'a>(native: &str, args: &'a [Value]) -> Result<(&'a str, bool), ControlFlow> {
+    match args {
+        [Value::String(text)] => Ok((text, false)),
+        [Value::String(text), Value::Boolean(as_bytes)] => Ok((text, *as_bytes)),
+        [_] | [_, _] => Err(ControlFlow::RuntimeError(format!("{}() expects a string and an optional true/false for as_bytes", native))),
+        _ => Err(ControlFlow::RuntimeError(format!("{}() expects 1 or 2 arguments, but got {}", native, args.len()))),
+    }
+}
+
+/// Decoded bytes as bytes, or as a string when they're valid UTF-8
+fn decoded(native: &str, bytes: Vec<u8>, as_bytes: bool) -> Result<Value, ControlFlow> {
+    if as_bytes {
+        return Ok(Value::bytes(bytes));
+    }
+    String::from_utf8(bytes).map(Value::String).map_err(|_| {
+        ControlFlow::RuntimeError(format!("{}() decoded something that isn't UTF-8 text; pass true as the second argument to get bytes", native))
+    })
+}
+
+/// `foam_base64_encode(data)`: standard base64, with padding
+pub fn foam_base64_encode(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    use base64::Engine;
+    Ok(Value::String(base64::engine::general_purpose::STANDARD.encode(encode_input("foam_base64_encode", &args)?)))
+}
+
+/// `foam_base64_decode(text, as_bytes?)`: accepts the standard and URL-safe alphabets, padded or not
+pub fn foam_base64_decode(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+    use base64::{alphabet, Engine};
+    let (text, as_bytes) = decode_input("foam_base64_decode", &args)?;
+    let lenient = GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+    let text: String = text.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    let bytes = if text.contains(['-', '_']) {
+        GeneralPurpose::new(&alphabet::URL_SAFE, lenient).decode(&text)
+    } else {
+        GeneralPurpose::new(&alphabet::STANDARD, lenient).decode(&text)
+    };
+    let bytes = bytes.map_err(|e| ControlFlow::RuntimeError(format!("foam_base64_decode() couldn't decode that: {}", e)))?;
+    decoded("foam_base64_decode", bytes, as_bytes)
+}
+
+/// `to_hex(data)`: lowercase hex, two digits per byte
+pub fn to_hex(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    Ok(hex_digest(encode_input("to_hex", &args)?))
+}
+
+/// `from_hex(text, as_bytes?)`: either case; spaces and a leading 0x are ignored
+pub fn from_hex(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (text, as_bytes) = decode_input("from_hex", &args)?;
+    let text = text.trim();
+    let digits: Vec<u8> = text
+        .strip_prefix("0x")
+        .or_else(|| text.strip_prefix("0X"))
+        .unwrap_or(text)
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace())
+        .collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err(ControlFlow::RuntimeError("from_hex() expects two hex digits per byte, but got an odd number".to_string()));
+    }
+    let bytes = digits
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| ControlFlow::RuntimeError(format!("from_hex() found '{}', which isn't hex", String::from_utf8_lossy(pair))))
+        })
+        .collect::<Result<Vec<u8>, ControlFlow>>()?;
+    decoded("from_hex", bytes, as_bytes)
+}
+
+/// `url_encode(data)`: percent-encode everything but letters, digits and `-_.~`
+pub fn url_encode(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let encoded = encode_input("url_encode", &args)?
+        .iter()
+        .map(|&byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect();
+    Ok(Value::String(encoded))
+}
+
+/// `url_decode(text)`: undo %XX escapes, reading `+` as a space the way query strings do
+pub fn url_decode(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [Value::String(text)] => Ok(Value::String(percent_decode(text, true))),
+        [_] => Err(ControlFlow::RuntimeError("url_decode() expects a string".to_string())),
+        _ => Err(ControlFlow::RuntimeError(format!("url_decode() expects 1 argument, but got {}", args.len()))),
+    }
+}
+
+/// Undo %XX escapes in a path or query piece; in queries `+` also stands for a space
+pub fn percent_decode(text: &str, plus_is_space: bool) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (escaped, bytes[i]) {
+            (Some(byte), _) => {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+            (None, b'+') if plus_is_space => decoded.push(b' '),
+            (None, byte) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+// Command line and environment
+pub fn coffee_order(args: Vec<Value>, script_args: &[String]) -> Result<Value, ControlFlow> {
+    if !args.is_empty() {
+        return Err(ControlFlow::RuntimeError(format!("coffee_order() takes no arguments, but got {}", args.len())));
+    }
+    Ok(Value::array(script_args.iter().map(|arg| Value::String(arg.clone())).collect()))
+}
+
+/// `read_pantry_env(name, fallback?)`: an environment variable, or the fallback (null) when unset
+pub fn read_pantry_env(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (name, fallback) = match args.as_slice() {
+        [Value::String(name)] => (name, Value::Null),
+        [Value::String(name), fallback] => (name, fallback.clone()),
+        [_] | [_, _] => return Err(ControlFlow::RuntimeError("read_pantry_env() expects a string variable name".to_string())),
+        _ => return Err(ControlFlow::RuntimeError(format!("read_pantry_env() expects 1 or 2 arguments, but got {}", args.len()))),
+    };
+    Ok(std::env::var(name).map(Value::String).unwrap_or(fallback))
+}
+
+pub fn set_pantry_env(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (name, value) = match args.as_slice() {
+        [Value::String(name), value] => (name, value),
+        [_, _] => return Err(ControlFlow::RuntimeError("set_pantry_env() expects a string variable name".to_string())),
+        _ => return Err(ControlFlow::RuntimeError(format!("set_pantry_env() expects 2 arguments, but got {}", args.len()))),
+    };
+    let value = match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+    // std::env::set_var panics on these instead of failing
+    if name.is_empty() || name.contains('=') || name.contains('\0') || value.contains('\0') {
+        return Err(ControlFlow::RuntimeError(format!(
+            "set_pantry_env() can't set '{}': names can't be empty or contain '=', and neither can contain a NUL",
+            name
+        )));
+    }
+    std::env::set_var(name, value);
+    Ok(Value::Null)
+}
+
+// Console input
+/// `drink_all_input()`: everything left on stdin, up to EOF
+pub fn drink_all_input(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    if !args.is_empty() {
+        return Err(ControlFlow::RuntimeError(format!("drink_all_input() takes no arguments, but got {}", args.len())));
+    }
+    let mut input = String::new();
+    std::io::Read::read_to_string(&mut std::io::stdin(), &mut input)
+        .map_err(|e| ControlFlow::RuntimeError(format!("drink_all_input() could not read stdin: {}", e)))?;
+    Ok(Value::String(input))
+}
+
+/// One line from stdin without its line ending, or None once stdin has run dry
+fn read_stdin_line(native: &str) -> Result<Option<String>, ControlFlow> {
+    let mut line = String::new();
+    let read = std::io::stdin()
+        .read_line(&mut line)
+        .map_err(|e| ControlFlow::RuntimeError(format!("{}() could not read stdin: {}", native, e)))?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+    Ok(Some(line))
+}
+
+/// `sip_line()`: the next line from stdin, or null at EOF so `while` loops know when to stop
+pub fn sip_line(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    if !args.is_empty() {
+        return Err(ControlFlow::RuntimeError(format!("sip_line() takes no arguments, but got {}", args.len())));
+    }
+    Ok(read_stdin_line("sip_line")?.map(Value::String).unwrap_or(Value::Null))
+}
+
+/// `gossip_number(prompt?)`: keep asking until the answer is a number
+pub fn gossip_number(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let prompt = match args.as_slice() {
+        [] => String::new(),
+        [Value::String(prompt)] => prompt.clone(),
+        [other] => other.to_string(),
+        _ => return Err(ControlFlow::RuntimeError(format!("gossip_number() expects 0 or 1 arguments, but got {}", args.len()))),
+    };
+    loop {
+        print!("{}", prompt);
+        let _ = std::io::stdout().flush();
+        let answer = match read_stdin_line("gossip_number")? {
+            Some(answer) => answer,
+            None => return Err(ControlFlow::RuntimeError("gossip_number() ran out of input before getting a number".to_string())),
+        };
+        match answer.trim().parse::<f64>() {
+            Ok(number) if number.is_finite() => return Ok(Value::Number(number)),
+            _ => println!("☕ '{}' isn't a number; try again.", answer.trim()),
+        }
+    }
+}
+
+// Process control
+/// `finish_service(code?)`: close the shop right away with the given exit status (0 by default).
+/// Unwinds as `ControlFlow::FinishService`; whoever is brewing the recipe does the closing
+pub fn finish_service(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let code = match args.as_slice() {
+        [] => 0,
+        [Value::Number(code)] if code.fract() == 0.0 && (0.0..=255.0).contains(code) => *code as i32,
+        [other] => {
+            return Err(ControlFlow::RuntimeError(format!(
+                "finish_service() expects a whole exit code from 0 to 255, but got {:?}",
+                other
+            )))
+        }
+        _ => return Err(ControlFlow::RuntimeError(format!("finish_service() expects 0 or 1 arguments, but got {}", args.len()))),
+    };
+    Err(ControlFlow::FinishService(code))
+}
+
+// Shell commands
+/// The command a shell native should run: a string goes through the system shell,
+/// a cup of strings runs the program directly with those arguments
+fn shell_command(native: &str, args: &[Value]) -> Result<std::process::Command, ControlFlow> {
+    match args {
+        [Value::String(line)] => {
+            let mut command = if cfg!(windows) {
+                let mut command = std::process::Command::new("cmd");
+                command.arg("/C");
+                command
+            } else {
+                let mut command = std::process::Command::new("sh");
+                command.arg("-c");
+                command
+            };
+            command.arg(line);
+            Ok(command)
+        }
+        [Value::Array(parts)] => {
+            let parts: Vec<String> = parts.iter().map(|part| match part {
+                Value::String(text) => Ok(text.clone()),
+                other => Err(ControlFlow::RuntimeError(format!("{}() expects a cup of strings, but found {}", native, other.type_name()))),
+            }).collect::<Result<_, _>>()?;
+            let Some((program, rest)) = parts.split_first() else {
+                return Err(ControlFlow::RuntimeError(format!("{}() got an empty cup; the first string names the program", native)));
+            };
+            let mut command = std::process::Command::new(program);
+            command.args(rest);
+            Ok(command)
+        }
+        [_] => Err(ControlFlow::RuntimeError(format!("{}() expects a command string or a cup of strings", native))),
+        _ => Err(ControlFlow::RuntimeError(format!("{}() expects 1 argument, but got {}", native, args.len()))),
+    }
+}
+
+/// Exit code as a number, or null when a signal ended the command
+fn exit_code_of(status: std::process::ExitStatus) -> Value {
+    status.code().map_or(Value::Null, |code| Value::Number(code as f64))
+}
+
+/// `brew_shell(cmd)`: run a command to completion and serve `{stdout, stderr, exit_code}`
+pub fn brew_shell(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let output = shell_command("brew_shell", &args)?
+        .output()
+        .map_err(|e| ControlFlow::RuntimeError(format!("brew_shell() could not start the command: {}", e)))?;
+    let mut fields = HashMap::new();
+    fields.insert("stdout".to_string(), Value::String(String::from_utf8_lossy(&output.stdout).to_string()));
+    fields.insert("stderr".to_string(), Value::String(String::from_utf8_lossy(&output.stderr).to_string()));
+    fields.insert("exit_code".to_string(), exit_code_of(output.status));
+    Ok(Value::object("ShellResult", fields))
+}
+
+/// `brew_shell_live(cmd)`: run a command with its output streaming straight to ours; serves the exit code
+pub fn brew_shell_live(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let _ = std::io::stdout().flush();
+    let status = shell_command("brew_shell_live", &args)?
+        .status()
+        .map_err(|e| ControlFlow::RuntimeError(format!("brew_shell_live() could not start the command: {}", e)))?;
+    Ok(exit_code_of(status))
 }
 
 // Random number generation
+thread_local! {
+    /// The grinder behind every random native: seeded from the OS, or by `seed_the_grinder(n)`
+    static GRINDER: std::cell::RefCell<rand_chacha::ChaCha8Rng> =
+        std::cell::RefCell::new(rand::SeedableRng::from_entropy());
+}
+
+fn with_grinder<T>(grind: impl FnOnce(&mut rand_chacha::ChaCha8Rng) -> T) -> T {
+    GRINDER.with(|grinder| grind(&mut grinder.borrow_mut()))
+}
+
+/// `random_bean()`: a number in [0, 1)
 pub fn random_bean() -> Result<Value, ControlFlow> {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-    use std::time::{SystemTime, UNIX_EPOCH};
-    
-    let seed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
-    let mut hasher = DefaultHasher::new();
-    seed.hash(&mut hasher);
-    let hash = hasher.finish();
-    
-    // Simple linear congruential generator for pseudo-randomness
-    let random = ((hash.wrapping_mul(1103515245).wrapping_add(12345)) % (1 << 31)) as f64 / (1 << 31) as f64;
-    Ok(Value::Number(random))
+    use rand::Rng;
+    Ok(Value::Number(with_grinder(|grinder| grinder.gen::<f64>())))
+}
+
+/// `seed_the_grinder(n)`: make every random native repeat the same sequence from here on
+pub fn seed_the_grinder(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let seed = match args.as_slice() {
+        [Value::Number(n)] if n.fract() == 0.0 && n.is_finite() => *n as i64 as u64,
+        [_] => return Err(ControlFlow::RuntimeError("seed_the_grinder() expects a whole number seed".to_string())),
+        _ => return Err(ControlFlow::RuntimeError(format!("seed_the_grinder() expects 1 argument, but got {}", args.len()))),
+    };
+    with_grinder(|grinder| *grinder = rand::SeedableRng::seed_from_u64(seed));
+    Ok(Value::Null)
+}
+
+/// `random_between(min, max)`: a whole number in [min, max] when both are whole, otherwise a number in [min, max)
+pub fn random_between(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    use rand::Rng;
+    let (min, max) = match args.as_slice() {
+        [Value::Number(min), Value::Number(max)] if min.is_finite() && max.is_finite() => (*min, *max),
+        [_, _] => return Err(ControlFlow::RuntimeError("random_between() expects two numbers".to_string())),
+        _ => return Err(ControlFlow::RuntimeError(format!("random_between() expects 2 arguments, but got {}", args.len()))),
+    };
+    if min > max {
+        return Err(ControlFlow::RuntimeError(format!("random_between() expects min <= max, but got {} and {}", min, max)));
+    }
+    let picked = if min.fract() == 0.0 && max.fract() == 0.0 {
+        with_grinder(|grinder| grinder.gen_range(min as i64..=max as i64)) as f64
+    } else if min == max {
+        min
+    } else {
+        with_grinder(|grinder| grinder.gen_range(min..max))
+    };
+    Ok(Value::Number(picked))
+}
+
+/// `random_pick(cup)`: one item from a non-empty cup
+pub fn random_pick(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    use rand::seq::SliceRandom;
+    match args.as_slice() {
+        [Value::Array(cup)] => with_grinder(|grinder| cup.choose(grinder).cloned())
+            .ok_or_else(|| ControlFlow::RuntimeError("random_pick() can't pick from an empty cup".to_string())),
+        [other] => Err(ControlFlow::RuntimeError(format!("random_pick() expects a cup, but got a {}", other.type_name()))),
+        _ => Err(ControlFlow::RuntimeError(format!("random_pick() expects 1 argument, but got {}", args.len()))),
+    }
+}
+
+/// `shuffle_cup(cup)`: a new cup with the same items in random order
+pub fn shuffle_cup(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    use rand::seq::SliceRandom;
+    match args.as_slice() {
+        [Value::Array(cup)] => {
+            let mut shuffled = cup.to_vec();
+            with_grinder(|grinder| shuffled.shuffle(grinder));
+            Ok(Value::array(shuffled))
+        }
+        [other] => Err(ControlFlow::RuntimeError(format!("shuffle_cup() expects a cup, but got a {}", other.type_name()))),
+        _ => Err(ControlFlow::RuntimeError(format!("shuffle_cup() expects 1 argument, but got {}", args.len()))),
+    }
 }
 
 // Type checking functions
@@ -183,8 +968,8 @@ pub fn is_brew(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("is_brew() expects 1 argument, but got {}", args.len())));
     }
 
-    match args.get(0).unwrap() {
-        Value::Function { .. } | Value::BoundMethod { .. } => Ok(Value::Boolean(true)),
+    match args.first().unwrap() {
+        brew if brew.is_brew() => Ok(Value::Boolean(true)),
         _ => Ok(Value::Boolean(false)),
     }
 }
@@ -194,7 +979,7 @@ pub fn is_number(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("is_number() expects 1 argument, but got {}", args.len())));
     }
 
-    match args.get(0).unwrap() {
+    match args.first().unwrap() {
         Value::Number(_) => Ok(Value::Boolean(true)),
         _ => Ok(Value::Boolean(false)),
     }
@@ -205,7 +990,7 @@ pub fn is_string(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("is_string() expects 1 argument, but got {}", args.len())));
     }
 
-    match args.get(0).unwrap() {
+    match args.first().unwrap() {
         Value::String(_) => Ok(Value::Boolean(true)),
         _ => Ok(Value::Boolean(false)),
     }
@@ -217,7 +1002,7 @@ pub fn grind_to_pieces(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("grind_to_pieces() expects 2 arguments, but got {}", args.len())));
     }
 
-    let text = match args.get(0).unwrap() {
+    let text = match args.first().unwrap() {
         Value::String(s) => s,
         _ => return Err(ControlFlow::RuntimeError("grind_to_pieces() expects a string as the first argument.".to_string())),
     };
@@ -231,7 +1016,7 @@ pub fn grind_to_pieces(args: Vec<Value>) -> Result<Value, ControlFlow> {
         .map(|piece| Value::String(piece.to_string()))
         .collect();
     
-    Ok(Value::Array(pieces))
+    Ok(Value::array(pieces))
 }
 
 pub fn filter_grounds(args: Vec<Value>) -> Result<Value, ControlFlow> {
@@ -239,7 +1024,7 @@ pub fn filter_grounds(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("filter_grounds() expects 2 arguments, but got {}", args.len())));
     }
 
-    let text = match args.get(0).unwrap() {
+    let text = match args.first().unwrap() {
         Value::String(s) => s,
         _ => return Err(ControlFlow::RuntimeError("filter_grounds() expects a string as the first argument.".to_string())),
     };
@@ -249,11 +1034,7 @@ pub fn filter_grounds(args: Vec<Value>) -> Result<Value, ControlFlow> {
         _ => return Err(ControlFlow::RuntimeError("filter_grounds() expects a number as the second argument.".to_string())),
     };
 
-    if start_pos >= text.len() {
-        return Ok(Value::String("".to_string()));
-    }
-
-    Ok(Value::String(text[start_pos..].to_string()))
+    Ok(Value::String(text.chars().skip(start_pos).collect()))
 }
 
 pub fn first_sip(args: Vec<Value>) -> Result<Value, ControlFlow> {
@@ -261,7 +1042,7 @@ pub fn first_sip(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("first_sip() expects 2 arguments, but got {}", args.len())));
     }
 
-    let text = match args.get(0).unwrap() {
+    let text = match args.first().unwrap() {
         Value::String(s) => s,
         _ => return Err(ControlFlow::RuntimeError("first_sip() expects a string as the first argument.".to_string())),
     };
@@ -271,13 +1052,54 @@ pub fn first_sip(args: Vec<Value>) -> Result<Value, ControlFlow> {
         _ => return Err(ControlFlow::RuntimeError("first_sip() expects a number as the second argument.".to_string())),
     };
 
-    let result = if length > text.len() {
-        text.clone()
+    Ok(Value::String(text.chars().take(length).collect()))
+}
+
+/// A character or cup position; negative ones count back from the end. Lands within 0..=len.
+fn clamped_position(n: f64, len: usize) -> usize {
+    if n < 0.0 {
+        len.saturating_sub((-n) as usize)
     } else {
-        text[..length].to_string()
+        (n as usize).min(len)
+    }
+}
+
+/// `slice_brew(s, start, end?)`: the characters from start up to (not including) end
+pub fn slice_brew(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (text, start, end) = match args.as_slice() {
+        [Value::String(text), Value::Number(start)] => (text, *start, None),
+        [Value::String(text), Value::Number(start), Value::Number(end)] => (text, *start, Some(*end)),
+        [_, _] | [_, _, _] => return Err(ControlFlow::RuntimeError("slice_brew() expects a string, a start and an optional end".to_string())),
+        _ => return Err(ControlFlow::RuntimeError(format!("slice_brew() expects 2 or 3 arguments, but got {}", args.len()))),
     };
+    let len = text.chars().count();
+    let start = clamped_position(start, len);
+    let end = end.map_or(len, |end| clamped_position(end, len));
+    Ok(Value::String(text.chars().skip(start).take(end.saturating_sub(start)).collect()))
+}
 
-    Ok(Value::String(result))
+/// `char_at(s, index)`: one character; negative indexes count back from the end
+pub fn char_at(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (text, index) = match args.as_slice() {
+        [Value::String(text), Value::Number(index)] if index.fract() == 0.0 => (text, *index),
+        [_, _] => return Err(ControlFlow::RuntimeError("char_at() expects a string and a whole-number index".to_string())),
+        _ => return Err(ControlFlow::RuntimeError(format!("char_at() expects 2 arguments, but got {}", args.len()))),
+    };
+    let len = text.chars().count();
+    let position = if index < 0.0 { len as f64 + index } else { index };
+    if position < 0.0 || position >= len as f64 {
+        return Err(ControlFlow::RuntimeError(format!("char_at() index {} is out of bounds for {} characters", index, len)));
+    }
+    Ok(Value::String(text.chars().nth(position as usize).map(String::from).unwrap_or_default()))
+}
+
+/// `char_count(s)`: how many characters, where string_length counts UTF-8 bytes
+pub fn char_count(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [Value::String(text)] => Ok(Value::Number(text.chars().count() as f64)),
+        [other] => Err(ControlFlow::RuntimeError(format!("char_count() expects a string, but got a {}", other.type_name()))),
+        _ => Err(ControlFlow::RuntimeError(format!("char_count() expects 1 argument, but got {}", args.len()))),
+    }
 }
 
 // Advanced Array Functions
@@ -286,7 +1108,7 @@ pub fn pour_together(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("pour_together() expects 2 arguments, but got {}", args.len())));
     }
 
-    let arr1 = match args.get(0).unwrap() {
+    let arr1 = match args.first().unwrap() {
         Value::Array(a) => a,
         _ => return Err(ControlFlow::RuntimeError("pour_together() expects arrays as arguments.".to_string())),
     };
@@ -296,9 +1118,10 @@ pub fn pour_together(args: Vec<Value>) -> Result<Value, ControlFlow> {
         _ => return Err(ControlFlow::RuntimeError("pour_together() expects arrays as arguments.".to_string())),
     };
 
-    let mut result = arr1.clone();
-    result.extend(arr2.clone());
-    Ok(Value::Array(result))
+    check_cup_growth("pour_together", arr1.len() + arr2.len())?;
+    let mut result = arr1.to_vec();
+    result.extend(arr2.iter().cloned());
+    Ok(Value::array(result))
 }
 
 pub fn extract_brew(args: Vec<Value>) -> Result<Value, ControlFlow> {
@@ -306,7 +1129,7 @@ pub fn extract_brew(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("extract_brew() expects 2 arguments, but got {}", args.len())));
     }
 
-    let arr = match args.get(0).unwrap() {
+    let arr = match args.first().unwrap() {
         Value::Array(a) => a,
         _ => return Err(ControlFlow::RuntimeError("extract_brew() expects an array as the first argument.".to_string())),
     };
@@ -328,11 +1151,11 @@ pub fn reverse_pour(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("reverse_pour() expects 1 argument, but got {}", args.len())));
     }
 
-    match args.get(0).unwrap() {
+    match args.first().unwrap() {
         Value::Array(arr) => {
-            let mut reversed = arr.clone();
+            let mut reversed = arr.to_vec();
             reversed.reverse();
-            Ok(Value::Array(reversed))
+            Ok(Value::array(reversed))
         },
         _ => Err(ControlFlow::RuntimeError("reverse_pour() expects an array as an argument.".to_string())),
     }
@@ -344,7 +1167,7 @@ pub fn brew_minimum(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("brew_minimum() expects 2 arguments, but got {}", args.len())));
     }
 
-    let n1 = match args.get(0).unwrap() {
+    let n1 = match args.first().unwrap() {
         Value::Number(n) => n,
         _ => return Err(ControlFlow::RuntimeError("brew_minimum() expects numbers as arguments.".to_string())),
     };
@@ -362,7 +1185,7 @@ pub fn brew_maximum(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("brew_maximum() expects 2 arguments, but got {}", args.len())));
     }
 
-    let n1 = match args.get(0).unwrap() {
+    let n1 = match args.first().unwrap() {
         Value::Number(n) => n,
         _ => return Err(ControlFlow::RuntimeError("brew_maximum() expects numbers as arguments.".to_string())),
     };
@@ -380,22 +1203,337 @@ pub fn perfect_temperature(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("perfect_temperature() expects 1 argument, but got {}", args.len())));
     }
 
-    match args.get(0).unwrap() {
+    match args.first().unwrap() {
         Value::Number(n) => Ok(Value::Number(n.round())),
         _ => Err(ControlFlow::RuntimeError("perfect_temperature() expects a number as an argument.".to_string())),
     }
 }
 
+/// Named numbers every recipe can read, unless it brews its own variable by the same name
+pub const BREW_CONSTANTS: &[(&str, f64)] = &[("PI_SHOT", std::f64::consts::PI), ("E_SPRESSO", std::f64::consts::E)];
+
+pub fn brew_constant(name: &str) -> Option<Value> {
+    BREW_CONSTANTS.iter().find(|(constant, _)| *constant == name).map(|(_, value)| Value::Number(*value))
+}
+
+/// One number in, one number out: the shape of sin, ln, exp and friends
+pub fn unary_math(native: &str, args: Vec<Value>, math: fn(f64) -> f64) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [Value::Number(n)] => Ok(Value::Number(math(*n))),
+        [other] => Err(ControlFlow::RuntimeError(format!("{}() expects a number, but got a {}", native, other.type_name()))),
+        _ => Err(ControlFlow::RuntimeError(format!("{}() expects 1 argument, but got {}", native, args.len()))),
+    }
+}
+
+/// `sign(n)`: -1, 0 or 1
+pub fn sign(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    unary_math("sign", args, |n| if n == 0.0 || n.is_nan() { n } else { n.signum() })
+}
+
+/// `clamp(n, low, high)`: n, kept between low and high
+pub fn clamp(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [Value::Number(n), Value::Number(low), Value::Number(high)] if low <= high => Ok(Value::Number(n.clamp(*low, *high))),
+        [Value::Number(_), Value::Number(low), Value::Number(high)] => {
+            Err(ControlFlow::RuntimeError(format!("clamp() expects low <= high, but got {} and {}", low, high)))
+        }
+        [_, _, _] => Err(ControlFlow::RuntimeError("clamp() expects three numbers".to_string())),
+        _ => Err(ControlFlow::RuntimeError(format!("clamp() expects 3 arguments, but got {}", args.len()))),
+    }
+}
+
+/// The numbers in a cup, spilling at the first thing that isn't one
+fn numeric_cup(native: &str, args: &[Value]) -> Result<Vec<f64>, ControlFlow> {
+    let cup = match args {
+        [Value::Array(cup)] => cup,
+        [other] => return Err(ControlFlow::RuntimeError(format!("{}() expects a cup of numbers, but got a {}", native, other.type_name()))),
+        _ => return Err(ControlFlow::RuntimeError(format!("{}() expects 1 argument, but got {}", native, args.len()))),
+    };
+    cup.iter()
+        .enumerate()
+        .map(|(index, item)| match item {
+            Value::Number(n) => Ok(*n),
+            other => Err(ControlFlow::RuntimeError(format!(
+                "{}() expects a cup of numbers, but item {} is a {}",
+                native,
+                index,
+                other.type_name()
+            ))),
+        })
+        .collect()
+}
+
+/// `sum_cup(cup)`: the total, 0 for an empty cup
+pub fn sum_cup(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    // Folding from 0.0 rather than `sum()`, whose empty sum is -0 and would pour out as "-0"
+    Ok(Value::Number(numeric_cup("sum_cup", &args)?.iter().fold(0.0, |total, n| total + n)))
+}
+
+/// `average_cup(cup)`: the mean of a non-empty cup
+pub fn average_cup(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let numbers = numeric_cup("average_cup", &args)?;
+    if numbers.is_empty() {
+        return Err(ControlFlow::RuntimeError("average_cup() can't average an empty cup".to_string()));
+    }
+    Ok(Value::Number(numbers.iter().sum::<f64>() / numbers.len() as f64))
+}
+
+/// `median_cup(cup)`: the middle number, or the mean of the middle two
+pub fn median_cup(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let mut numbers = numeric_cup("median_cup", &args)?;
+    if numbers.is_empty() {
+        return Err(ControlFlow::RuntimeError("median_cup() can't find the middle of an empty cup".to_string()));
+    }
+    numbers.sort_by(f64::total_cmp);
+    let middle = numbers.len() / 2;
+    Ok(Value::Number(if numbers.len() % 2 == 0 {
+        (numbers[middle - 1] + numbers[middle]) / 2.0
+    } else {
+        numbers[middle]
+    }))
+}
+
+// Number parsing and formatting
+/// `to_number(text, strict?)`: the number a string spells, or null when it doesn't
+/// spell one. With strict set to true a bad string spills instead.
+pub fn to_number(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (value, strict) = match args.as_slice() {
+        [value] => (value, false),
+        [value, Value::Boolean(strict)] => (value, *strict),
+        [_, _] => return Err(ControlFlow::RuntimeError("to_number() expects true/false as its second argument".to_string())),
+        _ => return Err(ControlFlow::RuntimeError(format!("to_number() expects 1 or 2 arguments, but got {}", args.len()))),
+    };
+    let text = match value {
+        Value::Number(n) => return Ok(Value::Number(*n)),
+        Value::String(text) => text,
+        other => return Err(ControlFlow::RuntimeError(format!("to_number() expects a string, but got a {}", other.type_name()))),
+    };
+    // "inf" and "NaN" parse as f64, but nobody typing them means a number
+    let trimmed = text.trim();
+    match number_literal(trimmed).or_else(|| trimmed.parse::<f64>().ok().filter(|n| n.is_finite())) {
+        Some(n) => Ok(Value::Number(n)),
+        None if strict => Err(ControlFlow::RuntimeError(format!("to_number() couldn't read '{}' as a number", text))),
+        None => Ok(Value::Null),
+    }
+}
+
+/// A number written the way a recipe would write it (`0x1F`, `0b1010`, `1_000.5e3`), maybe signed
+fn number_literal(text: &str) -> Option<f64> {
+    let (sign, digits) = match text.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, text.strip_prefix('+').unwrap_or(text)),
+    };
+    if !digits.starts_with(|ch: char| ch.is_ascii_digit()) {
+        return None;
+    }
+    let mut tokens = CoffeeTokenStream::new(digits);
+    match (tokens.next(), tokens.next()) {
+        (Some(SpannedToken { token: Token::Number(n), .. }), None) => Some(sign * n),
+        _ => None,
+    }
+}
+
+fn format_decimals(native: &str, value: Option<&Value>) -> Result<Option<usize>, ControlFlow> {
+    match value {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Number(n)) if n.fract() == 0.0 && (0.0..=20.0).contains(n) => Ok(Some(*n as usize)),
+        Some(_) => Err(ControlFlow::RuntimeError(format!("{}() expects decimals as a whole number from 0 to 20", native))),
+    }
+}
+
+/// `format_number(n, decimals?, separator?)`: fixed decimals (rounded), and the whole
+/// part grouped in threes with `separator`, e.g. format_number(1234.5, 2, ",") → "1,234.50"
+pub fn format_number(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let n = match args.first() {
+        Some(Value::Number(n)) if args.len() <= 3 => *n,
+        Some(_) if args.len() <= 3 => return Err(ControlFlow::RuntimeError("format_number() expects a number to format".to_string())),
+        _ => return Err(ControlFlow::RuntimeError(format!("format_number() expects 1 to 3 arguments, but got {}", args.len()))),
+    };
+    let decimals = format_decimals("format_number", args.get(1))?;
+    let separator = match args.get(2) {
+        None | Some(Value::Null) => "",
+        Some(Value::String(separator)) => separator.as_str(),
+        Some(_) => return Err(ControlFlow::RuntimeError("format_number() expects the thousands separator as a string".to_string())),
+    };
+    let formatted = match decimals {
+        Some(decimals) => format!("{:.*}", decimals, n),
+        None => n.to_string(),
+    };
+    if separator.is_empty() || !n.is_finite() {
+        return Ok(Value::String(formatted));
+    }
+    Ok(Value::String(group_thousands(&formatted, separator)))
+}
+
+/// "-1234567.5" → "-1,234,567.5" with "," as the separator
+fn group_thousands(formatted: &str, separator: &str) -> String {
+    let (sign, unsigned) = formatted.strip_prefix('-').map_or(("", formatted), |rest| ("-", rest));
+    let (whole, fraction) = unsigned.split_once('.').map_or((unsigned, None), |(whole, fraction)| (whole, Some(fraction)));
+    let mut grouped = String::from(sign);
+    for (index, digit) in whole.chars().enumerate() {
+        if index > 0 && (whole.len() - index) % 3 == 0 {
+            grouped.push_str(separator);
+        }
+        grouped.push(digit);
+    }
+    if let Some(fraction) = fraction {
+        grouped.push('.');
+        grouped.push_str(fraction);
+    }
+    grouped
+}
+
+/// `format_scientific(n, decimals?)`: scientific notation, e.g. format_scientific(12345, 2) → "1.23e4"
+pub fn format_scientific(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let n = match args.first() {
+        Some(Value::Number(n)) if args.len() <= 2 => *n,
+        Some(_) if args.len() <= 2 => return Err(ControlFlow::RuntimeError("format_scientific() expects a number to format".to_string())),
+        _ => return Err(ControlFlow::RuntimeError(format!("format_scientific() expects 1 or 2 arguments, but got {}", args.len()))),
+    };
+    Ok(Value::String(match format_decimals("format_scientific", args.get(1))? {
+        Some(decimals) => format!("{:.*e}", decimals, n),
+        None => format!("{:e}", n),
+    }))
+}
+
+/// `brew_format(template, ...values)`: fill `{}` placeholders in order, `{0}` by position, or
+/// `{name}` from a bean passed as the only value. After a `:` comes a spec of
+/// `[[fill]align][width][,][.precision][e]`, so `{1:.2}` is two decimals and `{:>8}` pads
+/// to eight characters on the left. `{{` and `}}` are literal braces.
+pub fn brew_format(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let (template, values) = match args.split_first() {
+        Some((Value::String(template), values)) => (template, values),
+        Some((other, _)) => return Err(ControlFlow::RuntimeError(format!("brew_format() expects a template string first, but got a {}", other.type_name()))),
+        None => return Err(ControlFlow::RuntimeError("brew_format() expects a template string and the values to fill it with".to_string())),
+    };
+    let mut brewed = String::new();
+    let mut next_position = 0;
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.as_str().starts_with('{') => {
+                chars.next();
+                brewed.push('{');
+            }
+            '}' if chars.as_str().starts_with('}') => {
+                chars.next();
+                brewed.push('}');
+            }
+            '{' => {
+                let rest = chars.as_str();
+                let close = rest
+                    .find('}')
+                    .ok_or_else(|| ControlFlow::RuntimeError("brew_format() found a '{' that's never closed (write '{{' for a literal brace)".to_string()))?;
+                let placeholder = &rest[..close];
+                chars = rest[close + 1..].chars();
+                let (key, spec) = placeholder.split_once(':').unwrap_or((placeholder, ""));
+                let value = match key.trim() {
+                    "" => {
+                        next_position += 1;
+                        format_value_at(values, next_position - 1)?
+                    }
+                    key if key.chars().all(|c| c.is_ascii_digit()) => format_value_at(values, key.parse().unwrap_or(usize::MAX))?,
+                    name => match values {
+                        [Value::Object { fields, .. }] => fields
+                            .get(name)
+                            .cloned()
+                            .ok_or_else(|| ControlFlow::RuntimeError(format!("brew_format() has no value named '{}' in the bean", name)))?,
+                        _ => return Err(ControlFlow::RuntimeError(format!("brew_format() needs a bean as the only value to fill {{{}}}", name))),
+                    },
+                };
+                brewed.push_str(&format_with_spec(&value, spec)?);
+            }
+            '}' => return Err(ControlFlow::RuntimeError("brew_format() found a '}' with no '{' (write '}}' for a literal brace)".to_string())),
+            c => brewed.push(c),
+        }
+    }
+    Ok(Value::String(brewed))
+}
+
+fn format_value_at(values: &[Value], position: usize) -> Result<Value, ControlFlow> {
+    values.get(position).cloned().ok_or_else(|| {
+        ControlFlow::RuntimeError(format!("brew_format() has no value for placeholder {} (got {} values)", position, values.len()))
+    })
+}
+
+/// Render one value by a `[[fill]align][width][,][.precision][e]` spec
+fn format_with_spec(value: &Value, spec: &str) -> Result<String, ControlFlow> {
+    let bad_spec = || ControlFlow::RuntimeError(format!("brew_format() doesn't understand the format spec ':{}'", spec));
+    let mut rest = spec;
+    let mut fill = ' ';
+    let mut align = None;
+    let mut leading = rest.chars();
+    match (leading.next(), leading.next()) {
+        (Some(f), Some(a @ ('<' | '>' | '^'))) => {
+            fill = f;
+            align = Some(a);
+            rest = leading.as_str();
+        }
+        (Some(a @ ('<' | '>' | '^')), _) => {
+            align = Some(a);
+            rest = &rest[1..];
+        }
+        _ => {}
+    }
+    let width_end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+    // Widths and precisions stop at u16::MAX, which is as far as Rust's formatting goes
+    let width = if width_end == 0 { 0 } else { rest[..width_end].parse::<u16>().map_err(|_| bad_spec())? as usize };
+    rest = &rest[width_end..];
+    let grouped = rest.starts_with(',');
+    if grouped {
+        rest = &rest[1..];
+    }
+    let mut precision = None;
+    if let Some(after_dot) = rest.strip_prefix('.') {
+        let digits_end = after_dot.find(|c: char| !c.is_ascii_digit()).unwrap_or(after_dot.len());
+        precision = Some(after_dot[..digits_end].parse::<u16>().map_err(|_| bad_spec())? as usize);
+        rest = &after_dot[digits_end..];
+    }
+    let scientific = match rest {
+        "" => false,
+        "e" => true,
+        _ => return Err(bad_spec()),
+    };
+
+    let text = match value {
+        Value::Number(n) => {
+            let text = match (scientific, precision) {
+                (true, Some(precision)) => format!("{:.*e}", precision, n),
+                (true, None) => format!("{:e}", n),
+                (false, Some(precision)) => format!("{:.*}", precision, n),
+                (false, None) => n.to_string(),
+            };
+            if grouped && !scientific && n.is_finite() {
+                group_thousands(&text, ",")
+            } else {
+                text
+            }
+        }
+        _ if grouped || scientific => {
+            return Err(ControlFlow::RuntimeError(format!("brew_format() can only use ',' or 'e' on numbers, not a {}", value.type_name())))
+        }
+        Value::String(text) => match precision {
+            Some(precision) => text.chars().take(precision).collect(),
+            None => text.clone(),
+        },
+        other => other.to_string(),
+    };
+
+    let padding = width.saturating_sub(text.chars().count());
+    let align = align.unwrap_or(if matches!(value, Value::Number(_)) { '>' } else { '<' });
+    let (before, after) = match align {
+        '>' => (padding, 0),
+        '^' => (padding / 2, padding - padding / 2),
+        _ => (0, padding),
+    };
+    let fill_with = |count: usize| std::iter::repeat_n(fill, count).collect::<String>();
+    Ok(format!("{}{}{}", fill_with(before), text, fill_with(after)))
+}
+
 // Coffee Shop Utilities
+/// `brewing_time()`: whole seconds since 1970. Read through chrono, whose clock also works in a browser.
 pub fn brewing_time() -> Result<Value, ControlFlow> {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as f64;
-    
-    Ok(Value::Number(timestamp))
+    Ok(Value::Number(chrono::Utc::now().timestamp() as f64))
 }
 
 pub fn coffee_strength_check(args: Vec<Value>) -> Result<Value, ControlFlow> {
@@ -403,7 +1541,7 @@ pub fn coffee_strength_check(args: Vec<Value>) -> Result<Value, ControlFlow> {
         return Err(ControlFlow::RuntimeError(format!("coffee_strength_check() expects 1 argument, but got {}", args.len())));
     }
 
-    match args.get(0).unwrap() {
+    match args.first().unwrap() {
         Value::Number(n) => {
             let strength = if *n < 3.0 {
                 "weak"
@@ -418,25 +1556,226 @@ pub fn coffee_strength_check(args: Vec<Value>) -> Result<Value, ControlFlow> {
     }
 }
 
+// Reflection
+/// `type_of_bean(value)`: "number", "string", "cup", "menu", a bean's name, ...
+pub fn type_of_bean(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [value] => Ok(Value::String(value.type_name().to_string())),
+        _ => Err(ControlFlow::RuntimeError(format!("type_of_bean() expects 1 argument, but got {}", args.len()))),
+    }
+}
+
+/// `bean_fields(obj)`: the names of an object's fields, sorted
+pub fn bean_fields(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let mut names: Vec<String> = match args.as_slice() {
+        [Value::Object { fields, .. }] => fields.keys().cloned().collect(),
+        [Value::Bean(decl)] => decl.fields.iter().map(|field| field.name.clone()).collect(),
+        [other] => return Err(ControlFlow::RuntimeError(format!("bean_fields() expects a bean, but got a {}", other.type_name()))),
+        _ => return Err(ControlFlow::RuntimeError(format!("bean_fields() expects 1 argument, but got {}", args.len()))),
+    };
+    names.sort();
+    Ok(Value::array(names.into_iter().map(Value::String).collect()))
+}
+
+/// `set_field(obj, name, value)`: `obj.name = value` with the name chosen at runtime; serves the value.
+/// Checks the arguments only: eval_call refills the field where obj came from
+pub fn set_field(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    match args.as_slice() {
+        [Value::Object { .. }, Value::String(_), value] => Ok(value.clone()),
+        [Value::Object { .. }, _, _] => Err(ControlFlow::RuntimeError("set_field() expects the field name as a string".to_string())),
+        [other, _, _] => Err(ControlFlow::RuntimeError(format!("set_field() expects a bean, but got a {}", other.type_name()))),
+        _ => Err(ControlFlow::RuntimeError(format!("set_field() expects 3 arguments, but got {}", args.len()))),
+    }
+}
+
 // Additional Type Checking
 pub fn is_cup(args: Vec<Value>) -> Result<Value, ControlFlow> {
     if args.len() != 1 {
         return Err(ControlFlow::RuntimeError(format!("is_cup() expects 1 argument, but got {}", args.len())));
     }
 
-    match args.get(0).unwrap() {
+    match args.first().unwrap() {
         Value::Array(_) => Ok(Value::Boolean(true)),
         _ => Ok(Value::Boolean(false)),
     }
 }
 
+pub fn is_bytes(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    if args.len() != 1 {
+        return Err(ControlFlow::RuntimeError(format!("is_bytes() expects 1 argument, but got {}", args.len())));
+    }
+    Ok(Value::Boolean(matches!(args[0], Value::Bytes(_))))
+}
+
+pub fn is_set(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    if args.len() != 1 {
+        return Err(ControlFlow::RuntimeError(format!("is_set() expects 1 argument, but got {}", args.len())));
+    }
+    Ok(Value::Boolean(matches!(args[0], Value::Set(_))))
+}
+
+pub fn is_chain(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    if args.len() != 1 {
+        return Err(ControlFlow::RuntimeError(format!("is_chain() expects 1 argument, but got {}", args.len())));
+    }
+    Ok(Value::Boolean(matches!(args[0], Value::Chain(_))))
+}
+
+pub fn is_sequence(args: Vec<Value>) -> Result<Value, ControlFlow> {
+    if args.len() != 1 {
+        return Err(ControlFlow::RuntimeError(format!("is_sequence() expects 1 argument, but got {}", args.len())));
+    }
+    Ok(Value::Boolean(matches!(args[0], Value::Sequence(_))))
+}
+
 pub fn is_boolean_bean(args: Vec<Value>) -> Result<Value, ControlFlow> {
     if args.len() != 1 {
         return Err(ControlFlow::RuntimeError(format!("is_boolean_bean() expects 1 argument, but got {}", args.len())));
     }
 
-    match args.get(0).unwrap() {
+    match args.first().unwrap() {
         Value::Boolean(_) => Ok(Value::Boolean(true)),
         _ => Ok(Value::Boolean(false)),
     }
-}
\ No newline at end of file
+}
+
+// Taste Test Assertions
+// These raise ordinary spills, so taste_carefully can catch them. `at` is the call site.
+pub fn assert_brew(args: Vec<Value>, at: (usize, usize)) -> Result<Value, ControlFlow> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(ControlFlow::RuntimeError(format!("assert_brew() expects 1 or 2 arguments, but got {}", args.len())));
+    }
+
+    match args.first().unwrap() {
+        Value::Boolean(true) => Ok(Value::Null),
+        Value::Boolean(false) => match args.get(1) {
+            Some(message) => Err(assertion_spill("assert_brew", at, &message.to_string(), &[])),
+            None => Err(assertion_spill("assert_brew", at, "the brew tasted false", &[])),
+        },
+        other => Err(assertion_spill(
+            "assert_brew",
+            at,
+            &format!("expected a boolean, but got a {}", other.type_name()),
+            &[("value", other)],
+        )),
+    }
+}
+
+pub fn assert_same_blend(args: Vec<Value>, at: (usize, usize)) -> Result<Value, ControlFlow> {
+    if args.len() != 2 {
+        return Err(ControlFlow::RuntimeError(format!("assert_same_blend() expects 2 arguments, but got {}", args.len())));
+    }
+
+    if same_blend(&args[0], &args[1]) {
+        Ok(Value::Null)
+    } else {
+        Err(assertion_spill("assert_same_blend", at, "the blends differ", &[("left", &args[0]), ("right", &args[1])]))
+    }
+}
+
+/// A failed assertion: what went wrong, where, and the values involved, one per line
+pub fn assertion_spill(assertion: &str, at: (usize, usize), reason: &str, values: &[(&str, &Value)]) -> ControlFlow {
+    let mut message = format!("{} failed at line {}, column {}: {}", assertion, at.0, at.1, reason);
+    for (label, value) in values {
+        let pretty = pretty_blend(value).replace('\n', "\n            ");
+        message.push_str(&format!("\n    {:<7} {}", format!("{}:", label), pretty));
+    }
+    ControlFlow::RuntimeError(message)
+}
+
+/// Render a value the way it would be written in a recipe. Beans' fields come out sorted,
+/// and anything too wide for one line is spread over several.
+pub fn pretty_blend(value: &Value) -> String {
+    pretty_blend_at(value, 0, 0)
+}
+
+fn pretty_blend_at(value: &Value, indent: usize, depth: usize) -> String {
+    const WIDTH: usize = 72;
+    if depth > 16 {
+        return "...".to_string(); // a cup holding itself, most likely
+    }
+    let (open, close, items): (String, &str, Vec<String>) = match value {
+        Value::Number(n) => return n.to_string(),
+        Value::String(s) => return format!("{:?}", s),
+        Value::Boolean(b) => return b.to_string(),
+        Value::Null => return "null".to_string(),
+        Value::Bean(bean) => return format!("bean {}", bean.name),
+        Value::Function { params, .. } => {
+            let names: Vec<&str> = params.iter().map(|param| param.name.as_str()).collect();
+            return format!("brew({})", names.join(", "));
+        }
+        Value::BoundMethod { class_name, params, .. } => {
+            let names: Vec<&str> = params.iter().map(|param| param.name.as_str()).collect();
+            return format!("brew {}({})", class_name, names.join(", "));
+        }
+        Value::Blend(blend) => return blend.recipe(),
+        Value::Sequence(sequence) => return sequence.recipe(),
+        Value::BigBrew(big) => return format!("big_brew(\"{}\")", big),
+        Value::Array(cup) => {
+            let items = cup.iter().map(|item| pretty_blend_at(item, indent + 4, depth + 1)).collect();
+            ("[".to_string(), "]", items)
+        }
+        // No bytes literal, so write the call that grinds them
+        Value::Bytes(bytes) => ("grind_to_bytes([".to_string(), "])", bytes.iter().map(|byte| byte.to_string()).collect()),
+        Value::Set(set) => {
+            let items = set.borrow().items().map(|item| pretty_blend_at(item, indent + 4, depth + 1)).collect();
+            ("flavor_set([".to_string(), "])", items)
+        }
+        Value::Chain(chain) => {
+            let items = chain.borrow().iter().map(|item| pretty_blend_at(item, indent + 4, depth + 1)).collect();
+            ("coffee_chain([".to_string(), "])", items)
+        }
+        Value::Object { class_name, fields } => {
+            let mut names: Vec<&String> = fields.keys().collect();
+            names.sort();
+            let items = names.into_iter()
+                .map(|name| format!("{}: {}", name, pretty_blend_at(&fields[name], indent + 4, depth + 1)))
+                .collect();
+            (format!("{} {{", class_name), "}", items)
+        }
+    };
+    if items.is_empty() {
+        return format!("{}{}", open, close);
+    }
+    let padded = open.ends_with('{');
+    let one_line = if padded {
+        format!("{} {} {}", open, items.join(", "), close)
+    } else {
+        format!("{}{}{}", open, items.join(", "), close)
+    };
+    if indent + one_line.len() <= WIDTH && !one_line.contains('\n') {
+        return one_line;
+    }
+    let inner = " ".repeat(indent + 4);
+    let lines: Vec<String> = items.iter().map(|item| format!("{}{},", inner, item)).collect();
+    format!("{}\n{}\n{}{}", open, lines.join("\n"), " ".repeat(indent), close)
+}
+
+/// Deep equality: cups and beans compare by what's inside, brews only to themselves
+pub fn same_blend(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a == b,
+        (Value::String(a), Value::String(b)) => a == b,
+        (Value::Boolean(a), Value::Boolean(b)) => a == b,
+        (Value::Null, Value::Null) => true,
+        (Value::Bytes(a), Value::Bytes(b)) => a == b,
+        (Value::Set(a), Value::Set(b)) => a.borrow().same_flavors(&b.borrow()),
+        (Value::Chain(a), Value::Chain(b)) => {
+            let (a, b) = (a.borrow(), b.borrow());
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| same_blend(a, b))
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| same_blend(a, b))
+        }
+        (Value::Object { class_name: a_class, fields: a }, Value::Object { class_name: b_class, fields: b }) => {
+            a_class == b_class
+                && a.len() == b.len()
+                && a.iter().all(|(name, value)| b.get(name).is_some_and(|other| same_blend(value, other)))
+        }
+        (Value::Bean(a), Value::Bean(b)) => a.name == b.name,
+        (Value::Function { body: a, .. }, Value::Function { body: b, .. }) => std::rc::Rc::ptr_eq(a, b),
+        (Value::Blend(a), Value::Blend(b)) => std::rc::Rc::ptr_eq(a, b),
+        (Value::BigBrew(a), Value::BigBrew(b)) => a == b,
+        _ => false,
+    }
+}
@@ -0,0 +1,91 @@
+/*
+ * 🗂️ Brewco Native Registry ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @menu_note: "Every native on one menu board, extras welcome!" ✨
+ *
+ * Each interpreter keeps its natives in a table: the built-in ones, then
+ * any that plugins or an embedding program add with
+ * `Interpreter::register_native`. An entry knows its parameters, written
+ * the way the README lists them ("s, width, fill?", "template, values..."),
+ * so calls with the wrong number of arguments spill before the native runs
+ * and the REPL's :natives can list everything without a hand-kept list.
+ */
+
+// src/native_registry.rs
+
+use std::fmt;
+use std::rc::Rc;
+
+use crate::interpreter::{ControlFlow, Interpreter, Value};
+
+/// What a native brews. It gets the interpreter, the name it was called by and its arguments.
+pub type NativeFn = Rc<dyn Fn(&mut Interpreter, &str, Vec<Value>) -> Result<Value, ControlFlow>>;
+
+/// How many arguments a native takes; no `most` means any number from `least` up
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct NativeArity {
+    pub least: usize,
+    pub most: Option<usize>,
+}
+
+impl NativeArity {
+    /// Read an arity from a parameter listing: `x?` may be left out, `xs...` (or `...xs`) takes the rest
+    pub fn from_params(params: &str) -> Self {
+        let mut arity = NativeArity { least: 0, most: Some(0) };
+        for param in params.split(',').map(str::trim).filter(|param| !param.is_empty()) {
+            if param.starts_with("...") || param.ends_with("...") {
+                arity.most = None;
+            } else if param.ends_with('?') {
+                arity.most = arity.most.map(|most| most + 1);
+            } else {
+                arity.least += 1;
+                arity.most = arity.most.map(|most| most + 1);
+            }
+        }
+        arity
+    }
+
+    pub fn accepts(&self, count: usize) -> bool {
+        count >= self.least && self.most.is_none_or(|most| count <= most)
+    }
+}
+
+impl fmt::Display for NativeArity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.most {
+            Some(1) if self.least == 1 => write!(f, "1 argument"),
+            Some(most) if most == self.least => write!(f, "{} arguments", most),
+            Some(most) if most == self.least + 1 => write!(f, "{} or {} arguments", self.least, most),
+            Some(most) => write!(f, "{} to {} arguments", self.least, most),
+            None if self.least == 1 => write!(f, "at least 1 argument"),
+            None => write!(f, "at least {} arguments", self.least),
+        }
+    }
+}
+
+/// One entry in an interpreter's native table
+pub struct NativeBrew {
+    /// Where :natives lists it, like "strings" or a plugin's name
+    pub group: String,
+    pub params: String,
+    pub arity: NativeArity,
+    pub brew: NativeFn,
+}
+
+impl NativeBrew {
+    pub fn new(group: &str, params: &str, brew: NativeFn) -> Self {
+        NativeBrew { group: group.to_string(), params: params.to_string(), arity: NativeArity::from_params(params), brew }
+    }
+
+    /// The spill for a call with the wrong number of arguments, or None when `count` fits
+    pub fn check_arity(&self, name: &str, count: usize) -> Option<ControlFlow> {
+        if self.arity.accepts(count) {
+            return None;
+        }
+        Some(ControlFlow::RuntimeError(match self.arity.most {
+            Some(0) => format!("{}() takes no arguments, but got {}", name, count),
+            _ => format!("{}() expects {} ({}), but got {}", name, self.arity, self.params, count),
+        }))
+    }
+}
@@ -0,0 +1,143 @@
+/*
+ * ⚖️ Brewco Pantry Scale ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @scale_note: "Weigh the beans before they take over the whole pantry!" ✨
+ *
+ * `pantry_memory()` and `pantry_stats()` weigh the pantry's live values,
+ * for finding the cup that keeps growing in a long-running script. The
+ * weights are estimates: each value's own slot plus what it keeps on the
+ * heap (a string's text, a cup's items, a menu's fields), without what the
+ * allocator adds on top. Cups, menus, sets, chains and bytes are shared by
+ * reference, so one scale weighs each of them once however many beans hold
+ * it. Brews weigh only their slot; their bodies belong to the parsed recipe.
+ */
+
+// src/pantry_scale.rs
+
+use std::collections::{HashMap, HashSet};
+use std::mem::size_of;
+use std::rc::Rc;
+
+use crate::flavor_set::BlendKey;
+use crate::interpreter::{ControlFlow, Value};
+
+/// How many bindings pantry_stats() lists when not told
+const PANTRY_STATS_LIMIT: usize = 10;
+
+/// What a scale has weighed so far, in total and by kind (`type_of_bean` names)
+#[derive(Default)]
+struct PantryScale {
+    bytes: usize,
+    kinds: HashMap<String, (usize, usize)>, // kind -> (values, bytes)
+    /// Shared values already on the scale, by address
+    weighed: HashSet<usize>,
+}
+
+impl PantryScale {
+    /// True the first time the shared value at `address` comes up
+    fn first_sight<T: ?Sized>(&mut self, shared: &Rc<T>) -> bool {
+        self.weighed.insert(Rc::as_ptr(shared) as *const () as usize)
+    }
+
+    /// Weigh `value` and everything in it that isn't on the scale yet; serves the bytes it added
+    fn weigh(&mut self, value: &Value) -> usize {
+        // A value's own slot and heap go under its kind; what it holds goes under theirs
+        let mut own = size_of::<Value>();
+        let mut held = 0;
+        match value {
+            Value::String(text) => own += text.capacity(),
+            Value::BigBrew(big) => own += big.digits() as usize / 2 + 1,
+            Value::Bytes(bytes) if self.first_sight(bytes) => own += bytes.capacity(),
+            Value::Array(cup) if self.first_sight(cup) => {
+                own += (cup.capacity() - cup.len()) * size_of::<Value>();
+                held += cup.iter().map(|item| self.weigh(item)).sum::<usize>();
+            }
+            Value::Chain(chain) if self.first_sight(chain) => {
+                let chain = chain.borrow();
+                own += (chain.capacity() - chain.len()) * size_of::<Value>();
+                held += chain.iter().map(|item| self.weigh(item)).sum::<usize>();
+            }
+            Value::Set(set) if self.first_sight(set) => {
+                let set = set.borrow();
+                own += set.len() * size_of::<BlendKey>();
+                held += set.items().map(|item| self.weigh(item)).sum::<usize>();
+            }
+            Value::Object { fields, .. } | Value::BoundMethod { this_obj: fields, .. } if self.first_sight(fields) => {
+                own += fields.capacity() * (size_of::<String>() + size_of::<Value>()) - fields.len() * size_of::<Value>();
+                own += fields.keys().map(String::capacity).sum::<usize>();
+                held += fields.values().map(|field| self.weigh(field)).sum::<usize>();
+            }
+            _ => {}
+        }
+        let kind = self.kinds.entry(value.type_name().to_string()).or_default();
+        kind.0 += 1;
+        kind.1 += own;
+        self.bytes += own;
+        own + held
+    }
+}
+
+/// `pantry_memory()`: about how many bytes the live values in `scopes` take up, as
+/// `{bytes, values, kinds}` where kinds maps each kind to `{count, bytes}`
+pub fn pantry_memory(scopes: &[HashMap<String, Value>]) -> Value {
+    let mut scale = PantryScale::default();
+    for value in scopes.iter().flat_map(HashMap::values) {
+        scale.weigh(value);
+    }
+    let values: usize = scale.kinds.values().map(|(count, _)| count).sum();
+    let kinds = scale
+        .kinds
+        .into_iter()
+        .map(|(kind, (count, bytes))| {
+            let tally = HashMap::from([
+                ("count".to_string(), Value::Number(count as f64)),
+                ("bytes".to_string(), Value::Number(bytes as f64)),
+            ]);
+            (kind, Value::object("", tally))
+        })
+        .collect();
+    Value::object(
+        "",
+        HashMap::from([
+            ("bytes".to_string(), Value::Number(scale.bytes as f64)),
+            ("values".to_string(), Value::Number(values as f64)),
+            ("kinds".to_string(), Value::object("", kinds)),
+        ]),
+    )
+}
+
+/// `pantry_stats(limit?)`: the heaviest bindings, heaviest first, as `{name, type, bytes}`
+/// menus (10 unless told). Each binding is weighed on its own, so a cup two beans share
+/// counts for both.
+pub fn pantry_stats(bindings: Vec<(String, Value)>, args: Vec<Value>) -> Result<Value, ControlFlow> {
+    let limit = match args.as_slice() {
+        [] => PANTRY_STATS_LIMIT,
+        [Value::Number(limit)] if limit.fract() == 0.0 && *limit >= 0.0 => *limit as usize,
+        _ => return Err(ControlFlow::RuntimeError("pantry_stats() expects an optional whole-number limit".to_string())),
+    };
+    let mut weights: Vec<(String, Value, usize)> = bindings
+        .into_iter()
+        .map(|(name, value)| {
+            let bytes = PantryScale::default().weigh(&value);
+            (name, value, bytes)
+        })
+        .collect();
+    // Heaviest first; names settle ties so the listing doesn't shuffle between calls
+    weights.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+    let stats = weights
+        .into_iter()
+        .take(limit)
+        .map(|(name, value, bytes)| {
+            Value::object(
+                "",
+                HashMap::from([
+                    ("name".to_string(), Value::String(name)),
+                    ("type".to_string(), Value::String(value.type_name().to_string())),
+                    ("bytes".to_string(), Value::Number(bytes as f64)),
+                ]),
+            )
+        })
+        .collect();
+    Ok(Value::array(stats))
+}
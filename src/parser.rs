@@ -14,52 +14,444 @@
 // src/parser.rs
 
 use crate::ast::*;
-use crate::lexer::Token;
-
+use crate::espresso_errors::{CoffeeSpillReport, SpillType};
+use crate::lexer::{CoffeeTokenStream, SpannedToken, Token};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::ops::Deref;
+
+/// Deepest the parser nests statements and expressions (long `a.b.c` or `1 + 2 + 3`
+/// chains count too). Deeper recipes spill instead of overflowing the stack here
+/// or in anything that walks the tree afterwards.
+const MAX_NESTING: usize = 256;
+
+#[derive(Clone, Default)]
 pub struct ParseResult {
     pub statements: Vec<Statement>,
     pub errors: Vec<String>,
+    pub error_positions: Vec<(usize, usize)>, // 1-based line and column of each error
+    pub statement_lines: Vec<usize>, // 1-based source line of each top-level statement
+    pub trivia: CoffeeTrivia,
 }
 
-pub fn parse(tokens: &[Token]) -> ParseResult {
-    let mut stmts = Vec::new();
-    let mut errors = Vec::new();
-    let mut i = 0;
-    // Skip leading newlines
-    while i < tokens.len() && tokens.get(i) == Some(&Token::Newline) {
-        i += 1;
+/// A comment or blank line the parser steps over
+#[derive(Debug, Clone, PartialEq)]
+pub enum Trivia {
+    Comment { line: usize, text: String }, // text keeps its 🎀 or // marker
+    BlankLine(usize),
+}
+
+/// Comments and blank lines kept in a side table keyed by 1-based source line,
+/// so the formatter and doc tools can find what was written around each statement
+#[derive(Debug, Clone, Default)]
+pub struct CoffeeTrivia {
+    pub leading: BTreeMap<usize, Vec<Trivia>>, // own-line trivia, keyed by the line of the code after it
+    pub trailing: BTreeMap<usize, String>,     // end-of-line comments, keyed by their line
+    pub closing: BTreeMap<usize, Vec<Trivia>>, // trivia just before a `}`, keyed by the brace's line
+    pub end_of_file: Vec<Trivia>,              // trivia after the last statement
+    pending: Vec<Trivia>,
+    last_token_line: usize,
+    last_code_line: usize,
+}
+
+impl CoffeeTrivia {
+    /// Note one token as the parser pours it; returns false for comments, which the parser never sees
+    fn note(&mut self, spanned: &SpannedToken) -> bool {
+        match &spanned.token {
+            Token::Comment(text) => {
+                if self.last_code_line == spanned.line {
+                    self.trailing.insert(spanned.line, text.clone());
+                } else {
+                    self.pending.push(Trivia::Comment { line: spanned.line, text: text.clone() });
+                }
+                self.last_token_line = spanned.line;
+                return false;
+            }
+            Token::Newline => {
+                if self.last_token_line != spanned.line {
+                    self.pending.push(Trivia::BlankLine(spanned.line));
+                }
+            }
+            token => {
+                if !self.pending.is_empty() {
+                    let table = if *token == Token::RBrace { &mut self.closing } else { &mut self.leading };
+                    table.entry(spanned.line).or_default().append(&mut self.pending);
+                }
+                // A string may run over several lines
+                self.last_code_line = spanned.end_line;
+                self.last_token_line = spanned.end_line;
+            }
+        }
+        true
+    }
+
+    /// The comment lines directly above `line` with their markers stripped,
+    /// i.e. the documentation of whatever is declared there
+    pub fn doc_comment(&self, line: usize) -> Option<String> {
+        let mut docs: Vec<&str> = self.leading.get(&line)?.iter().rev()
+            .map_while(|trivia| match trivia {
+                Trivia::Comment { text, .. } => Some(text.trim_start_matches('\u{1F380}').trim_start_matches("//").trim()),
+                Trivia::BlankLine(_) => None,
+            })
+            .collect();
+        docs.reverse();
+        if docs.is_empty() { None } else { Some(docs.join("\n")) }
+    }
+}
+
+/// Doc comments start with two bows; a single 🎀 is an ordinary comment
+pub const DOC_COMMENT_MARKER: &str = "\u{1F380}\u{1F380}";
+
+/// The 🎀🎀 lines above each line of code, markers stripped and joined, keyed by
+/// the code's 1-based line; the parser hangs them on the brews and beans declared there
+pub type BrewDocs = BTreeMap<usize, String>;
+
+/// Gathers 🎀🎀 lines until the code they sit above comes along
+#[derive(Default)]
+struct DocGatherer {
+    pending: Vec<String>,
+    last_code_line: usize,
+}
+
+impl DocGatherer {
+    fn note(&mut self, spanned: &SpannedToken, docs: &mut BrewDocs) {
+        match &spanned.token {
+            Token::Comment(text) if spanned.line != self.last_code_line => match text.strip_prefix(DOC_COMMENT_MARKER) {
+                // One space after the bows is padding; any more is indentation worth keeping, as in examples
+                Some(doc) => self.pending.push(doc.strip_prefix(' ').unwrap_or(doc).trim_end().to_string()),
+                None => self.pending.clear(),
+            },
+            Token::Comment(_) | Token::Newline => {}
+            _ => {
+                if !self.pending.is_empty() {
+                    docs.insert(spanned.line, self.pending.join("\n"));
+                    self.pending.clear();
+                }
+                self.last_code_line = spanned.end_line;
+            }
+        }
+    }
+}
+
+/// The tokens of one top-level statement plus where each one starts in the
+/// source. Derefs to the token slice the parse functions index into.
+struct TokenWindow<'a> {
+    tokens: &'a [Token],
+    positions: &'a [(usize, usize)],
+    docs: &'a BrewDocs,
+    depth: Cell<usize>,
+    stuck_at: Cell<Option<usize>>,    // furthest token a statement inside a block failed at
+    too_deep_at: Cell<Option<usize>>, // first token that went past MAX_NESTING
+}
+
+/// One level of nesting, given back when the parse function holding it returns
+struct NestingGuard<'a> {
+    depth: &'a Cell<usize>,
+}
+
+impl Drop for NestingGuard<'_> {
+    fn drop(&mut self) {
+        self.depth.set(self.depth.get() - 1);
+    }
+}
+
+impl<'a> Deref for TokenWindow<'a> {
+    type Target = [Token];
+
+    fn deref(&self) -> &[Token] {
+        self.tokens
+    }
+}
+
+impl<'a> TokenWindow<'a> {
+    fn new(tokens: &'a [Token], positions: &'a [(usize, usize)], docs: &'a BrewDocs) -> Self {
+        TokenWindow { tokens, positions, docs, depth: Cell::new(0), stuck_at: Cell::new(None), too_deep_at: Cell::new(None) }
+    }
+
+    /// The 🎀🎀 doc comment written above the declaration starting at `index`
+    fn doc_at(&self, index: usize) -> Option<String> {
+        let (line, _) = self.positions.get(index)?;
+        self.docs.get(line).cloned()
+    }
+
+    /// Go one level deeper until the guard is dropped, unless that's too deep
+    fn nest(&self, index: usize) -> Option<NestingGuard<'_>> {
+        if self.too_deep(index, 1) {
+            return None;
+        }
+        self.depth.set(self.depth.get() + 1);
+        Some(NestingGuard { depth: &self.depth })
+    }
+
+    /// Would `extra` more levels (say, links in a call chain) go past MAX_NESTING?
+    fn too_deep(&self, index: usize, extra: usize) -> bool {
+        if self.depth.get() + extra <= MAX_NESTING {
+            return false;
+        }
+        if self.too_deep_at.get().is_none() {
+            self.too_deep_at.set(Some(index));
+        }
+        true
+    }
+
+    fn note_stuck(&self, index: usize) {
+        if self.stuck_at.get().is_none_or(|stuck| index > stuck) {
+            self.stuck_at.set(Some(index));
+        }
     }
-    while i < tokens.len() {
-        match parse_statement(tokens, i) {
+
+    /// Source mark for the statement starting at `index` (newlines and semicolons skipped)
+    fn source_mark(&self, mut index: usize) -> Statement {
+        while index < self.tokens.len() && (self.tokens[index] == Token::Newline || self.tokens[index] == Token::Semicolon) {
+            index += 1;
+        }
+        let (line, column) = self.positions.get(index).or(self.positions.last()).copied().unwrap_or((0, 0));
+        Statement::SourceMark { line, column }
+    }
+}
+
+/// Parse straight from source text, lexing one top-level statement at a time
+pub fn parse_source(source: &str) -> ParseResult {
+    parse_stream(CoffeeTokenStream::with_comments(source))
+}
+
+/// Parse a token stream; comments in it (see `CoffeeTokenStream::with_comments`) land in `result.trivia`
+pub fn parse_stream(mut tokens: CoffeeTokenStream) -> ParseResult {
+    let mut result = ParseResult::default();
+    // Only the tokens of the statement being parsed are kept around
+    let mut window = Vec::new();
+    let mut positions = Vec::new();
+    let mut docs = BrewDocs::new();
+    let mut trivia = CoffeeTrivia::default();
+    while pour_statement_window(&mut tokens, &mut window, &mut positions, &mut docs, Some(&mut trivia)) {
+        parse_window(&window, &positions, &docs, &mut result);
+        window.clear();
+        positions.clear();
+        docs.clear();
+    }
+    trivia.end_of_file = std::mem::take(&mut trivia.pending);
+    result.trivia = trivia;
+    result
+}
+
+/// One top-level statement window: the source lines it covers (1-based, inclusive,
+/// counting the blank and comment lines before it) and what it parsed to
+#[derive(Clone)]
+pub struct ParsedChunk {
+    pub first_line: usize,
+    pub last_line: usize,
+    pub result: ParseResult,
+}
+
+impl ParsedChunk {
+    /// Move the chunk `delta` lines down (or up) after an edit above it
+    pub fn shift_lines(&mut self, delta: isize) {
+        let shift = |line: usize| (line as isize + delta).max(1) as usize;
+        self.first_line = shift(self.first_line);
+        self.last_line = shift(self.last_line);
+        for line in self.result.statement_lines.iter_mut() {
+            *line = shift(*line);
+        }
+        for (error, position) in self.result.errors.iter_mut().zip(self.result.error_positions.iter_mut()) {
+            let (line, column) = *position;
+            position.0 = shift(line);
+            // Messages spell out where they happened, so they move too
+            *error = error.replace(
+                &format!("at line {}, column {}", line, column),
+                &format!("at line {}, column {}", position.0, column),
+            );
+        }
+        shift_source_marks(&mut self.result.statements, &shift);
+    }
+}
+
+fn shift_source_marks(statements: &mut [Statement], shift: &dyn Fn(usize) -> usize) {
+    for statement in statements {
+        match statement {
+            Statement::SourceMark { line, .. } => *line = shift(*line),
+            Statement::If { then_branch, else_branch, .. } => {
+                shift_source_marks(then_branch, shift);
+                shift_source_marks(else_branch, shift);
+            }
+            Statement::For { init, body, .. } => {
+                if let Some(init) = init {
+                    shift_source_marks(std::slice::from_mut(init.as_mut()), shift);
+                }
+                shift_source_marks(body, shift);
+            }
+            Statement::While { body, .. }
+            | Statement::RoastDecl { body, .. }
+            | Statement::BrewDecl { body, .. }
+            | Statement::Foreach { body, .. }
+            | Statement::ConstructorDecl { body, .. }
+            | Statement::BenchBrew { body, .. }
+            | Statement::TasteTest { body, .. } => shift_source_marks(body, shift),
+            Statement::BeanDecl { methods, .. } => shift_source_marks(methods, shift),
+            Statement::RoastSwitch { arms, default, .. } => {
+                for (_, arm) in arms.iter_mut() {
+                    shift_source_marks(arm, shift);
+                }
+                shift_source_marks(default, shift);
+            }
+            Statement::TryCatch { try_branch, catch_branch, .. } => {
+                shift_source_marks(try_branch, shift);
+                shift_source_marks(catch_branch, shift);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Parses source one top-level statement window at a time, for callers that
+/// cache windows and only reparse the ones an edit touched
+pub struct CoffeeChunkParser<'a> {
+    tokens: CoffeeTokenStream<'a>,
+    line_offset: usize,
+    next_first_line: usize,
+}
+
+impl<'a> CoffeeChunkParser<'a> {
+    /// `source` starts at line `first_line` of the whole file; reported lines are file lines
+    pub fn new(source: &'a str, first_line: usize) -> Self {
+        CoffeeChunkParser {
+            tokens: CoffeeTokenStream::with_comments(source),
+            line_offset: first_line - 1,
+            next_first_line: first_line,
+        }
+    }
+}
+
+impl<'a> Iterator for CoffeeChunkParser<'a> {
+    type Item = ParsedChunk;
+
+    fn next(&mut self) -> Option<ParsedChunk> {
+        let mut window = Vec::new();
+        let mut positions = Vec::new();
+        let mut docs = BrewDocs::new();
+        if !pour_statement_window(&mut self.tokens, &mut window, &mut positions, &mut docs, None) {
+            return None;
+        }
+        for position in positions.iter_mut() {
+            position.0 += self.line_offset;
+        }
+        let docs: BrewDocs = docs.into_iter().map(|(line, doc)| (line + self.line_offset, doc)).collect();
+        let mut result = ParseResult::default();
+        parse_window(&window, &positions, &docs, &mut result);
+        let last_line = positions.last().map_or(self.next_first_line, |&(line, _)| line);
+        let chunk = ParsedChunk { first_line: self.next_first_line, last_line, result };
+        self.next_first_line = last_line + 1;
+        Some(chunk)
+    }
+}
+
+/// Parse every statement in one window into `result`
+fn parse_window(window: &[Token], positions: &[(usize, usize)], docs: &BrewDocs, result: &mut ParseResult) {
+    let window_view = TokenWindow::new(window, positions, docs);
+    let mut i = 0;
+    loop {
+        // Skip newlines and stray semicolons between statements
+        while i < window.len() && (window[i] == Token::Newline || window[i] == Token::Semicolon) {
+            i += 1;
+        }
+        if i >= window.len() {
+            break;
+        }
+        window_view.stuck_at.set(None);
+        window_view.too_deep_at.set(None);
+        match parse_statement(&window_view, i) {
             Some((st, ni)) => {
-                stmts.push(st);
+                let (line, column) = positions[i];
+                result.statements.push(Statement::SourceMark { line, column });
+                result.statement_lines.push(line);
+                result.statements.push(st);
+                result.statement_lines.push(line);
                 i = ni;
-                // Skip newlines after each statement
-                while i < tokens.len() && tokens.get(i) == Some(&Token::Newline) {
-                    i += 1;
-                }
             },
             None => {
-                let err_line = format!(
-                    "This syntax is never ever getting back together with the parser at token {}. You need to calm down, but this line is causing a stir!",
-                    i
-                );
-                errors.push(err_line);
-                // Skip to next newline or end
-                while i < tokens.len() && tokens.get(i) != Some(&Token::Newline) {
-                    i += 1;
+                let lex_error = (i..window.len()).find_map(|k| match &window[k] {
+                    Token::LexError(message) => Some((k, message.clone())),
+                    _ => None,
+                });
+                let too_deep = window_view.too_deep_at.get()
+                    .map(|k| (k, format!("this is nested more than {} levels deep", MAX_NESTING)));
+                // Point at the statement inside a block that broke, not just where the outer one starts
+                let stuck_at = window_view.stuck_at.get().filter(|&k| k > i).unwrap_or(i);
+                let (k, err_line) = match lex_error.or(too_deep) {
+                    Some((k, message)) => {
+                        let (line, column) = positions[k];
+                        (k, CoffeeSpillReport::new_brewing_disaster(SpillType::UnexpectedIngredient, line, column, &message).brief())
+                    }
+                    None => (stuck_at, format!(
+                        "This syntax is never ever getting back together with the parser at line {}, column {}. You need to calm down, but this line is causing a stir!",
+                        positions[stuck_at].0, positions[stuck_at].1
+                    )),
+                };
+                let (line, column) = positions[k];
+                // Recovery restarts on the next line, which can trip over the same broken spot again
+                if result.error_positions.last() != Some(&(line, column)) {
+                    result.errors.push(err_line);
+                    result.error_positions.push((line, column));
                 }
-                while i < tokens.len() && tokens.get(i) == Some(&Token::Newline) {
+                // Skip to next newline or end
+                while i < window.len() && window[i] != Token::Newline {
                     i += 1;
                 }
             }
         }
     }
-    ParseResult { statements: stmts, errors }
 }
 
-fn parse_statement(t: &[Token], mut i: usize) -> Option<(Statement, usize)> {
+/// Pull the next top-level statement into `window`: everything up to a newline
+/// outside any brackets that doesn't follow a trailing comma. Comments never
+/// reach the window; they and blank lines are noted in `trivia` when given.
+fn pour_statement_window(
+    tokens: &mut CoffeeTokenStream,
+    window: &mut Vec<Token>,
+    positions: &mut Vec<(usize, usize)>,
+    docs: &mut BrewDocs,
+    mut trivia: Option<&mut CoffeeTrivia>,
+) -> bool {
+    let mut gatherer = DocGatherer::default();
+    let mut keep = |spanned: &SpannedToken| {
+        gatherer.note(spanned, docs);
+        match trivia.as_deref_mut() {
+            Some(trivia) => trivia.note(spanned),
+            None => !matches!(spanned.token, Token::Comment(_)),
+        }
+    };
+    // Skip blank and comment lines before the statement
+    while tokens.peek().is_some_and(|spanned| matches!(spanned.token, Token::Newline | Token::Comment(_))) {
+        if let Some(spanned) = tokens.next() {
+            keep(&spanned);
+        }
+    }
+    let mut depth = 0usize;
+    let mut after_comma = false;
+    for spanned in tokens.by_ref() {
+        if !keep(&spanned) {
+            continue;
+        }
+        match spanned.token {
+            Token::LParen | Token::LBrace | Token::LBracket => depth += 1,
+            Token::RParen | Token::RBrace | Token::RBracket => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        let ends_statement = spanned.token == Token::Newline && depth == 0 && !after_comma;
+        if spanned.token != Token::Newline {
+            after_comma = spanned.token == Token::Comma;
+        }
+        window.push(spanned.token);
+        positions.push((spanned.line, spanned.column));
+        if ends_statement {
+            break;
+        }
+    }
+    !window.is_empty()
+}
+
+fn parse_statement(t: &TokenWindow, mut i: usize) -> Option<(Statement, usize)> {
     use Token::*;
+    let _nesting = t.nest(i)?;
     // Skip newlines or semicolons
     while i < t.len() && (t.get(i) == Some(&Newline) || t.get(i) == Some(&Semicolon)) {
         i += 1;
@@ -77,7 +469,7 @@ fn parse_statement(t: &[Token], mut i: usize) -> Option<(Statement, usize)> {
 
     // Return statement: serve [expr]
     if t.get(i) == Some(&Serve) {
-        let mut j = i + 1;
+        let j = i + 1;
         // Check if there's a return value
         if j < t.len() && t.get(j) != Some(&Newline) && t.get(j) != Some(&Semicolon) {
             let (expr, nj) = parse_expr(t, j)?;
@@ -87,6 +479,12 @@ fn parse_statement(t: &[Token], mut i: usize) -> Option<(Statement, usize)> {
         }
     }
 
+    // Generator step: yield_sip <expr>
+    if t.get(i) == Some(&YieldSip) {
+        let (expr, nj) = parse_expr(t, i + 1)?;
+        return Some((Statement::YieldSip(expr), nj));
+    }
+
     // While loop: steep <cond> { body }
     if t.get(i) == Some(&Steep) {
         return parse_while(t, i);
@@ -121,6 +519,16 @@ fn parse_statement(t: &[Token], mut i: usize) -> Option<(Statement, usize)> {
         return parse_coffee_recipe_declaration(t, i);
     }
 
+    // Benchmark block: bench_brew "name" { body }
+    if t.get(i) == Some(&BenchBrew) {
+        return parse_bench_brew(t, i);
+    }
+
+    // Test block: taste_test "name" { body }
+    if t.get(i) == Some(&TasteTest) {
+        return parse_taste_test(t, i);
+    }
+
     // Function declaration: brew <identifier>(<params>) { body }
     if t.get(i) == Some(&Brew) {
         return parse_brew_declaration(t, i);
@@ -131,9 +539,9 @@ fn parse_statement(t: &[Token], mut i: usize) -> Option<(Statement, usize)> {
         return parse_variable_declaration(t, i);
     }
 
-    // Handle print statement: pourout <expr>[, <expr> ...]
+    // Handle print statement: pourout <expr>[, <expr> ...], or pourout_flat / spill_out
     if let Some(Token::Identifier(id)) = t.get(i) {
-        if id == "pourout" {
+        if let Some(spout) = PourSpout::from_word(id) {
             let mut args = Vec::new();
             let mut j = i + 1;
             // Skip leading newlines
@@ -154,19 +562,28 @@ fn parse_statement(t: &[Token], mut i: usize) -> Option<(Statement, usize)> {
             } else {
                 Expr::ArrayLiteral(args)
             };
-            return Some((Statement::Print(expr), j));
+            return Some((Statement::Print(expr, spout), j));
         }
     }
 
     let (expr, ni) = parse_expr(t, i)?;
+    // A pipeline ending in `then_pour pourout` prints what came through it
+    if let Expr::Call { callee, args } = &expr {
+        if let Expr::Identifier(id) = callee.as_ref() {
+            if let (Some(spout), [arg]) = (PourSpout::from_word(id), args.as_slice()) {
+                return Some((Statement::Print(arg.clone(), spout), ni));
+            }
+        }
+    }
     Some((Statement::ExprStmt(expr), ni))
 }
 
-fn parse_expr(t: &[Token], i: usize) -> Option<(Expr, usize)> {
+fn parse_expr(t: &TokenWindow, i: usize) -> Option<(Expr, usize)> {
+    let _nesting = t.nest(i)?;
     parse_assignment(t, i)
 }
 
-fn parse_assignment(t: &[Token], i: usize) -> Option<(Expr, usize)> {
+fn parse_assignment(t: &TokenWindow, i: usize) -> Option<(Expr, usize)> {
     let (expr, ni) = parse_binary_op(t, i, 0)?;
 
     if ni < t.len() {
@@ -190,9 +607,10 @@ fn parse_assignment(t: &[Token], i: usize) -> Option<(Expr, usize)> {
     Some((expr, ni))
 }
 
-fn parse_binary_op(t: &[Token], mut i: usize, min_prec: u8) -> Option<(Expr, usize)> {
+fn parse_binary_op(t: &TokenWindow, mut i: usize, min_prec: u8) -> Option<(Expr, usize)> {
     let (mut lhs, ni) = parse_unary_op(t, i)?;
     i = ni;
+    let mut chain = 0;
     while i < t.len() {
         let op = match t.get(i) {
             Some(tok) => match op_prec(tok) {
@@ -203,9 +621,17 @@ fn parse_binary_op(t: &[Token], mut i: usize, min_prec: u8) -> Option<(Expr, usi
         };
         let prec = op_prec(t.get(i)?).unwrap().0;
         let next_min_prec = prec + 1;
+        chain += 1;
+        if t.too_deep(i, chain) {
+            return None;
+        }
         i += 1;
         let (rhs, nni) = parse_binary_op(t, i, next_min_prec)?;
         i = nni;
+        if op == BinaryOperator::ThenPour {
+            lhs = pour_into(lhs, rhs);
+            continue;
+        }
         lhs = Expr::BinaryOp {
             left: Box::new(lhs),
             op,
@@ -215,20 +641,39 @@ fn parse_binary_op(t: &[Token], mut i: usize, min_prec: u8) -> Option<(Expr, usi
     Some((lhs, i))
 }
 
-fn parse_unary_op(t: &[Token], i: usize) -> Option<(Expr, usize)> {
+/// `value then_pour f` is `f(value)`, and `value then_pour f(a)` is `f(value, a)`
+fn pour_into(value: Expr, rhs: Expr) -> Expr {
+    match rhs {
+        Expr::Call { callee, mut args } => {
+            args.insert(0, value);
+            Expr::Call { callee, args }
+        }
+        callee => Expr::Call { callee: Box::new(callee), args: vec![value] },
+    }
+}
+
+fn parse_unary_op(t: &TokenWindow, i: usize) -> Option<(Expr, usize)> {
     let op = match t.get(i)? {
         Token::Sip => UnaryOperator::Negate,
         Token::NoFoam => UnaryOperator::Not,
         Token::Invert => UnaryOperator::BitNot,
         _ => return parse_call(t, i),
     };
+    let _nesting = t.nest(i)?;
     let (expr, ni) = parse_unary_op(t, i + 1)?;
     Some((Expr::UnaryOp { op, expr: Box::new(expr) }, ni))
 }
 
-fn parse_call(t: &[Token], i: usize) -> Option<(Expr, usize)> {
+fn parse_call(t: &TokenWindow, i: usize) -> Option<(Expr, usize)> {
     let (mut expr, mut ni) = parse_primary(t, i)?;
+    let mut chain = 0;
     while ni < t.len() {
+        if matches!(t.get(ni), Some(Token::LParen | Token::Dot | Token::LBracket)) {
+            chain += 1;
+            if t.too_deep(ni, chain) {
+                return None;
+            }
+        }
         if t.get(ni) == Some(&Token::LParen) {
             let (args, nni) = parse_args(t, ni + 1)?;
             expr = Expr::Call { callee: Box::new(expr), args };
@@ -252,13 +697,17 @@ fn parse_call(t: &[Token], i: usize) -> Option<(Expr, usize)> {
     Some((expr, ni))
 }
 
-fn parse_args(t: &[Token], mut i: usize) -> Option<(Vec<Expr>, usize)> {
+fn parse_args(t: &TokenWindow, mut i: usize) -> Option<(Vec<Expr>, usize)> {
     let mut args = Vec::new();
+    // Long argument lists may be wrapped one argument per line
+    while i < t.len() && t.get(i) == Some(&Token::Newline) { i += 1; }
     if t.get(i) == Some(&Token::RParen) { return Some((args, i + 1)); }
     loop {
+        while i < t.len() && t.get(i) == Some(&Token::Newline) { i += 1; }
         let (arg, ni) = parse_expr(t, i)?;
         args.push(arg);
         i = ni;
+        while i < t.len() && t.get(i) == Some(&Token::Newline) { i += 1; }
         if t.get(i) == Some(&Token::Comma) {
             i += 1;
         } else if t.get(i) == Some(&Token::RParen) {
@@ -269,7 +718,7 @@ fn parse_args(t: &[Token], mut i: usize) -> Option<(Vec<Expr>, usize)> {
     }
 }
 
-fn parse_primary(t: &[Token], i: usize) -> Option<(Expr, usize)> {
+fn parse_primary(t: &TokenWindow, i: usize) -> Option<(Expr, usize)> {
     match t.get(i)? {
         Token::Number(n) => Some((Expr::Number(*n), i + 1)),
         Token::String(s) => Some((Expr::String(s.clone()), i + 1)),
@@ -283,7 +732,7 @@ fn parse_primary(t: &[Token], i: usize) -> Option<(Expr, usize)> {
         Token::New => {
             // Parse 'new ClassName(args)'
             if let Some(Token::Identifier(class_name)) = t.get(i + 1) {
-                let mut j = i + 2;
+                let j = i + 2;
                 if t.get(j) == Some(&Token::LParen) {
                     let (args, nj) = parse_args(t, j + 1)?;
                     Some((Expr::NewBean { name: class_name.clone(), args }, nj))
@@ -316,7 +765,7 @@ fn parse_primary(t: &[Token], i: usize) -> Option<(Expr, usize)> {
     }
 }
 
-fn parse_array_literal(t: &[Token], mut i: usize) -> Option<(Expr, usize)> {
+fn parse_array_literal(t: &TokenWindow, mut i: usize) -> Option<(Expr, usize)> {
     use Token::*;
     let mut elements = Vec::new();
     // Skip leading newlines
@@ -341,7 +790,7 @@ fn parse_array_literal(t: &[Token], mut i: usize) -> Option<(Expr, usize)> {
     }
 }
 
-fn parse_object_literal(t: &[Token], mut i: usize) -> Option<(Expr, usize)> {
+fn parse_object_literal(t: &TokenWindow, mut i: usize) -> Option<(Expr, usize)> {
     use Token::*;
     let mut fields = Vec::new();
     // Skip leading newlines
@@ -387,7 +836,10 @@ fn parse_object_literal(t: &[Token], mut i: usize) -> Option<(Expr, usize)> {
 fn op_prec(tok: &Token) -> Option<(u8, BinaryOperator)> {
     use BinaryOperator::*;
     let (prec, op) = match tok {
-        // Logical operators (lowest precedence)
+        // Pipeline (lowest precedence): `x then_pour f` becomes `f(x)`
+        Token::ThenPour => (0, ThenPour),
+
+        // Logical operators
         Token::Or => (1, Or),
         Token::With => (2, And),
         
@@ -442,30 +894,34 @@ fn op_prec(tok: &Token) -> Option<(u8, BinaryOperator)> {
 
 // ---------------------- Helper parsing routines ---------------------------
 
-fn parse_block(t: &[Token], mut i: usize) -> Option<(Vec<Statement>, usize)> {
+fn parse_block(t: &TokenWindow, mut i: usize) -> Option<(Vec<Statement>, usize)> {
     use Token::*;
     if t.get(i) != Some(&LBrace) { return None; }
     i += 1;
     let mut stmts = Vec::new();
     while i < t.len() {
+        while i < t.len() && (t.get(i) == Some(&Newline) || t.get(i) == Some(&Semicolon)) {
+            i += 1;
+        }
         if t.get(i) == Some(&RBrace) {
             return Some((stmts, i + 1));
         }
-        match parse_statement(t, i) {
-            Some((st, ni)) => {
-                stmts.push(st);
-                i = ni;
-            }
-            None => {
-                // Skip problematic token to avoid infinite loop
-                i += 1;
-            }
+        if i >= t.len() {
+            break;
         }
+        // A statement that won't parse sinks the whole block; skipping ahead would quietly drop code
+        let Some((st, ni)) = parse_statement(t, i) else {
+            t.note_stuck(i);
+            return None;
+        };
+        stmts.push(t.source_mark(i));
+        stmts.push(st);
+        i = ni;
     }
     None
 }
 
-fn parse_if(t: &[Token], i: usize) -> Option<(Statement, usize)> {
+fn parse_if(t: &TokenWindow, i: usize) -> Option<(Statement, usize)> {
     use Token::*;
     // Expect Taste
     if t.get(i) != Some(&Taste) { return None; }
@@ -482,7 +938,7 @@ fn parse_if(t: &[Token], i: usize) -> Option<(Statement, usize)> {
     Some((Statement::If { condition, then_branch, else_branch }, j))
 }
 
-fn parse_while(t: &[Token], i: usize) -> Option<(Statement, usize)> {
+fn parse_while(t: &TokenWindow, i: usize) -> Option<(Statement, usize)> {
     use Token::*;
     if t.get(i) != Some(&Steep) { return None; }
     let (condition, j) = parse_expr(t, i + 1)?;
@@ -490,14 +946,15 @@ fn parse_while(t: &[Token], i: usize) -> Option<(Statement, usize)> {
     Some((Statement::While { condition, body }, k))
 }
 
-fn parse_for(t: &[Token], i: usize) -> Option<(Statement, usize)> {
+fn parse_for(t: &TokenWindow, i: usize) -> Option<(Statement, usize)> {
     use Token::*;
     if t.get(i) != Some(&Pour) { return None; }
     let mut j = i + 1;
 
-    // Check if this is a foreach loop: pour var in iterable
-    if let Some(Token::Identifier(var_name)) = t.get(j) {
-        if t.get(j + 1) == Some(&In) {
+    // Check if this is a foreach loop: pour var in iterable, or pour var, second_var in iterable
+    if let Some(Token::Identifier(_)) = t.get(j) {
+        let paired = t.get(j + 1) == Some(&Comma) && matches!(t.get(j + 2), Some(Token::Identifier(_))) && t.get(j + 3) == Some(&In);
+        if t.get(j + 1) == Some(&In) || paired {
             return parse_foreach(t, i);
         }
     }
@@ -537,26 +994,38 @@ fn parse_for(t: &[Token], i: usize) -> Option<(Statement, usize)> {
     Some((Statement::For { init, condition, increment, body }, ni))
 }
 
-fn parse_foreach(t: &[Token], i: usize) -> Option<(Statement, usize)> {
+fn parse_foreach(t: &TokenWindow, i: usize) -> Option<(Statement, usize)> {
     use Token::*;
     if t.get(i) != Some(&Pour) { return None; }
     
-    // pour var in iterable { body }
+    // pour var[, second_var] in iterable { body }
     let var = if let Some(Token::Identifier(name)) = t.get(i + 1) {
         name.clone()
     } else {
         return None;
     };
+    let mut j = i + 2;
 
-    if t.get(i + 2) != Some(&In) { return None; }
+    let second_var = if t.get(j) == Some(&Comma) {
+        if let Some(Token::Identifier(name)) = t.get(j + 1) {
+            j += 2;
+            Some(name.clone())
+        } else {
+            return None;
+        }
+    } else {
+        None
+    };
+
+    if t.get(j) != Some(&In) { return None; }
 
-    let (iterable, ni) = parse_expr(t, i + 3)?;
+    let (iterable, ni) = parse_expr(t, j + 1)?;
     let (body, nni) = parse_block(t, ni)?;
 
-    Some((Statement::Foreach { var, iterable, body }, nni))
+    Some((Statement::Foreach { var, second_var, iterable, body }, nni))
 }
 
-fn parse_roast(t: &[Token], i: usize) -> Option<(Statement, usize)> {
+fn parse_roast(t: &TokenWindow, i: usize) -> Option<(Statement, usize)> {
     use Token::*;
     if t.get(i) != Some(&Roast) { return None; }
     // value expression after Roast
@@ -579,12 +1048,24 @@ fn parse_roast(t: &[Token], i: usize) -> Option<(Statement, usize)> {
             default_branch = body;
             j = nj;
         } else {
-            // case value
-            let (case_expr, nj) = parse_expr(t, j)?;
-            j = nj;
+            // case values: `60, 61:` or a range `0..59:`
+            let mut patterns = Vec::new();
+            loop {
+                let (case_expr, nj) = parse_expr(t, j)?;
+                j = nj;
+                if t.get(j) == Some(&DotDot) {
+                    let (upper, nj) = parse_expr(t, j + 1)?;
+                    j = nj;
+                    patterns.push(RoastPattern::Range(case_expr, upper));
+                } else {
+                    patterns.push(RoastPattern::Value(case_expr));
+                }
+                if t.get(j) != Some(&Comma) { break; }
+                j += 1;
+            }
             if t.get(j) != Some(&Colon) { return None; }
             let (body, nj) = parse_case_body(t, j + 1)?;
-            arms.push((case_expr, body));
+            arms.push((patterns, body));
             j = nj;
         }
     }
@@ -592,20 +1073,21 @@ fn parse_roast(t: &[Token], i: usize) -> Option<(Statement, usize)> {
     Some((Statement::RoastSwitch { value: value_expr, arms, default: default_branch }, j + 1))
 }
 
-fn parse_case_body(t: &[Token], i: usize) -> Option<(Vec<Statement>, usize)> {
+fn parse_case_body(t: &TokenWindow, i: usize) -> Option<(Vec<Statement>, usize)> {
     let mut body = Vec::new();
     // Case body can be a block or a single statement
     if t.get(i) == Some(&Token::LBrace) {
-        return parse_block(t, i);
+        parse_block(t, i)
     } else {
         // Single statement case
         let (stmt, ni) = parse_statement(t, i)?;
+        body.push(t.source_mark(i));
         body.push(stmt);
-        return Some((body, ni));
+        Some((body, ni))
     }
 }
 
-fn parse_try_catch(t: &[Token], mut i: usize) -> Option<(Statement, usize)> {
+fn parse_try_catch(t: &TokenWindow, mut i: usize) -> Option<(Statement, usize)> {
     // taste_carefully { try_body } if_spilled [(<err_var>)] { catch_body }
     if t.get(i) != Some(&Token::TasteCarefully) { return None; }
     i += 1;
@@ -633,7 +1115,7 @@ fn parse_try_catch(t: &[Token], mut i: usize) -> Option<(Statement, usize)> {
     Some((Statement::TryCatch { try_branch, error_variable, catch_branch }, ni))
 }
 
-fn parse_variable_declaration(t: &[Token], i: usize) -> Option<(Statement, usize)> {
+fn parse_variable_declaration(t: &TokenWindow, i: usize) -> Option<(Statement, usize)> {
     // Expects 'beans' at t[i]
     if let Some(Token::Identifier(name)) = t.get(i + 1) {
         let mut j = i + 2;
@@ -660,8 +1142,9 @@ fn parse_variable_declaration(t: &[Token], i: usize) -> Option<(Statement, usize
     }
 }
 
-fn parse_bean_declaration(t: &[Token], mut i: usize) -> Option<(Statement, usize)> {
+fn parse_bean_declaration(t: &TokenWindow, mut i: usize) -> Option<(Statement, usize)> {
     if t.get(i) != Some(&Token::Bean) { return None; }
+    let doc = t.doc_at(i);
     i += 1;
 
     let name = if let Some(Token::Identifier(name)) = t.get(i) {
@@ -724,11 +1207,12 @@ fn parse_bean_declaration(t: &[Token], mut i: usize) -> Option<(Statement, usize
     if t.get(i) != Some(&Token::RBrace) { return None; }
     i += 1;
 
-    Some((Statement::BeanDecl { name, parent, fields, methods }, i))
+    Some((Statement::BeanDecl { name, parent, fields, methods, doc }, i))
 }
 
-fn parse_brew_declaration(t: &[Token], mut i: usize) -> Option<(Statement, usize)> {
+fn parse_brew_declaration(t: &TokenWindow, mut i: usize) -> Option<(Statement, usize)> {
     if t.get(i) != Some(&Token::Brew) { return None; }
+    let doc = t.doc_at(i);
     i += 1;
 
     let name = if let Some(Token::Identifier(name)) = t.get(i) {
@@ -761,10 +1245,32 @@ fn parse_brew_declaration(t: &[Token], mut i: usize) -> Option<(Statement, usize
     let (body, ni) = parse_block(t, i)?;
     i = ni;
 
-    Some((Statement::BrewDecl { name, params, body, return_type }, i))
+    Some((Statement::BrewDecl { name, params, body, return_type, doc }, i))
+}
+
+fn parse_bench_brew(t: &TokenWindow, i: usize) -> Option<(Statement, usize)> {
+    if t.get(i) != Some(&Token::BenchBrew) { return None; }
+    let name = if let Some(Token::String(name)) = t.get(i + 1) {
+        name.clone()
+    } else {
+        return None; // Expected a string name after 'bench_brew'
+    };
+    let (body, ni) = parse_block(t, i + 2)?;
+    Some((Statement::BenchBrew { name, body }, ni))
+}
+
+fn parse_taste_test(t: &TokenWindow, i: usize) -> Option<(Statement, usize)> {
+    if t.get(i) != Some(&Token::TasteTest) { return None; }
+    let name = if let Some(Token::String(name)) = t.get(i + 1) {
+        name.clone()
+    } else {
+        return None; // Expected a string name after 'taste_test'
+    };
+    let (body, ni) = parse_block(t, i + 2)?;
+    Some((Statement::TasteTest { name, body }, ni))
 }
 
-fn parse_params(t: &[Token], mut i: usize) -> Option<(Vec<ParamDecl>, usize)> {
+fn parse_params(t: &TokenWindow, mut i: usize) -> Option<(Vec<ParamDecl>, usize)> {
     let mut params = Vec::new();
     if t.get(i) == Some(&Token::RParen) {
         return Some((params, i));
@@ -800,7 +1306,7 @@ fn parse_params(t: &[Token], mut i: usize) -> Option<(Vec<ParamDecl>, usize)> {
     Some((params, i))
 }
 
-fn parse_coffee_recipe_declaration(t: &[Token], mut i: usize) -> Option<(Statement, usize)> {
+fn parse_coffee_recipe_declaration(t: &TokenWindow, mut i: usize) -> Option<(Statement, usize)> {
     if t.get(i) != Some(&Token::CoffeeRecipe) { return None; }
     i += 1;
 
@@ -0,0 +1,175 @@
+/*
+ * 🏠 Brewco Roastery Config ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @house_note: "Every roastery brews its own way, and remembers it!" ✨
+ *
+ * A `brewco.toml` at the roastery root sets the defaults a project would
+ * otherwise repeat on every command line. `brew`, `brew check`, `brew fmt`
+ * and `brew lint` look for it in the current directory and its parents:
+ *
+ *     [brew]
+ *     strict = true           # like --strict-brew
+ *     deny_warnings = true    # like --deny-warnings
+ *     opt_level = "triple"    # --turbo passes: decaf, single, double, triple or turbo
+ *     memory_model = "manual" # cycles collected only by grind_and_clean(); the default
+ *                             # "grind_and_clean" also collects them as a recipe brews
+ *     lang = "es"             # like --lang
+ *     plugins = ["native/libgrinder.so"]   # native plugins, from the roastery root
+ *
+ *     [allow]
+ *     fs = true               # like --allow-fs (and exec, net)
+ *
+ *     [fmt]
+ *     width = 80
+ *
+ *     [lint]                  # the same settings as a brewlint.toml
+ *     max_complexity = 12
+ *     [lint.rules]
+ *     unused_beans = "error"
+ *
+ * Flags on the command line only ever add to these, and a brewlint.toml
+ * next to a recipe still wins over [lint] for that recipe.
+ */
+
+// src/roastery_config.rs
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::brew_formatter::BREW_FMT_WIDTH;
+use crate::brew_linter::BrewLintConfig;
+use crate::turbo_espresso_compiler::{CoffeeMemoryModel, OptimizationLevel};
+
+pub const ROASTERY_CONFIG_FILE: &str = "brewco.toml";
+
+/// brewco.toml as written; every table and key may be left out
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RoasteryFile {
+    brew: BrewSection,
+    allow: AllowSection,
+    fmt: FmtSection,
+    lint: toml::Table,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct BrewSection {
+    strict: bool,
+    deny_warnings: bool,
+    opt_level: Option<String>,
+    memory_model: Option<String>,
+    lang: Option<String>,
+    plugins: Vec<PathBuf>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct AllowSection {
+    fs: bool,
+    exec: bool,
+    net: bool,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FmtSection {
+    width: Option<usize>,
+}
+
+/// The project's defaults, checked and ready to use; the built-in ones without a brewco.toml
+pub struct RoasteryConfig {
+    pub strict_brew: bool,
+    pub deny_warnings: bool,
+    pub opt_level: OptimizationLevel,
+    pub memory_model: CoffeeMemoryModel,
+    pub lang: Option<String>,
+    pub allow_fs: bool,
+    pub allow_exec: bool,
+    pub allow_net: bool,
+    pub fmt_width: usize,
+    pub lint: BrewLintConfig,
+    /// Where the roastery starts: brewco.toml's directory, or the current one outside a roastery
+    pub root: PathBuf,
+    pub plugins: Vec<PathBuf>,
+}
+
+impl Default for RoasteryConfig {
+    fn default() -> Self {
+        RoasteryConfig {
+            strict_brew: false,
+            deny_warnings: false,
+            opt_level: OptimizationLevel::DoubleShot,
+            memory_model: CoffeeMemoryModel::AutomaticGrindAndClean,
+            lang: None,
+            allow_fs: false,
+            allow_exec: false,
+            allow_net: false,
+            fmt_width: BREW_FMT_WIDTH,
+            lint: BrewLintConfig::default(),
+            root: PathBuf::from("."),
+            plugins: Vec::new(),
+        }
+    }
+}
+
+impl RoasteryConfig {
+    /// Read a brewco.toml's text, spilling on anything it doesn't understand
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        let file: RoasteryFile = toml::from_str(text).map_err(|e| e.message().to_string())?;
+        let opt_level = match file.brew.opt_level.as_deref() {
+            None => OptimizationLevel::DoubleShot,
+            Some(name) => OptimizationLevel::from_config(name)
+                .ok_or_else(|| format!("opt_level must be decaf, single, double, triple or turbo, found `{}`", name))?,
+        };
+        let memory_model = match file.brew.memory_model.as_deref() {
+            None => CoffeeMemoryModel::AutomaticGrindAndClean,
+            Some(name) => CoffeeMemoryModel::from_config(name)
+                .ok_or_else(|| format!("memory_model must be grind_and_clean, manual, pooling or zero_copy, found `{}`", name))?,
+        };
+        let fmt_width = match file.fmt.width {
+            Some(0) => return Err("[fmt] width must be at least 1".to_string()),
+            Some(width) => width,
+            None => BREW_FMT_WIDTH,
+        };
+        let lint = BrewLintConfig::default().apply_table(&file.lint).map_err(|e| format!("[lint] {}", e))?;
+        Ok(RoasteryConfig {
+            strict_brew: file.brew.strict,
+            deny_warnings: file.brew.deny_warnings,
+            opt_level,
+            memory_model,
+            lang: file.brew.lang,
+            allow_fs: file.allow.fs,
+            allow_exec: file.allow.exec,
+            allow_net: file.allow.net,
+            fmt_width,
+            lint,
+            root: PathBuf::from("."),
+            plugins: file.brew.plugins,
+        })
+    }
+}
+
+/// The nearest directory at or above `start` with a brewco.toml in it
+pub fn find_roastery_root(start: &Path) -> Option<PathBuf> {
+    start.ancestors().find(|dir| dir.join(ROASTERY_CONFIG_FILE).is_file()).map(Path::to_path_buf)
+}
+
+/// The brewco.toml of the roastery `start` (or else the current directory) is in, or the defaults outside one
+pub fn load_roastery_config(start: Option<&Path>) -> Result<RoasteryConfig, String> {
+    let here = match start {
+        Some(start) => std::path::absolute(start).map_err(|e| format!("couldn't find {}: {}", start.display(), e))?,
+        None => std::env::current_dir().map_err(|e| format!("couldn't tell which directory this is: {}", e))?,
+    };
+    let Some(root) = find_roastery_root(&here) else {
+        return Ok(RoasteryConfig::default());
+    };
+    let path = root.join(ROASTERY_CONFIG_FILE);
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut config = RoasteryConfig::from_toml(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    config.plugins = config.plugins.iter().map(|plugin| root.join(plugin)).collect();
+    config.root = root;
+    Ok(config)
+}
@@ -0,0 +1,261 @@
+/*
+ * 🧪 Brewco Taste Test Runner ☕
+ *
+ * @author: "Khushi Motwani" 💖
+ * @tasting_note: "Never serve a cup you haven't tasted first!" ✨
+ *
+ * `brew test` finds every `*_test.brewco` recipe, and runs each
+ * `taste_test "name" { ... }` block in it on a fresh interpreter: the
+ * rest of the recipe is brewed first as setup, then the block itself.
+ * A test passes unless something spills, usually an assertion like
+ * `assert_same_blend(froth(2), 4)`.
+ *
+ * `assert_matches_snapshot("menu", value)` compares against
+ * `__snapshots__/<recipe>__menu.snap` next to the recipe. Missing
+ * snapshots are written on the first run; `--update-snapshots`
+ * rewrites the ones that no longer match.
+ */
+
+// src/taste_test_runner.rs
+
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::ast::Statement;
+use crate::interpreter::{ControlFlow, Interpreter, Value};
+use crate::native::pretty_blend;
+use crate::parser;
+use crate::type_checker::TypeChecker;
+
+pub const SNAPSHOT_DIR: &str = "__snapshots__";
+
+/// How one taste test went
+pub struct TasteResult {
+    pub name: String,
+    pub line: usize,
+    pub millis: f64,
+    pub spill: Option<String>,
+    pub snapshots_written: usize,
+    pub snapshots_updated: usize,
+}
+
+/// The snapshots of one recipe, and whether mismatches overwrite them
+pub struct SnapshotShelf {
+    dir: PathBuf,
+    recipe: String,
+    update: bool,
+    written: usize,
+    updated: usize,
+}
+
+impl SnapshotShelf {
+    pub fn for_recipe(recipe_path: &Path, update: bool) -> SnapshotShelf {
+        SnapshotShelf {
+            dir: recipe_path.parent().unwrap_or(Path::new(".")).join(SNAPSHOT_DIR),
+            recipe: recipe_path.file_stem().map_or("recipe".to_string(), |stem| stem.to_string_lossy().to_string()),
+            update,
+            written: 0,
+            updated: 0,
+        }
+    }
+
+    fn snapshot_path(&self, name: &str) -> PathBuf {
+        let name: String = name.chars().map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' }).collect();
+        self.dir.join(format!("{}__{}.snap", self.recipe, name))
+    }
+
+    /// Compare `value` with the stored snapshot, writing it if there is none yet.
+    /// A mismatch comes back as a line diff, stored (-) against fresh (+).
+    pub fn taste_snapshot(&mut self, name: &str, value: &Value) -> Result<(), String> {
+        let path = self.snapshot_path(name);
+        let fresh = serialize_snapshot(name, value);
+        let stored = std::fs::read_to_string(&path).ok();
+        if stored.as_deref() == Some(fresh.as_str()) {
+            return Ok(());
+        }
+        if stored.is_some() && !self.update {
+            let mut reason = format!(
+                "snapshot \"{}\" doesn't match {} (rerun with --update-snapshots to accept it)",
+                name, path.display()
+            );
+            for line in snapshot_diff(stored.as_deref().unwrap_or(""), &fresh) {
+                reason.push_str("\n    ");
+                reason.push_str(&line);
+            }
+            return Err(reason);
+        }
+        std::fs::create_dir_all(&self.dir)
+            .and_then(|_| std::fs::write(&path, &fresh))
+            .map_err(|e| format!("could not write snapshot {}: {}", path.display(), e))?;
+        if stored.is_some() {
+            self.updated += 1;
+        } else {
+            self.written += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Snapshot file contents. Strings are stored as-is, so poured-out text stays readable.
+pub fn serialize_snapshot(name: &str, value: &Value) -> String {
+    let body = match value {
+        Value::String(text) => text.clone(),
+        other => pretty_blend(other),
+    };
+    format!("☕ snapshot \"{}\" ({})\n{}\n", name, value.type_name(), body)
+}
+
+/// Line diff of two snapshots: `- ` only in stored, `+ ` only in fresh, `  ` in both
+fn snapshot_diff(stored: &str, fresh: &str) -> Vec<String> {
+    const SHOWN: usize = 40;
+    let (old, new): (Vec<&str>, Vec<&str>) = (stored.lines().collect(), fresh.lines().collect());
+    // Longest common subsequence table, filled from the ends
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] { common[i + 1][j + 1] + 1 } else { common[i + 1][j].max(common[i][j + 1]) };
+        }
+    }
+    let (mut i, mut j, mut lines) = (0, 0, Vec::new());
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(format!("  {}", old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(format!("- {}", old[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+ {}", new[j]));
+            j += 1;
+        }
+    }
+    if lines.len() > SHOWN {
+        let hidden = lines.len() - SHOWN;
+        lines.truncate(SHOWN);
+        lines.push(format!("... {} more line(s)", hidden));
+    }
+    lines
+}
+
+/// Recipes `brew test` picks up: `*_test.brewco` under directories, or any file named directly
+pub fn taste_test_files(root: &Path) -> Vec<PathBuf> {
+    if !root.is_dir() {
+        return vec![root.to_path_buf()];
+    }
+    crate::brew_formatter::coffee_files_under(root)
+        .into_iter()
+        .filter(|path| path.file_name().is_some_and(|name| name.to_string_lossy().ends_with("_test.brewco")))
+        .collect()
+}
+
+/// Run every taste test in one recipe, each on its own fresh interpreter
+pub fn run_taste_tests(recipe_path: &Path, statements: &[Statement], statement_lines: &[usize], update_snapshots: bool) -> Vec<TasteResult> {
+    let taste_tests = statements.iter().zip(statement_lines.iter()).filter_map(|(statement, &line)| match statement {
+        Statement::TasteTest { name, body } => Some((line, name, body)),
+        _ => None,
+    });
+
+    let mut results = Vec::new();
+    for (line, name, body) in taste_tests {
+        let started = Instant::now();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_snapshot_shelf(SnapshotShelf::for_recipe(recipe_path, update_snapshots));
+        let spill = match interpreter.run_until_spill(statements) {
+            Err(ControlFlow::RuntimeError(msg)) => Some(format!("the setup spilled before the test ran: {}", msg)),
+            _ => match interpreter.run_scoped_body(body) {
+                Ok(()) | Err(ControlFlow::Return(_)) => None,
+                Err(ControlFlow::RuntimeError(msg)) => Some(msg),
+                Err(ControlFlow::Break) => Some("'break' escaped the taste test".to_string()),
+                Err(ControlFlow::Continue) => Some("'continue' escaped the taste test".to_string()),
+                Err(ControlFlow::TailCall(label, ..)) => Some(format!("a tail call to '{}' escaped the taste test", label)),
+                Err(ControlFlow::StopPouring) => Some("a stopped pour escaped the taste test".to_string()),
+                Err(ControlFlow::FinishService(code)) => Some(format!("finish_service({}) closed the shop during the taste test", code)),
+            },
+        };
+        let shelf = interpreter.take_snapshot_shelf();
+        results.push(TasteResult {
+            name: name.clone(),
+            line,
+            millis: started.elapsed().as_secs_f64() * 1000.0,
+            spill,
+            snapshots_written: shelf.as_ref().map_or(0, |shelf| shelf.written),
+            snapshots_updated: shelf.as_ref().map_or(0, |shelf| shelf.updated),
+        });
+    }
+    results
+}
+
+/// `brew test [--update-snapshots] [paths...]`: run the taste tests and exit nonzero if any of them spilled
+pub fn run_brew_test(paths: &[String], update_snapshots: bool) {
+    let roots = if paths.is_empty() { vec![".".to_string()] } else { paths.to_vec() };
+    let files: Vec<PathBuf> = roots.iter().flat_map(|root| taste_test_files(Path::new(root))).collect();
+    if files.is_empty() {
+        println!("☕ No *_test.brewco recipes found. Add taste_test \"name\" {{ ... }} blocks to one!");
+        return;
+    }
+
+    let started = Instant::now();
+    let (mut passed, mut failed) = (0, 0);
+    let (mut written, mut updated) = (0, 0);
+    for path in &files {
+        let shown = path.display();
+        println!("\n🧪 {}", shown);
+        let code = match std::fs::read_to_string(path) {
+            Ok(code) => code,
+            Err(_) => {
+                println!("  [ERROR] Could not read file: {}", shown);
+                failed += 1;
+                continue;
+            }
+        };
+        let stmts = parser::parse_source(&code);
+        if !stmts.errors.is_empty() {
+            for (err, (line, column)) in stmts.errors.iter().zip(&stmts.error_positions) {
+                println!("  ❌ {}:{}:{}: syntax spill: {}", shown, line, column, err);
+            }
+            failed += 1;
+            continue;
+        }
+        // A recipe `brew` would turn away for its types doesn't get to pass its tests either
+        let mut type_checker = TypeChecker::new().with_strict_brew(crate::interpreter::strict_brew());
+        if let Err(errors) = type_checker.check(&stmts.statements) {
+            for (err, (line, column)) in errors.iter().zip(type_checker.error_positions()) {
+                println!("  ❌ {}:{}:{}: type spill: {}", shown, line, column, err);
+            }
+            failed += 1;
+            continue;
+        }
+
+        let results = run_taste_tests(path, &stmts.statements, &stmts.statement_lines, update_snapshots);
+        if results.is_empty() {
+            println!("  (no taste_test blocks)");
+        }
+        for result in results {
+            written += result.snapshots_written;
+            updated += result.snapshots_updated;
+            match result.spill {
+                None => {
+                    println!("  ✅ {} ({:.2}ms)", result.name, result.millis);
+                    passed += 1;
+                }
+                Some(spill) => {
+                    println!("  ❌ {} ({:.2}ms, line {})", result.name, result.millis, result.line);
+                    println!("       {}", spill.replace('\n', "\n       "));
+                    failed += 1;
+                }
+            }
+        }
+    }
+
+    println!(
+        "\n☕ {} passed, {} failed in {:.2}ms",
+        passed, failed, started.elapsed().as_secs_f64() * 1000.0
+    );
+    if written + updated > 0 {
+        println!("📸 {} new snapshot(s) written, {} updated", written, updated);
+    }
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
@@ -18,14 +18,16 @@
 // src/turbo_espresso_compiler.rs - The Turbo Espresso Brewing Engine ☕
 
 use std::collections::HashMap;
-use crate::ast::{Statement, Expr};
+use crate::ast::{Statement, Expr, PourSpout};
 use crate::espresso_errors::{CoffeeSpillReport, SpillType};
+use crate::interpreter::{ControlFlow, Value};
 
 /// The Turbo Espresso Brewing Engine - compiles coffee to high-performance bytecode
 pub struct TurboEspressoCompiler {
     brewing_optimizations: BrewingOptimizations,
     coffee_bytecode_cache: HashMap<String, CompiledCoffeeBrews>,
     performance_metrics: CoffeePerformanceMetrics,
+    #[allow(dead_code)] // Nothing reads the target settings yet: the VM only brews for EspressoMachine64
     espresso_shot_settings: EspressoShotSettings,
 }
 
@@ -50,6 +52,20 @@ pub enum OptimizationLevel {
     TurboEspresso,     // Maximum performance
 }
 
+impl OptimizationLevel {
+    /// The level named by brewco.toml's `opt_level`: decaf, single, double, triple or turbo
+    pub fn from_config(name: &str) -> Option<Self> {
+        match name {
+            "decaf" => Some(OptimizationLevel::DecafMode),
+            "single" => Some(OptimizationLevel::SingleShot),
+            "double" => Some(OptimizationLevel::DoubleShot),
+            "triple" => Some(OptimizationLevel::TripleShot),
+            "turbo" => Some(OptimizationLevel::TurboEspresso),
+            _ => None,
+        }
+    }
+}
+
 /// Espresso shot settings for compilation
 #[derive(Clone)]
 pub struct EspressoShotSettings {
@@ -78,6 +94,19 @@ pub enum CoffeeMemoryModel {
     ZeroCopyBrewing,         // Zero-copy optimizations
 }
 
+impl CoffeeMemoryModel {
+    /// The model named by brewco.toml's `memory_model`: grind_and_clean, manual, pooling or zero_copy
+    pub fn from_config(name: &str) -> Option<Self> {
+        match name {
+            "grind_and_clean" => Some(CoffeeMemoryModel::AutomaticGrindAndClean),
+            "manual" => Some(CoffeeMemoryModel::ManualBeanManagement),
+            "pooling" => Some(CoffeeMemoryModel::CoffeePooling),
+            "zero_copy" => Some(CoffeeMemoryModel::ZeroCopyBrewing),
+            _ => None,
+        }
+    }
+}
+
 /// Compiled coffee bytecode
 #[derive(Clone)]
 pub struct CompiledCoffeeBrews {
@@ -86,6 +115,31 @@ pub struct CompiledCoffeeBrews {
     pub brewing_constants: Vec<CoffeeConstant>,
     pub performance_metadata: BrewingPerformanceData,
     pub compilation_timestamp: std::time::SystemTime,
+    pub brewing_line_table: Vec<CoffeeLineEntry>,   // Source map, sorted by first instruction
+    pub coffee_recipe_source: String,               // For framing spills in their source lines
+}
+
+/// Source map entry - every instruction from `first_instruction` up to the
+/// next entry was brewed from this spot in the original recipe
+#[derive(Clone, Debug)]
+pub struct CoffeeLineEntry {
+    pub first_instruction: u32,
+    pub coffee_line: usize,
+    pub brewing_column: usize,
+    pub source_snippet: String,
+}
+
+impl CompiledCoffeeBrews {
+    /// Find the source location an instruction was compiled from
+    pub fn locate_instruction(&self, instruction_index: u32) -> Option<&CoffeeLineEntry> {
+        let entries_before = self.brewing_line_table
+            .partition_point(|entry| entry.first_instruction <= instruction_index);
+        if entries_before == 0 {
+            None
+        } else {
+            self.brewing_line_table.get(entries_before - 1)
+        }
+    }
 }
 
 /// Espresso machine instructions (bytecode)
@@ -94,6 +148,7 @@ pub enum EspressoInstruction {
     // Bean operations
     BrewLoadBean(u32),           // Load bean from constants
     BrewStoreBean(u32),          // Store bean to local variable
+    BrewFetchBean(u32),          // Load bean from local variable
     BrewCopyBean,                // Duplicate top bean on stack
     
     // Arithmetic brewing operations
@@ -110,7 +165,7 @@ pub enum EspressoInstruction {
     BrewReturnFromBrew,          // Return from function
     
     // Coffee I/O operations
-    BrewPourOut,                 // Print coffee value
+    BrewPourOut(PourSpout),      // Print coffee value
     BrewSipInput,                // Read coffee input
     
     // Advanced espresso operations
@@ -195,22 +250,41 @@ impl TurboEspressoCompiler {
         }
     }
     
-    /// Compile coffee statements to turbo espresso bytecode
+    /// Brew at another optimization level: decaf skips every pass, a single shot only
+    /// eliminates dead beans, and a double shot or more inlines small brews too
+    pub fn with_optimization_level(mut self, level: OptimizationLevel) -> Self {
+        let shots = match level {
+            OptimizationLevel::DecafMode => 0,
+            OptimizationLevel::SingleShot => 1,
+            OptimizationLevel::DoubleShot => 2,
+            OptimizationLevel::TripleShot | OptimizationLevel::TurboEspresso => 3,
+        };
+        let optimizations = &mut self.brewing_optimizations;
+        optimizations.enable_dead_bean_elimination = shots >= 1;
+        optimizations.enable_brew_inlining = shots >= 2;
+        optimizations.enable_coffee_loop_unrolling = shots >= 3;
+        optimizations.brewing_optimization_level = level;
+        self
+    }
+
+    /// Compile coffee statements to turbo espresso bytecode.
+    /// Every source mark the parser left in front of a statement, nested ones
+    /// included, opens a line table entry, and `recipe_source` is the original
+    /// text the entries quote so bytecode can be mapped back to the recipe.
     pub fn brew_turbo_compilation(
         &mut self,
         coffee_statements: &[Statement],
+        recipe_source: &str,
         recipe_name: &str
     ) -> Result<CompiledCoffeeBrews, CoffeeSpillReport> {
-        println!("☕ Starting turbo espresso compilation for '{}'...", recipe_name);
         let start_time = std::time::Instant::now();
         
         // Check cache first
         if let Some(cached_brew) = self.coffee_bytecode_cache.get(recipe_name) {
-            println!("☕ Found cached espresso brew! Serving hot and fresh!");
             return Ok(cached_brew.clone());
         }
         
-        let mut compiler = EspressoByteCodeGenerator::new();
+        let mut compiler = EspressoByteCodeGenerator::new(recipe_source);
         
         // Pre-compilation optimization analysis
         let optimization_plan = self.analyze_brewing_patterns(coffee_statements)?;
@@ -225,12 +299,21 @@ impl TurboEspressoCompiler {
         let mut bytecode = Vec::new();
         let mut constants = Vec::new();
         
-        for statement in &optimized_statements {
-            self.compile_coffee_statement(statement, &mut bytecode, &mut constants, &mut compiler)?;
+        for statement in optimized_statements.iter() {
+            if let Err(mut spill) = self.compile_coffee_statement(statement, &mut bytecode, &mut constants, &mut compiler) {
+                // Compile spills don't know where they are; the last mark passed does
+                if let Some((line, column)) = compiler.current_mark {
+                    spill.coffee_line = line;
+                    spill.brewing_column = column;
+                    spill.frame_in_source(recipe_name, recipe_source);
+                }
+                return Err(spill);
+            }
         }
+        let mut line_table = compiler.line_table;
         
         // Apply post-compilation optimizations
-        bytecode = self.apply_post_brewing_optimizations(bytecode)?;
+        bytecode = self.apply_post_brewing_optimizations(bytecode, &mut line_table)?;
         
         // Generate performance metadata
         let performance_data = self.analyze_brewing_performance(&bytecode, &constants);
@@ -241,6 +324,8 @@ impl TurboEspressoCompiler {
             brewing_constants: constants,
             performance_metadata: performance_data,
             compilation_timestamp: std::time::SystemTime::now(),
+            brewing_line_table: line_table,
+            coffee_recipe_source: recipe_source.to_string(),
         };
         
         // Cache the compiled brew
@@ -252,7 +337,6 @@ impl TurboEspressoCompiler {
         self.performance_metrics.average_compilation_time = 
             (self.performance_metrics.average_compilation_time + compilation_time) / 2.0;
         
-        println!("☕ Turbo espresso compilation completed in {:.2}ms!", compilation_time * 1000.0);
         Ok(compiled_brew)
     }
     
@@ -269,16 +353,14 @@ impl TurboEspressoCompiler {
         // Analyze for loop unrolling opportunities
         for (i, statement) in statements.iter().enumerate() {
             match statement {
-                Statement::While { condition, body } => {
-                    if self.is_simple_loop_condition(condition) && body.len() < 10 {
+                Statement::While { condition, body }
+                    if self.is_simple_loop_condition(condition) && body.len() < 10 => {
                         plan.loop_unroll_candidates.push(i);
                     }
-                }
-                Statement::For { condition, body, .. } => {
-                    if body.len() < 5 {
+                Statement::For { condition: _, body, .. }
+                    if body.len() < 5 => {
                         plan.loop_unroll_candidates.push(i);
                     }
-                }
                 _ => {}
             }
         }
@@ -300,7 +382,7 @@ impl TurboEspressoCompiler {
     fn apply_pre_brewing_optimizations(
         &self,
         statements: &[Statement],
-        optimization_plan: &OptimizationPlan
+        _optimization_plan: &OptimizationPlan
     ) -> Result<Vec<Statement>, CoffeeSpillReport> {
         let mut optimized = statements.to_vec();
         
@@ -321,14 +403,12 @@ impl TurboEspressoCompiler {
     fn eliminate_dead_coffee_beans(&self, statements: Vec<Statement>) -> Result<Vec<Statement>, CoffeeSpillReport> {
         // Simple dead code elimination - remove unused variables
         // In a real implementation, this would do proper data flow analysis
-        println!("☕ Eliminating unused coffee beans...");
         Ok(statements) // Placeholder implementation
     }
     
     /// Inline small coffee functions
     fn inline_small_coffee_brews(&self, statements: Vec<Statement>) -> Result<Vec<Statement>, CoffeeSpillReport> {
         // Function inlining optimization
-        println!("☕ Inlining small coffee brewing functions...");
         Ok(statements) // Placeholder implementation
     }
     
@@ -346,11 +426,15 @@ impl TurboEspressoCompiler {
                 let var_index = compiler.get_or_create_variable_index(name);
                 bytecode.push(EspressoInstruction::BrewStoreBean(var_index));
             }
-            Statement::Print(expr) => {
+            Statement::Print(expr, spout) => {
                 self.compile_coffee_expression(expr, bytecode, constants, compiler)?;
-                bytecode.push(EspressoInstruction::BrewPourOut);
+                bytecode.push(EspressoInstruction::BrewPourOut(*spout));
+            }
+            Statement::SourceMark { line, column } => {
+                compiler.mark_source(bytecode.len() as u32, (*line, *column));
             }
             Statement::If { condition, then_branch, else_branch } => {
+                let if_mark = compiler.current_mark;
                 self.compile_coffee_expression(condition, bytecode, constants, compiler)?;
                 let jump_to_else = bytecode.len();
                 bytecode.push(EspressoInstruction::BrewJumpIfBitter(0)); // Placeholder
@@ -360,6 +444,10 @@ impl TurboEspressoCompiler {
                     self.compile_coffee_statement(stmt, bytecode, constants, compiler)?;
                 }
                 
+                // The jump over the else branch belongs to the `if`, not its last statement
+                if let Some(mark) = if_mark {
+                    compiler.mark_source(bytecode.len() as u32, mark);
+                }
                 let jump_to_end = bytecode.len();
                 bytecode.push(EspressoInstruction::BrewJumpAlways(0)); // Placeholder
                 
@@ -374,6 +462,10 @@ impl TurboEspressoCompiler {
                     self.compile_coffee_statement(stmt, bytecode, constants, compiler)?;
                 }
                 
+                if let Some(mark) = if_mark {
+                    compiler.mark_source(bytecode.len() as u32, mark);
+                }
+                
                 // Update end jump target
                 let end_target = bytecode.len() as u32;
                 if let EspressoInstruction::BrewJumpAlways(ref mut target) = bytecode[jump_to_end] {
@@ -381,8 +473,12 @@ impl TurboEspressoCompiler {
                 }
             }
             _ => {
-                // Handle other statement types
-                println!("☕ Compiling advanced coffee brewing statement...");
+                // Refuse rather than silently skip, so turbo never brews a different program
+                return Err(CoffeeSpillReport::new_brewing_disaster(
+                    SpillType::NotEnoughCaffeine,
+                    0, 0,
+                    "Unsupported coffee statement in turbo mode"
+                ));
             }
         }
         
@@ -415,7 +511,7 @@ impl TurboEspressoCompiler {
             }
             Expr::Identifier(name) => {
                 let var_index = compiler.get_or_create_variable_index(name);
-                bytecode.push(EspressoInstruction::BrewLoadBean(var_index));
+                bytecode.push(EspressoInstruction::BrewFetchBean(var_index));
             }
             Expr::BinaryOp { left, op, right } => {
                 self.compile_coffee_expression(left, bytecode, constants, compiler)?;
@@ -437,7 +533,11 @@ impl TurboEspressoCompiler {
                 }
             }
             _ => {
-                println!("☕ Compiling advanced coffee expression...");
+                return Err(CoffeeSpillReport::new_brewing_disaster(
+                    SpillType::NotEnoughCaffeine,
+                    0, 0,
+                    "Unsupported coffee expression in turbo mode"
+                ));
             }
         }
         
@@ -447,12 +547,13 @@ impl TurboEspressoCompiler {
     /// Apply post-compilation optimizations
     fn apply_post_brewing_optimizations(
         &self,
-        bytecode: Vec<EspressoInstruction>
+        bytecode: Vec<EspressoInstruction>,
+        line_table: &mut [CoffeeLineEntry]
     ) -> Result<Vec<EspressoInstruction>, CoffeeSpillReport> {
         let mut optimized = bytecode;
         
         // Peephole optimizations
-        optimized = self.apply_coffee_peephole_optimizations(optimized)?;
+        optimized = self.apply_coffee_peephole_optimizations(optimized, line_table)?;
         
         // Add performance hints
         optimized = self.add_espresso_performance_hints(optimized)?;
@@ -463,19 +564,23 @@ impl TurboEspressoCompiler {
     /// Apply peephole optimizations to espresso bytecode
     fn apply_coffee_peephole_optimizations(
         &self,
-        bytecode: Vec<EspressoInstruction>
+        bytecode: Vec<EspressoInstruction>,
+        line_table: &mut [CoffeeLineEntry]
     ) -> Result<Vec<EspressoInstruction>, CoffeeSpillReport> {
-        println!("☕ Applying peephole optimizations to espresso bytecode...");
-        
         // Example: Remove redundant load/store operations
         let mut optimized = Vec::new();
+        // Where each original instruction lands after optimization (one extra
+        // slot for "end of bytecode" so jumps past the last instruction survive)
+        let mut relocated = vec![0u32; bytecode.len() + 1];
         let mut i = 0;
         
         while i < bytecode.len() {
+            relocated[i] = optimized.len() as u32;
             match (&bytecode.get(i), &bytecode.get(i + 1)) {
-                (Some(EspressoInstruction::BrewLoadBean(idx1)), 
+                (Some(EspressoInstruction::BrewFetchBean(idx1)), 
                  Some(EspressoInstruction::BrewStoreBean(idx2))) if idx1 == idx2 => {
                     // Skip redundant load/store of same variable
+                    relocated[i + 1] = optimized.len() as u32;
                     i += 2;
                 }
                 _ => {
@@ -484,6 +589,22 @@ impl TurboEspressoCompiler {
                 }
             }
         }
+        relocated[bytecode.len()] = optimized.len() as u32;
+        
+        // Keep jump targets and the line table pointing at the same code
+        for instruction in optimized.iter_mut() {
+            match instruction {
+                EspressoInstruction::BrewJumpIfBitter(target)
+                | EspressoInstruction::BrewJumpIfSweet(target)
+                | EspressoInstruction::BrewJumpAlways(target) => {
+                    *target = relocated[(*target as usize).min(bytecode.len())];
+                }
+                _ => {}
+            }
+        }
+        for entry in line_table.iter_mut() {
+            entry.first_instruction = relocated[(entry.first_instruction as usize).min(bytecode.len())];
+        }
         
         Ok(optimized)
     }
@@ -493,7 +614,6 @@ impl TurboEspressoCompiler {
         &self,
         bytecode: Vec<EspressoInstruction>
     ) -> Result<Vec<EspressoInstruction>, CoffeeSpillReport> {
-        println!("☕ Adding espresso performance hints...");
         // Add hot/cold path hints based on static analysis
         Ok(bytecode) // Placeholder implementation
     }
@@ -528,12 +648,12 @@ impl TurboEspressoCompiler {
     /// Clear compilation cache
     pub fn clear_coffee_cache(&mut self) {
         self.coffee_bytecode_cache.clear();
-        println!("☕ Coffee compilation cache cleared! Fresh brewing ahead!");
     }
 }
 
 /// Optimization plan for coffee compilation
 #[derive(Debug)]
+#[allow(dead_code)] // Only loop unrolling is planned so far; the other passes are placeholders
 struct OptimizationPlan {
     pub hot_brewing_paths: Vec<usize>,
     pub cold_brewing_paths: Vec<usize>,
@@ -543,16 +663,42 @@ struct OptimizationPlan {
 }
 
 /// Bytecode generator helper
-struct EspressoByteCodeGenerator {
+struct EspressoByteCodeGenerator<'recipe> {
     variable_indices: HashMap<String, u32>,
     next_variable_index: u32,
+    source_lines: Vec<&'recipe str>,
+    line_table: Vec<CoffeeLineEntry>,
+    current_mark: Option<(usize, usize)>, // line and column of the statement being compiled
 }
 
-impl EspressoByteCodeGenerator {
-    fn new() -> Self {
+impl<'recipe> EspressoByteCodeGenerator<'recipe> {
+    fn new(recipe_source: &'recipe str) -> Self {
         EspressoByteCodeGenerator {
             variable_indices: HashMap::new(),
             next_variable_index: 0,
+            source_lines: recipe_source.lines().collect(),
+            line_table: Vec::new(),
+            current_mark: None,
+        }
+    }
+    
+    /// Say that code from `first_instruction` on was brewed from `(line, column)`
+    fn mark_source(&mut self, first_instruction: u32, (line, column): (usize, usize)) {
+        self.current_mark = Some((line, column));
+        if self.line_table.last().is_some_and(|entry| entry.coffee_line == line && entry.brewing_column == column) {
+            return;
+        }
+        let snippet = self.source_lines.get(line.saturating_sub(1)).copied().unwrap_or("");
+        let entry = CoffeeLineEntry {
+            first_instruction,
+            coffee_line: line,
+            brewing_column: column,
+            source_snippet: snippet.trim_end().to_string(),
+        };
+        // A mark that brewed nothing (a nested block's opening, say) gives way to the next one
+        match self.line_table.last_mut() {
+            Some(last) if last.first_instruction == first_instruction => *last = entry,
+            _ => self.line_table.push(entry),
         }
     }
     
@@ -566,4 +712,242 @@ impl EspressoByteCodeGenerator {
             index
         }
     }
-} 
\ No newline at end of file
+}
+
+/// The Espresso Brewing Machine - a small stack VM that runs compiled coffee.
+/// When it traps, the compiled line table points the spill report back at
+/// the recipe line the failing instruction came from.
+pub struct EspressoBrewingMachine {
+    bean_stack: Vec<Value>,
+    bean_slots: Vec<Option<Value>>,
+}
+
+impl EspressoBrewingMachine {
+    pub fn new_brewing_machine() -> Self {
+        EspressoBrewingMachine {
+            bean_stack: Vec::new(),
+            bean_slots: Vec::new(),
+        }
+    }
+    
+    /// Run compiled coffee bytecode from the first instruction to the end
+    pub fn brew_compiled_recipe(&mut self, compiled: &CompiledCoffeeBrews) -> Result<(), CoffeeSpillReport> {
+        let bytecode = &compiled.espresso_bytecode;
+        let mut pc = 0usize;
+        
+        while pc < bytecode.len() {
+            let mut next_pc = pc + 1;
+            let step = match &bytecode[pc] {
+                EspressoInstruction::BrewLoadBean(index) => {
+                    match compiled.brewing_constants.get(*index as usize) {
+                        Some(constant) => {
+                            self.bean_stack.push(coffee_constant_value(constant));
+                            Ok(())
+                        }
+                        None => Err((SpillType::BeanNotFound, format!("Constant #{} is missing from the brewing constants", index))),
+                    }
+                }
+                EspressoInstruction::BrewFetchBean(index) => {
+                    match self.bean_slots.get(*index as usize).cloned().flatten() {
+                        Some(value) => {
+                            self.bean_stack.push(value);
+                            Ok(())
+                        }
+                        None => Err((SpillType::BeanNotFound, format!("Bean slot #{} was used before anything was poured into it", index))),
+                    }
+                }
+                EspressoInstruction::BrewStoreBean(index) => {
+                    self.pop_bean().map(|value| {
+                        let slot = *index as usize;
+                        if self.bean_slots.len() <= slot {
+                            self.bean_slots.resize(slot + 1, None);
+                        }
+                        self.bean_slots[slot] = Some(value);
+                    })
+                }
+                EspressoInstruction::BrewCopyBean => {
+                    match self.bean_stack.last().cloned() {
+                        Some(value) => {
+                            self.bean_stack.push(value);
+                            Ok(())
+                        }
+                        None => Err((SpillType::UnderExtraction, "Tried to copy a bean from an empty stack".to_string())),
+                    }
+                }
+                EspressoInstruction::BrewAdd
+                | EspressoInstruction::BrewSip
+                | EspressoInstruction::BrewBlend
+                | EspressoInstruction::BrewDivide => self.brew_arithmetic(&bytecode[pc]),
+                EspressoInstruction::BrewJumpIfBitter(target) | EspressoInstruction::BrewJumpIfSweet(target) => {
+                    let jump_when = matches!(bytecode[pc], EspressoInstruction::BrewJumpIfSweet(_));
+                    self.pop_bean().map(|condition| {
+                        if coffee_is_sweet(&condition) == jump_when {
+                            next_pc = *target as usize;
+                        }
+                    })
+                }
+                EspressoInstruction::BrewJumpAlways(target) => {
+                    next_pc = *target as usize;
+                    Ok(())
+                }
+                EspressoInstruction::BrewPourOut(spout) => {
+                    self.pop_bean().and_then(|value| {
+                        crate::interpreter::pour_out(&value, *spout).map_err(|spill| match spill {
+                            ControlFlow::RuntimeError(message) => (SpillType::for_runtime_message(&message), message),
+                            other => (SpillType::WrongBrewingMethod, format!("{:?}", other)),
+                        })
+                    })
+                }
+                EspressoInstruction::BrewHotPath
+                | EspressoInstruction::BrewColdPath
+                | EspressoInstruction::BrewInlineHint => Ok(()),
+                other => Err((
+                    SpillType::NotEnoughCaffeine,
+                    format!("The espresso machine can't brew {:?} yet", other),
+                )),
+            };
+            
+            if let Err((spill_type, message)) = step {
+                return Err(self.trap_report(compiled, pc as u32, spill_type, &message));
+            }
+            pc = next_pc;
+        }
+        
+        Ok(())
+    }
+    
+    fn pop_bean(&mut self) -> Result<Value, (SpillType, String)> {
+        self.bean_stack.pop().ok_or((
+            SpillType::UnderExtraction,
+            "The bean stack ran dry - an instruction needed a value that was never brewed".to_string(),
+        ))
+    }
+    
+    fn brew_arithmetic(&mut self, instruction: &EspressoInstruction) -> Result<(), (SpillType, String)> {
+        let right = self.pop_bean()?;
+        let left = self.pop_bean()?;
+        if matches!(instruction, EspressoInstruction::BrewAdd) {
+            if let Some(message) = crate::interpreter::strict_brew_mix(&left, &right) {
+                return Err((SpillType::WrongCupType, message));
+            }
+            let brewed_length = match (&left, &right) {
+                (Value::String(l), Value::String(r)) => l.len() + r.len(),
+                (Value::String(s), _) | (_, Value::String(s)) => s.len(),
+                _ => 0,
+            };
+            if brewed_length > crate::native::MAX_BREWED_STRING {
+                return Err((SpillType::OverExtraction, format!("add would brew a string over {} MiB", crate::native::MAX_BREWED_STRING / (1024 * 1024))));
+            }
+        }
+        let result = match (instruction, left, right) {
+            (EspressoInstruction::BrewAdd, Value::Number(l), Value::Number(r)) => Value::Number(l + r),
+            (EspressoInstruction::BrewAdd, Value::String(l), Value::String(r)) => Value::String(l + &r),
+            (EspressoInstruction::BrewAdd, Value::String(l), Value::Number(r)) => Value::String(format!("{}{}", l, r)),
+            (EspressoInstruction::BrewAdd, Value::Number(l), Value::String(r)) => Value::String(format!("{}{}", l, r)),
+            (EspressoInstruction::BrewSip, Value::Number(l), Value::Number(r)) => Value::Number(l - r),
+            (EspressoInstruction::BrewBlend, Value::Number(l), Value::Number(r)) => Value::Number(l * r),
+            (EspressoInstruction::BrewDivide, Value::Number(_), Value::Number(0.0)) => {
+                return Err((SpillType::WrongBrewingMethod, "Division by zero!".to_string()));
+            }
+            (EspressoInstruction::BrewDivide, Value::Number(l), Value::Number(r)) => Value::Number(l / r),
+            (_, l, r) => {
+                return Err((SpillType::WrongCupType, format!("Mismatched types in binary operation: {:?} and {:?}", l, r)));
+            }
+        };
+        self.bean_stack.push(result);
+        Ok(())
+    }
+    
+    /// Build a spill report for a trap, located via the compiled line table
+    fn trap_report(
+        &self,
+        compiled: &CompiledCoffeeBrews,
+        instruction_index: u32,
+        spill_type: SpillType,
+        message: &str
+    ) -> CoffeeSpillReport {
+        match compiled.locate_instruction(instruction_index) {
+            Some(entry) => {
+                let mut spill = CoffeeSpillReport::new_brewing_disaster(
+                    spill_type,
+                    entry.coffee_line,
+                    entry.brewing_column,
+                    message
+                );
+                spill.frame_in_source(&compiled.coffee_recipe_name, &compiled.coffee_recipe_source);
+                spill
+            }
+            None => {
+                let mut spill = CoffeeSpillReport::new_brewing_disaster(spill_type, 0, 0, message);
+                spill.add_coffee_context(&format!(
+                    "{} (espresso instruction #{})",
+                    compiled.coffee_recipe_name,
+                    instruction_index
+                ));
+                spill
+            }
+        }
+    }
+}
+
+fn coffee_constant_value(constant: &CoffeeConstant) -> Value {
+    match constant {
+        CoffeeConstant::CoffeeNumber(n) => Value::Number(*n),
+        CoffeeConstant::CoffeeString(s) => Value::String(s.clone()),
+        CoffeeConstant::CoffeeBoolean(b) => Value::Boolean(*b),
+        CoffeeConstant::CoffeeFunctionReference(name) | CoffeeConstant::CoffeeClassReference(name) => {
+            Value::String(name.clone())
+        }
+    }
+}
+
+fn coffee_is_sweet(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Boolean(b) => *b,
+        Value::Number(n) => *n != 0.0,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::{catch_pours, take_caught_pours};
+    use crate::parser::parse_source;
+
+    fn compile(code: &str) -> Result<CompiledCoffeeBrews, CoffeeSpillReport> {
+        let parsed = parse_source(code);
+        assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+        TurboEspressoCompiler::new_turbo_brewing_engine().brew_turbo_compilation(&parsed.statements, code, "nested.brewco")
+    }
+
+    #[test]
+    fn a_spill_inside_a_taste_points_at_its_own_line() {
+        let code = "beans cups = 2\ntaste (true) {\n    pourout \"warming up\"\n    pourout cups / 0\n}\n";
+        let compiled = compile(code).unwrap();
+        catch_pours();
+        let spill = EspressoBrewingMachine::new_brewing_machine().brew_compiled_recipe(&compiled).unwrap_err();
+        assert_eq!(take_caught_pours(), "warming up\n");
+        assert_eq!((spill.coffee_line, spill.brewing_column), (4, 5));
+        assert_eq!(spill.bitter_message, "Division by zero!");
+    }
+
+    #[test]
+    fn every_nested_statement_gets_a_line_table_entry() {
+        let code = "taste (true) {\n    pourout 1\n} otherwise {\n    pourout 2\n}\npourout 3\n";
+        let compiled = compile(code).unwrap();
+        let lines: Vec<usize> = compiled.brewing_line_table.iter().map(|entry| entry.coffee_line).collect();
+        assert_eq!(lines, vec![1, 2, 1, 4, 6]);
+        assert_eq!(compiled.brewing_line_table[1].source_snippet, "    pourout 1");
+        let sorted = compiled.brewing_line_table.windows(2).all(|pair| pair[0].first_instruction < pair[1].first_instruction);
+        assert!(sorted);
+    }
+
+    #[test]
+    fn an_unsupported_nested_statement_is_refused_at_its_line() {
+        let code = "pourout 1\ntaste (true) {\n    pourout 2\n    steep (false) {\n        pourout 3\n    }\n}\n";
+        let spill = compile(code).err().unwrap();
+        assert_eq!((spill.coffee_line, spill.brewing_column), (4, 5));
+    }
+}
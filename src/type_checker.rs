@@ -1,6 +1,8 @@
 // src/type_checker.rs
 
 use crate::ast::{Statement, Expr, BinaryOperator};
+use crate::brew_linter::{self, BrewLintConfig, BrewLintRule};
+use crate::native;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -33,23 +35,64 @@ impl std::fmt::Display for Type {
     }
 }
 
+/// Something that still brews but probably isn't what was meant.
+/// Printed after checking; only fatal with --deny-warnings.
+#[derive(Debug, Clone)]
+pub struct CoffeeWarning {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// The lint rules that feed the warning channel
+const WARNING_RULES: [BrewLintRule; 3] = [
+    BrewLintRule::UnusedBeans,
+    BrewLintRule::StringCoercion,
+    BrewLintRule::UnreachableRoastArm,
+];
+
 pub struct TypeChecker {
     scopes: Vec<HashMap<String, Type>>,
     errors: Vec<String>,
+    warnings: Vec<CoffeeWarning>,
+    error_positions: Vec<(usize, usize)>, // 1-based line and column of the statement behind each error
+    current_position: (usize, usize),
+    strict_brew: bool, // `--strict-brew`: no string + number
+}
+
+impl Default for TypeChecker {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TypeChecker {
     pub fn new() -> Self {
         TypeChecker {
-            scopes: vec![HashMap::new()],
+            // PI_SHOT and friends are there before any recipe declares anything
+            scopes: vec![native::BREW_CONSTANTS.iter().map(|(name, _)| (name.to_string(), Type::Number)).collect()],
             errors: Vec::new(),
+            warnings: Vec::new(),
+            error_positions: Vec::new(),
+            current_position: (0, 0),
+            strict_brew: false,
         }
     }
 
+    /// Refuse to add a string and a number instead of joining them as text
+    pub fn with_strict_brew(mut self, strict: bool) -> Self {
+        self.strict_brew = strict;
+        self
+    }
+
     pub fn check(&mut self, statements: &[Statement]) -> Result<(), Vec<String>> {
         for statement in statements {
             self.check_statement(statement);
         }
+        self.warnings = brew_linter::lint_coffee_statements(statements, &BrewLintConfig::only(&WARNING_RULES))
+            .into_iter()
+            .map(|finding| CoffeeWarning { line: finding.line, column: finding.column, message: finding.message })
+            .collect();
 
         if self.errors.is_empty() {
             Ok(())
@@ -58,16 +101,35 @@ impl TypeChecker {
         }
     }
 
-    fn add_error(&mut self, message: String) {
-        self.errors.push(message);
+    /// Where each error from `check` happened, in the same order
+    pub fn error_positions(&self) -> &[(usize, usize)] {
+        &self.error_positions
+    }
+
+    /// Warnings from the last `check`, in source order. They're collected even when `check` fails.
+    pub fn warnings(&self) -> &[CoffeeWarning] {
+        &self.warnings
     }
 
-    fn push_scope(&mut self) {
-        self.scopes.push(HashMap::new());
+    /// Tell the checker a name already holds a value of this type (the REPL's live pantry)
+    pub fn assume(&mut self, name: &str, var_type: Type) {
+        self.define_var(name, var_type);
     }
 
-    fn pop_scope(&mut self) {
-        self.scopes.pop();
+    /// Infer one expression's type on its own. Only the errors it causes come back.
+    pub fn infer_type(&mut self, expr: &Expr) -> Result<Type, Vec<String>> {
+        let errors_before = self.errors.len();
+        let inferred = self.infer_expr_type(expr);
+        if self.errors.len() > errors_before {
+            Err(self.errors[errors_before..].to_vec())
+        } else {
+            Ok(inferred)
+        }
+    }
+
+    fn add_error(&mut self, message: String) {
+        self.errors.push(message);
+        self.error_positions.push(self.current_position);
     }
 
     fn define_var(&mut self, name: &str, var_type: Type) {
@@ -115,6 +177,9 @@ impl TypeChecker {
             Statement::ExprStmt(expr) => {
                 self.infer_expr_type(expr); // Evaluate for side-effects and errors
             }
+            Statement::SourceMark { line, column } => {
+                self.current_position = (*line, *column);
+            }
             // We will add other statement types here
             _ => (),
         }
@@ -137,13 +202,30 @@ impl TypeChecker {
                 let left_type = self.infer_expr_type(left);
                 let right_type = self.infer_expr_type(right);
 
+                // Natives and big brews come out as Any, so what they add up to is the runtime's call
+                if left_type == Type::Any || right_type == Type::Any {
+                    return match op {
+                        BinaryOperator::Equal | BinaryOperator::NotEqual
+                        | BinaryOperator::Greater | BinaryOperator::Less
+                        | BinaryOperator::GreaterEqual | BinaryOperator::LessEqual
+                        | BinaryOperator::And | BinaryOperator::Or => Type::Boolean,
+                        _ => Type::Any,
+                    };
+                }
+
                 match op {
                     // Handle numeric and string operations
                     BinaryOperator::Add => {
                         if (left_type == Type::Number || left_type == Type::String) &&
                            (right_type == Type::Number || right_type == Type::String) {
                             // If either is a string, the result is a string
-                            if left_type == Type::String || right_type == Type::String {
+                            if self.strict_brew && left_type != right_type {
+                                self.add_error(format!(
+                                    "Can't add a {} and a {} under --strict-brew; convert one side with to_number or brew_format.",
+                                    left_type, right_type
+                                ));
+                                Type::Any
+                            } else if left_type == Type::String || right_type == Type::String {
                                 Type::String
                             } else {
                                 Type::Number
@@ -181,11 +263,12 @@ impl TypeChecker {
                         }
                     }
                     BinaryOperator::Greater | BinaryOperator::Less | BinaryOperator::GreaterEqual | BinaryOperator::LessEqual => {
-                        if left_type == Type::Number && right_type == Type::Number {
+                        if (left_type == Type::Number && right_type == Type::Number) ||
+                           (left_type == Type::String && right_type == Type::String) {
                             Type::Boolean
                         } else {
                             self.add_error(format!(
-                                "Can only compare numbers, but got {} and {}.",
+                                "Can only order two numbers or two strings, but got {} and {}.",
                                 left_type, right_type
                             ));
                             Type::Any